@@ -4,6 +4,7 @@
 use std::sync::Arc;
 
 use openraft::Config;
+use openraft::LogRetentionPolicy;
 
 use crate::app::App;
 use crate::router::Router;
@@ -77,7 +78,7 @@ pub async fn new_raft(node_id: NodeId, router: Router) -> (typ::Raft, App) {
         election_timeout_max: 3000,
         // Once snapshot is built, delete the logs at once.
         // So that all further replication will be based on the snapshot.
-        max_in_snapshot_log_to_keep: 0,
+        log_retention_policy: LogRetentionPolicy::KeepEntries(0),
         ..Default::default()
     };
 
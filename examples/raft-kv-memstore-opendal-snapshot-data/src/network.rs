@@ -1,6 +1,8 @@
 use std::future::Future;
 
+use openraft::error::Infallible;
 use openraft::error::ReplicationClosed;
+use openraft::error::Unauthenticated;
 use openraft::network::v2::RaftNetworkV2;
 use openraft::network::RPCOption;
 use openraft::raft::AppendEntriesRequest;
@@ -41,7 +43,7 @@ impl RaftNetworkV2<TypeConfig> for Connection {
         req: AppendEntriesRequest<TypeConfig>,
         _option: RPCOption,
     ) -> Result<AppendEntriesResponse<TypeConfig>, typ::RPCError> {
-        let resp = self.router.send(self.target, "/raft/append", req).await?;
+        let resp = self.router.send::<_, _, Unauthenticated>(self.target, "/raft/append", req).await?;
         Ok(resp)
     }
 
@@ -53,7 +55,10 @@ impl RaftNetworkV2<TypeConfig> for Connection {
         _cancel: impl Future<Output = ReplicationClosed> + OptionalSend + 'static,
         _option: RPCOption,
     ) -> Result<SnapshotResponse<TypeConfig>, typ::StreamingError> {
-        let resp = self.router.send(self.target, "/raft/snapshot", (vote, snapshot.meta, snapshot.snapshot)).await?;
+        let resp = self
+            .router
+            .send::<_, _, Infallible>(self.target, "/raft/snapshot", (vote, snapshot.meta, snapshot.snapshot))
+            .await?;
         Ok(resp)
     }
 
@@ -62,7 +67,7 @@ impl RaftNetworkV2<TypeConfig> for Connection {
         req: VoteRequest<TypeConfig>,
         _option: RPCOption,
     ) -> Result<VoteResponse<TypeConfig>, typ::RPCError> {
-        let resp = self.router.send(self.target, "/raft/vote", req).await?;
+        let resp = self.router.send::<_, _, Unauthenticated>(self.target, "/raft/vote", req).await?;
         Ok(resp)
     }
 }
@@ -128,6 +128,8 @@ impl RaftSnapshotBuilder<TypeConfig> for Arc<StateMachineStore> {
             last_log_id: last_applied_log,
             last_membership,
             snapshot_id: snapshot_id.clone(),
+            delta_base: None,
+            checksum: None,
         };
 
         let snapshot = StoredSnapshot {
@@ -49,8 +49,17 @@ openraft::declare_raft_types!(
         D = Request,
         R = Response,
         NodeId = NodeId,
+        AsyncRuntime = Runtime,
 );
 
+/// The asynchronous runtime that runs the raft nodes.
+#[cfg(not(feature = "glommio"))]
+pub type Runtime = openraft::TokioRuntime;
+
+/// The asynchronous runtime that runs the raft nodes.
+#[cfg(feature = "glommio")]
+pub type Runtime = openraft::GlommioRuntime;
+
 pub type LogStore = store::LogStore;
 pub type StateMachineStore = store::StateMachineStore;
 pub type Raft = openraft::Raft<TypeConfig>;
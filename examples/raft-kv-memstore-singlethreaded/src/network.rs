@@ -1,5 +1,6 @@
 use openraft::error::InstallSnapshotError;
 use openraft::error::RemoteError;
+use openraft::error::Unauthenticated;
 use openraft::network::RPCOption;
 use openraft::raft::AppendEntriesRequest;
 use openraft::raft::AppendEntriesResponse;
@@ -40,7 +41,7 @@ impl RaftNetwork<TypeConfig> for Connection {
     ) -> Result<AppendEntriesResponse<TypeConfig>, typ::RPCError> {
         let resp = self
             .router
-            .send(self.target, "/raft/append", req)
+            .send::<_, _, Unauthenticated>(self.target, "/raft/append", req)
             .await
             .map_err(|e| RemoteError::new(self.target, e))?;
         Ok(resp)
@@ -66,7 +67,7 @@ impl RaftNetwork<TypeConfig> for Connection {
     ) -> Result<VoteResponse<TypeConfig>, typ::RPCError> {
         let resp = self
             .router
-            .send(self.target, "/raft/vote", req)
+            .send::<_, _, Unauthenticated>(self.target, "/raft/vote", req)
             .await
             .map_err(|e| RemoteError::new(self.target, e))?;
         Ok(resp)
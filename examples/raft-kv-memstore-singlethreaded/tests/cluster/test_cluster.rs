@@ -7,6 +7,7 @@ use std::time::Duration;
 use maplit::btreemap;
 use maplit::btreeset;
 use openraft::error::Infallible;
+use openraft::AsyncRuntime;
 use openraft::BasicNode;
 use raft_kv_memstore_singlethreaded::router::Router;
 use raft_kv_memstore_singlethreaded::start_raft;
@@ -17,8 +18,7 @@ use raft_kv_memstore_singlethreaded::typ::ClientWriteResponse;
 use raft_kv_memstore_singlethreaded::typ::InitializeError;
 use raft_kv_memstore_singlethreaded::typ::RaftMetrics;
 use raft_kv_memstore_singlethreaded::NodeId;
-use tokio::task;
-use tokio::task::LocalSet;
+use raft_kv_memstore_singlethreaded::Runtime;
 use tracing_subscriber::EnvFilter;
 
 pub fn log_panic(panic: &PanicInfo) {
@@ -42,10 +42,7 @@ pub fn log_panic(panic: &PanicInfo) {
     eprintln!("{}", backtrace);
 }
 
-/// Setup a cluster of 3 nodes.
-/// Write to it and read from it.
-#[tokio::test]
-async fn test_cluster() {
+fn init_logging() {
     std::panic::set_hook(Box::new(|panic| {
         log_panic(panic);
     }));
@@ -57,6 +54,17 @@ async fn test_cluster() {
         .with_ansi(false)
         .with_env_filter(EnvFilter::from_default_env())
         .init();
+}
+
+/// Setup a cluster of 3 nodes.
+/// Write to it and read from it.
+#[cfg(not(feature = "glommio"))]
+#[tokio::test]
+async fn test_cluster() {
+    use tokio::task;
+    use tokio::task::LocalSet;
+
+    init_logging();
 
     let router = Router::default();
 
@@ -73,9 +81,28 @@ async fn test_cluster() {
         .await;
 }
 
+/// Same as the `tokio` one, except that the 3 nodes run on a `glommio` executor.
+#[cfg(feature = "glommio")]
+#[test]
+fn test_cluster() {
+    init_logging();
+
+    let ex = glommio::LocalExecutorBuilder::default().make().expect("Failed building the executor");
+
+    ex.run(async move {
+        let router = Router::default();
+
+        glommio::spawn_local(start_raft(NodeId::new(1), router.clone())).detach();
+        glommio::spawn_local(start_raft(NodeId::new(2), router.clone())).detach();
+        glommio::spawn_local(start_raft(NodeId::new(3), router.clone())).detach();
+
+        run_test(router).await;
+    });
+}
+
 async fn run_test(router: Router) {
     // Wait for server to start up.
-    tokio::time::sleep(Duration::from_millis(200)).await;
+    Runtime::sleep(Duration::from_millis(200)).await;
 
     // --- 1. Initialize the target node as a cluster of only one node.
     //        After init(), the single node cluster will be fully functional.
@@ -178,7 +205,7 @@ async fn run_test(router: Router) {
 
     // --- Wait for a while to let the replication get done.
 
-    tokio::time::sleep(Duration::from_millis(1_000)).await;
+    Runtime::sleep(Duration::from_millis(1_000)).await;
 
     // --- Read it
 
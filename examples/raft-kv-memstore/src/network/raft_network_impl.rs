@@ -1,6 +1,7 @@
 use openraft::error::InstallSnapshotError;
 use openraft::error::NetworkError;
 use openraft::error::RemoteError;
+use openraft::error::Unauthenticated;
 use openraft::error::Unreachable;
 use openraft::network::RPCOption;
 use openraft::network::RaftNetwork;
@@ -22,16 +23,22 @@ use crate::TypeConfig;
 pub struct Network {}
 
 impl Network {
-    pub async fn send_rpc<Req, Resp, Err>(
+    /// Send `req` to the target and decode the reply as `Result<Resp, Err>`.
+    ///
+    /// `Err` is the error type returned by the remote `Raft`, and is converted to the error type
+    /// `E` of the `RaftNetwork` method.
+    pub async fn send_rpc<Req, Resp, Err, E>(
         &self,
         target: NodeId,
         target_node: &BasicNode,
         uri: &str,
         req: Req,
-    ) -> Result<Resp, openraft::error::RPCError<TypeConfig, Err>>
+    ) -> Result<Resp, openraft::error::RPCError<TypeConfig, E>>
     where
         Req: Serialize,
         Err: std::error::Error + DeserializeOwned,
+        E: std::error::Error,
+        openraft::error::RPCError<TypeConfig, E>: From<RemoteError<TypeConfig, Err>>,
         Resp: DeserializeOwned,
     {
         let addr = &target_node.addr;
@@ -56,7 +63,7 @@ impl Network {
         let res: Result<Resp, Err> =
             resp.json().await.map_err(|e| openraft::error::RPCError::Network(NetworkError::new(&e)))?;
 
-        res.map_err(|e| openraft::error::RPCError::RemoteError(RemoteError::new(target, e)))
+        res.map_err(|e| RemoteError::new(target, e).into())
     }
 }
 
@@ -86,7 +93,9 @@ impl RaftNetwork<TypeConfig> for NetworkConnection {
         req: AppendEntriesRequest<TypeConfig>,
         _option: RPCOption,
     ) -> Result<AppendEntriesResponse<TypeConfig>, typ::RPCError> {
-        self.owner.send_rpc(self.target, &self.target_node, "raft-append", req).await
+        self.owner
+            .send_rpc::<_, _, typ::RaftError<Unauthenticated>, _>(self.target, &self.target_node, "raft-append", req)
+            .await
     }
 
     async fn install_snapshot(
@@ -94,7 +103,14 @@ impl RaftNetwork<TypeConfig> for NetworkConnection {
         req: InstallSnapshotRequest<TypeConfig>,
         _option: RPCOption,
     ) -> Result<InstallSnapshotResponse<TypeConfig>, typ::RPCError<InstallSnapshotError>> {
-        self.owner.send_rpc(self.target, &self.target_node, "raft-snapshot", req).await
+        self.owner
+            .send_rpc::<_, _, typ::RaftError<InstallSnapshotError>, _>(
+                self.target,
+                &self.target_node,
+                "raft-snapshot",
+                req,
+            )
+            .await
     }
 
     async fn vote(
@@ -102,6 +118,8 @@ impl RaftNetwork<TypeConfig> for NetworkConnection {
         req: VoteRequest<TypeConfig>,
         _option: RPCOption,
     ) -> Result<VoteResponse<TypeConfig>, typ::RPCError> {
-        self.owner.send_rpc(self.target, &self.target_node, "raft-vote", req).await
+        self.owner
+            .send_rpc::<_, _, typ::RaftError<Unauthenticated>, _>(self.target, &self.target_node, "raft-vote", req)
+            .await
     }
 }
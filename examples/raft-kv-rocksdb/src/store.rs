@@ -117,6 +117,8 @@ impl RaftSnapshotBuilder<TypeConfig> for StateMachineStore {
             last_log_id: last_applied_log,
             last_membership,
             snapshot_id,
+            delta_base: None,
+            checksum: None,
         };
 
         let snapshot = StoredSnapshot {
@@ -0,0 +1,9 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // `protox` compiles the proto files in pure Rust, thus `protoc` is not required to build.
+    let file_descriptors = protox::compile(["proto/raft.proto"], ["proto"])?;
+
+    tonic_build::configure().compile_fds(file_descriptors)?;
+
+    println!("cargo:rerun-if-changed=proto/raft.proto");
+    Ok(())
+}
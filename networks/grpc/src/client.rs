@@ -0,0 +1,219 @@
+//! The sending side: a `RaftNetwork` that sends RPCs to a remote [`GrpcRaftService`].
+//!
+//! [`GrpcRaftService`]: crate::GrpcRaftService
+
+use openraft::error::InstallSnapshotError;
+use openraft::error::NetworkError;
+use openraft::error::RPCError;
+use openraft::error::RaftError;
+use openraft::error::RemoteError;
+use openraft::error::Unauthenticated;
+use openraft::error::Unreachable;
+use openraft::network::RPCOption;
+use openraft::network::RaftNetwork;
+use openraft::network::RaftNetworkFactory;
+use openraft::raft::AppendEntriesRequest;
+use openraft::raft::AppendEntriesResponse;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::VoteRequest;
+use openraft::raft::VoteResponse;
+use openraft::AnyError;
+use openraft::BasicNode;
+use openraft::RaftTypeConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tonic::transport::Channel;
+use tonic::transport::Endpoint;
+use tonic::Code;
+use tonic::Status;
+
+use crate::codec::decode;
+use crate::codec::encode_compressed;
+use crate::pb;
+use crate::pb::raft_service_client::RaftServiceClient;
+
+/// Returns the address of a target node.
+type AddrOf<C> = dyn Fn(&<C as RaftTypeConfig>::NodeId, &<C as RaftTypeConfig>::Node) -> String + Send + Sync;
+
+/// Builds a [`GrpcNetwork`] for every replication target.
+///
+/// The address of a target is derived from its node id and `Node` by a user provided function,
+/// e.g., `"127.0.0.1:5051"` or `"https://raft-1.example.com:5051"`. If there is no scheme,
+/// `http://` is assumed. With [`BasicNode`], [`GrpcNetworkFactory::default()`] uses
+/// [`BasicNode::addr`].
+pub struct GrpcNetworkFactory<C>
+where C: RaftTypeConfig
+{
+    addr_of: Box<AddrOf<C>>,
+}
+
+impl<C> GrpcNetworkFactory<C>
+where C: RaftTypeConfig
+{
+    /// Create a factory that connects to the address returned by `addr_of(target, node)`.
+    pub fn new(addr_of: impl Fn(&C::NodeId, &C::Node) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            addr_of: Box::new(addr_of),
+        }
+    }
+}
+
+impl<C> Default for GrpcNetworkFactory<C>
+where C: RaftTypeConfig<Node = BasicNode>
+{
+    fn default() -> Self {
+        Self::new(|_target, node| node.addr.clone())
+    }
+}
+
+impl<C> RaftNetworkFactory<C> for GrpcNetworkFactory<C>
+where
+    C: RaftTypeConfig,
+    C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io::AsyncSeek + Unpin,
+{
+    type Network = GrpcNetwork<C>;
+
+    async fn new_client(&mut self, target: C::NodeId, node: &C::Node) -> Self::Network {
+        let addr = (self.addr_of)(&target, node);
+        GrpcNetwork::new(target, &addr)
+    }
+}
+
+/// A connection to a single target node.
+///
+/// The underlying channel connects lazily and reconnects by itself after a failure.
+///
+/// Errors are mapped as:
+/// - A `RaftError` returned by the remote `Raft` is a [`RPCError::RemoteError`].
+/// - An `Unauthenticated` error returned by the remote `Raft` for an AppendEntries or Vote RPC is a
+///   [`RPCError::Network`].
+/// - A malformed address, or a gRPC status `Unavailable`, i.e., the target can not be connected, is
+///   a [`RPCError::Unreachable`], so that Openraft backs off before retrying.
+/// - Any other gRPC status or a message that can not be decoded is a [`RPCError::Network`].
+pub struct GrpcNetwork<C>
+where C: RaftTypeConfig
+{
+    target: C::NodeId,
+    client: Result<RaftServiceClient<Channel>, Unreachable>,
+}
+
+impl<C> GrpcNetwork<C>
+where C: RaftTypeConfig
+{
+    /// Create a network sending RPCs to node `target`, which serves a [`GrpcRaftService`] at
+    /// `addr`.
+    ///
+    /// It does not connect to `addr`, thus it must be called inside a `tokio` runtime, but it
+    /// never fails. A malformed `addr` is reported by every RPC.
+    ///
+    /// [`GrpcRaftService`]: crate::GrpcRaftService
+    pub fn new(target: C::NodeId, addr: &str) -> Self {
+        let client = Self::connect_lazy(addr).map_err(|e| {
+            tracing::warn!("invalid address of target {}: {}: {}", target, addr, e);
+            Unreachable::new(&e)
+        });
+
+        Self { target, client }
+    }
+
+    fn connect_lazy(addr: &str) -> Result<RaftServiceClient<Channel>, tonic::transport::Error> {
+        let uri = if addr.contains("://") {
+            addr.to_string()
+        } else {
+            format!("http://{}", addr)
+        };
+
+        let channel = Endpoint::from_shared(uri)?.connect_lazy();
+        Ok(RaftServiceClient::new(channel))
+    }
+
+    fn client<E>(&self) -> Result<RaftServiceClient<Channel>, RPCError<C, RaftError<C, E>>>
+    where E: std::error::Error {
+        self.client.clone().map_err(RPCError::Unreachable)
+    }
+
+    fn request<E>(&self, req: &impl Serialize) -> Result<pb::RaftRequest, RPCError<C, RaftError<C, E>>>
+    where E: std::error::Error {
+        let (compression, data) = encode_compressed::<C::PayloadCodec, _>(req).map_err(|e| NetworkError::new(&e))?;
+        Ok(pb::RaftRequest {
+            data,
+            compression: compression.to_string(),
+        })
+    }
+
+    /// Decode the response, or the error `RE` returned by the remote `Raft`.
+    fn response<Resp, E, RE>(
+        &self,
+        reply: Result<tonic::Response<pb::RaftReply>, Status>,
+    ) -> Result<Resp, RPCError<C, RaftError<C, E>>>
+    where
+        Resp: DeserializeOwned,
+        E: std::error::Error,
+        RE: std::error::Error + DeserializeOwned,
+        RPCError<C, RaftError<C, E>>: From<RemoteError<C, RE>>,
+    {
+        let reply = reply.map_err(status_to_rpc_error)?.into_inner();
+
+        match reply.result {
+            Some(pb::raft_reply::Result::Ok(data)) => {
+                let resp = decode(&data).map_err(|e| NetworkError::new(&e))?;
+                Ok(resp)
+            }
+            Some(pb::raft_reply::Result::Err(data)) => {
+                let err: RE = decode(&data).map_err(|e| NetworkError::new(&e))?;
+                Err(RemoteError::new(self.target, err).into())
+            }
+            None => {
+                let e = AnyError::error("RaftReply has neither ok nor err");
+                Err(NetworkError::new(&e).into())
+            }
+        }
+    }
+}
+
+/// Map a gRPC status to an RPC error, the status of a remote `RaftError` is not included.
+fn status_to_rpc_error<C, E>(status: Status) -> RPCError<C, RaftError<C, E>>
+where
+    C: RaftTypeConfig,
+    E: std::error::Error,
+{
+    match status.code() {
+        Code::Unavailable => RPCError::Unreachable(Unreachable::new(&status)),
+        _ => RPCError::Network(NetworkError::new(&status)),
+    }
+}
+
+impl<C> RaftNetwork<C> for GrpcNetwork<C>
+where C: RaftTypeConfig
+{
+    async fn append_entries(
+        &mut self,
+        rpc: AppendEntriesRequest<C>,
+        _option: RPCOption,
+    ) -> Result<AppendEntriesResponse<C>, RPCError<C, RaftError<C>>> {
+        let req = self.request(&rpc)?;
+        let reply = self.client()?.append_entries(req).await;
+        self.response::<_, _, RaftError<C, Unauthenticated>>(reply)
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        rpc: InstallSnapshotRequest<C>,
+        _option: RPCOption,
+    ) -> Result<InstallSnapshotResponse<C>, RPCError<C, RaftError<C, InstallSnapshotError>>> {
+        let req = self.request(&rpc)?;
+        let reply = self.client()?.install_snapshot(req).await;
+        self.response::<_, _, RaftError<C, InstallSnapshotError>>(reply)
+    }
+
+    async fn vote(
+        &mut self,
+        rpc: VoteRequest<C>,
+        _option: RPCOption,
+    ) -> Result<VoteResponse<C>, RPCError<C, RaftError<C>>> {
+        let req = self.request(&rpc)?;
+        let reply = self.client()?.vote(req).await;
+        self.response::<_, _, RaftError<C, Unauthenticated>>(reply)
+    }
+}
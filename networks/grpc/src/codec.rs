@@ -0,0 +1,63 @@
+//! Encoding of the openraft types carried by the protobuf messages.
+//!
+//! `serde_json` is used because it is self-describing: the application types carried in the
+//! messages, such as `C::D` and `C::Node`, may use serde features that a format like `bincode`
+//! does not support, e.g., untagged enums.
+//!
+//! A request is compressed with the `PayloadCodec` of the sender, a reply is not compressed.
+
+use std::io;
+
+use openraft::network::decode_payload;
+use openraft::network::encode_payload;
+use openraft::network::PayloadCodec;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub(crate) fn encode<T: Serialize>(t: &T) -> Result<Vec<u8>, serde_json::Error> {
+    serde_json::to_vec(t)
+}
+
+pub(crate) fn decode<T: DeserializeOwned>(data: &[u8]) -> Result<T, serde_json::Error> {
+    serde_json::from_slice(data)
+}
+
+/// Encode `t` and compress it with `P`, returns the name of the compression algorithm along with
+/// the payload.
+pub(crate) fn encode_compressed<P, T>(t: &T) -> io::Result<(&'static str, Vec<u8>)>
+where
+    P: PayloadCodec,
+    T: Serialize,
+{
+    let data = encode(t)?;
+    encode_payload::<P>(data)
+}
+
+/// Decompress a payload with the algorithm named `compression` and decode it.
+pub(crate) fn decode_compressed<T: DeserializeOwned>(compression: &str, data: Vec<u8>) -> io::Result<T> {
+    let data = decode_payload(compression, data)?;
+    Ok(decode(&data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use openraft::network::Lz4;
+    use openraft::network::NoCompression;
+
+    use super::decode_compressed;
+    use super::encode_compressed;
+
+    #[test]
+    fn test_compressed_round_trip() -> anyhow::Result<()> {
+        let payload = vec!["foo".repeat(10); 100];
+
+        let (compression, data) = encode_compressed::<Lz4, _>(&payload)?;
+        assert_eq!("lz4", compression);
+        assert_eq!(payload, decode_compressed::<Vec<String>>(compression, data)?);
+
+        let (compression, data) = encode_compressed::<NoCompression, _>(&payload)?;
+        assert_eq!("none", compression);
+        assert_eq!(payload, decode_compressed::<Vec<String>>(compression, data)?);
+        Ok(())
+    }
+}
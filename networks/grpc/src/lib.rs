@@ -0,0 +1,19 @@
+#![doc = include_str!("../README.md")]
+#![deny(unused_crate_dependencies)]
+#![deny(unused_qualifications)]
+
+#[cfg(test)] mod test;
+
+mod client;
+mod codec;
+mod server;
+
+pub use client::GrpcNetwork;
+pub use client::GrpcNetworkFactory;
+pub use server::GrpcRaftService;
+
+/// Types generated from `proto/raft.proto`.
+#[allow(unused_qualifications)]
+pub mod pb {
+    tonic::include_proto!("openraft");
+}
@@ -0,0 +1,91 @@
+//! The receiving side: a gRPC service that forwards the RPCs into a local `Raft`.
+
+use openraft::Raft;
+use openraft::RaftTypeConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+use crate::codec::decode_compressed;
+use crate::codec::encode;
+use crate::pb;
+use crate::pb::raft_service_server::RaftService;
+use crate::pb::raft_service_server::RaftServiceServer;
+
+/// A gRPC service that forwards the Raft RPCs sent by a [`GrpcNetwork`] to a local [`Raft`].
+///
+/// A `RaftError` returned by [`Raft`] is sent back in the reply, and the sender returns it as a
+/// `RPCError::RemoteError`. A gRPC error status is returned only if the request can not be
+/// decoded.
+///
+/// ```ignore
+/// tonic::transport::Server::builder()
+///     .add_service(GrpcRaftService::new(raft).into_server())
+///     .serve(addr)
+///     .await?;
+/// ```
+///
+/// [`GrpcNetwork`]: crate::GrpcNetwork
+pub struct GrpcRaftService<C>
+where C: RaftTypeConfig
+{
+    raft: Raft<C>,
+}
+
+impl<C> GrpcRaftService<C>
+where C: RaftTypeConfig
+{
+    pub fn new(raft: Raft<C>) -> Self {
+        Self { raft }
+    }
+
+    /// Wrap it into a service that can be added to a `tonic` server.
+    pub fn into_server(self) -> RaftServiceServer<Self>
+    where C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io::AsyncSeek + Unpin {
+        RaftServiceServer::new(self)
+    }
+}
+
+/// Build a reply from the result returned by `Raft`.
+fn reply<T, E>(res: Result<T, E>) -> Result<Response<pb::RaftReply>, Status>
+where
+    T: Serialize,
+    E: Serialize,
+{
+    let result = match res {
+        Ok(resp) => pb::raft_reply::Result::Ok(encode(&resp).map_err(|e| Status::internal(e.to_string()))?),
+        Err(err) => pb::raft_reply::Result::Err(encode(&err).map_err(|e| Status::internal(e.to_string()))?),
+    };
+
+    Ok(Response::new(pb::RaftReply { result: Some(result) }))
+}
+
+/// Decompress and decode a request.
+fn decode_request<T: DeserializeOwned>(request: Request<pb::RaftRequest>) -> Result<T, Status> {
+    let req = request.into_inner();
+    decode_compressed(&req.compression, req.data).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+#[tonic::async_trait]
+impl<C> RaftService for GrpcRaftService<C>
+where
+    C: RaftTypeConfig,
+    C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io::AsyncSeek + Unpin,
+{
+    async fn append_entries(&self, request: Request<pb::RaftRequest>) -> Result<Response<pb::RaftReply>, Status> {
+        let rpc = decode_request(request)?;
+        reply(self.raft.append_entries(rpc).await)
+    }
+
+    async fn vote(&self, request: Request<pb::RaftRequest>) -> Result<Response<pb::RaftReply>, Status> {
+        let rpc = decode_request(request)?;
+        reply(self.raft.vote(rpc).await)
+    }
+
+    async fn install_snapshot(&self, request: Request<pb::RaftRequest>) -> Result<Response<pb::RaftReply>, Status> {
+        let rpc = decode_request(request)?;
+        reply(self.raft.install_snapshot(rpc).await)
+    }
+}
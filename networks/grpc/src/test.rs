@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use maplit::btreeset;
+use openraft::error::RPCError;
+use openraft::network::RPCOption;
+use openraft::network::RaftNetwork;
+use openraft::raft::VoteRequest;
+use openraft::Config;
+use openraft::Raft;
+use openraft::Vote;
+use openraft_memstore::new_mem_store;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+use openraft_memstore::MemNodeId;
+use openraft_memstore::TypeConfig;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+
+use crate::GrpcNetwork;
+use crate::GrpcNetworkFactory;
+use crate::GrpcRaftService;
+
+fn rpc_option() -> RPCOption {
+    RPCOption::new(Duration::from_millis(1_000))
+}
+
+/// Start a node and serve it on a random local port, returns the address.
+async fn start_node(
+    id: MemNodeId,
+    addrs: Arc<std::sync::Mutex<BTreeMap<MemNodeId, String>>>,
+) -> anyhow::Result<(Raft<TypeConfig>, String)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?.to_string();
+
+    let network = GrpcNetworkFactory::new(move |target, _node| addrs.lock().unwrap()[target].clone());
+    let config = Arc::new(Config::default().validate()?);
+    let (log_store, sm) = new_mem_store();
+    let raft = Raft::new(id, config, network, log_store, sm).await?;
+
+    let service = GrpcRaftService::new(raft.clone()).into_server();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    Ok((raft, addr))
+}
+
+/// Replicate logs to a cluster of 3 nodes through gRPC.
+#[tokio::test]
+async fn test_grpc_cluster() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+
+    let mut rafts = vec![];
+    for id in 0..3 {
+        let (raft, addr) = start_node(id, addrs.clone()).await?;
+        addrs.lock().unwrap().insert(id, addr);
+        rafts.push(raft);
+    }
+
+    rafts[0].initialize(btreeset! {0,1,2}).await?;
+    rafts[0].wait(Some(Duration::from_secs(5))).current_leader(0, "node-0 becomes leader").await?;
+
+    let resp = rafts[0].client_write(ClientRequest::make_request("foo", 1)).await?;
+
+    for raft in &rafts {
+        raft.wait(Some(Duration::from_secs(5)))
+            .applied_index_at_least(Some(resp.log_id.index), "replicated to every node")
+            .await?;
+    }
+
+    for raft in rafts {
+        raft.shutdown().await?;
+    }
+
+    Ok(())
+}
+
+/// Replicate a snapshot to a learner through gRPC, when the logs are purged.
+#[tokio::test]
+async fn test_grpc_install_snapshot() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+
+    let (leader, addr) = start_node(0, addrs.clone()).await?;
+    addrs.lock().unwrap().insert(0, addr);
+
+    leader.initialize(btreeset! {0}).await?;
+    leader.wait(Some(Duration::from_secs(5))).current_leader(0, "node-0 becomes leader").await?;
+
+    let mut last_log_id = None;
+    for i in 0..5 {
+        let resp = leader.client_write(ClientRequest::make_request("foo", i)).await?;
+        last_log_id = Some(resp.log_id);
+    }
+    let last_log_id = last_log_id.unwrap();
+
+    leader.trigger().snapshot().await?;
+    leader.wait(Some(Duration::from_secs(5))).snapshot(last_log_id, "snapshot is built").await?;
+
+    leader.trigger().purge_log(last_log_id.index).await?;
+    leader.wait(Some(Duration::from_secs(5))).purged(Some(last_log_id), "logs are purged").await?;
+
+    let (learner, addr) = start_node(1, addrs.clone()).await?;
+    addrs.lock().unwrap().insert(1, addr);
+
+    leader.add_learner(1, (), true).await?;
+    learner
+        .wait(Some(Duration::from_secs(5)))
+        .snapshot(last_log_id, "learner installed snapshot")
+        .await?;
+
+    leader.shutdown().await?;
+    learner.shutdown().await?;
+
+    Ok(())
+}
+
+/// A `RaftError` returned by the remote `Raft` is a `RemoteError`.
+#[tokio::test]
+async fn test_grpc_remote_error() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+    let (raft, addr) = start_node(1, addrs).await?;
+
+    // A stopped `Raft` returns `Fatal::Stopped`.
+    raft.shutdown().await?;
+
+    let mut net = GrpcNetwork::<TypeConfig>::new(1, &addr);
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+
+    match res {
+        Err(RPCError::RemoteError(e)) => assert_eq!(1, e.target),
+        other => panic!("expect RemoteError, got: {:?}", other),
+    }
+
+    Ok(())
+}
+
+/// A node that can not be connected is `Unreachable`.
+#[tokio::test]
+async fn test_grpc_unreachable() -> anyhow::Result<()> {
+    // Bind then drop a listener to get a local port that nothing listens on.
+    let addr = TcpListener::bind("127.0.0.1:0").await?.local_addr()?.to_string();
+
+    let mut net = GrpcNetwork::<TypeConfig>::new(1, &addr);
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+    assert!(matches!(res, Err(RPCError::Unreachable(_))), "got: {:?}", res);
+
+    let mut net = GrpcNetwork::<TypeConfig>::new(1, "http://bad address");
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+    assert!(matches!(res, Err(RPCError::Unreachable(_))), "got: {:?}", res);
+
+    Ok(())
+}
@@ -0,0 +1,300 @@
+//! The sending side: a `RaftNetwork` that sends RPCs to a remote [`QuicRaftServer`].
+//!
+//! [`QuicRaftServer`]: crate::QuicRaftServer
+
+use std::io;
+use std::sync::Arc;
+
+use openraft::error::InstallSnapshotError;
+use openraft::error::NetworkError;
+use openraft::error::RPCError;
+use openraft::error::RaftError;
+use openraft::error::RemoteError;
+use openraft::error::Unreachable;
+use openraft::network::RPCOption;
+use openraft::network::RaftNetwork;
+use openraft::network::RaftNetworkFactory;
+use openraft::raft::AppendEntriesRequest;
+use openraft::raft::AppendEntriesResponse;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::VoteRequest;
+use openraft::raft::VoteResponse;
+use openraft::AnyError;
+use openraft::BasicNode;
+use openraft::RaftTypeConfig;
+use quinn::Connection;
+use quinn::Endpoint;
+
+use crate::codec::recv_message;
+use crate::codec::send_message;
+use crate::connections::Connections;
+use crate::message::RaftReply;
+use crate::message::RaftRequest;
+
+/// Returns the address of a target node.
+type AddrOf<C> = dyn Fn(&<C as RaftTypeConfig>::NodeId, &<C as RaftTypeConfig>::Node) -> String + Send + Sync;
+
+/// Builds a [`QuicNetwork`] for every replication target.
+///
+/// Connections are made from `endpoint` with its default client config, e.g., one built by
+/// [`client_config()`](crate::client_config). The same endpoint can also serve a
+/// [`QuicRaftServer`](crate::QuicRaftServer).
+///
+/// The address of a target is derived from its node id and `Node` by a user provided function,
+/// e.g., `"10.0.0.1:5051"` or `"raft-1.example.com:5051"`. The host is the server name to verify
+/// the certificate of the target against. With [`BasicNode`],
+/// [`QuicNetworkFactory::with_basic_node()`] uses [`BasicNode::addr`].
+///
+/// The networks built by a factory share one connection to every node, keyed by node id.
+pub struct QuicNetworkFactory<C>
+where C: RaftTypeConfig
+{
+    endpoint: Endpoint,
+    addr_of: Arc<AddrOf<C>>,
+    connections: Arc<Connections<C>>,
+}
+
+impl<C> QuicNetworkFactory<C>
+where C: RaftTypeConfig
+{
+    /// Create a factory that connects from `endpoint` to the address returned by
+    /// `addr_of(target, node)`.
+    pub fn new(endpoint: Endpoint, addr_of: impl Fn(&C::NodeId, &C::Node) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            endpoint,
+            addr_of: Arc::new(addr_of),
+            connections: Arc::new(Connections::new()),
+        }
+    }
+}
+
+impl<C> QuicNetworkFactory<C>
+where C: RaftTypeConfig<Node = BasicNode>
+{
+    /// Create a factory that connects from `endpoint` to [`BasicNode::addr`].
+    pub fn with_basic_node(endpoint: Endpoint) -> Self {
+        Self::new(endpoint, |_target, node| node.addr.clone())
+    }
+}
+
+impl<C> RaftNetworkFactory<C> for QuicNetworkFactory<C>
+where
+    C: RaftTypeConfig,
+    C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io::AsyncSeek + Unpin,
+{
+    type Network = QuicNetwork<C>;
+
+    async fn new_client(&mut self, target: C::NodeId, node: &C::Node) -> Self::Network {
+        QuicNetwork {
+            target,
+            addr: (self.addr_of)(&target, node),
+            endpoint: self.endpoint.clone(),
+            connections: self.connections.clone(),
+        }
+    }
+}
+
+/// Sends RPCs to a single target node.
+///
+/// Every RPC is sent on its own stream of the connection to the target, thus a large snapshot
+/// chunk does not delay the heartbeats and votes sent at the same time. A closed connection is
+/// replaced with a new one, in 0-RTT if the server is connected before.
+///
+/// Errors are mapped as:
+/// - A `RaftError` returned by the remote `Raft` is a [`RPCError::RemoteError`].
+/// - An `Unauthenticated` error returned by the remote `Raft` for an AppendEntries or Vote RPC is a
+///   [`RPCError::Network`].
+/// - Failing to connect is a [`RPCError::Unreachable`], so that Openraft backs off before
+///   reconnecting.
+/// - Failing to send or receive on an established connection is a [`RPCError::Network`].
+pub struct QuicNetwork<C>
+where C: RaftTypeConfig
+{
+    target: C::NodeId,
+    addr: String,
+    endpoint: Endpoint,
+    connections: Arc<Connections<C>>,
+}
+
+/// The reason an RPC failed before a reply is received.
+enum CallError {
+    Connect(io::Error),
+    Io(io::Error),
+    UnexpectedReply(&'static str),
+}
+
+impl CallError {
+    fn into_rpc_error<C, E>(self) -> RPCError<C, RaftError<C, E>>
+    where
+        C: RaftTypeConfig,
+        E: std::error::Error,
+    {
+        match self {
+            CallError::Connect(e) => RPCError::Unreachable(Unreachable::new(&e)),
+            CallError::Io(e) => RPCError::Network(NetworkError::new(&e)),
+            CallError::UnexpectedReply(kind) => {
+                let e = AnyError::error(format!("unexpected reply: {}", kind));
+                RPCError::Network(NetworkError::new(&e))
+            }
+        }
+    }
+}
+
+impl<C> QuicNetwork<C>
+where C: RaftTypeConfig
+{
+    /// Send a request on a new stream and wait for the reply.
+    async fn call(&mut self, req: RaftRequest<C>) -> Result<RaftReply<C>, CallError> {
+        let conn = match self.connections.get(self.target, &self.addr) {
+            Some(conn) => conn,
+            None => {
+                let (conn, _zero_rtt) = self.connect().await.map_err(CallError::Connect)?;
+                self.connections.insert(self.target, &self.addr, conn.clone());
+                conn
+            }
+        };
+
+        let (mut send, mut recv) = conn.open_bi().await.map_err(|e| CallError::Io(e.into()))?;
+        send_message::<C::PayloadCodec, _>(&mut send, &req).await.map_err(CallError::Io)?;
+        recv_message(&mut recv).await.map_err(CallError::Io)
+    }
+
+    /// Connect to the target, returns the connection and whether it is in 0-RTT.
+    ///
+    /// In 0-RTT, the connection can be used before the handshake completes.
+    async fn connect(&self) -> io::Result<(Connection, bool)> {
+        let sock_addr = tokio::net::lookup_host(&self.addr)
+            .await?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no address for {}", self.addr)))?;
+
+        let connecting = self.endpoint.connect(sock_addr, self.server_name()).map_err(io::Error::other)?;
+
+        match connecting.into_0rtt() {
+            Ok((conn, _accepted)) => Ok((conn, true)),
+            Err(connecting) => {
+                let conn = connecting.await?;
+                Ok((conn, false))
+            }
+        }
+    }
+
+    /// The host part of the address, e.g., `"10.0.0.1"` of `"10.0.0.1:5051"` or `"::1"` of
+    /// `"[::1]:5051"`.
+    fn server_name(&self) -> &str {
+        let host = self.addr.rsplit_once(':').map_or(self.addr.as_str(), |(host, _port)| host);
+        host.trim_start_matches('[').trim_end_matches(']')
+    }
+}
+
+impl<C> RaftNetwork<C> for QuicNetwork<C>
+where C: RaftTypeConfig
+{
+    async fn append_entries(
+        &mut self,
+        rpc: AppendEntriesRequest<C>,
+        _option: RPCOption,
+    ) -> Result<AppendEntriesResponse<C>, RPCError<C, RaftError<C>>> {
+        let reply = self.call(RaftRequest::AppendEntries(rpc)).await;
+        match reply {
+            Ok(RaftReply::AppendEntries(res)) => res.map_err(|e| RemoteError::new(self.target, e).into()),
+            Ok(other) => Err(CallError::UnexpectedReply(other.kind()).into_rpc_error()),
+            Err(e) => Err(e.into_rpc_error()),
+        }
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        rpc: InstallSnapshotRequest<C>,
+        _option: RPCOption,
+    ) -> Result<InstallSnapshotResponse<C>, RPCError<C, RaftError<C, InstallSnapshotError>>> {
+        let reply = self.call(RaftRequest::InstallSnapshot(rpc)).await;
+        match reply {
+            Ok(RaftReply::InstallSnapshot(res)) => res.map_err(|e| RemoteError::new(self.target, e).into()),
+            Ok(other) => Err(CallError::UnexpectedReply(other.kind()).into_rpc_error()),
+            Err(e) => Err(e.into_rpc_error()),
+        }
+    }
+
+    async fn vote(
+        &mut self,
+        rpc: VoteRequest<C>,
+        _option: RPCOption,
+    ) -> Result<VoteResponse<C>, RPCError<C, RaftError<C>>> {
+        let reply = self.call(RaftRequest::Vote(rpc)).await;
+        match reply {
+            Ok(RaftReply::Vote(res)) => res.map_err(|e| RemoteError::new(self.target, e).into()),
+            Ok(other) => Err(CallError::UnexpectedReply(other.kind()).into_rpc_error()),
+            Err(e) => Err(e.into_rpc_error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use openraft::network::RaftNetwork;
+    use openraft::network::RaftNetworkFactory;
+    use openraft::raft::VoteRequest;
+    use openraft::Vote;
+    use openraft_memstore::TypeConfig;
+
+    use crate::test::endpoint;
+    use crate::test::network;
+    use crate::test::rpc_option;
+    use crate::test::start_node;
+    use crate::QuicNetworkFactory;
+
+    /// A closed connection is replaced with a new one in 0-RTT.
+    #[tokio::test]
+    async fn test_reconnect_in_0rtt() -> anyhow::Result<()> {
+        let (raft, addr) = start_node(1, Arc::new(Mutex::new(BTreeMap::new()))).await?;
+
+        let mut net = network(&addr).await?;
+
+        // The first connection does a full handshake and receives a session ticket.
+        net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await?;
+
+        let conn = net.connections.get(1, &addr).unwrap();
+        conn.close(0u32.into(), b"test");
+        conn.closed().await;
+        assert!(net.connections.get(1, &addr).is_none(), "closed connection is removed");
+
+        let (_conn, zero_rtt) = net.connect().await?;
+        assert!(zero_rtt, "reconnect in 0-RTT");
+
+        net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await?;
+
+        raft.shutdown().await?;
+        Ok(())
+    }
+
+    /// The networks built by a factory share one connection to a node.
+    #[tokio::test]
+    async fn test_share_connection() -> anyhow::Result<()> {
+        let (raft, addr) = start_node(1, Arc::new(Mutex::new(BTreeMap::new()))).await?;
+
+        let a = addr.clone();
+        let mut factory = QuicNetworkFactory::<TypeConfig>::new(endpoint()?, move |_target, _node| a.clone());
+        let mut net1 = factory.new_client(1, &()).await;
+        let mut net2 = factory.new_client(1, &()).await;
+
+        let (r1, r2) = tokio::join!(
+            net1.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()),
+            net2.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()),
+        );
+        r1?;
+        r2?;
+
+        let c1 = net1.connections.get(1, &addr).unwrap();
+        let c2 = net2.connections.get(1, &addr).unwrap();
+        assert_eq!(c1.stable_id(), c2.stable_id());
+
+        raft.shutdown().await?;
+        Ok(())
+    }
+}
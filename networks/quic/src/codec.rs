@@ -0,0 +1,133 @@
+//! Every RPC is sent on its own bidirectional stream: the client sends a message of the request
+//! and finishes its side, the server replies the same way. The end of a stream delimits a
+//! message, thus there is no length prefix.
+//!
+//! A message is a header followed by the `serde_json` encoding of the request or reply. The header
+//! is the name of the `PayloadCodec` algorithm that compressed the encoding, prefixed with its
+//! 1-byte length. The receiver decompresses the message by the name.
+
+use std::io;
+
+use openraft::network::decode_payload;
+use openraft::network::encode_payload;
+use openraft::network::PayloadCodec;
+use quinn::RecvStream;
+use quinn::SendStream;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// The max size in bytes of a message, i.e., the header and the compressed encoding.
+///
+/// A larger message is rejected, so that a peer can not exhaust the memory. Snapshot chunks are
+/// limited by `Config::snapshot_max_chunk_size`, which defaults to 3 MiB.
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Send a message compressed with `P` and finish the stream.
+pub(crate) async fn send_message<P, T>(send: &mut SendStream, msg: &T) -> io::Result<()>
+where
+    P: PayloadCodec,
+    T: Serialize,
+{
+    let buf = encode_message::<P, T>(msg)?;
+    if buf.len() > MAX_MESSAGE_SIZE {
+        return Err(message_too_large(buf.len()));
+    }
+
+    send.write_all(&buf).await?;
+    send.finish()?;
+    Ok(())
+}
+
+/// Receive a message until the peer finishes the stream.
+pub(crate) async fn recv_message<T>(recv: &mut RecvStream) -> io::Result<T>
+where T: DeserializeOwned {
+    let buf = recv.read_to_end(MAX_MESSAGE_SIZE).await.map_err(|e| match e {
+        quinn::ReadToEndError::TooLong => message_too_large(MAX_MESSAGE_SIZE + 1),
+        quinn::ReadToEndError::Read(e) => io::Error::from(e),
+    })?;
+
+    decode_message(buf)
+}
+
+fn encode_message<P, T>(msg: &T) -> io::Result<Vec<u8>>
+where
+    P: PayloadCodec,
+    T: Serialize,
+{
+    let (compression, payload) = encode_payload::<P>(serde_json::to_vec(msg)?)?;
+    if compression.len() > u8::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "compression name is too long",
+        ));
+    }
+
+    let mut buf = Vec::with_capacity(1 + compression.len() + payload.len());
+    buf.push(compression.len() as u8);
+    buf.extend_from_slice(compression.as_bytes());
+    buf.extend_from_slice(&payload);
+    Ok(buf)
+}
+
+fn decode_message<T>(mut buf: Vec<u8>) -> io::Result<T>
+where T: DeserializeOwned {
+    let name_len = *buf.first().ok_or_else(|| invalid_data("empty message"))? as usize;
+    if buf.len() < 1 + name_len {
+        return Err(invalid_data("incomplete message header"));
+    }
+
+    let payload = buf.split_off(1 + name_len);
+    let compression = std::str::from_utf8(&buf[1..]).map_err(|_| invalid_data("invalid compression name"))?;
+    let payload = decode_payload(compression, payload)?;
+
+    let msg = serde_json::from_slice(&payload)?;
+    Ok(msg)
+}
+
+fn message_too_large(len: usize) -> io::Error {
+    invalid_data(&format!(
+        "message size {} exceeds max message size {}",
+        len, MAX_MESSAGE_SIZE
+    ))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use openraft::network::Lz4;
+    use openraft::network::NoCompression;
+
+    use crate::codec::decode_message;
+    use crate::codec::encode_message;
+
+    #[test]
+    fn test_message_round_trip() -> anyhow::Result<()> {
+        let buf = encode_message::<NoCompression, _>(&"foo".to_string())?;
+        assert_eq!(b"\x04none\"foo\"".to_vec(), buf);
+        assert_eq!("foo", decode_message::<String>(buf)?);
+
+        let msg = vec!["foo".repeat(10); 100];
+        let buf = encode_message::<Lz4, _>(&msg)?;
+        assert_eq!(b"\x03lz4", &buf[..4]);
+        assert!(buf.len() < serde_json::to_vec(&msg)?.len());
+        assert_eq!(msg, decode_message::<Vec<String>>(buf)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_header() -> anyhow::Result<()> {
+        let err = decode_message::<String>(vec![]).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+        let err = decode_message::<String>(b"\x05none".to_vec()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+//! `quinn` configurations for a Raft cluster, with 0-RTT enabled.
+//!
+//! Data sent in 0-RTT can be replayed by an attacker that captures it. A Raft RPC is safe to be
+//! delivered more than once, thus every RPC is allowed to be sent in 0-RTT.
+
+use std::sync::Arc;
+
+use quinn::crypto::rustls::QuicClientConfig;
+use quinn::crypto::rustls::QuicServerConfig;
+use quinn::rustls;
+use quinn::rustls::pki_types::CertificateDer;
+use quinn::rustls::pki_types::PrivateKeyDer;
+
+/// The ALPN protocol id of the Raft RPCs.
+pub const ALPN: &[u8] = b"openraft";
+
+/// Build a client config that trusts the certificates in `roots`.
+///
+/// When reconnecting to a server it has connected to before, the client sends the RPCs in 0-RTT
+/// data, without waiting for the handshake to complete.
+pub fn client_config(roots: rustls::RootCertStore) -> Result<quinn::ClientConfig, rustls::Error> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let mut tls = rustls::ClientConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&rustls::version::TLS13])?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    tls.enable_early_data = true;
+    tls.alpn_protocols = vec![ALPN.to_vec()];
+
+    let quic = QuicClientConfig::try_from(tls).map_err(|e| rustls::Error::General(e.to_string()))?;
+    Ok(quinn::ClientConfig::new(Arc::new(quic)))
+}
+
+/// Build a server config that presents `cert_chain` and accepts 0-RTT data.
+pub fn server_config(
+    cert_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+) -> Result<quinn::ServerConfig, rustls::Error> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let mut tls = rustls::ServerConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&rustls::version::TLS13])?
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, key)?;
+    tls.max_early_data_size = u32::MAX;
+    tls.alpn_protocols = vec![ALPN.to_vec()];
+
+    let quic = QuicServerConfig::try_from(tls).map_err(|e| rustls::Error::General(e.to_string()))?;
+    Ok(quinn::ServerConfig::with_crypto(Arc::new(quic)))
+}
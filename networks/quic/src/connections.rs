@@ -0,0 +1,50 @@
+//! The connections to the other nodes, shared by all of the [`QuicNetwork`]s built by a
+//! [`QuicNetworkFactory`].
+//!
+//! There is at most one connection to a node: RPCs are multiplexed on it, each on its own stream.
+//!
+//! [`QuicNetwork`]: crate::QuicNetwork
+//! [`QuicNetworkFactory`]: crate::QuicNetworkFactory
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use openraft::RaftTypeConfig;
+use quinn::Connection;
+
+pub(crate) struct Connections<C>
+where C: RaftTypeConfig
+{
+    /// The connection to a node and the address it is connected to.
+    conns: Mutex<HashMap<C::NodeId, (String, Connection)>>,
+}
+
+impl<C> Connections<C>
+where C: RaftTypeConfig
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            conns: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the open connection to `target` at `addr`.
+    ///
+    /// A connection that is closed, or to a former address of `target`, is removed.
+    pub(crate) fn get(&self, target: C::NodeId, addr: &str) -> Option<Connection> {
+        let mut conns = self.conns.lock().unwrap();
+
+        let (conn_addr, conn) = conns.get(&target)?;
+        if conn_addr == addr && conn.close_reason().is_none() {
+            return Some(conn.clone());
+        }
+
+        conns.remove(&target);
+        None
+    }
+
+    pub(crate) fn insert(&self, target: C::NodeId, addr: &str, conn: Connection) {
+        let mut conns = self.conns.lock().unwrap();
+        conns.insert(target, (addr.to_string(), conn));
+    }
+}
@@ -0,0 +1,21 @@
+#![doc = include_str!("../README.md")]
+#![deny(unused_crate_dependencies)]
+#![deny(unused_qualifications)]
+
+#[cfg(test)] mod test;
+
+mod client;
+mod codec;
+mod config;
+mod connections;
+mod message;
+mod server;
+
+pub use client::QuicNetwork;
+pub use client::QuicNetworkFactory;
+pub use codec::MAX_MESSAGE_SIZE;
+pub use config::client_config;
+pub use config::server_config;
+pub use config::ALPN;
+pub use quinn;
+pub use server::QuicRaftServer;
@@ -0,0 +1,108 @@
+//! The receiving side: accepts connections and forwards the RPCs into a local `Raft`.
+
+use openraft::network::NoCompression;
+use openraft::Raft;
+use openraft::RaftTypeConfig;
+use quinn::Connection;
+use quinn::Endpoint;
+use quinn::Incoming;
+use quinn::RecvStream;
+use quinn::SendStream;
+
+use crate::codec::recv_message;
+use crate::codec::send_message;
+use crate::message::RaftReply;
+use crate::message::RaftRequest;
+
+/// Serves the Raft RPCs sent by [`QuicNetwork`]s, by forwarding them to a local [`Raft`].
+///
+/// Every stream is served in its own task, thus a slow RPC, such as installing a snapshot chunk,
+/// does not delay the others on the same connection.
+///
+/// A `RaftError` returned by [`Raft`] is sent back in the reply, and the sender returns it as a
+/// `RPCError::RemoteError`.
+///
+/// ```ignore
+/// let endpoint = quinn::Endpoint::server(openraft_quic::server_config(certs, key)?, addr)?;
+/// tokio::spawn(QuicRaftServer::new(raft).serve(endpoint));
+/// ```
+///
+/// [`QuicNetwork`]: crate::QuicNetwork
+pub struct QuicRaftServer<C>
+where C: RaftTypeConfig
+{
+    raft: Raft<C>,
+}
+
+impl<C> QuicRaftServer<C>
+where
+    C: RaftTypeConfig,
+    C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io::AsyncSeek + Unpin,
+{
+    pub fn new(raft: Raft<C>) -> Self {
+        Self { raft }
+    }
+
+    /// Accept connections on `endpoint` and serve every connection in a task.
+    ///
+    /// It returns when `endpoint` is closed.
+    pub async fn serve(self, endpoint: Endpoint) {
+        while let Some(incoming) = endpoint.accept().await {
+            tokio::spawn(Self::serve_connection(self.raft.clone(), incoming));
+        }
+    }
+
+    async fn serve_connection(raft: Raft<C>, incoming: Incoming) {
+        let remote = incoming.remote_address();
+
+        let conn = match Self::accept(incoming).await {
+            Ok(conn) => conn,
+            Err(e) => {
+                tracing::warn!("failed to accept connection from {}: {}", remote, e);
+                return;
+            }
+        };
+
+        loop {
+            match conn.accept_bi().await {
+                Ok((send, recv)) => {
+                    tokio::spawn(Self::serve_stream(raft.clone(), send, recv));
+                }
+                Err(e) => {
+                    tracing::debug!("connection from {} closed: {}", remote, e);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Accept a connection, and the 0-RTT data sent on it if there is.
+    async fn accept(incoming: Incoming) -> Result<Connection, quinn::ConnectionError> {
+        let connecting = incoming.accept()?;
+
+        match connecting.into_0rtt() {
+            Ok((conn, _accepted)) => Ok(conn),
+            Err(connecting) => connecting.await,
+        }
+    }
+
+    async fn serve_stream(raft: Raft<C>, mut send: SendStream, mut recv: RecvStream) {
+        let req: RaftRequest<C> = match recv_message(&mut recv).await {
+            Ok(req) => req,
+            Err(e) => {
+                tracing::warn!("failed to receive request: {}", e);
+                return;
+            }
+        };
+
+        let reply = match req {
+            RaftRequest::AppendEntries(rpc) => RaftReply::AppendEntries(raft.append_entries(rpc).await),
+            RaftRequest::Vote(rpc) => RaftReply::Vote(raft.vote(rpc).await),
+            RaftRequest::InstallSnapshot(rpc) => RaftReply::InstallSnapshot(raft.install_snapshot(rpc).await),
+        };
+
+        if let Err(e) = send_message::<NoCompression, _>(&mut send, &reply).await {
+            tracing::warn!("failed to send reply: {}", e);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use maplit::btreeset;
+use openraft::error::RPCError;
+use openraft::network::RPCOption;
+use openraft::network::RaftNetwork;
+use openraft::network::RaftNetworkFactory;
+use openraft::raft::VoteRequest;
+use openraft::Config;
+use openraft::Raft;
+use openraft::Vote;
+use openraft_memstore::new_mem_store;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+use openraft_memstore::MemNodeId;
+use openraft_memstore::TypeConfig;
+use quinn::rustls::pki_types::PrivatePkcs8KeyDer;
+use quinn::rustls::RootCertStore;
+use quinn::Endpoint;
+use quinn::TransportConfig;
+
+use crate::client_config;
+use crate::server_config;
+use crate::QuicNetwork;
+use crate::QuicNetworkFactory;
+use crate::QuicRaftServer;
+
+pub(crate) fn rpc_option() -> RPCOption {
+    RPCOption::new(Duration::from_millis(1_000))
+}
+
+/// A self-signed certificate for `127.0.0.1` and its key, shared by all of the nodes.
+fn cert() -> &'static rcgen::CertifiedKey {
+    static CERT: OnceLock<rcgen::CertifiedKey> = OnceLock::new();
+    CERT.get_or_init(|| rcgen::generate_simple_self_signed(vec!["127.0.0.1".to_string()]).unwrap())
+}
+
+/// Build an endpoint listening on a random local port, which trusts the shared certificate.
+pub(crate) fn endpoint() -> anyhow::Result<Endpoint> {
+    let cert = cert();
+    let key = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
+
+    let mut roots = RootCertStore::empty();
+    roots.add(cert.cert.der().clone())?;
+
+    let mut client = client_config(roots)?;
+    let mut transport = TransportConfig::default();
+    transport.max_idle_timeout(Some(Duration::from_millis(500).try_into()?));
+    client.transport_config(Arc::new(transport));
+
+    let server = server_config(vec![cert.cert.der().clone()], key.into())?;
+
+    let addr: SocketAddr = "127.0.0.1:0".parse()?;
+    let mut endpoint = Endpoint::server(server, addr)?;
+    endpoint.set_default_client_config(client);
+    Ok(endpoint)
+}
+
+/// Build a network to node 1 at `addr`.
+pub(crate) async fn network(addr: &str) -> anyhow::Result<QuicNetwork<TypeConfig>> {
+    let addr = addr.to_string();
+    let mut factory = QuicNetworkFactory::new(endpoint()?, move |_target, _node| addr.clone());
+    Ok(factory.new_client(1, &()).await)
+}
+
+/// Start a node and serve it on a random local port, returns the address.
+pub(crate) async fn start_node(
+    id: MemNodeId,
+    addrs: Arc<std::sync::Mutex<BTreeMap<MemNodeId, String>>>,
+) -> anyhow::Result<(Raft<TypeConfig>, String)> {
+    let endpoint = endpoint()?;
+    let addr = endpoint.local_addr()?.to_string();
+
+    let network = QuicNetworkFactory::new(endpoint.clone(), move |target, _node| {
+        addrs.lock().unwrap()[target].clone()
+    });
+    let config = Arc::new(Config::default().validate()?);
+    let (log_store, sm) = new_mem_store();
+    let raft = Raft::new(id, config, network, log_store, sm).await?;
+
+    tokio::spawn(QuicRaftServer::new(raft.clone()).serve(endpoint));
+
+    Ok((raft, addr))
+}
+
+/// Replicate logs to a cluster of 3 nodes through QUIC.
+#[tokio::test]
+async fn test_quic_cluster() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+
+    let mut rafts = vec![];
+    for id in 0..3 {
+        let (raft, addr) = start_node(id, addrs.clone()).await?;
+        addrs.lock().unwrap().insert(id, addr);
+        rafts.push(raft);
+    }
+
+    rafts[0].initialize(btreeset! {0,1,2}).await?;
+    rafts[0].wait(Some(Duration::from_secs(5))).current_leader(0, "node-0 becomes leader").await?;
+
+    let resp = rafts[0].client_write(ClientRequest::make_request("foo", 1)).await?;
+
+    for raft in &rafts {
+        raft.wait(Some(Duration::from_secs(5)))
+            .applied_index_at_least(Some(resp.log_id.index), "replicated to every node")
+            .await?;
+    }
+
+    for raft in rafts {
+        raft.shutdown().await?;
+    }
+
+    Ok(())
+}
+
+/// Replicate a snapshot to a learner through QUIC, when the logs are purged.
+#[tokio::test]
+async fn test_quic_install_snapshot() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+
+    let (leader, addr) = start_node(0, addrs.clone()).await?;
+    addrs.lock().unwrap().insert(0, addr);
+
+    leader.initialize(btreeset! {0}).await?;
+    leader.wait(Some(Duration::from_secs(5))).current_leader(0, "node-0 becomes leader").await?;
+
+    let mut last_log_id = None;
+    for i in 0..5 {
+        let resp = leader.client_write(ClientRequest::make_request("foo", i)).await?;
+        last_log_id = Some(resp.log_id);
+    }
+    let last_log_id = last_log_id.unwrap();
+
+    leader.trigger().snapshot().await?;
+    leader.wait(Some(Duration::from_secs(5))).snapshot(last_log_id, "snapshot is built").await?;
+
+    leader.trigger().purge_log(last_log_id.index).await?;
+    leader.wait(Some(Duration::from_secs(5))).purged(Some(last_log_id), "logs are purged").await?;
+
+    let (learner, addr) = start_node(1, addrs.clone()).await?;
+    addrs.lock().unwrap().insert(1, addr);
+
+    leader.add_learner(1, (), true).await?;
+    learner
+        .wait(Some(Duration::from_secs(5)))
+        .snapshot(last_log_id, "learner installed snapshot")
+        .await?;
+
+    leader.shutdown().await?;
+    learner.shutdown().await?;
+
+    Ok(())
+}
+
+/// A `RaftError` returned by the remote `Raft` is a `RemoteError`.
+#[tokio::test]
+async fn test_quic_remote_error() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+    let (raft, addr) = start_node(1, addrs).await?;
+
+    // A stopped `Raft` returns `Fatal::Stopped`.
+    raft.shutdown().await?;
+
+    let mut net = network(&addr).await?;
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+
+    match res {
+        Err(RPCError::RemoteError(e)) => assert_eq!(1, e.target),
+        other => panic!("expect RemoteError, got: {:?}", other),
+    }
+
+    Ok(())
+}
+
+/// A node that can not be connected is `Unreachable`.
+#[tokio::test]
+async fn test_quic_unreachable() -> anyhow::Result<()> {
+    // Nothing listens on the port of a closed endpoint, the handshake times out.
+    let addr = endpoint()?.local_addr()?.to_string();
+
+    let mut net = network(&addr).await?;
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+    assert!(matches!(res, Err(RPCError::Unreachable(_))), "got: {:?}", res);
+
+    let mut net = network("bad address").await?;
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+    assert!(matches!(res, Err(RPCError::Unreachable(_))), "got: {:?}", res);
+
+    Ok(())
+}
@@ -0,0 +1,342 @@
+//! The sending side: a `RaftNetwork` that sends RPCs to a remote [`TcpRaftServer`].
+//!
+//! [`TcpRaftServer`]: crate::TcpRaftServer
+
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use openraft::error::InstallSnapshotError;
+use openraft::error::NetworkError;
+use openraft::error::RPCError;
+use openraft::error::RaftError;
+use openraft::error::RemoteError;
+use openraft::error::Unreachable;
+use openraft::network::Backoff;
+use openraft::network::ExponentialBackoff;
+use openraft::network::RPCOption;
+use openraft::network::RaftNetwork;
+use openraft::network::RaftNetworkFactory;
+use openraft::raft::AppendEntriesRequest;
+use openraft::raft::AppendEntriesResponse;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::VoteRequest;
+use openraft::raft::VoteResponse;
+use openraft::AnyError;
+use openraft::BasicNode;
+use openraft::RaftTypeConfig;
+use tokio::net::TcpStream;
+
+use crate::codec::read_frame;
+use crate::codec::write_frame;
+use crate::message::Envelope;
+use crate::message::GroupId;
+use crate::message::RaftReply;
+use crate::message::RaftRequest;
+use crate::message::DEFAULT_GROUP;
+use crate::pool::ConnectionPool;
+
+/// Returns the address of a target node.
+type AddrOf<C> = dyn Fn(&<C as RaftTypeConfig>::NodeId, &<C as RaftTypeConfig>::Node) -> String + Send + Sync;
+
+/// Builds a [`TcpNetwork`] for every replication target.
+///
+/// The address of a target is derived from its node id and `Node` by a user provided function,
+/// e.g., `"127.0.0.1:5051"`. With [`BasicNode`], [`TcpNetworkFactory::default()`] uses
+/// [`BasicNode::addr`].
+///
+/// The networks built by a factory share a pool of idle connections, keyed by node id.
+///
+/// The requests are sent to the Raft group [`DEFAULT_GROUP`]. To run more than one Raft group
+/// over the same connections, build the factories with a [`MultiplexedNetworkFactory`].
+///
+/// [`MultiplexedNetworkFactory`]: crate::MultiplexedNetworkFactory
+#[derive(Clone)]
+pub struct TcpNetworkFactory<C>
+where C: RaftTypeConfig
+{
+    pub(crate) group_id: GroupId,
+    addr_of: Arc<AddrOf<C>>,
+    pool: Arc<ConnectionPool<C>>,
+    min_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl<C> TcpNetworkFactory<C>
+where C: RaftTypeConfig
+{
+    /// Create a factory that connects to the address returned by `addr_of(target, node)`.
+    pub fn new(addr_of: impl Fn(&C::NodeId, &C::Node) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            group_id: DEFAULT_GROUP,
+            addr_of: Arc::new(addr_of),
+            pool: Arc::new(ConnectionPool::new(4)),
+            min_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+
+    /// Set the max number of idle connections kept for every target node. The default is 4.
+    pub fn with_max_idle_per_node(mut self, max_idle_per_node: usize) -> Self {
+        self.pool = Arc::new(ConnectionPool::new(max_idle_per_node));
+        self
+    }
+
+    /// Set how long to wait before reconnecting to an unreachable node.
+    ///
+    /// The first wait is `min`, and it doubles on every failure up to `max`. The default is from
+    /// 100 ms to 5 s.
+    pub fn with_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max;
+        self
+    }
+}
+
+impl<C> Default for TcpNetworkFactory<C>
+where C: RaftTypeConfig<Node = BasicNode>
+{
+    fn default() -> Self {
+        Self::new(|_target, node| node.addr.clone())
+    }
+}
+
+impl<C> RaftNetworkFactory<C> for TcpNetworkFactory<C>
+where
+    C: RaftTypeConfig,
+    C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io::AsyncSeek + Unpin,
+{
+    type Network = TcpNetwork<C>;
+
+    async fn new_client(&mut self, target: C::NodeId, node: &C::Node) -> Self::Network {
+        TcpNetwork {
+            group_id: self.group_id,
+            target,
+            addr: (self.addr_of)(&target, node),
+            pool: self.pool.clone(),
+            min_backoff: self.min_backoff,
+            max_backoff: self.max_backoff,
+        }
+    }
+}
+
+/// A connection to a single target node.
+///
+/// An RPC takes an idle connection from the pool or connects to the target. The connection is
+/// returned to the pool when the RPC completes, or closed if it fails.
+///
+/// Errors are mapped as:
+/// - A `RaftError` returned by the remote `Raft` is a [`RPCError::RemoteError`].
+/// - An `Unauthenticated` error returned by the remote `Raft` for an AppendEntries or Vote RPC is a
+///   [`RPCError::Network`].
+/// - Failing to connect is a [`RPCError::Unreachable`], so that Openraft backs off before
+///   reconnecting.
+/// - Failing to send or receive on an established connection is a [`RPCError::Network`].
+pub struct TcpNetwork<C>
+where C: RaftTypeConfig
+{
+    group_id: GroupId,
+    target: C::NodeId,
+    addr: String,
+    pool: Arc<ConnectionPool<C>>,
+    min_backoff: Duration,
+    max_backoff: Duration,
+}
+
+/// The reason an RPC failed before a reply is received.
+enum CallError {
+    Connect(io::Error),
+    Io(io::Error),
+    UnexpectedReply(&'static str),
+    GroupNotFound(GroupId),
+}
+
+impl CallError {
+    fn into_rpc_error<C, E>(self) -> RPCError<C, RaftError<C, E>>
+    where
+        C: RaftTypeConfig,
+        E: std::error::Error,
+    {
+        match self {
+            CallError::Connect(e) => RPCError::Unreachable(Unreachable::new(&e)),
+            CallError::Io(e) => RPCError::Network(NetworkError::new(&e)),
+            CallError::UnexpectedReply(kind) => {
+                let e = AnyError::error(format!("unexpected reply: {}", kind));
+                RPCError::Network(NetworkError::new(&e))
+            }
+            CallError::GroupNotFound(group_id) => {
+                // The group may be created on the target later, back off before retrying.
+                let e = AnyError::error(format!("group {} is not served by the target", group_id));
+                RPCError::Unreachable(Unreachable::new(&e))
+            }
+        }
+    }
+}
+
+impl<C> TcpNetwork<C>
+where C: RaftTypeConfig
+{
+    /// Send a request and wait for the reply.
+    ///
+    /// An idle connection may have been closed by the remote end, in which case the request is
+    /// sent once more on a new connection. Raft RPCs are safe to be delivered more than once.
+    async fn call(&mut self, req: RaftRequest<C>) -> Result<RaftReply<C>, CallError> {
+        let req = Envelope {
+            group_id: self.group_id,
+            message: req,
+        };
+
+        let reply = self.send_to_target(&req).await?;
+        if let RaftReply::GroupNotFound(group_id) = reply {
+            return Err(CallError::GroupNotFound(group_id));
+        }
+        Ok(reply)
+    }
+
+    async fn send_to_target(&mut self, req: &Envelope<RaftRequest<C>>) -> Result<RaftReply<C>, CallError> {
+        if let Some(mut stream) = self.pool.take(self.target, &self.addr) {
+            match Self::send_recv(&mut stream, req).await {
+                Ok(reply) => {
+                    self.pool.put(self.target, &self.addr, stream);
+                    return Ok(reply);
+                }
+                Err(e) => {
+                    tracing::debug!("idle connection to {} failed: {}, reconnect", self.target, e);
+                }
+            }
+        }
+
+        let mut stream = self.connect().await.map_err(CallError::Connect)?;
+        let reply = Self::send_recv(&mut stream, req).await.map_err(CallError::Io)?;
+        self.pool.put(self.target, &self.addr, stream);
+        Ok(reply)
+    }
+
+    async fn connect(&self) -> io::Result<TcpStream> {
+        let stream = TcpStream::connect(&self.addr).await?;
+        stream.set_nodelay(true)?;
+        Ok(stream)
+    }
+
+    async fn send_recv(stream: &mut TcpStream, req: &Envelope<RaftRequest<C>>) -> io::Result<RaftReply<C>> {
+        write_frame::<C::PayloadCodec, _, _>(stream, req).await?;
+        read_frame(stream).await
+    }
+}
+
+impl<C> RaftNetwork<C> for TcpNetwork<C>
+where C: RaftTypeConfig
+{
+    async fn append_entries(
+        &mut self,
+        rpc: AppendEntriesRequest<C>,
+        _option: RPCOption,
+    ) -> Result<AppendEntriesResponse<C>, RPCError<C, RaftError<C>>> {
+        let reply = self.call(RaftRequest::AppendEntries(rpc)).await;
+        match reply {
+            Ok(RaftReply::AppendEntries(res)) => res.map_err(|e| RemoteError::new(self.target, e).into()),
+            Ok(other) => Err(CallError::UnexpectedReply(other.kind()).into_rpc_error()),
+            Err(e) => Err(e.into_rpc_error()),
+        }
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        rpc: InstallSnapshotRequest<C>,
+        _option: RPCOption,
+    ) -> Result<InstallSnapshotResponse<C>, RPCError<C, RaftError<C, InstallSnapshotError>>> {
+        let reply = self.call(RaftRequest::InstallSnapshot(rpc)).await;
+        match reply {
+            Ok(RaftReply::InstallSnapshot(res)) => res.map_err(|e| RemoteError::new(self.target, e).into()),
+            Ok(other) => Err(CallError::UnexpectedReply(other.kind()).into_rpc_error()),
+            Err(e) => Err(e.into_rpc_error()),
+        }
+    }
+
+    async fn vote(
+        &mut self,
+        rpc: VoteRequest<C>,
+        _option: RPCOption,
+    ) -> Result<VoteResponse<C>, RPCError<C, RaftError<C>>> {
+        let reply = self.call(RaftRequest::Vote(rpc)).await;
+        match reply {
+            Ok(RaftReply::Vote(res)) => res.map_err(|e| RemoteError::new(self.target, e).into()),
+            Ok(other) => Err(CallError::UnexpectedReply(other.kind()).into_rpc_error()),
+            Err(e) => Err(e.into_rpc_error()),
+        }
+    }
+
+    /// Backoff exponentially from `min_backoff` to `max_backoff`.
+    fn backoff(&self) -> Backoff {
+        Backoff::from_policy(ExponentialBackoff::new(self.min_backoff, self.max_backoff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use openraft::network::RPCOption;
+    use openraft::network::RaftNetwork;
+    use openraft::network::RaftNetworkFactory;
+    use openraft::raft::VoteRequest;
+    use openraft::Config;
+    use openraft::Raft;
+    use openraft::Vote;
+    use openraft_memstore::new_mem_store;
+    use openraft_memstore::TypeConfig;
+    use tokio::net::TcpListener;
+    use tokio::net::TcpStream;
+
+    use crate::TcpNetworkFactory;
+    use crate::TcpRaftServer;
+
+    /// An idle connection closed by the remote end is replaced with a new one.
+    #[tokio::test]
+    async fn test_reconnect_closed_idle_connection() -> anyhow::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?.to_string();
+
+        let factory = || {
+            let addr = addr.clone();
+            TcpNetworkFactory::<TypeConfig>::new(move |_target, _node| addr.clone())
+        };
+
+        let mut net = factory().new_client(1, &()).await;
+
+        let closed = TcpStream::connect(&addr).await?;
+        drop(listener.accept().await?);
+        net.pool.put(1, &addr, closed);
+
+        let (log_store, sm) = new_mem_store();
+        let raft = Raft::new(1, Arc::new(Config::default().validate()?), factory(), log_store, sm).await?;
+        tokio::spawn(TcpRaftServer::new(raft.clone()).serve(listener));
+
+        let option = RPCOption::new(Duration::from_millis(1_000));
+        net.vote(VoteRequest::new(Vote::new(1, 0), None), option).await?;
+
+        assert!(
+            net.pool.take(1, &addr).is_some(),
+            "the new connection is returned to the pool"
+        );
+
+        raft.shutdown().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_backoff() -> anyhow::Result<()> {
+        let ms = Duration::from_millis;
+
+        let mut factory = TcpNetworkFactory::<TypeConfig>::new(|_target, _node| "127.0.0.1:0".to_string())
+            .with_backoff(ms(100), ms(500));
+        let net = factory.new_client(1, &()).await;
+
+        let got = net.backoff().take(5).collect::<Vec<_>>();
+        assert_eq!(vec![ms(100), ms(200), ms(400), ms(500), ms(500)], got);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+//! Length-prefixed framing: every message is a 4-byte big-endian length followed by a header and
+//! the `serde_json` encoding of the message.
+//!
+//! The header is the name of the `PayloadCodec` algorithm that compressed the encoded message,
+//! prefixed with its 1-byte length. The receiver decompresses the message by the name.
+
+use std::io;
+
+use openraft::network::decode_payload;
+use openraft::network::encode_payload;
+use openraft::network::PayloadCodec;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+/// The max size in bytes of a frame, i.e., the header and the compressed message.
+///
+/// A larger frame is rejected without being read, so that a corrupted length does not exhaust the
+/// memory. Snapshot chunks are limited by `Config::snapshot_max_chunk_size`, which defaults to
+/// 3 MiB.
+pub const MAX_FRAME_SIZE: usize = 64 * 1024 * 1024;
+
+/// Encode `msg`, compress it with `P` and write it as a frame.
+pub(crate) async fn write_frame<P, W, T>(w: &mut W, msg: &T) -> io::Result<()>
+where
+    P: PayloadCodec,
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let (compression, payload) = encode_payload::<P>(serde_json::to_vec(msg)?)?;
+    if compression.len() > u8::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "compression name is too long",
+        ));
+    }
+
+    let mut buf = Vec::with_capacity(4 + 1 + compression.len() + payload.len());
+    buf.extend_from_slice(&[0; 4]);
+    buf.push(compression.len() as u8);
+    buf.extend_from_slice(compression.as_bytes());
+    buf.extend_from_slice(&payload);
+
+    let len = buf.len() - 4;
+    if len > MAX_FRAME_SIZE {
+        return Err(frame_too_large(len));
+    }
+    buf[..4].copy_from_slice(&(len as u32).to_be_bytes());
+
+    // Write the length and the payload at once, to send them in a single segment.
+    w.write_all(&buf).await?;
+    w.flush().await
+}
+
+pub(crate) async fn read_frame<R, T>(r: &mut R) -> io::Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let len = r.read_u32().await? as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(frame_too_large(len));
+    }
+
+    let mut buf = vec![0; len];
+    r.read_exact(&mut buf).await?;
+
+    let (compression, payload) = split_header(buf)?;
+    let payload = decode_payload(&compression, payload)?;
+
+    let msg = serde_json::from_slice(&payload)?;
+    Ok(msg)
+}
+
+/// Split a frame into the name of the compression algorithm and the payload.
+fn split_header(mut buf: Vec<u8>) -> io::Result<(String, Vec<u8>)> {
+    let name_len = *buf.first().ok_or_else(|| invalid_data("empty frame"))? as usize;
+    if buf.len() < 1 + name_len {
+        return Err(invalid_data("incomplete frame header"));
+    }
+
+    let payload = buf.split_off(1 + name_len);
+    let name = String::from_utf8(buf.split_off(1)).map_err(|_| invalid_data("invalid compression name"))?;
+    Ok((name, payload))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn frame_too_large(len: usize) -> io::Error {
+    invalid_data(&format!("frame size {} exceeds max frame size {}", len, MAX_FRAME_SIZE))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use openraft::network::Lz4;
+    use openraft::network::NoCompression;
+
+    use crate::codec::read_frame;
+    use crate::codec::write_frame;
+    use crate::codec::MAX_FRAME_SIZE;
+
+    #[tokio::test]
+    async fn test_frame_round_trip() -> anyhow::Result<()> {
+        let mut buf = vec![];
+        write_frame::<NoCompression, _, _>(&mut buf, &"foo".to_string()).await?;
+        write_frame::<NoCompression, _, _>(&mut buf, &vec![1u64, 2, 3]).await?;
+
+        assert_eq!(&[0, 0, 0, 10, 4], &buf[..5]);
+        assert_eq!(b"none\"foo\"", &buf[5..14]);
+
+        let mut r = buf.as_slice();
+        assert_eq!("foo", read_frame::<_, String>(&mut r).await?);
+        assert_eq!(vec![1u64, 2, 3], read_frame::<_, Vec<u64>>(&mut r).await?);
+
+        let err = read_frame::<_, String>(&mut r).await.unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compressed_frame() -> anyhow::Result<()> {
+        let msg = vec!["foo".repeat(10); 100];
+
+        let mut buf = vec![];
+        write_frame::<Lz4, _, _>(&mut buf, &msg).await?;
+
+        assert_eq!(&[3], &buf[4..5]);
+        assert_eq!(b"lz4", &buf[5..8]);
+        assert!(buf.len() < serde_json::to_vec(&msg)?.len());
+
+        assert_eq!(msg, read_frame::<_, Vec<String>>(&mut buf.as_slice()).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_frame_too_large() -> anyhow::Result<()> {
+        let len = (MAX_FRAME_SIZE as u32 + 1).to_be_bytes();
+
+        let err = read_frame::<_, String>(&mut len.as_slice()).await.unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+        Ok(())
+    }
+}
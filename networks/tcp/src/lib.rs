@@ -0,0 +1,20 @@
+#![doc = include_str!("../README.md")]
+#![deny(unused_crate_dependencies)]
+#![deny(unused_qualifications)]
+
+#[cfg(test)] mod test;
+
+mod client;
+mod codec;
+mod message;
+mod multiplex;
+mod pool;
+mod server;
+
+pub use client::TcpNetwork;
+pub use client::TcpNetworkFactory;
+pub use codec::MAX_FRAME_SIZE;
+pub use message::GroupId;
+pub use message::DEFAULT_GROUP;
+pub use multiplex::MultiplexedNetworkFactory;
+pub use server::TcpRaftServer;
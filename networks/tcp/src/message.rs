@@ -0,0 +1,68 @@
+//! The messages sent over a connection.
+//!
+//! A client sends a [`RaftRequest`] in an [`Envelope`] and waits for the [`RaftReply`] of the same
+//! kind before sending the next one on the same connection.
+
+use openraft::error::InstallSnapshotError;
+use openraft::error::RaftError;
+use openraft::error::Unauthenticated;
+use openraft::raft::AppendEntriesRequest;
+use openraft::raft::AppendEntriesResponse;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::VoteRequest;
+use openraft::raft::VoteResponse;
+use openraft::RaftTypeConfig;
+
+/// Identifies a Raft group, when a node runs more than one `Raft`.
+///
+/// A `Raft` served by [`TcpRaftServer::new()`](crate::TcpRaftServer::new) is group
+/// [`DEFAULT_GROUP`].
+pub type GroupId = u64;
+
+/// The group of a single-`Raft` deployment.
+pub const DEFAULT_GROUP: GroupId = 0;
+
+/// A request addressed to the `Raft` of a group.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Envelope<T> {
+    pub(crate) group_id: GroupId,
+    pub(crate) message: T,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound = "")]
+pub(crate) enum RaftRequest<C>
+where C: RaftTypeConfig
+{
+    AppendEntries(AppendEntriesRequest<C>),
+    Vote(VoteRequest<C>),
+    InstallSnapshot(InstallSnapshotRequest<C>),
+}
+
+/// The result returned by the remote `Raft`.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound = "")]
+pub(crate) enum RaftReply<C>
+where C: RaftTypeConfig
+{
+    AppendEntries(Result<AppendEntriesResponse<C>, RaftError<C, Unauthenticated>>),
+    Vote(Result<VoteResponse<C>, RaftError<C, Unauthenticated>>),
+    InstallSnapshot(Result<InstallSnapshotResponse<C>, RaftError<C, InstallSnapshotError>>),
+
+    /// The target node does not serve the group of the request.
+    GroupNotFound(GroupId),
+}
+
+impl<C> RaftReply<C>
+where C: RaftTypeConfig
+{
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            RaftReply::AppendEntries(_) => "AppendEntries",
+            RaftReply::Vote(_) => "Vote",
+            RaftReply::InstallSnapshot(_) => "InstallSnapshot",
+            RaftReply::GroupNotFound(_) => "GroupNotFound",
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Run many Raft groups over the same connections.
+
+use std::time::Duration;
+
+use openraft::BasicNode;
+use openraft::RaftTypeConfig;
+
+use crate::message::GroupId;
+use crate::TcpNetworkFactory;
+
+/// Builds a [`TcpNetworkFactory`] for every Raft group of a multi-raft deployment, and all of
+/// them share one pool of connections to the other nodes.
+///
+/// Without it, every group opens its own connections to every peer. With it, the requests of all
+/// groups are sent on the same connections, each tagged with the [`GroupId`] of its group, and a
+/// [`TcpRaftServer`] forwards a request to the `Raft` added for the group.
+///
+/// All of the groups must use the same `RaftTypeConfig`, and the same node id for a node.
+///
+/// ```ignore
+/// let multiplexed = MultiplexedNetworkFactory::default();
+///
+/// let raft_1 = Raft::new(id, config.clone(), multiplexed.group(1), log_store_1, sm_1).await?;
+/// let raft_2 = Raft::new(id, config.clone(), multiplexed.group(2), log_store_2, sm_2).await?;
+///
+/// let server = TcpRaftServer::multiplexed();
+/// server.add_group(1, raft_1);
+/// server.add_group(2, raft_2);
+/// tokio::spawn(server.serve(listener));
+/// ```
+///
+/// [`TcpRaftServer`]: crate::TcpRaftServer
+pub struct MultiplexedNetworkFactory<C>
+where C: RaftTypeConfig
+{
+    inner: TcpNetworkFactory<C>,
+}
+
+impl<C> MultiplexedNetworkFactory<C>
+where C: RaftTypeConfig
+{
+    /// Create a factory that connects to the address returned by `addr_of(target, node)`.
+    pub fn new(addr_of: impl Fn(&C::NodeId, &C::Node) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            inner: TcpNetworkFactory::new(addr_of),
+        }
+    }
+
+    /// Set the max number of idle connections kept for every target node, shared by all groups.
+    /// The default is 4.
+    ///
+    /// It must be called before building any group factory.
+    pub fn with_max_idle_per_node(mut self, max_idle_per_node: usize) -> Self {
+        self.inner = self.inner.with_max_idle_per_node(max_idle_per_node);
+        self
+    }
+
+    /// Set how long to wait before reconnecting to an unreachable node, see
+    /// [`TcpNetworkFactory::with_backoff()`].
+    pub fn with_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.inner = self.inner.with_backoff(min, max);
+        self
+    }
+
+    /// Build the network factory for the `Raft` of group `group_id`.
+    pub fn group(&self, group_id: GroupId) -> TcpNetworkFactory<C> {
+        let mut f = self.inner.clone();
+        f.group_id = group_id;
+        f
+    }
+}
+
+impl<C> Default for MultiplexedNetworkFactory<C>
+where C: RaftTypeConfig<Node = BasicNode>
+{
+    fn default() -> Self {
+        Self::new(|_target, node| node.addr.clone())
+    }
+}
@@ -0,0 +1,126 @@
+//! Idle connections to the other nodes, shared by all of the [`TcpNetwork`]s built by a
+//! [`TcpNetworkFactory`].
+//!
+//! [`TcpNetwork`]: crate::TcpNetwork
+//! [`TcpNetworkFactory`]: crate::TcpNetworkFactory
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use openraft::RaftTypeConfig;
+use tokio::net::TcpStream;
+
+/// Idle connections to a node and the address they are connected to.
+struct Idle {
+    addr: String,
+    streams: Vec<TcpStream>,
+}
+
+pub(crate) struct ConnectionPool<C>
+where C: RaftTypeConfig
+{
+    max_idle_per_node: usize,
+    idle: Mutex<HashMap<C::NodeId, Idle>>,
+}
+
+impl<C> ConnectionPool<C>
+where C: RaftTypeConfig
+{
+    pub(crate) fn new(max_idle_per_node: usize) -> Self {
+        Self {
+            max_idle_per_node,
+            idle: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take an idle connection to `target` at `addr`.
+    ///
+    /// If the address of `target` has changed, the connections to the former address are closed.
+    pub(crate) fn take(&self, target: C::NodeId, addr: &str) -> Option<TcpStream> {
+        let mut idle = self.idle.lock().unwrap();
+
+        let entry = idle.get_mut(&target)?;
+        if entry.addr != addr {
+            idle.remove(&target);
+            return None;
+        }
+        entry.streams.pop()
+    }
+
+    /// Return a connection that completed an RPC, to be reused by the next one.
+    pub(crate) fn put(&self, target: C::NodeId, addr: &str, stream: TcpStream) {
+        let mut idle = self.idle.lock().unwrap();
+
+        let entry = idle.entry(target).or_insert_with(|| Idle {
+            addr: addr.to_string(),
+            streams: vec![],
+        });
+
+        if entry.addr != addr {
+            *entry = Idle {
+                addr: addr.to_string(),
+                streams: vec![],
+            };
+        }
+
+        if entry.streams.len() < self.max_idle_per_node {
+            entry.streams.push(stream);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openraft_memstore::TypeConfig;
+    use tokio::net::TcpListener;
+    use tokio::net::TcpStream;
+
+    use crate::pool::ConnectionPool;
+
+    #[tokio::test]
+    async fn test_take_put() -> anyhow::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?.to_string();
+
+        let pool = ConnectionPool::<TypeConfig>::new(2);
+        assert!(pool.take(1, &addr).is_none());
+
+        for _ in 0..3 {
+            pool.put(1, &addr, TcpStream::connect(&addr).await?);
+        }
+
+        // At most 2 idle connections are kept.
+        assert!(pool.take(1, &addr).is_some());
+        assert!(pool.take(1, &addr).is_some());
+        assert!(pool.take(1, &addr).is_none());
+
+        // Other nodes have their own connections.
+        pool.put(1, &addr, TcpStream::connect(&addr).await?);
+        assert!(pool.take(2, &addr).is_none());
+        assert!(pool.take(1, &addr).is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_addr_changed() -> anyhow::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?.to_string();
+
+        let pool = ConnectionPool::<TypeConfig>::new(2);
+
+        pool.put(1, &addr, TcpStream::connect(&addr).await?);
+        assert!(pool.take(1, "127.0.0.1:1").is_none());
+        assert!(
+            pool.take(1, &addr).is_none(),
+            "connections to the former address are closed"
+        );
+
+        pool.put(1, &addr, TcpStream::connect(&addr).await?);
+        pool.put(1, "127.0.0.1:1", TcpStream::connect(&addr).await?);
+        assert!(pool.take(1, "127.0.0.1:1").is_some());
+        assert!(pool.take(1, "127.0.0.1:1").is_none(), "the former connection is closed");
+
+        Ok(())
+    }
+}
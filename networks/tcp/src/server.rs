@@ -0,0 +1,128 @@
+//! The receiving side: accepts connections and forwards the RPCs into a local `Raft`.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use openraft::network::NoCompression;
+use openraft::Raft;
+use openraft::RaftTypeConfig;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+
+use crate::codec::read_frame;
+use crate::codec::write_frame;
+use crate::message::Envelope;
+use crate::message::GroupId;
+use crate::message::RaftReply;
+use crate::message::RaftRequest;
+use crate::message::DEFAULT_GROUP;
+
+/// Serves the Raft RPCs sent by [`TcpNetwork`]s, by forwarding them to a local [`Raft`].
+///
+/// A server can serve more than one Raft group on the same listener, e.g., in a multi-raft
+/// deployment: every request carries the [`GroupId`] of the [`TcpNetworkFactory`] that sent it,
+/// and is forwarded to the `Raft` added for that group. A request for a group that is not served
+/// fails with `RPCError::Unreachable` on the sender.
+///
+/// A `RaftError` returned by [`Raft`] is sent back in the reply, and the sender returns it as a
+/// `RPCError::RemoteError`.
+///
+/// ```ignore
+/// let listener = tokio::net::TcpListener::bind(addr).await?;
+/// tokio::spawn(TcpRaftServer::new(raft).serve(listener));
+/// ```
+///
+/// The server is cheap to clone, and the clones share the groups, thus groups can be added or
+/// removed while it is serving:
+///
+/// ```ignore
+/// let server = TcpRaftServer::multiplexed();
+/// tokio::spawn(server.clone().serve(listener));
+/// server.add_group(1, raft_1);
+/// server.add_group(2, raft_2);
+/// ```
+///
+/// [`TcpNetwork`]: crate::TcpNetwork
+/// [`TcpNetworkFactory`]: crate::TcpNetworkFactory
+#[derive(Clone)]
+pub struct TcpRaftServer<C>
+where C: RaftTypeConfig
+{
+    groups: Arc<RwLock<HashMap<GroupId, Raft<C>>>>,
+}
+
+impl<C> TcpRaftServer<C>
+where
+    C: RaftTypeConfig,
+    C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io::AsyncSeek + Unpin,
+{
+    /// Create a server for a single `Raft`, which is group [`DEFAULT_GROUP`].
+    pub fn new(raft: Raft<C>) -> Self {
+        let s = Self::multiplexed();
+        s.add_group(DEFAULT_GROUP, raft);
+        s
+    }
+
+    /// Create a server without any group, groups are added by [`Self::add_group()`].
+    pub fn multiplexed() -> Self {
+        Self {
+            groups: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Serve the requests of `group_id` with `raft`, replacing the former one if any.
+    pub fn add_group(&self, group_id: GroupId, raft: Raft<C>) {
+        self.groups.write().unwrap().insert(group_id, raft);
+    }
+
+    /// Stop serving the requests of `group_id`, returns the `Raft` of it.
+    pub fn remove_group(&self, group_id: GroupId) -> Option<Raft<C>> {
+        self.groups.write().unwrap().remove(&group_id)
+    }
+
+    /// Accept connections on `listener` and serve every connection in a task.
+    ///
+    /// It returns only if accepting a connection fails.
+    pub async fn serve(self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            stream.set_nodelay(true)?;
+
+            tokio::spawn(self.clone().serve_connection(stream, peer));
+        }
+    }
+
+    /// Serve the requests on a connection one by one, until it is closed by the client.
+    async fn serve_connection(self, mut stream: TcpStream, peer: SocketAddr) {
+        loop {
+            let req: Envelope<RaftRequest<C>> = match read_frame(&mut stream).await {
+                Ok(req) => req,
+                Err(e) => {
+                    if e.kind() != io::ErrorKind::UnexpectedEof {
+                        tracing::warn!("failed to read request from {}: {}", peer, e);
+                    }
+                    return;
+                }
+            };
+
+            let raft = self.groups.read().unwrap().get(&req.group_id).cloned();
+
+            let reply = match raft {
+                None => RaftReply::GroupNotFound(req.group_id),
+                Some(raft) => match req.message {
+                    RaftRequest::AppendEntries(rpc) => RaftReply::AppendEntries(raft.append_entries(rpc).await),
+                    RaftRequest::Vote(rpc) => RaftReply::Vote(raft.vote(rpc).await),
+                    RaftRequest::InstallSnapshot(rpc) => RaftReply::InstallSnapshot(raft.install_snapshot(rpc).await),
+                },
+            };
+
+            if let Err(e) = write_frame::<NoCompression, _, _>(&mut stream, &reply).await {
+                tracing::warn!("failed to send reply to {}: {}", peer, e);
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use maplit::btreeset;
+use openraft::error::RPCError;
+use openraft::network::RPCOption;
+use openraft::network::RaftNetwork;
+use openraft::network::RaftNetworkFactory;
+use openraft::raft::VoteRequest;
+use openraft::Config;
+use openraft::Raft;
+use openraft::Vote;
+use openraft_memstore::new_mem_store;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+use openraft_memstore::MemNodeId;
+use openraft_memstore::TypeConfig;
+use tokio::net::TcpListener;
+
+use crate::MultiplexedNetworkFactory;
+use crate::TcpNetwork;
+use crate::TcpNetworkFactory;
+use crate::TcpRaftServer;
+
+fn rpc_option() -> RPCOption {
+    RPCOption::new(Duration::from_millis(1_000))
+}
+
+/// Build a network to node 1 at `addr`.
+async fn network(addr: &str) -> TcpNetwork<TypeConfig> {
+    let addr = addr.to_string();
+    TcpNetworkFactory::new(move |_target, _node| addr.clone()).new_client(1, &()).await
+}
+
+/// Start a node and serve it on a random local port, returns the address.
+async fn start_node(
+    id: MemNodeId,
+    addrs: Arc<std::sync::Mutex<BTreeMap<MemNodeId, String>>>,
+) -> anyhow::Result<(Raft<TypeConfig>, String)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?.to_string();
+
+    let network = TcpNetworkFactory::new(move |target, _node| addrs.lock().unwrap()[target].clone());
+    let config = Arc::new(Config::default().validate()?);
+    let (log_store, sm) = new_mem_store();
+    let raft = Raft::new(id, config, network, log_store, sm).await?;
+
+    tokio::spawn(TcpRaftServer::new(raft.clone()).serve(listener));
+
+    Ok((raft, addr))
+}
+
+/// Start a node that runs the Raft groups 1 and 2 on one server, returns the address.
+async fn start_multiplexed_node(
+    id: MemNodeId,
+    addrs: Arc<std::sync::Mutex<BTreeMap<MemNodeId, String>>>,
+) -> anyhow::Result<(Vec<Raft<TypeConfig>>, String)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?.to_string();
+
+    let multiplexed = MultiplexedNetworkFactory::new(move |target, _node| addrs.lock().unwrap()[target].clone());
+    let config = Arc::new(Config::default().validate()?);
+    let server = TcpRaftServer::multiplexed();
+
+    let mut rafts = vec![];
+    for group_id in [1, 2] {
+        let (log_store, sm) = new_mem_store();
+        let raft = Raft::new(id, config.clone(), multiplexed.group(group_id), log_store, sm).await?;
+        server.add_group(group_id, raft.clone());
+        rafts.push(raft);
+    }
+
+    tokio::spawn(server.serve(listener));
+
+    Ok((rafts, addr))
+}
+
+/// Replicate logs to a cluster of 3 nodes through TCP.
+#[tokio::test]
+async fn test_tcp_cluster() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+
+    let mut rafts = vec![];
+    for id in 0..3 {
+        let (raft, addr) = start_node(id, addrs.clone()).await?;
+        addrs.lock().unwrap().insert(id, addr);
+        rafts.push(raft);
+    }
+
+    rafts[0].initialize(btreeset! {0,1,2}).await?;
+    rafts[0].wait(Some(Duration::from_secs(5))).current_leader(0, "node-0 becomes leader").await?;
+
+    let resp = rafts[0].client_write(ClientRequest::make_request("foo", 1)).await?;
+
+    for raft in &rafts {
+        raft.wait(Some(Duration::from_secs(5)))
+            .applied_index_at_least(Some(resp.log_id.index), "replicated to every node")
+            .await?;
+    }
+
+    for raft in rafts {
+        raft.shutdown().await?;
+    }
+
+    Ok(())
+}
+
+/// Replicate a snapshot to a learner through TCP, when the logs are purged.
+#[tokio::test]
+async fn test_tcp_install_snapshot() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+
+    let (leader, addr) = start_node(0, addrs.clone()).await?;
+    addrs.lock().unwrap().insert(0, addr);
+
+    leader.initialize(btreeset! {0}).await?;
+    leader.wait(Some(Duration::from_secs(5))).current_leader(0, "node-0 becomes leader").await?;
+
+    let mut last_log_id = None;
+    for i in 0..5 {
+        let resp = leader.client_write(ClientRequest::make_request("foo", i)).await?;
+        last_log_id = Some(resp.log_id);
+    }
+    let last_log_id = last_log_id.unwrap();
+
+    leader.trigger().snapshot().await?;
+    leader.wait(Some(Duration::from_secs(5))).snapshot(last_log_id, "snapshot is built").await?;
+
+    leader.trigger().purge_log(last_log_id.index).await?;
+    leader.wait(Some(Duration::from_secs(5))).purged(Some(last_log_id), "logs are purged").await?;
+
+    let (learner, addr) = start_node(1, addrs.clone()).await?;
+    addrs.lock().unwrap().insert(1, addr);
+
+    leader.add_learner(1, (), true).await?;
+    learner
+        .wait(Some(Duration::from_secs(5)))
+        .snapshot(last_log_id, "learner installed snapshot")
+        .await?;
+
+    leader.shutdown().await?;
+    learner.shutdown().await?;
+
+    Ok(())
+}
+
+/// A `RaftError` returned by the remote `Raft` is a `RemoteError`.
+#[tokio::test]
+async fn test_tcp_remote_error() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+    let (raft, addr) = start_node(1, addrs).await?;
+
+    // A stopped `Raft` returns `Fatal::Stopped`.
+    raft.shutdown().await?;
+
+    let mut net = network(&addr).await;
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+
+    match res {
+        Err(RPCError::RemoteError(e)) => assert_eq!(1, e.target),
+        other => panic!("expect RemoteError, got: {:?}", other),
+    }
+
+    Ok(())
+}
+
+/// A node that can not be connected is `Unreachable`.
+#[tokio::test]
+async fn test_tcp_unreachable() -> anyhow::Result<()> {
+    // Bind then drop a listener to get a local port that nothing listens on.
+    let addr = TcpListener::bind("127.0.0.1:0").await?.local_addr()?.to_string();
+
+    let mut net = network(&addr).await;
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+    assert!(matches!(res, Err(RPCError::Unreachable(_))), "got: {:?}", res);
+
+    let mut net = network("bad address").await;
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+    assert!(matches!(res, Err(RPCError::Unreachable(_))), "got: {:?}", res);
+
+    Ok(())
+}
+
+/// Two Raft groups replicate independently over the same connections.
+#[tokio::test]
+async fn test_tcp_multiplexed_groups() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+
+    let mut nodes = vec![];
+    for id in 0..3 {
+        let (rafts, addr) = start_multiplexed_node(id, addrs.clone()).await?;
+        addrs.lock().unwrap().insert(id, addr);
+        nodes.push(rafts);
+    }
+
+    // Group 1 is led by node-0 and group 2 by node-1.
+    for (group, leader) in [(0, 0), (1, 1)] {
+        nodes[leader][group].initialize(btreeset! {0,1,2}).await?;
+        nodes[leader][group]
+            .wait(Some(Duration::from_secs(5)))
+            .current_leader(leader as MemNodeId, "group leader is elected")
+            .await?;
+    }
+
+    let resp1 = nodes[0][0].client_write(ClientRequest::make_request("foo", 1)).await?;
+    let resp2 = nodes[1][1].client_write(ClientRequest::make_request("bar", 1)).await?;
+
+    for rafts in &nodes {
+        for (raft, resp) in rafts.iter().zip([&resp1, &resp2]) {
+            raft.wait(Some(Duration::from_secs(5)))
+                .applied_index_at_least(Some(resp.log_id.index), "replicated to every node of the group")
+                .await?;
+        }
+    }
+
+    for raft in nodes.into_iter().flatten() {
+        raft.shutdown().await?;
+    }
+
+    Ok(())
+}
+
+/// A request to a group that the server does not run is `Unreachable`.
+#[tokio::test]
+async fn test_tcp_group_not_found() -> anyhow::Result<()> {
+    let addrs = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+    let (rafts, addr) = start_multiplexed_node(1, addrs).await?;
+
+    let multiplexed = MultiplexedNetworkFactory::<TypeConfig>::new(move |_target, _node| addr.clone());
+
+    let mut net = multiplexed.group(2).new_client(1, &()).await;
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+    assert!(res.is_ok(), "got: {:?}", res);
+
+    let mut net = multiplexed.group(3).new_client(1, &()).await;
+    let res = net.vote(VoteRequest::new(Vote::new(1, 0), None), rpc_option()).await;
+    assert!(matches!(res, Err(RPCError::Unreachable(_))), "got: {:?}", res);
+
+    for raft in rafts {
+        raft.shutdown().await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! Bounded multi-producer, multi-consumer broadcast channel.
+//!
+//! Unlike a `watch` channel that only keeps the last value, every subscriber receives every value
+//! sent after it subscribed. A subscriber that falls more than `capacity` values behind loses the
+//! oldest ones and is notified with [`RecvError::Lagged`]. Senders never wait.
+
+use std::fmt;
+use std::future::Future;
+
+use crate::async_runtime::mpsc::SendError;
+use crate::OptionalSend;
+use crate::OptionalSync;
+
+/// A family of `broadcast` channel types provided by an [`AsyncRuntime`].
+///
+/// [`AsyncRuntime`]: crate::AsyncRuntime
+pub trait Broadcast: Sized + OptionalSend {
+    /// Type of a `broadcast` sender.
+    type Sender<T: OptionalSend + OptionalSync + Clone + 'static>: BroadcastSender<T, Receiver = Self::Receiver<T>>;
+
+    /// Type of a `broadcast` receiver.
+    type Receiver<T: OptionalSend + OptionalSync + Clone + 'static>: BroadcastReceiver<T>;
+
+    /// Creates a channel in which each receiver buffers at most `capacity` values.
+    ///
+    /// `capacity` must be greater than 0.
+    fn channel<T>(capacity: usize) -> (Self::Sender<T>, Self::Receiver<T>)
+    where T: OptionalSend + OptionalSync + Clone + 'static;
+}
+
+/// The sending half of a `broadcast` channel.
+pub trait BroadcastSender<T>: OptionalSend + OptionalSync + Clone + fmt::Debug
+where T: OptionalSend + OptionalSync + Clone
+{
+    /// The receiver type returned by [`Self::subscribe`].
+    type Receiver: BroadcastReceiver<T>;
+
+    /// Sends a value to all current receivers without waiting.
+    ///
+    /// It returns the number of receivers the value is sent to, or the value back in an error if
+    /// there is no receiver.
+    fn send(&self, msg: T) -> Result<usize, SendError<T>>;
+
+    /// Creates a new receiver that receives the values sent after this call.
+    fn subscribe(&self) -> Self::Receiver;
+
+    /// Returns the number of active receivers.
+    fn receiver_count(&self) -> usize;
+}
+
+/// The receiving half of a `broadcast` channel.
+pub trait BroadcastReceiver<T>: OptionalSend + OptionalSync
+where T: OptionalSend + OptionalSync + Clone
+{
+    /// Receives the next value.
+    ///
+    /// This method is cancel safe: if it is used in `select!` and another branch completes first,
+    /// no value is lost.
+    fn recv(&mut self) -> impl Future<Output = Result<T, RecvError>> + OptionalSend;
+}
+
+/// Error returned by [`BroadcastReceiver::recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum RecvError {
+    /// All senders have been dropped and there is no more value to receive.
+    #[error("receiving on a closed channel")]
+    Closed,
+
+    /// The receiver fell behind and the given number of the oldest values are skipped.
+    ///
+    /// The next call to [`BroadcastReceiver::recv`] returns the oldest value still buffered.
+    #[error("receiver lagged by {0} values")]
+    Lagged(u64),
+}
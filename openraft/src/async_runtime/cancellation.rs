@@ -0,0 +1,37 @@
+//! Cooperative cancellation of tasks.
+//!
+//! A token is cancelled explicitly with [`CancellationToken::cancel`], which also cancels all of
+//! the tokens derived from it with [`CancellationToken::child_token`]. Thus a task tree can be
+//! stopped by cancelling the token at its root. Dropping a token does not cancel it.
+
+use std::fmt;
+use std::future::Future;
+
+use crate::OptionalSend;
+use crate::OptionalSync;
+
+/// A token to signal cancellation to one or more tasks.
+///
+/// Cloned tokens share the same cancellation state.
+pub trait CancellationToken: Clone + Default + fmt::Debug + OptionalSend + OptionalSync + 'static {
+    /// Creates a token that is not cancelled.
+    fn new() -> Self;
+
+    /// Creates a token that is cancelled when this token is cancelled.
+    ///
+    /// Cancelling the child token does not cancel this token.
+    fn child_token(&self) -> Self;
+
+    /// Cancels this token and all of its descendants.
+    ///
+    /// It is idempotent.
+    fn cancel(&self);
+
+    /// Returns `true` if this token is cancelled.
+    fn is_cancelled(&self) -> bool;
+
+    /// Waits until this token is cancelled.
+    ///
+    /// It returns at once if the token is already cancelled. It is cancel safe.
+    fn cancelled(&self) -> impl Future<Output = ()> + OptionalSend;
+}
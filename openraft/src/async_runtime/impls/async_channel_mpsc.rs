@@ -0,0 +1,59 @@
+//! Bounded `mpsc` channel backed by [`async-channel`](https://docs.rs/async-channel).
+//!
+//! It is used by the runtimes built on top of `async-io`, i.e., `async-std` and `smol`.
+
+use std::future::Future;
+
+use crate::async_runtime::mpsc::SendError;
+use crate::async_runtime::mpsc::TryRecvError;
+use crate::async_runtime::mpsc::TrySendError;
+use crate::async_runtime::Mpsc;
+use crate::async_runtime::MpscReceiver;
+use crate::async_runtime::MpscSender;
+use crate::OptionalSend;
+
+/// Bounded `mpsc` channel backed by `async-channel`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AsyncChannelMpsc;
+
+impl Mpsc for AsyncChannelMpsc {
+    type Sender<T: OptionalSend> = async_channel::Sender<T>;
+    type Receiver<T: OptionalSend> = async_channel::Receiver<T>;
+
+    #[inline]
+    fn channel<T: OptionalSend>(buffer: usize) -> (Self::Sender<T>, Self::Receiver<T>) {
+        async_channel::bounded(buffer)
+    }
+}
+
+impl<T: OptionalSend> MpscSender<T> for async_channel::Sender<T> {
+    #[inline]
+    fn send(&self, msg: T) -> impl Future<Output = Result<(), SendError<T>>> + OptionalSend {
+        let fu = async_channel::Sender::send(self, msg);
+        async move { fu.await.map_err(|e| SendError(e.0)) }
+    }
+
+    #[inline]
+    fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        async_channel::Sender::try_send(self, msg).map_err(|e| match e {
+            async_channel::TrySendError::Full(t) => TrySendError::Full(t),
+            async_channel::TrySendError::Closed(t) => TrySendError::Closed(t),
+        })
+    }
+}
+
+impl<T: OptionalSend> MpscReceiver<T> for async_channel::Receiver<T> {
+    #[inline]
+    fn recv(&mut self) -> impl Future<Output = Option<T>> + OptionalSend {
+        let fu = async_channel::Receiver::recv(self);
+        async move { fu.await.ok() }
+    }
+
+    #[inline]
+    fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        async_channel::Receiver::try_recv(self).map_err(|e| match e {
+            async_channel::TryRecvError::Empty => TryRecvError::Empty,
+            async_channel::TryRecvError::Closed => TryRecvError::Disconnected,
+        })
+    }
+}
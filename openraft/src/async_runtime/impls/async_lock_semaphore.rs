@@ -0,0 +1,28 @@
+//! Semaphore backed by [`async-lock`](https://docs.rs/async-lock).
+//!
+//! It is used by the runtimes built on top of `async-io`, i.e., `async-std` and `smol`.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::async_runtime::Semaphore;
+use crate::OptionalSend;
+
+impl Semaphore for async_lock::Semaphore {
+    type Permit = async_lock::SemaphoreGuardArc;
+
+    #[inline]
+    fn new(permits: usize) -> Self {
+        async_lock::Semaphore::new(permits)
+    }
+
+    #[inline]
+    fn acquire_owned(self: Arc<Self>) -> impl Future<Output = Self::Permit> + OptionalSend {
+        self.acquire_arc()
+    }
+
+    #[inline]
+    fn try_acquire_owned(self: Arc<Self>) -> Option<Self::Permit> {
+        self.try_acquire_arc()
+    }
+}
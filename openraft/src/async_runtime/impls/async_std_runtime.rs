@@ -0,0 +1,220 @@
+//! [`AsyncRuntime`] implementation backed by [`async-std`](https://docs.rs/async-std).
+//!
+//! Enabled by feature flag `async-std-runtime`.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use futures::FutureExt;
+
+use crate::async_runtime::impls::panic_message;
+use crate::async_runtime::AsyncChannelMpsc;
+use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::MissedTickBehavior;
+use crate::async_runtime::SleepInterval;
+use crate::async_runtime::TokioBroadcast;
+use crate::instant::StdInstant;
+use crate::AsyncRuntime;
+use crate::OptionalSend;
+
+/// `async-std` asynchronous runtime.
+///
+/// Timers are driven by the `async-io` reactor that `async-std` itself uses,
+/// and `oneshot` channels are provided by the `futures` crate.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AsyncStdRuntime;
+
+/// The error returned by awaiting an [`AsyncStdJoinHandle`].
+///
+/// `async-std` does not report task failures through its join handle, thus the spawned future is
+/// wrapped with `catch_unwind` and a panic is converted into this error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AsyncStdJoinError {
+    #[error("task panicked: {0}")]
+    Panic(String),
+}
+
+/// The join handle of a task spawned by [`AsyncStdRuntime::spawn`].
+pub struct AsyncStdJoinHandle<T>(async_std::task::JoinHandle<Result<T, AsyncStdJoinError>>);
+
+impl<T> Future for AsyncStdJoinHandle<T> {
+    type Output = Result<T, AsyncStdJoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+/// A future that completes after a deadline, backed by [`async_io::Timer`].
+pub struct AsyncStdSleep(async_io::Timer);
+
+impl Future for AsyncStdSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map(|_instant| ())
+    }
+}
+
+/// The error returned by [`AsyncStdTimeout`] if the deadline is reached before the inner future
+/// completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("deadline has elapsed")]
+pub struct AsyncStdTimeoutError;
+
+/// Require a future to complete before a deadline.
+pub struct AsyncStdTimeout<F> {
+    future: Pin<Box<F>>,
+    timer: async_io::Timer,
+}
+
+impl<F> Future for AsyncStdTimeout<F>
+where F: Future
+{
+    type Output = Result<F::Output, AsyncStdTimeoutError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(v) = this.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(v));
+        }
+
+        match Pin::new(&mut this.timer).poll(cx) {
+            Poll::Ready(_) => Poll::Ready(Err(AsyncStdTimeoutError)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub struct AsyncStdOneshotSender<T>(futures::channel::oneshot::Sender<T>);
+
+impl AsyncRuntime for AsyncStdRuntime {
+    type JoinError = AsyncStdJoinError;
+    type JoinHandle<T: OptionalSend + 'static> = AsyncStdJoinHandle<T>;
+    type Sleep = AsyncStdSleep;
+    type Instant = StdInstant;
+    type TimeoutError = AsyncStdTimeoutError;
+    type Timeout<R, T: Future<Output = R> + OptionalSend> = AsyncStdTimeout<T>;
+    type ThreadLocalRng = rand::rngs::ThreadRng;
+    type OneshotSender<T: OptionalSend> = AsyncStdOneshotSender<T>;
+    type OneshotReceiver<T: OptionalSend> = futures::channel::oneshot::Receiver<T>;
+    type OneshotReceiverError = futures::channel::oneshot::Canceled;
+    type Mpsc = AsyncChannelMpsc;
+    type Broadcast = TokioBroadcast;
+    type CancellationToken = tokio_util::sync::CancellationToken;
+    type Semaphore = async_lock::Semaphore;
+    type Interval = SleepInterval<Self>;
+
+    #[inline]
+    fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        let fu = AssertUnwindSafe(future)
+            .catch_unwind()
+            .map(|res| res.map_err(|panic| AsyncStdJoinError::Panic(panic_message(panic))));
+
+        #[cfg(feature = "singlethreaded")]
+        {
+            AsyncStdJoinHandle(async_std::task::spawn_local(fu))
+        }
+        #[cfg(not(feature = "singlethreaded"))]
+        {
+            AsyncStdJoinHandle(async_std::task::spawn(fu))
+        }
+    }
+
+    #[inline]
+    fn spawn_blocking<F, T>(f: F) -> Self::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let f = move || std::panic::catch_unwind(AssertUnwindSafe(f));
+        AsyncStdJoinHandle(async_std::task::spawn_blocking(move || {
+            f().map_err(|panic| AsyncStdJoinError::Panic(panic_message(panic)))
+        }))
+    }
+
+    #[inline]
+    fn sleep(duration: Duration) -> Self::Sleep {
+        AsyncStdSleep(async_io::Timer::after(duration))
+    }
+
+    #[inline]
+    fn sleep_until(deadline: Self::Instant) -> Self::Sleep {
+        AsyncStdSleep(async_io::Timer::at(deadline))
+    }
+
+    #[inline]
+    fn interval_at(start: Self::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self::Interval {
+        SleepInterval::new(start, period, missed_tick_behavior)
+    }
+
+    #[inline]
+    fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F> {
+        AsyncStdTimeout {
+            future: Box::pin(future),
+            timer: async_io::Timer::after(duration),
+        }
+    }
+
+    #[inline]
+    fn timeout_at<R, F: Future<Output = R> + OptionalSend>(deadline: Self::Instant, future: F) -> Self::Timeout<R, F> {
+        AsyncStdTimeout {
+            future: Box::pin(future),
+            timer: async_io::Timer::at(deadline),
+        }
+    }
+
+    #[inline]
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        matches!(join_error, AsyncStdJoinError::Panic(_))
+    }
+
+    #[inline]
+    fn thread_rng() -> Self::ThreadLocalRng {
+        rand::thread_rng()
+    }
+
+    #[inline]
+    fn oneshot<T>() -> (Self::OneshotSender<T>, Self::OneshotReceiver<T>)
+    where T: OptionalSend {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        (AsyncStdOneshotSender(tx), rx)
+    }
+}
+
+impl<T: OptionalSend> AsyncOneshotSendExt<T> for AsyncStdOneshotSender<T> {
+    #[inline]
+    fn send(self, t: T) -> Result<(), T> {
+        self.0.send(t)
+    }
+}
+
+impl<T> Debug for AsyncStdOneshotSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AsyncStdOneshotSender").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_runtime::AsyncStdRuntime;
+    use crate::testing::runtime::Suite;
+
+    // `spawn()` is `spawn_local()` with singlethreaded enabled, which requires a local executor.
+    #[cfg(not(feature = "singlethreaded"))]
+    #[test]
+    fn test_async_std_rt() {
+        async_std::task::block_on(Suite::<AsyncStdRuntime>::test_all());
+    }
+}
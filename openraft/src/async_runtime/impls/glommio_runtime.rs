@@ -0,0 +1,228 @@
+//! [`AsyncRuntime`] implementation backed by [`glommio`](https://docs.rs/glommio).
+//!
+//! Enabled by feature flag `glommio-runtime`, which also enables `singlethreaded`.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use futures::FutureExt;
+use glommio::timer::Timer;
+
+use crate::async_runtime::impls::panic_message;
+use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::MissedTickBehavior;
+use crate::async_runtime::SleepInterval;
+use crate::async_runtime::TokioBroadcast;
+use crate::async_runtime::TokioMpsc;
+use crate::instant::StdInstant;
+use crate::AsyncRuntime;
+use crate::OptionalSend;
+
+/// `glommio` thread-per-core asynchronous runtime, built on top of `io_uring`. It runs only on
+/// Linux.
+///
+/// Tasks are spawned onto the executor of the current thread and never move to another thread,
+/// thus the futures are not required to be `Send`. `glommio` provides only single-producer
+/// channels, the `mpsc`, `broadcast` and `oneshot` channels are provided by `tokio` and `futures`,
+/// which do not depend on an executor.
+///
+/// `RaftCore` must be run inside a `glommio` executor, e.g.:
+///
+/// ```ignore
+/// glommio::LocalExecutorBuilder::default().make()?.run(fut);
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GlommioRuntime;
+
+/// The error returned by awaiting a [`GlommioJoinHandle`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GlommioJoinError {
+    #[error("task panicked: {0}")]
+    Panic(String),
+
+    #[error("task was cancelled")]
+    Cancelled,
+}
+
+/// The join handle of a task spawned by [`GlommioRuntime::spawn`].
+///
+/// The task is detached: it keeps running when the handle is dropped.
+pub struct GlommioJoinHandle<T>(glommio::task::JoinHandle<Result<T, GlommioJoinError>>);
+
+impl<T> Future for GlommioJoinHandle<T> {
+    type Output = Result<T, GlommioJoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map(|res| res.unwrap_or(Err(GlommioJoinError::Cancelled)))
+    }
+}
+
+/// A future that completes after a duration.
+///
+/// A `glommio` [`Timer`] resolves to the instant it fired, which is discarded.
+pub struct GlommioSleep(Timer);
+
+impl Future for GlommioSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map(|_fired_at| ())
+    }
+}
+
+/// The error returned by [`GlommioTimeout`] if the deadline is reached before the inner future
+/// completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("deadline has elapsed")]
+pub struct GlommioTimeoutError;
+
+/// Require a future to complete before a deadline.
+pub struct GlommioTimeout<F> {
+    future: Pin<Box<F>>,
+    sleep: GlommioSleep,
+}
+
+impl<F> Future for GlommioTimeout<F>
+where F: Future
+{
+    type Output = Result<F::Output, GlommioTimeoutError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(v) = this.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(v));
+        }
+
+        match Pin::new(&mut this.sleep).poll(cx) {
+            Poll::Ready(_) => Poll::Ready(Err(GlommioTimeoutError)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub struct GlommioOneshotSender<T>(futures::channel::oneshot::Sender<T>);
+
+impl AsyncRuntime for GlommioRuntime {
+    type JoinError = GlommioJoinError;
+    type JoinHandle<T: OptionalSend + 'static> = GlommioJoinHandle<T>;
+    type Sleep = GlommioSleep;
+    type Instant = StdInstant;
+    type TimeoutError = GlommioTimeoutError;
+    type Timeout<R, T: Future<Output = R> + OptionalSend> = GlommioTimeout<T>;
+    type ThreadLocalRng = rand::rngs::ThreadRng;
+    type OneshotSender<T: OptionalSend> = GlommioOneshotSender<T>;
+    type OneshotReceiver<T: OptionalSend> = futures::channel::oneshot::Receiver<T>;
+    type OneshotReceiverError = futures::channel::oneshot::Canceled;
+    type Mpsc = TokioMpsc;
+    type Broadcast = TokioBroadcast;
+    type CancellationToken = tokio_util::sync::CancellationToken;
+    type Semaphore = tokio::sync::Semaphore;
+    type Interval = SleepInterval<Self>;
+
+    #[inline]
+    fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        let fu = AssertUnwindSafe(future)
+            .catch_unwind()
+            .map(|res| res.map_err(|panic| GlommioJoinError::Panic(panic_message(panic))));
+
+        GlommioJoinHandle(glommio::spawn_local(fu).detach())
+    }
+
+    /// The function is run on the blocking thread pool of the current `glommio` executor.
+    #[inline]
+    fn spawn_blocking<F, T>(f: F) -> Self::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let blocking = glommio::executor().spawn_blocking(move || {
+            std::panic::catch_unwind(AssertUnwindSafe(f)).map_err(|panic| GlommioJoinError::Panic(panic_message(panic)))
+        });
+
+        GlommioJoinHandle(glommio::spawn_local(blocking).detach())
+    }
+
+    #[inline]
+    fn sleep(duration: Duration) -> Self::Sleep {
+        GlommioSleep(Timer::new(duration))
+    }
+
+    #[inline]
+    fn sleep_until(deadline: Self::Instant) -> Self::Sleep {
+        GlommioSleep(Timer::new(deadline.saturating_duration_since(StdInstant::now())))
+    }
+
+    #[inline]
+    fn interval_at(start: Self::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self::Interval {
+        SleepInterval::new(start, period, missed_tick_behavior)
+    }
+
+    #[inline]
+    fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F> {
+        GlommioTimeout {
+            future: Box::pin(future),
+            sleep: Self::sleep(duration),
+        }
+    }
+
+    #[inline]
+    fn timeout_at<R, F: Future<Output = R> + OptionalSend>(deadline: Self::Instant, future: F) -> Self::Timeout<R, F> {
+        GlommioTimeout {
+            future: Box::pin(future),
+            sleep: Self::sleep_until(deadline),
+        }
+    }
+
+    #[inline]
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        matches!(join_error, GlommioJoinError::Panic(_))
+    }
+
+    #[inline]
+    fn thread_rng() -> Self::ThreadLocalRng {
+        rand::thread_rng()
+    }
+
+    #[inline]
+    fn oneshot<T>() -> (Self::OneshotSender<T>, Self::OneshotReceiver<T>)
+    where T: OptionalSend {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        (GlommioOneshotSender(tx), rx)
+    }
+}
+
+impl<T: OptionalSend> AsyncOneshotSendExt<T> for GlommioOneshotSender<T> {
+    #[inline]
+    fn send(self, t: T) -> Result<(), T> {
+        self.0.send(t)
+    }
+}
+
+impl<T> Debug for GlommioOneshotSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GlommioOneshotSender").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_runtime::GlommioRuntime;
+    use crate::testing::runtime::Suite;
+
+    #[test]
+    fn test_glommio_rt() {
+        let ex = glommio::LocalExecutorBuilder::default().make().expect("Failed building the executor");
+        ex.run(Suite::<GlommioRuntime>::test_all());
+    }
+}
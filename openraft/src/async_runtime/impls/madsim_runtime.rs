@@ -0,0 +1,256 @@
+//! [`AsyncRuntime`] implementation backed by [`madsim`](https://docs.rs/madsim).
+//!
+//! Enabled by feature flag `madsim-runtime`.
+//!
+//! When built with `RUSTFLAGS="--cfg madsim"`, tasks, timers and random numbers are driven by the
+//! deterministic simulator of `madsim`; otherwise `madsim` delegates to `tokio`.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use futures::FutureExt;
+
+use crate::async_runtime::impls::panic_message;
+use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::MissedTickBehavior;
+use crate::async_runtime::SleepInterval;
+use crate::async_runtime::TokioBroadcast;
+use crate::async_runtime::TokioMpsc;
+use crate::AsyncRuntime;
+use crate::OptionalSend;
+
+#[cfg(madsim)]
+type RtSleep = madsim::time::Sleep;
+#[cfg(not(madsim))]
+type RtSleep = tokio::time::Sleep;
+
+/// `madsim` deterministic simulation runtime.
+///
+/// Under `--cfg madsim`, the whole cluster runs in virtual time on a single simulated executor,
+/// and [`AsyncRuntime::thread_rng`] returns the global random number generator of the simulation,
+/// so that a run, including election timeouts, is reproducible from its seed.
+///
+/// `oneshot` channels are provided by the `futures` crate, which does not depend on an executor.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MadsimRuntime;
+
+/// The error returned by awaiting a [`MadsimJoinHandle`].
+///
+/// The simulator does not report task panics through its join handle, thus the spawned future is
+/// wrapped with `catch_unwind` and a panic is converted into this error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MadsimJoinError {
+    #[error("task panicked: {0}")]
+    Panic(String),
+
+    #[error("task was cancelled")]
+    Cancelled,
+}
+
+/// The join handle of a task spawned by [`MadsimRuntime::spawn`].
+pub struct MadsimJoinHandle<T>(madsim::task::JoinHandle<Result<T, MadsimJoinError>>);
+
+impl<T> Future for MadsimJoinHandle<T> {
+    type Output = Result<T, MadsimJoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map(|res| res.unwrap_or(Err(MadsimJoinError::Cancelled)))
+    }
+}
+
+/// A future that completes after a deadline, on virtual time under `--cfg madsim`.
+pub struct MadsimSleep(Pin<Box<RtSleep>>);
+
+impl Future for MadsimSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.as_mut().poll(cx)
+    }
+}
+
+/// The error returned by [`MadsimTimeout`] if the deadline is reached before the inner future
+/// completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("deadline has elapsed")]
+pub struct MadsimTimeoutError;
+
+/// Require a future to complete before a deadline.
+pub struct MadsimTimeout<F> {
+    future: Pin<Box<F>>,
+    sleep: MadsimSleep,
+}
+
+impl<F> Future for MadsimTimeout<F>
+where F: Future
+{
+    type Output = Result<F::Output, MadsimTimeoutError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(v) = this.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(v));
+        }
+
+        match Pin::new(&mut this.sleep).poll(cx) {
+            Poll::Ready(_) => Poll::Ready(Err(MadsimTimeoutError)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub struct MadsimOneshotSender<T>(futures::channel::oneshot::Sender<T>);
+
+impl AsyncRuntime for MadsimRuntime {
+    type JoinError = MadsimJoinError;
+    type JoinHandle<T: OptionalSend + 'static> = MadsimJoinHandle<T>;
+    type Sleep = MadsimSleep;
+    type Instant = madsim::time::Instant;
+    type TimeoutError = MadsimTimeoutError;
+    type Timeout<R, T: Future<Output = R> + OptionalSend> = MadsimTimeout<T>;
+
+    #[cfg(madsim)]
+    type ThreadLocalRng = madsim::rand::GlobalRng;
+    #[cfg(not(madsim))]
+    type ThreadLocalRng = rand::rngs::ThreadRng;
+
+    type OneshotSender<T: OptionalSend> = MadsimOneshotSender<T>;
+    type OneshotReceiver<T: OptionalSend> = futures::channel::oneshot::Receiver<T>;
+    type OneshotReceiverError = futures::channel::oneshot::Canceled;
+    type Mpsc = TokioMpsc;
+    type Broadcast = TokioBroadcast;
+    type CancellationToken = tokio_util::sync::CancellationToken;
+    type Semaphore = tokio::sync::Semaphore;
+    type Interval = SleepInterval<Self>;
+
+    #[inline]
+    fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        let fu = AssertUnwindSafe(future)
+            .catch_unwind()
+            .map(|res| res.map_err(|panic| MadsimJoinError::Panic(panic_message(panic))));
+
+        #[cfg(feature = "singlethreaded")]
+        {
+            MadsimJoinHandle(madsim::task::spawn_local(fu))
+        }
+        #[cfg(not(feature = "singlethreaded"))]
+        {
+            MadsimJoinHandle(madsim::task::spawn(fu))
+        }
+    }
+
+    #[inline]
+    fn spawn_blocking<F, T>(f: F) -> Self::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        MadsimJoinHandle(madsim::task::spawn_blocking(move || {
+            std::panic::catch_unwind(AssertUnwindSafe(f)).map_err(|panic| MadsimJoinError::Panic(panic_message(panic)))
+        }))
+    }
+
+    #[inline]
+    fn sleep(duration: Duration) -> Self::Sleep {
+        MadsimSleep(Box::pin(madsim::time::sleep(duration)))
+    }
+
+    #[inline]
+    fn sleep_until(deadline: Self::Instant) -> Self::Sleep {
+        MadsimSleep(Box::pin(madsim::time::sleep_until(deadline)))
+    }
+
+    #[inline]
+    fn interval_at(start: Self::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self::Interval {
+        SleepInterval::new(start, period, missed_tick_behavior)
+    }
+
+    #[inline]
+    fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F> {
+        MadsimTimeout {
+            future: Box::pin(future),
+            sleep: Self::sleep(duration),
+        }
+    }
+
+    #[inline]
+    fn timeout_at<R, F: Future<Output = R> + OptionalSend>(deadline: Self::Instant, future: F) -> Self::Timeout<R, F> {
+        MadsimTimeout {
+            future: Box::pin(future),
+            sleep: Self::sleep_until(deadline),
+        }
+    }
+
+    #[inline]
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        matches!(join_error, MadsimJoinError::Panic(_))
+    }
+
+    #[inline]
+    fn thread_rng() -> Self::ThreadLocalRng {
+        #[cfg(madsim)]
+        {
+            madsim::rand::thread_rng()
+        }
+        #[cfg(not(madsim))]
+        {
+            rand::thread_rng()
+        }
+    }
+
+    #[inline]
+    fn oneshot<T>() -> (Self::OneshotSender<T>, Self::OneshotReceiver<T>)
+    where T: OptionalSend {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        (MadsimOneshotSender(tx), rx)
+    }
+}
+
+impl<T: OptionalSend> AsyncOneshotSendExt<T> for MadsimOneshotSender<T> {
+    #[inline]
+    fn send(self, t: T) -> Result<(), T> {
+        self.0.send(t)
+    }
+}
+
+impl<T> Debug for MadsimOneshotSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MadsimOneshotSender").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_runtime::MadsimRuntime;
+    use crate::testing::runtime::Suite;
+
+    #[cfg(madsim)]
+    #[test]
+    fn test_madsim_rt() {
+        let rt = madsim::runtime::Runtime::new();
+        rt.block_on(Suite::<MadsimRuntime>::test_all());
+    }
+
+    // Without `--cfg madsim`, `madsim` delegates to `tokio`, whose `spawn()` is `spawn_local()`
+    // with singlethreaded enabled, which requires a `LocalSet`.
+    #[cfg(all(not(madsim), not(feature = "singlethreaded")))]
+    #[test]
+    fn test_madsim_rt() {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("Failed building the runtime");
+        rt.block_on(Suite::<MadsimRuntime>::test_all());
+    }
+}
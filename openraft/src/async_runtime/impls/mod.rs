@@ -0,0 +1,32 @@
+//! Built-in [`AsyncRuntime`](crate::AsyncRuntime) implementations.
+
+#[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+pub(crate) mod async_channel_mpsc;
+#[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+pub(crate) mod async_lock_semaphore;
+#[cfg(feature = "async-std-runtime")] pub(crate) mod async_std_runtime;
+#[cfg(feature = "glommio-runtime")] pub(crate) mod glommio_runtime;
+#[cfg(feature = "madsim-runtime")] pub(crate) mod madsim_runtime;
+#[cfg(feature = "monoio-runtime")] pub(crate) mod monoio_runtime;
+#[cfg(feature = "smol-runtime")] pub(crate) mod smol_runtime;
+pub(crate) mod tokio_runtime;
+#[cfg(feature = "wasm-runtime")] pub(crate) mod wasm_runtime;
+
+/// Extract the message from the payload of a panic caught by `catch_unwind`.
+#[cfg(any(
+    feature = "async-std-runtime",
+    feature = "glommio-runtime",
+    feature = "madsim-runtime",
+    feature = "monoio-runtime",
+    feature = "smol-runtime",
+    feature = "wasm-runtime"
+))]
+pub(crate) fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
@@ -0,0 +1,186 @@
+//! [`AsyncRuntime`] implementation backed by [`monoio`](https://docs.rs/monoio).
+//!
+//! Enabled by feature flag `monoio-runtime`, which also enables `singlethreaded`.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use futures::FutureExt;
+
+use crate::async_runtime::impls::panic_message;
+use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::MissedTickBehavior;
+use crate::async_runtime::SleepInterval;
+use crate::async_runtime::TokioBroadcast;
+use crate::async_runtime::TokioMpsc;
+use crate::instant::MonoioInstant;
+use crate::AsyncRuntime;
+use crate::OptionalSend;
+
+/// `monoio` thread-per-core asynchronous runtime.
+///
+/// Tasks are spawned onto the executor of the current thread and never move to another thread,
+/// thus neither the futures nor the `oneshot` channels, which are provided by
+/// [`local-sync`](https://docs.rs/local-sync), are required to be `Send`.
+///
+/// The `monoio` runtime that drives `RaftCore` must be built with the timer enabled, e.g.:
+///
+/// ```ignore
+/// monoio::RuntimeBuilder::<monoio::FusionDriver>::new().enable_timer().build()?.block_on(fut);
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MonoioRuntime;
+
+/// The error returned by awaiting a [`MonoioJoinHandle`].
+///
+/// `monoio` re-raises the panic of a task on the executor thread, thus the spawned future is
+/// wrapped with `catch_unwind` and a panic is converted into this error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MonoioJoinError {
+    #[error("task panicked: {0}")]
+    Panic(String),
+}
+
+/// The join handle of a task spawned by [`MonoioRuntime::spawn`].
+pub struct MonoioJoinHandle<T>(monoio::task::JoinHandle<Result<T, MonoioJoinError>>);
+
+impl<T> Future for MonoioJoinHandle<T> {
+    type Output = Result<T, MonoioJoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+pub struct MonoioOneshotSender<T>(local_sync::oneshot::Sender<T>);
+
+impl AsyncRuntime for MonoioRuntime {
+    type JoinError = MonoioJoinError;
+    type JoinHandle<T: OptionalSend + 'static> = MonoioJoinHandle<T>;
+    type Sleep = monoio::time::Sleep;
+    type Instant = MonoioInstant;
+    type TimeoutError = monoio::time::error::Elapsed;
+    type Timeout<R, T: Future<Output = R> + OptionalSend> = monoio::time::Timeout<T>;
+    type ThreadLocalRng = rand::rngs::ThreadRng;
+    type OneshotSender<T: OptionalSend> = MonoioOneshotSender<T>;
+    type OneshotReceiver<T: OptionalSend> = local_sync::oneshot::Receiver<T>;
+    type OneshotReceiverError = local_sync::oneshot::error::RecvError;
+    type Mpsc = TokioMpsc;
+    type Broadcast = TokioBroadcast;
+    type CancellationToken = tokio_util::sync::CancellationToken;
+    type Semaphore = tokio::sync::Semaphore;
+    type Interval = SleepInterval<Self>;
+
+    #[inline]
+    fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        let fu = AssertUnwindSafe(future)
+            .catch_unwind()
+            .map(|res| res.map_err(|panic| MonoioJoinError::Panic(panic_message(panic))));
+
+        MonoioJoinHandle(monoio::spawn(fu))
+    }
+
+    /// `monoio` runs blocking functions only on a thread pool attached to the runtime, which is not
+    /// always configured, thus the function is run on a newly spawned thread. The output is sent
+    /// back through a channel, which wakes the runtime with the `sync` feature of `monoio`.
+    #[inline]
+    fn spawn_blocking<F, T>(f: F) -> Self::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        std::thread::spawn(move || {
+            let res = std::panic::catch_unwind(AssertUnwindSafe(f))
+                .map_err(|panic| MonoioJoinError::Panic(panic_message(panic)));
+            // The receiver may have been dropped, i.e., the task is detached.
+            let _ = tx.send(res);
+        });
+
+        MonoioJoinHandle(monoio::spawn(async move {
+            rx.await
+                .unwrap_or_else(|_canceled| Err(MonoioJoinError::Panic("blocking thread exited".to_string())))
+        }))
+    }
+
+    #[inline]
+    fn sleep(duration: Duration) -> Self::Sleep {
+        monoio::time::sleep(duration)
+    }
+
+    #[inline]
+    fn sleep_until(deadline: Self::Instant) -> Self::Sleep {
+        monoio::time::sleep_until(deadline)
+    }
+
+    #[inline]
+    fn interval_at(start: Self::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self::Interval {
+        SleepInterval::new(start, period, missed_tick_behavior)
+    }
+
+    #[inline]
+    fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F> {
+        monoio::time::timeout(duration, future)
+    }
+
+    #[inline]
+    fn timeout_at<R, F: Future<Output = R> + OptionalSend>(deadline: Self::Instant, future: F) -> Self::Timeout<R, F> {
+        monoio::time::timeout_at(deadline, future)
+    }
+
+    #[inline]
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        matches!(join_error, MonoioJoinError::Panic(_))
+    }
+
+    #[inline]
+    fn thread_rng() -> Self::ThreadLocalRng {
+        rand::thread_rng()
+    }
+
+    #[inline]
+    fn oneshot<T>() -> (Self::OneshotSender<T>, Self::OneshotReceiver<T>)
+    where T: OptionalSend {
+        let (tx, rx) = local_sync::oneshot::channel();
+        (MonoioOneshotSender(tx), rx)
+    }
+}
+
+impl<T: OptionalSend> AsyncOneshotSendExt<T> for MonoioOneshotSender<T> {
+    #[inline]
+    fn send(self, t: T) -> Result<(), T> {
+        self.0.send(t)
+    }
+}
+
+impl<T> Debug for MonoioOneshotSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MonoioOneshotSender").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_runtime::MonoioRuntime;
+    use crate::testing::runtime::Suite;
+
+    #[test]
+    fn test_monoio_rt() {
+        let mut rt = monoio::RuntimeBuilder::<monoio::LegacyDriver>::new()
+            .enable_timer()
+            .build()
+            .expect("Failed building the runtime");
+        rt.block_on(Suite::<MonoioRuntime>::test_all());
+    }
+}
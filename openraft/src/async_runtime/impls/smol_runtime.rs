@@ -0,0 +1,271 @@
+//! [`AsyncRuntime`] implementation backed by [`smol`](https://docs.rs/smol).
+//!
+//! Enabled by feature flag `smol-runtime`.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use futures::FutureExt;
+use futures::Stream;
+
+use crate::async_runtime::impls::panic_message;
+use crate::async_runtime::AsyncChannelMpsc;
+use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::MissedTickBehavior;
+use crate::async_runtime::SleepInterval;
+use crate::async_runtime::TokioBroadcast;
+use crate::instant::StdInstant;
+use crate::AsyncRuntime;
+use crate::OptionalSend;
+
+#[cfg(feature = "singlethreaded")]
+thread_local! {
+    /// Executor for tasks spawned with `singlethreaded` enabled, driven by [`SmolRuntime::block_on`].
+    static LOCAL_EXECUTOR: smol::LocalExecutor<'static> = smol::LocalExecutor::new();
+}
+
+/// `smol` asynchronous runtime.
+///
+/// Timers are provided by [`smol::Timer`] and `oneshot` channels are built on
+/// [`async-channel`](https://docs.rs/async-channel).
+///
+/// Tasks are spawned onto the global `smol` executor, or, with feature flag `singlethreaded`
+/// enabled, onto a thread-local executor that has to be driven by [`SmolRuntime::block_on`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SmolRuntime;
+
+impl SmolRuntime {
+    /// Run a future to completion on the current thread.
+    ///
+    /// With feature flag `singlethreaded` enabled, it also runs the tasks spawned by
+    /// [`AsyncRuntime::spawn`] on this thread.
+    pub fn block_on<T>(future: impl Future<Output = T>) -> T {
+        #[cfg(feature = "singlethreaded")]
+        {
+            LOCAL_EXECUTOR.with(|ex| smol::block_on(ex.run(future)))
+        }
+        #[cfg(not(feature = "singlethreaded"))]
+        {
+            smol::block_on(future)
+        }
+    }
+}
+
+/// The error returned by awaiting a [`SmolJoinHandle`].
+///
+/// The spawned future is wrapped with `catch_unwind` and a panic is converted into this error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SmolJoinError {
+    #[error("task panicked: {0}")]
+    Panic(String),
+}
+
+/// The join handle of a task spawned by [`SmolRuntime::spawn`].
+///
+/// Unlike [`smol::Task`], dropping the handle detaches the task instead of canceling it.
+pub struct SmolJoinHandle<T>(Option<smol::Task<Result<T, SmolJoinError>>>);
+
+impl<T> Future for SmolJoinHandle<T> {
+    type Output = Result<T, SmolJoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let task = self.0.as_mut().expect("SmolJoinHandle polled after completion");
+        Pin::new(task).poll(cx)
+    }
+}
+
+impl<T> Drop for SmolJoinHandle<T> {
+    fn drop(&mut self) {
+        if let Some(task) = self.0.take() {
+            task.detach();
+        }
+    }
+}
+
+/// A future that completes after a deadline, backed by [`smol::Timer`].
+pub struct SmolSleep(smol::Timer);
+
+impl Future for SmolSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map(|_instant| ())
+    }
+}
+
+/// The error returned by [`SmolTimeout`] if the deadline is reached before the inner future
+/// completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("deadline has elapsed")]
+pub struct SmolTimeoutError;
+
+/// Require a future to complete before a deadline.
+pub struct SmolTimeout<F> {
+    future: Pin<Box<F>>,
+    timer: smol::Timer,
+}
+
+impl<F> Future for SmolTimeout<F>
+where F: Future
+{
+    type Output = Result<F::Output, SmolTimeoutError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(v) = this.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(v));
+        }
+
+        match Pin::new(&mut this.timer).poll(cx) {
+            Poll::Ready(_) => Poll::Ready(Err(SmolTimeoutError)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub struct SmolOneshotSender<T>(smol::channel::Sender<T>);
+
+/// The error returned by [`SmolOneshotReceiver`] if the sender is dropped without sending a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("oneshot sender dropped")]
+pub struct SmolOneshotRecvError;
+
+/// The receiving half of a `oneshot` channel created by [`SmolRuntime::oneshot`].
+pub struct SmolOneshotReceiver<T>(Pin<Box<smol::channel::Receiver<T>>>);
+
+impl<T> Future for SmolOneshotReceiver<T> {
+    type Output = Result<T, SmolOneshotRecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.as_mut().poll_next(cx).map(|v| v.ok_or(SmolOneshotRecvError))
+    }
+}
+
+impl AsyncRuntime for SmolRuntime {
+    type JoinError = SmolJoinError;
+    type JoinHandle<T: OptionalSend + 'static> = SmolJoinHandle<T>;
+    type Sleep = SmolSleep;
+    type Instant = StdInstant;
+    type TimeoutError = SmolTimeoutError;
+    type Timeout<R, T: Future<Output = R> + OptionalSend> = SmolTimeout<T>;
+    type ThreadLocalRng = rand::rngs::ThreadRng;
+    type OneshotSender<T: OptionalSend> = SmolOneshotSender<T>;
+    type OneshotReceiver<T: OptionalSend> = SmolOneshotReceiver<T>;
+    type OneshotReceiverError = SmolOneshotRecvError;
+    type Mpsc = AsyncChannelMpsc;
+    type Broadcast = TokioBroadcast;
+    type CancellationToken = tokio_util::sync::CancellationToken;
+    type Semaphore = async_lock::Semaphore;
+    type Interval = SleepInterval<Self>;
+
+    #[inline]
+    fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        let fu = AssertUnwindSafe(future)
+            .catch_unwind()
+            .map(|res| res.map_err(|panic| SmolJoinError::Panic(panic_message(panic))));
+
+        #[cfg(feature = "singlethreaded")]
+        {
+            SmolJoinHandle(Some(LOCAL_EXECUTOR.with(|ex| ex.spawn(fu))))
+        }
+        #[cfg(not(feature = "singlethreaded"))]
+        {
+            SmolJoinHandle(Some(smol::spawn(fu)))
+        }
+    }
+
+    #[inline]
+    fn spawn_blocking<F, T>(f: F) -> Self::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let task = smol::unblock(move || {
+            std::panic::catch_unwind(AssertUnwindSafe(f)).map_err(|panic| SmolJoinError::Panic(panic_message(panic)))
+        });
+        SmolJoinHandle(Some(task))
+    }
+
+    #[inline]
+    fn sleep(duration: Duration) -> Self::Sleep {
+        SmolSleep(smol::Timer::after(duration))
+    }
+
+    #[inline]
+    fn sleep_until(deadline: Self::Instant) -> Self::Sleep {
+        SmolSleep(smol::Timer::at(deadline))
+    }
+
+    #[inline]
+    fn interval_at(start: Self::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self::Interval {
+        SleepInterval::new(start, period, missed_tick_behavior)
+    }
+
+    #[inline]
+    fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F> {
+        SmolTimeout {
+            future: Box::pin(future),
+            timer: smol::Timer::after(duration),
+        }
+    }
+
+    #[inline]
+    fn timeout_at<R, F: Future<Output = R> + OptionalSend>(deadline: Self::Instant, future: F) -> Self::Timeout<R, F> {
+        SmolTimeout {
+            future: Box::pin(future),
+            timer: smol::Timer::at(deadline),
+        }
+    }
+
+    #[inline]
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        matches!(join_error, SmolJoinError::Panic(_))
+    }
+
+    #[inline]
+    fn thread_rng() -> Self::ThreadLocalRng {
+        rand::thread_rng()
+    }
+
+    #[inline]
+    fn oneshot<T>() -> (Self::OneshotSender<T>, Self::OneshotReceiver<T>)
+    where T: OptionalSend {
+        let (tx, rx) = smol::channel::bounded(1);
+        (SmolOneshotSender(tx), SmolOneshotReceiver(Box::pin(rx)))
+    }
+}
+
+impl<T: OptionalSend> AsyncOneshotSendExt<T> for SmolOneshotSender<T> {
+    #[inline]
+    fn send(self, t: T) -> Result<(), T> {
+        self.0.try_send(t).map_err(|e| e.into_inner())
+    }
+}
+
+impl<T> Debug for SmolOneshotSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SmolOneshotSender").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_runtime::SmolRuntime;
+    use crate::testing::runtime::Suite;
+
+    #[test]
+    fn test_smol_rt() {
+        SmolRuntime::block_on(Suite::<SmolRuntime>::test_all());
+    }
+}
@@ -0,0 +1,333 @@
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::async_runtime::broadcast;
+use crate::async_runtime::mpsc::SendError;
+use crate::async_runtime::mpsc::TryRecvError;
+use crate::async_runtime::mpsc::TrySendError;
+use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::Broadcast;
+use crate::async_runtime::BroadcastReceiver;
+use crate::async_runtime::BroadcastSender;
+use crate::async_runtime::CancellationToken;
+use crate::async_runtime::Interval;
+use crate::async_runtime::MissedTickBehavior;
+use crate::async_runtime::Mpsc;
+use crate::async_runtime::MpscReceiver;
+use crate::async_runtime::MpscSender;
+use crate::async_runtime::Semaphore;
+use crate::AsyncRuntime;
+use crate::OptionalSend;
+use crate::OptionalSync;
+use crate::TokioInstant;
+
+/// `Tokio` is the default asynchronous executor.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TokioRuntime;
+
+pub struct TokioOneShotSender<T: OptionalSend>(pub tokio::sync::oneshot::Sender<T>);
+
+impl AsyncRuntime for TokioRuntime {
+    type JoinError = tokio::task::JoinError;
+    type JoinHandle<T: OptionalSend + 'static> = tokio::task::JoinHandle<T>;
+    type Sleep = tokio::time::Sleep;
+    type Instant = TokioInstant;
+    type TimeoutError = tokio::time::error::Elapsed;
+    type Timeout<R, T: Future<Output = R> + OptionalSend> = tokio::time::Timeout<T>;
+    type ThreadLocalRng = rand::rngs::ThreadRng;
+    type OneshotSender<T: OptionalSend> = TokioOneShotSender<T>;
+    type OneshotReceiver<T: OptionalSend> = tokio::sync::oneshot::Receiver<T>;
+    type OneshotReceiverError = tokio::sync::oneshot::error::RecvError;
+    type Mpsc = TokioMpsc;
+    type Broadcast = TokioBroadcast;
+    type CancellationToken = tokio_util::sync::CancellationToken;
+    type Semaphore = tokio::sync::Semaphore;
+    type Interval = tokio::time::Interval;
+
+    #[inline]
+    fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        #[cfg(feature = "singlethreaded")]
+        {
+            tokio::task::spawn_local(future)
+        }
+        #[cfg(not(feature = "singlethreaded"))]
+        {
+            tokio::task::spawn(future)
+        }
+    }
+
+    /// With feature flag `tokio-tracing` enabled and built with `RUSTFLAGS="--cfg tokio_unstable"`,
+    /// the task is spawned with a name by [`tokio::task::Builder`].
+    #[inline]
+    fn spawn_named<T>(name: &str, future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        #[cfg(all(feature = "tokio-tracing", tokio_unstable))]
+        {
+            let builder = tokio::task::Builder::new().name(name);
+
+            #[cfg(feature = "singlethreaded")]
+            let res = builder.spawn_local(future);
+            #[cfg(not(feature = "singlethreaded"))]
+            let res = builder.spawn(future);
+
+            res.expect("failed to spawn a named task")
+        }
+        #[cfg(not(all(feature = "tokio-tracing", tokio_unstable)))]
+        {
+            let _ = name;
+            Self::spawn(future)
+        }
+    }
+
+    #[inline]
+    fn spawn_blocking<F, T>(f: F) -> Self::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        tokio::task::spawn_blocking(f)
+    }
+
+    #[inline]
+    fn sleep(duration: Duration) -> Self::Sleep {
+        tokio::time::sleep(duration)
+    }
+
+    #[inline]
+    fn sleep_until(deadline: Self::Instant) -> Self::Sleep {
+        tokio::time::sleep_until(deadline)
+    }
+
+    #[inline]
+    fn interval_at(start: Self::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self::Interval {
+        let mut interval = tokio::time::interval_at(start, period);
+        interval.set_missed_tick_behavior(match missed_tick_behavior {
+            MissedTickBehavior::Burst => tokio::time::MissedTickBehavior::Burst,
+            MissedTickBehavior::Delay => tokio::time::MissedTickBehavior::Delay,
+            MissedTickBehavior::Skip => tokio::time::MissedTickBehavior::Skip,
+        });
+        interval
+    }
+
+    #[inline]
+    fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F> {
+        tokio::time::timeout(duration, future)
+    }
+
+    #[inline]
+    fn timeout_at<R, F: Future<Output = R> + OptionalSend>(deadline: Self::Instant, future: F) -> Self::Timeout<R, F> {
+        tokio::time::timeout_at(deadline, future)
+    }
+
+    #[inline]
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        join_error.is_panic()
+    }
+
+    #[inline]
+    fn thread_rng() -> Self::ThreadLocalRng {
+        rand::thread_rng()
+    }
+
+    #[inline]
+    fn oneshot<T>() -> (Self::OneshotSender<T>, Self::OneshotReceiver<T>)
+    where T: OptionalSend {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        (TokioOneShotSender(tx), rx)
+    }
+}
+
+impl<T: OptionalSend> AsyncOneshotSendExt<T> for TokioOneShotSender<T> {
+    #[inline]
+    fn send(self, t: T) -> Result<(), T> {
+        self.0.send(t)
+    }
+}
+
+impl<T: OptionalSend> Debug for TokioOneShotSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TokioSendWrapper").finish()
+    }
+}
+
+/// Bounded `mpsc` channel backed by [`tokio::sync::mpsc`].
+///
+/// `tokio::sync` does not depend on the `tokio` executor, thus it is also used by other runtimes.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TokioMpsc;
+
+impl Mpsc for TokioMpsc {
+    type Sender<T: OptionalSend> = tokio::sync::mpsc::Sender<T>;
+    type Receiver<T: OptionalSend> = tokio::sync::mpsc::Receiver<T>;
+
+    #[inline]
+    fn channel<T: OptionalSend>(buffer: usize) -> (Self::Sender<T>, Self::Receiver<T>) {
+        tokio::sync::mpsc::channel(buffer)
+    }
+}
+
+impl<T: OptionalSend> MpscSender<T> for tokio::sync::mpsc::Sender<T> {
+    #[inline]
+    fn send(&self, msg: T) -> impl Future<Output = Result<(), SendError<T>>> + OptionalSend {
+        let fu = tokio::sync::mpsc::Sender::send(self, msg);
+        async move { fu.await.map_err(|e| SendError(e.0)) }
+    }
+
+    #[inline]
+    fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        tokio::sync::mpsc::Sender::try_send(self, msg).map_err(|e| match e {
+            tokio::sync::mpsc::error::TrySendError::Full(t) => TrySendError::Full(t),
+            tokio::sync::mpsc::error::TrySendError::Closed(t) => TrySendError::Closed(t),
+        })
+    }
+}
+
+impl<T: OptionalSend> MpscReceiver<T> for tokio::sync::mpsc::Receiver<T> {
+    #[inline]
+    fn recv(&mut self) -> impl Future<Output = Option<T>> + OptionalSend {
+        tokio::sync::mpsc::Receiver::recv(self)
+    }
+
+    #[inline]
+    fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        tokio::sync::mpsc::Receiver::try_recv(self).map_err(|e| match e {
+            tokio::sync::mpsc::error::TryRecvError::Empty => TryRecvError::Empty,
+            tokio::sync::mpsc::error::TryRecvError::Disconnected => TryRecvError::Disconnected,
+        })
+    }
+}
+
+/// `broadcast` channel backed by [`tokio::sync::broadcast`].
+///
+/// `tokio::sync` does not depend on the `tokio` executor, thus it is also used by other runtimes.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TokioBroadcast;
+
+impl Broadcast for TokioBroadcast {
+    type Sender<T: OptionalSend + OptionalSync + Clone + 'static> = tokio::sync::broadcast::Sender<T>;
+    type Receiver<T: OptionalSend + OptionalSync + Clone + 'static> = tokio::sync::broadcast::Receiver<T>;
+
+    #[inline]
+    fn channel<T>(capacity: usize) -> (Self::Sender<T>, Self::Receiver<T>)
+    where T: OptionalSend + OptionalSync + Clone + 'static {
+        tokio::sync::broadcast::channel(capacity)
+    }
+}
+
+impl<T> BroadcastSender<T> for tokio::sync::broadcast::Sender<T>
+where T: OptionalSend + OptionalSync + Clone + 'static
+{
+    type Receiver = tokio::sync::broadcast::Receiver<T>;
+
+    #[inline]
+    fn send(&self, msg: T) -> Result<usize, SendError<T>> {
+        tokio::sync::broadcast::Sender::send(self, msg).map_err(|e| SendError(e.0))
+    }
+
+    #[inline]
+    fn subscribe(&self) -> Self::Receiver {
+        tokio::sync::broadcast::Sender::subscribe(self)
+    }
+
+    #[inline]
+    fn receiver_count(&self) -> usize {
+        tokio::sync::broadcast::Sender::receiver_count(self)
+    }
+}
+
+impl<T> BroadcastReceiver<T> for tokio::sync::broadcast::Receiver<T>
+where T: OptionalSend + OptionalSync + Clone + 'static
+{
+    #[inline]
+    fn recv(&mut self) -> impl Future<Output = Result<T, broadcast::RecvError>> + OptionalSend {
+        let fu = tokio::sync::broadcast::Receiver::recv(self);
+        async move {
+            fu.await.map_err(|e| match e {
+                tokio::sync::broadcast::error::RecvError::Closed => broadcast::RecvError::Closed,
+                tokio::sync::broadcast::error::RecvError::Lagged(n) => broadcast::RecvError::Lagged(n),
+            })
+        }
+    }
+}
+
+/// `tokio_util::sync::CancellationToken` does not depend on the `tokio` executor, thus it is also
+/// used by other runtimes.
+impl CancellationToken for tokio_util::sync::CancellationToken {
+    #[inline]
+    fn new() -> Self {
+        tokio_util::sync::CancellationToken::new()
+    }
+
+    #[inline]
+    fn child_token(&self) -> Self {
+        tokio_util::sync::CancellationToken::child_token(self)
+    }
+
+    #[inline]
+    fn cancel(&self) {
+        tokio_util::sync::CancellationToken::cancel(self)
+    }
+
+    #[inline]
+    fn is_cancelled(&self) -> bool {
+        tokio_util::sync::CancellationToken::is_cancelled(self)
+    }
+
+    #[inline]
+    fn cancelled(&self) -> impl Future<Output = ()> + OptionalSend {
+        tokio_util::sync::CancellationToken::cancelled(self)
+    }
+}
+
+impl Interval<TokioInstant> for tokio::time::Interval {
+    #[inline]
+    fn tick(&mut self) -> impl Future<Output = TokioInstant> + OptionalSend {
+        tokio::time::Interval::tick(self)
+    }
+}
+
+impl Semaphore for tokio::sync::Semaphore {
+    type Permit = tokio::sync::OwnedSemaphorePermit;
+
+    #[inline]
+    fn new(permits: usize) -> Self {
+        tokio::sync::Semaphore::new(permits)
+    }
+
+    #[inline]
+    fn acquire_owned(self: Arc<Self>) -> impl Future<Output = Self::Permit> + OptionalSend {
+        let fu = tokio::sync::Semaphore::acquire_owned(self);
+        async move { fu.await.expect("the semaphore is never closed") }
+    }
+
+    #[inline]
+    fn try_acquire_owned(self: Arc<Self>) -> Option<Self::Permit> {
+        tokio::sync::Semaphore::try_acquire_owned(self).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::runtime::Suite;
+    use crate::TokioRuntime;
+
+    // `spawn()` is `spawn_local()` with singlethreaded enabled, which requires a `LocalSet`.
+    #[cfg(not(feature = "singlethreaded"))]
+    #[test]
+    fn test_tokio_rt() {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("Failed building the runtime");
+        rt.block_on(Suite::<TokioRuntime>::test_all());
+    }
+}
@@ -0,0 +1,229 @@
+//! [`AsyncRuntime`] implementation for `wasm32-unknown-unknown`, e.g., browsers or edge workers.
+//!
+//! Enabled by feature flag `wasm-runtime`, which also enables `singlethreaded`.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use futures::FutureExt;
+use gloo_timers::future::TimeoutFuture;
+use rand::SeedableRng;
+
+use crate::async_runtime::impls::panic_message;
+use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::MissedTickBehavior;
+use crate::async_runtime::SleepInterval;
+use crate::async_runtime::TokioBroadcast;
+use crate::async_runtime::TokioMpsc;
+use crate::instant::WasmInstant;
+use crate::AsyncRuntime;
+use crate::OptionalSend;
+
+/// Asynchronous runtime for WebAssembly.
+///
+/// There is no thread in a WebAssembly host: tasks are run by the JavaScript event loop via
+/// [`wasm-bindgen-futures`](https://docs.rs/wasm-bindgen-futures), timers are JavaScript timers
+/// provided by [`gloo-timers`](https://docs.rs/gloo-timers), and `oneshot` channels come from the
+/// `futures` crate.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WasmRuntime;
+
+/// The error returned by awaiting a [`WasmJoinHandle`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WasmJoinError {
+    #[error("task panicked: {0}")]
+    Panic(String),
+
+    #[error("task was cancelled")]
+    Cancelled,
+}
+
+/// The join handle of a task spawned by [`WasmRuntime::spawn`].
+///
+/// `wasm_bindgen_futures::spawn_local()` does not return a handle, thus the output of the task is
+/// delivered through a `oneshot` channel.
+pub struct WasmJoinHandle<T>(futures::channel::oneshot::Receiver<Result<T, WasmJoinError>>);
+
+impl<T> Future for WasmJoinHandle<T> {
+    type Output = Result<T, WasmJoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map(|res| res.unwrap_or(Err(WasmJoinError::Cancelled)))
+    }
+}
+
+/// The error returned by [`WasmTimeout`] if the deadline is reached before the inner future
+/// completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("deadline has elapsed")]
+pub struct WasmTimeoutError;
+
+/// Require a future to complete before a deadline.
+pub struct WasmTimeout<F> {
+    future: Pin<Box<F>>,
+    sleep: TimeoutFuture,
+}
+
+impl<F> Future for WasmTimeout<F>
+where F: Future
+{
+    type Output = Result<F::Output, WasmTimeoutError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(v) = this.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(v));
+        }
+
+        match Pin::new(&mut this.sleep).poll(cx) {
+            Poll::Ready(_) => Poll::Ready(Err(WasmTimeoutError)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub struct WasmOneshotSender<T>(futures::channel::oneshot::Sender<T>);
+
+/// Build a JavaScript timer that fires no earlier than `duration`.
+///
+/// JavaScript timers have a resolution of milliseconds, thus `duration` is rounded up.
+fn timer(duration: Duration) -> TimeoutFuture {
+    let millis = duration.as_micros().div_ceil(1_000);
+    TimeoutFuture::new(u32::try_from(millis).unwrap_or(u32::MAX))
+}
+
+impl AsyncRuntime for WasmRuntime {
+    type JoinError = WasmJoinError;
+    type JoinHandle<T: OptionalSend + 'static> = WasmJoinHandle<T>;
+    type Sleep = TimeoutFuture;
+    type Instant = WasmInstant;
+    type TimeoutError = WasmTimeoutError;
+    type Timeout<R, T: Future<Output = R> + OptionalSend> = WasmTimeout<T>;
+
+    /// `ThreadRng` relies on thread-local storage, a generator seeded from the host entropy source
+    /// is used instead.
+    type ThreadLocalRng = rand::rngs::StdRng;
+
+    type OneshotSender<T: OptionalSend> = WasmOneshotSender<T>;
+    type OneshotReceiver<T: OptionalSend> = futures::channel::oneshot::Receiver<T>;
+    type OneshotReceiverError = futures::channel::oneshot::Canceled;
+    type Mpsc = TokioMpsc;
+    type Broadcast = TokioBroadcast;
+    type CancellationToken = tokio_util::sync::CancellationToken;
+    type Semaphore = tokio::sync::Semaphore;
+    type Interval = SleepInterval<Self>;
+
+    #[inline]
+    fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        let (tx, rx) = futures::channel::oneshot::channel();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let res = AssertUnwindSafe(future)
+                .catch_unwind()
+                .await
+                .map_err(|panic| WasmJoinError::Panic(panic_message(panic)));
+
+            // The receiver may have been dropped, i.e., the task is detached.
+            let _ = tx.send(res);
+        });
+
+        WasmJoinHandle(rx)
+    }
+
+    /// There is no thread to offload to in a WebAssembly host, thus the function is run as a task
+    /// on the JavaScript event loop.
+    #[inline]
+    fn spawn_blocking<F, T>(f: F) -> Self::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        Self::spawn(async move { f() })
+    }
+
+    #[inline]
+    fn sleep(duration: Duration) -> Self::Sleep {
+        timer(duration)
+    }
+
+    #[inline]
+    fn sleep_until(deadline: Self::Instant) -> Self::Sleep {
+        timer(deadline.saturating_duration_since(WasmInstant::now()))
+    }
+
+    #[inline]
+    fn interval_at(start: Self::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self::Interval {
+        SleepInterval::new(start, period, missed_tick_behavior)
+    }
+
+    #[inline]
+    fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F> {
+        WasmTimeout {
+            future: Box::pin(future),
+            sleep: Self::sleep(duration),
+        }
+    }
+
+    #[inline]
+    fn timeout_at<R, F: Future<Output = R> + OptionalSend>(deadline: Self::Instant, future: F) -> Self::Timeout<R, F> {
+        WasmTimeout {
+            future: Box::pin(future),
+            sleep: Self::sleep_until(deadline),
+        }
+    }
+
+    #[inline]
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        matches!(join_error, WasmJoinError::Panic(_))
+    }
+
+    #[inline]
+    fn thread_rng() -> Self::ThreadLocalRng {
+        rand::rngs::StdRng::from_entropy()
+    }
+
+    #[inline]
+    fn oneshot<T>() -> (Self::OneshotSender<T>, Self::OneshotReceiver<T>)
+    where T: OptionalSend {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        (WasmOneshotSender(tx), rx)
+    }
+}
+
+impl<T: OptionalSend> AsyncOneshotSendExt<T> for WasmOneshotSender<T> {
+    #[inline]
+    fn send(self, t: T) -> Result<(), T> {
+        self.0.send(t)
+    }
+}
+
+impl<T> Debug for WasmOneshotSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WasmOneshotSender").finish()
+    }
+}
+
+// JavaScript APIs are only available on a WebAssembly host, run with `wasm-pack test --node`.
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use crate::async_runtime::WasmRuntime;
+    use crate::testing::runtime::Suite;
+
+    #[wasm_bindgen_test]
+    async fn test_wasm_rt() {
+        Suite::<WasmRuntime>::test_all().await;
+    }
+}
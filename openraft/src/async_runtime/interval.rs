@@ -0,0 +1,168 @@
+//! Periodic timer.
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use crate::AsyncRuntime;
+use crate::Instant;
+use crate::OptionalSend;
+
+/// Defines the behavior of an [`Interval`] when it misses a tick, e.g., the task is busy for
+/// longer than the period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissedTickBehavior {
+    /// Fire the missed ticks immediately one after another, to catch up with the schedule.
+    #[default]
+    Burst,
+
+    /// Fire the missed tick immediately, and schedule the following ticks one period after it.
+    Delay,
+
+    /// Fire the missed tick immediately, and skip the following missed ticks to get back on the
+    /// original schedule.
+    Skip,
+}
+
+/// A timer that fires periodically, provided by an [`AsyncRuntime`].
+pub trait Interval<I>: OptionalSend + 'static
+where I: Instant
+{
+    /// Waits until the next tick and returns the instant the tick was scheduled at.
+    ///
+    /// This method is cancel safe: if it is used in `select!` and another branch completes first,
+    /// no tick is lost.
+    fn tick(&mut self) -> impl Future<Output = I> + OptionalSend;
+}
+
+/// An [`Interval`] built on [`AsyncRuntime::sleep_until`], for runtimes that do not provide a
+/// periodic timer.
+pub struct SleepInterval<RT: AsyncRuntime> {
+    next: RT::Instant,
+    period: Duration,
+    missed_tick_behavior: MissedTickBehavior,
+    _p: PhantomData<RT>,
+}
+
+impl<RT: AsyncRuntime> SleepInterval<RT> {
+    /// Creates an interval that fires first at `start` and then every `period`.
+    ///
+    /// # Panics
+    ///
+    /// It panics if `period` is zero.
+    pub fn new(start: RT::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self {
+        assert!(period > Duration::ZERO, "`period` must be non-zero");
+
+        Self {
+            next: start,
+            period,
+            missed_tick_behavior,
+            _p: PhantomData,
+        }
+    }
+
+    /// Returns the next deadline after a tick scheduled at `scheduled` fired at `now`.
+    fn next_deadline(&self, scheduled: RT::Instant, now: RT::Instant) -> RT::Instant {
+        let next = scheduled + self.period;
+        if next > now {
+            return next;
+        }
+
+        match self.missed_tick_behavior {
+            MissedTickBehavior::Burst => next,
+            MissedTickBehavior::Delay => now + self.period,
+            MissedTickBehavior::Skip => {
+                let period = self.period.as_nanos();
+                let periods = (now - scheduled).as_nanos() / period + 1;
+                scheduled + Duration::from_nanos((periods * period) as u64)
+            }
+        }
+    }
+}
+
+impl<RT: AsyncRuntime> Interval<RT::Instant> for SleepInterval<RT> {
+    async fn tick(&mut self) -> RT::Instant {
+        let scheduled = self.next;
+        RT::sleep_until(scheduled).await;
+
+        self.next = self.next_deadline(scheduled, RT::now());
+        scheduled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::FutureExt;
+
+    use crate::async_runtime::Interval;
+    use crate::async_runtime::MissedTickBehavior;
+    use crate::testing::TestRuntime;
+    use crate::AsyncRuntime;
+
+    const PERIOD: Duration = Duration::from_millis(10);
+
+    /// Build an interval starting now, advance the clock by `elapsed`, then return the instants
+    /// of the ticks fired without waiting, relative to the start.
+    fn ready_ticks(behavior: MissedTickBehavior, elapsed: Duration) -> Vec<Duration> {
+        let start = TestRuntime::now();
+        let mut interval = TestRuntime::interval_at(start, PERIOD, behavior);
+
+        TestRuntime::advance(elapsed);
+
+        let mut ticks = vec![];
+        while let Some(t) = interval.tick().now_or_never() {
+            ticks.push(t - start);
+        }
+        ticks
+    }
+
+    fn ms(v: &[u64]) -> Vec<Duration> {
+        v.iter().map(|x| Duration::from_millis(*x)).collect()
+    }
+
+    #[test]
+    fn test_sleep_interval_on_schedule() {
+        let start = TestRuntime::now();
+        let mut interval = TestRuntime::interval_at(start, PERIOD, MissedTickBehavior::Burst);
+
+        assert_eq!(Some(start), interval.tick().now_or_never());
+        assert_eq!(None, interval.tick().now_or_never());
+
+        TestRuntime::advance(PERIOD);
+        assert_eq!(Some(start + PERIOD), interval.tick().now_or_never());
+    }
+
+    #[test]
+    fn test_sleep_interval_missed_tick_behavior() {
+        let elapsed = Duration::from_millis(35);
+
+        assert_eq!(ms(&[0, 10, 20, 30]), ready_ticks(MissedTickBehavior::Burst, elapsed));
+
+        // The next tick is scheduled at 35 + 10 after the first missed tick.
+        assert_eq!(ms(&[0]), ready_ticks(MissedTickBehavior::Delay, elapsed));
+
+        // The next tick is scheduled at 40, on the original schedule.
+        assert_eq!(ms(&[0]), ready_ticks(MissedTickBehavior::Skip, elapsed));
+    }
+
+    #[test]
+    fn test_sleep_interval_delay_and_skip_deadline() {
+        for (behavior, want) in [(MissedTickBehavior::Delay, 45), (MissedTickBehavior::Skip, 40)] {
+            let start = TestRuntime::now();
+            let mut interval = TestRuntime::interval_at(start, PERIOD, behavior);
+
+            TestRuntime::advance(Duration::from_millis(35));
+            interval.tick().now_or_never().unwrap();
+
+            let mut tick = Box::pin(interval.tick());
+            TestRuntime::advance(Duration::from_millis(want - 35 - 1));
+            assert!(tick.as_mut().now_or_never().is_none(), "{:?}", behavior);
+
+            TestRuntime::advance(Duration::from_millis(1));
+            let t = tick.as_mut().now_or_never().unwrap();
+            assert_eq!(Duration::from_millis(want), t - start, "{:?}", behavior);
+        }
+    }
+}
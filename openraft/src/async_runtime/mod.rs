@@ -7,10 +7,42 @@ use std::fmt::Display;
 use std::future::Future;
 use std::time::Duration;
 
+pub mod broadcast;
+pub mod cancellation;
+pub(crate) mod impls;
+pub mod interval;
+pub mod mpsc;
+pub mod semaphore;
+
+pub use broadcast::Broadcast;
+pub use broadcast::BroadcastReceiver;
+pub use broadcast::BroadcastSender;
+pub use cancellation::CancellationToken;
+#[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+pub use impls::async_channel_mpsc::AsyncChannelMpsc;
+#[cfg(feature = "async-std-runtime")]
+pub use impls::async_std_runtime::AsyncStdRuntime;
+#[cfg(feature = "glommio-runtime")]
+pub use impls::glommio_runtime::GlommioRuntime;
+#[cfg(feature = "madsim-runtime")] pub use impls::madsim_runtime::MadsimRuntime;
+#[cfg(feature = "monoio-runtime")] pub use impls::monoio_runtime::MonoioRuntime;
+#[cfg(feature = "smol-runtime")] pub use impls::smol_runtime::SmolRuntime;
+pub use impls::tokio_runtime::TokioBroadcast;
+pub use impls::tokio_runtime::TokioMpsc;
+pub use impls::tokio_runtime::TokioOneShotSender;
+pub use impls::tokio_runtime::TokioRuntime;
+#[cfg(feature = "wasm-runtime")] pub use impls::wasm_runtime::WasmRuntime;
+pub use interval::Interval;
+pub use interval::MissedTickBehavior;
+pub use interval::SleepInterval;
+pub use mpsc::Mpsc;
+pub use mpsc::MpscReceiver;
+pub use mpsc::MpscSender;
+pub use semaphore::Semaphore;
+
 use crate::Instant;
 use crate::OptionalSend;
 use crate::OptionalSync;
-use crate::TokioInstant;
 
 /// A trait defining interfaces with an asynchronous runtime.
 ///
@@ -60,18 +92,75 @@ pub trait AsyncRuntime: Debug + Default + PartialEq + Eq + OptionalSend + Option
         + Future<Output = Result<T, Self::OneshotReceiverError>>
         + Unpin;
 
+    /// Type of bounded `mpsc` channels.
+    type Mpsc: Mpsc;
+
+    /// Type of `broadcast` channels, which deliver every value to all subscribers.
+    type Broadcast: Broadcast;
+
+    /// Type of a token to cancel tasks.
+    type CancellationToken: CancellationToken;
+
+    /// Type of a counting semaphore.
+    type Semaphore: Semaphore;
+
+    /// Type of a periodic timer.
+    type Interval: Interval<Self::Instant>;
+
     /// Spawn a new task.
     fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
     where
         T: Future + OptionalSend + 'static,
         T::Output: OptionalSend + 'static;
 
+    /// Spawn a new task with a name, which identifies the task in diagnostic tools such as
+    /// `tokio-console`.
+    ///
+    /// Openraft spawns its long running tasks, such as `RaftCore`, replication streams and the
+    /// tick loop, with this method. The default implementation ignores the name and calls
+    /// [`Self::spawn`].
+    fn spawn_named<T>(name: &str, future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        let _ = name;
+        Self::spawn(future)
+    }
+
+    /// Run a blocking function on a thread where blocking is acceptable, such as a dedicated
+    /// thread pool, so that it does not stall the tasks of the asynchronous executor.
+    ///
+    /// It is meant for CPU or IO intensive work, e.g., serializing a state machine when building
+    /// a snapshot.
+    ///
+    /// The function is required to be `Send` even with feature flag `singlethreaded` enabled,
+    /// because it may be run on another thread.
+    fn spawn_blocking<F, T>(f: F) -> Self::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static;
+
+    /// Return the current instant of the clock of this runtime.
+    ///
+    /// Openraft reads the time through this method, so that a runtime for testing can provide a
+    /// clock that is advanced manually.
+    #[inline]
+    fn now() -> Self::Instant {
+        Self::Instant::now()
+    }
+
     /// Wait until `duration` has elapsed.
     fn sleep(duration: Duration) -> Self::Sleep;
 
     /// Wait until `deadline` is reached.
     fn sleep_until(deadline: Self::Instant) -> Self::Sleep;
 
+    /// Create an [`Interval`] that fires first at `start` and then every `period`.
+    ///
+    /// `missed_tick_behavior` defines how to catch up when ticks are missed.
+    fn interval_at(start: Self::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self::Interval;
+
     /// Require a [`Future`] to complete before the specified duration has elapsed.
     fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F>;
 
@@ -100,78 +189,6 @@ pub trait AsyncRuntime: Debug + Default + PartialEq + Eq + OptionalSend + Option
     where T: OptionalSend;
 }
 
-/// `Tokio` is the default asynchronous executor.
-#[derive(Debug, Default, PartialEq, Eq)]
-pub struct TokioRuntime;
-
-pub struct TokioOneShotSender<T: OptionalSend>(pub tokio::sync::oneshot::Sender<T>);
-
-impl AsyncRuntime for TokioRuntime {
-    type JoinError = tokio::task::JoinError;
-    type JoinHandle<T: OptionalSend + 'static> = tokio::task::JoinHandle<T>;
-    type Sleep = tokio::time::Sleep;
-    type Instant = TokioInstant;
-    type TimeoutError = tokio::time::error::Elapsed;
-    type Timeout<R, T: Future<Output = R> + OptionalSend> = tokio::time::Timeout<T>;
-    type ThreadLocalRng = rand::rngs::ThreadRng;
-    type OneshotSender<T: OptionalSend> = TokioOneShotSender<T>;
-    type OneshotReceiver<T: OptionalSend> = tokio::sync::oneshot::Receiver<T>;
-    type OneshotReceiverError = tokio::sync::oneshot::error::RecvError;
-
-    #[inline]
-    fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
-    where
-        T: Future + OptionalSend + 'static,
-        T::Output: OptionalSend + 'static,
-    {
-        #[cfg(feature = "singlethreaded")]
-        {
-            tokio::task::spawn_local(future)
-        }
-        #[cfg(not(feature = "singlethreaded"))]
-        {
-            tokio::task::spawn(future)
-        }
-    }
-
-    #[inline]
-    fn sleep(duration: Duration) -> Self::Sleep {
-        tokio::time::sleep(duration)
-    }
-
-    #[inline]
-    fn sleep_until(deadline: Self::Instant) -> Self::Sleep {
-        tokio::time::sleep_until(deadline)
-    }
-
-    #[inline]
-    fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F> {
-        tokio::time::timeout(duration, future)
-    }
-
-    #[inline]
-    fn timeout_at<R, F: Future<Output = R> + OptionalSend>(deadline: Self::Instant, future: F) -> Self::Timeout<R, F> {
-        tokio::time::timeout_at(deadline, future)
-    }
-
-    #[inline]
-    fn is_panic(join_error: &Self::JoinError) -> bool {
-        join_error.is_panic()
-    }
-
-    #[inline]
-    fn thread_rng() -> Self::ThreadLocalRng {
-        rand::thread_rng()
-    }
-
-    #[inline]
-    fn oneshot<T>() -> (Self::OneshotSender<T>, Self::OneshotReceiver<T>)
-    where T: OptionalSend {
-        let (tx, rx) = tokio::sync::oneshot::channel();
-        (TokioOneShotSender(tx), rx)
-    }
-}
-
 pub trait AsyncOneshotSendExt<T> {
     /// Attempts to send a value on this channel, returning it back if it could
     /// not be sent.
@@ -183,16 +200,3 @@ pub trait AsyncOneshotSendExt<T> {
     /// problems.
     fn send(self, t: T) -> Result<(), T>;
 }
-
-impl<T: OptionalSend> AsyncOneshotSendExt<T> for TokioOneShotSender<T> {
-    #[inline]
-    fn send(self, t: T) -> Result<(), T> {
-        self.0.send(t)
-    }
-}
-
-impl<T: OptionalSend> Debug for TokioOneShotSender<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("TokioSendWrapper").finish()
-    }
-}
@@ -0,0 +1,124 @@
+//! Bounded multi-producer, single-consumer channel.
+//!
+//! A bounded channel applies backpressure to senders: [`MpscSender::send`] waits until there is
+//! capacity in the channel.
+
+use std::fmt;
+use std::future::Future;
+
+use crate::OptionalSend;
+use crate::OptionalSync;
+
+/// A family of bounded `mpsc` channel types provided by an [`AsyncRuntime`].
+///
+/// [`AsyncRuntime`]: crate::AsyncRuntime
+pub trait Mpsc: Sized + OptionalSend {
+    /// Type of a `mpsc` sender.
+    type Sender<T: OptionalSend>: MpscSender<T>;
+
+    /// Type of a `mpsc` receiver.
+    type Receiver<T: OptionalSend>: MpscReceiver<T>;
+
+    /// Creates a bounded channel that holds at most `buffer` messages.
+    ///
+    /// `buffer` must be greater than 0.
+    fn channel<T: OptionalSend>(buffer: usize) -> (Self::Sender<T>, Self::Receiver<T>);
+}
+
+/// The sending half of a bounded `mpsc` channel.
+pub trait MpscSender<T>: OptionalSend + OptionalSync + Clone + fmt::Debug
+where T: OptionalSend
+{
+    /// Sends a value, waiting until there is capacity.
+    ///
+    /// It returns the value back in an error if the receiving half has been dropped.
+    fn send(&self, msg: T) -> impl Future<Output = Result<(), SendError<T>>> + OptionalSend;
+
+    /// Attempts to send a value immediately without waiting for capacity.
+    fn try_send(&self, msg: T) -> Result<(), TrySendError<T>>;
+}
+
+/// The receiving half of a bounded `mpsc` channel.
+pub trait MpscReceiver<T>: OptionalSend + OptionalSync
+where T: OptionalSend
+{
+    /// Receives the next value, or `None` if all senders have been dropped and the channel is
+    /// empty.
+    ///
+    /// This method is cancel safe: if it is used in `select!` and another branch completes first,
+    /// no message is lost.
+    fn recv(&mut self) -> impl Future<Output = Option<T>> + OptionalSend;
+
+    /// Attempts to receive the next value without waiting.
+    fn try_recv(&mut self) -> Result<T, TryRecvError>;
+}
+
+/// Error returned by [`MpscSender::send`] if the receiving half has been dropped.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "channel closed")
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+/// Error returned by [`MpscSender::try_send`].
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    /// The channel is full.
+    Full(T),
+
+    /// The receiving half has been dropped.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Returns the value that failed to be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(t) => t,
+            TrySendError::Closed(t) => t,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "Full(..)"),
+            TrySendError::Closed(_) => write!(f, "Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "no available capacity"),
+            TrySendError::Closed(_) => write!(f, "channel closed"),
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {}
+
+/// Error returned by [`MpscReceiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum TryRecvError {
+    /// The channel is currently empty, but there are still senders.
+    #[error("receiving on an empty channel")]
+    Empty,
+
+    /// All senders have been dropped and the channel is empty.
+    #[error("receiving on a closed channel")]
+    Disconnected,
+}
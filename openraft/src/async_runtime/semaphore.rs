@@ -0,0 +1,30 @@
+//! Counting semaphore for limiting concurrency.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::OptionalSend;
+use crate::OptionalSync;
+
+/// A counting semaphore provided by an [`AsyncRuntime`].
+///
+/// Openraft uses it to limit the number of concurrent operations, such as snapshot
+/// transmissions. A permit is held as long as the operation runs and is returned to the semaphore
+/// when it is dropped.
+///
+/// [`AsyncRuntime`]: crate::AsyncRuntime
+pub trait Semaphore: OptionalSend + OptionalSync + Sized + 'static {
+    /// A permit acquired from a semaphore, which is released when dropped.
+    type Permit: OptionalSend + OptionalSync + 'static;
+
+    /// Creates a semaphore with the given number of permits.
+    fn new(permits: usize) -> Self;
+
+    /// Acquires a permit, waiting until one is available.
+    fn acquire_owned(self: Arc<Self>) -> impl Future<Output = Self::Permit> + OptionalSend;
+
+    /// Attempts to acquire a permit without waiting.
+    ///
+    /// It returns `None` if there is no permit available.
+    fn try_acquire_owned(self: Arc<Self>) -> Option<Self::Permit>;
+}
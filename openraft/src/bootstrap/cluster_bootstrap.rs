@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::bootstrap::Discovery;
+use crate::error::BootstrapError;
+use crate::error::Fatal;
+use crate::error::InitializeError;
+use crate::metrics::WaitError;
+use crate::type_config::alias::InstantOf;
+use crate::AsyncRuntime;
+use crate::Instant;
+use crate::Raft;
+use crate::RaftTypeConfig;
+
+/// How a node joined a new cluster, returned by [`Bootstrap::run()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootstrapOutcome {
+    /// This node initialized the cluster with all of the discovered members.
+    Initialized,
+
+    /// This node received the membership from the leader of the new cluster.
+    Joined,
+
+    /// This node was already initialized, e.g., it is restarted.
+    AlreadyInitialized,
+}
+
+/// Forms a new cluster from seed members, so that exactly one node calls
+/// [`Raft::initialize()`].
+///
+/// Every node of the new cluster runs a `Bootstrap` with its own [`Raft`] and the same seed
+/// members. Once the expected number of members is discovered, the member with the smallest node
+/// id initializes the cluster with all of them, while the others wait until they receive the
+/// membership from the new leader. A node that is not one of the discovered members waits as well,
+/// e.g., to be added later with [`Raft::add_learner()`].
+///
+/// A node that is already initialized returns at once, thus it is safe to run a `Bootstrap` every
+/// time a node starts.
+pub struct Bootstrap<C, D>
+where
+    C: RaftTypeConfig,
+    D: Discovery<C>,
+{
+    raft: Raft<C>,
+    discovery: D,
+    expected_members: usize,
+    retry_interval: Duration,
+    timeout: Option<Duration>,
+}
+
+impl<C, D> Bootstrap<C, D>
+where
+    C: RaftTypeConfig,
+    D: Discovery<C>,
+{
+    /// Create a bootstrap of `raft`, which finds the seed members with `discovery`.
+    pub fn new(raft: Raft<C>, discovery: D) -> Self {
+        Self {
+            raft,
+            discovery,
+            expected_members: 1,
+            retry_interval: Duration::from_millis(500),
+            timeout: None,
+        }
+    }
+
+    /// Set the number of members to discover before choosing the node to initialize the cluster.
+    ///
+    /// With a [`Discovery`] that finds the members one by one, it should be the size of the new
+    /// cluster, so that every node chooses from the same members. The default is `1`.
+    pub fn expected_members(mut self, n: usize) -> Self {
+        self.expected_members = std::cmp::max(n, 1);
+        self
+    }
+
+    /// Set the time to wait before calling [`Discovery::discover()`] again if not enough members
+    /// are found. The default is 500 milliseconds.
+    pub fn retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Set the time to wait for the cluster to be formed. By default it waits forever.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Discover the seed members, and initialize the cluster if this node is the chosen one, or
+    /// wait until this node receives the membership from the leader.
+    pub async fn run(self) -> Result<BootstrapOutcome, BootstrapError<C>> {
+        if self.raft.is_initialized().await? {
+            return Ok(BootstrapOutcome::AlreadyInitialized);
+        }
+
+        let deadline = self.timeout.map(|t| C::AsyncRuntime::now() + t);
+
+        let id = self.raft.metrics().borrow().id;
+        let members = self.discover_members(deadline).await?;
+
+        // `discover_members()` returns at least one member.
+        let initializer = *members.keys().next().unwrap();
+
+        if initializer == id {
+            tracing::info!(members = debug(members.keys()), "bootstrap: initialize the cluster");
+
+            return match self.raft.initialize(members).await {
+                Ok(()) => Ok(BootstrapOutcome::Initialized),
+                // Contacted by the leader of another cluster in the meantime.
+                Err(e) if matches!(e.api_error(), Some(InitializeError::NotAllowed(_))) => Ok(BootstrapOutcome::Joined),
+                Err(e) => Err(e.into()),
+            };
+        }
+
+        tracing::info!(
+            initializer = display(initializer),
+            "bootstrap: wait for the membership from the leader"
+        );
+
+        let res = self
+            .raft
+            .wait(deadline.map(remaining))
+            .metrics(
+                |m| m.membership_config.log_id().is_some(),
+                "bootstrap: receive membership",
+            )
+            .await;
+
+        match res {
+            Ok(_) => Ok(BootstrapOutcome::Joined),
+            Err(WaitError::Timeout(_, _)) => Err(BootstrapError::Timeout(self.timeout.unwrap_or_default())),
+            Err(WaitError::ShuttingDown) => Err(Fatal::Stopped.into()),
+        }
+    }
+
+    /// Call [`Discovery::discover()`] until the expected number of members is found.
+    async fn discover_members(
+        &self,
+        deadline: Option<InstantOf<C>>,
+    ) -> Result<BTreeMap<C::NodeId, C::Node>, BootstrapError<C>> {
+        loop {
+            let members = self.discovery.discover().await.map_err(BootstrapError::Discovery)?;
+            if members.len() >= self.expected_members {
+                return Ok(members);
+            }
+
+            tracing::debug!(
+                found = members.len(),
+                expected = self.expected_members,
+                "bootstrap: not enough members found, retry in {:?}",
+                self.retry_interval
+            );
+
+            if deadline.is_some_and(|t| C::AsyncRuntime::now() + self.retry_interval > t) {
+                return Err(BootstrapError::Timeout(self.timeout.unwrap_or_default()));
+            }
+
+            C::AsyncRuntime::sleep(self.retry_interval).await;
+        }
+    }
+}
+
+/// The time left until `deadline`, or zero if it has passed.
+fn remaining<I: Instant>(deadline: I) -> Duration {
+    let now = I::now();
+    if deadline > now {
+        deadline - now
+    } else {
+        Duration::ZERO
+    }
+}
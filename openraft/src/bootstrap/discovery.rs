@@ -0,0 +1,46 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use anyerror::AnyError;
+use openraft_macros::add_async_trait;
+
+use crate::OptionalSend;
+use crate::OptionalSync;
+use crate::RaftTypeConfig;
+
+/// Finds the initial members of a cluster for a [`Bootstrap`](`crate::bootstrap::Bootstrap`).
+///
+/// It is implemented for a static seed list, a `BTreeMap` of node id to node, and for an async
+/// function that returns the members found so far, e.g., by querying DNS or a service registry.
+///
+/// Every node of the cluster must eventually discover the same members, since the node to
+/// initialize the cluster is chosen from them.
+#[add_async_trait]
+pub trait Discovery<C>: OptionalSend + OptionalSync + 'static
+where C: RaftTypeConfig
+{
+    /// Returns the members found so far.
+    ///
+    /// It is called again until the expected number of members is found. An error aborts the
+    /// bootstrap.
+    async fn discover(&self) -> Result<BTreeMap<C::NodeId, C::Node>, AnyError>;
+}
+
+impl<C> Discovery<C> for BTreeMap<C::NodeId, C::Node>
+where C: RaftTypeConfig
+{
+    async fn discover(&self) -> Result<BTreeMap<C::NodeId, C::Node>, AnyError> {
+        Ok(self.clone())
+    }
+}
+
+impl<C, F, Fut> Discovery<C> for F
+where
+    C: RaftTypeConfig,
+    F: Fn() -> Fut + OptionalSend + OptionalSync + 'static,
+    Fut: Future<Output = Result<BTreeMap<C::NodeId, C::Node>, AnyError>> + OptionalSend,
+{
+    async fn discover(&self) -> Result<BTreeMap<C::NodeId, C::Node>, AnyError> {
+        self().await
+    }
+}
@@ -0,0 +1,19 @@
+//! Form a new cluster from a list of seed members, without choosing by hand which node calls
+//! [`Raft::initialize()`](`crate::Raft::initialize`).
+//!
+//! Every node of a new cluster runs a [`Bootstrap`] with the same seed members, either a static
+//! list or a [`Discovery`] callback. The member with the smallest node id initializes the cluster
+//! with all of the members, and the others wait until they receive the membership from the new
+//! leader:
+//!
+//! ```ignore
+//! let seeds = btreemap! {1 => node1, 2 => node2, 3 => node3};
+//! let outcome = Bootstrap::new(raft.clone(), seeds).timeout(Duration::from_secs(30)).run().await?;
+//! ```
+
+mod cluster_bootstrap;
+mod discovery;
+
+pub use cluster_bootstrap::Bootstrap;
+pub use cluster_bootstrap::BootstrapOutcome;
+pub use discovery::Discovery;
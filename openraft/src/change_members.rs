@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
+use crate::membership::QuorumPolicy;
 use crate::Node;
 use crate::NodeId;
 
@@ -25,6 +26,12 @@ pub enum ChangeMembers<NID: NodeId, N: Node> {
     /// Replace voter ids with a new set. The node of every new voter has to already be a learner.
     ReplaceAllVoters(BTreeSet<NID>),
 
+    /// Replace the voter `.0` with the node `.1`, which has to already be a learner.
+    ///
+    /// The voters of the other configs are kept. If `.0` is not a voter, it just adds `.1` as a
+    /// voter. See [`Raft::replace_node()`](`crate::Raft::replace_node`).
+    ReplaceVoter(NID, NID),
+
     /// Add nodes to membership, as learners.
     ///
     /// it **WONT** replace existing node.
@@ -43,6 +50,12 @@ pub enum ChangeMembers<NID: NodeId, N: Node> {
     /// See: [Update-Node](`crate::docs::cluster_control::dynamic_membership#update-node`)
     SetNodes(BTreeMap<NID, N>),
 
+    /// Replace the nodes of existing members, e.g., to update the address of a node.
+    ///
+    /// The voters and learners are not changed. Nodes that are not in the membership are ignored.
+    /// See [`Raft::update_node()`](`crate::Raft::update_node`).
+    UpdateNodes(BTreeMap<NID, N>),
+
     /// Remove nodes from membership.
     ///
     /// If a node is still a voter, it returns
@@ -54,6 +67,34 @@ pub enum ChangeMembers<NID: NodeId, N: Node> {
     /// Every voter has to have a corresponding node in the new
     /// set, otherwise it returns [`error::LearnerNotFound`](`crate::error::LearnerNotFound`) error.
     ReplaceAllNodes(BTreeMap<NID, N>),
+
+    /// Set the election priorities of nodes. A priority of `0` removes the priority of a node.
+    ///
+    /// Priorities of nodes that are not in the membership are ignored.
+    /// See [`Membership::election_priority()`](`crate::Membership::election_priority`).
+    SetPriorities(BTreeMap<NID, u64>),
+
+    /// Replace the set of witnesses.
+    ///
+    /// Nodes that are not in the membership are ignored. A witness can not be removed from the
+    /// set, which is rejected with a [`WitnessCleared`](`crate::error::WitnessCleared`) error:
+    /// remove it from the membership and add it back as a learner instead.
+    /// See [`Membership::is_witness()`](`crate::Membership::is_witness`).
+    SetWitnesses(BTreeSet<NID>),
+
+    /// Set the failure zones of nodes. An empty zone removes the zone of a node.
+    ///
+    /// Zones of nodes that are not in the membership are ignored.
+    /// See [`Membership::zone()`](`crate::Membership::zone`).
+    SetZones(BTreeMap<NID, String>),
+
+    /// Replace the policy that defines the quorums of every config.
+    ///
+    /// Every two quorums of the new policy must intersect, and every quorum of the new policy
+    /// must intersect every quorum of the current one, otherwise
+    /// [`error::InvalidQuorumPolicy`](`crate::error::InvalidQuorumPolicy`) error will be returned.
+    /// See [`QuorumPolicy`].
+    SetQuorumPolicy(QuorumPolicy<NID>),
 }
 
 /// Convert a series of ids to a `Replace` operation.
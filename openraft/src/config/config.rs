@@ -1,7 +1,8 @@
 //! Raft runtime configuration.
 
-use std::ops::Deref;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use anyerror::AnyError;
@@ -9,25 +10,54 @@ use clap::Parser;
 use rand::Rng;
 
 use crate::config::error::ConfigError;
-use crate::raft_state::LogStateReader;
+use crate::config::snapshot_trigger::SnapshotTrigger;
+use crate::network::Backoff;
+use crate::network::CircuitBreakerBackoff;
+use crate::network::ConstantBackoff;
+use crate::network::ExponentialBackoff;
+use crate::network::JitteredBackoff;
+use crate::network::RPCTypes;
 use crate::AsyncRuntime;
-use crate::LogIdOptionExt;
-use crate::NodeId;
+use crate::RaftState;
+use crate::RaftTypeConfig;
 
 /// Log compaction and snapshot policy.
 ///
 /// This governs when periodic snapshots will be taken, and also governs the conditions which
 /// would cause a leader to send an `InstallSnapshot` RPC to a follower based on replication lag.
 ///
-/// Additional policies may become available in the future.
+/// The policy is evaluated every time the committed log id is updated, and it can be changed at
+/// runtime with [`RuntimeConfigHandle::snapshot_policy()`].
+///
+/// [`RuntimeConfigHandle::snapshot_policy()`]: crate::raft::RuntimeConfigHandle::snapshot_policy
 #[derive(Clone, Debug)]
 #[derive(PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum SnapshotPolicy {
     /// A snapshot will be generated once the log has grown the specified number of logs since
-    /// the last snapshot.
+    /// the last snapshot: `since_last:<n>`.
     LogsSinceLast(u64),
 
+    /// A snapshot will be generated once the logs since the last snapshot have grown to the
+    /// specified number of bytes: `bytes:<size>`.
+    ///
+    /// The size of a log is estimated with [`RaftEntry::size_hint()`]. The logs loaded from
+    /// storage when starting up are not counted.
+    ///
+    /// [`RaftEntry::size_hint()`]: crate::entry::RaftEntry::size_hint
+    Bytes(u64),
+
+    /// A snapshot will be generated once the oldest log since the last snapshot has been
+    /// appended for the specified duration: `interval:<ms>`.
+    ///
+    /// The logs loaded from storage when starting up are not taken into account.
+    Interval(Duration),
+
+    /// The application decides with [`RaftTypeConfig::SnapshotTrigger`]: `custom`.
+    ///
+    /// [`RaftTypeConfig::SnapshotTrigger`]: crate::RaftTypeConfig::SnapshotTrigger
+    Custom,
+
     /// Openraft will never trigger a snapshot building.
     /// With this option, the application calls
     /// [`Raft::trigger().snapshot()`](`crate::raft::trigger::Trigger::snapshot`) to manually
@@ -36,17 +66,72 @@ pub enum SnapshotPolicy {
 }
 
 impl SnapshotPolicy {
-    pub(crate) fn should_snapshot<NID>(&self, state: &impl Deref<Target = impl LogStateReader<NID>>) -> bool
-    where NID: NodeId {
+    pub(crate) fn should_snapshot<C>(&self, state: &RaftState<C>) -> bool
+    where C: RaftTypeConfig {
+        if let SnapshotPolicy::Never = self {
+            return false;
+        }
+
+        let ctx = state.snapshot_trigger_context(C::AsyncRuntime::now());
+
         match self {
-            SnapshotPolicy::LogsSinceLast(threshold) => {
-                state.committed().next_index() >= state.snapshot_last_log_id().next_index() + threshold
-            }
+            SnapshotPolicy::LogsSinceLast(threshold) => ctx.logs_since_last >= *threshold,
+            SnapshotPolicy::Bytes(threshold) => ctx.logs_since_last > 0 && ctx.bytes_since_last >= *threshold,
+            SnapshotPolicy::Interval(interval) => ctx.oldest_log_age.map_or(false, |age| age >= *interval),
+            SnapshotPolicy::Custom => C::SnapshotTrigger::should_snapshot(&ctx),
             SnapshotPolicy::Never => false,
         }
     }
 }
 
+/// The built-in backoff policy a leader uses before retrying an unreachable node.
+///
+/// It is used when [`RaftNetwork::backoff()`] returns the default [`Backoff`]. Durations are in
+/// milliseconds.
+///
+/// [`RaftNetwork::backoff()`]: crate::network::RaftNetwork::backoff
+#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum BackoffConfig {
+    /// [`ConstantBackoff`]: `constant:<delay>`.
+    Constant { delay: u64 },
+
+    /// [`ExponentialBackoff`]: `exponential:<min>:<max>`.
+    Exponential { min: u64, max: u64 },
+
+    /// [`JitteredBackoff`]: `jittered:<min>:<max>`.
+    Jittered { min: u64, max: u64 },
+
+    /// [`CircuitBreakerBackoff`]: `circuit_breaker:<threshold>:<retry_interval>:<open_duration>`.
+    CircuitBreaker {
+        threshold: u64,
+        retry_interval: u64,
+        open_duration: u64,
+    },
+}
+
+impl BackoffConfig {
+    /// Build a [`Backoff`] with the policy this config describes.
+    pub fn build(&self) -> Backoff {
+        let ms = Duration::from_millis;
+        match *self {
+            BackoffConfig::Constant { delay } => Backoff::from_policy(ConstantBackoff::new(ms(delay))),
+            BackoffConfig::Exponential { min, max } => Backoff::from_policy(ExponentialBackoff::new(ms(min), ms(max))),
+            BackoffConfig::Jittered { min, max } => Backoff::from_policy(JitteredBackoff::new(ms(min), ms(max))),
+            BackoffConfig::CircuitBreaker {
+                threshold,
+                retry_interval,
+                open_duration,
+            } => Backoff::from_policy(CircuitBreakerBackoff::new(
+                threshold,
+                ms(retry_interval),
+                ms(open_duration),
+            )),
+        }
+    }
+}
+
 /// Parse number with unit such as 5.3 KB
 fn parse_bytes_with_unit(src: &str) -> Result<u64, ConfigError> {
     let res = byte_unit::Byte::from_str(src).map_err(|e| ConfigError::InvalidNumber {
@@ -58,30 +143,220 @@ fn parse_bytes_with_unit(src: &str) -> Result<u64, ConfigError> {
 }
 
 fn parse_snapshot_policy(src: &str) -> Result<SnapshotPolicy, ConfigError> {
-    if src == "never" {
-        return Ok(SnapshotPolicy::Never);
+    let invalid = || ConfigError::InvalidSnapshotPolicy {
+        syntax: "never|custom|since_last:<num>|bytes:<size>|interval:<ms>".to_string(),
+        invalid: src.to_string(),
+    };
+
+    match src {
+        "never" => return Ok(SnapshotPolicy::Never),
+        "custom" => return Ok(SnapshotPolicy::Custom),
+        _ => {}
     }
 
-    let elts = src.split(':').collect::<Vec<_>>();
-    if elts.len() != 2 {
-        return Err(ConfigError::InvalidSnapshotPolicy {
-            syntax: "never|since_last:<num>".to_string(),
+    let Some((name, value)) = src.split_once(':') else {
+        return Err(invalid());
+    };
+
+    let parse_u64 = |x: &str| {
+        x.parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
             invalid: src.to_string(),
-        });
+            reason: e.to_string(),
+        })
+    };
+
+    let policy = match name {
+        "since_last" => SnapshotPolicy::LogsSinceLast(parse_u64(value)?),
+        "bytes" => SnapshotPolicy::Bytes(parse_bytes_with_unit(value)?),
+        "interval" => SnapshotPolicy::Interval(Duration::from_millis(parse_u64(value)?)),
+        _ => return Err(invalid()),
+    };
+    Ok(policy)
+}
+
+/// The policy to decide how many logs that are already included in the snapshot to keep, when
+/// purging logs after building a snapshot.
+///
+/// Logs that are not in the snapshot will never be purged.
+#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LogRetentionPolicy {
+    /// Keep at most this number of logs: `keep_entries:<n>`.
+    KeepEntries(u64),
+
+    /// Keep the latest logs whose total size is at most this number of bytes: `keep_bytes:<size>`.
+    ///
+    /// The size of a log is estimated with [`RaftEntry::size_hint()`]. The logs loaded from
+    /// storage when starting up are not counted.
+    ///
+    /// [`RaftEntry::size_hint()`]: crate::entry::RaftEntry::size_hint
+    KeepBytes(u64),
+
+    /// Keep the logs appended in the last this number of milliseconds: `keep_duration:<ms>`.
+    ///
+    /// The logs loaded from storage when starting up are purged along with the first logs
+    /// appended after starting up.
+    KeepDuration(u64),
+
+    /// Do not purge the logs that are not yet replicated to every follower and learner, so that a
+    /// slow node catches up without a snapshot, but keep at most `max_entries` logs for it:
+    /// `slowest_replica:<max_entries>`.
+    ///
+    /// A node that is not a leader keeps no logs that are included in the snapshot.
+    SlowestReplica { max_entries: u64 },
+}
+
+impl Default for LogRetentionPolicy {
+    fn default() -> Self {
+        LogRetentionPolicy::KeepEntries(1000)
     }
+}
+
+fn parse_log_retention_policy(src: &str) -> Result<LogRetentionPolicy, ConfigError> {
+    let invalid = || ConfigError::InvalidLogRetentionPolicy {
+        syntax: "keep_entries:<n>|keep_bytes:<size>|keep_duration:<ms>|slowest_replica:<max_entries>".to_string(),
+        invalid: src.to_string(),
+    };
+
+    let Some((name, value)) = src.split_once(':') else {
+        return Err(invalid());
+    };
 
-    if elts[0] != "since_last" {
-        return Err(ConfigError::InvalidSnapshotPolicy {
-            syntax: "never|since_last:<num>".to_string(),
+    let parse_u64 = |x: &str| {
+        x.parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
             invalid: src.to_string(),
-        });
+            reason: e.to_string(),
+        })
+    };
+
+    let policy = match name {
+        "keep_entries" => LogRetentionPolicy::KeepEntries(parse_u64(value)?),
+        "keep_bytes" => LogRetentionPolicy::KeepBytes(parse_bytes_with_unit(value)?),
+        "keep_duration" => LogRetentionPolicy::KeepDuration(parse_u64(value)?),
+        "slowest_replica" => LogRetentionPolicy::SlowestReplica {
+            max_entries: parse_u64(value)?,
+        },
+        _ => return Err(invalid()),
+    };
+    Ok(policy)
+}
+
+/// What to do when a corrupted log entry is found at startup.
+///
+/// See [`LogCorruption`](`crate::LogCorruption`).
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LogCorruptionPolicy {
+    /// Fail to start with the corruption error, and leave the storage for the operator to repair.
+    #[default]
+    Halt,
+
+    /// Truncate the log since the first corrupted entry and start with the entries before it.
+    ///
+    /// The truncated entries are replicated again from the leader. Note that if a truncated entry
+    /// is committed, a quorum of the other nodes must still have it, otherwise it is lost.
+    TruncateToLastValid,
+}
+
+fn parse_log_corruption_policy(src: &str) -> Result<LogCorruptionPolicy, ConfigError> {
+    match src {
+        "halt" => Ok(LogCorruptionPolicy::Halt),
+        "truncate" => Ok(LogCorruptionPolicy::TruncateToLastValid),
+        _ => Err(ConfigError::InvalidLogCorruptionPolicy {
+            syntax: "halt|truncate".to_string(),
+            invalid: src.to_string(),
+        }),
     }
+}
 
-    let n_logs = elts[1].parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
+/// What to do when the log storage or the state machine returns an error.
+///
+/// The error is handled the same way no matter which storage returns it.
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum ErrorPolicy {
+    /// Shut down the node, and surface the error as a fatal state in
+    /// [`RaftMetrics::running_state`](`crate::RaftMetrics::running_state`): `shutdown`.
+    #[default]
+    Shutdown,
+
+    /// Panic with the error, e.g., to let a supervisor restart the process: `panic`.
+    Panic,
+
+    /// Step down and restart the node from the data in storage after a delay:
+    /// `retry:<max_retries>:<min_ms>:<max_ms>`.
+    ///
+    /// The node quits leadership, fails the pending client requests, waits, then reloads the
+    /// vote, logs and the state machine state from storage and starts over as a follower, as if the
+    /// process is restarted. The delay grows exponentially from `min` to `max` milliseconds.
+    ///
+    /// The node shuts down as with [`ErrorPolicy::Shutdown`] after `max_retries` restarts in a row.
+    /// Restarts are counted as in a row if the node runs less than `max` milliseconds between
+    /// them. While the node is restarting, the error is reported in
+    /// [`RaftMetrics::running_state`](`crate::RaftMetrics::running_state`).
+    StepDownAndRetry { max_retries: u64, min: u64, max: u64 },
+}
+
+fn parse_error_policy(src: &str) -> Result<ErrorPolicy, ConfigError> {
+    let invalid = || ConfigError::InvalidErrorPolicy {
+        syntax: "shutdown|panic|retry:<max_retries>:<min_ms>:<max_ms>".to_string(),
         invalid: src.to_string(),
-        reason: e.to_string(),
-    })?;
-    Ok(SnapshotPolicy::LogsSinceLast(n_logs))
+    };
+
+    let elts = src.split(':').collect::<Vec<_>>();
+    let nums = elts[1..]
+        .iter()
+        .map(|x| {
+            x.parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
+                invalid: src.to_string(),
+                reason: e.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let policy = match (elts[0], nums.as_slice()) {
+        ("shutdown", &[]) => ErrorPolicy::Shutdown,
+        ("panic", &[]) => ErrorPolicy::Panic,
+        ("retry", &[max_retries, min, max]) => ErrorPolicy::StepDownAndRetry { max_retries, min, max },
+        _ => return Err(invalid()),
+    };
+    Ok(policy)
+}
+
+fn parse_backoff_policy(src: &str) -> Result<BackoffConfig, ConfigError> {
+    let invalid = || {
+        ConfigError::InvalidBackoffPolicy {
+        syntax: "constant:<ms>|exponential:<min_ms>:<max_ms>|jittered:<min_ms>:<max_ms>|circuit_breaker:<threshold>:<retry_ms>:<open_ms>".to_string(),
+        invalid: src.to_string(),
+    }
+    };
+
+    let elts = src.split(':').collect::<Vec<_>>();
+    let nums = elts[1..]
+        .iter()
+        .map(|x| {
+            x.parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
+                invalid: src.to_string(),
+                reason: e.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let policy = match (elts[0], nums.as_slice()) {
+        ("constant", &[delay]) => BackoffConfig::Constant { delay },
+        ("exponential", &[min, max]) => BackoffConfig::Exponential { min, max },
+        ("jittered", &[min, max]) => BackoffConfig::Jittered { min, max },
+        ("circuit_breaker", &[threshold, retry_interval, open_duration]) => BackoffConfig::CircuitBreaker {
+            threshold,
+            retry_interval,
+            open_duration,
+        },
+        _ => return Err(invalid()),
+    };
+    Ok(policy)
 }
 
 /// The runtime configuration for a Raft node.
@@ -127,9 +402,172 @@ pub struct Config {
     #[clap(long, default_value = "50")]
     pub heartbeat_interval: u64,
 
+    /// The timeout for a vote RPC, in milliseconds.
+    ///
+    /// A vote should fail fast, so that a candidate does not wait for an unresponsive node.
+    /// It is `election_timeout_min` if set to `0`, which is the default.
+    /// It can be changed at runtime with
+    /// [`RuntimeConfigHandle::vote_timeout()`](`crate::raft::RuntimeConfigHandle::vote_timeout`).
+    #[clap(long, default_value = "0")]
+    pub vote_timeout: u64,
+
+    /// The delay in milliseconds after which a vote request is sent again to a node that has not
+    /// responded, during an election.
+    ///
+    /// The first response from a node is used and the others are ignored. In a lossy network it
+    /// elects a leader without waiting for the vote timeout of a lost request.
+    /// It is disabled if set to `0`, which is the default.
+    /// Hedging is skipped if it is not less than the vote timeout.
+    #[clap(long, default_value = "0")]
+    pub vote_hedge_delay: u64,
+
+    /// Whether to run a pre-vote round before starting an election.
+    ///
+    /// When the election timeout passes, a node first asks the voters whether they would grant
+    /// its vote, without increasing its term. It starts a real election only if a quorum would
+    /// grant it. A node that rejoins the cluster after being partitioned then does not disturb a
+    /// live leader by bumping the term.
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub enable_pre_vote: bool,
+
+    /// Whether a leader steps down if it is not acknowledged by a quorum for a while.
+    ///
+    /// If a leader has not received any append-entries response from a quorum within the
+    /// [`leader_lease`](`Self::leader_lease`), it gives up the leadership and stops accepting
+    /// writes, which could not be committed anyway. A new election is started after the election
+    /// timeout.
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub enable_check_quorum: bool,
+
+    /// Whether a newly elected leader appends a blank log entry, which is the default.
+    ///
+    /// The blank entry commits the entries of previous terms as soon as a leader is elected, and
+    /// establishes the leadership for linearizable reads. If it is disabled, as some classic Raft
+    /// implementations do, the entries of previous terms are committed only after the first entry
+    /// proposed by this leader is committed. A blank entry is still appended when it is needed:
+    /// when the leader is elected if the last membership config is not committed, since a
+    /// membership change is refused until it is; and upon the first linearizable read if the
+    /// leader has not proposed any entry yet.
+    ///
+    /// It can be changed at runtime with
+    /// [`RuntimeConfigHandle::leader_blank_entry`](`crate::raft::RuntimeConfigHandle::leader_blank_entry`).
+    #[clap(long,
+           default_value_t = true,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub leader_blank_entry: bool,
+
+    /// Whether a node that has heard from a leader rejects vote requests within the
+    /// [`leader_lease`](`Self::leader_lease`), which is the default.
+    ///
+    /// If it is disabled, a vote request with a greater term is granted at any time, as in
+    /// classic Raft, so that a candidate can take over a live leader. A leader then holds no
+    /// lease, and [`ReadPolicy::LeaseRead`](`crate::raft::ReadPolicy::LeaseRead`) always falls back
+    /// to a heartbeat round.
+    ///
+    /// It can be changed at runtime with
+    /// [`RuntimeConfigHandle::reject_vote_within_lease`](`crate::raft::RuntimeConfigHandle::reject_vote_within_lease`).
+    #[clap(long,
+           default_value_t = true,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub reject_vote_within_lease: bool,
+
+    /// Whether a node grants at most one vote per term, as in classic Raft; disabled by default.
+    ///
+    /// By default, a vote of a candidate with the same term and a greater node id can replace the
+    /// vote a node has granted, which lets a candidate be elected without increasing the term.
+    /// Enable it when a cluster has to interoperate with components that expect at most one
+    /// candidate per term, e.g., during a migration. It is always enabled with the feature flag
+    /// `single-term-leader`.
+    ///
+    /// It can be changed at runtime with
+    /// [`RuntimeConfigHandle::one_vote_per_term`](`crate::raft::RuntimeConfigHandle::one_vote_per_term`).
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub one_vote_per_term: bool,
+
+    /// The lease of a leader in milliseconds, during which a node that has heard from the leader
+    /// rejects the vote requests of other candidates.
+    ///
+    /// A follower does not grant a vote within the lease since it received the last heartbeat,
+    /// and does not start an election until the lease and its election timeout have passed, so
+    /// that a candidate partitioned for a while can not disrupt a live leader. A longer lease
+    /// keeps a leader in a jittery network, but it takes longer to elect a new leader when the
+    /// leader fails. The lease is also used by [`ReadPolicy::LeaseRead`] and
+    /// [`enable_check_quorum`](`Self::enable_check_quorum`).
+    ///
+    /// It is `election_timeout_max` if set to `0`, which is the default.
+    ///
+    /// [`ReadPolicy::LeaseRead`]: `crate::raft::ReadPolicy::LeaseRead`
+    #[clap(long, default_value = "0")]
+    pub leader_lease: u64,
+
+    /// The maximum extra time in milliseconds a follower that has heard from a leader waits
+    /// before starting an election; `0` disables it, which is the default.
+    ///
+    /// When such a follower loses the leader, its election is delayed by a random time less
+    /// than this, in addition to the election timeout. The delay is drawn again for every
+    /// election. It spreads out the elections of the followers that miss heartbeats at the same
+    /// time, e.g., in a jittery network, which gives the leader more time to recover and lets
+    /// one candidate win before the others start.
+    #[clap(long, default_value = "0")]
+    pub election_backoff_max: u64,
+
+    /// Whether to tune the election timeout to the round-trip time of heartbeats.
+    ///
+    /// If enabled, a leader sends the largest smoothed RTT of its RPCs to the voters along with
+    /// every heartbeat, in the [`RpcContext`] of the request. The leader and every node that
+    /// accepts the heartbeat then pick an election timeout at random between 10 and 20 times the
+    /// RTT, within `election_timeout_min` and `election_timeout_max`. With a wide range of these
+    /// two, the same config works for a cluster in a LAN and in a WAN. The current election
+    /// timeout is reported in
+    /// [`RaftMetrics::election_timeout_ms`](`crate::RaftMetrics::election_timeout_ms`).
+    ///
+    /// [`RpcContext`]: crate::network::RpcContext
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub adaptive_election_timeout: bool,
+
+    /// The timeout for an append-entries RPC, including a heartbeat, in milliseconds.
+    ///
+    /// It is `heartbeat_interval` if set to `0`, which is the default.
+    /// It can be changed at runtime with [`RuntimeConfigHandle::append_entries_timeout()`].
+    ///
+    /// [`RuntimeConfigHandle::append_entries_timeout()`]: `crate::raft::RuntimeConfigHandle::append_entries_timeout`
+    #[clap(long, default_value = "0")]
+    pub append_entries_timeout: u64,
+
     /// The timeout for sending then installing the last snapshot segment,
     /// in millisecond. It is also used as the timeout for sending a non-last segment, if
     /// `send_snapshot_timeout` is 0.
+    ///
+    /// Installing a large snapshot may take minutes, set it according to the snapshot size.
+    /// It can be changed at runtime with [`RuntimeConfigHandle::install_snapshot_timeout()`].
+    ///
+    /// [`RuntimeConfigHandle::install_snapshot_timeout()`]: `crate::raft::RuntimeConfigHandle::install_snapshot_timeout`
     #[clap(long, default_value = "200")]
     pub install_snapshot_timeout: u64,
 
@@ -144,6 +582,113 @@ pub struct Config {
     #[clap(long, default_value = "0")]
     pub send_snapshot_timeout: u64,
 
+    /// The timeout for a client write forwarded to the leader by
+    /// [`Raft::client_write_forwarded()`](`crate::Raft::client_write_forwarded`), or for getting
+    /// the read log id from the leader by
+    /// [`Raft::ensure_linearizable_on_follower()`](`crate::Raft::ensure_linearizable_on_follower`),
+    /// in milliseconds.
+    ///
+    /// It is `election_timeout_max` if set to `0`, which is the default.
+    #[clap(long, default_value = "0")]
+    pub forward_client_write_timeout: u64,
+
+    /// The timeout for [`Raft::transfer_leadership()`](`crate::Raft::transfer_leadership`), in
+    /// milliseconds.
+    ///
+    /// The target node has to catch up with the leader and then win an election in time,
+    /// otherwise the leader resumes serving. It is 3 times `election_timeout_max` if set to `0`,
+    /// which is the default.
+    #[clap(long, default_value = "0")]
+    pub transfer_leader_timeout: u64,
+
+    /// Whether a leader hands over the leadership before stepping down, when it is removed from
+    /// the voters by a membership change.
+    ///
+    /// By default a removed leader steps down once the membership that excludes it is committed
+    /// and applied, and the remaining voters elect a new leader after an election timeout; a
+    /// leader that is kept as a learner keeps leading. If enabled, the leader transfers the
+    /// leadership to the voter that has replicated the most logs, the same way as
+    /// [`Raft::transfer_leadership()`](`crate::Raft::transfer_leadership`) does. A leader removed
+    /// from the membership steps down as soon as the transfer request is sent, or when the
+    /// transfer does not finish within the
+    /// [`transfer_leader_timeout`](`Self::transfer_leader_timeout`). A leader kept as a learner
+    /// becomes a learner when the new leader replicates to it.
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub transfer_leader_on_removal: bool,
+
+    /// Whether the leader refuses membership changes that reduce the fault tolerance of the
+    /// cluster; disabled by default.
+    ///
+    /// If enabled, [`Raft::change_membership()`] fails with an
+    /// [`UnsafeMembershipChange`] error if the change:
+    /// - drops the number of voters that can fail, counting only the voters the leader has heard
+    ///   from within the [`leader_lease`](`Self::leader_lease`), below
+    ///   [`membership_change_min_fault_tolerance`](`Self::membership_change_min_fault_tolerance`),
+    ///   unless it is already lower;
+    /// - or removes the last such node that stores data in a zone, see [`Membership::zone()`].
+    ///
+    /// [`Raft::change_membership_unguarded()`] applies a change without this check.
+    ///
+    /// [`Raft::change_membership()`]: `crate::Raft::change_membership`
+    /// [`Raft::change_membership_unguarded()`]: `crate::Raft::change_membership_unguarded`
+    /// [`UnsafeMembershipChange`]: `crate::error::UnsafeMembershipChange`
+    /// [`Membership::zone()`]: `crate::Membership::zone`
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub membership_change_guard: bool,
+
+    /// The minimal number of voters that can fail after a membership change, checked if
+    /// [`membership_change_guard`](`Self::membership_change_guard`) is enabled.
+    #[clap(long, default_value = "1")]
+    pub membership_change_min_fault_tolerance: u64,
+
+    /// The minimum interval in milliseconds between two membership changes proposed by
+    /// [`Raft::change_membership()`]; `0` disables it, which is the default.
+    ///
+    /// A leader refuses a membership change with a [`ChangeMembershipTooFrequent`] error if it
+    /// has started another one within this interval, to keep an orchestrator that churns members
+    /// rapidly from leaving the cluster in joint configs. The second step of a change, which
+    /// leaves the joint config, and [`Raft::add_learner()`] are not limited.
+    ///
+    /// [`Raft::change_membership()`]: `crate::Raft::change_membership`
+    /// [`Raft::add_learner()`]: `crate::Raft::add_learner`
+    /// [`ChangeMembershipTooFrequent`]: `crate::error::ChangeMembershipTooFrequent`
+    #[clap(long, default_value = "0")]
+    pub membership_change_min_interval: u64,
+
+    /// The zone to keep the leader in, such as the zone that serves most of the clients; empty by
+    /// default, which prefers no zone. See [`Membership::zone()`].
+    ///
+    /// A voter out of this zone waits an extra election timeout before starting an election, and
+    /// a leader out of this zone hands over the leadership to a voter in this zone that has
+    /// caught up. A preferred zone takes precedence over the election priorities, see
+    /// [`ChangeMembers::SetPriorities`]. It should be set to the same value on every node.
+    ///
+    /// [`Membership::zone()`]: `crate::Membership::zone`
+    /// [`ChangeMembers::SetPriorities`]: `crate::ChangeMembers::SetPriorities`
+    #[clap(long, default_value = "")]
+    pub preferred_leader_zone: String,
+
+    /// The maximum clock drift between nodes during a leader lease, in milliseconds.
+    ///
+    /// A read with [`ReadPolicy::LeaseRead`] is served by the leader without a heartbeat round,
+    /// until `leader_lease - lease_read_clock_drift` after the last heartbeat acknowledged by a
+    /// quorum was sent. Set it to a value larger than the clocks of the nodes may drift apart
+    /// within the [`leader_lease`](`Self::leader_lease`).
+    ///
+    /// [`ReadPolicy::LeaseRead`]: `crate::raft::ReadPolicy::LeaseRead`
+    #[clap(long, default_value = "10")]
+    pub lease_read_clock_drift: u64,
+
     /// The maximum number of entries per payload allowed to be transmitted during replication
     ///
     /// If this is too low, it will take longer for the nodes to be brought up to
@@ -151,6 +696,69 @@ pub struct Config {
     #[clap(long, default_value = "300")]
     pub max_payload_entries: u64,
 
+    /// The target round-trip time of an AppendEntries RPC carrying log entries, in milliseconds,
+    /// with which a leader adapts the number of entries per RPC to every follower.
+    ///
+    /// The number of entries grows while the RPCs are acknowledged within this time, and shrinks
+    /// when they are slower or fail, never exceeding `max_payload_entries`. The current number is
+    /// reported in [`RaftMetrics::batch_size`](`crate::RaftMetrics::batch_size`).
+    /// It is disabled if set to `0`, which is the default: every RPC sends up to
+    /// `max_payload_entries` entries.
+    #[clap(long, default_value = "0")]
+    pub adaptive_batch_target_latency: u64,
+
+    /// The time in microseconds a leader waits to coalesce client writes into one batch before
+    /// appending them to its log; `0` disables coalescing, which is the default.
+    ///
+    /// A write that arrives while a batch is open joins it, and the batch is appended and
+    /// replicated as a whole once this window expires, or once it holds
+    /// [`write_coalesce_max_entries`](`Self::write_coalesce_max_entries`) entries. On a storage
+    /// that is bound by `fsync`, this trades a bit of latency for throughput under bursts of
+    /// writes. The batch sizes are reported in
+    /// [`RaftMetrics::write_batch`](`crate::RaftMetrics::write_batch`).
+    #[clap(long, default_value = "0")]
+    pub write_coalesce_window: u64,
+
+    /// The maximum number of client writes coalesced into one batch.
+    #[clap(long, default_value = "64")]
+    pub write_coalesce_max_entries: u64,
+
+    /// The maximum bytes of log entries a leader reads ahead for every follower or learner that
+    /// is catching up; `0` disables read-ahead, which is the default.
+    ///
+    /// When a follower is far behind, the entries to send in the next AppendEntries RPC are read
+    /// from the log store while the current RPC is in flight, so that a slow disk does not add to
+    /// the replication latency. The size of log entries is estimated with
+    /// [`RaftEntry::size_hint()`].
+    ///
+    /// [`RaftEntry::size_hint()`]: crate::entry::RaftEntry::size_hint
+    #[clap(long, default_value = "0", value_parser=parse_bytes_with_unit)]
+    pub replication_read_ahead_bytes: u64,
+
+    /// The maximum number of AppendEntries RPCs a leader sends to a follower or learner without
+    /// waiting for their responses; the default `1` sends the next RPC only after the previous
+    /// one is acknowledged.
+    ///
+    /// A larger window keeps a link with a high bandwidth-delay product busy while a follower
+    /// catches up. The RPCs are sent in order on the AppendEntries stream of the target, see
+    /// [`RaftNetworkV2::append_entries_stream()`]. After an error or a conflict, the leader falls
+    /// back to sending one RPC at a time, until an RPC succeeds.
+    ///
+    /// [`RaftNetworkV2::append_entries_stream()`]: crate::network::v2::RaftNetworkV2::append_entries_stream
+    #[clap(long, default_value = "1")]
+    pub max_inflight_append_entries: u64,
+
+    /// The maximum bytes of log entries in the AppendEntries RPCs sent to a follower or learner
+    /// that are not acknowledged; `0` means unlimited, which is the default.
+    ///
+    /// It limits the window of
+    /// [`max_inflight_append_entries`](`Self::max_inflight_append_entries`), but one RPC is
+    /// always allowed. The size of log entries is estimated with [`RaftEntry::size_hint()`].
+    ///
+    /// [`RaftEntry::size_hint()`]: crate::entry::RaftEntry::size_hint
+    #[clap(long, default_value = "0", value_parser=parse_bytes_with_unit)]
+    pub max_inflight_bytes: u64,
+
     /// The distance behind in log replication a follower must fall before it is considered lagging
     ///
     /// A follower falls behind this index are replicated with snapshot.
@@ -161,6 +769,55 @@ pub struct Config {
     #[clap(long, default_value = "5000")]
     pub replication_lag_threshold: u64,
 
+    /// Whether an entry can be committed once a quorum of followers has persisted it, before it
+    /// is flushed to the leader's local log store.
+    ///
+    /// The leader replicates entries in parallel with flushing them locally. By default an entry
+    /// is committed only when the leader has flushed it too. If enabled, the leader is counted as
+    /// just another voter, and a leader with a slow disk does not delay the commit, as permitted
+    /// by the Raft thesis. The state machine may then apply an entry that is not yet in the
+    /// leader's log store; it is purged from the log on restart, since it is already applied.
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub commit_without_local_flush: bool,
+
+    /// The time in milliseconds a learner added with [`Raft::add_learner()`] has to keep up with
+    /// the leader before the leader automatically promotes it to a voter.
+    ///
+    /// A learner keeps up with the leader if its replication lag is not greater than
+    /// [`auto_promote_learner_lag`](`Self::auto_promote_learner_lag`).
+    /// The learners waiting to be promoted are tracked by the leader in memory, and are
+    /// discarded if the leadership changes.
+    /// It is disabled if set to `0`, which is the default.
+    ///
+    /// [`Raft::add_learner()`]: `crate::Raft::add_learner`
+    #[clap(long, default_value = "0")]
+    pub auto_promote_learner_duration: u64,
+
+    /// The maximum number of log entries a learner can fall behind the leader and still be
+    /// considered as keeping up with it, for automatic promotion.
+    #[clap(long, default_value = "10")]
+    pub auto_promote_learner_lag: u64,
+
+    /// The time in milliseconds a member has to be unreachable before the leader automatically
+    /// removes it from the membership.
+    ///
+    /// A member is unreachable if the leader has not heard from it. A voter is removed only if
+    /// the voters the leader has heard from within the leader lease still form a quorum without
+    /// it. Before the removal is proposed, a [`DeadMember`] event is sent to the
+    /// subscribers of [`Raft::subscribe_dead_members()`].
+    /// It is meant for deployments in which a failed node never comes back and is replaced by a
+    /// new one. It is disabled if set to `0`, which is the default.
+    ///
+    /// [`DeadMember`]: `crate::metrics::DeadMember`
+    /// [`Raft::subscribe_dead_members()`]: `crate::Raft::subscribe_dead_members`
+    #[clap(long, default_value = "0")]
+    pub auto_remove_dead_member_duration: u64,
+
     /// The snapshot policy to use for a Raft node.
     #[clap(
         long,
@@ -173,16 +830,203 @@ pub struct Config {
     #[clap(long, default_value = "3MiB", value_parser=parse_bytes_with_unit)]
     pub snapshot_max_chunk_size: u64,
 
+    /// Whether to compress the snapshot chunks with Zstandard when transmitting a snapshot with
+    /// the default chunked transport. It is disabled by default.
+    ///
+    /// It requires feature flag [`compression-zstd`]. Compression is negotiated for every
+    /// transmission: if the target does not acknowledge the compressed first chunk, e.g., it is
+    /// built without the feature flag, the rest of the snapshot is sent uncompressed.
+    ///
+    /// [`compression-zstd`]: crate::docs::feature_flags#feature-flag-compression-zstd
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub compress_snapshot: bool,
+
+    /// Whether to build a snapshot in a blocking thread. It is disabled by default.
+    ///
+    /// When enabled, the future returned by [`RaftSnapshotBuilder::build_snapshot_with_progress()`]
+    /// is driven to completion in a thread spawned with [`AsyncRuntime::spawn_blocking()`],
+    /// instead of in a task on the executor. Thus a builder that serializes the state machine
+    /// synchronously does not stall other tasks, such as `RaftCore` that responds to heartbeats
+    /// and votes.
+    ///
+    /// The builder can still use the timers and IO of the runtime if the blocking thread has
+    /// access to them, as a `tokio` blocking thread does. It is not supported with feature flag
+    /// `singlethreaded`, where a builder is not required to be `Send`.
+    ///
+    /// [`RaftSnapshotBuilder::build_snapshot_with_progress()`]: crate::storage::RaftSnapshotBuilder::build_snapshot_with_progress
+    /// [`AsyncRuntime::spawn_blocking()`]: crate::AsyncRuntime::spawn_blocking
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub offload_snapshot_build: bool,
+
+    /// The maximum number of snapshots to keep in the state machine; `0` means Openraft does not
+    /// delete any snapshot, which is the default.
+    ///
+    /// After a snapshot is built or installed, Openraft deletes the stale ones except the latest
+    /// this number of snapshots, with [`RaftStateMachine::list_snapshots()`] and
+    /// [`RaftStateMachine::delete_snapshot()`].
+    ///
+    /// [`RaftStateMachine::list_snapshots()`]: crate::storage::RaftStateMachine::list_snapshots
+    /// [`RaftStateMachine::delete_snapshot()`]: crate::storage::RaftStateMachine::delete_snapshot
+    #[clap(long, default_value = "0")]
+    pub max_snapshots_to_keep: u64,
+
+    /// The maximum number of snapshots a leader transmits to followers at the same time.
+    ///
+    /// Transmitting a snapshot is IO intensive; a replication stream that needs to send a
+    /// snapshot waits until a running transmission finishes when this limit is reached.
+    #[clap(long, default_value = "16")]
+    pub max_concurrent_snapshot_transmissions: u64,
+
+    /// Whether a leader asks an up-to-date follower to send a snapshot to a lagging node,
+    /// instead of sending it by itself. It is disabled by default.
+    ///
+    /// It offloads the bandwidth of the leader in a large cluster. The follower is asked with
+    /// [`RaftNetworkV2::transfer_snapshot()`]; if it is not supported or fails, the leader sends
+    /// the snapshot by itself.
+    ///
+    /// [`RaftNetworkV2::transfer_snapshot()`]: crate::network::v2::RaftNetworkV2::transfer_snapshot
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           num_args = 0..=1,
+           default_missing_value = "true"
+    )]
+    pub delegate_snapshot_transfer: bool,
+
+    /// The maximum bytes per second a leader sends to every follower or learner, including log
+    /// entries and snapshot data; `0` means unlimited, which is the default.
+    ///
+    /// It keeps snapshot transmission and catching up a new learner from saturating the network
+    /// and disturbing the application traffic. Heartbeats are never delayed. It can be overridden
+    /// for a node with [`RaftNetwork::replication_rate_limit()`].
+    ///
+    /// The size of log entries is estimated with [`RaftEntry::size_hint()`].
+    ///
+    /// [`RaftNetwork::replication_rate_limit()`]: crate::network::RaftNetwork::replication_rate_limit
+    /// [`RaftEntry::size_hint()`]: crate::entry::RaftEntry::size_hint
+    #[clap(long, default_value = "0", value_parser=parse_bytes_with_unit)]
+    pub replication_rate_limit_bytes_per_sec: u64,
+
+    /// The maximum bytes per second of a single snapshot transmission; `0` means unlimited,
+    /// which is the default.
+    ///
+    /// Together with
+    /// [`max_concurrent_snapshot_transmissions`](`Self::max_concurrent_snapshot_transmissions`)
+    /// it bounds the bandwidth a leader spends on snapshots, e.g., when several learners are
+    /// added at once. If
+    /// [`replication_rate_limit_bytes_per_sec`](`Self::replication_rate_limit_bytes_per_sec`)
+    /// is also set, the lower one applies to a snapshot.
+    #[clap(long, default_value = "0", value_parser=parse_bytes_with_unit)]
+    pub snapshot_transmission_rate_limit_bytes_per_sec: u64,
+
+    /// The time in milliseconds the replication lag of a follower or learner has to keep growing
+    /// before the leader quarantines it; `0` disables quarantine, which is the default.
+    ///
+    /// A follower whose lag keeps growing, e.g., because of a dying disk or long GC pauses, can
+    /// not keep up with the leader however much is sent to it. A quarantined target still
+    /// receives heartbeats, but log entries are sent to it one AppendEntries RPC at a time,
+    /// without read-ahead, and at most at
+    /// [`quarantine_rate_limit_bytes_per_sec`](`Self::quarantine_rate_limit_bytes_per_sec`).
+    /// It is released once it catches up with the leader. The quarantined targets are reported
+    /// in [`RaftMetrics::quarantined`](`crate::RaftMetrics::quarantined`).
+    #[clap(long, default_value = "0")]
+    pub quarantine_lag_growth_duration: u64,
+
+    /// The maximum bytes per second a leader sends to a quarantined follower or learner,
+    /// including log entries and snapshot data; `0` means no limit other than
+    /// [`replication_rate_limit_bytes_per_sec`](`Self::replication_rate_limit_bytes_per_sec`),
+    /// which is the default.
+    #[clap(long, default_value = "0", value_parser=parse_bytes_with_unit)]
+    pub quarantine_rate_limit_bytes_per_sec: u64,
+
+    /// The policy of waiting before retrying a node that returns an `Unreachable` error.
+    ///
+    /// It is used unless [`RaftNetwork::backoff()`] returns its own [`Backoff`]. The syntax is
+    /// one of:
+    /// - `constant:<ms>`,
+    /// - `exponential:<min_ms>:<max_ms>`,
+    /// - `jittered:<min_ms>:<max_ms>`,
+    /// - `circuit_breaker:<threshold>:<retry_ms>:<open_ms>`.
+    ///
+    /// [`RaftNetwork::backoff()`]: crate::network::RaftNetwork::backoff
+    #[clap(long, default_value = "constant:500", value_parser=parse_backoff_policy)]
+    pub backoff_policy: BackoffConfig,
+
     /// The maximum number of logs to keep that are already included in **snapshot**.
     ///
     /// Logs that are not in snapshot will never be purged.
+    #[deprecated(
+        since = "0.10.0",
+        note = "It is ignored; use `log_retention_policy` with `LogRetentionPolicy::KeepEntries` instead"
+    )]
     #[clap(long, default_value = "1000")]
     pub max_in_snapshot_log_to_keep: u64,
 
+    /// The policy to decide which logs that are already included in the snapshot to keep. The
+    /// syntax is one of:
+    /// - `keep_entries:<n>`,
+    /// - `keep_bytes:<size>`,
+    /// - `keep_duration:<ms>`,
+    /// - `slowest_replica:<max_entries>`.
+    ///
+    /// The current state of it is reported in
+    /// [`RaftMetrics::log_retention`](`crate::RaftMetrics::log_retention`).
+    #[clap(long, default_value = "keep_entries:1000", value_parser=parse_log_retention_policy)]
+    pub log_retention_policy: LogRetentionPolicy,
+
     /// The minimal number of applied logs to purge in a batch.
     #[clap(long, default_value = "1")]
     pub purge_batch_size: u64,
 
+    /// What to do when a log entry is found corrupted when starting up: `halt` to fail to start,
+    /// or `truncate` to truncate the log since the corrupted entry.
+    ///
+    /// The log entries that are not yet applied are verified by reading them when starting up.
+    #[clap(long, default_value = "halt", value_parser=parse_log_corruption_policy)]
+    pub log_corruption_policy: LogCorruptionPolicy,
+
+    /// What to do when the log storage or the state machine returns an error:
+    /// - `shutdown`: shut down the node,
+    /// - `panic`: panic,
+    /// - `retry:<max_retries>:<min_ms>:<max_ms>`: step down and restart from storage.
+    ///
+    /// See [`ErrorPolicy`].
+    #[clap(long, default_value = "shutdown", value_parser=parse_error_policy)]
+    pub error_policy: ErrorPolicy,
+
+    /// The maximum number of API requests, such as client writes, that are queued and waiting for
+    /// `RaftCore` to process.
+    ///
+    /// When the queue is full, a caller of [`Raft`](`crate::Raft`) API waits until there is room,
+    /// instead of queuing unlimited requests in memory.
+    #[clap(long, default_value = "65536")]
+    pub api_channel_size: u64,
+
+    /// The number of applied log ids buffered for each subscriber of
+    /// [`Raft::subscribe_applied`](`crate::Raft::subscribe_applied`).
+    ///
+    /// A subscriber that falls further behind loses the oldest log ids.
+    #[clap(long, default_value = "1024")]
+    pub applied_channel_size: u64,
+
+    /// The number of the latest committed membership configs kept in memory for
+    /// [`Raft::membership_history`](`crate::Raft::membership_history`).
+    ///
+    /// The history is not persisted: it starts with the committed config loaded on startup.
+    /// It is disabled if set to `0`.
+    #[clap(long, default_value = "16")]
+    pub membership_history_size: u64,
+
     /// Enable or disable tick.
     ///
     /// If ticking is disabled, timeout based events are all disabled:
@@ -190,7 +1034,15 @@ pub struct Config {
     /// and a leader won't send heartbeat.
     ///
     /// This flag is mainly used for test, or to build a consensus system that does not depend on
-    /// wall clock. The value of this config is evaluated as follow:
+    /// wall clock. In such a case the application drives the timers by calling
+    /// [`Raft::tick()`](`crate::Raft::tick`) from its own scheduler.
+    ///
+    /// If it is `false`, openraft spawns no timer task: neither the tick task nor the heartbeat
+    /// worker of every replication target, and a leader sends heartbeats with the replication
+    /// stream when it is ticked. It can not be enabled later with
+    /// [`RuntimeConfigHandle::tick()`](`crate::raft::RuntimeConfigHandle::tick`).
+    ///
+    /// The value of this config is evaluated as follow:
     /// - being absent: true
     /// - `--enable-tick`: true
     /// - `--enable-tick=true`: true
@@ -233,6 +1085,11 @@ pub struct Config {
 pub(crate) struct RuntimeConfig {
     pub(crate) enable_heartbeat: AtomicBool,
     pub(crate) enable_elect: AtomicBool,
+
+    /// RPC timeouts in milliseconds.
+    pub(crate) vote_timeout: AtomicU64,
+    pub(crate) append_entries_timeout: AtomicU64,
+    pub(crate) install_snapshot_timeout: AtomicU64,
 }
 
 impl RuntimeConfig {
@@ -240,8 +1097,21 @@ impl RuntimeConfig {
         Self {
             enable_heartbeat: AtomicBool::from(config.enable_heartbeat),
             enable_elect: AtomicBool::from(config.enable_elect),
+            vote_timeout: AtomicU64::from(config.vote_timeout().as_millis() as u64),
+            append_entries_timeout: AtomicU64::from(config.append_entries_timeout().as_millis() as u64),
+            install_snapshot_timeout: AtomicU64::from(config.install_snapshot_timeout),
         }
     }
+
+    /// Get the current timeout for an RPC of type `rpc_type`.
+    pub(crate) fn rpc_timeout(&self, rpc_type: RPCTypes) -> Duration {
+        let ms = match rpc_type {
+            RPCTypes::Vote => &self.vote_timeout,
+            RPCTypes::AppendEntries => &self.append_entries_timeout,
+            RPCTypes::InstallSnapshot => &self.install_snapshot_timeout,
+        };
+        Duration::from_millis(ms.load(Ordering::Relaxed))
+    }
 }
 
 impl Default for Config {
@@ -256,6 +1126,115 @@ impl Config {
         RT::thread_rng().gen_range(self.election_timeout_min..self.election_timeout_max)
     }
 
+    /// Get the timeout for a vote RPC.
+    pub fn vote_timeout(&self) -> Duration {
+        if self.vote_timeout > 0 {
+            Duration::from_millis(self.vote_timeout)
+        } else {
+            Duration::from_millis(self.election_timeout_min)
+        }
+    }
+
+    /// Get the range in milliseconds of the election timeout tuned to the heartbeat round-trip
+    /// time `rtt`, if [`adaptive_election_timeout`](`Self::adaptive_election_timeout`) is enabled.
+    ///
+    /// It is `[10 * rtt, 20 * rtt]`, bounded by `election_timeout_min` and `election_timeout_max`.
+    pub fn adaptive_election_timeout_range(&self, rtt: Duration) -> (u64, u64) {
+        let rtt_ms = rtt.as_micros().div_ceil(1_000) as u64;
+
+        let low = std::cmp::min(
+            std::cmp::max(rtt_ms.saturating_mul(10), self.election_timeout_min),
+            self.election_timeout_max,
+        );
+        let high = std::cmp::min(low.saturating_mul(2), self.election_timeout_max);
+        (low, high)
+    }
+
+    /// Get the lease of a leader.
+    pub fn leader_lease(&self) -> Duration {
+        if self.leader_lease > 0 {
+            Duration::from_millis(self.leader_lease)
+        } else {
+            Duration::from_millis(self.election_timeout_max)
+        }
+    }
+
+    /// Generate a new random extra delay before an election of a follower that has heard from a
+    /// leader, within `[0, election_backoff_max)`.
+    pub fn new_rand_election_backoff<RT: AsyncRuntime>(&self) -> Duration {
+        if self.election_backoff_max == 0 {
+            return Duration::default();
+        }
+        Duration::from_millis(RT::thread_rng().gen_range(0..self.election_backoff_max))
+    }
+
+    /// Get the delay before re-sending a vote request to a slow node, if vote hedging is enabled.
+    pub fn vote_hedge_delay(&self) -> Option<Duration> {
+        if self.vote_hedge_delay > 0 {
+            Some(Duration::from_millis(self.vote_hedge_delay))
+        } else {
+            None
+        }
+    }
+
+    /// Get the time a learner has to keep up with the leader before being promoted to a voter, or
+    /// `None` if automatic promotion is disabled.
+    pub fn auto_promote_learner_duration(&self) -> Option<Duration> {
+        if self.auto_promote_learner_duration > 0 {
+            Some(Duration::from_millis(self.auto_promote_learner_duration))
+        } else {
+            None
+        }
+    }
+
+    /// Get the time a member has to be unreachable before being removed from the membership, or
+    /// `None` if automatic removal is disabled.
+    pub fn auto_remove_dead_member_duration(&self) -> Option<Duration> {
+        if self.auto_remove_dead_member_duration > 0 {
+            Some(Duration::from_millis(self.auto_remove_dead_member_duration))
+        } else {
+            None
+        }
+    }
+
+    /// Get the time the replication lag of a target has to keep growing before it is quarantined,
+    /// or `None` if quarantine is disabled.
+    pub fn quarantine_lag_growth_duration(&self) -> Option<Duration> {
+        if self.quarantine_lag_growth_duration > 0 {
+            Some(Duration::from_millis(self.quarantine_lag_growth_duration))
+        } else {
+            None
+        }
+    }
+
+    /// Get the time a leader waits to coalesce client writes, or `None` if coalescing is
+    /// disabled.
+    pub fn write_coalesce_window(&self) -> Option<Duration> {
+        if self.write_coalesce_window > 0 {
+            Some(Duration::from_micros(self.write_coalesce_window))
+        } else {
+            None
+        }
+    }
+
+    /// Get the target latency of adaptive batching, or `None` if it is disabled.
+    pub fn adaptive_batch_target_latency(&self) -> Option<Duration> {
+        if self.adaptive_batch_target_latency > 0 {
+            Some(Duration::from_millis(self.adaptive_batch_target_latency))
+        } else {
+            None
+        }
+    }
+
+    /// Get the timeout for an append-entries RPC.
+    pub fn append_entries_timeout(&self) -> Duration {
+        if self.append_entries_timeout > 0 {
+            Duration::from_millis(self.append_entries_timeout)
+        } else {
+            Duration::from_millis(self.heartbeat_interval)
+        }
+    }
+
     /// Get the timeout for sending and installing the last snapshot segment.
     pub fn install_snapshot_timeout(&self) -> Duration {
         Duration::from_millis(self.install_snapshot_timeout)
@@ -275,6 +1254,42 @@ impl Config {
         }
     }
 
+    /// Get the timeout for a client write forwarded to the leader.
+    pub fn forward_client_write_timeout(&self) -> Duration {
+        if self.forward_client_write_timeout > 0 {
+            Duration::from_millis(self.forward_client_write_timeout)
+        } else {
+            Duration::from_millis(self.election_timeout_max)
+        }
+    }
+
+    /// Get the minimum interval between two membership changes, or `None` if it is disabled.
+    pub fn membership_change_min_interval(&self) -> Option<Duration> {
+        if self.membership_change_min_interval > 0 {
+            Some(Duration::from_millis(self.membership_change_min_interval))
+        } else {
+            None
+        }
+    }
+
+    /// Get the zone to keep the leader in, or `None` if no zone is preferred.
+    pub fn preferred_leader_zone(&self) -> Option<&str> {
+        if self.preferred_leader_zone.is_empty() {
+            None
+        } else {
+            Some(&self.preferred_leader_zone)
+        }
+    }
+
+    /// Get the timeout for transferring the leadership to another node.
+    pub fn transfer_leader_timeout(&self) -> Duration {
+        if self.transfer_leader_timeout > 0 {
+            Duration::from_millis(self.transfer_leader_timeout)
+        } else {
+            Duration::from_millis(self.election_timeout_max * 3)
+        }
+    }
+
     /// Build a `Config` instance from a series of command line arguments.
     ///
     /// The first element in `args` must be the application name.
@@ -302,10 +1317,45 @@ impl Config {
             });
         }
 
+        if self.leader_lease > 0 && self.leader_lease <= self.heartbeat_interval {
+            return Err(ConfigError::LeaderLeaseLEHeartBeat {
+                leader_lease: self.leader_lease,
+                heartbeat_interval: self.heartbeat_interval,
+            });
+        }
+
         if self.max_payload_entries == 0 {
             return Err(ConfigError::MaxPayloadIs0);
         }
 
+        if self.write_coalesce_max_entries == 0 {
+            return Err(ConfigError::WriteCoalesceMaxEntriesIs0);
+        }
+
+        if self.max_inflight_append_entries == 0 {
+            return Err(ConfigError::MaxInflightAppendEntriesIs0);
+        }
+
+        if self.api_channel_size == 0 {
+            return Err(ConfigError::ApiChannelSizeIs0);
+        }
+
+        if self.applied_channel_size == 0 {
+            return Err(ConfigError::AppliedChannelSizeIs0);
+        }
+
+        if self.max_concurrent_snapshot_transmissions == 0 {
+            return Err(ConfigError::MaxConcurrentSnapshotTransmissionsIs0);
+        }
+
+        if self.compress_snapshot && !cfg!(feature = "compression-zstd") {
+            return Err(ConfigError::CompressSnapshotWithoutZstd);
+        }
+
+        if self.offload_snapshot_build && cfg!(feature = "singlethreaded") {
+            return Err(ConfigError::OffloadSnapshotBuildInSingleThreaded);
+        }
+
         Ok(self)
     }
 }
@@ -1,8 +1,20 @@
 use core::time::Duration;
+use std::sync::atomic::Ordering;
 
 use crate::config::error::ConfigError;
+use crate::config::ErrorPolicy;
+use crate::config::LogCorruptionPolicy;
+use crate::config::LogRetentionPolicy;
+use crate::config::RuntimeConfig;
+use crate::engine::testing::UTConfig;
+use crate::network::RPCTypes;
+use crate::testing::log_id;
+use crate::BackoffConfig;
 use crate::Config;
+use crate::RaftState;
 use crate::SnapshotPolicy;
+use crate::TokioInstant;
+use crate::TokioRuntime;
 
 #[test]
 fn test_config_defaults() {
@@ -13,10 +25,111 @@ fn test_config_defaults() {
 
     assert_eq!(50, cfg.heartbeat_interval);
     assert_eq!(300, cfg.max_payload_entries);
+    assert_eq!(65536, cfg.api_channel_size);
+    assert_eq!(1024, cfg.applied_channel_size);
+    assert_eq!(16, cfg.membership_history_size);
     assert_eq!(5000, cfg.replication_lag_threshold);
 
     assert_eq!(3 * 1024 * 1024, cfg.snapshot_max_chunk_size);
+    assert_eq!(16, cfg.max_concurrent_snapshot_transmissions);
     assert_eq!(SnapshotPolicy::LogsSinceLast(5000), cfg.snapshot_policy);
+    assert_eq!(BackoffConfig::Constant { delay: 500 }, cfg.backoff_policy);
+    assert_eq!(LogRetentionPolicy::KeepEntries(1000), cfg.log_retention_policy);
+    assert_eq!(LogCorruptionPolicy::Halt, cfg.log_corruption_policy);
+    assert_eq!(ErrorPolicy::Shutdown, cfg.error_policy);
+
+    assert_eq!(Duration::from_millis(cfg.election_timeout_min), cfg.vote_timeout());
+    assert_eq!(Duration::from_millis(50), cfg.append_entries_timeout());
+    assert_eq!(Duration::from_millis(200), cfg.install_snapshot_timeout());
+    assert_eq!(None, cfg.vote_hedge_delay(), "vote hedging is disabled by default");
+    assert_eq!(Duration::from_millis(900), cfg.transfer_leader_timeout());
+    assert!(!cfg.enable_pre_vote, "pre-vote is disabled by default");
+    assert!(!cfg.enable_check_quorum, "check-quorum is disabled by default");
+    assert!(
+        !cfg.transfer_leader_on_removal,
+        "a removed leader steps down without transfer by default"
+    );
+    assert!(
+        !cfg.adaptive_election_timeout,
+        "adaptive election timeout is disabled by default"
+    );
+    assert_eq!(
+        Duration::from_millis(cfg.election_timeout_max),
+        cfg.leader_lease(),
+        "leader lease is election_timeout_max by default"
+    );
+    assert_eq!(
+        Duration::default(),
+        cfg.new_rand_election_backoff::<TokioRuntime>(),
+        "election backoff is disabled by default"
+    );
+    assert_eq!(
+        None,
+        cfg.adaptive_batch_target_latency(),
+        "adaptive batching is disabled by default"
+    );
+    assert_eq!(0, cfg.replication_rate_limit_bytes_per_sec, "unlimited by default");
+    assert_eq!(0, cfg.max_snapshots_to_keep, "snapshots are not deleted by default");
+    assert!(
+        !cfg.delegate_snapshot_transfer,
+        "snapshot transfer is not delegated by default"
+    );
+    assert!(!cfg.compress_snapshot, "snapshot is not compressed by default");
+    assert!(
+        !cfg.offload_snapshot_build,
+        "snapshot is built on the executor by default"
+    );
+    assert_eq!(
+        0, cfg.snapshot_transmission_rate_limit_bytes_per_sec,
+        "unlimited by default"
+    );
+    assert_eq!(0, cfg.replication_read_ahead_bytes, "read-ahead is disabled by default");
+    assert_eq!(1, cfg.max_inflight_append_entries, "one RPC at a time by default");
+    assert_eq!(0, cfg.max_inflight_bytes, "unlimited by default");
+    assert_eq!(
+        None,
+        cfg.quarantine_lag_growth_duration(),
+        "quarantine is disabled by default"
+    );
+    assert_eq!(0, cfg.quarantine_rate_limit_bytes_per_sec, "unlimited by default");
+    assert_eq!(None, cfg.write_coalesce_window(), "coalescing is disabled by default");
+    assert_eq!(64, cfg.write_coalesce_max_entries);
+    assert!(cfg.leader_blank_entry);
+    assert!(cfg.reject_vote_within_lease);
+    assert!(!cfg.one_vote_per_term);
+    assert!(
+        !cfg.membership_change_guard,
+        "membership change guard is disabled by default"
+    );
+    assert_eq!(1, cfg.membership_change_min_fault_tolerance);
+    assert_eq!(
+        None,
+        cfg.membership_change_min_interval(),
+        "membership changes are not rate limited by default"
+    );
+    assert_eq!(None, cfg.preferred_leader_zone(), "no zone is preferred by default");
+    assert_eq!(
+        None,
+        cfg.auto_remove_dead_member_duration(),
+        "dead members are not removed by default"
+    );
+}
+
+#[test]
+fn test_runtime_rpc_timeout() {
+    let cfg = Config {
+        vote_timeout: 10,
+        install_snapshot_timeout: 60_000,
+        ..Default::default()
+    };
+
+    let rt = RuntimeConfig::new(&cfg);
+    assert_eq!(Duration::from_millis(10), rt.rpc_timeout(RPCTypes::Vote));
+    assert_eq!(Duration::from_millis(50), rt.rpc_timeout(RPCTypes::AppendEntries));
+    assert_eq!(Duration::from_millis(60_000), rt.rpc_timeout(RPCTypes::InstallSnapshot));
+
+    rt.append_entries_timeout.store(20, Ordering::Relaxed);
+    assert_eq!(Duration::from_millis(20), rt.rpc_timeout(RPCTypes::AppendEntries));
 }
 
 #[test]
@@ -54,39 +167,134 @@ fn test_build() -> anyhow::Result<()> {
         "--election-timeout-min=10",
         "--election-timeout-max=20",
         "--heartbeat-interval=5",
+        "--vote-timeout=197",
+        "--append-entries-timeout=198",
+        "--vote-hedge-delay=196",
         "--send-snapshot-timeout=199",
         "--install-snapshot-timeout=200",
+        "--forward-client-write-timeout=206",
+        "--transfer-leader-timeout=212",
+        "--transfer-leader-on-removal",
+        "--lease-read-clock-drift=213",
+        "--leader-lease=217",
+        "--election-backoff-max=218",
+        "--adaptive-election-timeout",
         "--max-payload-entries=201",
+        "--adaptive-batch-target-latency=211",
+        "--replication-read-ahead-bytes=4MiB",
+        "--max-inflight-append-entries=216",
+        "--max-inflight-bytes=8MiB",
         "--snapshot-policy=since_last:202",
         "--replication-lag-threshold=203",
+        "--auto-promote-learner-duration=214",
+        "--auto-promote-learner-lag=215",
+        "--auto-remove-dead-member-duration=222",
         "--snapshot-max-chunk-size=204",
+        "--max-snapshots-to-keep=3",
         "--max-in-snapshot-log-to-keep=205",
+        "--log-retention-policy=keep_bytes:2KiB",
         "--purge-batch-size=207",
+        "--log-corruption-policy=truncate",
+        "--error-policy=panic",
+        "--api-channel-size=208",
+        "--max-concurrent-snapshot-transmissions=209",
+        "--delegate-snapshot-transfer",
+        "--commit-without-local-flush",
+        "--applied-channel-size=210",
+        "--membership-history-size=223",
+        "--replication-rate-limit-bytes-per-sec=1MiB",
+        "--snapshot-transmission-rate-limit-bytes-per-sec=512KiB",
+        "--quarantine-lag-growth-duration=219",
+        "--quarantine-rate-limit-bytes-per-sec=64KiB",
+        "--write-coalesce-window=220",
+        "--write-coalesce-max-entries=221",
+        "--leader-blank-entry=false",
+        "--reject-vote-within-lease=false",
+        "--one-vote-per-term",
+        "--membership-change-guard",
+        "--membership-change-min-fault-tolerance=2",
+        "--membership-change-min-interval=3000",
+        "--preferred-leader-zone=us-east-1a",
     ])?;
 
     assert_eq!("bar", config.cluster_name);
     assert_eq!(10, config.election_timeout_min);
     assert_eq!(20, config.election_timeout_max);
     assert_eq!(5, config.heartbeat_interval);
+    assert_eq!(197, config.vote_timeout);
+    assert_eq!(198, config.append_entries_timeout);
+    assert_eq!(196, config.vote_hedge_delay);
 
     #[allow(deprecated)]
     {
         assert_eq!(199, config.send_snapshot_timeout);
+        assert_eq!(205, config.max_in_snapshot_log_to_keep);
     }
     assert_eq!(200, config.install_snapshot_timeout);
+    assert_eq!(206, config.forward_client_write_timeout);
+    assert_eq!(212, config.transfer_leader_timeout);
+    assert_eq!(213, config.lease_read_clock_drift);
+    assert_eq!(217, config.leader_lease);
+    assert_eq!(218, config.election_backoff_max);
+    assert!(config.adaptive_election_timeout);
+    assert!(config.transfer_leader_on_removal);
     assert_eq!(201, config.max_payload_entries);
+    assert_eq!(211, config.adaptive_batch_target_latency);
+    assert_eq!(4 * 1024 * 1024, config.replication_read_ahead_bytes);
+    assert_eq!(216, config.max_inflight_append_entries);
+    assert_eq!(8 * 1024 * 1024, config.max_inflight_bytes);
     assert_eq!(SnapshotPolicy::LogsSinceLast(202), config.snapshot_policy);
     assert_eq!(203, config.replication_lag_threshold);
+    assert_eq!(214, config.auto_promote_learner_duration);
+    assert_eq!(215, config.auto_promote_learner_lag);
+    assert_eq!(222, config.auto_remove_dead_member_duration);
     assert_eq!(204, config.snapshot_max_chunk_size);
-    assert_eq!(205, config.max_in_snapshot_log_to_keep);
+    assert_eq!(3, config.max_snapshots_to_keep);
+    assert!(config.delegate_snapshot_transfer);
+    assert!(config.commit_without_local_flush);
+    assert_eq!(LogRetentionPolicy::KeepBytes(2048), config.log_retention_policy);
     assert_eq!(207, config.purge_batch_size);
+    assert_eq!(LogCorruptionPolicy::TruncateToLastValid, config.log_corruption_policy);
+    assert_eq!(ErrorPolicy::Panic, config.error_policy);
+    assert_eq!(208, config.api_channel_size);
+    assert_eq!(209, config.max_concurrent_snapshot_transmissions);
+    assert_eq!(210, config.applied_channel_size);
+    assert_eq!(223, config.membership_history_size);
+    assert_eq!(1024 * 1024, config.replication_rate_limit_bytes_per_sec);
+    assert_eq!(512 * 1024, config.snapshot_transmission_rate_limit_bytes_per_sec);
+    assert_eq!(219, config.quarantine_lag_growth_duration);
+    assert_eq!(64 * 1024, config.quarantine_rate_limit_bytes_per_sec);
+    assert_eq!(220, config.write_coalesce_window);
+    assert_eq!(221, config.write_coalesce_max_entries);
+    assert!(!config.leader_blank_entry);
+    assert!(!config.reject_vote_within_lease);
+    assert!(config.one_vote_per_term);
+    assert!(config.membership_change_guard);
+    assert_eq!(2, config.membership_change_min_fault_tolerance);
+    assert_eq!(
+        Some(Duration::from_millis(3000)),
+        config.membership_change_min_interval()
+    );
+    assert_eq!(Some("us-east-1a"), config.preferred_leader_zone());
 
     // Test config methods
     #[allow(deprecated)]
     {
         let mut c = config;
+        assert_eq!(Duration::from_millis(197), c.vote_timeout());
+        assert_eq!(Some(Duration::from_millis(196)), c.vote_hedge_delay());
+        assert_eq!(Duration::from_millis(198), c.append_entries_timeout());
         assert_eq!(Duration::from_millis(199), c.send_snapshot_timeout());
         assert_eq!(Duration::from_millis(200), c.install_snapshot_timeout());
+        assert_eq!(Duration::from_millis(206), c.forward_client_write_timeout());
+        assert_eq!(Duration::from_millis(212), c.transfer_leader_timeout());
+        assert_eq!(Some(Duration::from_millis(211)), c.adaptive_batch_target_latency());
+        assert_eq!(Some(Duration::from_millis(214)), c.auto_promote_learner_duration());
+        assert_eq!(Some(Duration::from_millis(222)), c.auto_remove_dead_member_duration());
+        assert_eq!(Some(Duration::from_millis(219)), c.quarantine_lag_growth_duration());
+        assert_eq!(Some(Duration::from_micros(220)), c.write_coalesce_window());
+        assert_eq!(Duration::from_millis(217), c.leader_lease());
+        assert!(c.new_rand_election_backoff::<TokioRuntime>() < Duration::from_millis(218));
 
         c.send_snapshot_timeout = 0;
         assert_eq!(
@@ -98,6 +306,129 @@ fn test_build() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_adaptive_election_timeout_range() {
+    let config = Config {
+        election_timeout_min: 100,
+        election_timeout_max: 3_000,
+        ..Default::default()
+    };
+
+    let ms = Duration::from_millis;
+
+    assert_eq!((100, 200), config.adaptive_election_timeout_range(ms(1)), "LAN");
+    assert_eq!((400, 800), config.adaptive_election_timeout_range(ms(40)), "WAN");
+    assert_eq!(
+        (110, 220),
+        config.adaptive_election_timeout_range(Duration::from_micros(10_001)),
+        "round up to millisecond"
+    );
+    assert_eq!((2_000, 3_000), config.adaptive_election_timeout_range(ms(200)));
+    assert_eq!((3_000, 3_000), config.adaptive_election_timeout_range(ms(1_000)));
+}
+
+#[test]
+fn test_invalid_leader_lease() {
+    let config = Config {
+        heartbeat_interval: 50,
+        leader_lease: 50,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    assert_eq!(res.unwrap_err(), ConfigError::LeaderLeaseLEHeartBeat {
+        leader_lease: 50,
+        heartbeat_interval: 50
+    });
+}
+
+#[test]
+fn test_invalid_max_inflight_append_entries() {
+    let config = Config {
+        max_inflight_append_entries: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    assert_eq!(res.unwrap_err(), ConfigError::MaxInflightAppendEntriesIs0);
+}
+
+#[test]
+fn test_invalid_write_coalesce_max_entries() {
+    let config = Config {
+        write_coalesce_max_entries: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    assert_eq!(res.unwrap_err(), ConfigError::WriteCoalesceMaxEntriesIs0);
+}
+
+#[test]
+fn test_invalid_api_channel_size() {
+    let config = Config {
+        api_channel_size: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    assert_eq!(res.unwrap_err(), ConfigError::ApiChannelSizeIs0);
+}
+
+#[test]
+fn test_invalid_applied_channel_size() {
+    let config = Config {
+        applied_channel_size: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    assert_eq!(res.unwrap_err(), ConfigError::AppliedChannelSizeIs0);
+}
+
+#[test]
+fn test_invalid_max_concurrent_snapshot_transmissions() {
+    let config = Config {
+        max_concurrent_snapshot_transmissions: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    assert_eq!(res.unwrap_err(), ConfigError::MaxConcurrentSnapshotTransmissionsIs0);
+}
+
+#[test]
+fn test_config_compress_snapshot() -> anyhow::Result<()> {
+    let res = Config::build(&["foo", "--compress-snapshot"]);
+
+    if cfg!(feature = "compression-zstd") {
+        assert!(res?.compress_snapshot);
+    } else {
+        assert_eq!(res.unwrap_err(), ConfigError::CompressSnapshotWithoutZstd);
+    }
+
+    let config = Config::build(&["foo", "--compress-snapshot=false"])?;
+    assert!(!config.compress_snapshot);
+
+    Ok(())
+}
+
+#[test]
+fn test_config_offload_snapshot_build() -> anyhow::Result<()> {
+    let res = Config::build(&["foo", "--offload-snapshot-build"]);
+
+    if cfg!(feature = "singlethreaded") {
+        assert_eq!(res.unwrap_err(), ConfigError::OffloadSnapshotBuildInSingleThreaded);
+    } else {
+        assert!(res?.offload_snapshot_build);
+    }
+
+    let config = Config::build(&["foo", "--offload-snapshot-build=false"])?;
+    assert!(!config.offload_snapshot_build);
+
+    Ok(())
+}
+
 #[test]
 fn test_config_snapshot_policy() -> anyhow::Result<()> {
     let config = Config::build(&["foo", "--snapshot-policy=never"])?;
@@ -106,9 +437,163 @@ fn test_config_snapshot_policy() -> anyhow::Result<()> {
     let config = Config::build(&["foo", "--snapshot-policy=since_last:3"])?;
     assert_eq!(SnapshotPolicy::LogsSinceLast(3), config.snapshot_policy);
 
+    let config = Config::build(&["foo", "--snapshot-policy=bytes:1KiB"])?;
+    assert_eq!(SnapshotPolicy::Bytes(1024), config.snapshot_policy);
+
+    let config = Config::build(&["foo", "--snapshot-policy=interval:3000"])?;
+    assert_eq!(
+        SnapshotPolicy::Interval(Duration::from_millis(3000)),
+        config.snapshot_policy
+    );
+
+    let config = Config::build(&["foo", "--snapshot-policy=custom"])?;
+    assert_eq!(SnapshotPolicy::Custom, config.snapshot_policy);
+
     let res = Config::build(&["foo", "--snapshot-policy=bar:3"]);
     assert!(res.is_err());
 
+    let res = Config::build(&["foo", "--snapshot-policy=since_last"]);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_policy_should_snapshot() {
+    let now = TokioInstant::now();
+    let ms = Duration::from_millis;
+
+    let mut st = RaftState::<UTConfig>::default();
+    st.log_retention.append(3, 30, now - ms(1_000));
+    st.log_retention.append(6, 60, now - ms(10));
+
+    // Nothing committed
+    assert!(!SnapshotPolicy::LogsSinceLast(1).should_snapshot(&st));
+    assert!(!SnapshotPolicy::Bytes(1).should_snapshot(&st));
+    assert!(!SnapshotPolicy::Interval(ms(1)).should_snapshot(&st));
+
+    st.committed = Some(log_id(1, 0, 6));
+
+    assert!(SnapshotPolicy::LogsSinceLast(7).should_snapshot(&st));
+    assert!(!SnapshotPolicy::LogsSinceLast(8).should_snapshot(&st));
+
+    assert!(SnapshotPolicy::Bytes(90).should_snapshot(&st));
+    assert!(!SnapshotPolicy::Bytes(91).should_snapshot(&st));
+
+    assert!(SnapshotPolicy::Interval(ms(500)).should_snapshot(&st));
+    assert!(!SnapshotPolicy::Interval(ms(60_000)).should_snapshot(&st));
+
+    assert!(!SnapshotPolicy::Custom.should_snapshot(&st), "NoSnapshotTrigger");
+    assert!(!SnapshotPolicy::Never.should_snapshot(&st));
+
+    // Logs included in the snapshot are not counted
+    st.snapshot_meta.last_log_id = Some(log_id(1, 0, 3));
+
+    assert!(SnapshotPolicy::LogsSinceLast(3).should_snapshot(&st));
+    assert!(!SnapshotPolicy::LogsSinceLast(4).should_snapshot(&st));
+
+    assert!(SnapshotPolicy::Bytes(60).should_snapshot(&st));
+    assert!(!SnapshotPolicy::Bytes(61).should_snapshot(&st));
+
+    assert!(!SnapshotPolicy::Interval(ms(500)).should_snapshot(&st));
+}
+
+#[test]
+fn test_config_log_retention_policy() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--log-retention-policy=keep_entries:5"])?;
+    assert_eq!(LogRetentionPolicy::KeepEntries(5), config.log_retention_policy);
+
+    let config = Config::build(&["foo", "--log-retention-policy=keep_bytes:1KiB"])?;
+    assert_eq!(LogRetentionPolicy::KeepBytes(1024), config.log_retention_policy);
+
+    let config = Config::build(&["foo", "--log-retention-policy=keep_duration:3000"])?;
+    assert_eq!(LogRetentionPolicy::KeepDuration(3000), config.log_retention_policy);
+
+    let config = Config::build(&["foo", "--log-retention-policy=slowest_replica:100"])?;
+    assert_eq!(
+        LogRetentionPolicy::SlowestReplica { max_entries: 100 },
+        config.log_retention_policy
+    );
+
+    let res = Config::build(&["foo", "--log-retention-policy=keep_all:1"]);
+    assert!(res.is_err());
+
+    let res = Config::build(&["foo", "--log-retention-policy=keep_entries"]);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_config_log_corruption_policy() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--log-corruption-policy=halt"])?;
+    assert_eq!(LogCorruptionPolicy::Halt, config.log_corruption_policy);
+
+    let config = Config::build(&["foo", "--log-corruption-policy=truncate"])?;
+    assert_eq!(LogCorruptionPolicy::TruncateToLastValid, config.log_corruption_policy);
+
+    let res = Config::build(&["foo", "--log-corruption-policy=repair"]);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_config_error_policy() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--error-policy=shutdown"])?;
+    assert_eq!(ErrorPolicy::Shutdown, config.error_policy);
+
+    let config = Config::build(&["foo", "--error-policy=panic"])?;
+    assert_eq!(ErrorPolicy::Panic, config.error_policy);
+
+    let config = Config::build(&["foo", "--error-policy=retry:3:100:2000"])?;
+    assert_eq!(
+        ErrorPolicy::StepDownAndRetry {
+            max_retries: 3,
+            min: 100,
+            max: 2000
+        },
+        config.error_policy
+    );
+
+    let res = Config::build(&["foo", "--error-policy=retry:3"]);
+    assert!(res.is_err());
+
+    let res = Config::build(&["foo", "--error-policy=panic:1"]);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_config_backoff_policy() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--backoff-policy=constant:100"])?;
+    assert_eq!(BackoffConfig::Constant { delay: 100 }, config.backoff_policy);
+
+    let config = Config::build(&["foo", "--backoff-policy=exponential:100:5000"])?;
+    assert_eq!(
+        BackoffConfig::Exponential { min: 100, max: 5000 },
+        config.backoff_policy
+    );
+
+    let config = Config::build(&["foo", "--backoff-policy=jittered:100:5000"])?;
+    assert_eq!(BackoffConfig::Jittered { min: 100, max: 5000 }, config.backoff_policy);
+
+    let config = Config::build(&["foo", "--backoff-policy=circuit_breaker:5:100:30000"])?;
+    assert_eq!(
+        BackoffConfig::CircuitBreaker {
+            threshold: 5,
+            retry_interval: 100,
+            open_duration: 30000
+        },
+        config.backoff_policy
+    );
+
+    assert!(Config::build(&["foo", "--backoff-policy=constant"]).is_err());
+    assert!(Config::build(&["foo", "--backoff-policy=exponential:100"]).is_err());
+    assert!(Config::build(&["foo", "--backoff-policy=linear:100"]).is_err());
+    assert!(Config::build(&["foo", "--backoff-policy=constant:x"]).is_err());
+
     Ok(())
 }
 
@@ -162,3 +647,25 @@ fn test_config_enable_elect() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_config_enable_pre_vote() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--enable-pre-vote"])?;
+    assert!(config.enable_pre_vote);
+
+    let config = Config::build(&["foo", "--enable-pre-vote=false"])?;
+    assert!(!config.enable_pre_vote);
+
+    Ok(())
+}
+
+#[test]
+fn test_config_enable_check_quorum() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--enable-check-quorum"])?;
+    assert!(config.enable_check_quorum);
+
+    let config = Config::build(&["foo", "--enable-check-quorum=false"])?;
+    assert!(!config.enable_check_quorum);
+
+    Ok(())
+}
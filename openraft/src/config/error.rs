@@ -14,15 +14,51 @@ pub enum ConfigError {
     #[error("max_payload_entries must be > 0")]
     MaxPayloadIs0,
 
+    #[error("write_coalesce_max_entries must be > 0")]
+    WriteCoalesceMaxEntriesIs0,
+
+    #[error("max_inflight_append_entries must be > 0")]
+    MaxInflightAppendEntriesIs0,
+
+    #[error("api_channel_size must be > 0")]
+    ApiChannelSizeIs0,
+
+    #[error("applied_channel_size must be > 0")]
+    AppliedChannelSizeIs0,
+
+    #[error("max_concurrent_snapshot_transmissions must be > 0")]
+    MaxConcurrentSnapshotTransmissionsIs0,
+
+    #[error("compress_snapshot requires feature flag compression-zstd")]
+    CompressSnapshotWithoutZstd,
+
+    #[error("offload_snapshot_build is not supported with feature flag singlethreaded")]
+    OffloadSnapshotBuildInSingleThreaded,
+
     #[error("election_timeout_min({election_timeout_min}) must be > heartbeat_interval({heartbeat_interval})")]
     ElectionTimeoutLTHeartBeat {
         election_timeout_min: u64,
         heartbeat_interval: u64,
     },
 
+    #[error("leader_lease({leader_lease}) must be > heartbeat_interval({heartbeat_interval})")]
+    LeaderLeaseLEHeartBeat { leader_lease: u64, heartbeat_interval: u64 },
+
     #[error("snapshot policy string is invalid: '{invalid:?}' expect: '{syntax}'")]
     InvalidSnapshotPolicy { invalid: String, syntax: String },
 
+    #[error("log retention policy string is invalid: '{invalid:?}' expect: '{syntax}'")]
+    InvalidLogRetentionPolicy { invalid: String, syntax: String },
+
+    #[error("log corruption policy string is invalid: '{invalid:?}' expect: '{syntax}'")]
+    InvalidLogCorruptionPolicy { invalid: String, syntax: String },
+
+    #[error("error policy string is invalid: '{invalid:?}' expect: '{syntax}'")]
+    InvalidErrorPolicy { invalid: String, syntax: String },
+
+    #[error("backoff policy string is invalid: '{invalid:?}' expect: '{syntax}'")]
+    InvalidBackoffPolicy { invalid: String, syntax: String },
+
     #[error("{reason} when parsing {invalid:?}")]
     InvalidNumber { invalid: String, reason: String },
 }
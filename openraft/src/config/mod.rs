@@ -1,9 +1,17 @@
 #[allow(clippy::module_inception)] mod config;
 mod error;
+mod snapshot_trigger;
 
 #[cfg(test)] mod config_test;
 
+pub use config::BackoffConfig;
 pub use config::Config;
+pub use config::ErrorPolicy;
+pub use config::LogCorruptionPolicy;
+pub use config::LogRetentionPolicy;
 pub(crate) use config::RuntimeConfig;
 pub use config::SnapshotPolicy;
 pub use error::ConfigError;
+pub use snapshot_trigger::NoSnapshotTrigger;
+pub use snapshot_trigger::SnapshotTrigger;
+pub use snapshot_trigger::SnapshotTriggerContext;
@@ -0,0 +1,50 @@
+//! Application defined condition to build a snapshot.
+
+use std::time::Duration;
+
+use crate::OptionalSend;
+use crate::OptionalSync;
+
+/// The state of the logs not yet included in a snapshot, to decide whether to build one.
+///
+/// Only committed logs are counted.
+#[derive(Debug, Clone, Default)]
+#[derive(PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SnapshotTriggerContext {
+    /// The number of logs since the last snapshot.
+    pub logs_since_last: u64,
+
+    /// The total size of the logs since the last snapshot, estimated with
+    /// [`RaftEntry::size_hint()`](`crate::entry::RaftEntry::size_hint`).
+    ///
+    /// The logs loaded from storage at startup are not counted.
+    pub bytes_since_last: u64,
+
+    /// How long ago the oldest log since the last snapshot was appended.
+    ///
+    /// It is `None` if there is no such log, or if it is loaded from storage at startup.
+    pub oldest_log_age: Option<Duration>,
+}
+
+/// Decides whether to build a snapshot, when [`SnapshotPolicy::Custom`] is used.
+///
+/// It is configured by [`RaftTypeConfig::SnapshotTrigger`], and the default is
+/// [`NoSnapshotTrigger`]. It is evaluated every time the committed log id is updated.
+///
+/// [`SnapshotPolicy::Custom`]: crate::SnapshotPolicy::Custom
+/// [`RaftTypeConfig::SnapshotTrigger`]: crate::RaftTypeConfig::SnapshotTrigger
+pub trait SnapshotTrigger: OptionalSend + OptionalSync + 'static {
+    /// Returns `true` to build a snapshot.
+    fn should_snapshot(ctx: &SnapshotTriggerContext) -> bool;
+}
+
+/// Never builds a snapshot.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoSnapshotTrigger;
+
+impl SnapshotTrigger for NoSnapshotTrigger {
+    fn should_snapshot(_ctx: &SnapshotTriggerContext) -> bool {
+        false
+    }
+}
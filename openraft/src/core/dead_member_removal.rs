@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::type_config::alias::InstantOf;
+use crate::RaftTypeConfig;
+
+/// Tracks the members a leader removes once they have been unreachable for a configured duration.
+///
+/// A member is unreachable since the last time the leader heard from it, or since the leader
+/// started tracking it if it has never replied in this term. At most one member is removed at a
+/// time, since removing a voter is a two-step membership change.
+pub(crate) struct DeadMemberRemoval<C>
+where C: RaftTypeConfig
+{
+    /// The time the leader starts tracking every member.
+    tracked_since: BTreeMap<C::NodeId, InstantOf<C>>,
+
+    /// The member whose removal is being committed.
+    pub(crate) removing: Option<C::NodeId>,
+}
+
+impl<C> Default for DeadMemberRemoval<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            tracked_since: BTreeMap::new(),
+            removing: None,
+        }
+    }
+}
+
+impl<C> DeadMemberRemoval<C>
+where C: RaftTypeConfig
+{
+    /// Update the tracked members with the last time the leader heard from each of them, and
+    /// return the ones that have been unreachable for at least `duration`, longest first.
+    ///
+    /// The members not in `last_heard` are no longer tracked.
+    pub(crate) fn dead_members(
+        &mut self,
+        last_heard: impl IntoIterator<Item = (C::NodeId, Option<InstantOf<C>>)>,
+        now: InstantOf<C>,
+        duration: Duration,
+    ) -> Vec<(C::NodeId, Duration)> {
+        let last_heard = last_heard.into_iter().collect::<BTreeMap<_, _>>();
+
+        self.tracked_since.retain(|id, _| last_heard.contains_key(id));
+
+        let mut dead = vec![];
+        for (id, heard) in last_heard {
+            let since = *self.tracked_since.entry(id).or_insert(now);
+            let since = heard.map_or(since, |t| std::cmp::max(t, since));
+
+            if now >= since + duration {
+                dead.push((id, now - since));
+            }
+        }
+
+        dead.sort_by(|a, b| b.1.cmp(&a.1));
+        dead
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::dead_member_removal::DeadMemberRemoval;
+    use crate::engine::testing::UTConfig;
+    use crate::TokioInstant;
+
+    #[test]
+    fn test_dead_member_removal_dead_members() {
+        let d = Duration::from_millis(100);
+        let t0 = TokioInstant::now();
+
+        let mut r = DeadMemberRemoval::<UTConfig>::default();
+
+        // Members that never replied are tracked from now on.
+        assert!(r.dead_members([(2, None), (3, None), (4, None)], t0, d).is_empty());
+
+        let got = r.dead_members([(2, Some(t0 + d)), (3, None), (4, Some(t0 + d / 2))], t0 + d * 2, d);
+        assert_eq!(vec![(3, d * 2), (4, d * 3 / 2), (2, d)], got);
+
+        // A member removed from the membership is no longer tracked, and is tracked from the start
+        // if it is added back.
+        assert_eq!(vec![(2, d)], r.dead_members([(2, Some(t0 + d))], t0 + d * 2, d));
+        assert_eq!(
+            vec![(2, d)],
+            r.dead_members([(2, Some(t0 + d)), (3, None)], t0 + d * 2, d)
+        );
+    }
+}
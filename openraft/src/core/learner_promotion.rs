@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::type_config::alias::InstantOf;
+use crate::RaftTypeConfig;
+
+/// Tracks the learners a leader promotes to voters once they have caught up.
+///
+/// A learner is a candidate from the time it is added with `Raft::add_learner()`. It is promoted
+/// once its replication lag has stayed under the threshold for a configured duration. At most one
+/// learner is promoted at a time, since a promotion is a two-step membership change.
+pub(crate) struct LearnerPromotion<C>
+where C: RaftTypeConfig
+{
+    /// The learners to promote, and the time since which each one has kept up with the leader.
+    pub(crate) candidates: BTreeMap<C::NodeId, Option<InstantOf<C>>>,
+
+    /// The learner whose promotion is being committed.
+    pub(crate) promoting: Option<C::NodeId>,
+}
+
+impl<C> Default for LearnerPromotion<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            candidates: BTreeMap::new(),
+            promoting: None,
+        }
+    }
+}
+
+impl<C> LearnerPromotion<C>
+where C: RaftTypeConfig
+{
+    pub(crate) fn add_candidate(&mut self, id: C::NodeId) {
+        self.candidates.insert(id, None);
+    }
+
+    /// Update whether the candidate `id` keeps up with the leader at `now`.
+    pub(crate) fn observe(&mut self, id: &C::NodeId, caught_up: bool, now: InstantOf<C>) {
+        let Some(since) = self.candidates.get_mut(id) else {
+            return;
+        };
+
+        if caught_up {
+            since.get_or_insert(now);
+        } else {
+            *since = None;
+        }
+    }
+
+    /// Take a candidate that has kept up with the leader for at least `duration` and mark it as
+    /// being promoted.
+    pub(crate) fn take_ready(&mut self, now: InstantOf<C>, duration: Duration) -> Option<C::NodeId> {
+        if self.promoting.is_some() {
+            return None;
+        }
+
+        let id = self
+            .candidates
+            .iter()
+            .find(|(_, since)| since.is_some_and(|t| now >= t + duration))
+            .map(|(id, _)| *id)?;
+
+        self.candidates.remove(&id);
+        self.promoting = Some(id);
+        Some(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::learner_promotion::LearnerPromotion;
+    use crate::engine::testing::UTConfig;
+    use crate::TokioInstant;
+
+    #[test]
+    fn test_learner_promotion_take_ready() {
+        let d = Duration::from_millis(100);
+        let t0 = TokioInstant::now();
+
+        let mut p = LearnerPromotion::<UTConfig>::default();
+        p.add_candidate(3);
+        p.add_candidate(4);
+
+        p.observe(&3, true, t0);
+        p.observe(&4, true, t0);
+        p.observe(&5, true, t0);
+        assert!(!p.candidates.contains_key(&5), "not a candidate");
+
+        assert_eq!(None, p.take_ready(t0 + d / 2, d));
+
+        // Falling behind resets the timer.
+        p.observe(&3, false, t0 + d / 2);
+        p.observe(&3, true, t0 + d);
+
+        assert_eq!(Some(4), p.take_ready(t0 + d, d));
+        assert_eq!(Some(4), p.promoting);
+
+        // One promotion at a time.
+        assert_eq!(None, p.take_ready(t0 + d * 3, d));
+
+        p.promoting = None;
+        assert_eq!(Some(3), p.take_ready(t0 + d * 3, d));
+        assert!(p.candidates.is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::entry::RaftEntry;
+use crate::metrics::LogCacheMetrics;
+use crate::RaftLogId;
+use crate::RaftTypeConfig;
+
+/// An in-memory cache of the latest log entries appended by `RaftCore`, so that the replication
+/// streams do not read the entries just appended back from the log store.
+///
+/// The cache holds contiguous entries and is bounded by both the number of entries and the total
+/// [`RaftEntry::size_hint()`] of them: the oldest entries are evicted first. It is kept
+/// consistent with the log store by removing the truncated and purged entries.
+///
+/// It is disabled until [`Self::enable()`] is called, because caching requires cloning an entry,
+/// while `C::Entry` is not required to be `Clone`.
+pub(crate) struct LogCache<C>
+where C: RaftTypeConfig
+{
+    inner: Arc<Mutex<Inner<C>>>,
+}
+
+impl<C> Clone for LogCache<C>
+where C: RaftTypeConfig
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C> Default for LogCache<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                clone_entry: None,
+                max_entries: 0,
+                max_bytes: 0,
+                entries: VecDeque::new(),
+                bytes: 0,
+                hits: 0,
+                misses: 0,
+            })),
+        }
+    }
+}
+
+struct Inner<C>
+where C: RaftTypeConfig
+{
+    /// Clones an entry, `None` if the cache is disabled.
+    clone_entry: Option<fn(&C::Entry) -> C::Entry>,
+
+    max_entries: usize,
+    max_bytes: u64,
+
+    /// Entries with contiguous indexes.
+    entries: VecDeque<C::Entry>,
+
+    /// The total size hint of `entries`.
+    bytes: u64,
+
+    hits: u64,
+    misses: u64,
+}
+
+impl<C> Inner<C>
+where C: RaftTypeConfig
+{
+    fn first_index(&self) -> Option<u64> {
+        self.entries.front().map(|e| e.get_log_id().index)
+    }
+
+    fn pop_front(&mut self) {
+        if let Some(e) = self.entries.pop_front() {
+            self.bytes -= e.size_hint() as u64;
+        }
+    }
+
+    fn pop_back(&mut self) {
+        if let Some(e) = self.entries.pop_back() {
+            self.bytes -= e.size_hint() as u64;
+        }
+    }
+}
+
+impl<C> LogCache<C>
+where C: RaftTypeConfig
+{
+    /// Enable the cache to hold at most `max_entries` entries of at most `max_bytes` in total.
+    pub(crate) fn enable(&self, max_entries: usize, max_bytes: u64, clone_entry: fn(&C::Entry) -> C::Entry) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.clone_entry = Some(clone_entry);
+        inner.max_entries = max_entries;
+        inner.max_bytes = max_bytes;
+
+        while inner.entries.len() > inner.max_entries || inner.bytes > inner.max_bytes {
+            inner.pop_front();
+        }
+    }
+
+    /// Add the entries just appended to the log store.
+    pub(crate) fn append(&self, entries: &[C::Entry]) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let Some(clone_entry) = inner.clone_entry else {
+            return;
+        };
+
+        for entry in entries {
+            let index = entry.get_log_id().index;
+
+            // Keep the cache contiguous
+            let next = inner.first_index().map(|first| first + inner.entries.len() as u64);
+            if next.is_some() && next != Some(index) {
+                inner.entries.clear();
+                inner.bytes = 0;
+            }
+
+            inner.bytes += entry.size_hint() as u64;
+            inner.entries.push_back(clone_entry(entry));
+
+            while inner.entries.len() > inner.max_entries || inner.bytes > inner.max_bytes {
+                inner.pop_front();
+            }
+        }
+    }
+
+    /// Remove the entries since `index`, inclusive.
+    pub(crate) fn truncate(&self, index: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        while inner.entries.back().map(|e| e.get_log_id().index >= index).unwrap_or(false) {
+            inner.pop_back();
+        }
+    }
+
+    /// Remove the entries upto `index`, inclusive.
+    pub(crate) fn purge(&self, index: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        while inner.first_index().map(|first| first <= index).unwrap_or(false) {
+            inner.pop_front();
+        }
+    }
+
+    /// Returns `true` if the entry at `index` is in the cache.
+    pub(crate) fn contains(&self, index: u64) -> bool {
+        let inner = self.inner.lock().unwrap();
+
+        if inner.clone_entry.is_none() {
+            return false;
+        }
+
+        let first = inner.first_index();
+        first.map(|first| first <= index && index < first + inner.entries.len() as u64).unwrap_or(false)
+    }
+
+    /// Returns the cached entries in `[start, end)` starting from `start`, which may be fewer than
+    /// requested, or `None` if `start` is not in the cache.
+    pub(crate) fn get(&self, start: u64, end: u64) -> Option<Vec<C::Entry>> {
+        let mut inner = self.inner.lock().unwrap();
+
+        let clone_entry = inner.clone_entry?;
+
+        let first = inner.first_index();
+        let Some(first) = first.filter(|first| *first <= start && start < first + inner.entries.len() as u64) else {
+            inner.misses += 1;
+            return None;
+        };
+
+        inner.hits += 1;
+
+        let from = (start - first) as usize;
+        let to = std::cmp::min((end - first) as usize, inner.entries.len());
+        Some(inner.entries.range(from..to).map(clone_entry).collect())
+    }
+
+    pub(crate) fn metrics(&self) -> LogCacheMetrics {
+        let inner = self.inner.lock().unwrap();
+        LogCacheMetrics {
+            entries: inner.entries.len() as u64,
+            bytes: inner.bytes,
+            hits: inner.hits,
+            misses: inner.misses,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::log_cache::LogCache;
+    use crate::engine::testing::UTConfig;
+    use crate::entry::RaftEntry;
+    use crate::metrics::LogCacheMetrics;
+    use crate::testing::blank_ent;
+    use crate::Entry;
+    use crate::RaftLogId;
+
+    fn ents(indexes: impl IntoIterator<Item = u64>) -> Vec<Entry<UTConfig>> {
+        indexes.into_iter().map(|i| blank_ent::<UTConfig>(1, 1, i)).collect()
+    }
+
+    fn indexes(entries: Option<Vec<Entry<UTConfig>>>) -> Option<Vec<u64>> {
+        entries.map(|x| x.iter().map(|e| e.get_log_id().index).collect())
+    }
+
+    #[test]
+    fn test_log_cache_disabled() {
+        let c = LogCache::<UTConfig>::default();
+        c.append(&ents(1..3));
+        assert_eq!(None, indexes(c.get(1, 3)));
+        assert_eq!(LogCacheMetrics::default(), c.metrics());
+    }
+
+    #[test]
+    fn test_log_cache() {
+        let c = LogCache::<UTConfig>::default();
+        c.enable(5, u64::MAX, Entry::clone);
+
+        c.append(&ents(1..4));
+        assert_eq!(Some(vec![1, 2, 3]), indexes(c.get(1, 10)));
+        assert_eq!(Some(vec![2]), indexes(c.get(2, 3)));
+        assert_eq!(None, indexes(c.get(4, 5)));
+
+        // Evict the oldest entries
+        c.append(&ents(4..8));
+        assert_eq!(None, indexes(c.get(2, 5)));
+        assert_eq!(Some(vec![3, 4, 5, 6, 7]), indexes(c.get(3, 8)));
+
+        c.truncate(6);
+        assert_eq!(Some(vec![4, 5]), indexes(c.get(4, 8)));
+
+        c.purge(3);
+        assert_eq!(Some(vec![4, 5]), indexes(c.get(4, 8)));
+        c.purge(4);
+        assert_eq!(None, indexes(c.get(4, 8)));
+
+        // A non-contiguous entry resets the cache
+        c.append(&ents([9]));
+        assert_eq!(None, indexes(c.get(5, 8)));
+        assert_eq!(Some(vec![9]), indexes(c.get(9, 10)));
+
+        let m = c.metrics();
+        assert_eq!(1, m.entries);
+        assert_eq!(6, m.hits);
+        assert_eq!(4, m.misses);
+    }
+
+    #[test]
+    fn test_log_cache_max_bytes() {
+        let size = blank_ent::<UTConfig>(1, 1, 1).size_hint() as u64;
+
+        let c = LogCache::<UTConfig>::default();
+        c.enable(100, size * 2, Entry::clone);
+
+        c.append(&ents(1..4));
+        assert_eq!(Some(vec![2, 3]), indexes(c.get(2, 4)));
+        assert_eq!(size * 2, c.metrics().bytes);
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::membership::MembershipRecord;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftTypeConfig;
+
+/// The latest committed membership configs, recorded by the state machine worker when they are
+/// applied, and read by `Raft::membership_history()`.
+///
+/// It holds at most `capacity` records in log order: the oldest ones are evicted first.
+pub(crate) struct MembershipHistory<C>
+where C: RaftTypeConfig
+{
+    inner: Arc<Mutex<Inner<C>>>,
+}
+
+impl<C> Clone for MembershipHistory<C>
+where C: RaftTypeConfig
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+struct Inner<C>
+where C: RaftTypeConfig
+{
+    capacity: usize,
+    records: VecDeque<MembershipRecord<C>>,
+}
+
+impl<C> MembershipHistory<C>
+where C: RaftTypeConfig
+{
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                capacity,
+                records: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// Record a committed membership config.
+    ///
+    /// A config that is not newer than the last recorded one is ignored, e.g., the config in a
+    /// snapshot that has already been applied from the log.
+    pub(crate) fn record(&self, log_id: LogId<C::NodeId>, membership: Membership<C>, applied_at: SystemTime) {
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.capacity == 0 {
+            return;
+        }
+
+        if inner.records.back().is_some_and(|r| r.log_id >= log_id) {
+            return;
+        }
+
+        if inner.records.len() >= inner.capacity {
+            inner.records.pop_front();
+        }
+
+        inner.records.push_back(MembershipRecord {
+            log_id,
+            membership,
+            applied_at,
+        });
+    }
+
+    /// Returns the recorded configs, the oldest first.
+    pub(crate) fn records(&self) -> Vec<MembershipRecord<C>> {
+        self.inner.lock().unwrap().records.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use maplit::btreeset;
+
+    use crate::core::MembershipHistory;
+    use crate::engine::testing::UTConfig;
+    use crate::testing::log_id;
+    use crate::Membership;
+
+    fn m(voters: &[u64]) -> Membership<UTConfig> {
+        Membership::new(vec![voters.iter().copied().collect()], None)
+    }
+
+    #[test]
+    fn test_membership_history_record() {
+        let t = SystemTime::now();
+        let h = MembershipHistory::<UTConfig>::new(2);
+
+        h.record(log_id(1, 1, 1), m(&[1]), t);
+        h.record(log_id(1, 1, 3), m(&[1, 2]), t);
+
+        // Not newer than the last one.
+        h.record(log_id(1, 1, 3), m(&[1, 2, 3]), t);
+        h.record(log_id(1, 1, 2), m(&[1, 2, 3]), t);
+
+        // The oldest is evicted.
+        h.record(log_id(1, 1, 5), m(&[1, 2, 3]), t);
+
+        let got = h.records();
+        assert_eq!(
+            vec![(log_id(1, 1, 3), btreeset! {1,2}), (log_id(1, 1, 5), btreeset! {1,2,3})],
+            got.iter().map(|r| (r.log_id, r.membership.voter_ids().collect())).collect::<Vec<_>>()
+        );
+
+        let disabled = MembershipHistory::<UTConfig>::new(0);
+        disabled.record(log_id(1, 1, 1), m(&[1]), t);
+        assert!(disabled.records().is_empty());
+    }
+}
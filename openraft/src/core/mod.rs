@@ -6,18 +6,33 @@
 
 pub(crate) mod balancer;
 pub(crate) mod command_state;
+mod dead_member_removal;
+mod learner_promotion;
+pub(crate) mod log_cache;
+mod membership_history;
 pub(crate) mod notify;
+mod quarantine;
 mod raft_core;
 pub(crate) mod raft_msg;
+mod read_index_batch;
 mod replication_state;
 mod server_state;
 pub(crate) mod sm;
 mod tick;
+mod write_batch;
+mod write_deadlines;
 
+pub(crate) use dead_member_removal::DeadMemberRemoval;
+pub(crate) use learner_promotion::LearnerPromotion;
+pub(crate) use membership_history::MembershipHistory;
+pub(crate) use quarantine::Quarantine;
 pub(crate) use raft_core::ApplyResult;
 pub(crate) use raft_core::ApplyingEntry;
 pub use raft_core::RaftCore;
+pub(crate) use read_index_batch::ReadIndexBatch;
 pub(crate) use replication_state::replication_lag;
 pub use server_state::ServerState;
 pub(crate) use tick::Tick;
 pub(crate) use tick::TickHandle;
+pub(crate) use write_batch::WriteBatch;
+pub(crate) use write_deadlines::WriteDeadlines;
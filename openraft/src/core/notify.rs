@@ -1,7 +1,9 @@
 use std::fmt;
+use std::io;
 
 use crate::core::sm;
 use crate::raft::VoteResponse;
+use crate::raft_state::LogIOId;
 use crate::replication;
 use crate::RaftTypeConfig;
 use crate::Vote;
@@ -20,6 +22,15 @@ where C: RaftTypeConfig
         sender_vote: Vote<C::NodeId>,
     },
 
+    /// Response to a pre-vote request.
+    PreVoteResponse {
+        target: C::NodeId,
+        resp: VoteResponse<C>,
+
+        /// The vote carried by the pre-vote request.
+        sender_vote: Vote<C::NodeId>,
+    },
+
     /// Seen a higher `vote`.
     HigherVote {
         /// The ID of the target node from which the new term was observed.
@@ -43,6 +54,19 @@ where C: RaftTypeConfig
     /// Result of executing a command sent from state machine worker.
     StateMachine { command_result: sm::CommandResult<C> },
 
+    /// A heartbeat round confirming the leadership for read requests has finished.
+    ReadIndexRoundDone,
+
+    /// Log entries appended by the leader have been flushed to the local log store.
+    ///
+    /// The leader does not wait for its own log io before replicating the entries to followers.
+    LocalLogFlushed {
+        /// The vote of the leader that appended the entries.
+        vote: Vote<C::NodeId>,
+
+        result: Result<LogIOId<C::NodeId>, io::Error>,
+    },
+
     /// A tick event to wake up RaftCore to check timeout etc.
     Tick {
         /// ith tick
@@ -70,6 +94,13 @@ where C: RaftTypeConfig
             } => {
                 write!(f, "VoteResponse: from: {}: {}, res-vote: {}", target, resp, vote)
             }
+            Self::PreVoteResponse {
+                target,
+                resp,
+                sender_vote: vote,
+            } => {
+                write!(f, "PreVoteResponse: from: {}: {}, res-vote: {}", target, resp, vote)
+            }
             Self::HigherVote {
                 ref target,
                 higher: ref new_vote,
@@ -87,6 +118,16 @@ where C: RaftTypeConfig
             Self::StateMachine { command_result } => {
                 write!(f, "StateMachine command done: {:?}", command_result)
             }
+            Self::ReadIndexRoundDone => {
+                write!(f, "ReadIndexRoundDone")
+            }
+            Self::LocalLogFlushed { vote, result } => {
+                write!(f, "LocalLogFlushed: vote: {}, result: ", vote)?;
+                match result {
+                    Ok(io_id) => write!(f, "Ok({})", io_id),
+                    Err(e) => write!(f, "Err({})", e),
+                }
+            }
             Self::Tick { i } => {
                 write!(f, "Tick {}", i)
             }
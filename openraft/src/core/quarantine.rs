@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::type_config::alias::InstantOf;
+use crate::RaftTypeConfig;
+
+/// The replication lag of a target observed by the leader, and since when it has kept growing.
+struct LagTrend<C>
+where C: RaftTypeConfig
+{
+    lag: u64,
+    growing_since: Option<InstantOf<C>>,
+}
+
+/// Tracks the followers and learners whose replication lag keeps growing, and quarantines them.
+///
+/// The lag of every target is sampled by the leader on every tick. A target is quarantined once
+/// its lag has not decreased, and has grown at least once, for a configured duration. It is
+/// released once it catches up with the leader.
+pub(crate) struct Quarantine<C>
+where C: RaftTypeConfig
+{
+    trends: BTreeMap<C::NodeId, LagTrend<C>>,
+
+    /// The targets that are quarantined.
+    pub(crate) quarantined: BTreeSet<C::NodeId>,
+}
+
+impl<C> Default for Quarantine<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            trends: BTreeMap::new(),
+            quarantined: BTreeSet::new(),
+        }
+    }
+}
+
+impl<C> Quarantine<C>
+where C: RaftTypeConfig
+{
+    /// Update the lag of target `id` at `now`.
+    ///
+    /// It returns `Some(true)` if `id` becomes quarantined, `Some(false)` if it is released, or
+    /// `None` if nothing changes.
+    pub(crate) fn observe(&mut self, id: C::NodeId, lag: u64, now: InstantOf<C>, duration: Duration) -> Option<bool> {
+        let trend = self.trends.entry(id).or_insert(LagTrend {
+            lag,
+            growing_since: None,
+        });
+
+        if lag == 0 {
+            trend.growing_since = None;
+        } else if lag > trend.lag {
+            trend.growing_since.get_or_insert(now);
+        } else if lag < trend.lag {
+            trend.growing_since = None;
+        }
+        trend.lag = lag;
+
+        if self.quarantined.contains(&id) {
+            if lag == 0 {
+                self.quarantined.remove(&id);
+                return Some(false);
+            }
+            return None;
+        }
+
+        if trend.growing_since.is_some_and(|t| now >= t + duration) {
+            trend.growing_since = None;
+            self.quarantined.insert(id);
+            return Some(true);
+        }
+
+        None
+    }
+
+    /// Forget the targets that are no longer replicated to.
+    pub(crate) fn retain(&mut self, f: impl Fn(&C::NodeId) -> bool) {
+        self.trends.retain(|id, _| f(id));
+        self.quarantined.retain(|id| f(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::quarantine::Quarantine;
+    use crate::engine::testing::UTConfig;
+    use crate::TokioInstant;
+
+    #[test]
+    fn test_quarantine_observe() {
+        let d = Duration::from_millis(100);
+        let t0 = TokioInstant::now();
+
+        let mut q = Quarantine::<UTConfig>::default();
+
+        assert_eq!(None, q.observe(2, 10, t0, d));
+        assert_eq!(None, q.observe(2, 20, t0, d), "starts growing");
+        assert_eq!(None, q.observe(2, 20, t0 + d / 2, d), "not decreasing");
+
+        // Decreasing lag resets the timer.
+        assert_eq!(None, q.observe(2, 15, t0 + d / 2, d));
+        assert_eq!(None, q.observe(2, 30, t0 + d, d));
+        assert_eq!(None, q.observe(2, 40, t0 + d * 3 / 2, d));
+
+        assert_eq!(Some(true), q.observe(2, 50, t0 + d * 2, d));
+        assert!(q.quarantined.contains(&2));
+
+        assert_eq!(None, q.observe(2, 5, t0 + d * 3, d), "released only when caught up");
+        assert_eq!(Some(false), q.observe(2, 0, t0 + d * 3, d));
+        assert!(q.quarantined.is_empty());
+
+        q.observe(3, 10, t0, d);
+        q.observe(3, 20, t0, d);
+        assert_eq!(Some(true), q.observe(3, 30, t0 + d, d));
+
+        q.retain(|id| *id != 3);
+        assert!(q.quarantined.is_empty());
+    }
+}
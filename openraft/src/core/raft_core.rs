@@ -1,9 +1,12 @@
 use std::borrow::Borrow;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::io;
 use std::marker::PhantomData;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
@@ -12,7 +15,9 @@ use anyerror::AnyError;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use futures::TryFutureExt;
+use maplit::btreemap;
 use maplit::btreeset;
+use rand::Rng;
 use tokio::select;
 use tokio::sync::mpsc;
 use tokio::sync::watch;
@@ -20,22 +25,41 @@ use tracing::Instrument;
 use tracing::Level;
 use tracing::Span;
 
+use crate::async_runtime::mpsc::TryRecvError;
 use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::BroadcastSender;
+use crate::async_runtime::CancellationToken;
+use crate::async_runtime::MpscReceiver;
 use crate::config::Config;
+use crate::config::ErrorPolicy;
 use crate::config::RuntimeConfig;
 use crate::core::balancer::Balancer;
 use crate::core::command_state::CommandState;
+use crate::core::log_cache::LogCache;
 use crate::core::notify::Notify;
 use crate::core::raft_msg::external_command::ExternalCommand;
 use crate::core::raft_msg::AppendEntriesTx;
 use crate::core::raft_msg::ClientReadTx;
+use crate::core::raft_msg::ForwardClientWriteTx;
+use crate::core::raft_msg::ForwardReadTx;
 use crate::core::raft_msg::RaftMsg;
 use crate::core::raft_msg::ResultSender;
+use crate::core::raft_msg::TransferLeaderTx;
+use crate::core::raft_msg::TransferSnapshotTx;
 use crate::core::raft_msg::VoteTx;
+use crate::core::replication_lag;
 use crate::core::sm;
 use crate::core::sm::handle;
+use crate::core::sm::worker::Worker;
 use crate::core::sm::CommandSeq;
+use crate::core::DeadMemberRemoval;
+use crate::core::LearnerPromotion;
+use crate::core::MembershipHistory;
+use crate::core::Quarantine;
+use crate::core::ReadIndexBatch;
 use crate::core::ServerState;
+use crate::core::WriteBatch;
+use crate::core::WriteDeadlines;
 use crate::display_ext::DisplayOption;
 use crate::display_ext::DisplayOptionExt;
 use crate::display_ext::DisplaySlice;
@@ -43,26 +67,51 @@ use crate::engine::Command;
 use crate::engine::Condition;
 use crate::engine::Engine;
 use crate::engine::Respond;
-use crate::entry::FromAppData;
 use crate::entry::RaftEntry;
+use crate::error::ApplyError;
+use crate::error::ChangeMembershipTooFrequent;
+use crate::error::CheckIsLeaderError;
 use crate::error::ClientWriteError;
+use crate::error::ClientWriteTimeout;
 use crate::error::Fatal;
 use crate::error::ForwardToLeader;
 use crate::error::Infallible;
 use crate::error::InitializeError;
+use crate::error::NetworkError;
+use crate::error::NotVoter;
 use crate::error::QuorumNotEnough;
 use crate::error::RPCError;
+use crate::error::ReplicationClosed;
+use crate::error::SnapshotTooOld;
 use crate::error::Timeout;
+use crate::error::TransferLeaderTimeout;
+use crate::error::UnsafeMembershipChange;
 use crate::log_id::LogIdOptionExt;
 use crate::log_id::RaftLogId;
+use crate::metrics::BackoffMetrics;
+use crate::metrics::BatchSizeMetrics;
+use crate::metrics::DeadMember;
+use crate::metrics::LearnerPromotionMetrics;
+use crate::metrics::LogRetentionMetrics;
 use crate::metrics::RaftDataMetrics;
 use crate::metrics::RaftMetrics;
 use crate::metrics::RaftServerMetrics;
 use crate::metrics::ReplicationMetrics;
+use crate::metrics::RoleChange;
+use crate::metrics::RttMetrics;
+use crate::metrics::ZoneHealth;
+use crate::metrics::ZoneMetrics;
+use crate::network::network_observer::RpcRecorder;
 use crate::network::v2::RaftNetworkV2;
+use crate::network::Backoff;
+use crate::network::ExponentialBackoff;
+use crate::network::RPCErrorKind;
 use crate::network::RPCOption;
 use crate::network::RPCTypes;
 use crate::network::RaftNetworkFactory;
+use crate::network::RpcAuth;
+use crate::network::RpcContext;
+use crate::network::SnapshotResume;
 use crate::progress::entry::ProgressEntry;
 use crate::progress::Inflight;
 use crate::progress::Progress;
@@ -71,10 +120,18 @@ use crate::raft::responder::Responder;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
 use crate::raft::ClientWriteResponse;
+use crate::raft::ReadPolicy;
+use crate::raft::ReplicationStatus;
+use crate::raft::SnapshotTransferStatus;
+use crate::raft::TransferLeaderRequest;
+use crate::raft::TransferSnapshotRequest;
+use crate::raft::TransferSnapshotResponse;
 use crate::raft::VoteRequest;
 use crate::raft_state::LogIOId;
 use crate::raft_state::LogStateReader;
 use crate::replication;
+use crate::replication::heartbeat::heartbeat_rtt;
+use crate::replication::heartbeat::HeartbeatEvent;
 use crate::replication::request::Replicate;
 use crate::replication::request_id::RequestId;
 use crate::replication::response::ReplicationResult;
@@ -82,14 +139,23 @@ use crate::replication::ReplicationCore;
 use crate::replication::ReplicationHandle;
 use crate::replication::ReplicationSessionId;
 use crate::runtime::RaftRuntime;
+use crate::storage::seal;
+use crate::storage::EncodedEntries;
 use crate::storage::LogFlushed;
 use crate::storage::RaftLogReaderExt;
 use crate::storage::RaftLogStorage;
 use crate::storage::RaftStateMachine;
+use crate::storage::SnapshotBuildProgress;
 use crate::type_config::alias::AsyncRuntimeOf;
+use crate::type_config::alias::BroadcastSenderOf;
+use crate::type_config::alias::CancellationTokenOf;
 use crate::type_config::alias::InstantOf;
+use crate::type_config::alias::LogIdOf;
+use crate::type_config::alias::MpscReceiverOf;
+use crate::type_config::alias::MpscSenderOf;
 use crate::type_config::alias::OneshotReceiverOf;
 use crate::type_config::alias::ResponderOf;
+use crate::type_config::alias::SemaphoreOf;
 use crate::AsyncRuntime;
 use crate::ChangeMembers;
 use crate::Instant;
@@ -98,14 +164,15 @@ use crate::Membership;
 use crate::OptionalSend;
 use crate::RaftTypeConfig;
 use crate::StorageError;
+use crate::StorageHelper;
 use crate::StorageIOError;
 use crate::Vote;
 
 /// A temp struct to hold the data for a node that is being applied.
 #[derive(Debug)]
 pub(crate) struct ApplyingEntry<C: RaftTypeConfig> {
-    log_id: LogId<C::NodeId>,
-    membership: Option<Membership<C>>,
+    pub(crate) log_id: LogId<C::NodeId>,
+    pub(crate) membership: Option<Membership<C>>,
 }
 
 impl<C: RaftTypeConfig> ApplyingEntry<C> {
@@ -120,7 +187,7 @@ pub(crate) struct ApplyResult<C: RaftTypeConfig> {
     pub(crate) end: u64,
     pub(crate) last_applied: LogId<C::NodeId>,
     pub(crate) applying_entries: Vec<ApplyingEntry<C>>,
-    pub(crate) apply_results: Vec<C::R>,
+    pub(crate) apply_results: Vec<Result<C::R, C::AppError>>,
 }
 
 impl<C: RaftTypeConfig> Debug for ApplyResult<C> {
@@ -133,6 +200,28 @@ impl<C: RaftTypeConfig> Debug for ApplyResult<C> {
     }
 }
 
+/// The restarts of `RaftCore` on storage errors in a row, with [`ErrorPolicy::StepDownAndRetry`].
+struct Restarts<C: RaftTypeConfig> {
+    /// The number of retries to load the state from storage.
+    retries: u64,
+
+    /// The delays between retries, built when the first error occurs.
+    backoff: Option<Backoff>,
+
+    /// When the last restart succeeded.
+    last_restart: Option<InstantOf<C>>,
+}
+
+impl<C: RaftTypeConfig> Default for Restarts<C> {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            backoff: None,
+            last_restart: None,
+        }
+    }
+}
+
 /// Data for a Leader.
 ///
 /// It is created when RaftCore enters leader state, and will be dropped when it quits leader state.
@@ -142,19 +231,91 @@ pub(crate) struct LeaderData<C: RaftTypeConfig> {
     //           It requires the Engine to emit correct add/remove replication commands
     pub(super) replications: BTreeMap<C::NodeId, ReplicationHandle<C>>,
 
+    /// The time this node became leader.
+    pub(crate) established_at: InstantOf<C>,
+
     /// The time to send next heartbeat.
     pub(crate) next_heartbeat: InstantOf<C>,
+
+    /// The earliest time to hand over the leadership to a voter with a higher election priority.
+    pub(crate) next_priority_transfer: InstantOf<C>,
+
+    /// Whether a leadership transfer request has been sent in this term.
+    ///
+    /// The voters grant the vote to the transfer target regardless of the leader lease, thus the
+    /// lease can not be used for reads any more.
+    pub(crate) lease_revoked: bool,
+
+    /// The backoff states of the replication targets that are unreachable.
+    pub(crate) backoffs: BackoffMetrics<C::NodeId>,
+
+    /// The adaptive batch sizes of the replication targets, updated by the replication tasks.
+    pub(crate) batch_sizes: Arc<std::sync::Mutex<BatchSizeMetrics<C::NodeId>>>,
+
+    /// The learners added in this term that are waiting to be promoted to voters.
+    pub(crate) learner_promotion: LearnerPromotion<C>,
+
+    /// The members that are unreachable, to remove from the membership.
+    pub(crate) dead_member_removal: DeadMemberRemoval<C>,
+
+    /// Whether a leadership transfer has been started since this leader is removed from the
+    /// voters.
+    pub(crate) removal_transfer_started: bool,
+
+    /// The replication targets whose lag keeps growing.
+    pub(crate) quarantine: Quarantine<C>,
+
+    /// The time the last membership change proposed by `Raft::change_membership()` started.
+    pub(crate) last_membership_change: Option<InstantOf<C>>,
+
+    /// The lag in entries of every replication target when the replication status was last
+    /// queried, to estimate how fast it catches up.
+    pub(crate) catch_up_samples: BTreeMap<C::NodeId, (InstantOf<C>, u64)>,
 }
 
 impl<C: RaftTypeConfig> LeaderData<C> {
     pub(crate) fn new() -> Self {
+        let now = C::AsyncRuntime::now();
         Self {
             replications: BTreeMap::new(),
-            next_heartbeat: InstantOf::<C>::now(),
+            established_at: now,
+            next_heartbeat: now,
+            next_priority_transfer: now,
+            lease_revoked: false,
+            backoffs: BTreeMap::new(),
+            batch_sizes: Default::default(),
+            learner_promotion: Default::default(),
+            dead_member_removal: Default::default(),
+            removal_transfer_started: false,
+            quarantine: Default::default(),
+            last_membership_change: None,
+            catch_up_samples: BTreeMap::new(),
         }
     }
 }
 
+/// A leadership transfer started by
+/// [`Raft::transfer_leadership()`](`crate::Raft::transfer_leadership`).
+///
+/// New proposals are rejected until it finishes.
+pub(crate) struct LeaderTransfer<C: RaftTypeConfig> {
+    /// The node to become the new leader.
+    pub(crate) to: C::NodeId,
+
+    /// When to give up and resume serving as the leader.
+    pub(crate) deadline: InstantOf<C>,
+
+    /// Whether the transfer request has been sent, i.e., `to` has caught up with the leader.
+    pub(crate) sent: bool,
+
+    /// The caller to notify, `None` if the transfer is started by election priority.
+    pub(crate) tx: Option<TransferLeaderTx<C>>,
+
+    /// Whether it is started by [`Raft::step_down()`](`crate::Raft::step_down`), in which case
+    /// this node steps down instead of resuming serving when the transfer times out.
+    pub(crate) step_down: bool,
+}
+
 // TODO: remove SM
 /// The core type implementing the Raft protocol.
 pub struct RaftCore<C, N, LS, SM>
@@ -179,7 +340,7 @@ where
     pub(crate) log_store: LS,
 
     /// A controlling handle to the [`RaftStateMachine`] worker.
-    pub(crate) sm_handle: handle::Handle<C>,
+    pub(crate) sm_handle: handle::Handle<C, SM>,
 
     pub(crate) engine: Engine<C>,
 
@@ -188,9 +349,56 @@ where
 
     pub(crate) leader_data: Option<LeaderData<C>>,
 
+    /// The leadership transfer in progress, if any.
+    pub(crate) leader_transfer: Option<LeaderTransfer<C>>,
+
+    /// The read requests waiting for a heartbeat round to confirm the leadership.
+    pub(crate) read_index: ReadIndexBatch<C>,
+
+    /// The client writes being coalesced before being appended to the log.
+    pub(crate) write_batch: WriteBatch<C>,
+
+    /// The deadlines of the client writes in `client_resp_channels` that have one.
+    pub(crate) write_deadlines: WriteDeadlines<C>,
+
+    /// The learners that have been promoted to voters automatically.
+    pub(crate) learner_promotion: LearnerPromotionMetrics<C::NodeId>,
+
+    /// This node has stepped down with [`Raft::step_down()`](`crate::Raft::step_down`) and does not
+    /// start an election before this time.
+    pub(crate) step_down_until: Option<InstantOf<C>>,
+
+    /// The random extra delay of the next election if this node has heard from a leader.
+    ///
+    /// See [`Config::election_backoff_max`].
+    pub(crate) election_backoff: Duration,
+
+    /// The range in milliseconds from which the current election timeout is picked.
+    ///
+    /// See [`Config::adaptive_election_timeout`].
+    pub(crate) election_timeout_range: (u64, u64),
+
+    /// The estimated RTT of the RPCs to every node, updated by the tasks sending RPCs.
+    pub(crate) rtt: Arc<std::sync::Mutex<RttMetrics<C::NodeId>>>,
+
+    /// The latest appended log entries, read by replication streams before the log store.
+    pub(crate) log_cache: LogCache<C>,
+
+    /// The latest committed membership configs, recorded by the state machine worker.
+    pub(crate) membership_history: MembershipHistory<C>,
+
+    /// Signs the RPCs sent by this node.
+    pub(crate) auth: RpcAuth<C>,
+
+    /// Limits the number of snapshots transmitted by all replication streams at the same time.
+    pub(crate) snapshot_transmission_semaphore: Arc<SemaphoreOf<C>>,
+
+    /// The progress of the snapshot being built, shared with the sm worker and the application.
+    pub(crate) snapshot_building: Option<SnapshotBuildProgress<C>>,
+
     #[allow(dead_code)]
-    pub(crate) tx_api: mpsc::UnboundedSender<RaftMsg<C>>,
-    pub(crate) rx_api: mpsc::UnboundedReceiver<RaftMsg<C>>,
+    pub(crate) tx_api: MpscSenderOf<C, RaftMsg<C>>,
+    pub(crate) rx_api: MpscReceiverOf<C, RaftMsg<C>>,
 
     /// A Sender to send callback by other components to [`RaftCore`], when an action is finished,
     /// such as flushing log to disk, or applying log entries to state machine.
@@ -203,8 +411,32 @@ where
     pub(crate) tx_data_metrics: watch::Sender<RaftDataMetrics<C>>,
     pub(crate) tx_server_metrics: watch::Sender<RaftServerMetrics<C>>,
 
+    /// Broadcasts the log id of every entry applied to the state machine.
+    pub(crate) tx_applied: BroadcastSenderOf<C, LogId<C::NodeId>>,
+
+    /// Broadcasts every change of the server state.
+    pub(crate) tx_role_change: BroadcastSenderOf<C, RoleChange<C>>,
+
+    /// Broadcasts every member that is about to be removed because it is unreachable.
+    pub(crate) tx_dead_member: BroadcastSenderOf<C, DeadMember<C>>,
+
+    /// The server state and vote last broadcast by [`Self::tx_role_change`].
+    pub(crate) reported_role: (ServerState, Vote<C::NodeId>),
+
+    /// The nodes in the membership last reported to [`RaftNetworkFactory::node_updated()`].
+    pub(crate) reported_nodes: BTreeMap<C::NodeId, C::Node>,
+
+    /// Cancelled to stop `RaftCore` and the tasks it spawns, such as replication streams.
+    pub(crate) cancel: CancellationTokenOf<C>,
+
     pub(crate) command_state: CommandState,
 
+    /// Commands that wait for a state machine command to finish, with the sequence number of the
+    /// state machine command.
+    ///
+    /// They are parked here instead of blocking the command queue.
+    pub(crate) waiting_sm_commands: Vec<(CommandSeq, Command<C>)>,
+
     pub(crate) span: Span,
 
     pub(crate) _p: PhantomData<SM>,
@@ -218,9 +450,28 @@ where
     SM: RaftStateMachine<C>,
 {
     /// The main loop of the Raft protocol.
-    pub(crate) async fn main(mut self, rx_shutdown: OneshotReceiverOf<C, ()>) -> Result<Infallible, Fatal<C>> {
-        let span = tracing::span!(parent: &self.span, Level::DEBUG, "main");
-        let res = self.do_main(rx_shutdown).instrument(span).await;
+    pub(crate) async fn main(mut self) -> Result<Infallible, Fatal<C>> {
+        let mut restarts = Restarts::default();
+
+        let res = loop {
+            let span = tracing::span!(parent: &self.span, Level::DEBUG, "main");
+            let res = self.do_main().instrument(span).await;
+
+            let Err(Fatal::StorageError(e)) = &res else {
+                break res;
+            };
+            if !matches!(self.config.error_policy, ErrorPolicy::StepDownAndRetry { .. }) {
+                break res;
+            }
+
+            if let Err(fatal) = self.restart_from_storage(e.clone(), &mut restarts).await {
+                break Err(fatal);
+            }
+        };
+
+        // Stop the tasks spawned by RaftCore, in case RaftCore quits on an error.
+        self.cancel.cancel();
+        self.join_replication_tasks().await;
 
         // Flush buffered metrics
         self.report_metrics(None);
@@ -245,11 +496,124 @@ where
 
         tracing::info!("RaftCore shutdown complete");
 
+        if let (ErrorPolicy::Panic, Fatal::StorageError(e)) = (self.config.error_policy, &err) {
+            panic!("RaftCore quit on storage error: {}", e);
+        }
+
         Err(err)
     }
 
+    /// Step down and restart from the data in storage after a storage error, as configured by
+    /// [`ErrorPolicy::StepDownAndRetry`].
+    ///
+    /// The error is surfaced in [`RaftMetrics::running_state`] until the restart succeeds.
+    /// It returns a [`Fatal`] error if the retries are used up, or `RaftCore` is shut down.
+    async fn restart_from_storage(
+        &mut self,
+        err: StorageError<C::NodeId>,
+        restarts: &mut Restarts<C>,
+    ) -> Result<(), Fatal<C>> {
+        let ErrorPolicy::StepDownAndRetry { max_retries, min, max } = self.config.error_policy else {
+            return Err(Fatal::StorageError(err));
+        };
+
+        tracing::error!(error = display(&err), "storage error, step down and restart RaftCore");
+
+        // Errors that are far apart are not counted as retries in a row.
+        let now = C::AsyncRuntime::now();
+        if let Some(t) = restarts.last_restart {
+            if now > t + Duration::from_millis(max) {
+                *restarts = Restarts::default();
+            }
+        }
+        let backoff = restarts.backoff.get_or_insert_with(|| {
+            Backoff::from_policy(ExponentialBackoff::new(
+                Duration::from_millis(min),
+                Duration::from_millis(max),
+            ))
+        });
+
+        {
+            let mut curr = self.tx_metrics.borrow().clone();
+            curr.running_state = Err(Fatal::StorageError(err.clone()));
+            let _ = self.tx_metrics.send(curr);
+        }
+
+        // Step down: stop replication and fail the pending client requests.
+        self.remove_all_replication().await;
+        self.leader_data = None;
+
+        for (_log_index, tx) in std::mem::take(&mut self.client_resp_channels) {
+            tx.send(Err(ClientWriteError::ForwardToLeader(ForwardToLeader::empty())));
+        }
+        self.write_deadlines.clear();
+        self.log_cache.truncate(0);
+
+        let Some(mut state_machine) = self.sm_handle.take_state_machine().await else {
+            tracing::error!("state machine worker panicked, can not restart RaftCore");
+            return Err(Fatal::StorageError(err));
+        };
+
+        let mut last_err = err;
+        let state = loop {
+            if restarts.retries >= max_retries {
+                tracing::error!(
+                    error = display(&last_err),
+                    "retried {} times, shutdown RaftCore",
+                    restarts.retries
+                );
+                return Err(Fatal::StorageError(last_err));
+            }
+            restarts.retries += 1;
+
+            // Safe unwrap: the exponential backoff is infinite.
+            let delay = backoff.next().unwrap();
+            tracing::info!("restart RaftCore in {:?}, retry: {}", delay, restarts.retries);
+
+            select! {
+                _ = self.cancel.cancelled() => {
+                    return Err(Fatal::Stopped);
+                }
+                _ = C::AsyncRuntime::sleep(delay) => {}
+            }
+
+            let mut helper = StorageHelper::new(&mut self.log_store, &mut state_machine)
+                .log_corruption_policy(self.config.log_corruption_policy);
+
+            match helper.get_initial_state().await {
+                Ok(state) => break state,
+                Err(e) => {
+                    tracing::error!(error = display(&e), "failed to load state from storage");
+                    last_err = e;
+                }
+            }
+        };
+
+        // The notifications from the previous run are stale.
+        while self.rx_notify.try_recv().is_ok() {}
+
+        // Keep the sm::Command seq increasing, in case a spawned snapshot building task responds.
+        let seq = self.engine.output.last_sm_seq();
+        self.engine = Engine::new(state, self.engine.config.clone());
+        self.engine.output.seq = seq;
+        self.command_state.finished_sm_seq = seq;
+        // Like the queued commands of the old engine, the parked ones are dropped.
+        self.waiting_sm_commands.clear();
+
+        self.sm_handle = Worker::spawn(
+            state_machine,
+            self.tx_notify.clone(),
+            self.membership_history.clone(),
+            self.config.offload_snapshot_build,
+        );
+        restarts.last_restart = Some(C::AsyncRuntime::now());
+
+        tracing::info!("RaftCore restarted from storage");
+        Ok(())
+    }
+
     #[tracing::instrument(level="trace", skip_all, fields(id=display(self.id), cluster=%self.config.cluster_name))]
-    async fn do_main(&mut self, rx_shutdown: OneshotReceiverOf<C, ()>) -> Result<Infallible, Fatal<C>> {
+    async fn do_main(&mut self) -> Result<Infallible, Fatal<C>> {
         tracing::debug!("raft node is initializing");
 
         self.engine.startup();
@@ -259,13 +623,111 @@ where
         // Initialize metrics.
         self.report_metrics(None);
 
-        self.runtime_loop(rx_shutdown).await
+        self.runtime_loop().await
+    }
+
+    /// Returns true if this leader holds a lease, during which no other node can be elected.
+    ///
+    /// A voter does not grant a vote to another candidate within the leader lease since it
+    /// received the last heartbeat. The lease of the leader starts when the last heartbeat
+    /// acknowledged by a quorum was sent, and is shortened by the clock drift bound.
+    fn is_leader_lease_valid(&mut self) -> bool {
+        // A voter grants a vote to another candidate at any time.
+        if !self.engine.config.reject_vote_within_lease {
+            return false;
+        }
+
+        if self.leader_data.as_ref().map_or(true, |l| l.lease_revoked) {
+            return false;
+        }
+
+        if self.leader_transfer.is_some() {
+            return false;
+        }
+
+        let lease = self.engine.config.timer_config.leader_lease;
+        let Some(lease) = lease.checked_sub(Duration::from_millis(self.config.lease_read_clock_drift)) else {
+            return false;
+        };
+
+        let Some(leading) = self.engine.internal_server_state.leading_mut() else {
+            return false;
+        };
+
+        let Some(acked) = leading.last_quorum_acked_time() else {
+            return false;
+        };
+
+        let now = C::AsyncRuntime::now();
+        tracing::debug!(
+            acked = debug(acked),
+            lease = debug(lease),
+            now = debug(now),
+            "{}",
+            func_name!()
+        );
+
+        now < acked + lease
+    }
+
+    /// Wait for the cancelled replication tasks to quit.
+    async fn join_replication_tasks(&mut self) {
+        let Some(l) = &mut self.leader_data else {
+            return;
+        };
+
+        for (target, s) in std::mem::take(&mut l.replications) {
+            let res = s.join_handle.await;
+            tracing::debug!(res = debug(&res), "replication to {} quit", target);
+        }
     }
 
     /// Handle `is_leader` requests.
     ///
-    /// Send heartbeat to all voters. We respond once we have
-    /// a quorum of agreement.
+    /// With [`ReadPolicy::LeaseRead`] it responds at once if the leader lease has not expired.
+    /// Otherwise the request is queued, and is served by the next heartbeat round, see
+    /// [`Self::confirm_leadership()`].
+    #[tracing::instrument(level = "trace", skip(self, tx))]
+    pub(super) async fn handle_check_is_leader_request(&mut self, read_policy: ReadPolicy, tx: ClientReadTx<C>) {
+        match self.engine.leader_handler() {
+            Ok(mut lh) => lh.ensure_first_entry_proposed(),
+            Err(forward) => {
+                let _ = tx.send(Err(forward.into()));
+                return;
+            }
+        }
+
+        if read_policy == ReadPolicy::LeaseRead && self.is_leader_lease_valid() {
+            let resp = self.read_index_resp();
+            let _ = tx.send(Ok(resp));
+            return;
+        }
+
+        self.read_index.queued.push(tx);
+
+        if !self.read_index.in_flight {
+            self.confirm_leadership().await;
+        }
+    }
+
+    /// Returns the log id up to which the state machine should apply for a read, and the last
+    /// applied log id.
+    fn read_index_resp(&mut self) -> (Option<LogIdOf<C>>, Option<LogIdOf<C>>) {
+        // Safe unwrap: it is called only when this node is the leader.
+        let read_log_id = self.engine.leader_handler().unwrap().get_read_log_id();
+
+        // TODO: this applied is a little stale when being returned to client.
+        //       Fix this when the following heartbeats are replaced with calling RaftNetwork.
+        let applied = self.engine.state.io_applied().copied();
+
+        (read_log_id, applied)
+    }
+
+    /// Send heartbeat to all voters to serve the queued read requests in one round. We respond
+    /// to all of them once we have a quorum of agreement.
+    ///
+    /// When the round finishes, `Notify::ReadIndexRoundDone` is sent back to start the next round
+    /// for the requests that arrived meanwhile.
     ///
     /// Why:
     /// To ensure linearizability, a read request proposed at time `T1` confirms this node's
@@ -273,39 +735,37 @@ where
     /// this node.
     // TODO: the second condition is such a read request can only read from state machine only when the last log it sees
     //       at `T1` is committed.
-    #[tracing::instrument(level = "trace", skip(self, tx))]
-    pub(super) async fn handle_check_is_leader_request(&mut self, tx: ClientReadTx<C>) {
-        // Setup sentinel values to track when we've received majority confirmation of leadership.
-
-        let resp = {
-            let l = self.engine.leader_handler();
-            let lh = match l {
-                Ok(leading_handler) => leading_handler,
-                Err(forward) => {
-                    let _ = tx.send(Err(forward.into()));
-                    return;
-                }
-            };
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(super) async fn confirm_leadership(&mut self) {
+        if let Err(forward) = self.engine.leader_handler() {
+            for tx in std::mem::take(&mut self.read_index.queued) {
+                let _ = tx.send(Err(forward.clone().into()));
+            }
+            return;
+        }
 
-            let read_log_id = lh.get_read_log_id();
+        let txs = self.read_index.start_round();
+        if txs.is_empty() {
+            return;
+        }
 
-            // TODO: this applied is a little stale when being returned to client.
-            //       Fix this when the following heartbeats are replaced with calling RaftNetwork.
-            let applied = self.engine.state.io_applied().copied();
+        tracing::debug!(batch_size = txs.len(), "confirm leadership for read requests");
 
-            (read_log_id, applied)
-        };
+        let resp = self.read_index_resp();
 
         let my_id = self.id;
         let my_vote = *self.engine.state.vote_ref();
-        let ttl = Duration::from_millis(self.config.heartbeat_interval);
+        let ttl = self.runtime_config.rpc_timeout(RPCTypes::AppendEntries);
         let eff_mem = self.engine.state.membership_state.effective().clone();
         let core_tx = self.tx_notify.clone();
 
         let mut granted = btreeset! {my_id};
 
         if eff_mem.is_quorum(granted.iter()) {
-            let _ = tx.send(Ok(resp));
+            for tx in txs {
+                let _ = tx.send(Ok(resp));
+            }
+            self.read_index.finish_round();
             return;
         }
 
@@ -324,22 +784,27 @@ where
                 continue;
             }
 
-            let rpc = AppendEntriesRequest {
+            let mut rpc = AppendEntriesRequest {
                 vote: my_vote,
                 prev_log_id: progress.matching,
                 entries: vec![],
                 leader_commit: self.engine.state.committed().copied(),
+                context: RpcContext::default(),
             };
 
             // Safe unwrap(): target is in membership
             let target_node = eff_mem.get_node(&target).unwrap().clone();
             let mut client = self.network.new_client(target, &target_node).await;
+            N::inject_context(&mut rpc.context);
+            self.auth.sign_append_entries(target, &mut rpc);
 
-            let option = RPCOption::new(ttl);
+            let recorder = RpcRecorder::new(target, client.observer(), self.rtt.clone());
+            let option = recorder.option(ttl);
 
             let fu = async move {
+                let start = C::AsyncRuntime::now();
                 let outer_res = C::AsyncRuntime::timeout(ttl, client.append_entries(rpc, option)).await;
-                match outer_res {
+                let res = match outer_res {
                     Ok(append_res) => match append_res {
                         Ok(x) => Ok((target, x)),
                         Err(err) => Err((target, err)),
@@ -354,7 +819,11 @@ where
 
                         Err((target, RPCError::Timeout(timeout_err)))
                     }
-                }
+                };
+
+                let error = res.as_ref().err().map(|(_, e)| RPCErrorKind::from(e));
+                recorder.record(RPCTypes::AppendEntries, 0, start.elapsed(), error);
+                res
             };
 
             let fu = fu.instrument(tracing::debug_span!("spawn_is_leader", target = target.to_string()));
@@ -363,7 +832,7 @@ where
             pending.push(task);
         }
 
-        let waiting_fu = async move {
+        let confirm_fu = async move {
             // Handle responses as they return.
             while let Some(res) = pending.next().await {
                 let (target, append_res) = match res {
@@ -400,568 +869,1317 @@ where
 
                     // we are no longer leader so error out early
                     let err = ForwardToLeader::empty();
-                    let _ = tx.send(Err(err.into()));
-                    return;
+                    return Err(err.into());
                 }
 
                 granted.insert(target);
 
                 if eff_mem.is_quorum(granted.iter()) {
-                    let _ = tx.send(Ok(resp));
-                    return;
+                    return Ok(resp);
                 }
             }
 
             // If we've hit this location, then we've failed to gather needed confirmations due to
             // request failures.
 
-            let _ = tx.send(Err(QuorumNotEnough {
+            Err(QuorumNotEnough {
                 cluster: eff_mem.membership().to_string(),
                 got: granted,
             }
-            .into()));
+            .into())
         };
 
-        // TODO: do not spawn, manage read requests with a queue by RaftCore
+        let core_tx = self.tx_notify.clone();
+        let waiting_fu = async move {
+            let res: Result<_, CheckIsLeaderError<C>> = confirm_fu.await;
+
+            for tx in txs {
+                let _ = tx.send(res.clone());
+            }
+
+            let send_res = core_tx.send(Notify::ReadIndexRoundDone);
+            if let Err(_e) = send_res {
+                tracing::error!("fail to send ReadIndexRoundDone to RaftCore");
+            }
+        };
 
         // False positive lint warning(`non-binding `let` on a future`): https://github.com/rust-lang/rust-clippy/issues/9932
         #[allow(clippy::let_underscore_future)]
         let _ = C::AsyncRuntime::spawn(waiting_fu.instrument(tracing::debug_span!("spawn_is_leader_waiting")));
     }
 
-    /// Submit change-membership by writing a Membership log entry.
-    ///
-    /// If `retain` is `true`, removed `voter` will becomes `learner`. Otherwise they will
-    /// be just removed.
-    ///
-    /// Changing membership includes changing voters config or adding/removing learners:
-    ///
-    /// - To change voters config, it will build a new **joint** config. If it already a joint
-    ///   config, it returns the final uniform config.
-    /// - Adding a learner does not affect election, thus it does not need to enter joint consensus.
-    ///   But it still has to wait for the previous membership to commit. Otherwise a second
-    ///   proposed membership implies the previous one is committed.
-    // ---
-    // TODO: This limit can be removed if membership_state is replaced by a list of membership logs.
-    //       Because allowing this requires the engine to be able to store more than 2
-    //       membership logs. And it does not need to wait for the previous membership log to commit
-    //       to propose the new membership log.
-    #[tracing::instrument(level = "debug", skip(self, tx))]
-    pub(super) fn change_membership(
+    /// Forward a client write request to `target`, which is believed to be the leader, and send
+    /// back the response via `tx`.
+    #[tracing::instrument(level = "debug", skip(self, app_data, tx))]
+    pub(super) async fn forward_client_write(
         &mut self,
-        changes: ChangeMembers<C::NodeId, C::Node>,
-        retain: bool,
-        tx: ResponderOf<C>,
+        target: C::NodeId,
+        node: C::Node,
+        app_data: C::D,
+        tx: ForwardClientWriteTx<C>,
     ) {
-        let res = self.engine.state.membership_state.change_handler().apply(changes, retain);
-        let new_membership = match res {
-            Ok(x) => x,
-            Err(e) => {
-                tx.send(Err(ClientWriteError::ChangeMembershipError(e)));
-                return;
-            }
+        let mut client = self.network.new_client(target, &node).await;
+        let ttl = self.config.forward_client_write_timeout();
+
+        let fu = async move {
+            let res = C::AsyncRuntime::timeout(ttl, client.forward_client_write(app_data, RPCOption::new(ttl))).await;
+            let res = res.unwrap_or_else(|_| {
+                let err = AnyError::error(format!("timeout after {:?} forwarding client write to {}", ttl, target));
+                Err(RPCError::Network(NetworkError::new(&err)))
+            });
+            let _ = tx.send(res);
         };
 
-        let ent = C::Entry::new_membership(LogId::default(), new_membership);
-        self.write_entry(ent, Some(tx));
+        let _handle = C::AsyncRuntime::spawn(
+            fu.instrument(tracing::debug_span!("forward_client_write", target = display(target))),
+        );
     }
 
-    /// Write a log entry to the cluster through raft protocol.
-    ///
-    /// I.e.: append the log entry to local store, forward it to a quorum(including the leader),
-    /// waiting for it to be committed and applied.
-    ///
-    /// The result of applying it to state machine is sent to `resp_tx`, if it is not `None`.
-    /// The calling side may not receive a result from `resp_tx`, if raft is shut down.
-    #[tracing::instrument(level = "debug", skip_all, fields(id = display(self.id)))]
-    pub fn write_entry(&mut self, entry: C::Entry, resp_tx: Option<ResponderOf<C>>) -> bool {
-        tracing::debug!(payload = display(&entry), "write_entry");
-
-        let (mut lh, tx) = if let Some((lh, tx)) = self.engine.get_leader_handler_or_reject(resp_tx) {
-            (lh, tx)
-        } else {
-            return false;
+    /// Ask `target`, which is believed to be the leader, for the read log id, and send back the
+    /// response via `tx`.
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    pub(super) async fn forward_get_read_log_id(
+        &mut self,
+        target: C::NodeId,
+        node: C::Node,
+        read_policy: ReadPolicy,
+        tx: ForwardReadTx<C>,
+    ) {
+        let mut client = self.network.new_client(target, &node).await;
+        let ttl = self.config.forward_client_write_timeout();
+
+        let fu = async move {
+            let res = C::AsyncRuntime::timeout(ttl, client.get_read_log_id(read_policy, RPCOption::new(ttl))).await;
+            let res = res.unwrap_or_else(|_| {
+                let err = AnyError::error(format!("timeout after {:?} getting read log id from {}", ttl, target));
+                Err(RPCError::Network(NetworkError::new(&err)))
+            });
+            let _ = tx.send(res);
         };
 
-        let entries = vec![entry];
-        // TODO: it should returns membership config error etc. currently this is done by the
-        //       caller.
-        lh.leader_append_entries(entries);
-        let index = lh.state.last_log_id().unwrap().index;
+        let _handle = C::AsyncRuntime::spawn(fu.instrument(tracing::debug_span!(
+            "forward_get_read_log_id",
+            target = display(target)
+        )));
+    }
 
-        // Install callback channels.
-        if let Some(tx) = tx {
-            self.client_resp_channels.insert(index, tx);
+    /// Send the latest snapshot of this node to `rpc.target` on behalf of the leader, and send
+    /// back the response via `tx`.
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    pub(super) async fn transfer_snapshot(&mut self, rpc: TransferSnapshotRequest<C>, tx: TransferSnapshotTx<C>) {
+        let span = tracing::debug_span!("handle_transfer_snapshot");
+        N::extract_context(&rpc.context, &span);
+
+        let my_vote = *self.engine.state.vote_ref();
+        if my_vote > rpc.vote {
+            tracing::info!(
+                my_vote = display(&my_vote),
+                "reject transfer snapshot request with a smaller vote: {}",
+                rpc
+            );
+            let _ = tx.send(Ok(TransferSnapshotResponse {
+                vote: my_vote,
+                last_log_id: None,
+            }));
+            return;
         }
 
-        true
-    }
+        let (snapshot_tx, snapshot_rx) = C::AsyncRuntime::oneshot();
+        if let Err(e) = self.sm_handle.send(sm::Command::get_snapshot(None, snapshot_tx)) {
+            tracing::error!(error = display(e), "error sending GetSnapshot to sm worker");
+            return;
+        }
 
-    /// Send a heartbeat message to every followers/learners.
-    ///
-    /// Currently heartbeat is a blank log
-    #[tracing::instrument(level = "debug", skip_all, fields(id = display(self.id)))]
-    pub fn send_heartbeat(&mut self, emitter: impl Display) -> bool {
-        tracing::debug!(now = debug(InstantOf::<C>::now()), "send_heartbeat");
+        let target = rpc.target;
+        let mut client = self.network.new_client(target, &rpc.target_node).await;
 
-        let mut lh = if let Some((lh, _)) = self.engine.get_leader_handler_or_reject(None) {
-            lh
-        } else {
-            tracing::debug!(
-                now = debug(InstantOf::<C>::now()),
-                "{} failed to send heartbeat",
-                emitter
-            );
-            return false;
-        };
-
-        lh.send_heartbeat();
-
-        tracing::debug!("{} triggered sending heartbeat", emitter);
-        true
-    }
+        let mut option = RPCOption::new(self.config.install_snapshot_timeout());
+        option.snapshot_chunk_size = Some(self.config.snapshot_max_chunk_size as usize);
+        option.compress_snapshot = self.config.compress_snapshot;
+        N::inject_context(&mut option.context);
 
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub fn flush_metrics(&mut self) {
-        let leader_metrics = if let Some(leader) = self.engine.internal_server_state.leading() {
-            let prog = &leader.progress;
-            Some(prog.iter().map(|(id, p)| (*id, *p.borrow())).collect())
-        } else {
-            None
-        };
-        self.report_metrics(leader_metrics);
-    }
+        let auth = self.auth.clone();
+        let cancel = self.cancel.child_token();
 
-    /// Report a metrics payload on the current state of the Raft node.
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn report_metrics(&mut self, replication: Option<ReplicationMetrics<C::NodeId>>) {
-        let last_quorum_acked = self.last_quorum_acked_time();
-        let millis_since_quorum_ack = last_quorum_acked.map(|t| t.elapsed().as_millis() as u64);
+        let fu = async move {
+            // The sm worker quits only when RaftCore quits, the caller then gets a Fatal error.
+            let Ok(Ok(snapshot)) = snapshot_rx.await else {
+                return;
+            };
 
-        let st = &self.engine.state;
+            let last_log_id = snapshot.as_ref().and_then(|s| s.meta.last_log_id);
+            let snapshot = match snapshot {
+                Some(s) if s.meta.last_log_id >= rpc.min_last_log_id => s,
+                _ => {
+                    let err = SnapshotTooOld {
+                        min_last_log_id: rpc.min_last_log_id,
+                        last_log_id,
+                    };
+                    tracing::info!(error = display(&err), "can not transfer snapshot to {}", target);
+                    let _ = tx.send(Err(err.into()));
+                    return;
+                }
+            };
 
-        let membership_config = st.membership_state.effective().stored_membership().clone();
-        let current_leader = self.current_leader();
+            auth.sign_snapshot(target, &rpc.vote, &snapshot.meta, &mut option.context);
 
-        let m = RaftMetrics {
-            running_state: Ok(()),
-            id: self.id,
+            let closed = async move {
+                cancel.cancelled().await;
+                ReplicationClosed::new("RaftCore is shutting down")
+            };
 
-            // --- data ---
-            current_term: st.vote_ref().leader_id().get_term(),
-            vote: *st.io_state().vote(),
-            last_log_index: st.last_log_id().index(),
-            last_applied: st.io_applied().copied(),
-            snapshot: st.io_snapshot_last_log_id().copied(),
-            purged: st.io_purged().copied(),
+            let res = client.full_snapshot(rpc.vote, snapshot, closed, option).await;
+            let res = match res {
+                Ok(resp) => Ok(TransferSnapshotResponse {
+                    vote: resp.vote,
+                    last_log_id,
+                }),
+                Err(e) => {
+                    tracing::warn!(error = display(&e), "failed to transfer snapshot to {}", target);
+                    Err(NetworkError::new(&e).into())
+                }
+            };
+            let _ = tx.send(res);
+        };
 
-            // --- cluster ---
-            state: st.server_state,
-            current_leader,
-            millis_since_quorum_ack,
-            membership_config: membership_config.clone(),
+        let _handle = C::AsyncRuntime::spawn(fu.instrument(span));
+    }
 
-            // --- replication ---
-            replication: replication.clone(),
-        };
+    /// Ask `source` to send a snapshot to `target` on behalf of this leader.
+    ///
+    /// If `source` fails, or the snapshot it sent is not new enough, the snapshot is sent by the
+    /// replication stream of this leader instead, with `fallback`.
+    #[tracing::instrument(level = "debug", skip(self, tx_repl, fallback))]
+    pub(crate) async fn delegate_snapshot_transfer(
+        &mut self,
+        source: C::NodeId,
+        target: C::NodeId,
+        request_id: RequestId,
+        tx_repl: mpsc::UnboundedSender<Replicate<C>>,
+        fallback: Replicate<C>,
+    ) {
+        let membership = self.engine.state.membership_state.effective();
+        // Safe unwrap(): source and target are in membership
+        let source_node = membership.get_node(&source).unwrap().clone();
+        let target_node = membership.get_node(&target).unwrap().clone();
 
-        let data_metrics = RaftDataMetrics {
-            last_log: st.last_log_id().copied(),
-            last_applied: st.io_applied().copied(),
-            snapshot: st.io_snapshot_last_log_id().copied(),
-            purged: st.io_purged().copied(),
-            millis_since_quorum_ack,
-            replication,
-        };
+        let vote = *self.engine.state.vote_ref();
+        let session_id = ReplicationSessionId::new(vote, *membership.log_id());
+        let min_last_log_id = self.engine.state.last_purged_log_id().copied();
 
-        let server_metrics = RaftServerMetrics {
-            id: self.id,
-            vote: *st.io_state().vote(),
-            state: st.server_state,
-            current_leader,
-            membership_config,
+        let mut rpc = TransferSnapshotRequest {
+            vote,
+            target,
+            target_node,
+            min_last_log_id,
+            context: RpcContext::default(),
         };
+        N::inject_context(&mut rpc.context);
+        self.auth.sign_transfer_snapshot(source, &mut rpc);
+
+        let mut client = self.network.new_client(source, &source_node).await;
+        let ttl = self.config.install_snapshot_timeout();
+        let tx_notify = self.tx_notify.clone();
+
+        let fu = async move {
+            let start_time = C::AsyncRuntime::now();
+            let res = client.transfer_snapshot(rpc, RPCOption::new(ttl)).await;
+
+            match res {
+                Ok(resp) if resp.vote > vote => {
+                    let _ = tx_notify.send(Notify::HigherVote {
+                        target: source,
+                        higher: resp.vote,
+                        sender_vote: vote,
+                    });
+                }
+                Ok(resp) if resp.last_log_id >= min_last_log_id => {
+                    tracing::info!("{} transferred snapshot to {}: {}", source, target, resp);
 
-        // Start to send metrics
-        // `RaftMetrics` is sent last, because `Wait` only examines `RaftMetrics`
-        // but not `RaftDataMetrics` and `RaftServerMetrics`.
-        // Thus if `RaftMetrics` change is perceived, the other two should have been updated.
-
-        self.tx_data_metrics.send_if_modified(|metrix| {
-            if data_metrics.ne(metrix) {
-                *metrix = data_metrics.clone();
-                return true;
+                    let _ = tx_notify.send(Notify::Network {
+                        response: replication::Response::Progress {
+                            target,
+                            request_id,
+                            result: Ok(ReplicationResult::new(start_time, Ok(resp.last_log_id))),
+                            session_id,
+                        },
+                    });
+                }
+                res => {
+                    tracing::info!(
+                        "{} did not transfer snapshot to {}: {:?}; send it by the leader",
+                        source,
+                        target,
+                        res
+                    );
+                    let _ = tx_repl.send(fallback);
+                }
             }
-            false
-        });
+        };
 
-        self.tx_server_metrics.send_if_modified(|metrix| {
-            if server_metrics.ne(metrix) {
-                *metrix = server_metrics.clone();
-                return true;
-            }
-            false
-        });
+        let _handle = C::AsyncRuntime::spawn(fu.instrument(tracing::debug_span!(
+            "delegate_snapshot_transfer",
+            source = display(source),
+            target = display(target)
+        )));
+    }
 
-        tracing::debug!("report_metrics: {}", m);
-        let res = self.tx_metrics.send(m);
+    /// Choose a node other than `target` to send a snapshot to `target`, i.e., the one that has
+    /// replicated the most logs, if it has replicated all purged logs.
+    ///
+    /// A witness neither sends nor receives a delegated snapshot: it has no data to send, and the
+    /// leader sends it an empty snapshot.
+    pub(crate) fn snapshot_transfer_source(&self, target: C::NodeId) -> Option<C::NodeId> {
+        let leading = self.engine.internal_server_state.leading()?;
+        let purged = self.engine.state.last_purged_log_id().copied();
+        let membership = self.engine.state.membership_state.effective();
+
+        if membership.is_witness(&target) {
+            return None;
+        }
 
-        if let Err(err) = res {
-            tracing::error!(error=%err, id=display(self.id), "error reporting metrics");
+        let (source, matching) = leading
+            .progress
+            .iter()
+            .filter(|(id, _)| *id != target && *id != self.id && !membership.is_witness(id))
+            .map(|(id, p)| {
+                let matching: Option<LogId<C::NodeId>> = *p.borrow();
+                (*id, matching)
+            })
+            .max_by_key(|(_, matching)| *matching)?;
+
+        if matching >= purged && matching.is_some() {
+            Some(source)
+        } else {
+            None
         }
     }
 
-    /// Handle the admin command `initialize`.
+    /// Submit change-membership by writing a Membership log entry.
     ///
-    /// It is allowed to initialize only when `last_log_id.is_none()` and `vote==(0,0)`.
-    /// See: [Conditions for initialization][precondition]
+    /// If `retain` is `true`, removed `voter` will becomes `learner`. Otherwise they will
+    /// be just removed.
     ///
-    /// [precondition]: crate::docs::cluster_control::cluster_formation#preconditions-for-initialization
+    /// Changing membership includes changing voters config or adding/removing learners:
+    ///
+    /// - To change voters config, it will build a new **joint** config. If it already a joint
+    ///   config, it returns the final uniform config.
+    /// - Adding a learner does not affect election, thus it does not need to enter joint consensus.
+    ///   But it still has to wait for the previous membership to commit. Otherwise a second
+    ///   proposed membership implies the previous one is committed.
+    // ---
+    // TODO: This limit can be removed if membership_state is replaced by a list of membership logs.
+    //       Because allowing this requires the engine to be able to store more than 2
+    //       membership logs. And it does not need to wait for the previous membership log to commit
+    //       to propose the new membership log.
     #[tracing::instrument(level = "debug", skip(self, tx))]
-    pub(crate) fn handle_initialize(
+    pub(super) fn change_membership(
         &mut self,
-        member_nodes: BTreeMap<C::NodeId, C::Node>,
-        tx: ResultSender<C, (), InitializeError<C>>,
-    ) {
-        tracing::debug!(member_nodes = debug(&member_nodes), "{}", func_name!());
+        changes: ChangeMembers<C::NodeId, C::Node>,
+        retain: bool,
+        guard: bool,
+        tx: ResponderOf<C>,
+    ) -> bool {
+        let res = self.engine.state.membership_state.change_handler().apply(changes, retain);
+        let new_membership = match res {
+            Ok(x) => x,
+            Err(e) => {
+                tx.send(Err(ClientWriteError::ChangeMembershipError(e)));
+                return false;
+            }
+        };
 
-        let membership = Membership::from(member_nodes);
+        let critical = new_membership.critical_zones();
+        if !critical.is_empty() {
+            tracing::warn!(
+                zones = debug(&critical),
+                "a quorum of voters shares a zone, the failure of which stops the cluster"
+            );
+        }
 
-        let entry = C::Entry::new_membership(LogId::default(), membership);
-        let res = self.engine.initialize(entry);
-        self.engine.output.push_command(Command::Respond {
-            when: None,
-            resp: Respond::new(res, tx),
-        });
-    }
+        if guard && self.config.membership_change_guard {
+            if let Err(e) = self.check_membership_change(&new_membership) {
+                tracing::warn!(error = display(&e), "refuse membership change");
+                tx.send(Err(ClientWriteError::ChangeMembershipError(e.into())));
+                return false;
+            }
+        }
 
-    /// Trigger a snapshot building(log compaction) job if there is no pending building job.
-    #[tracing::instrument(level = "debug", skip(self))]
-    pub(crate) fn trigger_snapshot(&mut self) {
-        tracing::debug!("{}", func_name!());
-        self.engine.snapshot_handler().trigger_snapshot();
+        let ent = C::Entry::new_membership(LogId::default(), new_membership);
+        self.write_entry(ent, Some(tx))
     }
 
-    /// Reject a request due to the Raft node being in a state which prohibits the request.
-    #[tracing::instrument(level = "trace", skip(self, tx))]
-    pub(crate) fn reject_with_forward_to_leader<T: OptionalSend, E>(&self, tx: ResultSender<C, T, E>)
-    where E: From<ForwardToLeader<C>> + OptionalSend {
-        let mut leader_id = self.current_leader();
-        let leader_node = self.get_leader_node(leader_id);
+    /// Check if the last membership change started by `Raft::change_membership()` is at least
+    /// [`Config::membership_change_min_interval`] ago.
+    ///
+    /// Nothing is checked if this node is not a leader.
+    fn check_membership_change_interval(&self) -> Result<(), ChangeMembershipTooFrequent> {
+        let Some(interval) = self.config.membership_change_min_interval() else {
+            return Ok(());
+        };
 
-        // Leader is no longer a node in the membership config.
-        if leader_node.is_none() {
-            leader_id = None;
-        }
+        let Some(last) = self.leader_data.as_ref().and_then(|l| l.last_membership_change) else {
+            return Ok(());
+        };
 
-        let err = ForwardToLeader { leader_id, leader_node };
+        let elapsed = C::AsyncRuntime::now() - last;
+        if elapsed < interval {
+            return Err(ChangeMembershipTooFrequent { elapsed, interval });
+        }
 
-        let _ = tx.send(Err(err.into()));
+        Ok(())
     }
 
-    #[tracing::instrument(level = "debug", skip(self))]
-    pub(crate) fn current_leader(&self) -> Option<C::NodeId> {
-        tracing::debug!(
-            self_id = display(self.id),
-            vote = display(self.engine.state.vote_ref()),
-            "get current_leader"
-        );
-
-        let vote = self.engine.state.vote_ref();
+    /// Check if changing to `new_membership` keeps the fault tolerance of the cluster.
+    ///
+    /// Only the nodes this leader has heard from within the leader lease are counted. The fault
+    /// tolerance is that of the last config of a joint config, which is the config to change to.
+    /// Nothing is checked if this node is not a leader, which can not change the membership.
+    fn check_membership_change(&self, new_membership: &Membership<C>) -> Result<(), UnsafeMembershipChange> {
+        let Some(alive) = self.alive_nodes() else {
+            return Ok(());
+        };
 
-        if !vote.is_committed() {
-            return None;
-        }
+        let curr = self.engine.state.membership_state.effective().membership();
 
-        // Safe unwrap(): vote that is committed has to already have voted for some node.
-        let id = vote.leader_id().voted_for().unwrap();
+        let fault_tolerance = |m: &Membership<C>| {
+            let voters = m.get_joint_config().last().map(|c| c.iter().copied().collect::<Vec<_>>()).unwrap_or_default();
+            m.quorum_policy().fault_tolerance(&voters, &alive) as u64
+        };
 
-        // TODO: `is_voter()` is slow, maybe cache `current_leader`,
-        //       e.g., only update it when membership or vote changes
-        if self.engine.state.membership_state.effective().is_voter(&id) {
-            Some(id)
-        } else {
-            tracing::debug!("id={} is not a voter", id);
-            None
+        let curr_ft = fault_tolerance(curr);
+        let new_ft = fault_tolerance(new_membership);
+        let min_ft = self.config.membership_change_min_fault_tolerance;
+
+        if new_ft < min_ft && new_ft < curr_ft {
+            return Err(UnsafeMembershipChange {
+                reason: format!(
+                    "fault tolerance drops from {} to {}, below {}, with alive nodes {:?}",
+                    curr_ft, new_ft, min_ft, alive
+                ),
+            });
         }
-    }
 
-    /// Retrieves the most recent timestamp that is acknowledged by a quorum.
-    ///
-    /// This function returns the latest known time at which the leader received acknowledgment
-    /// from a quorum of followers, indicating its leadership is current and recognized.
-    /// If the node is not a leader or no acknowledgment has been received, `None` is returned.
-    fn last_quorum_acked_time(&mut self) -> Option<InstantOf<C>> {
-        let leading = self.engine.internal_server_state.leading_mut();
-        leading.and_then(|l| l.last_quorum_acked_time())
-    }
+        // The alive nodes that store data and are kept by the last config, by zone.
+        let replicas = |m: &Membership<C>| {
+            let last = m.get_joint_config().last().cloned().unwrap_or_default();
+            let learners = m.learner_ids().collect::<BTreeSet<_>>();
 
-    pub(crate) fn get_leader_node(&self, leader_id: Option<C::NodeId>) -> Option<C::Node> {
-        let leader_id = match leader_id {
-            None => return None,
-            Some(x) => x,
+            let mut by_zone = BTreeMap::<String, usize>::new();
+            for (id, zone) in m.zones() {
+                let kept = last.contains(id) || learners.contains(id);
+                if kept && alive.contains(id) && !m.is_witness(id) {
+                    *by_zone.entry(zone.clone()).or_default() += 1;
+                }
+            }
+            by_zone
         };
 
-        self.engine.state.membership_state.effective().get_node(&leader_id).cloned()
+        let new_replicas = replicas(new_membership);
+        for zone in replicas(curr).keys() {
+            if !new_replicas.contains_key(zone) {
+                return Err(UnsafeMembershipChange {
+                    reason: format!("the last alive replica in zone {:?} is removed", zone),
+                });
+            }
+        }
+
+        Ok(())
     }
 
-    /// A temp wrapper to make non-blocking `append_to_log` a blocking.
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) async fn append_to_log<I>(
-        &mut self,
-        entries: I,
-        vote: Vote<C::NodeId>,
-        last_log_id: LogId<C::NodeId>,
-    ) -> Result<(), StorageError<C::NodeId>>
-    where
-        I: IntoIterator<Item = C::Entry> + OptionalSend,
-        I::IntoIter: OptionalSend,
-    {
-        tracing::debug!("append_to_log");
+    /// The nodes this leader has heard from within the leader lease, including itself.
+    ///
+    /// It returns `None` if this node is not a leader.
+    fn alive_nodes(&self) -> Option<BTreeSet<C::NodeId>> {
+        let leading = self.engine.internal_server_state.leading()?;
+
+        let now = C::AsyncRuntime::now();
+        let lease = self.engine.config.timer_config.leader_lease;
+
+        let mut alive = leading
+            .clock_progress
+            .iter()
+            .filter(|(_, t)| t.is_some_and(|t| now <= t + lease))
+            .map(|(id, _)| *id)
+            .collect::<BTreeSet<_>>();
+        alive.insert(self.id);
+
+        Some(alive)
+    }
 
-        let (tx, rx) = C::AsyncRuntime::oneshot();
-        let log_io_id = LogIOId::new(vote, Some(last_log_id));
+    /// The health of the nodes in every zone, as seen by this leader.
+    ///
+    /// It returns `None` if this node is not a leader.
+    fn zone_metrics(&self) -> Option<ZoneMetrics> {
+        let alive = self.alive_nodes()?;
+
+        let membership = self.engine.state.membership_state.effective().membership();
+        let critical = membership.critical_zones();
+
+        let mut zones = ZoneMetrics::new();
+        for (id, zone) in membership.zones() {
+            let health = zones.entry(zone.clone()).or_insert_with(|| ZoneHealth {
+                critical: critical.contains(zone),
+                ..Default::default()
+            });
+
+            if membership.is_voter(id) {
+                health.voters += 1;
+            } else {
+                health.learners += 1;
+            }
 
-        let callback = LogFlushed::new(log_io_id, tx);
+            if alive.contains(id) {
+                health.alive += 1;
+            }
+        }
 
-        self.log_store.append(entries, callback).await?;
-        rx.await
-            .map_err(|e| StorageIOError::write_logs(AnyError::error(e)))?
-            .map_err(|e| StorageIOError::write_logs(AnyError::error(e)))?;
-        Ok(())
+        Some(zones)
     }
 
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) async fn apply_to_state_machine(
-        &mut self,
-        seq: CommandSeq,
-        since: u64,
-        upto_index: u64,
-    ) -> Result<(), StorageError<C::NodeId>> {
-        tracing::debug!(upto_index = display(upto_index), "{}", func_name!());
-
-        let end = upto_index + 1;
-
-        debug_assert!(
-            since <= end,
-            "last_applied index {} should <= committed index {}",
-            since,
-            end
-        );
+    /// Write a log entry to the cluster through raft protocol.
+    ///
+    /// I.e.: append the log entry to local store, forward it to a quorum(including the leader),
+    /// waiting for it to be committed and applied.
+    ///
+    /// The result of applying it to state machine is sent to `resp_tx`, if it is not `None`.
+    /// The calling side may not receive a result from `resp_tx`, if raft is shut down.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = display(self.id)))]
+    pub fn write_entry(&mut self, entry: C::Entry, resp_tx: Option<ResponderOf<C>>) -> bool {
+        tracing::debug!(payload = display(&entry), "write_entry");
 
-        if since == end {
-            return Ok(());
+        if self.leader_transfer.is_some() {
+            tracing::info!("reject write: leadership is being transferred");
+            if let Some(tx) = resp_tx {
+                tx.send(Err(ForwardToLeader::empty().into()));
+            }
+            return false;
         }
 
-        let entries = self.log_store.get_log_entries(since..end).await?;
-        tracing::debug!(
-            entries = display(DisplaySlice::<_>(entries.as_slice())),
-            "about to apply"
-        );
+        let (mut lh, tx) = if let Some((lh, tx)) = self.engine.get_leader_handler_or_reject(resp_tx) {
+            (lh, tx)
+        } else {
+            return false;
+        };
 
-        let last_applied = *entries[entries.len() - 1].get_log_id();
+        let entries = vec![entry];
+        // TODO: it should returns membership config error etc. currently this is done by the
+        //       caller.
+        lh.leader_append_entries(entries);
+        let index = lh.state.last_log_id().unwrap().index;
 
-        let cmd = sm::Command::apply(entries).with_seq(seq);
-        self.sm_handle.send(cmd).map_err(|e| StorageIOError::apply(last_applied, AnyError::error(e)))?;
+        // Install callback channels.
+        if let Some(tx) = tx {
+            self.client_resp_channels.insert(index, tx);
+        }
 
-        Ok(())
+        true
     }
 
-    /// When received results of applying log entries to the state machine, send back responses to
-    /// the callers that proposed the entries.
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn handle_apply_result(&mut self, res: ApplyResult<C>) {
-        tracing::debug!(last_applied = display(res.last_applied), "{}", func_name!());
-
-        let mut results = res.apply_results.into_iter();
-        let mut applying_entries = res.applying_entries.into_iter();
+    /// Add a client write to the open batch, and append the batch to the log if it is full.
+    ///
+    /// The write is handled at once if this node is not a leader, so that it is rejected without
+    /// waiting for the batch to close.
+    fn coalesce_write(
+        &mut self,
+        entry: C::Entry,
+        tx: ResponderOf<C>,
+        deadline: Option<InstantOf<C>>,
+        window: Duration,
+    ) {
+        if self.engine.internal_server_state.leading().is_none() {
+            self.write_entry(entry, Some(tx));
+            return;
+        }
 
-        for log_index in res.since..res.end {
-            let ent = applying_entries.next().unwrap();
-            let apply_res = results.next().unwrap();
-            let tx = self.client_resp_channels.remove(&log_index);
+        self.write_batch.push(entry, tx, deadline, C::AsyncRuntime::now(), window);
 
-            Self::send_response(ent, apply_res, tx);
+        if self.write_batch.entries.len() as u64 >= self.config.write_coalesce_max_entries {
+            self.flush_write_batch();
         }
     }
 
-    /// Send result of applying a log entry to its client.
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub(super) fn send_response(entry: ApplyingEntry<C>, resp: C::R, tx: Option<ResponderOf<C>>) {
-        tracing::debug!(entry = debug(&entry), "send_response");
+    /// Close the open batch of client writes, if any, and append them to the log at once.
+    fn flush_write_batch(&mut self) {
+        let batch = self.write_batch.take();
+        if batch.is_empty() {
+            return;
+        }
 
-        let tx = match tx {
-            None => return,
-            Some(x) => x,
+        tracing::debug!(batch_size = batch.len(), "append coalesced client writes");
+
+        if self.leader_transfer.is_some() {
+            tracing::info!("reject write: leadership is being transferred");
+            for (_, tx, _) in batch {
+                tx.send(Err(ForwardToLeader::empty().into()));
+            }
+            return;
+        }
+
+        let mut lh = match self.engine.leader_handler() {
+            Ok(lh) => lh,
+            Err(forward_err) => {
+                for (_, tx, _) in batch {
+                    tx.send(Err(forward_err.clone().into()));
+                }
+                return;
+            }
         };
 
-        let membership = entry.membership;
+        let mut entries = Vec::with_capacity(batch.len());
+        let mut txs = Vec::with_capacity(batch.len());
+        for (entry, tx, deadline) in batch {
+            entries.push(entry);
+            txs.push((tx, deadline));
+        }
 
-        let res = Ok(ClientWriteResponse {
-            log_id: entry.log_id,
-            data: resp,
-            membership,
-        });
+        lh.leader_append_entries(entries);
+        let last_index = lh.state.last_log_id().unwrap().index;
 
-        tx.send(res);
+        // Install callback channels.
+        let first_index = last_index + 1 - txs.len() as u64;
+        for (index, (tx, deadline)) in (first_index..).zip(txs) {
+            self.client_resp_channels.insert(index, tx);
+            if let Some(deadline) = deadline {
+                self.write_deadlines.insert(index, deadline);
+            }
+        }
     }
 
-    /// Spawn a new replication stream returning its replication state handle.
-    #[tracing::instrument(level = "debug", skip(self))]
-    #[allow(clippy::type_complexity)]
-    pub(crate) async fn spawn_replication_stream(
-        &mut self,
-        target: C::NodeId,
-        progress_entry: ProgressEntry<C::NodeId>,
-    ) -> ReplicationHandle<C> {
-        // Safe unwrap(): target must be in membership
-        let target_node = self.engine.state.membership_state.effective().get_node(&target).unwrap();
+    /// Respond with a timeout error to the client writes that are not committed before their
+    /// deadline, and drop their response channels.
+    ///
+    /// A committed entry is not timed out because it is about to be applied.
+    fn expire_client_writes(&mut self) {
+        let committed = self.engine.state.committed().map(|x| x.index);
 
-        let membership_log_id = self.engine.state.membership_state.effective().log_id();
-        let network = self.network.new_client(target, target_node).await;
-        let snapshot_network = self.network.new_client(target, target_node).await;
+        for index in self.write_deadlines.pop_expired(C::AsyncRuntime::now()) {
+            if Some(index) <= committed {
+                continue;
+            }
 
-        let session_id = ReplicationSessionId::new(*self.engine.state.vote_ref(), *membership_log_id);
+            // The log id is unknown if the entry is being deleted because it conflicts with the
+            // logs of a new leader. The responder is left in place, to be answered with a
+            // `ForwardToLeader` when the conflicting logs are deleted.
+            let Some(log_id) = self.engine.state.get_log_id(index) else {
+                continue;
+            };
 
-        ReplicationCore::<C, N, LS>::spawn(
-            target,
-            session_id,
-            self.config.clone(),
-            self.engine.state.committed().copied(),
-            progress_entry.matching,
-            network,
-            snapshot_network,
-            self.log_store.get_log_reader().await,
-            self.sm_handle.new_snapshot_reader(),
-            self.tx_notify.clone(),
-            tracing::span!(parent: &self.span, Level::DEBUG, "replication", id=display(self.id), target=display(target)),
-        )
-    }
+            let Some(tx) = self.client_resp_channels.remove(&index) else {
+                continue;
+            };
 
-    /// Remove all replication.
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub async fn remove_all_replication(&mut self) {
-        tracing::info!("remove all replication");
+            tracing::info!(
+                log_id = display(log_id),
+                "client write is not committed before the deadline"
+            );
+            tx.send(Err(ClientWriteTimeout { log_id: Some(log_id) }.into()));
+        }
+    }
 
-        if let Some(l) = &mut self.leader_data {
-            let nodes = std::mem::take(&mut l.replications);
+    /// Send a heartbeat message to every followers/learners.
+    ///
+    /// Currently heartbeat is a blank log
+    #[tracing::instrument(level = "debug", skip_all, fields(id = display(self.id)))]
+    pub fn send_heartbeat(&mut self, emitter: impl Display) -> bool {
+        tracing::debug!(now = debug(C::AsyncRuntime::now()), "send_heartbeat");
 
+        let mut lh = if let Some((lh, _)) = self.engine.get_leader_handler_or_reject(None) {
+            lh
+        } else {
             tracing::debug!(
-                targets = debug(nodes.iter().map(|x| *x.0).collect::<Vec<_>>()),
-                "remove all targets from replication_metrics"
+                now = debug(C::AsyncRuntime::now()),
+                "{} failed to send heartbeat",
+                emitter
             );
+            return false;
+        };
 
-            for (target, s) in nodes {
-                let handle = s.join_handle;
-
-                // Drop sender to notify the task to shutdown
-                drop(s.tx_repl);
+        lh.send_heartbeat();
 
-                tracing::debug!("joining removed replication: {}", target);
-                let _x = handle.await;
-                tracing::info!("Done joining removed replication : {}", target);
-            }
-        } else {
-            unreachable!("it has to be a leader!!!");
-        };
+        tracing::debug!("{} triggered sending heartbeat", emitter);
+        true
     }
 
-    /// Run as many commands as possible.
-    ///
-    /// If there is a command that waits for a callback, just return and wait for
-    /// next RaftMsg.
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) async fn run_engine_commands(&mut self) -> Result<(), StorageError<C::NodeId>> {
-        if tracing::enabled!(Level::DEBUG) {
-            tracing::debug!("queued commands: start...");
-            for c in self.engine.output.iter_commands() {
-                tracing::debug!("queued commands: {:?}", c);
-            }
-            tracing::debug!("queued commands: end...");
-        }
-
-        while let Some(cmd) = self.engine.output.pop_command() {
-            tracing::debug!("run command: {:?}", cmd);
+    /// Broadcast a [`RoleChange`] if the server state changes, or this node becomes the leader of
+    /// another term.
+    fn report_role_change(&mut self, m: &RaftServerMetrics<C>) {
+        let (prev, prev_vote) = self.reported_role;
 
-            let res = self.run_command(cmd).await?;
+        let changed = prev != m.state || (m.state == ServerState::Leader && prev_vote != m.vote);
+        if !changed {
+            return;
+        }
 
-            if let Some(cmd) = res {
-                tracing::debug!("early return: postpone command: {:?}", cmd);
-                self.engine.output.postpone_command(cmd);
+        self.reported_role = (m.state, m.vote);
 
-                if tracing::enabled!(Level::DEBUG) {
-                    for c in self.engine.output.iter_commands().take(8) {
-                        tracing::debug!("postponed, first 8 queued commands: {:?}", c);
-                    }
-                }
+        let change = RoleChange {
+            id: self.id,
+            prev,
+            state: m.state,
+            vote: m.vote,
+            current_leader: m.current_leader,
+            membership_config: m.membership_config.clone(),
+        };
 
-                return Ok(());
-            }
-        }
+        tracing::info!(change = display(&change), "server state changed");
 
-        Ok(())
+        // No subscriber is not an error.
+        let _ = self.tx_role_change.send(change);
     }
 
-    /// Run an event handling loop
-    ///
-    /// It always returns a [`Fatal`] error upon returning.
-    #[tracing::instrument(level="debug", skip_all, fields(id=display(self.id)))]
-    async fn runtime_loop(&mut self, mut rx_shutdown: OneshotReceiverOf<C, ()>) -> Result<Infallible, Fatal<C>> {
-        // Ratio control the ratio of number of RaftMsg to process to number of Notify to process.
-        let mut balancer = Balancer::new(10_000);
+    /// Build the replication progress of every voter and learner other than this leader.
+    fn replication_status(&mut self) -> Result<BTreeMap<C::NodeId, ReplicationStatus<C>>, ForwardToLeader<C>> {
+        self.engine.leader_handler()?;
 
-        loop {
-            self.flush_metrics();
+        let (Some(leading), Some(l)) = (self.engine.internal_server_state.leading(), &mut self.leader_data) else {
+            return Err(self.engine.state.forward_to_leader());
+        };
 
-            // In each loop, it does not have to check rx_shutdown and flush metrics for every RaftMsg
-            // processed.
-            // In each loop, the first step is blocking waiting for any message from any channel.
-            // Then if there is any message, process as many as possible to maximize throughput.
+        let now = C::AsyncRuntime::now();
+        let st = &self.engine.state;
+        let membership = st.membership_state.effective();
+        let last_next_index = st.last_log_id().next_index();
 
-            select! {
-                // Check shutdown in each loop first so that a message flood in `tx_api` won't block shutting down.
-                // `select!` without `biased` provides a random fairness.
-                // We want to check shutdown prior to other channels.
-                // See: https://docs.rs/tokio/latest/tokio/macro.select.html#fairness
-                biased;
+        let mut res = BTreeMap::new();
 
-                _ = &mut rx_shutdown => {
-                    tracing::info!("recv from rx_shutdown");
-                    return Err(Fatal::Stopped);
-                }
+        for (id, p) in leading.progress.iter() {
+            if *id == self.id {
+                continue;
+            }
 
-                notify_res = self.rx_notify.recv() => {
-                    match notify_res {
-                        Some(notify) => self.handle_notify(notify)?,
-                        None => {
-                            tracing::error!("all rx_notify senders are dropped");
-                            return Err(Fatal::Stopped);
-                        }
-                    };
-                }
+            let matched_next_index = p.matching.next_index();
+            let lag_entries = last_next_index.saturating_sub(matched_next_index);
+
+            let snapshot = match p.inflight {
+                Inflight::Snapshot { last_log_id, .. } => Some(SnapshotTransferStatus {
+                    last_log_id,
+                    acked_bytes: l
+                        .replications
+                        .get(id)
+                        .and_then(|h| h.snapshot_resume.get())
+                        .map(|seg| seg.offset)
+                        .unwrap_or_default(),
+                }),
+                _ => None,
+            };
 
-                msg_res = self.rx_api.recv() => {
-                    match msg_res {
-                        Some(msg) => self.handle_api_msg(msg).await,
-                        None => {
-                            tracing::info!("all rx_api senders are dropped");
-                            return Err(Fatal::Stopped);
-                        }
-                    };
+            let catch_up_time = if lag_entries == 0 {
+                Some(Duration::default())
+            } else {
+                match l.catch_up_samples.get(id) {
+                    Some((t, prev_lag)) if *prev_lag > lag_entries && now > *t => {
+                        let elapsed = now - *t;
+                        Some(elapsed.mul_f64(lag_entries as f64 / (prev_lag - lag_entries) as f64))
+                    }
+                    _ => None,
                 }
-            }
+            };
 
-            self.run_engine_commands().await?;
+            l.catch_up_samples.insert(*id, (now, lag_entries));
 
-            // There is a message waking up the loop, process channels one by one.
+            res.insert(*id, ReplicationStatus {
+                voter: membership.is_voter(id),
+                matched: p.matching,
+                lag_entries,
+                lag_bytes: st.log_retention.bytes_between(matched_next_index, last_next_index),
+                snapshot,
+                catch_up_time,
+            });
+        }
 
-            let raft_msg_processed = self.process_raft_msg(balancer.raft_msg()).await?;
-            let notify_processed = self.process_notify(balancer.notify()).await?;
+        l.catch_up_samples.retain(|id, _| res.contains_key(id));
 
-            // If one of the channel consumed all its budget, re-balance the budget ratio.
+        Ok(res)
+    }
 
-            #[allow(clippy::collapsible_else_if)]
-            if notify_processed == balancer.notify() {
+    /// Notify the network factory of the nodes whose record is updated in the effective
+    /// membership since the last call.
+    ///
+    /// A node that is added or removed is not reported.
+    fn report_node_updates(&mut self) {
+        let membership = self.engine.state.membership_state.effective().membership();
+
+        for (id, node) in membership.nodes() {
+            if self.reported_nodes.get(id).is_some_and(|n| n != node) {
+                tracing::info!(target = display(id), node = debug(node), "node updated");
+                self.network.node_updated(*id, node);
+            }
+        }
+
+        self.reported_nodes = membership.nodes().map(|(id, node)| (*id, node.clone())).collect();
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn flush_metrics(&mut self) {
+        let leader_metrics = if let Some(leader) = self.engine.internal_server_state.leading() {
+            let prog = &leader.progress;
+            Some(prog.iter().map(|(id, p)| (*id, *p.borrow())).collect())
+        } else {
+            None
+        };
+        self.report_metrics(leader_metrics);
+    }
+
+    /// Report a metrics payload on the current state of the Raft node.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn report_metrics(&mut self, replication: Option<ReplicationMetrics<C::NodeId>>) {
+        let last_quorum_acked = self.last_quorum_acked_time();
+        let millis_since_quorum_ack = last_quorum_acked.map(|t| t.elapsed().as_millis() as u64);
+
+        let st = &self.engine.state;
+
+        let membership_config = st.membership_state.effective().stored_membership().clone();
+        let current_leader = self.current_leader();
+
+        let m = RaftMetrics {
+            running_state: Ok(()),
+            id: self.id,
+
+            // --- data ---
+            current_term: st.vote_ref().leader_id().get_term(),
+            vote: *st.io_state().vote(),
+            last_log_index: st.last_log_id().index(),
+            last_applied: st.io_applied().copied(),
+            snapshot: st.io_snapshot_last_log_id().copied(),
+            purged: st.io_purged().copied(),
+
+            // --- cluster ---
+            state: st.server_state,
+            current_leader,
+            millis_since_quorum_ack,
+            election_timeout_ms: self.engine.config.timer_config.election_timeout.as_millis() as u64,
+            membership_config: membership_config.clone(),
+
+            // --- replication ---
+            replication: replication.clone(),
+            backoff: self.leader_data.as_ref().map(|l| l.backoffs.clone()),
+            batch_size: self.leader_data.as_ref().map(|l| l.batch_sizes.lock().unwrap().clone()),
+            quarantined: self.leader_data.as_ref().map(|l| l.quarantine.quarantined.clone()),
+            zones: self.zone_metrics(),
+            rtt: self.rtt.lock().unwrap().clone(),
+            log_cache: self.log_cache.metrics(),
+            read_index: self.read_index.metrics,
+            write_batch: self.write_batch.metrics,
+            learner_promotion: self.learner_promotion.clone(),
+            log_retention: LogRetentionMetrics {
+                policy: self.engine.config.log_retention_policy.clone(),
+                purge_upto: st.purge_upto().copied(),
+                bytes: st.log_retention.bytes(),
+            },
+            snapshot_building: self.snapshot_building.as_ref().map(|p| p.metrics()),
+        };
+
+        let data_metrics = RaftDataMetrics {
+            last_log: st.last_log_id().copied(),
+            last_applied: st.io_applied().copied(),
+            snapshot: st.io_snapshot_last_log_id().copied(),
+            purged: st.io_purged().copied(),
+            millis_since_quorum_ack,
+            replication,
+        };
+
+        let server_metrics = RaftServerMetrics {
+            id: self.id,
+            vote: *st.io_state().vote(),
+            state: st.server_state,
+            current_leader,
+            membership_config,
+        };
+
+        self.report_role_change(&server_metrics);
+        self.report_node_updates();
+
+        // Start to send metrics
+        // `RaftMetrics` is sent last, because `Wait` only examines `RaftMetrics`
+        // but not `RaftDataMetrics` and `RaftServerMetrics`.
+        // Thus if `RaftMetrics` change is perceived, the other two should have been updated.
+
+        self.tx_data_metrics.send_if_modified(|metrix| {
+            if data_metrics.ne(metrix) {
+                *metrix = data_metrics.clone();
+                return true;
+            }
+            false
+        });
+
+        self.tx_server_metrics.send_if_modified(|metrix| {
+            if server_metrics.ne(metrix) {
+                *metrix = server_metrics.clone();
+                return true;
+            }
+            false
+        });
+
+        tracing::debug!("report_metrics: {}", m);
+        let res = self.tx_metrics.send(m);
+
+        if let Err(err) = res {
+            tracing::error!(error=%err, id=display(self.id), "error reporting metrics");
+        }
+    }
+
+    /// Handle the admin command `initialize`.
+    ///
+    /// It is allowed to initialize only when `last_log_id.is_none()` and `vote==(0,0)`.
+    /// See: [Conditions for initialization][precondition]
+    ///
+    /// [precondition]: crate::docs::cluster_control::cluster_formation#preconditions-for-initialization
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    pub(crate) fn handle_initialize(
+        &mut self,
+        member_nodes: BTreeMap<C::NodeId, C::Node>,
+        tx: ResultSender<C, (), InitializeError<C>>,
+    ) {
+        tracing::debug!(member_nodes = debug(&member_nodes), "{}", func_name!());
+
+        let membership = Membership::from(member_nodes);
+
+        let entry = C::Entry::new_membership(LogId::default(), membership);
+        let res = self.engine.initialize(entry);
+        self.engine.output.push_command(Command::Respond {
+            when: None,
+            resp: Respond::new(res, tx),
+        });
+    }
+
+    /// Trigger a snapshot building(log compaction) job if there is no pending building job.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn trigger_snapshot(&mut self) {
+        tracing::debug!("{}", func_name!());
+        self.engine.snapshot_handler().trigger_snapshot();
+    }
+
+    /// Let the state machine delete the stale snapshots, according to
+    /// [`Config::max_snapshots_to_keep`].
+    pub(crate) fn purge_snapshots(&mut self) {
+        let keep = self.config.max_snapshots_to_keep;
+        if keep == 0 {
+            return;
+        }
+
+        let res = self.sm_handle.send(sm::Command::purge_snapshots(keep));
+        if let Err(e) = res {
+            tracing::error!(error = display(e), "error sending PurgeSnapshots to sm worker");
+        }
+    }
+
+    /// Reject a request due to the Raft node being in a state which prohibits the request.
+    #[allow(dead_code)]
+    #[tracing::instrument(level = "trace", skip(self, tx))]
+    pub(crate) fn reject_with_forward_to_leader<T: OptionalSend, E>(&self, tx: ResultSender<C, T, E>)
+    where E: From<ForwardToLeader<C>> + OptionalSend {
+        let mut leader_id = self.current_leader();
+        let leader_node = self.get_leader_node(leader_id);
+
+        // Leader is no longer a node in the membership config.
+        if leader_node.is_none() {
+            leader_id = None;
+        }
+
+        let err = ForwardToLeader { leader_id, leader_node };
+
+        let _ = tx.send(Err(err.into()));
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn current_leader(&self) -> Option<C::NodeId> {
+        tracing::debug!(
+            self_id = display(self.id),
+            vote = display(self.engine.state.vote_ref()),
+            "get current_leader"
+        );
+
+        let vote = self.engine.state.vote_ref();
+
+        if !vote.is_committed() {
+            return None;
+        }
+
+        // Safe unwrap(): vote that is committed has to already have voted for some node.
+        let id = vote.leader_id().voted_for().unwrap();
+
+        // TODO: `is_voter()` is slow, maybe cache `current_leader`,
+        //       e.g., only update it when membership or vote changes
+        if self.engine.state.membership_state.effective().is_voter(&id) {
+            Some(id)
+        } else {
+            tracing::debug!("id={} is not a voter", id);
+            None
+        }
+    }
+
+    /// Retrieves the most recent timestamp that is acknowledged by a quorum.
+    ///
+    /// This function returns the latest known time at which the leader received acknowledgment
+    /// from a quorum of followers, indicating its leadership is current and recognized.
+    /// If the node is not a leader or no acknowledgment has been received, `None` is returned.
+    fn last_quorum_acked_time(&mut self) -> Option<InstantOf<C>> {
+        let leading = self.engine.internal_server_state.leading_mut();
+        leading.and_then(|l| l.last_quorum_acked_time())
+    }
+
+    pub(crate) fn get_leader_node(&self, leader_id: Option<C::NodeId>) -> Option<C::Node> {
+        let leader_id = match leader_id {
+            None => return None,
+            Some(x) => x,
+        };
+
+        self.engine.state.membership_state.effective().get_node(&leader_id).cloned()
+    }
+
+    /// A temp wrapper to make non-blocking `append_to_log` a blocking.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) async fn append_to_log(
+        &mut self,
+        entries: Vec<C::Entry>,
+        vote: Vote<C::NodeId>,
+        last_log_id: LogId<C::NodeId>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let rx = self.submit_append_to_log(entries, vote, last_log_id).await?;
+
+        rx.await
+            .map_err(|e| StorageIOError::write_logs(AnyError::error(e)))?
+            .map_err(|e| StorageIOError::write_logs(AnyError::error(e)))?;
+        Ok(())
+    }
+
+    /// Submit log entries to the log store, and return a receiver of the flush result without
+    /// waiting for it.
+    pub(crate) async fn submit_append_to_log(
+        &mut self,
+        entries: Vec<C::Entry>,
+        vote: Vote<C::NodeId>,
+        last_log_id: LogId<C::NodeId>,
+    ) -> Result<OneshotReceiverOf<C, Result<LogIOId<C::NodeId>, io::Error>>, StorageError<C::NodeId>> {
+        tracing::debug!("submit_append_to_log");
+
+        let (tx, rx) = C::AsyncRuntime::oneshot();
+        let log_io_id = LogIOId::new(vote, Some(last_log_id));
+
+        let callback = LogFlushed::new(log_io_id, tx);
+
+        self.log_cache.append(&entries);
+
+        let mut bufs = Vec::with_capacity(entries.len());
+        for entry in entries.iter() {
+            match self.log_store.encode_entry(entry)? {
+                Some(buf) => {
+                    let buf = seal::<C::StorageCipher>(buf)
+                        .map_err(|e| StorageIOError::write_log_entry(*entry.get_log_id(), &e))?;
+                    bufs.push(buf);
+                }
+                None => break,
+            }
+        }
+
+        if bufs.len() == entries.len() {
+            self.log_store.append_vectored(EncodedEntries::new(entries, bufs), callback).await?;
+        } else {
+            self.log_store.append(entries, callback).await?;
+        }
+
+        Ok(rx)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) async fn apply_to_state_machine(
+        &mut self,
+        seq: CommandSeq,
+        since: u64,
+        upto_index: u64,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        tracing::debug!(upto_index = display(upto_index), "{}", func_name!());
+
+        let end = upto_index + 1;
+
+        debug_assert!(
+            since <= end,
+            "last_applied index {} should <= committed index {}",
+            since,
+            end
+        );
+
+        if since == end {
+            return Ok(());
+        }
+
+        let entries = self.log_store.get_log_entries(since..end).await?;
+        tracing::debug!(
+            entries = display(DisplaySlice::<_>(entries.as_slice())),
+            "about to apply"
+        );
+
+        let last_applied = *entries[entries.len() - 1].get_log_id();
+
+        let cmd = sm::Command::apply(entries).with_seq(seq);
+        self.sm_handle.send(cmd).map_err(|e| StorageIOError::apply(last_applied, AnyError::error(e)))?;
+
+        Ok(())
+    }
+
+    /// When received results of applying log entries to the state machine, send back responses to
+    /// the callers that proposed the entries.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn handle_apply_result(&mut self, res: ApplyResult<C>) {
+        tracing::debug!(last_applied = display(res.last_applied), "{}", func_name!());
+
+        let mut results = res.apply_results.into_iter();
+        let mut applying_entries = res.applying_entries.into_iter();
+
+        for log_index in res.since..res.end {
+            let ent = applying_entries.next().unwrap();
+            let apply_res = results.next().unwrap();
+            let tx = self.client_resp_channels.remove(&log_index);
+            self.write_deadlines.remove(log_index);
+
+            // An error means there is no subscriber.
+            let _ = self.tx_applied.send(ent.log_id);
+
+            Self::send_response(ent, apply_res, tx);
+        }
+    }
+
+    /// Send result of applying a log entry to its client.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(super) fn send_response(entry: ApplyingEntry<C>, resp: Result<C::R, C::AppError>, tx: Option<ResponderOf<C>>) {
+        tracing::debug!(entry = debug(&entry), "send_response");
+
+        let tx = match tx {
+            None => return,
+            Some(x) => x,
+        };
+
+        let res = match resp {
+            Ok(data) => Ok(ClientWriteResponse {
+                log_id: entry.log_id,
+                data,
+                membership: entry.membership,
+            }),
+            Err(error) => {
+                tracing::debug!(
+                    log_id = display(entry.log_id),
+                    error = display(&error),
+                    "entry is rejected"
+                );
+
+                Err(ApplyError {
+                    log_id: entry.log_id,
+                    error,
+                }
+                .into())
+            }
+        };
+
+        tx.send(res);
+    }
+
+    /// Spawn a new replication stream returning its replication state handle.
+    #[tracing::instrument(level = "debug", skip(self))]
+    #[allow(clippy::type_complexity)]
+    pub(crate) async fn spawn_replication_stream(
+        &mut self,
+        target: C::NodeId,
+        progress_entry: ProgressEntry<C::NodeId>,
+        snapshot_resume: SnapshotResume,
+    ) -> ReplicationHandle<C> {
+        // Safe unwrap(): target must be in membership
+        let target_node = self.engine.state.membership_state.effective().get_node(&target).unwrap();
+
+        let membership_log_id = self.engine.state.membership_state.effective().log_id();
+        let witness = self.engine.state.membership_state.effective().is_witness(&target);
+        let network = self.network.new_client(target, target_node).await;
+        let snapshot_network = self.network.new_client(target, target_node).await;
+        // A node driven by `Raft::tick()` sends heartbeats with the replication task, without
+        // spawning a heartbeat worker.
+        let heartbeat_network = if self.config.enable_tick {
+            Some(self.network.new_client(target, target_node).await)
+        } else {
+            None
+        };
+
+        let session_id = ReplicationSessionId::new(*self.engine.state.vote_ref(), *membership_log_id);
+
+        // Safe unwrap(): replication streams are only spawned by a leader
+        let batch_sizes = self.leader_data.as_ref().unwrap().batch_sizes.clone();
+
+        ReplicationCore::<C, N, LS>::spawn(
+            target,
+            session_id,
+            self.config.clone(),
+            self.runtime_config.clone(),
+            self.engine.state.committed().copied(),
+            progress_entry.matching,
+            witness,
+            network,
+            snapshot_network,
+            heartbeat_network,
+            self.log_store.get_log_reader().await,
+            self.sm_handle.new_snapshot_reader(),
+            self.snapshot_transmission_semaphore.clone(),
+            snapshot_resume,
+            self.rtt.clone(),
+            batch_sizes,
+            self.log_cache.clone(),
+            self.auth.clone(),
+            self.tx_notify.clone(),
+            self.cancel.child_token(),
+            tracing::span!(parent: &self.span, Level::DEBUG, "replication", id=display(self.id), target=display(target)),
+        )
+    }
+
+    /// Remove all replication.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn remove_all_replication(&mut self) {
+        tracing::info!("remove all replication");
+
+        if let Some(l) = &mut self.leader_data {
+            let nodes = std::mem::take(&mut l.replications);
+            l.backoffs.clear();
+            l.batch_sizes.lock().unwrap().clear();
+
+            tracing::debug!(
+                targets = debug(nodes.iter().map(|x| *x.0).collect::<Vec<_>>()),
+                "remove all targets from replication_metrics"
+            );
+
+            for (target, s) in nodes {
+                let handle = s.join_handle;
+
+                s.cancel.cancel();
+                drop(s.tx_repl);
+
+                tracing::debug!("joining removed replication: {}", target);
+                let _x = handle.await;
+                tracing::info!("Done joining removed replication : {}", target);
+            }
+        } else {
+            unreachable!("it has to be a leader!!!");
+        };
+    }
+
+    /// Run as many commands as possible.
+    ///
+    /// If there is a command that waits for a callback, just return and wait for
+    /// next RaftMsg.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) async fn run_engine_commands(&mut self) -> Result<(), StorageError<C::NodeId>> {
+        if tracing::enabled!(Level::DEBUG) {
+            tracing::debug!("queued commands: start...");
+            for c in self.engine.output.iter_commands() {
+                tracing::debug!("queued commands: {:?}", c);
+            }
+            tracing::debug!("queued commands: end...");
+        }
+
+        while let Some(cmd) = self.engine.output.pop_command() {
+            tracing::debug!("run command: {:?}", cmd);
+
+            let res = self.run_command(cmd).await?;
+
+            if let Some(cmd) = res {
+                tracing::debug!("early return: postpone command: {:?}", cmd);
+                self.engine.output.postpone_command(cmd);
+
+                if tracing::enabled!(Level::DEBUG) {
+                    for c in self.engine.output.iter_commands().take(8) {
+                        tracing::debug!("postponed, first 8 queued commands: {:?}", c);
+                    }
+                }
+
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Move the parked commands whose state machine command has finished back to the command
+    /// queue, to run them in the next [`run_engine_commands()`](Self::run_engine_commands).
+    fn release_waiting_sm_commands(&mut self) {
+        let finished = self.command_state.finished_sm_seq;
+
+        let (ready, waiting): (Vec<_>, Vec<_>) =
+            self.waiting_sm_commands.drain(..).partition(|(seq, _)| *seq <= finished);
+        self.waiting_sm_commands = waiting;
+
+        for (_seq, cmd) in ready {
+            tracing::debug!("sm::Command({}) finished, release cmd: {:?}", finished, cmd);
+            self.engine.output.push_command(cmd);
+        }
+    }
+
+    /// Run an event handling loop
+    ///
+    /// It always returns a [`Fatal`] error upon returning.
+    #[tracing::instrument(level="debug", skip_all, fields(id=display(self.id)))]
+    async fn runtime_loop(&mut self) -> Result<Infallible, Fatal<C>> {
+        // Ratio control the ratio of number of RaftMsg to process to number of Notify to process.
+        let mut balancer = Balancer::new(10_000);
+
+        loop {
+            self.flush_metrics();
+
+            let write_deadline = self.write_batch.deadline;
+            let expire_at = self.write_deadlines.first();
+
+            // In each loop, it does not have to check cancellation and flush metrics for every RaftMsg
+            // processed.
+            // In each loop, the first step is blocking waiting for any message from any channel.
+            // Then if there is any message, process as many as possible to maximize throughput.
+
+            select! {
+                // Check shutdown in each loop first so that a message flood in `tx_api` won't block shutting down.
+                // `select!` without `biased` provides a random fairness.
+                // We want to check shutdown prior to other channels.
+                // See: https://docs.rs/tokio/latest/tokio/macro.select.html#fairness
+                biased;
+
+                _ = self.cancel.cancelled() => {
+                    tracing::info!("RaftCore is cancelled");
+                    return Err(Fatal::Stopped);
+                }
+
+                _ = AsyncRuntimeOf::<C>::sleep_until(write_deadline.unwrap_or_else(C::AsyncRuntime::now)), if write_deadline.is_some() => {
+                    self.flush_write_batch();
+                }
+
+                _ = AsyncRuntimeOf::<C>::sleep_until(expire_at.unwrap_or_else(C::AsyncRuntime::now)), if expire_at.is_some() => {
+                    self.expire_client_writes();
+                }
+
+                notify_res = self.rx_notify.recv() => {
+                    match notify_res {
+                        Some(notify) => self.handle_notify(notify)?,
+                        None => {
+                            tracing::error!("all rx_notify senders are dropped");
+                            return Err(Fatal::Stopped);
+                        }
+                    };
+                }
+
+                msg_res = self.rx_api.recv() => {
+                    match msg_res {
+                        Some(msg) => self.handle_api_msg(msg).await,
+                        None => {
+                            tracing::info!("all rx_api senders are dropped");
+                            return Err(Fatal::Stopped);
+                        }
+                    };
+                }
+            }
+
+            self.run_engine_commands().await?;
+
+            // There is a message waking up the loop, process channels one by one.
+
+            let raft_msg_processed = self.process_raft_msg(balancer.raft_msg()).await?;
+            let notify_processed = self.process_notify(balancer.notify()).await?;
+
+            // If one of the channel consumed all its budget, re-balance the budget ratio.
+
+            #[allow(clippy::collapsible_else_if)]
+            if notify_processed == balancer.notify() {
                 tracing::info!("there may be more Notify to process, increase Notify ratio");
                 balancer.increase_notify();
             } else {
@@ -970,452 +2188,1460 @@ where
                     balancer.increase_raft_msg();
                 }
             }
-        }
+        }
+    }
+
+    /// Process RaftMsg as many as possible.
+    ///
+    /// It returns the number of processed message.
+    /// If the input channel is closed, it returns `Fatal::Stopped`.
+    async fn process_raft_msg(&mut self, at_most: u64) -> Result<u64, Fatal<C>> {
+        for i in 0..at_most {
+            let res = self.rx_api.try_recv();
+            let msg = match res {
+                Ok(msg) => msg,
+                Err(e) => match e {
+                    TryRecvError::Empty => {
+                        tracing::debug!("all RaftMsg are processed, wait for more");
+                        return Ok(i + 1);
+                    }
+                    TryRecvError::Disconnected => {
+                        tracing::debug!("rx_api is disconnected, quit");
+                        return Err(Fatal::Stopped);
+                    }
+                },
+            };
+
+            self.handle_api_msg(msg).await;
+
+            if self.write_batch.deadline.is_some_and(|t| C::AsyncRuntime::now() >= t) {
+                self.flush_write_batch();
+            }
+
+            // TODO: does run_engine_commands() run too frequently?
+            //       to run many commands in one shot, it is possible to batch more commands to gain
+            //       better performance.
+
+            self.run_engine_commands().await?;
+        }
+
+        tracing::debug!("at_most({}) reached, there are more queued RaftMsg to process", at_most);
+
+        Ok(at_most)
+    }
+
+    /// Process Notify as many as possible.
+    ///
+    /// It returns the number of processed notifications.
+    /// If the input channel is closed, it returns `Fatal::Stopped`.
+    async fn process_notify(&mut self, at_most: u64) -> Result<u64, Fatal<C>> {
+        for i in 0..at_most {
+            let res = self.rx_notify.try_recv();
+            let notify = match res {
+                Ok(msg) => msg,
+                Err(e) => match e {
+                    mpsc::error::TryRecvError::Empty => {
+                        tracing::debug!("all Notify are processed, wait for more");
+                        return Ok(i + 1);
+                    }
+                    mpsc::error::TryRecvError::Disconnected => {
+                        tracing::error!("rx_notify is disconnected, quit");
+                        return Err(Fatal::Stopped);
+                    }
+                },
+            };
+
+            self.handle_notify(notify)?;
+
+            // TODO: does run_engine_commands() run too frequently?
+            //       to run many commands in one shot, it is possible to batch more commands to gain
+            //       better performance.
+
+            self.run_engine_commands().await?;
+        }
+
+        tracing::debug!("at_most({}) reached, there are more queued Notify to process", at_most);
+
+        Ok(at_most)
+    }
+
+    /// Spawn parallel vote requests to all cluster members.
+    ///
+    /// If vote hedging is enabled, a request is sent again to a node that does not respond in
+    /// [`Config::vote_hedge_delay`].
+    #[tracing::instrument(level = "trace", skip_all)]
+    async fn spawn_parallel_vote_requests(&mut self, vote_req: &VoteRequest<C>) {
+        let members = self.engine.state.membership_state.effective().voter_ids();
+
+        let ttl = self.runtime_config.rpc_timeout(RPCTypes::Vote);
+        let hedge_delay = self.config.vote_hedge_delay().filter(|d| *d < ttl);
+
+        for target in members {
+            if target == self.id {
+                continue;
+            }
+
+            // Safe unwrap(): target must be in membership
+            let target_node = self.engine.state.membership_state.effective().get_node(&target).unwrap().clone();
+            let client = self.network.new_client(target, &target_node).await;
+            let recorder = RpcRecorder::new(target, client.observer(), self.rtt.clone());
+
+            let mut req = vote_req.clone();
+            self.auth.sign_vote(target, &mut req);
+
+            let responded = Arc::new(AtomicBool::new(false));
+
+            // False positive lint warning(`non-binding `let` on a future`): https://github.com/rust-lang/rust-clippy/issues/9932
+            #[allow(clippy::let_underscore_future)]
+            let _ = C::AsyncRuntime::spawn(
+                Self::send_vote_request(
+                    self.id,
+                    target,
+                    client,
+                    recorder.clone(),
+                    req.clone(),
+                    ttl,
+                    responded.clone(),
+                    self.tx_notify.clone(),
+                )
+                .instrument(tracing::debug_span!(
+                    parent: &Span::current(),
+                    "send_vote_req",
+                    target = display(target)
+                )),
+            );
+
+            let Some(delay) = hedge_delay else {
+                continue;
+            };
+
+            // The hedged request uses another client, because the first one may still be busy.
+            let client = self.network.new_client(target, &target_node).await;
+            let id = self.id;
+            let tx = self.tx_notify.clone();
+
+            #[allow(clippy::let_underscore_future)]
+            let _ = C::AsyncRuntime::spawn(
+                async move {
+                    C::AsyncRuntime::sleep(delay).await;
+
+                    if responded.load(Ordering::Relaxed) {
+                        return;
+                    }
+
+                    tracing::info!(target = display(target), "no vote response in {:?}, send again", delay);
+                    Self::send_vote_request(id, target, client, recorder, req, ttl - delay, responded, tx).await;
+                }
+                .instrument(tracing::debug_span!(
+                    parent: &Span::current(),
+                    "send_hedged_vote_req",
+                    target = display(target)
+                )),
+            );
+        }
+    }
+
+    /// Send a vote request to `target` and forward the response to `RaftCore`.
+    ///
+    /// `responded` is set if a response is received.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_vote_request(
+        id: C::NodeId,
+        target: C::NodeId,
+        mut client: N::Network,
+        recorder: RpcRecorder<C>,
+        mut req: VoteRequest<C>,
+        ttl: Duration,
+        responded: Arc<AtomicBool>,
+        tx: mpsc::UnboundedSender<Notify<C>>,
+    ) {
+        let vote = req.vote;
+        let pre_vote = req.pre_vote;
+        N::inject_context(&mut req.context);
+        let option = recorder.option(ttl);
+
+        let start = C::AsyncRuntime::now();
+        let tm_res = C::AsyncRuntime::timeout(ttl, client.vote(req, option)).await;
+        let res = match tm_res {
+            Ok(res) => res,
+
+            Err(_timeout) => {
+                let timeout_err = Timeout::<C> {
+                    action: RPCTypes::Vote,
+                    id,
+                    target,
+                    timeout: ttl,
+                };
+                recorder.record(RPCTypes::Vote, 0, start.elapsed(), Some(RPCErrorKind::Timeout));
+                tracing::error!({error = %timeout_err, target = display(target)}, "timeout");
+                return;
+            }
+        };
+
+        recorder.record(
+            RPCTypes::Vote,
+            0,
+            start.elapsed(),
+            res.as_ref().err().map(RPCErrorKind::from),
+        );
+
+        match res {
+            Ok(resp) => {
+                responded.store(true, Ordering::Relaxed);
+                let notify = if pre_vote {
+                    Notify::PreVoteResponse {
+                        target,
+                        resp,
+                        sender_vote: vote,
+                    }
+                } else {
+                    Notify::VoteResponse {
+                        target,
+                        resp,
+                        sender_vote: vote,
+                    }
+                };
+                let _ = tx.send(notify);
+            }
+            Err(err) => tracing::error!({error=%err, target=display(target)}, "while requesting vote"),
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(super) fn handle_vote_request(&mut self, req: VoteRequest<C>, tx: VoteTx<C>) {
+        tracing::info!(req = display(&req), func = func_name!());
+
+        let resp = self.engine.handle_vote_req(req);
+        self.engine.output.push_command(Command::Respond {
+            when: None,
+            resp: Respond::new(Ok(resp), tx),
+        });
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(super) fn handle_append_entries_request(
+        &mut self,
+        req: AppendEntriesRequest<C>,
+        accepted: usize,
+        tx: AppendEntriesTx<C>,
+    ) {
+        tracing::debug!(req = display(&req), accepted, func = func_name!());
+
+        let rtt = if self.config.adaptive_election_timeout {
+            heartbeat_rtt(&req.context)
+        } else {
+            None
+        };
+
+        let is_ok = self.engine.handle_append_entries(&req.vote, req.prev_log_id, req.entries, accepted, Some(tx));
+
+        if is_ok {
+            self.engine.handle_commit_entries(req.leader_commit);
+
+            if let Some(rtt) = rtt {
+                self.tune_election_timeout(rtt);
+            }
+        }
+    }
+
+    /// Returns the largest smoothed RTT of the RPCs to the other voters, if adaptive election
+    /// timeout is enabled.
+    fn cluster_rtt(&self) -> Option<Duration> {
+        if !self.config.adaptive_election_timeout {
+            return None;
+        }
+
+        let rtt = self.rtt.lock().unwrap();
+        let membership = self.engine.state.membership_state.effective();
+
+        membership
+            .voter_ids()
+            .filter(|id| id != &self.id)
+            .filter_map(|id| rtt.get(&id).map(|e| e.smoothed()))
+            .max()
+    }
+
+    /// Tune the election timeout to the round-trip time of the RPCs in the cluster.
+    ///
+    /// A new random election timeout is picked only when its range changes.
+    fn tune_election_timeout(&mut self, rtt: Duration) {
+        let range = self.config.adaptive_election_timeout_range(rtt);
+        if range == self.election_timeout_range {
+            return;
+        }
+
+        let (low, high) = range;
+        let timeout = AsyncRuntimeOf::<C>::thread_rng().gen_range(low..=high);
+
+        tracing::info!(
+            rtt = debug(rtt),
+            low,
+            high,
+            election_timeout = timeout,
+            "tune election timeout to RTT"
+        );
+
+        self.election_timeout_range = range;
+        self.engine.config.timer_config.election_timeout = Duration::from_millis(timeout);
+    }
+
+    // TODO: Make this method non-async. It does not need to run any async command in it.
+    #[tracing::instrument(level = "debug", skip(self, msg), fields(state = debug(self.engine.state.server_state), id=display(self.id)))]
+    pub(crate) async fn handle_api_msg(&mut self, msg: RaftMsg<C>) {
+        tracing::debug!("recv from rx_api: {}", msg);
+
+        // Do not reorder the coalesced writes with other requests.
+        if !matches!(msg, RaftMsg::ClientWriteRequest { .. }) {
+            self.flush_write_batch();
+        }
+
+        match msg {
+            RaftMsg::AppendEntries { rpc, tx } => {
+                let span = tracing::debug_span!("handle_append_entries");
+                N::extract_context(&rpc.context, &span);
+
+                let accepted = if rpc.entries.is_empty() {
+                    0
+                } else {
+                    let n = self.log_store.appendable_count(&rpc.entries).instrument(span.clone()).await;
+                    std::cmp::min(n, rpc.entries.len())
+                };
+
+                span.in_scope(|| self.handle_append_entries_request(rpc, accepted, tx));
+            }
+            RaftMsg::RequestVote { rpc, tx } => {
+                let now = C::AsyncRuntime::now();
+                tracing::info!(
+                    now = debug(now),
+                    vote_request = display(&rpc),
+                    "received RaftMsg::RequestVote: {}",
+                    func_name!()
+                );
+
+                let span = tracing::debug_span!("handle_vote");
+                N::extract_context(&rpc.context, &span);
+                span.in_scope(|| self.handle_vote_request(rpc, tx));
+            }
+            RaftMsg::BeginReceivingSnapshot { tx } => {
+                self.engine.handle_begin_receiving_snapshot(tx);
+            }
+            RaftMsg::InstallFullSnapshot {
+                vote,
+                snapshot,
+                context,
+                tx,
+            } => {
+                let span = tracing::debug_span!("handle_install_full_snapshot");
+                N::extract_context(&context, &span);
+                span.in_scope(|| self.engine.handle_install_full_snapshot(vote, snapshot, tx));
+            }
+            RaftMsg::TransferSnapshot { rpc, tx } => {
+                self.transfer_snapshot(rpc, tx).await;
+            }
+            RaftMsg::ForwardClientWrite {
+                target,
+                node,
+                app_data,
+                tx,
+            } => {
+                self.forward_client_write(target, node, app_data, tx).await;
+            }
+            RaftMsg::ForwardGetReadLogId {
+                target,
+                node,
+                read_policy,
+                tx,
+            } => {
+                self.forward_get_read_log_id(target, node, read_policy, tx).await;
+            }
+            RaftMsg::CheckIsLeaderRequest { read_policy, tx } => {
+                self.handle_check_is_leader_request(read_policy, tx).await;
+            }
+            RaftMsg::ClientWriteRequest { entry, tx, deadline } => {
+                if let Some(window) = self.config.write_coalesce_window() {
+                    self.coalesce_write(entry, tx, deadline, window);
+                } else if self.write_entry(entry, Some(tx)) {
+                    if let Some(deadline) = deadline {
+                        let index = self.engine.state.last_log_id().unwrap().index;
+                        self.write_deadlines.insert(index, deadline);
+                    }
+                }
+            }
+            RaftMsg::Initialize { members, tx } => {
+                tracing::info!(
+                    members = debug(&members),
+                    "received RaftMsg::Initialize: {}",
+                    func_name!()
+                );
+
+                self.handle_initialize(members, tx);
+            }
+            #[cfg(feature = "unsafe-force-reset-membership")]
+            RaftMsg::ForceResetMembership { members, tx } => {
+                tracing::warn!(
+                    members = debug(&members),
+                    "received RaftMsg::ForceResetMembership: {}",
+                    func_name!()
+                );
+
+                let entry = C::Entry::new_membership(LogId::default(), Membership::from(members));
+                let res = self.engine.force_reset_membership(entry);
+                self.engine.output.push_command(Command::Respond {
+                    when: None,
+                    resp: Respond::new(res, tx),
+                });
+            }
+            RaftMsg::ChangeMembership {
+                changes,
+                retain,
+                guard,
+                tx,
+            } => {
+                tracing::info!(
+                    members = debug(&changes),
+                    retain = debug(&retain),
+                    guard = debug(&guard),
+                    "received RaftMsg::ChangeMembership: {}",
+                    func_name!()
+                );
+
+                // The second step, which leaves a joint config, is not limited.
+                let uniform = self.engine.state.membership_state.effective().membership().get_joint_config().len() == 1;
+
+                if uniform {
+                    if let Err(e) = self.check_membership_change_interval() {
+                        tracing::warn!(error = display(&e), "refuse membership change");
+                        tx.send(Err(ClientWriteError::ChangeMembershipError(e.into())));
+                        return;
+                    }
+                }
+
+                let proposed = self.change_membership(changes, retain, guard, tx);
+
+                if let Some(l) = &mut self.leader_data {
+                    if uniform && proposed {
+                        l.last_membership_change = Some(C::AsyncRuntime::now());
+                    }
+                }
+            }
+            RaftMsg::AddLearner { id, node, tx } => {
+                tracing::info!(
+                    id = display(id),
+                    node = debug(&node),
+                    "received RaftMsg::AddLearner: {}",
+                    func_name!()
+                );
+
+                self.change_membership(ChangeMembers::AddNodes(btreemap! {id=>node}), true, true, tx);
+
+                if self.config.auto_promote_learner_duration().is_some() {
+                    if let Some(l) = &mut self.leader_data {
+                        l.learner_promotion.add_candidate(id);
+                    }
+                }
+            }
+            RaftMsg::TransferLeader { to, tx } => {
+                tracing::info!(to = display(to), "received RaftMsg::TransferLeader: {}", func_name!());
+
+                self.handle_transfer_leadership(to, tx);
+            }
+            RaftMsg::StepDown { transfer, tx } => {
+                tracing::info!(transfer, "received RaftMsg::StepDown: {}", func_name!());
+
+                self.handle_step_down(transfer, tx);
+            }
+            RaftMsg::HandleTransferLeader { rpc, tx } => {
+                tracing::info!(
+                    rpc = display(&rpc),
+                    "received RaftMsg::HandleTransferLeader: {}",
+                    func_name!()
+                );
+
+                self.engine.handle_transfer_leader(&rpc);
+                let _ = tx.send(Ok(()));
+            }
+            RaftMsg::GetReplicationStatus { tx } => {
+                let _ = tx.send(self.replication_status());
+            }
+            RaftMsg::ExternalCoreRequest { req } => {
+                req(&self.engine.state);
+            }
+            RaftMsg::ExternalCommand { cmd } => {
+                tracing::info!(cmd = debug(&cmd), "received RaftMsg::ExternalCommand: {}", func_name!());
+
+                match cmd {
+                    ExternalCommand::Elect => {
+                        let membership = self.engine.state.membership_state.effective();
+                        if membership.is_voter(&self.id) && !membership.is_witness(&self.id) {
+                            // TODO: reject if it is already a leader?
+                            self.engine.elect();
+                            tracing::debug!("ExternalCommand: triggered election");
+                        } else {
+                            // Node is switched to learner.
+                        }
+                    }
+                    ExternalCommand::Heartbeat => {
+                        self.send_heartbeat("ExternalCommand");
+                    }
+                    ExternalCommand::Tick => {
+                        self.handle_tick();
+                    }
+                    ExternalCommand::Snapshot { tx } => {
+                        self.trigger_snapshot();
+
+                        // A new building is pushed as a command, or one is already in progress.
+                        let progress = self.snapshot_building.get_or_insert_with(Default::default).clone();
+                        let _ = tx.send(Ok(progress));
+                    }
+                    ExternalCommand::GetSnapshot { tx } => {
+                        let cmd = sm::Command::get_snapshot(None, tx);
+                        let res = self.sm_handle.send(cmd);
+                        if let Err(e) = res {
+                            tracing::error!(error = display(e), "error sending GetSnapshot to sm worker");
+                        }
+                    }
+                    ExternalCommand::PurgeLog { upto } => {
+                        self.engine.trigger_purge_log(upto);
+                    }
+                    ExternalCommand::SetSnapshotPolicy { policy } => {
+                        self.engine.config.snapshot_policy = policy;
+
+                        // The logs committed so far may already satisfy the new policy.
+                        if self.engine.config.snapshot_policy.should_snapshot(&self.engine.state) {
+                            self.trigger_snapshot();
+                        }
+                    }
+                    ExternalCommand::SetLeaderBlankEntry { enabled } => {
+                        self.engine.config.leader_blank_entry = enabled;
+                    }
+                    ExternalCommand::SetRejectVoteWithinLease { enabled } => {
+                        self.engine.config.reject_vote_within_lease = enabled;
+                    }
+                    ExternalCommand::SetOneVotePerTerm { enabled } => {
+                        self.engine.config.one_vote_per_term = enabled;
+                    }
+                }
+            }
+        };
     }
 
-    /// Process RaftMsg as many as possible.
-    ///
-    /// It returns the number of processed message.
-    /// If the input channel is closed, it returns `Fatal::Stopped`.
-    async fn process_raft_msg(&mut self, at_most: u64) -> Result<u64, Fatal<C>> {
-        for i in 0..at_most {
-            let res = self.rx_api.try_recv();
-            let msg = match res {
-                Ok(msg) => msg,
-                Err(e) => match e {
-                    mpsc::error::TryRecvError::Empty => {
-                        tracing::debug!("all RaftMsg are processed, wait for more");
-                        return Ok(i + 1);
+    // TODO: Make this method non-async. It does not need to run any async command in it.
+    #[tracing::instrument(level = "debug", skip_all, fields(state = debug(self.engine.state.server_state), id=display(self.id)))]
+    pub(crate) fn handle_notify(&mut self, notify: Notify<C>) -> Result<(), Fatal<C>> {
+        tracing::debug!("recv from rx_notify: {}", notify);
+
+        match notify {
+            Notify::VoteResponse {
+                target,
+                resp,
+                sender_vote: vote,
+            } => {
+                let now = C::AsyncRuntime::now();
+
+                tracing::info!(
+                    now = debug(now),
+                    resp = display(&resp),
+                    "received Notify::VoteResponse: {}",
+                    func_name!()
+                );
+
+                if self.does_vote_match(&vote, "VoteResponse") {
+                    self.engine.handle_vote_resp(target, resp);
+                }
+            }
+
+            Notify::PreVoteResponse {
+                target,
+                resp,
+                sender_vote: vote,
+            } => {
+                tracing::info!(
+                    resp = display(&resp),
+                    "received Notify::PreVoteResponse: {}",
+                    func_name!()
+                );
+
+                self.engine.handle_pre_vote_resp(target, &vote, resp);
+            }
+
+            Notify::HigherVote {
+                target,
+                higher,
+                sender_vote: vote,
+            } => {
+                tracing::info!(
+                    target = display(target),
+                    higher_vote = display(&higher),
+                    sending_vote = display(&vote),
+                    "received Notify::HigherVote: {}",
+                    func_name!()
+                );
+
+                if self.does_vote_match(&vote, "HigherVote") {
+                    // Rejected vote change is ok.
+                    let _ = self.engine.vote_handler().update_vote(&higher);
+                }
+            }
+
+            Notify::ReadIndexRoundDone => {
+                self.read_index.finish_round();
+
+                if !self.read_index.queued.is_empty() {
+                    self.engine.output.push_command(Command::ConfirmLeadership);
+                }
+            }
+
+            Notify::LocalLogFlushed { vote, result } => {
+                let io_id = result.map_err(|e| StorageError::from(StorageIOError::write_logs(AnyError::error(e))))?;
+
+                // Reporting to a different leader is not a problem, but it is ignored.
+                if self.engine.internal_server_state.leading().map(|l| l.vote) == Some(vote) {
+                    if let Ok(mut lh) = self.engine.leader_handler() {
+                        lh.replication_handler().update_local_progress(io_id.log_id);
                     }
-                    mpsc::error::TryRecvError::Disconnected => {
-                        tracing::debug!("rx_api is disconnected, quit");
-                        return Err(Fatal::Stopped);
+                }
+            }
+
+            Notify::Tick { i } => {
+                tracing::debug!("received tick: {}", i);
+                self.handle_tick();
+            }
+
+            Notify::Network { response } => {
+                //
+                match response {
+                    replication::Response::Progress {
+                        target,
+                        request_id: id,
+                        result,
+                        session_id,
+                    } => {
+                        // If vote or membership changes, ignore the message.
+                        // There is chance delayed message reports a wrong state.
+                        if self.does_replication_session_match(&session_id, "UpdateReplicationMatched") {
+                            self.handle_replication_progress(target, id, result);
+                            self.check_leader_transfer();
+                        }
                     }
-                },
-            };
 
-            self.handle_api_msg(msg).await;
+                    replication::Response::Backoff {
+                        target,
+                        state,
+                        session_id,
+                    } => {
+                        if self.does_replication_session_match(&session_id, "Backoff") {
+                            if let Some(l) = &mut self.leader_data {
+                                match state {
+                                    Some(state) => l.backoffs.insert(target, state),
+                                    None => l.backoffs.remove(&target),
+                                };
+                            }
+                        }
+                    }
 
-            // TODO: does run_engine_commands() run too frequently?
-            //       to run many commands in one shot, it is possible to batch more commands to gain
-            //       better performance.
+                    replication::Response::StorageError { error } => {
+                        tracing::error!(
+                            error = display(&error),
+                            "received Notify::ReplicationStorageError: {}",
+                            func_name!()
+                        );
+
+                        return Err(Fatal::from(error));
+                    }
+
+                    replication::Response::HigherVote { target, higher, vote } => {
+                        tracing::info!(
+                            target = display(target),
+                            higher_vote = display(&higher),
+                            sending_vote = display(&vote),
+                            "received Notify::HigherVote: {}",
+                            func_name!()
+                        );
+
+                        if self.does_vote_match(&vote, "HigherVote") {
+                            // Rejected vote change is ok.
+                            let _ = self.engine.vote_handler().update_vote(&higher);
+                        }
+                    }
+                }
+            }
+
+            Notify::StateMachine { command_result } => {
+                tracing::debug!("sm::StateMachine command result: {:?}", command_result);
+
+                let seq = command_result.command_seq;
+                let res = command_result.result?;
+
+                match res {
+                    // BuildSnapshot is a read operation that does not have to be serialized by
+                    // sm::Worker. Thus it may finish out of order.
+                    sm::Response::BuildSnapshot(_) | sm::Response::BuildSnapshotCancelled => {}
+                    _ => {
+                        debug_assert!(
+                            self.command_state.finished_sm_seq < seq,
+                            "sm::StateMachine command result is out of order: expect {} < {}",
+                            self.command_state.finished_sm_seq,
+                            seq
+                        );
+                    }
+                }
+                self.command_state.finished_sm_seq = seq;
+                self.release_waiting_sm_commands();
+
+                match res {
+                    sm::Response::BuildSnapshot(meta) => {
+                        tracing::info!(
+                            "sm::StateMachine command done: BuildSnapshot: {}: {}",
+                            meta,
+                            func_name!()
+                        );
+
+                        // Update in-memory state first, then the io state.
+                        // In-memory state should always be ahead or equal to the io state.
+
+                        let last_log_id = meta.last_log_id;
+                        self.engine.finish_building_snapshot(meta);
+                        self.snapshot_building = None;
+
+                        let st = self.engine.state.io_state_mut();
+                        st.update_snapshot(last_log_id);
+
+                        self.purge_snapshots();
+                    }
+                    sm::Response::BuildSnapshotCancelled => {
+                        tracing::info!(
+                            "sm::StateMachine command done: BuildSnapshotCancelled: {}",
+                            func_name!()
+                        );
+
+                        self.engine.state.io_state_mut().set_building_snapshot(false);
+                        self.snapshot_building = None;
+                    }
+                    sm::Response::InstallSnapshot(meta) => {
+                        tracing::info!(
+                            "sm::StateMachine command done: InstallSnapshot: {}: {}",
+                            meta.display(),
+                            func_name!()
+                        );
+
+                        if let Some(meta) = meta {
+                            let st = self.engine.state.io_state_mut();
+                            st.update_applied(meta.last_log_id);
+
+                            // A delta is not stored as a snapshot, a full one is built after it.
+                            if !meta.is_delta() {
+                                st.update_snapshot(meta.last_log_id);
+                                self.purge_snapshots();
+                            }
+                        }
+                    }
+                    sm::Response::Apply(res) => {
+                        self.engine.state.io_state_mut().update_applied(Some(res.last_applied));
+
+                        self.handle_apply_result(res);
+                    }
+                }
+            }
+        };
+        Ok(())
+    }
+
+    /// Start to transfer the leadership to `to`.
+    ///
+    /// The result is sent to `tx` by [`Self::check_leader_transfer()`] when `to` becomes the
+    /// leader or the transfer times out.
+    fn handle_transfer_leadership(&mut self, to: C::NodeId, tx: TransferLeaderTx<C>) {
+        if let Err(forward_err) = self.engine.leader_handler() {
+            let _ = tx.send(Err(forward_err.into()));
+            return;
+        }
+
+        let membership = self.engine.state.membership_state.effective();
+        if !membership.is_voter(&to) || membership.is_witness(&to) {
+            let _ = tx.send(Err(NotVoter { node_id: to }.into()));
+            return;
+        }
 
-            self.run_engine_commands().await?;
+        if to == self.id {
+            let _ = tx.send(Ok(()));
+            return;
         }
 
-        tracing::debug!("at_most({}) reached, there are more queued RaftMsg to process", at_most);
+        if let Some(transfer) = &self.leader_transfer {
+            tracing::info!(to = display(transfer.to), "another leadership transfer is in progress");
+            let _ = tx.send(Err(ForwardToLeader::empty().into()));
+            return;
+        }
 
-        Ok(at_most)
+        let deadline = C::AsyncRuntime::now() + self.config.transfer_leader_timeout();
+        self.leader_transfer = Some(LeaderTransfer {
+            to,
+            deadline,
+            sent: false,
+            tx: Some(tx),
+            step_down: false,
+        });
+
+        self.check_leader_transfer();
     }
 
-    /// Process Notify as many as possible.
+    /// Stop serving as the leader on request of the application.
     ///
-    /// It returns the number of processed notifications.
-    /// If the input channel is closed, it returns `Fatal::Stopped`.
-    async fn process_notify(&mut self, at_most: u64) -> Result<u64, Fatal<C>> {
-        for i in 0..at_most {
-            let res = self.rx_notify.try_recv();
-            let notify = match res {
-                Ok(msg) => msg,
-                Err(e) => match e {
-                    mpsc::error::TryRecvError::Empty => {
-                        tracing::debug!("all Notify are processed, wait for more");
-                        return Ok(i + 1);
-                    }
-                    mpsc::error::TryRecvError::Disconnected => {
-                        tracing::error!("rx_notify is disconnected, quit");
-                        return Err(Fatal::Stopped);
-                    }
-                },
-            };
+    /// If `transfer` is true, the leadership is handed over to the voter that has replicated the
+    /// most logs, and the result is sent to `tx` by [`Self::check_leader_transfer()`]. Otherwise,
+    /// or if there is no other voter, this node steps down at once.
+    fn handle_step_down(&mut self, transfer: bool, tx: TransferLeaderTx<C>) {
+        if let Err(forward_err) = self.engine.leader_handler() {
+            let _ = tx.send(Err(forward_err.into()));
+            return;
+        }
 
-            self.handle_notify(notify)?;
+        if let Some(transfer) = &self.leader_transfer {
+            tracing::info!(to = display(transfer.to), "another leadership transfer is in progress");
+            let _ = tx.send(Err(ForwardToLeader::empty().into()));
+            return;
+        }
 
-            // TODO: does run_engine_commands() run too frequently?
-            //       to run many commands in one shot, it is possible to batch more commands to gain
-            //       better performance.
+        let to = if transfer {
+            let membership = self.engine.state.membership_state.effective();
+            let leading = self.engine.internal_server_state.leading();
 
-            self.run_engine_commands().await?;
-        }
+            membership
+                .voter_ids()
+                .filter(|id| *id != self.id && !membership.is_witness(id))
+                .max_by_key(|id| leading.and_then(|l| l.progress.try_get(id)).and_then(|p| p.matching))
+        } else {
+            None
+        };
 
-        tracing::debug!("at_most({}) reached, there are more queued Notify to process", at_most);
+        let Some(to) = to else {
+            self.step_down_at_once();
+            let _ = tx.send(Ok(()));
+            return;
+        };
 
-        Ok(at_most)
+        tracing::info!(to = display(to), "step down, hand over leadership");
+
+        self.leader_transfer = Some(LeaderTransfer {
+            to,
+            deadline: C::AsyncRuntime::now() + self.config.transfer_leader_timeout(),
+            sent: false,
+            tx: Some(tx),
+            step_down: true,
+        });
+
+        self.check_leader_transfer();
     }
 
-    /// Spawn parallel vote requests to all cluster members.
-    #[tracing::instrument(level = "trace", skip_all)]
-    async fn spawn_parallel_vote_requests(&mut self, vote_req: &VoteRequest<C>) {
-        let members = self.engine.state.membership_state.effective().voter_ids();
+    /// Revert this leader to a candidate that does not start an election for a leader lease plus
+    /// an election timeout, so that another voter is elected before it.
+    fn step_down_at_once(&mut self) {
+        let timer_config = &self.engine.config.timer_config;
+        let delay = timer_config.leader_lease + timer_config.election_timeout;
 
-        let vote = vote_req.vote;
+        self.step_down_until = Some(C::AsyncRuntime::now() + delay);
+        self.engine.transferring_to = None;
+        self.engine.leader_step_down_voluntarily();
+    }
 
-        for target in members {
-            if target == self.id {
-                continue;
-            }
+    /// Hand over the leadership to the voter ranked highest by the preferred leader zone and the
+    /// election priority, if it ranks higher than this leader and the voter has caught up.
+    ///
+    /// It is tried only after this node has been the leader for an election timeout, and not
+    /// again for a while after a transfer times out.
+    fn check_priority_transfer(&mut self) {
+        if self.leader_transfer.is_some() {
+            return;
+        }
 
-            let req = vote_req.clone();
+        let Some(l) = &self.leader_data else {
+            return;
+        };
 
-            // Safe unwrap(): target must be in membership
-            let target_node = self.engine.state.membership_state.effective().get_node(&target).unwrap().clone();
-            let mut client = self.network.new_client(target, &target_node).await;
+        let now = C::AsyncRuntime::now();
+        if now < l.established_at + self.engine.config.timer_config.election_timeout || now < l.next_priority_transfer {
+            return;
+        }
 
-            let tx = self.tx_notify.clone();
+        let Some(leading) = self.engine.internal_server_state.leading() else {
+            return;
+        };
 
-            let ttl = Duration::from_millis(self.config.election_timeout_min);
-            let id = self.id;
-            let option = RPCOption::new(ttl);
+        let membership = self.engine.state.membership_state.effective();
+        let last_log_id = self.engine.state.last_log_id().copied();
+        let my_rank = self.engine.leader_rank(&self.id);
 
-            // False positive lint warning(`non-binding `let` on a future`): https://github.com/rust-lang/rust-clippy/issues/9932
-            #[allow(clippy::let_underscore_future)]
-            let _ = C::AsyncRuntime::spawn(
-                async move {
-                    let tm_res = C::AsyncRuntime::timeout(ttl, client.vote(req, option)).await;
-                    let res = match tm_res {
-                        Ok(res) => res,
-
-                        Err(_timeout) => {
-                            let timeout_err = Timeout::<C> {
-                                action: RPCTypes::Vote,
-                                id,
-                                target,
-                                timeout: ttl,
-                            };
-                            tracing::error!({error = %timeout_err, target = display(target)}, "timeout");
-                            return;
-                        }
-                    };
+        let to = membership
+            .voter_ids()
+            .filter(|id| self.engine.leader_rank(id) > my_rank && !membership.is_witness(id))
+            .filter(|id| leading.progress.try_get(id).and_then(|p| p.matching) >= last_log_id)
+            .max_by_key(|id| self.engine.leader_rank(id));
 
-                    match res {
-                        Ok(resp) => {
-                            let _ = tx.send(Notify::VoteResponse {
-                                target,
-                                resp,
-                                sender_vote: vote,
-                            });
-                        }
-                        Err(err) => tracing::error!({error=%err, target=display(target)}, "while requesting vote"),
-                    }
-                }
-                .instrument(tracing::debug_span!(
-                    parent: &Span::current(),
-                    "send_vote_req",
-                    target = display(target)
-                )),
-            );
-        }
-    }
+        let Some(to) = to else {
+            return;
+        };
 
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub(super) fn handle_vote_request(&mut self, req: VoteRequest<C>, tx: VoteTx<C>) {
-        tracing::info!(req = display(&req), func = func_name!());
+        tracing::info!(
+            to = display(to),
+            rank = debug(self.engine.leader_rank(&to)),
+            my_rank = debug(my_rank),
+            "hand over leadership to a voter ranked higher by preferred zone and election priority"
+        );
 
-        let resp = self.engine.handle_vote_req(req);
-        self.engine.output.push_command(Command::Respond {
-            when: None,
-            resp: Respond::new(Ok(resp), tx),
+        self.leader_transfer = Some(LeaderTransfer {
+            to,
+            deadline: now + self.config.transfer_leader_timeout(),
+            sent: false,
+            tx: None,
+            step_down: false,
         });
+
+        self.check_leader_transfer();
     }
 
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub(super) fn handle_append_entries_request(&mut self, req: AppendEntriesRequest<C>, tx: AppendEntriesTx<C>) {
-        tracing::debug!(req = display(&req), func = func_name!());
+    /// Hand over the leadership to a remaining voter before stepping down, once a membership that
+    /// removes this leader from the voters is committed.
+    ///
+    /// The target is the voter that has replicated the most logs. If this node is removed from the
+    /// membership, it steps down as soon as the transfer request is sent, or when the transfer
+    /// times out. If it is kept as a learner, it learns the new leader by replication.
+    fn transfer_leader_on_removal(&mut self) {
+        let membership = self.engine.state.membership_state.effective();
+        if membership.is_voter(&self.id) || membership.log_id().as_ref() > self.engine.state.committed() {
+            return;
+        }
 
-        let is_ok = self.engine.handle_append_entries(&req.vote, req.prev_log_id, req.entries, Some(tx));
+        let Some(leading) = self.engine.internal_server_state.leading() else {
+            return;
+        };
+        let Some(l) = &mut self.leader_data else {
+            return;
+        };
 
-        if is_ok {
-            self.engine.handle_commit_entries(req.leader_commit);
-        }
-    }
+        match &self.leader_transfer {
+            // A transfer requested by an application is waited for, until it finishes or times
+            // out.
+            Some(transfer) if transfer.tx.is_some() => return,
+            Some(_) => {}
+            None if l.removal_transfer_started => {
+                if !membership.membership().contains(&self.id) {
+                    tracing::warn!("leadership transfer after removal failed, step down");
+                    self.engine.leader_step_down();
+                }
+                return;
+            }
+            None => {
+                let to = membership
+                    .voter_ids()
+                    .filter(|id| !membership.is_witness(id))
+                    .max_by_key(|id| leading.progress.try_get(id).and_then(|p| p.matching));
 
-    // TODO: Make this method non-async. It does not need to run any async command in it.
-    #[tracing::instrument(level = "debug", skip(self, msg), fields(state = debug(self.engine.state.server_state), id=display(self.id)))]
-    pub(crate) async fn handle_api_msg(&mut self, msg: RaftMsg<C>) {
-        tracing::debug!("recv from rx_api: {}", msg);
+                let Some(to) = to else {
+                    self.engine.leader_step_down();
+                    return;
+                };
 
-        match msg {
-            RaftMsg::AppendEntries { rpc, tx } => {
-                self.handle_append_entries_request(rpc, tx);
+                tracing::info!(to = display(to), "leader is removed from voters, hand over leadership");
+
+                l.removal_transfer_started = true;
+                self.leader_transfer = Some(LeaderTransfer {
+                    to,
+                    deadline: C::AsyncRuntime::now() + self.config.transfer_leader_timeout(),
+                    sent: false,
+                    tx: None,
+                    step_down: false,
+                });
             }
-            RaftMsg::RequestVote { rpc, tx } => {
-                let now = InstantOf::<C>::now();
+        }
+
+        self.check_leader_transfer();
+
+        // The new leader does not replicate to a removed node, thus this node can not learn the
+        // transfer result. Step down once the request is sent.
+        if let Some(transfer) = &self.leader_transfer {
+            if transfer.sent && !self.engine.state.membership_state.contains(&self.id) {
                 tracing::info!(
-                    now = debug(now),
-                    vote_request = display(&rpc),
-                    "received RaftMsg::RequestVote: {}",
-                    func_name!()
+                    to = display(transfer.to),
+                    "leadership transfer request is sent, step down"
                 );
 
-                self.handle_vote_request(rpc, tx);
-            }
-            RaftMsg::BeginReceivingSnapshot { tx } => {
-                self.engine.handle_begin_receiving_snapshot(tx);
+                self.leader_transfer = None;
+                self.engine.leader_step_down();
             }
-            RaftMsg::InstallFullSnapshot { vote, snapshot, tx } => {
-                self.engine.handle_install_full_snapshot(vote, snapshot, tx);
-            }
-            RaftMsg::CheckIsLeaderRequest { tx } => {
-                self.handle_check_is_leader_request(tx).await;
+        }
+    }
+
+    /// Quarantine a follower or learner whose replication lag has kept growing for
+    /// [`Config::quarantine_lag_growth_duration`], and release it once it catches up.
+    ///
+    /// The replication stream to a quarantined target is told to cap the resources it uses.
+    fn check_quarantine(&mut self) {
+        let Some(duration) = self.config.quarantine_lag_growth_duration() else {
+            return;
+        };
+
+        let Some(leading) = self.engine.internal_server_state.leading() else {
+            return;
+        };
+        let Some(l) = &mut self.leader_data else {
+            return;
+        };
+
+        let now = C::AsyncRuntime::now();
+        let last_log_index = self.engine.state.last_log_id().index();
+
+        let replications = &l.replications;
+        l.quarantine.retain(|id| replications.contains_key(id));
+
+        for (id, handle) in replications.iter() {
+            let matching = leading.progress.try_get(id).and_then(|p| p.matching.index());
+            let lag = replication_lag(&matching, &last_log_index);
+
+            let Some(quarantined) = l.quarantine.observe(*id, lag, now, duration) else {
+                continue;
+            };
+
+            if quarantined {
+                tracing::warn!(
+                    target = display(id),
+                    lag,
+                    "replication lag kept growing for {:?}, quarantine",
+                    duration
+                );
+            } else {
+                tracing::info!(target = display(id), "quarantined target caught up, release");
             }
-            RaftMsg::ClientWriteRequest { app_data, tx } => {
-                self.write_entry(C::Entry::from_app_data(app_data), Some(tx));
+
+            let _ = handle.tx_repl.send(Replicate::Quarantine(quarantined));
+        }
+    }
+
+    /// Promote a learner added with `Raft::add_learner()` to a voter, once it has kept up with the
+    /// leader for
+    /// [`Config::auto_promote_learner_duration`](`crate::Config::auto_promote_learner_duration`).
+    ///
+    /// A promotion proposes a joint config, then the uniform config once the joint one is
+    /// committed, the same way as `Raft::change_membership()` does.
+    fn check_learner_promotion(&mut self) {
+        let Some(duration) = self.config.auto_promote_learner_duration() else {
+            return;
+        };
+
+        if self.leader_transfer.is_some() {
+            return;
+        }
+
+        let Some(leading) = self.engine.internal_server_state.leading() else {
+            return;
+        };
+        let Some(l) = &mut self.leader_data else {
+            return;
+        };
+
+        let now = C::AsyncRuntime::now();
+        let membership_state = &self.engine.state.membership_state;
+        let last_log_index = self.engine.state.last_log_id().index();
+        let promotion = &mut l.learner_promotion;
+
+        promotion.candidates.retain(|id, _| membership_state.contains(id) && !membership_state.is_voter(id));
+
+        let ids = promotion.candidates.keys().copied().collect::<Vec<_>>();
+        for id in ids {
+            let matching = leading.progress.try_get(&id).and_then(|p| p.matching.index());
+            let caught_up = replication_lag(&matching, &last_log_index) <= self.config.auto_promote_learner_lag;
+            promotion.observe(&id, caught_up, now);
+        }
+
+        // Wait for the membership change in progress to be committed.
+        let effective = membership_state.effective();
+        if membership_state.committed().log_id() != effective.log_id() {
+            return;
+        }
+
+        let id = if let Some(id) = promotion.promoting {
+            if effective.membership().get_joint_config().len() == 1 {
+                promotion.promoting = None;
+
+                if effective.is_voter(&id) {
+                    tracing::info!(id = display(id), "learner is promoted to voter");
+                    self.learner_promotion.record_promotion(id);
+                }
+                return;
             }
-            RaftMsg::Initialize { members, tx } => {
-                tracing::info!(
-                    members = debug(&members),
-                    "received RaftMsg::Initialize: {}",
-                    func_name!()
-                );
 
-                self.handle_initialize(members, tx);
+            // The joint config is committed, move on to the uniform config.
+            id
+        } else if let Some(id) = promotion.take_ready(now, duration) {
+            tracing::info!(
+                id = display(id),
+                "learner has kept up with the leader for {:?}, promote it to voter",
+                duration
+            );
+            id
+        } else {
+            return;
+        };
+
+        let res = membership_state.change_handler().apply(ChangeMembers::AddVoterIds(btreeset! {id}), true);
+        let new_membership = match res {
+            Ok(x) => x,
+            Err(e) => {
+                tracing::warn!(id = display(id), "failed to promote learner: {}", e);
+                promotion.promoting = None;
+                return;
             }
-            RaftMsg::ChangeMembership { changes, retain, tx } => {
-                tracing::info!(
-                    members = debug(&changes),
-                    retain = debug(&retain),
-                    "received RaftMsg::ChangeMembership: {}",
-                    func_name!()
-                );
+        };
 
-                self.change_membership(changes, retain, tx);
+        let ent = C::Entry::new_membership(LogId::default(), new_membership);
+        if !self.write_entry(ent, None) {
+            if let Some(l) = &mut self.leader_data {
+                l.learner_promotion.promoting = None;
             }
-            RaftMsg::ExternalCoreRequest { req } => {
-                req(&self.engine.state);
+        }
+    }
+
+    /// Remove a member that has been unreachable for
+    /// [`Config::auto_remove_dead_member_duration`](`crate::Config::auto_remove_dead_member_duration`).
+    ///
+    /// A voter is removed only if the alive voters other than it form a quorum of both the current
+    /// and the new config. A [`DeadMember`] is broadcast before the removal is proposed. Removing a
+    /// voter proposes a joint config, then the uniform config once the joint one is committed,
+    /// the same way as `Raft::change_membership()` does.
+    fn check_dead_member_removal(&mut self) {
+        let Some(duration) = self.config.auto_remove_dead_member_duration() else {
+            return;
+        };
+
+        if self.leader_transfer.is_some() {
+            return;
+        }
+
+        let Some(alive) = self.alive_nodes() else {
+            return;
+        };
+        let Some(leading) = self.engine.internal_server_state.leading() else {
+            return;
+        };
+        let Some(l) = &mut self.leader_data else {
+            return;
+        };
+
+        let now = C::AsyncRuntime::now();
+        let membership_state = &self.engine.state.membership_state;
+        let effective = membership_state.effective();
+        let removal = &mut l.dead_member_removal;
+
+        let last_heard = effective
+            .membership()
+            .nodes()
+            .map(|(id, _)| *id)
+            .filter(|id| *id != self.id)
+            .map(|id| (id, leading.clock_progress.try_get(&id).copied().flatten()));
+        let dead = removal.dead_members(last_heard, now, duration);
+
+        // Wait for the membership change in progress to be committed.
+        if membership_state.committed().log_id() != effective.log_id() {
+            return;
+        }
+
+        let (id, voter) = if let Some(id) = removal.removing {
+            if effective.membership().get_joint_config().len() == 1 {
+                removal.removing = None;
+
+                if !effective.membership().contains(&id) {
+                    tracing::info!(id = display(id), "unreachable member is removed");
+                }
+                return;
             }
-            RaftMsg::ExternalCommand { cmd } => {
-                tracing::info!(cmd = debug(&cmd), "received RaftMsg::ExternalCommand: {}", func_name!());
 
-                match cmd {
-                    ExternalCommand::Elect => {
-                        if self.engine.state.membership_state.effective().is_voter(&self.id) {
-                            // TODO: reject if it is already a leader?
-                            self.engine.elect();
-                            tracing::debug!("ExternalCommand: triggered election");
-                        } else {
-                            // Node is switched to learner.
-                        }
-                    }
-                    ExternalCommand::Heartbeat => {
-                        self.send_heartbeat("ExternalCommand");
-                    }
-                    ExternalCommand::Snapshot => self.trigger_snapshot(),
-                    ExternalCommand::GetSnapshot { tx } => {
-                        let cmd = sm::Command::get_snapshot(tx);
-                        let res = self.sm_handle.send(cmd);
-                        if let Err(e) = res {
-                            tracing::error!(error = display(e), "error sending GetSnapshot to sm worker");
-                        }
-                    }
-                    ExternalCommand::PurgeLog { upto } => {
-                        self.engine.trigger_purge_log(upto);
-                    }
+            // The joint config is committed, move on to the uniform config.
+            (id, true)
+        } else {
+            let membership = effective.membership();
+
+            // Wait for a joint config proposed by others to be completed.
+            let [last] = membership.get_joint_config().as_slice() else {
+                return;
+            };
+
+            let removable = dead.into_iter().find(|(id, _)| {
+                if !last.contains(id) {
+                    return true;
                 }
+
+                // The joint config has to be committed by a quorum of both configs.
+                let curr = last.iter().copied().collect::<Vec<_>>();
+                let voters = last.iter().copied().filter(|x| x != id).collect::<Vec<_>>();
+                let alive_voters = alive.iter().copied().filter(|x| voters.contains(x)).collect();
+                let policy = membership.quorum_policy();
+                policy.is_quorum_of(&curr, &alive_voters) && policy.is_quorum_of(&voters, &alive_voters)
+            });
+
+            let Some((id, unreachable_for)) = removable else {
+                return;
+            };
+
+            let voter = effective.is_voter(&id);
+            let dead_member = DeadMember {
+                id,
+                voter,
+                unreachable_for,
+                membership_config: effective.stored_membership().clone(),
+            };
+
+            tracing::warn!(
+                dead_member = display(&dead_member),
+                "member has been unreachable for {:?}, remove it",
+                duration
+            );
+
+            // No subscriber is not an error.
+            let _ = self.tx_dead_member.send(dead_member);
+
+            removal.removing = Some(id);
+            (id, voter)
+        };
+
+        let changes = if voter {
+            ChangeMembers::RemoveVoters(btreeset! {id})
+        } else {
+            ChangeMembers::RemoveNodes(btreeset! {id})
+        };
+
+        let res = membership_state.change_handler().apply(changes, false);
+        let new_membership = match res {
+            Ok(x) => x,
+            Err(e) => {
+                tracing::warn!(id = display(id), "failed to remove unreachable member: {}", e);
+                removal.removing = None;
+                return;
             }
         };
-    }
 
-    // TODO: Make this method non-async. It does not need to run any async command in it.
-    #[tracing::instrument(level = "debug", skip_all, fields(state = debug(self.engine.state.server_state), id=display(self.id)))]
-    pub(crate) fn handle_notify(&mut self, notify: Notify<C>) -> Result<(), Fatal<C>> {
-        tracing::debug!("recv from rx_notify: {}", notify);
+        let ent = C::Entry::new_membership(LogId::default(), new_membership);
+        if !self.write_entry(ent, None) {
+            if let Some(l) = &mut self.leader_data {
+                l.dead_member_removal.removing = None;
+            }
+        }
+    }
 
-        match notify {
-            Notify::VoteResponse {
-                target,
-                resp,
-                sender_vote: vote,
-            } => {
-                let now = InstantOf::<C>::now();
+    /// Move the leadership transfer in progress forward.
+    ///
+    /// The transfer request is sent once the target has replicated all the logs of the leader.
+    /// The transfer finishes when the target becomes the leader, or it times out and this node
+    /// resumes serving as the leader.
+    fn check_leader_transfer(&mut self) {
+        let Some(transfer) = &mut self.leader_transfer else {
+            return;
+        };
+        let to = transfer.to;
 
-                tracing::info!(
-                    now = debug(now),
-                    resp = display(&resp),
-                    "received Notify::VoteResponse: {}",
-                    func_name!()
-                );
+        let vote = *self.engine.state.vote_ref();
+        if vote.is_committed() && vote.leader_id().voted_for() == Some(to) {
+            tracing::info!(to = display(to), "leadership is transferred");
 
-                if self.does_vote_match(&vote, "VoteResponse") {
-                    self.engine.handle_vote_resp(target, resp);
-                }
+            let transfer = self.leader_transfer.take().unwrap();
+            if let Some(tx) = transfer.tx {
+                let _ = tx.send(Ok(()));
             }
+            return;
+        }
 
-            Notify::HigherVote {
-                target,
-                higher,
-                sender_vote: vote,
-            } => {
-                tracing::info!(
-                    target = display(target),
-                    higher_vote = display(&higher),
-                    sending_vote = display(&vote),
-                    "received Notify::HigherVote: {}",
-                    func_name!()
-                );
+        if C::AsyncRuntime::now() >= transfer.deadline && transfer.step_down {
+            tracing::warn!(to = display(to), "leadership transfer timeout, step down");
 
-                if self.does_vote_match(&vote, "HigherVote") {
-                    // Rejected vote change is ok.
-                    let _ = self.engine.vote_handler().update_vote(&higher);
-                }
-            }
+            let transfer = self.leader_transfer.take().unwrap();
+            self.step_down_at_once();
 
-            Notify::Tick { i } => {
-                // check every timer
+            if let Some(tx) = transfer.tx {
+                let _ = tx.send(Ok(()));
+            }
+            return;
+        }
 
-                let now = InstantOf::<C>::now();
-                tracing::debug!("received tick: {}, now: {:?}", i, now);
+        if C::AsyncRuntime::now() >= transfer.deadline {
+            tracing::warn!(to = display(to), "leadership transfer timeout, resume serving");
 
-                self.handle_tick_election();
+            let transfer = self.leader_transfer.take().unwrap();
+            self.engine.transferring_to = None;
 
-                // TODO: test: fixture: make isolated_nodes a single-way isolating.
+            let timeout = self.config.transfer_leader_timeout();
+            if let Some(l) = &mut self.leader_data {
+                l.next_priority_transfer = C::AsyncRuntime::now() + timeout;
+            }
+            if let Some(tx) = transfer.tx {
+                let _ = tx.send(Err(TransferLeaderTimeout { to, timeout }.into()));
+            }
+            return;
+        }
 
-                // Leader send heartbeat
-                let heartbeat_at = self.leader_data.as_ref().map(|x| x.next_heartbeat);
-                if let Some(t) = heartbeat_at {
-                    if now >= t {
-                        if self.runtime_config.enable_heartbeat.load(Ordering::Relaxed) {
-                            self.send_heartbeat("tick");
-                        }
+        if transfer.sent {
+            return;
+        }
 
-                        // Install next heartbeat
-                        if let Some(l) = &mut self.leader_data {
-                            l.next_heartbeat =
-                                InstantOf::<C>::now() + Duration::from_millis(self.config.heartbeat_interval);
-                        }
-                    }
-                }
+        let Some(leading) = self.engine.internal_server_state.leading() else {
+            return;
+        };
 
-                // When a membership that removes the leader is committed,
-                // the leader continue to work for a short while before reverting to a learner.
-                // This way, let the leader replicate the `membership-log-is-committed` message to
-                // followers.
-                // Otherwise, if the leader step down at once, the follower might have to
-                // re-commit the membership log again, electing itself.
-                //
-                // ---
-                //
-                // Stepping down only when the response of the second change-membership is sent.
-                // Otherwise the Sender to the caller will be dropped before sending back the
-                // response.
-
-                // TODO: temp solution: Manually wait until the second membership log being applied to state
-                //       machine. Because the response is sent back to the caller after log is
-                //       applied.
-                //       ---
-                //       A better way is to make leader step down a command that waits for the log to be applied.
-                if self.engine.state.io_applied() >= self.engine.state.membership_state.effective().log_id().as_ref() {
-                    self.engine.leader_step_down();
-                }
-            }
+        let last_log_id = self.engine.state.last_log_id().copied();
+        let matching = leading.progress.try_get(&to).and_then(|p| p.matching);
+        if matching < last_log_id {
+            tracing::debug!(
+                to = display(to),
+                matching = display(matching.display()),
+                "leadership transfer target has not caught up with: {}",
+                last_log_id.display()
+            );
+            return;
+        }
 
-            Notify::Network { response } => {
-                //
-                match response {
-                    replication::Response::Progress {
-                        target,
-                        request_id: id,
-                        result,
-                        session_id,
-                    } => {
-                        // If vote or membership changes, ignore the message.
-                        // There is chance delayed message reports a wrong state.
-                        if self.does_replication_session_match(&session_id, "UpdateReplicationMatched") {
-                            self.handle_replication_progress(target, id, result);
-                        }
-                    }
+        transfer.sent = true;
 
-                    replication::Response::StorageError { error } => {
-                        tracing::error!(
-                            error = display(&error),
-                            "received Notify::ReplicationStorageError: {}",
-                            func_name!()
-                        );
+        if let Some(l) = &mut self.leader_data {
+            l.lease_revoked = true;
+        }
 
-                        return Err(Fatal::from(error));
-                    }
+        let req = TransferLeaderRequest {
+            from_leader: vote,
+            to,
+            last_log_id,
+            context: RpcContext::default(),
+        };
+        self.engine.transferring_to = Some((vote, to));
+        self.engine.output.push_command(Command::BroadcastTransferLeader { req });
+    }
 
-                    replication::Response::HigherVote { target, higher, vote } => {
-                        tracing::info!(
-                            target = display(target),
-                            higher_vote = display(&higher),
-                            sending_vote = display(&vote),
-                            "received Notify::HigherVote: {}",
-                            func_name!()
-                        );
+    /// Send a leadership transfer request to every voter except this node.
+    async fn broadcast_transfer_leader(&mut self, req: TransferLeaderRequest<C>) {
+        let ttl = self.runtime_config.rpc_timeout(RPCTypes::Vote);
 
-                        if self.does_vote_match(&vote, "HigherVote") {
-                            // Rejected vote change is ok.
-                            let _ = self.engine.vote_handler().update_vote(&higher);
-                        }
-                    }
-                }
+        for target in self.engine.state.membership_state.effective().voter_ids() {
+            if target == self.id {
+                continue;
             }
 
-            Notify::StateMachine { command_result } => {
-                tracing::debug!("sm::StateMachine command result: {:?}", command_result);
+            // Safe unwrap(): target must be in membership
+            let target_node = self.engine.state.membership_state.effective().get_node(&target).unwrap().clone();
+            let mut client = self.network.new_client(target, &target_node).await;
 
-                let seq = command_result.command_seq;
-                let res = command_result.result?;
+            let mut req = req.clone();
+            self.auth.sign_transfer_leader(target, &mut req);
+            N::inject_context(&mut req.context);
 
+            let fu = async move {
+                let res = C::AsyncRuntime::timeout(ttl, client.transfer_leader(req, RPCOption::new(ttl))).await;
                 match res {
-                    // BuildSnapshot is a read operation that does not have to be serialized by
-                    // sm::Worker. Thus it may finish out of order.
-                    sm::Response::BuildSnapshot(_) => {}
-                    _ => {
-                        debug_assert!(
-                            self.command_state.finished_sm_seq < seq,
-                            "sm::StateMachine command result is out of order: expect {} < {}",
-                            self.command_state.finished_sm_seq,
-                            seq
-                        );
+                    Ok(Ok(())) => {}
+                    Ok(Err(err)) => {
+                        tracing::error!({error=%err, target=display(target)}, "while sending transfer leader request")
+                    }
+                    Err(_timeout) => {
+                        tracing::error!(target = display(target), "timeout sending transfer leader request")
                     }
                 }
-                self.command_state.finished_sm_seq = seq;
+            };
 
-                match res {
-                    sm::Response::BuildSnapshot(meta) => {
-                        tracing::info!(
-                            "sm::StateMachine command done: BuildSnapshot: {}: {}",
-                            meta,
-                            func_name!()
-                        );
+            // False positive lint warning(`non-binding `let` on a future`): https://github.com/rust-lang/rust-clippy/issues/9932
+            #[allow(clippy::let_underscore_future)]
+            let _ = C::AsyncRuntime::spawn(fu.instrument(tracing::debug_span!(
+                parent: &Span::current(),
+                "send_transfer_leader",
+                target = display(target)
+            )));
+        }
+    }
 
-                        // Update in-memory state first, then the io state.
-                        // In-memory state should always be ahead or equal to the io state.
+    /// Check every timer, on a tick emitted by the timer task or by `Raft::tick()`.
+    fn handle_tick(&mut self) {
+        let now = C::AsyncRuntime::now();
+        tracing::debug!("handle tick, now: {:?}", now);
+
+        self.handle_tick_check_quorum();
+        self.check_priority_transfer();
+        self.check_leader_transfer();
+        self.check_learner_promotion();
+        self.check_dead_member_removal();
+        self.check_quarantine();
+        self.handle_tick_election();
+
+        // TODO: test: fixture: make isolated_nodes a single-way isolating.
+
+        // Leader send heartbeat
+        let heartbeat_at = self.leader_data.as_ref().map(|x| x.next_heartbeat);
+        if let Some(t) = heartbeat_at {
+            if now >= t {
+                if self.runtime_config.enable_heartbeat.load(Ordering::Relaxed) {
+                    self.send_heartbeat("tick");
+                }
 
-                        let last_log_id = meta.last_log_id;
-                        self.engine.finish_building_snapshot(meta);
+                // Install next heartbeat
+                if let Some(l) = &mut self.leader_data {
+                    l.next_heartbeat = C::AsyncRuntime::now() + Duration::from_millis(self.config.heartbeat_interval);
+                }
+            }
+        }
 
-                        let st = self.engine.state.io_state_mut();
-                        st.update_snapshot(last_log_id);
-                    }
-                    sm::Response::InstallSnapshot(meta) => {
-                        tracing::info!(
-                            "sm::StateMachine command done: InstallSnapshot: {}: {}",
-                            meta.display(),
-                            func_name!()
-                        );
+        // When a membership that removes the leader is committed,
+        // the leader continue to work for a short while before reverting to a learner.
+        // This way, let the leader replicate the `membership-log-is-committed` message to
+        // followers.
+        // Otherwise, if the leader step down at once, the follower might have to
+        // re-commit the membership log again, electing itself.
+        //
+        // ---
+        //
+        // Stepping down only when the response of the second change-membership is sent.
+        // Otherwise the Sender to the caller will be dropped before sending back the
+        // response.
+
+        // TODO: temp solution: Manually wait until the second membership log being applied to state
+        //       machine. Because the response is sent back to the caller after log is
+        //       applied.
+        //       ---
+        //       A better way is to make leader step down a command that waits for the log to be applied.
+        if self.engine.state.io_applied() >= self.engine.state.membership_state.effective().log_id().as_ref() {
+            if self.config.transfer_leader_on_removal {
+                self.transfer_leader_on_removal();
+            } else {
+                self.engine.leader_step_down();
+            }
+        }
+    }
 
-                        if let Some(meta) = meta {
-                            let st = self.engine.state.io_state_mut();
-                            st.update_applied(meta.last_log_id);
-                            st.update_snapshot(meta.last_log_id);
-                        }
-                    }
-                    sm::Response::Apply(res) => {
-                        self.engine.state.io_state_mut().update_applied(Some(res.last_applied));
+    /// Let the leader step down if no quorum has acknowledged it for a leader lease.
+    ///
+    /// It is disabled unless [`Config::enable_check_quorum`] is set.
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn handle_tick_check_quorum(&mut self) {
+        if !self.config.enable_check_quorum {
+            return;
+        }
 
-                        self.handle_apply_result(res);
-                    }
-                }
-            }
+        let Some(established_at) = self.leader_data.as_ref().map(|l| l.established_at) else {
+            return;
         };
-        Ok(())
+
+        // A newly established leader is given a full lease before any acknowledgement arrives.
+        let acked = std::cmp::max(self.last_quorum_acked_time(), Some(established_at));
+        let lease = self.engine.config.timer_config.leader_lease;
+        let now = C::AsyncRuntime::now();
+
+        if acked > Some(now - lease) {
+            return;
+        }
+
+        tracing::warn!(
+            "leader has not been acknowledged by a quorum for {:?}, longer than lease {:?}, step down",
+            acked.map(|t| now - t),
+            lease
+        );
+        self.engine.leader_step_down_by_lost_quorum();
     }
 
     #[tracing::instrument(level = "debug", skip_all)]
     fn handle_tick_election(&mut self) {
-        let now = InstantOf::<C>::now();
+        let now = C::AsyncRuntime::now();
 
         tracing::debug!("try to trigger election by tick, now: {:?}", now);
 
@@ -1426,11 +3652,21 @@ where
             return;
         }
 
+        if self.step_down_until > Some(now) {
+            tracing::debug!("this node has stepped down, do not elect");
+            return;
+        }
+
         if !self.engine.state.membership_state.effective().is_voter(&self.id) {
             tracing::debug!("this node is not a voter");
             return;
         }
 
+        if self.engine.state.membership_state.effective().is_witness(&self.id) {
+            tracing::debug!("this node is a witness, it never becomes the leader");
+            return;
+        }
+
         if !self.runtime_config.enable_elect.load(Ordering::Relaxed) {
             tracing::debug!("election is disabled");
             return;
@@ -1442,11 +3678,13 @@ where
             tracing::debug!("there are multiple voter, check election timeout");
 
             let current_vote = self.engine.state.vote_ref();
-            let utime = self.engine.state.vote_last_modified();
+            // A pre-vote round in progress defers the next one, just like a vote does.
+            let pre_vote_utime = self.engine.pre_voting.as_ref().map(|v| *v.starting_time());
+            let utime = std::cmp::max(self.engine.state.vote_last_modified(), pre_vote_utime);
             let timer_config = &self.engine.config.timer_config;
 
             let mut election_timeout = if current_vote.is_committed() {
-                timer_config.leader_lease + timer_config.election_timeout
+                timer_config.leader_lease + timer_config.election_timeout + self.election_backoff
             } else {
                 timer_config.election_timeout
             };
@@ -1455,6 +3693,8 @@ where
                 election_timeout += timer_config.smaller_log_timeout;
             }
 
+            election_timeout += self.engine.election_priority_delay();
+
             tracing::debug!(
                 "vote utime: {:?}, current_vote: {}, now-utime:{:?}, election_timeout: {:?}",
                 utime,
@@ -1474,9 +3714,10 @@ where
 
         // Every time elect, reset this flag.
         self.engine.reset_greater_log();
+        self.election_backoff = self.config.new_rand_election_backoff::<AsyncRuntimeOf<C>>();
 
         tracing::info!("do trigger election");
-        self.engine.elect();
+        self.engine.pre_elect();
     }
 
     #[tracing::instrument(level = "debug", skip_all)]
@@ -1575,14 +3816,19 @@ where
                     }
                 }
                 Condition::StateMachineCommand { command_seq } => {
-                    if self.command_state.finished_sm_seq < *command_seq {
+                    let command_seq = *command_seq;
+                    if self.command_state.finished_sm_seq < command_seq {
+                        // Such as responding to an install-snapshot request: Installing may take
+                        // a long time; Do not let it block the following commands, such as
+                        // responding to a vote or append-entries request.
                         tracing::debug!(
-                            "sm::Command({}) has not yet finished({}), postpone cmd: {:?}",
+                            "sm::Command({}) has not yet finished({}), park cmd: {:?}",
                             command_seq,
                             self.command_state.finished_sm_seq,
                             cmd
                         );
-                        return Ok(Some(cmd));
+                        self.waiting_sm_commands.push((command_seq, cmd));
+                        return Ok(None);
                     }
                 }
             }
@@ -1600,12 +3846,40 @@ where
                 let last_log_id = *entries.last().unwrap().get_log_id();
                 tracing::debug!("AppendInputEntries: {}", DisplaySlice::<_>(&entries),);
 
-                self.append_to_log(entries, vote, last_log_id).await?;
+                // The leader does not wait for the entries to be flushed: the following
+                // `Replicate` command sends them to the followers at once, and the leader's own
+                // progress is updated by `Notify::LocalLogFlushed`, like a follower's.
+                //
+                // If the leader alone is a quorum, the entries are committed as soon as they are
+                // flushed, and there is nothing to gain by not waiting.
+                let leading = self.engine.internal_server_state.leading().filter(|l| l.vote == vote);
+                let is_leader = leading.is_some();
+                let is_single_quorum = leading.is_some_and(|l| l.progress.quorum_set().is_quorum([self.id].iter()));
+
+                if is_leader && !is_single_quorum {
+                    let rx = self.submit_append_to_log(entries, vote, last_log_id).await?;
+                    let tx_notify = self.tx_notify.clone();
+
+                    let fu = async move {
+                        let result = match rx.await {
+                            Ok(res) => res,
+                            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e.to_string())),
+                        };
+
+                        let _ = tx_notify.send(Notify::LocalLogFlushed { vote, result });
+                    };
+
+                    // False positive lint warning(`non-binding `let` on a future`): https://github.com/rust-lang/rust-clippy/issues/9932
+                    #[allow(clippy::let_underscore_future)]
+                    let _ = C::AsyncRuntime::spawn(fu.instrument(tracing::debug_span!("wait_local_log_flushed")));
+                } else {
+                    self.append_to_log(entries, vote, last_log_id).await?;
 
-                // The leader may have changed.
-                // But reporting to a different leader is not a problem.
-                if let Ok(mut lh) = self.engine.leader_handler() {
-                    lh.replication_handler().update_local_progress(Some(last_log_id));
+                    if is_leader {
+                        if let Ok(mut lh) = self.engine.leader_handler() {
+                            lh.replication_handler().update_local_progress(Some(last_log_id));
+                        }
+                    }
                 }
             }
             Command::SaveVote { vote } => {
@@ -1613,14 +3887,17 @@ where
                 self.engine.state.io_state_mut().update_vote(vote);
             }
             Command::PurgeLog { upto } => {
+                self.log_cache.purge(upto.index);
                 self.log_store.purge(upto).await?;
                 self.engine.state.io_state_mut().update_purged(Some(upto));
             }
             Command::DeleteConflictLog { since } => {
+                self.log_cache.truncate(since.index);
                 self.log_store.truncate(since).await?;
 
                 // Inform clients waiting for logs to be applied.
                 let removed = self.client_resp_channels.split_off(&since.index);
+                self.write_deadlines.truncate(since.index);
                 if !removed.is_empty() {
                     let leader_id = self.current_leader();
                     let leader_node = self.get_leader_node(leader_id);
@@ -1642,6 +3919,12 @@ where
             Command::SendVote { vote_req } => {
                 self.spawn_parallel_vote_requests(&vote_req).await;
             }
+            Command::ConfirmLeadership => {
+                self.confirm_leadership().await;
+            }
+            Command::BroadcastTransferLeader { req } => {
+                self.broadcast_transfer_leader(req).await;
+            }
             Command::ReplicateCommitted { committed } => {
                 if let Some(l) = &self.leader_data {
                     for node in l.replications.values() {
@@ -1651,6 +3934,24 @@ where
                     unreachable!("it has to be a leader!!!");
                 }
             }
+            Command::BroadcastHeartbeat { committed } => {
+                let rtt = self.cluster_rtt();
+                if let Some(rtt) = rtt {
+                    self.tune_election_timeout(rtt);
+                }
+
+                if let Some(l) = &self.leader_data {
+                    for node in l.replications.values() {
+                        if let Some(tx_heartbeat) = &node.tx_heartbeat {
+                            let _ = tx_heartbeat.send(Some(HeartbeatEvent::new(committed).with_rtt(rtt)));
+                        } else {
+                            let _ = node.tx_repl.send(Replicate::Heartbeat);
+                        }
+                    }
+                } else {
+                    unreachable!("it has to be a leader!!!");
+                }
+            }
             Command::Commit {
                 seq,
                 ref already_committed,
@@ -1671,10 +3972,24 @@ where
                             let _ = node.tx_repl.send(Replicate::logs(RequestId::new_append_entries(id), log_id_range));
                         }
                         Inflight::Snapshot { id, last_log_id } => {
-                            // unwrap: The replication channel must not be dropped or it is a bug.
-                            node.tx_repl.send(Replicate::snapshot(RequestId::new_snapshot(id), last_log_id)).map_err(
-                                |_e| StorageIOError::read_snapshot(None, AnyError::error("replication channel closed")),
-                            )?;
+                            let request_id = RequestId::new_snapshot(id);
+                            let req = Replicate::snapshot(request_id, last_log_id);
+
+                            let source = if self.config.delegate_snapshot_transfer {
+                                self.snapshot_transfer_source(target)
+                            } else {
+                                None
+                            };
+
+                            if let Some(source) = source {
+                                let tx_repl = node.tx_repl.clone();
+                                self.delegate_snapshot_transfer(source, target, request_id, tx_repl, req).await;
+                            } else {
+                                // unwrap: The replication channel must not be dropped or it is a bug.
+                                node.tx_repl.send(req).map_err(|_e| {
+                                    StorageIOError::read_snapshot(None, AnyError::error("replication channel closed"))
+                                })?;
+                            }
                         }
                     }
                 } else {
@@ -1682,20 +3997,40 @@ where
                 }
             }
             Command::RebuildReplicationStreams { targets } => {
+                // Keep the snapshot transmission progress for the targets that are still
+                // replicated to.
+                let mut resumes = if let Some(l) = &self.leader_data {
+                    l.replications.iter().map(|(id, h)| (*id, h.snapshot_resume.clone())).collect::<BTreeMap<_, _>>()
+                } else {
+                    BTreeMap::new()
+                };
+
                 self.remove_all_replication().await;
 
+                // Let the network drop the connections to the old addresses before connecting.
+                self.report_node_updates();
+
                 for (target, matching) in targets.iter() {
-                    let handle = self.spawn_replication_stream(*target, *matching).await;
+                    let resume = resumes.remove(target).unwrap_or_default();
+                    let handle = self.spawn_replication_stream(*target, *matching, resume).await;
 
                     if let Some(l) = &mut self.leader_data {
+                        // A new stream to a quarantined target has to cap its resources too.
+                        if l.quarantine.quarantined.contains(target) {
+                            let _ = handle.tx_repl.send(Replicate::Quarantine(true));
+                        }
                         l.replications.insert(*target, handle);
                     } else {
                         unreachable!("it has to be a leader!!!");
                     }
                 }
             }
-            Command::StateMachine { command } => {
-                // Just forward a state machine command to the worker.
+            Command::StateMachine { mut command } => {
+                if let sm::CommandPayload::BuildSnapshot { progress } = &mut command.payload {
+                    *progress = Some(self.snapshot_building.get_or_insert_with(Default::default).clone());
+                }
+
+                // Forward a state machine command to the worker.
                 self.sm_handle.send(command).map_err(|_e| {
                     StorageIOError::write_state_machine(AnyError::error("can not send to sm::Worker".to_string()))
                 })?;
@@ -3,8 +3,10 @@
 use std::fmt;
 
 use crate::core::raft_msg::ResultSender;
+use crate::storage::SnapshotBuildProgress;
 use crate::RaftTypeConfig;
 use crate::Snapshot;
+use crate::SnapshotPolicy;
 
 /// Application-triggered Raft actions for testing and administration.
 ///
@@ -19,18 +21,45 @@ pub(crate) enum ExternalCommand<C: RaftTypeConfig> {
     /// Send a heartbeat message, only if the node is leader, or it will be ignored.
     Heartbeat,
 
+    /// Check every timer, just like a tick emitted by the timer task.
+    Tick,
+
     /// Initiate to build a snapshot on this node.
-    Snapshot,
+    ///
+    /// The handle of the snapshot being built is sent back via `tx`.
+    Snapshot {
+        tx: ResultSender<C, SnapshotBuildProgress<C>>,
+    },
 
     /// Get a snapshot from the state machine, send back via a oneshot::Sender.
     GetSnapshot { tx: ResultSender<C, Option<Snapshot<C>>> },
 
     /// Purge logs covered by a snapshot up to a specified index.
     ///
-    /// Openraft respects the [`max_in_snapshot_log_to_keep`] config when purging.
+    /// The [`log_retention_policy`] config is not taken into account when purging.
     ///
-    /// [`max_in_snapshot_log_to_keep`]: `crate::Config::max_in_snapshot_log_to_keep`
+    /// [`log_retention_policy`]: `crate::Config::log_retention_policy`
     PurgeLog { upto: u64 },
+
+    /// Replace the [`snapshot_policy`] config.
+    ///
+    /// [`snapshot_policy`]: `crate::Config::snapshot_policy`
+    SetSnapshotPolicy { policy: SnapshotPolicy },
+
+    /// Replace the [`leader_blank_entry`] config.
+    ///
+    /// [`leader_blank_entry`]: `crate::Config::leader_blank_entry`
+    SetLeaderBlankEntry { enabled: bool },
+
+    /// Replace the [`reject_vote_within_lease`] config.
+    ///
+    /// [`reject_vote_within_lease`]: `crate::Config::reject_vote_within_lease`
+    SetRejectVoteWithinLease { enabled: bool },
+
+    /// Replace the [`one_vote_per_term`] config.
+    ///
+    /// [`one_vote_per_term`]: `crate::Config::one_vote_per_term`
+    SetOneVotePerTerm { enabled: bool },
 }
 
 impl<C> fmt::Debug for ExternalCommand<C>
@@ -52,7 +81,10 @@ where C: RaftTypeConfig
             ExternalCommand::Heartbeat => {
                 write!(f, "Heartbeat")
             }
-            ExternalCommand::Snapshot => {
+            ExternalCommand::Tick => {
+                write!(f, "Tick")
+            }
+            ExternalCommand::Snapshot { .. } => {
                 write!(f, "Snapshot")
             }
             ExternalCommand::GetSnapshot { .. } => {
@@ -61,6 +93,18 @@ where C: RaftTypeConfig
             ExternalCommand::PurgeLog { upto } => {
                 write!(f, "PurgeLog[..={}]", upto)
             }
+            ExternalCommand::SetSnapshotPolicy { policy } => {
+                write!(f, "SetSnapshotPolicy: {:?}", policy)
+            }
+            ExternalCommand::SetLeaderBlankEntry { enabled } => {
+                write!(f, "SetLeaderBlankEntry: {}", enabled)
+            }
+            ExternalCommand::SetRejectVoteWithinLease { enabled } => {
+                write!(f, "SetRejectVoteWithinLease: {}", enabled)
+            }
+            ExternalCommand::SetOneVotePerTerm { enabled } => {
+                write!(f, "SetOneVotePerTerm: {}", enabled)
+            }
         }
     }
 }
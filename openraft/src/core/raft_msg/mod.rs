@@ -3,14 +3,28 @@ use std::fmt;
 
 use crate::core::raft_msg::external_command::ExternalCommand;
 use crate::error::CheckIsLeaderError;
+use crate::error::ClientWriteError;
+use crate::error::ForwardToLeader;
 use crate::error::Infallible;
 use crate::error::InitializeError;
+use crate::error::RPCError;
+use crate::error::RaftError;
+use crate::error::TransferLeaderError;
+use crate::error::TransferSnapshotError;
+use crate::network::RpcContext;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
 use crate::raft::BoxCoreFn;
+use crate::raft::ClientWriteResponse;
+use crate::raft::ReadPolicy;
+use crate::raft::ReplicationStatus;
 use crate::raft::SnapshotResponse;
+use crate::raft::TransferLeaderRequest;
+use crate::raft::TransferSnapshotRequest;
+use crate::raft::TransferSnapshotResponse;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
+use crate::type_config::alias::InstantOf;
 use crate::type_config::alias::LogIdOf;
 use crate::type_config::alias::OneshotSenderOf;
 use crate::type_config::alias::ResponderOf;
@@ -34,6 +48,20 @@ pub(crate) type AppendEntriesTx<C> = ResultSender<C, AppendEntriesResponse<C>>;
 /// TX for Linearizable Read Response
 pub(crate) type ClientReadTx<C> = ResultSender<C, (Option<LogIdOf<C>>, Option<LogIdOf<C>>), CheckIsLeaderError<C>>;
 
+/// TX for the response of a client write forwarded to the leader.
+pub(crate) type ForwardClientWriteTx<C> =
+    ResultSender<C, ClientWriteResponse<C>, RPCError<C, RaftError<C, ClientWriteError<C>>>>;
+
+/// TX for the read log id returned by the leader for a read on a follower.
+pub(crate) type ForwardReadTx<C> =
+    ResultSender<C, Option<LogIdOf<C>>, RPCError<C, RaftError<C, CheckIsLeaderError<C>>>>;
+
+/// TX for the response of sending a snapshot on behalf of the leader.
+pub(crate) type TransferSnapshotTx<C> = ResultSender<C, TransferSnapshotResponse<C>, TransferSnapshotError<C>>;
+
+/// TX for the result of transferring the leadership to another node.
+pub(crate) type TransferLeaderTx<C> = ResultSender<C, (), TransferLeaderError<C>>;
+
 /// A message sent by application to the [`RaftCore`].
 ///
 /// [`RaftCore`]: crate::core::RaftCore
@@ -53,6 +81,8 @@ where C: RaftTypeConfig
     InstallFullSnapshot {
         vote: Vote<C::NodeId>,
         snapshot: Snapshot<C>,
+        /// The context propagated with the request that completes the snapshot.
+        context: RpcContext,
         tx: ResultSender<C, SnapshotResponse<C>>,
     },
 
@@ -66,12 +96,57 @@ where C: RaftTypeConfig
         tx: ResultSender<C, Box<SnapshotDataOf<C>>, Infallible>,
     },
 
+    /// Send the snapshot of this node to another node on behalf of the leader.
+    TransferSnapshot {
+        rpc: TransferSnapshotRequest<C>,
+        tx: TransferSnapshotTx<C>,
+    },
+
+    /// Hand over the leadership of this node to `to`.
+    TransferLeader {
+        to: C::NodeId,
+        tx: TransferLeaderTx<C>,
+    },
+
+    /// Stop serving as the leader, optionally handing over the leadership first.
+    StepDown {
+        transfer: bool,
+        tx: TransferLeaderTx<C>,
+    },
+
+    /// Take part in a leadership transfer started by the leader.
+    HandleTransferLeader {
+        rpc: TransferLeaderRequest<C>,
+        tx: ResultSender<C, ()>,
+    },
+
     ClientWriteRequest {
-        app_data: C::D,
+        /// The entry built from the application data by the caller.
+        entry: C::Entry,
         tx: ResponderOf<C>,
+
+        /// Respond with a timeout error if the entry is not committed by this time.
+        deadline: Option<InstantOf<C>>,
+    },
+
+    /// Forward a client write request to `target`, which is believed to be the leader.
+    ForwardClientWrite {
+        target: C::NodeId,
+        node: C::Node,
+        app_data: C::D,
+        tx: ForwardClientWriteTx<C>,
+    },
+
+    /// Ask `target`, which is believed to be the leader, for the read log id.
+    ForwardGetReadLogId {
+        target: C::NodeId,
+        node: C::Node,
+        read_policy: ReadPolicy,
+        tx: ForwardReadTx<C>,
     },
 
     CheckIsLeaderRequest {
+        read_policy: ReadPolicy,
         tx: ClientReadTx<C>,
     },
 
@@ -80,6 +155,12 @@ where C: RaftTypeConfig
         tx: ResultSender<C, (), InitializeError<C>>,
     },
 
+    #[cfg(feature = "unsafe-force-reset-membership")]
+    ForceResetMembership {
+        members: BTreeMap<C::NodeId, C::Node>,
+        tx: ResultSender<C, (), crate::error::NotInMembers<C>>,
+    },
+
     ChangeMembership {
         changes: ChangeMembers<C::NodeId, C::Node>,
 
@@ -87,9 +168,26 @@ where C: RaftTypeConfig
         /// config will be converted into learners, otherwise they will be removed.
         retain: bool,
 
+        /// Whether to refuse the change if it reduces the fault tolerance of the cluster, see
+        /// [`Config::membership_change_guard`](`crate::Config::membership_change_guard`).
+        guard: bool,
+
         tx: ResponderOf<C>,
     },
 
+    /// Add a learner, which is promoted to a voter once it has caught up, if automatic promotion
+    /// is enabled.
+    AddLearner {
+        id: C::NodeId,
+        node: C::Node,
+        tx: ResponderOf<C>,
+    },
+
+    /// Get the replication progress of every voter and learner, on the leader.
+    GetReplicationStatus {
+        tx: ResultSender<C, BTreeMap<C::NodeId, ReplicationStatus<C>>, ForwardToLeader<C>>,
+    },
+
     ExternalCoreRequest {
         req: BoxCoreFn<C>,
     },
@@ -114,19 +212,54 @@ where C: RaftTypeConfig
             RaftMsg::BeginReceivingSnapshot { .. } => {
                 write!(f, "BeginReceivingSnapshot")
             }
+            RaftMsg::TransferSnapshot { rpc, .. } => {
+                write!(f, "TransferSnapshot: {}", rpc)
+            }
+            RaftMsg::TransferLeader { to, .. } => write!(f, "TransferLeader: to: {}", to),
+            RaftMsg::StepDown { transfer, .. } => write!(f, "StepDown: transfer: {}", transfer),
+            RaftMsg::HandleTransferLeader { rpc, .. } => {
+                write!(f, "HandleTransferLeader: {}", rpc)
+            }
             RaftMsg::InstallFullSnapshot { vote, snapshot, .. } => {
                 write!(f, "InstallFullSnapshot: vote: {}, snapshot: {}", vote, snapshot)
             }
             RaftMsg::ClientWriteRequest { .. } => write!(f, "ClientWriteRequest"),
-            RaftMsg::CheckIsLeaderRequest { .. } => write!(f, "CheckIsLeaderRequest"),
+            RaftMsg::ForwardClientWrite { target, .. } => write!(f, "ForwardClientWrite: target: {}", target),
+            RaftMsg::ForwardGetReadLogId {
+                target, read_policy, ..
+            } => {
+                write!(
+                    f,
+                    "ForwardGetReadLogId: target: {}, read_policy: {:?}",
+                    target, read_policy
+                )
+            }
+            RaftMsg::CheckIsLeaderRequest { read_policy, .. } => {
+                write!(f, "CheckIsLeaderRequest: read_policy: {:?}", read_policy)
+            }
             RaftMsg::Initialize { members, .. } => {
                 // TODO: avoid using Debug
                 write!(f, "Initialize: {:?}", members)
             }
-            RaftMsg::ChangeMembership { changes, retain, .. } => {
+            #[cfg(feature = "unsafe-force-reset-membership")]
+            RaftMsg::ForceResetMembership { members, .. } => {
+                // TODO: avoid using Debug
+                write!(f, "ForceResetMembership: {:?}", members)
+            }
+            RaftMsg::ChangeMembership {
+                changes, retain, guard, ..
+            } => {
                 // TODO: avoid using Debug
-                write!(f, "ChangeMembership: members: {:?}, retain: {}", changes, retain,)
+                write!(
+                    f,
+                    "ChangeMembership: members: {:?}, retain: {}, guard: {}",
+                    changes, retain, guard
+                )
+            }
+            RaftMsg::AddLearner { id, node, .. } => {
+                write!(f, "AddLearner: id: {}, node: {:?}", id, node)
             }
+            RaftMsg::GetReplicationStatus { .. } => write!(f, "GetReplicationStatus"),
             RaftMsg::ExternalCoreRequest { .. } => write!(f, "External Request"),
             RaftMsg::ExternalCommand { cmd } => {
                 write!(f, "ExternalCommand: {}", cmd)
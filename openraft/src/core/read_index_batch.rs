@@ -0,0 +1,56 @@
+use crate::core::raft_msg::ClientReadTx;
+use crate::metrics::ReadIndexMetrics;
+use crate::RaftTypeConfig;
+
+/// Coalesces the read requests that confirm the leadership with a heartbeat round.
+///
+/// At most one round is in flight. The requests that arrive meanwhile are queued, and are all
+/// served by the next round, which is started when the current one finishes. A queued request
+/// can not join the round in flight, because the heartbeats of that round may have been sent
+/// before the request arrived.
+pub(crate) struct ReadIndexBatch<C>
+where C: RaftTypeConfig
+{
+    /// Whether a round is in flight.
+    pub(crate) in_flight: bool,
+
+    /// The requests to serve with the next round.
+    pub(crate) queued: Vec<ClientReadTx<C>>,
+
+    pub(crate) metrics: ReadIndexMetrics,
+}
+
+impl<C> Default for ReadIndexBatch<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            in_flight: false,
+            queued: Vec::new(),
+            metrics: ReadIndexMetrics::default(),
+        }
+    }
+}
+
+impl<C> ReadIndexBatch<C>
+where C: RaftTypeConfig
+{
+    /// Take the queued requests to serve with a new round, and mark the round as in flight.
+    ///
+    /// It returns an empty `Vec` if there is no queued request.
+    pub(crate) fn start_round(&mut self) -> Vec<ClientReadTx<C>> {
+        let batch = std::mem::take(&mut self.queued);
+
+        if !batch.is_empty() {
+            self.in_flight = true;
+            self.metrics.record_round(batch.len() as u64);
+        }
+
+        batch
+    }
+
+    /// Mark the round in flight as finished.
+    pub(crate) fn finish_round(&mut self) {
+        self.in_flight = false;
+    }
+}
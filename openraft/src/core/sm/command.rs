@@ -5,6 +5,8 @@ use crate::core::raft_msg::ResultSender;
 use crate::display_ext::DisplaySlice;
 use crate::error::Infallible;
 use crate::log_id::RaftLogId;
+use crate::storage::SnapshotBuildProgress;
+use crate::type_config::alias::LogIdOf;
 use crate::type_config::alias::SnapshotDataOf;
 use crate::RaftTypeConfig;
 use crate::Snapshot;
@@ -50,12 +52,12 @@ where C: RaftTypeConfig
     }
 
     pub(crate) fn build_snapshot() -> Self {
-        let payload = CommandPayload::BuildSnapshot;
+        let payload = CommandPayload::BuildSnapshot { progress: None };
         Command::new(payload)
     }
 
-    pub(crate) fn get_snapshot(tx: ResultSender<C, Option<Snapshot<C>>>) -> Self {
-        let payload = CommandPayload::GetSnapshot { tx };
+    pub(crate) fn get_snapshot(since: Option<LogIdOf<C>>, tx: ResultSender<C, Option<Snapshot<C>>>) -> Self {
+        let payload = CommandPayload::GetSnapshot { since, tx };
         Command::new(payload)
     }
 
@@ -69,6 +71,11 @@ where C: RaftTypeConfig
         Command::new(payload)
     }
 
+    pub(crate) fn purge_snapshots(keep: u64) -> Self {
+        let payload = CommandPayload::PurgeSnapshots { keep };
+        Command::new(payload)
+    }
+
     pub(crate) fn apply(entries: Vec<C::Entry>) -> Self {
         let payload = CommandPayload::Apply { entries };
         Command::new(payload)
@@ -87,10 +94,17 @@ pub(crate) enum CommandPayload<C>
 where C: RaftTypeConfig
 {
     /// Instruct the state machine to create a snapshot based on its most recent view.
-    BuildSnapshot,
+    ///
+    /// `progress` is filled in by RaftCore before sending the command to the worker.
+    BuildSnapshot {
+        progress: Option<SnapshotBuildProgress<C>>,
+    },
 
     /// Get the latest built snapshot.
+    ///
+    /// If `since` is given, try to build a delta snapshot since this log id first.
     GetSnapshot {
+        since: Option<LogIdOf<C>>,
         tx: ResultSender<C, Option<Snapshot<C>>>,
     },
 
@@ -102,6 +116,11 @@ where C: RaftTypeConfig
         snapshot: Snapshot<C>,
     },
 
+    /// Delete the stored snapshots except the latest `keep` ones.
+    PurgeSnapshots {
+        keep: u64,
+    },
+
     /// Apply the log entries to the state machine.
     Apply {
         entries: Vec<C::Entry>,
@@ -113,7 +132,7 @@ where C: RaftTypeConfig
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            CommandPayload::BuildSnapshot => write!(f, "BuildSnapshot"),
+            CommandPayload::BuildSnapshot { .. } => write!(f, "BuildSnapshot"),
             CommandPayload::GetSnapshot { .. } => write!(f, "GetSnapshot"),
             CommandPayload::InstallFullSnapshot { snapshot } => {
                 write!(f, "InstallFullSnapshot: meta: {:?}", snapshot.meta)
@@ -121,6 +140,7 @@ where C: RaftTypeConfig
             CommandPayload::BeginReceivingSnapshot { .. } => {
                 write!(f, "BeginReceivingSnapshot")
             }
+            CommandPayload::PurgeSnapshots { keep } => write!(f, "PurgeSnapshots: keep: {}", keep),
             CommandPayload::Apply { entries } => write!(f, "Apply: {}", DisplaySlice::<_>(entries)),
         }
     }
@@ -132,13 +152,14 @@ where C: RaftTypeConfig
 {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (CommandPayload::BuildSnapshot, CommandPayload::BuildSnapshot) => true,
+            (CommandPayload::BuildSnapshot { .. }, CommandPayload::BuildSnapshot { .. }) => true,
             (CommandPayload::GetSnapshot { .. }, CommandPayload::GetSnapshot { .. }) => true,
             (CommandPayload::BeginReceivingSnapshot { .. }, CommandPayload::BeginReceivingSnapshot { .. }) => true,
             (
                 CommandPayload::InstallFullSnapshot { snapshot: s1 },
                 CommandPayload::InstallFullSnapshot { snapshot: s2 },
             ) => s1.meta == s2.meta,
+            (CommandPayload::PurgeSnapshots { keep: k1 }, CommandPayload::PurgeSnapshots { keep: k2 }) => k1 == k2,
             (CommandPayload::Apply { entries: entries1 }, CommandPayload::Apply { entries: entries2 }) => {
                 // Entry may not be `Eq`, we just compare log id.
                 // This would be enough for testing.
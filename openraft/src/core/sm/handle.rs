@@ -3,30 +3,50 @@
 use tokio::sync::mpsc;
 
 use crate::core::sm;
+use crate::storage::RaftStateMachine;
 use crate::type_config::alias::AsyncRuntimeOf;
 use crate::type_config::alias::JoinHandleOf;
+use crate::type_config::alias::LogIdOf;
+use crate::type_config::alias::SnapshotDataOf;
 use crate::AsyncRuntime;
 use crate::RaftTypeConfig;
 use crate::Snapshot;
 
 /// State machine worker handle for sending command to it.
-pub(crate) struct Handle<C>
-where C: RaftTypeConfig
+pub(crate) struct Handle<C, SM>
+where
+    C: RaftTypeConfig,
+    SM: RaftStateMachine<C>,
 {
     pub(in crate::core::sm) cmd_tx: mpsc::UnboundedSender<sm::Command<C>>,
 
-    #[allow(dead_code)]
-    pub(in crate::core::sm) join_handle: JoinHandleOf<C, ()>,
+    /// The worker task, which returns the state machine when it quits.
+    pub(in crate::core::sm) join_handle: Option<JoinHandleOf<C, SM>>,
 }
 
-impl<C> Handle<C>
-where C: RaftTypeConfig
+impl<C, SM> Handle<C, SM>
+where
+    C: RaftTypeConfig,
+    SM: RaftStateMachine<C>,
 {
     pub(crate) fn send(&mut self, cmd: sm::Command<C>) -> Result<(), mpsc::error::SendError<sm::Command<C>>> {
         tracing::debug!("sending command to state machine worker: {:?}", cmd);
         self.cmd_tx.send(cmd)
     }
 
+    /// Stop the worker and take back the state machine.
+    ///
+    /// The worker quits after handling the commands already sent to it, or right after an error.
+    /// Returns `None` if the worker task panicked or the state machine is already taken.
+    pub(crate) async fn take_state_machine(&mut self) -> Option<SM> {
+        // Replace the sender with a closed one so that the worker sees the channel closed.
+        let (closed_tx, _) = mpsc::unbounded_channel();
+        self.cmd_tx = closed_tx;
+
+        let join_handle = self.join_handle.take()?;
+        join_handle.await.ok()
+    }
+
     /// Create a [`SnapshotReader`] to get the current snapshot from the state machine.
     pub(crate) fn new_snapshot_reader(&self) -> SnapshotReader<C> {
         SnapshotReader {
@@ -50,14 +70,15 @@ where C: RaftTypeConfig
 impl<C> SnapshotReader<C>
 where C: RaftTypeConfig
 {
-    /// Get a snapshot from the state machine.
+    /// Get a snapshot from the state machine, or a delta snapshot since `since` if the state
+    /// machine can build one.
     ///
     /// If the state machine worker has shutdown, it will return an error.
     /// If there is not snapshot available, it will return `Ok(None)`.
-    pub(crate) async fn get_snapshot(&self) -> Result<Option<Snapshot<C>>, &'static str> {
+    pub(crate) async fn get_snapshot(&self, since: Option<LogIdOf<C>>) -> Result<Option<Snapshot<C>>, &'static str> {
         let (tx, rx) = AsyncRuntimeOf::<C>::oneshot();
 
-        let cmd = sm::Command::get_snapshot(tx);
+        let cmd = sm::Command::get_snapshot(since, tx);
         tracing::debug!("SnapshotReader sending command to sm::Worker: {:?}", cmd);
 
         let Some(cmd_tx) = self.cmd_tx.upgrade() else {
@@ -81,4 +102,29 @@ where C: RaftTypeConfig
 
         Ok(snapshot)
     }
+
+    /// Get an empty snapshot data from the state machine, i.e., the one to receive a snapshot
+    /// into.
+    ///
+    /// If the state machine worker has shutdown, it will return an error.
+    pub(crate) async fn empty_snapshot_data(&self) -> Result<Box<SnapshotDataOf<C>>, &'static str> {
+        let (tx, rx) = AsyncRuntimeOf::<C>::oneshot();
+
+        let cmd = sm::Command::begin_receiving_snapshot(tx);
+
+        let Some(cmd_tx) = self.cmd_tx.upgrade() else {
+            tracing::info!("failed to upgrade cmd_tx, sm::Worker may have shutdown");
+            return Err("failed to upgrade cmd_tx, sm::Worker may have shutdown");
+        };
+
+        let _ = cmd_tx.send(cmd);
+
+        let got = rx.await.map_err(|_e| {
+            tracing::error!("failed to receive empty snapshot data, sm::Worker may have shutdown");
+            "failed to receive empty snapshot data, sm::Worker may have shutdown"
+        })?;
+
+        // Safe unwrap(): error is Infallible.
+        Ok(got.unwrap())
+    }
 }
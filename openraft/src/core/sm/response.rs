@@ -12,6 +12,9 @@ where C: RaftTypeConfig
     /// Build a snapshot, it returns result via the universal RaftCore response channel.
     BuildSnapshot(SnapshotMeta<C>),
 
+    /// Building a snapshot is cancelled by the application, no snapshot is built.
+    BuildSnapshotCancelled,
+
     /// When finishing installing a snapshot.
     ///
     /// It does not return any value to RaftCore.
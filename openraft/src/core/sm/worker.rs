@@ -1,3 +1,8 @@
+use std::pin::pin;
+use std::time::SystemTime;
+
+use futures::future::select;
+use futures::future::Either;
 use tokio::sync::mpsc;
 
 use crate::async_runtime::AsyncOneshotSendExt;
@@ -11,10 +16,13 @@ use crate::core::sm::CommandSeq;
 use crate::core::sm::Response;
 use crate::core::ApplyResult;
 use crate::core::ApplyingEntry;
+use crate::core::MembershipHistory;
 use crate::display_ext::DisplayOptionExt;
 use crate::entry::RaftPayload;
 use crate::storage::RaftStateMachine;
+use crate::storage::SnapshotBuildProgress;
 use crate::type_config::alias::JoinHandleOf;
+use crate::type_config::alias::LogIdOf;
 use crate::AsyncRuntime;
 use crate::RaftLogId;
 use crate::RaftSnapshotBuilder;
@@ -32,6 +40,18 @@ where
     cmd_rx: mpsc::UnboundedReceiver<Command<C>>,
 
     resp_tx: mpsc::UnboundedSender<Notify<C>>,
+
+    /// Records the membership configs applied to the state machine.
+    membership_history: MembershipHistory<C>,
+
+    /// A command received while collecting an apply batch, to be handled in the next iteration.
+    pending: Option<Command<C>>,
+
+    /// Whether to build snapshot in a blocking thread, see [`Config::offload_snapshot_build`].
+    ///
+    /// [`Config::offload_snapshot_build`]: crate::Config::offload_snapshot_build
+    #[cfg_attr(feature = "singlethreaded", allow(dead_code))]
+    offload_snapshot_build: bool,
 }
 
 impl<C, SM> Worker<C, SM>
@@ -40,22 +60,37 @@ where
     SM: RaftStateMachine<C>,
 {
     /// Spawn a new state machine worker, return a controlling handle.
-    pub(crate) fn spawn(state_machine: SM, resp_tx: mpsc::UnboundedSender<Notify<C>>) -> Handle<C> {
+    pub(crate) fn spawn(
+        state_machine: SM,
+        resp_tx: mpsc::UnboundedSender<Notify<C>>,
+        membership_history: MembershipHistory<C>,
+        offload_snapshot_build: bool,
+    ) -> Handle<C, SM> {
         let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
 
         let worker = Worker {
             state_machine,
             cmd_rx,
             resp_tx,
+            membership_history,
+            pending: None,
+            offload_snapshot_build,
         };
 
         let join_handle = worker.do_spawn();
 
-        Handle { cmd_tx, join_handle }
+        Handle {
+            cmd_tx,
+            join_handle: Some(join_handle),
+        }
     }
 
-    fn do_spawn(mut self) -> JoinHandleOf<C, ()> {
-        C::AsyncRuntime::spawn(async move {
+    /// Run the worker loop in a task, which returns the state machine when the loop quits.
+    ///
+    /// The loop quits when the command channel is closed, or when the state machine returns an
+    /// error, after which no more command is handled.
+    fn do_spawn(mut self) -> JoinHandleOf<C, SM> {
+        C::AsyncRuntime::spawn_named("state-machine-worker", async move {
             let res = self.worker_loop().await;
 
             if let Err(err) = res {
@@ -68,13 +103,18 @@ where
                     },
                 });
             }
+
+            self.state_machine
         })
     }
 
     #[tracing::instrument(level = "debug", skip_all)]
     async fn worker_loop(&mut self) -> Result<(), StorageError<C::NodeId>> {
         loop {
-            let cmd = self.cmd_rx.recv().await;
+            let cmd = match self.pending.take() {
+                Some(x) => Some(x),
+                None => self.cmd_rx.recv().await,
+            };
             let cmd = match cmd {
                 None => {
                     tracing::info!("{}: rx closed, state machine worker quit", func_name!());
@@ -86,29 +126,43 @@ where
             tracing::debug!("{}: received command: {:?}", func_name!(), cmd);
 
             match cmd.payload {
-                CommandPayload::BuildSnapshot => {
+                CommandPayload::BuildSnapshot { progress } => {
                     tracing::info!("{}: build snapshot", func_name!());
 
                     // It is a read operation and is spawned, and it responds in another task
-                    self.build_snapshot(cmd.seq, self.resp_tx.clone()).await;
+                    let progress = progress.unwrap_or_default();
+                    self.build_snapshot(cmd.seq, progress, self.resp_tx.clone()).await;
                 }
-                CommandPayload::GetSnapshot { tx } => {
+                CommandPayload::GetSnapshot { since, tx } => {
                     tracing::info!("{}: get snapshot", func_name!());
 
-                    self.get_snapshot(tx).await?;
+                    self.get_snapshot(since, tx).await?;
                     // GetSnapshot does not respond to RaftCore
                 }
                 CommandPayload::InstallFullSnapshot { snapshot } => {
                     tracing::info!("{}: install complete snapshot", func_name!());
 
                     let meta = snapshot.meta.clone();
-                    self.state_machine.install_snapshot(&meta, snapshot.snapshot).await?;
+                    if meta.is_delta() {
+                        self.state_machine.install_delta_snapshot(&meta, snapshot.snapshot).await?;
+                    } else {
+                        self.state_machine.install_snapshot(&meta, snapshot.snapshot).await?;
+                    }
 
                     tracing::info!("Done install complete snapshot, meta: {}", meta);
 
+                    if let Some(log_id) = meta.last_membership.log_id() {
+                        let membership = meta.last_membership.membership().clone();
+                        self.membership_history.record(*log_id, membership, SystemTime::now());
+                    }
+
                     let res = CommandResult::new(cmd.seq, Ok(Response::InstallSnapshot(Some(meta))));
                     let _ = self.resp_tx.send(Notify::sm(res));
                 }
+                CommandPayload::PurgeSnapshots { keep } => {
+                    self.purge_snapshots(keep).await?;
+                    // No response to RaftCore
+                }
                 CommandPayload::BeginReceivingSnapshot { tx } => {
                     tracing::info!("{}: BeginReceivingSnapshot", func_name!());
 
@@ -118,13 +172,45 @@ where
                     // No response to RaftCore
                 }
                 CommandPayload::Apply { entries } => {
-                    let resp = self.apply(entries).await?;
-                    let res = CommandResult::new(cmd.seq, Ok(Response::Apply(resp)));
-                    let _ = self.resp_tx.send(Notify::sm(res));
+                    self.apply_batch(cmd.seq, entries).await?;
                 }
             };
         }
     }
+
+    /// Apply the given entries and all the other entries already queued in one batch.
+    ///
+    /// The batch stops at the first queued command that is not an `Apply`, which will be handled
+    /// by the next iteration of the worker loop.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn apply_batch(&mut self, seq: CommandSeq, entries: Vec<C::Entry>) -> Result<(), StorageError<C::NodeId>> {
+        self.state_machine.begin_apply_batch().await?;
+
+        let mut results = vec![(seq, self.apply(entries).await?)];
+
+        while let Ok(cmd) = self.cmd_rx.try_recv() {
+            match cmd.payload {
+                CommandPayload::Apply { entries } => {
+                    tracing::debug!("{}: batch apply command: seq: {}", func_name!(), cmd.seq);
+                    results.push((cmd.seq, self.apply(entries).await?));
+                }
+                payload => {
+                    self.pending = Some(Command { seq: cmd.seq, payload });
+                    break;
+                }
+            }
+        }
+
+        let last_applied = results.last().map(|(_, resp)| resp.last_applied).unwrap();
+        self.state_machine.commit_apply_batch(last_applied).await?;
+
+        for (seq, resp) in results {
+            let res = CommandResult::new(seq, Ok(Response::Apply(resp)));
+            let _ = self.resp_tx.send(Notify::sm(res));
+        }
+
+        Ok(())
+    }
     #[tracing::instrument(level = "debug", skip_all)]
     async fn apply(&mut self, entries: Vec<C::Entry>) -> Result<ApplyResult<C>, StorageError<C::NodeId>> {
         // TODO: prepare response before apply,
@@ -144,10 +230,17 @@ where
 
         let n_entries = applying_entries.len();
 
-        let apply_results = self.state_machine.apply(entries).await?;
+        let apply_results = self.state_machine.try_apply(entries).await?;
 
         let n_replies = apply_results.len();
 
+        let applied_at = SystemTime::now();
+        for ent in applying_entries.iter() {
+            if let Some(membership) = &ent.membership {
+                self.membership_history.record(ent.log_id, membership.clone(), applied_at);
+            }
+        }
+
         debug_assert_eq!(
             n_entries, n_replies,
             "n_entries: {} should equal n_replies: {}",
@@ -173,29 +266,109 @@ where
     /// - hold a consistent view of the state machine that won't be affected by further writes such
     ///   as applying a log entry,
     /// - or it must be able to acquire a lock that prevents any write operations.
+    ///
+    /// The building is aborted when `progress` is cancelled, and
+    /// [`Response::BuildSnapshotCancelled`] is sent back instead.
+    ///
+    /// If `offload_snapshot_build` is enabled, the building is run in a blocking thread.
     #[tracing::instrument(level = "info", skip_all)]
-    async fn build_snapshot(&mut self, seq: CommandSeq, resp_tx: mpsc::UnboundedSender<Notify<C>>) {
-        // TODO: need to be abortable?
-        // use futures::future::abortable;
-        // let (fu, abort_handle) = abortable(async move { builder.build_snapshot().await });
-
+    async fn build_snapshot(
+        &mut self,
+        seq: CommandSeq,
+        progress: SnapshotBuildProgress<C>,
+        resp_tx: mpsc::UnboundedSender<Notify<C>>,
+    ) {
         tracing::info!("{}", func_name!());
 
-        let mut builder = self.state_machine.get_snapshot_builder().await;
+        let builder = self.state_machine.get_snapshot_builder().await;
+        let build = Self::run_snapshot_builder(builder, progress);
+
+        #[cfg(not(feature = "singlethreaded"))]
+        if self.offload_snapshot_build {
+            let _handle = C::AsyncRuntime::spawn_blocking(move || {
+                let res = futures::executor::block_on(build);
+                let _ = resp_tx.send(Notify::sm(CommandResult::new(seq, res)));
+            });
+            tracing::info!("{} returning; spawned building snapshot thread", func_name!());
+            return;
+        }
 
         let _handle = C::AsyncRuntime::spawn(async move {
-            let res = builder.build_snapshot().await;
-            let res = res.map(|snap| Response::BuildSnapshot(snap.meta));
-            let cmd_res = CommandResult::new(seq, res);
-            let _ = resp_tx.send(Notify::sm(cmd_res));
+            let res = build.await;
+            let _ = resp_tx.send(Notify::sm(CommandResult::new(seq, res)));
         });
         tracing::info!("{} returning; spawned building snapshot task", func_name!());
     }
 
+    /// Build a snapshot with `builder` until it finishes or `progress` is cancelled.
+    async fn run_snapshot_builder(
+        mut builder: SM::SnapshotBuilder,
+        progress: SnapshotBuildProgress<C>,
+    ) -> Result<Response<C>, StorageError<C::NodeId>> {
+        let cancelled = progress.cancelled();
+        let build = builder.build_snapshot_with_progress(progress.clone());
+
+        let res = match select(pin!(build), pin!(cancelled)).await {
+            Either::Left((res, _)) => res.map(Some),
+            Either::Right(((), _)) => {
+                tracing::info!("building snapshot is cancelled");
+                Ok(None)
+            }
+        };
+
+        // The builder may return an error when it stops because of cancellation.
+        let res = match res {
+            Err(e) if progress.is_cancelled() => {
+                tracing::info!(error = display(&e), "building snapshot is cancelled");
+                Ok(None)
+            }
+            _ => res,
+        };
+
+        res.map(|snap| match snap {
+            Some(snap) => Response::BuildSnapshot(snap.meta),
+            None => Response::BuildSnapshotCancelled,
+        })
+    }
+
+    /// Delete the stored snapshots except the latest `keep` ones.
+    #[tracing::instrument(level = "info", skip_all)]
+    async fn purge_snapshots(&mut self, keep: u64) -> Result<(), StorageError<C::NodeId>> {
+        let mut metas = self.state_machine.list_snapshots().await?;
+        metas.sort_by(|a, b| b.last_log_id.cmp(&a.last_log_id));
+
+        for meta in metas.iter().skip(keep as usize) {
+            tracing::info!("delete stale snapshot: {}", meta);
+            self.state_machine.delete_snapshot(meta).await?;
+        }
+        Ok(())
+    }
+
+    /// Get the current snapshot, or a delta snapshot since `since` if the snapshot builder can
+    /// build one.
     #[tracing::instrument(level = "info", skip_all)]
-    async fn get_snapshot(&mut self, tx: ResultSender<C, Option<Snapshot<C>>>) -> Result<(), StorageError<C::NodeId>> {
+    async fn get_snapshot(
+        &mut self,
+        since: Option<LogIdOf<C>>,
+        tx: ResultSender<C, Option<Snapshot<C>>>,
+    ) -> Result<(), StorageError<C::NodeId>> {
         tracing::info!("{}", func_name!());
 
+        if let Some(since) = since {
+            let mut builder = self.state_machine.get_snapshot_builder().await;
+            if let Some(mut delta) = builder.build_delta(since).await? {
+                if delta.meta.last_log_id > Some(since) {
+                    delta.meta.delta_base = Some(since);
+
+                    tracing::info!("sending back delta snapshot: meta: {}", delta.meta);
+                    let _ = tx.send(Ok(Some(delta)));
+                    return Ok(());
+                }
+
+                tracing::warn!("ignore delta snapshot not newer than {}: meta: {}", since, delta.meta);
+            }
+        }
+
         let snapshot = self.state_machine.get_current_snapshot().await?;
 
         tracing::info!(
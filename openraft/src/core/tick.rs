@@ -8,17 +8,18 @@ use std::time::Duration;
 
 use futures::future::Either;
 use tokio::sync::mpsc;
-use tokio::sync::oneshot;
 use tracing::Instrument;
 use tracing::Level;
 use tracing::Span;
 
+use crate::async_runtime::CancellationToken;
+use crate::async_runtime::Interval;
+use crate::async_runtime::MissedTickBehavior;
 use crate::core::notify::Notify;
 use crate::type_config::alias::AsyncRuntimeOf;
-use crate::type_config::alias::InstantOf;
+use crate::type_config::alias::CancellationTokenOf;
 use crate::type_config::alias::JoinHandleOf;
 use crate::AsyncRuntime;
-use crate::Instant;
 use crate::RaftTypeConfig;
 
 /// Emit RaftMsg::Tick event at regular `interval`.
@@ -37,7 +38,13 @@ pub(crate) struct TickHandle<C>
 where C: RaftTypeConfig
 {
     enabled: Arc<AtomicBool>,
-    shutdown: Mutex<Option<oneshot::Sender<()>>>,
+
+    /// Cancelled to stop the tick loop.
+    cancel: CancellationTokenOf<C>,
+
+    /// Whether the tick loop is spawned. It is not if the node is driven by `Raft::tick()`.
+    spawned: bool,
+
     join_handle: Mutex<Option<JoinHandleOf<C, ()>>>,
 }
 
@@ -46,53 +53,69 @@ where C: RaftTypeConfig
 {
     /// Signal the tick loop to stop, without waiting for it to stop.
     fn drop(&mut self) {
-        if self.shutdown.lock().unwrap().is_none() {
-            return;
-        }
-        let _ = self.shutdown();
+        self.cancel.cancel();
     }
 }
 
 impl<C> Tick<C>
 where C: RaftTypeConfig
 {
-    pub(crate) fn spawn(interval: Duration, tx: mpsc::UnboundedSender<Notify<C>>, enabled: bool) -> TickHandle<C> {
-        let enabled = Arc::new(AtomicBool::from(enabled));
+    /// Spawn the tick loop, which quits when `cancel` is cancelled.
+    ///
+    /// If `enabled` is `false`, the node is driven by `Raft::tick()` and no tick loop is spawned.
+    pub(crate) fn spawn(
+        interval: Duration,
+        tx: mpsc::UnboundedSender<Notify<C>>,
+        enabled: bool,
+        cancel: CancellationTokenOf<C>,
+    ) -> TickHandle<C> {
+        if !enabled {
+            return TickHandle {
+                enabled: Arc::new(AtomicBool::from(false)),
+                cancel,
+                spawned: false,
+                join_handle: Mutex::new(None),
+            };
+        }
+
+        let enabled = Arc::new(AtomicBool::from(true));
         let this = Self {
             interval,
             enabled: enabled.clone(),
             tx,
         };
 
-        let (shutdown, shutdown_rx) = oneshot::channel();
-
-        let shutdown = Mutex::new(Some(shutdown));
-
-        let join_handle = AsyncRuntimeOf::<C>::spawn(this.tick_loop(shutdown_rx).instrument(tracing::span!(
-            parent: &Span::current(),
-            Level::DEBUG,
-            "tick"
-        )));
+        let join_handle = AsyncRuntimeOf::<C>::spawn_named(
+            "tick",
+            this.tick_loop(cancel.clone())
+                .instrument(tracing::span!(parent: &Span::current(), Level::DEBUG, "tick")),
+        );
 
         TickHandle {
             enabled,
-            shutdown,
+            cancel,
+            spawned: true,
             join_handle: Mutex::new(Some(join_handle)),
         }
     }
 
-    pub(crate) async fn tick_loop(self, mut cancel_rx: oneshot::Receiver<()>) {
+    pub(crate) async fn tick_loop(self, cancel: CancellationTokenOf<C>) {
         let mut i = 0;
 
-        let mut cancel = std::pin::pin!(cancel_rx);
+        let mut cancel = std::pin::pin!(cancel.cancelled());
+
+        // A tick delayed by a busy executor does not cause a burst of ticks to catch up.
+        let mut interval = AsyncRuntimeOf::<C>::interval_at(
+            C::AsyncRuntime::now() + self.interval,
+            self.interval,
+            MissedTickBehavior::Delay,
+        );
 
         loop {
-            let at = InstantOf::<C>::now() + self.interval;
-            let mut sleep_fut = AsyncRuntimeOf::<C>::sleep_until(at);
-            let sleep_fut = std::pin::pin!(sleep_fut);
+            let tick_fut = std::pin::pin!(interval.tick());
             let cancel_fut = cancel.as_mut();
 
-            match futures::future::select(cancel_fut, sleep_fut).await {
+            match futures::future::select(cancel_fut, tick_fut).await {
                 Either::Left((_canceled, _)) => {
                     tracing::info!("TickLoop received cancel signal, quit");
                     return;
@@ -122,7 +145,17 @@ where C: RaftTypeConfig
 impl<C> TickHandle<C>
 where C: RaftTypeConfig
 {
+    /// Enable or disable the tick loop.
+    ///
+    /// It does nothing if the tick loop is not spawned, i.e., the node is driven by `Raft::tick()`.
     pub(crate) fn enable(&self, enabled: bool) {
+        if !self.spawned {
+            tracing::warn!(
+                "tick loop is not spawned because Config::enable_tick is false, ignore enable({})",
+                enabled
+            );
+            return;
+        }
         self.enabled.store(enabled, Ordering::Relaxed);
     }
 
@@ -130,24 +163,21 @@ where C: RaftTypeConfig
     ///
     /// If it is called twice, the second call will return None.
     pub(crate) fn shutdown(&self) -> Option<JoinHandleOf<C, ()>> {
-        {
-            let shutdown = {
-                let mut x = self.shutdown.lock().unwrap();
-                x.take()
-            };
+        self.cancel.cancel();
+        tracing::info!("Timer shutdown signal sent");
 
-            if let Some(shutdown) = shutdown {
-                let send_res = shutdown.send(());
-                tracing::info!("Timer shutdown signal sent: {send_res:?}");
-            } else {
-                tracing::warn!("Double call to Raft::shutdown()");
-            }
+        if !self.spawned {
+            return None;
         }
 
         let jh = {
             let mut x = self.join_handle.lock().unwrap();
             x.take()
         };
+
+        if jh.is_none() {
+            tracing::warn!("Double call to Raft::shutdown()");
+        }
         jh
     }
 }
@@ -160,6 +190,7 @@ mod tests {
 
     use crate::core::Tick;
     use crate::type_config::alias::AsyncRuntimeOf;
+    use crate::type_config::alias::CancellationTokenOf;
     use crate::AsyncRuntime;
     use crate::RaftTypeConfig;
     use crate::TokioRuntime;
@@ -170,12 +201,16 @@ mod tests {
     impl RaftTypeConfig for TickUTConfig {
         type D = ();
         type R = ();
+        type AppError = crate::error::Infallible;
         type NodeId = u64;
         type Node = ();
         type Entry = crate::Entry<TickUTConfig>;
         type SnapshotData = Cursor<Vec<u8>>;
         type AsyncRuntime = TokioRuntime;
         type Responder = crate::impls::OneshotResponder<Self>;
+        type PayloadCodec = crate::network::NoCompression;
+        type StorageCipher = crate::storage::NoEncryption;
+        type SnapshotTrigger = crate::NoSnapshotTrigger;
     }
 
     // AsyncRuntime::spawn is `spawn_local` with singlethreaded enabled.
@@ -185,7 +220,12 @@ mod tests {
     #[tokio::test]
     async fn test_shutdown() -> anyhow::Result<()> {
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
-        let th = Tick::<TickUTConfig>::spawn(Duration::from_millis(100), tx, true);
+        let th = Tick::<TickUTConfig>::spawn(
+            Duration::from_millis(100),
+            tx,
+            true,
+            CancellationTokenOf::<TickUTConfig>::new(),
+        );
 
         AsyncRuntimeOf::<TickUTConfig>::sleep(Duration::from_millis(500)).await;
         let _ = th.shutdown().unwrap().await;
@@ -204,4 +244,38 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_not_spawned_if_disabled() -> anyhow::Result<()> {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let th = Tick::<TickUTConfig>::spawn(
+            Duration::from_millis(10),
+            tx,
+            false,
+            CancellationTokenOf::<TickUTConfig>::new(),
+        );
+
+        th.enable(true);
+
+        // No tick loop holds the sender.
+        assert!(rx.recv().await.is_none());
+        assert!(th.shutdown().is_none());
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "singlethreaded"))]
+    #[tokio::test]
+    async fn test_cancel_parent_token() -> anyhow::Result<()> {
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let parent = CancellationTokenOf::<TickUTConfig>::new();
+        let th = Tick::<TickUTConfig>::spawn(Duration::from_millis(100), tx, true, parent.child_token());
+
+        parent.cancel();
+
+        let join_handle = th.join_handle.lock().unwrap().take().unwrap();
+        AsyncRuntimeOf::<TickUTConfig>::timeout(Duration::from_millis(500), join_handle).await??;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use crate::metrics::WriteBatchMetrics;
+use crate::type_config::alias::InstantOf;
+use crate::type_config::alias::ResponderOf;
+use crate::RaftTypeConfig;
+
+/// Coalesces the client writes received by a leader into one batch of log entries.
+///
+/// The first write opens a batch that is closed when the coalescing window expires, when it is
+/// full, or when a request other than a client write has to be handled, so that the writes are
+/// not reordered with the other requests.
+pub(crate) struct WriteBatch<C>
+where C: RaftTypeConfig
+{
+    /// The entries to append, the channels to respond to and the deadlines of the writes, in the
+    /// order they are received.
+    pub(crate) entries: Vec<(C::Entry, ResponderOf<C>, Option<InstantOf<C>>)>,
+
+    /// The time the open batch has to be closed, or `None` if no batch is open.
+    pub(crate) deadline: Option<InstantOf<C>>,
+
+    pub(crate) metrics: WriteBatchMetrics,
+}
+
+impl<C> Default for WriteBatch<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            deadline: None,
+            metrics: WriteBatchMetrics::default(),
+        }
+    }
+}
+
+impl<C> WriteBatch<C>
+where C: RaftTypeConfig
+{
+    /// Add a write to the batch, opening a new one that is closed after `window` if none is open.
+    pub(crate) fn push(
+        &mut self,
+        entry: C::Entry,
+        tx: ResponderOf<C>,
+        write_deadline: Option<InstantOf<C>>,
+        now: InstantOf<C>,
+        window: Duration,
+    ) {
+        self.deadline.get_or_insert(now + window);
+        self.entries.push((entry, tx, write_deadline));
+    }
+
+    /// Close the open batch and take its writes.
+    ///
+    /// It returns an empty `Vec` if there is no open batch.
+    pub(crate) fn take(&mut self) -> Vec<(C::Entry, ResponderOf<C>, Option<InstantOf<C>>)> {
+        self.deadline = None;
+        let batch = std::mem::take(&mut self.entries);
+
+        if !batch.is_empty() {
+            self.metrics.record_batch(batch.len() as u64);
+        }
+
+        batch
+    }
+}
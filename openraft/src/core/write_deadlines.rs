@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use crate::type_config::alias::InstantOf;
+use crate::RaftTypeConfig;
+
+/// The deadlines of the client writes that are waiting to be committed, by log index.
+pub(crate) struct WriteDeadlines<C>
+where C: RaftTypeConfig
+{
+    by_index: BTreeMap<u64, InstantOf<C>>,
+    by_time: BTreeSet<(InstantOf<C>, u64)>,
+}
+
+impl<C> Default for WriteDeadlines<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            by_index: BTreeMap::new(),
+            by_time: BTreeSet::new(),
+        }
+    }
+}
+
+impl<C> WriteDeadlines<C>
+where C: RaftTypeConfig
+{
+    pub(crate) fn insert(&mut self, index: u64, deadline: InstantOf<C>) {
+        self.remove(index);
+        self.by_index.insert(index, deadline);
+        self.by_time.insert((deadline, index));
+    }
+
+    pub(crate) fn remove(&mut self, index: u64) {
+        if let Some(deadline) = self.by_index.remove(&index) {
+            self.by_time.remove(&(deadline, index));
+        }
+    }
+
+    /// Remove the deadlines of the writes at `index` and after.
+    pub(crate) fn truncate(&mut self, index: u64) {
+        for (i, deadline) in self.by_index.split_off(&index) {
+            self.by_time.remove(&(deadline, i));
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.by_index.clear();
+        self.by_time.clear();
+    }
+
+    /// The earliest deadline, if any.
+    pub(crate) fn first(&self) -> Option<InstantOf<C>> {
+        self.by_time.first().map(|(deadline, _)| *deadline)
+    }
+
+    /// Remove and return the indexes of the writes whose deadline is not after `now`.
+    pub(crate) fn pop_expired(&mut self, now: InstantOf<C>) -> Vec<u64> {
+        let mut expired = vec![];
+
+        while let Some(&(deadline, index)) = self.by_time.first() {
+            if deadline > now {
+                break;
+            }
+            self.by_time.pop_first();
+            self.by_index.remove(&index);
+            expired.push(index);
+        }
+
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::write_deadlines::WriteDeadlines;
+    use crate::engine::testing::UTConfig;
+    use crate::TokioInstant;
+
+    #[test]
+    fn test_write_deadlines() {
+        let d = Duration::from_millis(100);
+        let t0 = TokioInstant::now();
+
+        let mut w = WriteDeadlines::<UTConfig>::default();
+        assert_eq!(None, w.first());
+
+        w.insert(3, t0 + d * 2);
+        w.insert(4, t0 + d);
+        w.insert(5, t0 + d * 3);
+        w.insert(6, t0 + d);
+        assert_eq!(Some(t0 + d), w.first());
+
+        w.remove(4);
+        assert_eq!(Vec::<u64>::new(), w.pop_expired(t0));
+        assert_eq!(vec![6, 3], w.pop_expired(t0 + d * 2));
+
+        w.truncate(5);
+        assert_eq!(None, w.first());
+    }
+}
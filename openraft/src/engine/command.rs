@@ -11,6 +11,7 @@ use crate::progress::Inflight;
 use crate::raft::AppendEntriesResponse;
 use crate::raft::InstallSnapshotResponse;
 use crate::raft::SnapshotResponse;
+use crate::raft::TransferLeaderRequest;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::type_config::alias::OneshotSenderOf;
@@ -52,6 +53,10 @@ where C: RaftTypeConfig
     /// Replicate the committed log id to other nodes
     ReplicateCommitted { committed: Option<LogId<C::NodeId>> },
 
+    /// Send a heartbeat with the committed log id to every replication target, by the heartbeat
+    /// worker of each target.
+    BroadcastHeartbeat { committed: Option<LogId<C::NodeId>> },
+
     /// Commit log entries that are already persisted in the store, upto `upto`, inclusive.
     ///
     /// To `commit` logs, [`RaftLogStorage::save_committed()`] is called. And then committed logs
@@ -91,6 +96,12 @@ where C: RaftTypeConfig
     /// Send vote to all other members
     SendVote { vote_req: VoteRequest<C> },
 
+    /// Send a leadership transfer request to every other voter.
+    BroadcastTransferLeader { req: TransferLeaderRequest<C> },
+
+    /// Send heartbeat to every voter to confirm the leadership for the queued read requests.
+    ConfirmLeadership,
+
     /// Purge log from the beginning to `upto`, inclusive.
     PurgeLog { upto: LogId<C::NodeId> },
 
@@ -133,11 +144,14 @@ where
             (Command::QuitLeader,                              Command::QuitLeader)                                                            => true,
             (Command::AppendInputEntries { vote, entries },    Command::AppendInputEntries { vote: vb, entries: b }, )                         => vote == vb && entries == b,
             (Command::ReplicateCommitted { committed },        Command::ReplicateCommitted { committed: b }, )                                 => committed == b,
+            (Command::BroadcastHeartbeat { committed },        Command::BroadcastHeartbeat { committed: b }, )                                 => committed == b,
             (Command::Commit { seq, already_committed, upto, }, Command::Commit { seq: b_seq, already_committed: b_committed, upto: b_upto, }, ) => seq == b_seq && already_committed == b_committed && upto == b_upto,
             (Command::Replicate { target, req },               Command::Replicate { target: b_target, req: other_req, }, )                     => target == b_target && req == other_req,
             (Command::RebuildReplicationStreams { targets },   Command::RebuildReplicationStreams { targets: b }, )                            => targets == b,
             (Command::SaveVote { vote },                       Command::SaveVote { vote: b })                                                  => vote == b,
             (Command::SendVote { vote_req },                   Command::SendVote { vote_req: b }, )                                            => vote_req == b,
+            (Command::BroadcastTransferLeader { req },         Command::BroadcastTransferLeader { req: b })                                    => req == b,
+            (Command::ConfirmLeadership,                       Command::ConfirmLeadership)                                                     => true,
             (Command::PurgeLog { upto },                       Command::PurgeLog { upto: b })                                                  => upto == b,
             (Command::DeleteConflictLog { since },             Command::DeleteConflictLog { since: b }, )                                      => since == b,
             (Command::Respond { when, resp: send },            Command::Respond { when: b_when, resp: b })                                     => send == b && when == b_when,
@@ -165,8 +179,11 @@ where C: RaftTypeConfig
             Command::DeleteConflictLog { .. }         => CommandKind::Log,
 
             Command::ReplicateCommitted { .. }        => CommandKind::Network,
+            Command::BroadcastHeartbeat { .. }        => CommandKind::Network,
             Command::Replicate { .. }                 => CommandKind::Network,
             Command::SendVote { .. }                  => CommandKind::Network,
+            Command::BroadcastTransferLeader { .. }   => CommandKind::Network,
+            Command::ConfirmLeadership                => CommandKind::Network,
 
             Command::StateMachine { .. }              => CommandKind::StateMachine,
             // Apply is firstly handled by RaftCore, then forwarded to state machine worker.
@@ -184,12 +201,15 @@ where C: RaftTypeConfig
             Command::QuitLeader                       => None,
             Command::AppendInputEntries { .. }        => None,
             Command::ReplicateCommitted { .. }        => None,
+            Command::BroadcastHeartbeat { .. }        => None,
             // TODO: Apply also write `committed` to log-store, which should be run in CommandKind::Log
             Command::Commit { .. }                     => None,
             Command::Replicate { .. }                 => None,
             Command::RebuildReplicationStreams { .. } => None,
             Command::SaveVote { .. }                  => None,
             Command::SendVote { .. }                  => None,
+            Command::BroadcastTransferLeader { .. }   => None,
+            Command::ConfirmLeadership                => None,
             Command::PurgeLog { .. }                  => None,
             Command::DeleteConflictLog { .. }         => None,
             Command::Respond { when, .. }             => when.as_ref(),
@@ -236,6 +256,8 @@ where C: RaftTypeConfig
     InstallSnapshot(ValueSender<C, Result<InstallSnapshotResponse<C>, InstallSnapshotError>>),
     InstallFullSnapshot(ValueSender<C, Result<SnapshotResponse<C>, Infallible>>),
     Initialize(ValueSender<C, Result<(), InitializeError<C>>>),
+    #[cfg(feature = "unsafe-force-reset-membership")]
+    ForceResetMembership(ValueSender<C, Result<(), crate::error::NotInMembers<C>>>),
 }
 
 impl<C> Respond<C>
@@ -257,6 +279,8 @@ where C: RaftTypeConfig
             Respond::InstallSnapshot(x) => x.send(),
             Respond::InstallFullSnapshot(x) => x.send(),
             Respond::Initialize(x) => x.send(),
+            #[cfg(feature = "unsafe-force-reset-membership")]
+            Respond::ForceResetMembership(x) => x.send(),
         }
     }
 }
@@ -3,6 +3,7 @@ use std::time::Duration;
 use crate::engine::time_state;
 use crate::type_config::alias::AsyncRuntimeOf;
 use crate::Config;
+use crate::LogRetentionPolicy;
 use crate::RaftTypeConfig;
 use crate::SnapshotPolicy;
 
@@ -16,8 +17,8 @@ pub(crate) struct EngineConfig<C: RaftTypeConfig> {
     /// The snapshot policy to use for a Raft node.
     pub(crate) snapshot_policy: SnapshotPolicy,
 
-    /// The maximum number of applied logs to keep before purging.
-    pub(crate) max_in_snapshot_log_to_keep: u64,
+    /// The policy to decide which logs included in the snapshot to keep.
+    pub(crate) log_retention_policy: LogRetentionPolicy,
 
     /// The minimal number of applied logs to purge in a batch.
     pub(crate) purge_batch_size: u64,
@@ -25,6 +26,27 @@ pub(crate) struct EngineConfig<C: RaftTypeConfig> {
     /// The maximum number of entries per payload allowed to be transmitted during replication
     pub(crate) max_payload_entries: u64,
 
+    /// The maximum number of AppendEntries RPCs in flight to a target.
+    pub(crate) max_inflight_append_entries: u64,
+
+    /// Whether to run a pre-vote round before starting an election.
+    pub(crate) enable_pre_vote: bool,
+
+    /// Whether an entry can be committed by a quorum that does not include the leader.
+    pub(crate) commit_without_local_flush: bool,
+
+    /// Whether a newly elected leader appends a blank log entry.
+    pub(crate) leader_blank_entry: bool,
+
+    /// Whether to reject vote requests within the leader lease.
+    pub(crate) reject_vote_within_lease: bool,
+
+    /// Whether to grant at most one vote per term.
+    pub(crate) one_vote_per_term: bool,
+
+    /// The zone to keep the leader in.
+    pub(crate) preferred_leader_zone: Option<String>,
+
     pub(crate) timer_config: time_state::Config,
 }
 
@@ -36,13 +58,20 @@ where C: RaftTypeConfig
         Self {
             id,
             snapshot_policy: config.snapshot_policy.clone(),
-            max_in_snapshot_log_to_keep: config.max_in_snapshot_log_to_keep,
+            log_retention_policy: config.log_retention_policy.clone(),
             purge_batch_size: config.purge_batch_size,
             max_payload_entries: config.max_payload_entries,
+            max_inflight_append_entries: config.max_inflight_append_entries,
+            enable_pre_vote: config.enable_pre_vote,
+            commit_without_local_flush: config.commit_without_local_flush,
+            leader_blank_entry: config.leader_blank_entry,
+            reject_vote_within_lease: config.reject_vote_within_lease,
+            one_vote_per_term: config.one_vote_per_term,
+            preferred_leader_zone: config.preferred_leader_zone().map(|z| z.to_string()),
             timer_config: time_state::Config {
                 election_timeout,
                 smaller_log_timeout: Duration::from_millis(config.election_timeout_max * 2),
-                leader_lease: Duration::from_millis(config.election_timeout_max),
+                leader_lease: config.leader_lease(),
             },
         }
     }
@@ -52,10 +81,23 @@ where C: RaftTypeConfig
         Self {
             id,
             snapshot_policy: SnapshotPolicy::LogsSinceLast(5000),
-            max_in_snapshot_log_to_keep: 1000,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(1000),
             purge_batch_size: 256,
             max_payload_entries: 300,
+            max_inflight_append_entries: 1,
+            enable_pre_vote: false,
+            commit_without_local_flush: false,
+            leader_blank_entry: true,
+            reject_vote_within_lease: true,
+            one_vote_per_term: false,
+            preferred_leader_zone: None,
             timer_config: time_state::Config::default(),
         }
     }
+
+    /// The maximum number of entries to replicate to a target in one request to the replication
+    /// stream, which sends them in up to `max_inflight_append_entries` RPCs in flight.
+    pub(crate) fn max_inflight_entries(&self) -> u64 {
+        self.max_payload_entries.saturating_mul(self.max_inflight_append_entries)
+    }
 }
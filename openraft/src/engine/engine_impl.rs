@@ -13,7 +13,6 @@ use crate::engine::handler::following_handler::FollowingHandler;
 use crate::engine::handler::leader_handler::LeaderHandler;
 use crate::engine::handler::log_handler::LogHandler;
 use crate::engine::handler::replication_handler::ReplicationHandler;
-use crate::engine::handler::replication_handler::SendNone;
 use crate::engine::handler::server_state_handler::ServerStateHandler;
 use crate::engine::handler::snapshot_handler::SnapshotHandler;
 use crate::engine::handler::vote_handler::VoteHandler;
@@ -29,17 +28,19 @@ use crate::error::NotAllowed;
 use crate::error::NotInMembers;
 use crate::error::RejectAppendEntries;
 use crate::internal_server_state::InternalServerState;
+use crate::internal_server_state::LeaderQuorumSet;
+use crate::leader::voting::Voting;
 use crate::raft::responder::Responder;
 use crate::raft::AppendEntriesResponse;
 use crate::raft::SnapshotResponse;
+use crate::raft::TransferLeaderRequest;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::raft_state::LogStateReader;
 use crate::raft_state::RaftState;
-use crate::type_config::alias::InstantOf;
 use crate::type_config::alias::ResponderOf;
 use crate::type_config::alias::SnapshotDataOf;
-use crate::Instant;
+use crate::AsyncRuntime;
 use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::Membership;
@@ -74,6 +75,20 @@ where C: RaftTypeConfig
     /// should be greater.
     pub(crate) seen_greater_log: bool,
 
+    /// The pre-vote round in progress, if [`Config::enable_pre_vote`] is set.
+    ///
+    /// It does not change the vote of this node. A real election starts only when a quorum
+    /// grants it.
+    ///
+    /// [`Config::enable_pre_vote`]: crate::Config::enable_pre_vote
+    pub(crate) pre_voting: Option<Voting<C, LeaderQuorumSet<C::NodeId>>>,
+
+    /// The leader vote that is handing over its leadership, and the node to become the new leader.
+    ///
+    /// While the vote of this node is still this leader vote, the vote of the new leader is
+    /// granted even if the leader lease has not yet expired.
+    pub(crate) transferring_to: Option<(Vote<C::NodeId>, C::NodeId)>,
+
     /// The internal server state used by Engine.
     pub(crate) internal_server_state: InternalServerState<C>,
 
@@ -89,6 +104,8 @@ where C: RaftTypeConfig
             config,
             state: Valid::new(init_state),
             seen_greater_log: false,
+            pre_voting: None,
+            transferring_to: None,
             internal_server_state: InternalServerState::default(),
             output: EngineOutput::new(4096),
         }
@@ -123,7 +140,7 @@ where C: RaftTypeConfig
             // Restore the progress about the local log
             rh.update_local_progress(rh.state.last_log_id().copied());
 
-            rh.initiate_replication(SendNone::False);
+            rh.initiate_replication();
 
             return;
         }
@@ -173,9 +190,79 @@ where C: RaftTypeConfig
         Ok(())
     }
 
+    /// Replace the membership with the one in `entry` locally, without consensus.
+    ///
+    /// The entry is appended with a new term of this node, so that it conflicts with no log entry
+    /// on other nodes. Then this node starts an election with the new membership, and the new
+    /// membership is committed by a quorum of it.
+    #[cfg(feature = "unsafe-force-reset-membership")]
+    pub(crate) fn force_reset_membership(&mut self, mut entry: C::Entry) -> Result<(), NotInMembers<C>> {
+        let m = entry.get_membership().expect("the log entry for resetting has to be membership log");
+        self.check_members_contain_me(m)?;
+
+        let vote = Vote::new(self.state.vote_ref().leader_id().term + 1, self.config.id);
+        let log_id = LogId::new(
+            crate::CommittedLeaderId::new(vote.leader_id().term, self.config.id),
+            self.state.last_log_id().next_index(),
+        );
+
+        tracing::warn!(
+            vote = display(&vote),
+            log_id = display(&log_id),
+            "force reset membership: {}",
+            m
+        );
+
+        self.state.vote.update(C::AsyncRuntime::now(), vote);
+        self.output.push_command(Command::SaveVote { vote });
+
+        // Leave the leading state, the entry is appended as a local follower write.
+        self.internal_server_state = InternalServerState::Following;
+
+        entry.set_log_id(&log_id);
+        self.following_handler().do_append_entries(vec![entry], 0);
+
+        self.elect();
+
+        Ok(())
+    }
+
+    /// Start a pre-vote round if it is enabled, otherwise start to elect at once.
+    ///
+    /// A pre-vote request carries the vote this node would use in the next election, but neither
+    /// this node nor the receivers save it. The real election is started by
+    /// [`Self::handle_pre_vote_resp()`] when a quorum grants it.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn pre_elect(&mut self) {
+        if !self.config.enable_pre_vote {
+            self.elect();
+            return;
+        }
+
+        let v = Vote::new(self.state.vote_ref().leader_id().term + 1, self.config.id);
+        tracing::info!(vote = display(&v), "{}", func_name!());
+
+        let quorum_set = self.state.membership_state.effective().membership().to_quorum_set();
+        let last_log_id = self.state.last_log_id().copied();
+        let mut voting = Voting::new(C::AsyncRuntime::now(), v, last_log_id, quorum_set);
+
+        if voting.grant_by(&self.config.id) {
+            self.elect();
+            return;
+        }
+
+        self.pre_voting = Some(voting);
+
+        self.output.push_command(Command::SendVote {
+            vote_req: VoteRequest::new_pre_vote(v, last_log_id),
+        });
+    }
+
     /// Start to elect this node as leader
     #[tracing::instrument(level = "debug", skip(self))]
     pub(crate) fn elect(&mut self) {
+        self.pre_voting = None;
+
         let v = Vote::new(self.state.vote_ref().leader_id().term + 1, self.config.id);
         tracing::info!(vote = display(&v), "{}", func_name!());
 
@@ -188,7 +275,7 @@ where C: RaftTypeConfig
 
         // Safe unwrap(): leading state is just created
         let leading = self.internal_server_state.leading_mut().unwrap();
-        let voting = leading.initialize_voting(self.state.last_log_id().copied(), InstantOf::<C>::now());
+        let voting = leading.initialize_voting(self.state.last_log_id().copied(), C::AsyncRuntime::now());
 
         let quorum_granted = voting.grant_by(&self.config.id);
 
@@ -235,7 +322,7 @@ where C: RaftTypeConfig
 
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) fn handle_vote_req(&mut self, req: VoteRequest<C>) -> VoteResponse<C> {
-        let now = InstantOf::<C>::now();
+        let now = C::AsyncRuntime::now();
         let lease = self.config.timer_config.leader_lease;
         let vote = self.state.vote_ref();
 
@@ -257,7 +344,11 @@ where C: RaftTypeConfig
             vote_utime + lease - now
         );
 
-        if vote.is_committed() {
+        // The leader hands over its leadership to the candidate: ignore the lease.
+        let transferring_to = self.transferring_to.filter(|(v, _)| v == vote).map(|(_, to)| to);
+        let is_transfer_target = transferring_to.is_some() && transferring_to == req.vote.leader_id().voted_for();
+
+        if self.config.reject_vote_within_lease && vote.is_committed() && !is_transfer_target {
             // Current leader lease has not yet expired, reject voting request
             if now <= vote_utime + lease {
                 tracing::info!(
@@ -276,7 +367,30 @@ where C: RaftTypeConfig
             }
         }
 
-        // The first step is to check log. If the candidate has less log, nothing needs to be done.
+        // A node that has voted for another candidate in the same term keeps its vote.
+        if self.config.one_vote_per_term {
+            let my_leader_id = vote.leader_id();
+            let req_leader_id = req.vote.leader_id();
+
+            if my_leader_id.get_term() == req_leader_id.get_term()
+                && my_leader_id.voted_for().is_some()
+                && my_leader_id.voted_for() != req_leader_id.voted_for()
+            {
+                tracing::info!(
+                    "reject vote-request: already voted for {:?} in term {}",
+                    my_leader_id.voted_for(),
+                    my_leader_id.get_term()
+                );
+
+                return VoteResponse {
+                    vote: *self.state.vote_ref(),
+                    vote_granted: false,
+                    last_log_id: self.state.last_log_id().copied(),
+                };
+            }
+        }
+
+        // Check log. If the candidate has less log, nothing needs to be done.
 
         if req.last_log_id.as_ref() >= self.state.last_log_id() {
             // Ok
@@ -297,6 +411,19 @@ where C: RaftTypeConfig
             };
         }
 
+        // A pre-vote is granted if the vote would be granted, but the local vote is left intact.
+        if req.pre_vote {
+            let vote_granted = &req.vote > self.state.vote_ref();
+
+            tracing::info!(req = display(&req), vote_granted, "handle pre-vote request result");
+
+            return VoteResponse {
+                vote: *self.state.vote_ref(),
+                vote_granted,
+                last_log_id: self.state.last_log_id().copied(),
+            };
+        }
+
         // Then check vote just as it does for every incoming event.
 
         let res = self.vote_handler().update_vote(&req.vote);
@@ -334,6 +461,13 @@ where C: RaftTypeConfig
             return;
         };
 
+        // Only the first response from a node counts. A hedged vote request may be responded
+        // more than once.
+        if !voting.record_response(target) {
+            tracing::debug!(target = display(target), "ignore duplicate vote response");
+            return;
+        }
+
         if &resp.vote < self.state.vote_ref() {
             debug_assert!(!resp.vote_granted);
         }
@@ -368,32 +502,206 @@ where C: RaftTypeConfig
         }
     }
 
+    /// Handle the response to a pre-vote request sent with `sender_vote`.
+    ///
+    /// The real election is started when a quorum grants the pre-vote.
+    #[tracing::instrument(level = "debug", skip(self, resp))]
+    pub(crate) fn handle_pre_vote_resp(
+        &mut self,
+        target: C::NodeId,
+        sender_vote: &Vote<C::NodeId>,
+        resp: VoteResponse<C>,
+    ) {
+        tracing::info!(
+            resp = display(&resp),
+            target = display(target),
+            my_vote = display(self.state.vote_ref()),
+            "{}",
+            func_name!()
+        );
+
+        let Some(voting) = self.pre_voting.as_mut() else {
+            tracing::debug!("no pre-vote in progress, ignore response");
+            return;
+        };
+
+        if voting.vote_ref() != sender_vote {
+            tracing::debug!(
+                pre_vote = display(voting.vote_ref()),
+                sender_vote = display(sender_vote),
+                "ignore response to a previous pre-vote"
+            );
+            return;
+        }
+
+        if !voting.record_response(target) {
+            tracing::debug!(target = display(target), "ignore duplicate pre-vote response");
+            return;
+        }
+
+        if resp.vote_granted {
+            if voting.grant_by(&target) {
+                // The vote is changed or renewed by a leader after the pre-vote started.
+                // There is no need to elect any more.
+                let started = Some(*voting.starting_time());
+                if self.state.vote_last_modified() > started {
+                    tracing::info!("vote is updated during pre-vote, do not elect");
+                    self.pre_voting = None;
+                    return;
+                }
+
+                tracing::info!("a quorum granted my pre-vote, start to elect");
+                self.elect();
+            }
+            return;
+        }
+
+        // Pre-vote is rejected:
+        // Adopt a greater vote the same way as a rejected vote request does.
+        let _ = self.vote_handler().update_vote(&resp.vote);
+
+        if resp.last_log_id.as_ref() > self.state.last_log_id() {
+            tracing::info!(
+                greater_log_id = display(resp.last_log_id.display()),
+                "seen a greater log id when {}",
+                func_name!()
+            );
+            self.set_greater_log();
+        }
+    }
+
+    /// The extra time to wait before starting an election, according to the election priority of
+    /// this node.
+    ///
+    /// A voter with the highest priority does not wait. The others wait up to an extra
+    /// election timeout, in proportion to how much lower their priority is. A voter out of the
+    /// preferred leader zone waits another election timeout, if the zone has a voter that stores
+    /// data.
+    pub(crate) fn election_priority_delay(&self) -> Duration {
+        let membership = self.state.membership_state.effective();
+        let election_timeout = self.config.timer_config.election_timeout;
+
+        let mut delay = Duration::default();
+
+        if let Some(zone) = &self.config.preferred_leader_zone {
+            let m = membership.membership();
+            let in_zone = |id: &C::NodeId| m.zone(id) == Some(zone.as_str());
+
+            if !in_zone(&self.config.id) && m.voter_ids().any(|id| in_zone(&id) && !m.is_witness(&id)) {
+                delay += election_timeout;
+            }
+        }
+
+        let max_priority = membership.max_voter_priority();
+        if max_priority == 0 {
+            return delay;
+        }
+
+        let my_priority = std::cmp::min(membership.election_priority(&self.config.id), max_priority);
+        let ratio = (max_priority - my_priority) as f64 / max_priority as f64;
+        delay + election_timeout.mul_f64(ratio)
+    }
+
+    /// The preference for a node to be the leader.
+    ///
+    /// A node in the preferred leader zone ranks higher than any node out of it, and then a node
+    /// with a higher election priority ranks higher.
+    pub(crate) fn leader_rank(&self, node_id: &C::NodeId) -> (bool, u64) {
+        let membership = self.state.membership_state.effective();
+
+        let in_zone = self
+            .config
+            .preferred_leader_zone
+            .as_ref()
+            .is_some_and(|zone| membership.membership().zone(node_id) == Some(zone.as_str()));
+
+        (in_zone, membership.election_priority(node_id))
+    }
+
+    /// Take part in a leadership transfer started by the leader.
+    ///
+    /// The vote of `req.to` will be granted regardless of the leader lease. If this node is
+    /// `req.to` and has all the logs of the leader, it starts an election at once.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn handle_transfer_leader(&mut self, req: &TransferLeaderRequest<C>) {
+        if &req.from_leader != self.state.vote_ref() {
+            tracing::info!(
+                my_vote = display(self.state.vote_ref()),
+                "ignore transfer leader request from a different leader: {}",
+                req
+            );
+            return;
+        }
+
+        self.transferring_to = Some((req.from_leader, req.to));
+
+        if req.to != self.config.id {
+            return;
+        }
+
+        let membership = self.state.membership_state.effective();
+        if !membership.is_voter(&self.config.id) || membership.is_witness(&self.config.id) {
+            tracing::warn!("can not become leader by transfer: not a voter or a witness");
+            return;
+        }
+
+        if self.state.last_log_id() < req.last_log_id.as_ref() {
+            tracing::info!(
+                my_last_log_id = display(self.state.last_log_id().display()),
+                "can not become leader by transfer: not all logs of the leader are received: {}",
+                req
+            );
+            return;
+        }
+
+        tracing::info!("start to elect by transfer: {}", req);
+        self.elect();
+    }
+
     /// Append entries to follower/learner.
     ///
     /// Also clean conflicting entries and update membership state.
     #[tracing::instrument(level = "debug", skip_all)]
+    /// Handle an AppendEntries request.
+    ///
+    /// Only the first `accepted` entries are appended, if the log storage can not append all of
+    /// them. In this case the response is a `PartialSuccess` with the last appended log id, and
+    /// the leader sends the rest in the following requests.
     pub(crate) fn handle_append_entries(
         &mut self,
         vote: &Vote<C::NodeId>,
         prev_log_id: Option<LogId<C::NodeId>>,
-        entries: Vec<C::Entry>,
+        mut entries: Vec<C::Entry>,
+        accepted: usize,
         tx: Option<AppendEntriesTx<C>>,
     ) -> bool {
         tracing::debug!(
             vote = display(vote),
             prev_log_id = display(prev_log_id.display()),
             entries = display(DisplaySlice::<_>(&entries)),
+            accepted,
             my_vote = display(self.state.vote_ref()),
             my_last_log_id = display(self.state.last_log_id().display()),
             "{}",
             func_name!()
         );
 
+        let partial = if accepted < entries.len() {
+            entries.truncate(accepted);
+            let matching = entries.last().map(|x| *x.get_log_id()).or(prev_log_id);
+            Some(matching)
+        } else {
+            None
+        };
+
         let res = self.append_entries(vote, prev_log_id, entries);
         let is_ok = res.is_ok();
 
         if let Some(tx) = tx {
-            let resp: AppendEntriesResponse<C> = res.into();
+            let resp: AppendEntriesResponse<C> = match (res, partial) {
+                (Ok(()), Some(matching)) => AppendEntriesResponse::PartialSuccess(matching),
+                (res, _) => res.into(),
+            };
             self.output.push_command(Command::Respond {
                 when: None,
                 resp: Respond::new(Ok(resp), tx),
@@ -498,6 +806,40 @@ where C: RaftTypeConfig
         }
     }
 
+    /// Leader steps down because it has not been acknowledged by a quorum for a leader lease.
+    ///
+    /// The vote is reverted to a non-committed one of the same leader id, in memory only, so that
+    /// this node becomes a candidate and stops accepting writes. It is safe not to persist it:
+    /// the persisted committed vote is greater and is only restored when this node restarts.
+    /// Another election is started when the election timeout passes.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn leader_step_down_by_lost_quorum(&mut self) {
+        self.leader_revert_to_candidate("no quorum acknowledgement");
+    }
+
+    /// Leader steps down on request of the application, see `Raft::step_down()`.
+    ///
+    /// Just like [`Self::leader_step_down_by_lost_quorum()`], the vote is reverted to a
+    /// non-committed one in memory only.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn leader_step_down_voluntarily(&mut self) {
+        self.leader_revert_to_candidate("requested by application");
+    }
+
+    fn leader_revert_to_candidate(&mut self, reason: &str) {
+        if !self.state.is_leader(&self.config.id) {
+            return;
+        }
+
+        let mut vote = *self.state.vote_ref();
+        tracing::info!(vote = display(&vote), "leader steps down: {}", reason);
+
+        vote.committed = false;
+        self.state.vote.update(C::AsyncRuntime::now(), vote);
+
+        self.vote_handler().update_internal_server_state();
+    }
+
     /// Update Engine state when a new snapshot is built.
     ///
     /// NOTE:
@@ -522,7 +864,8 @@ where C: RaftTypeConfig
             return;
         }
 
-        self.log_handler().schedule_policy_based_purge();
+        let replicated = self.internal_server_state.leading().map(|l| l.min_replicated_next_index());
+        self.log_handler().schedule_policy_based_purge(replicated);
         self.try_purge_log();
     }
 
@@ -649,6 +992,16 @@ where C: RaftTypeConfig
 
         rh.rebuild_replication_streams();
 
+        // A membership change is refused until the last membership config is committed, which
+        // requires an entry of this leader to be committed. Thus the blank entry is still
+        // appended if the last membership config is not committed.
+        let membership_committed = self.state.membership_state.change_handler().ensure_committed().is_ok();
+
+        if !self.config.leader_blank_entry && membership_committed {
+            tracing::info!("leader blank entry is disabled, wait for the first proposed entry");
+            return;
+        }
+
         // Safe unwrap(): Leader is just established
         self.leader_handler()
             .unwrap()
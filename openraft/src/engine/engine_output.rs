@@ -49,11 +49,14 @@ where C: RaftTypeConfig
             Command::QuitLeader => {}
             Command::AppendInputEntries { .. } => {}
             Command::ReplicateCommitted { .. } => {}
+            Command::BroadcastHeartbeat { .. } => {}
             Command::Commit { .. } => {}
             Command::Replicate { .. } => {}
             Command::RebuildReplicationStreams { .. } => {}
             Command::SaveVote { .. } => {}
             Command::SendVote { .. } => {}
+            Command::BroadcastTransferLeader { .. } => {}
+            Command::ConfirmLeadership => {}
             Command::PurgeLog { .. } => {}
             Command::DeleteConflictLog { .. } => {}
             Command::Respond { .. } => {}
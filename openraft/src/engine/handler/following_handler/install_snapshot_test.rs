@@ -45,6 +45,8 @@ fn eng() -> Engine<UTConfig> {
         last_log_id: Some(log_id(2, 1, 2)),
         last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m12()),
         snapshot_id: "1-2-3-4".to_string(),
+        delta_base: None,
+        checksum: None,
     };
     eng.state.server_state = eng.calc_server_state();
 
@@ -62,6 +64,8 @@ fn test_install_snapshot_lt_last_snapshot() -> anyhow::Result<()> {
             last_log_id: Some(log_id(2, 1, 2)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         snapshot: Box::new(Cursor::new(vec![0u8])),
     });
@@ -73,6 +77,8 @@ fn test_install_snapshot_lt_last_snapshot() -> anyhow::Result<()> {
             last_log_id: Some(log_id(2, 1, 2)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m12()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         eng.state.snapshot_meta
     );
@@ -94,6 +100,8 @@ fn test_install_snapshot_lt_committed() -> anyhow::Result<()> {
             last_log_id: Some(log_id(4, 1, 5)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         snapshot: Box::new(Cursor::new(vec![0u8])),
     });
@@ -105,6 +113,8 @@ fn test_install_snapshot_lt_committed() -> anyhow::Result<()> {
             last_log_id: Some(log_id(2, 1, 2)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m12()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         eng.state.snapshot_meta
     );
@@ -123,6 +133,8 @@ fn test_install_snapshot_not_conflict() -> anyhow::Result<()> {
             last_log_id: Some(log_id(4, 1, 6)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         snapshot: Box::new(Cursor::new(vec![0u8])),
     });
@@ -134,6 +146,8 @@ fn test_install_snapshot_not_conflict() -> anyhow::Result<()> {
             last_log_id: Some(log_id(4, 1, 6)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         eng.state.snapshot_meta
     );
@@ -152,6 +166,8 @@ fn test_install_snapshot_not_conflict() -> anyhow::Result<()> {
                         last_log_id: Some(log_id(4, 1, 6)),
                         last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
                         snapshot_id: "1-2-3-4".to_string(),
+                        delta_base: None,
+                        checksum: None,
                     },
                     snapshot: Box::new(Cursor::new(vec![0u8])),
                 })
@@ -187,6 +203,8 @@ fn test_install_snapshot_conflict() -> anyhow::Result<()> {
             last_log_id: Some(log_id(2, 1, 2)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m12()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         };
 
         eng.state.server_state = eng.calc_server_state();
@@ -199,6 +217,8 @@ fn test_install_snapshot_conflict() -> anyhow::Result<()> {
             last_log_id: Some(log_id(5, 1, 6)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         snapshot: Box::new(Cursor::new(vec![0u8])),
     });
@@ -210,6 +230,8 @@ fn test_install_snapshot_conflict() -> anyhow::Result<()> {
             last_log_id: Some(log_id(5, 1, 6)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         eng.state.snapshot_meta
     );
@@ -229,6 +251,8 @@ fn test_install_snapshot_conflict() -> anyhow::Result<()> {
                         last_log_id: Some(log_id(5, 1, 6)),
                         last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
                         snapshot_id: "1-2-3-4".to_string(),
+                        delta_base: None,
+                        checksum: None,
                     },
                     snapshot: Box::new(Cursor::new(vec![0u8])),
                 })
@@ -252,6 +276,8 @@ fn test_install_snapshot_advance_last_log_id() -> anyhow::Result<()> {
             last_log_id: Some(log_id(100, 1, 100)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         snapshot: Box::new(Cursor::new(vec![0u8])),
     });
@@ -263,6 +289,8 @@ fn test_install_snapshot_advance_last_log_id() -> anyhow::Result<()> {
             last_log_id: Some(log_id(100, 1, 100)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         eng.state.snapshot_meta
     );
@@ -284,6 +312,8 @@ fn test_install_snapshot_advance_last_log_id() -> anyhow::Result<()> {
                         last_log_id: Some(log_id(100, 1, 100)),
                         last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
                         snapshot_id: "1-2-3-4".to_string(),
+                        delta_base: None,
+                        checksum: None,
                     },
                     snapshot: Box::new(Cursor::new(vec![0u8])),
                 })
@@ -309,6 +339,8 @@ fn test_install_snapshot_update_accepted() -> anyhow::Result<()> {
             last_log_id: Some(log_id(100, 1, 100)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         snapshot: Box::new(Cursor::new(vec![0u8])),
     });
@@ -319,3 +351,74 @@ fn test_install_snapshot_update_accepted() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_install_delta_snapshot() -> anyhow::Result<()> {
+    // The logs up to the base are applied before the delta is installed, and a full snapshot is
+    // built after it.
+    let mut eng = eng();
+
+    let meta = SnapshotMeta {
+        last_log_id: Some(log_id(100, 1, 100)),
+        last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
+        snapshot_id: "1-2-3-4".to_string(),
+        delta_base: Some(log_id(4, 1, 8)),
+        checksum: None,
+    };
+
+    let cond = eng.following_handler().install_full_snapshot(Snapshot {
+        meta: meta.clone(),
+        snapshot: Box::new(Cursor::new(vec![0u8])),
+    });
+
+    assert_eq!(Some(Condition::StateMachineCommand { command_seq: 2 }), cond);
+
+    assert_eq!(meta, eng.state.snapshot_meta);
+    assert_eq!(Some(&log_id(100, 1, 100)), eng.state.committed());
+    assert_eq!(
+        vec![
+            Command::Commit {
+                seq: 1,
+                already_committed: Some(log_id(4, 1, 5)),
+                upto: log_id(4, 1, 8),
+            },
+            Command::from(
+                sm::Command::install_full_snapshot(Snapshot {
+                    meta,
+                    snapshot: Box::new(Cursor::new(vec![0u8])),
+                })
+                .with_seq(2)
+            ),
+            Command::PurgeLog {
+                upto: log_id(100, 1, 100)
+            },
+            Command::from(sm::Command::build_snapshot().with_seq(3)),
+        ],
+        eng.output.take_commands()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_install_delta_snapshot_base_not_found() -> anyhow::Result<()> {
+    // A delta can not be installed if the local log does not contain the base.
+    let mut eng = eng();
+
+    let cond = eng.following_handler().install_full_snapshot(Snapshot {
+        meta: SnapshotMeta {
+            last_log_id: Some(log_id(100, 1, 100)),
+            last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
+            snapshot_id: "1-2-3-4".to_string(),
+            delta_base: Some(log_id(5, 1, 8)),
+            checksum: None,
+        },
+        snapshot: Box::new(Cursor::new(vec![0u8])),
+    });
+
+    assert_eq!(None, cond);
+    assert_eq!(Some(&log_id(4, 1, 5)), eng.state.committed());
+    assert!(eng.output.take_commands().is_empty());
+
+    Ok(())
+}
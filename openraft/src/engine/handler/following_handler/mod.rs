@@ -134,6 +134,7 @@ where C: RaftTypeConfig
         debug_assert!(Some(entries[0].get_log_id()) > self.state.log_ids.last());
 
         self.state.extend_log_ids(&entries);
+        self.state.track_appended_entries(&entries);
         self.append_membership(entries.iter());
 
         self.output.push_command(Command::AppendInputEntries {
@@ -166,7 +167,7 @@ where C: RaftTypeConfig
                 upto: committed.unwrap(),
             });
 
-            if self.config.snapshot_policy.should_snapshot(&self.state) {
+            if self.config.snapshot_policy.should_snapshot(self.state) {
                 self.snapshot_handler().trigger_snapshot();
             }
         }
@@ -191,6 +192,7 @@ where C: RaftTypeConfig
         };
 
         self.state.log_ids.truncate(since);
+        self.state.log_retention.truncate(since);
         self.output.push_command(Command::DeleteConflictLog { since: since_log_id });
 
         let changed = self.state.membership_state.truncate(since);
@@ -279,6 +281,28 @@ where C: RaftTypeConfig
         // snapshot_last_log_id can not be None
         let snap_last_log_id = snap_last_log_id.unwrap();
 
+        if let Some(base) = meta.delta_base {
+            if !self.state.has_log_id(&base) {
+                tracing::warn!(
+                    "Can not install delta snapshot: base {} is not found; meta:{}",
+                    base,
+                    meta
+                );
+                return None;
+            }
+
+            // The delta is applied upon the state at `base`: apply the local logs up to `base`
+            // first. `base` is committed since it is before the leader's snapshot.
+            if let Some(prev_committed) = self.state.update_committed(&Some(base)) {
+                let seq = self.output.next_sm_seq();
+                self.output.push_command(Command::Commit {
+                    seq,
+                    already_committed: prev_committed,
+                    upto: base,
+                });
+            }
+        }
+
         // 1. Truncate all logs if conflict.
         // 2. Install snapshot.
         // 3. Purge logs the snapshot covers.
@@ -303,12 +327,19 @@ where C: RaftTypeConfig
             meta.last_membership.clone(),
         ));
 
+        let is_delta = snapshot.meta.is_delta();
+
         self.output.push_command(Command::from(sm::Command::install_full_snapshot(snapshot)));
         let last_sm_seq = self.output.last_sm_seq();
 
         self.state.purge_upto = Some(snap_last_log_id);
         self.log_handler().purge_log();
 
+        // A delta is not a snapshot that can be sent to other nodes: build a full one.
+        if is_delta {
+            self.snapshot_handler().trigger_snapshot();
+        }
+
         Some(Condition::StateMachineCommand {
             command_seq: last_sm_seq,
         })
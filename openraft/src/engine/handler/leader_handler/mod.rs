@@ -1,13 +1,14 @@
 use crate::engine::handler::replication_handler::ReplicationHandler;
-use crate::engine::handler::replication_handler::SendNone;
 use crate::engine::Command;
 use crate::engine::EngineConfig;
 use crate::engine::EngineOutput;
+use crate::entry::RaftEntry;
 use crate::entry::RaftPayload;
 use crate::internal_server_state::LeaderQuorumSet;
 use crate::leader::Leading;
 use crate::raft_state::LogStateReader;
 use crate::type_config::alias::LogIdOf;
+use crate::LogId;
 use crate::RaftLogId;
 use crate::RaftState;
 use crate::RaftTypeConfig;
@@ -53,6 +54,7 @@ where C: RaftTypeConfig
         self.leader.assign_log_ids(&mut entries).unwrap();
 
         self.state.extend_log_ids_from_same_leader(&entries);
+        self.state.track_appended_entries(&entries);
 
         let mut membership_entry = None;
         for entry in entries.iter() {
@@ -100,13 +102,32 @@ where C: RaftTypeConfig
             rh.append_membership(&log_id, &m);
         }
 
-        rh.initiate_replication(SendNone::False);
+        rh.initiate_replication();
     }
 
+    /// Send a heartbeat to every target, and initiate replication to the targets that have data
+    /// to send.
+    ///
+    /// A heartbeat is sent even if there is data in flight to a target, since the heartbeats are
+    /// sent separately from the data.
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn send_heartbeat(&mut self) -> () {
+    pub(crate) fn send_heartbeat(&mut self) {
+        self.output.push_command(Command::BroadcastHeartbeat {
+            committed: self.state.committed().copied(),
+        });
+
         let mut rh = self.replication_handler();
-        rh.initiate_replication(SendNone::True);
+        rh.initiate_replication();
+    }
+
+    /// Append a blank entry if this leader has not yet proposed any entry, which happens only if
+    /// [`Config::leader_blank_entry`](`crate::Config::leader_blank_entry`) is disabled.
+    ///
+    /// A linearizable read has to wait for the first entry of this leader to be committed.
+    pub(crate) fn ensure_first_entry_proposed(&mut self) {
+        if self.leader.last_log_id() < self.leader.noop_log_id.as_ref() {
+            self.leader_append_entries(vec![C::Entry::new_blank(LogId::<C::NodeId>::default())]);
+        }
     }
 
     /// Get the log id for a linearizable read.
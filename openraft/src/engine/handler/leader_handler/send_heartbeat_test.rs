@@ -54,6 +54,9 @@ fn test_leader_send_heartbeat() -> anyhow::Result<()> {
         eng.leader_handler()?.send_heartbeat();
         assert_eq!(
             vec![
+                Command::BroadcastHeartbeat {
+                    committed: Some(log_id(0, 1, 0)),
+                },
                 Command::Replicate {
                     target: 2,
                     req: Inflight::logs(None, Some(log_id(2, 1, 3))).with_id(1),
@@ -67,14 +70,19 @@ fn test_leader_send_heartbeat() -> anyhow::Result<()> {
         );
     }
 
-    // No RPC will be sent if there are inflight RPC
+    // Only a heartbeat is sent if there are inflight RPC
     {
         eng.output.clear_commands();
         eng.leader_handler()?.send_heartbeat();
-        assert!(eng.output.take_commands().is_empty());
+        assert_eq!(
+            vec![Command::BroadcastHeartbeat {
+                committed: Some(log_id(0, 1, 0)),
+            }],
+            eng.output.take_commands()
+        );
     }
 
-    // No data to send, sending a heartbeat is to send empty RPC:
+    // No data to send, only the heartbeat is sent:
     {
         let l = eng.leader_handler()?;
         let _ = l.leader.progress.update_with(&2, |ent| ent.update_matching(1, Some(log_id(2, 1, 3))).unwrap());
@@ -83,16 +91,9 @@ fn test_leader_send_heartbeat() -> anyhow::Result<()> {
     eng.output.clear_commands();
     eng.leader_handler()?.send_heartbeat();
     assert_eq!(
-        vec![
-            Command::Replicate {
-                target: 2,
-                req: Inflight::logs(Some(log_id(2, 1, 3)), Some(log_id(2, 1, 3))).with_id(1),
-            },
-            Command::Replicate {
-                target: 3,
-                req: Inflight::logs(Some(log_id(2, 1, 3)), Some(log_id(2, 1, 3))).with_id(1),
-            },
-        ],
+        vec![Command::BroadcastHeartbeat {
+            committed: Some(log_id(0, 1, 0)),
+        }],
         eng.output.take_commands()
     );
 
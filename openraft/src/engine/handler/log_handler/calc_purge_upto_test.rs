@@ -3,6 +3,8 @@ use crate::engine::Engine;
 use crate::engine::LogIdList;
 use crate::CommittedLeaderId;
 use crate::LogId;
+use crate::LogRetentionPolicy;
+use crate::TokioInstant;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
     LogId::<u64> {
@@ -59,7 +61,7 @@ fn test_calc_purge_upto() -> anyhow::Result<()> {
 
     for (last_purged, snapshot_last_log_id, max_keep, want) in cases {
         let mut eng = eng();
-        eng.config.max_in_snapshot_log_to_keep = max_keep;
+        eng.config.log_retention_policy = LogRetentionPolicy::KeepEntries(max_keep);
         eng.config.purge_batch_size = 1;
 
         if let Some(last_purged) = last_purged {
@@ -67,7 +69,7 @@ fn test_calc_purge_upto() -> anyhow::Result<()> {
             eng.state.purged_next = last_purged.index + 1;
         }
         eng.state.snapshot_meta.last_log_id = snapshot_last_log_id;
-        let got = eng.log_handler().calc_purge_upto();
+        let got = eng.log_handler().calc_purge_upto(None);
 
         assert_eq!(
             want, got,
@@ -78,3 +80,80 @@ fn test_calc_purge_upto() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_calc_purge_upto_keep_bytes() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.log_retention_policy = LogRetentionPolicy::KeepBytes(20);
+    eng.config.purge_batch_size = 1;
+    eng.state.snapshot_meta.last_log_id = Some(log_id(3, 4));
+
+    // Untracked logs are kept
+    assert_eq!(None, eng.log_handler().calc_purge_upto(None));
+
+    let now = TokioInstant::now();
+    eng.state.log_retention.append(2, 10, now);
+    eng.state.log_retention.append(4, 10, now);
+    assert_eq!(None, eng.log_handler().calc_purge_upto(None));
+
+    eng.state.log_retention.append(5, 100, now);
+    assert_eq!(
+        None,
+        eng.log_handler().calc_purge_upto(None),
+        "logs not in snapshot are not counted"
+    );
+
+    eng.config.log_retention_policy = LogRetentionPolicy::KeepBytes(15);
+    assert_eq!(Some(log_id(1, 2)), eng.log_handler().calc_purge_upto(None));
+
+    Ok(())
+}
+
+#[test]
+fn test_calc_purge_upto_keep_duration() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.log_retention_policy = LogRetentionPolicy::KeepDuration(1_000_000);
+    eng.config.purge_batch_size = 1;
+    eng.state.snapshot_meta.last_log_id = Some(log_id(3, 4));
+
+    let now = TokioInstant::now();
+    eng.state.log_retention.append(2, 10, now);
+    eng.state.log_retention.append(5, 10, now);
+    assert_eq!(None, eng.log_handler().calc_purge_upto(None));
+
+    // Logs not in snapshot are not purged
+    eng.config.log_retention_policy = LogRetentionPolicy::KeepDuration(0);
+    assert_eq!(Some(log_id(3, 4)), eng.log_handler().calc_purge_upto(None));
+
+    Ok(())
+}
+
+#[test]
+fn test_calc_purge_upto_slowest_replica() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.purge_batch_size = 1;
+    eng.state.snapshot_meta.last_log_id = Some(log_id(3, 4));
+
+    // max_entries, replicated, want
+    let cases = vec![
+        (10, None, Some(log_id(3, 4))),
+        (10, Some(0), None),
+        (10, Some(3), Some(log_id(1, 2))),
+        (10, Some(9), Some(log_id(3, 4))),
+        (2, Some(0), Some(log_id(1, 2))),
+        (0, Some(0), Some(log_id(3, 4))),
+    ];
+
+    for (max_entries, replicated, want) in cases {
+        eng.config.log_retention_policy = LogRetentionPolicy::SlowestReplica { max_entries };
+        let got = eng.log_handler().calc_purge_upto(replicated);
+
+        assert_eq!(
+            want, got,
+            "case: max_entries: {}, replicated: {:?}",
+            max_entries, replicated
+        );
+    }
+
+    Ok(())
+}
@@ -1,10 +1,14 @@
+use std::time::Duration;
+
 use crate::display_ext::DisplayOptionExt;
 use crate::engine::Command;
 use crate::engine::EngineConfig;
 use crate::engine::EngineOutput;
 use crate::raft_state::LogStateReader;
+use crate::AsyncRuntime;
 use crate::LogId;
 use crate::LogIdOptionExt;
+use crate::LogRetentionPolicy;
 use crate::RaftState;
 use crate::RaftTypeConfig;
 
@@ -49,11 +53,15 @@ where C: RaftTypeConfig
     /// policy.
     ///
     /// This method is called after building a snapshot, because openraft only purge logs that are
-    /// already included in snapshot.
+    /// already included in snapshot. `replicated` is the smallest next index of all replication
+    /// targets on a leader, or `None` on other nodes.
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn schedule_policy_based_purge(&mut self) {
-        if let Some(purge_upto) = self.calc_purge_upto() {
-            self.update_purge_upto(purge_upto);
+    pub(crate) fn schedule_policy_based_purge(&mut self, replicated: Option<u64>) {
+        if let Some(purge_upto) = self.calc_purge_upto(replicated) {
+            // A manually triggered purge may be greater.
+            if self.state.purge_upto() < Some(&purge_upto) {
+                self.update_purge_upto(purge_upto);
+            }
         }
     }
 
@@ -66,30 +74,44 @@ where C: RaftTypeConfig
 
     /// Calculate the log id up to which to purge, inclusive.
     ///
-    /// Only log included in snapshot will be purged.
-    /// It may return None if there is no log to purge.
-    ///
-    /// `max_keep` specifies the number of applied logs to keep.
-    /// `max_keep==0` means every applied log can be purged.
+    /// Only log included in snapshot will be purged, and the logs to keep are decided by
+    /// [`LogRetentionPolicy`]. It may return None if there is no log to purge.
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn calc_purge_upto(&self) -> Option<LogId<C::NodeId>> {
+    pub(crate) fn calc_purge_upto(&self, replicated: Option<u64>) -> Option<LogId<C::NodeId>> {
         let st = &self.state;
-        let max_keep = self.config.max_in_snapshot_log_to_keep;
+        let policy = &self.config.log_retention_policy;
         let batch_size = self.config.purge_batch_size;
 
-        let purge_end = self.state.snapshot_meta.last_log_id.next_index().saturating_sub(max_keep);
+        let snapshot_end = self.state.snapshot_meta.last_log_id.next_index();
+        let start = st.last_purged_log_id().next_index();
+
+        let purge_end = match policy {
+            LogRetentionPolicy::KeepEntries(max_keep) => snapshot_end.saturating_sub(*max_keep),
+            LogRetentionPolicy::KeepBytes(max_bytes) => {
+                st.log_retention.purge_end_by_bytes(snapshot_end, *max_bytes).unwrap_or(start)
+            }
+            LogRetentionPolicy::KeepDuration(ms) => st
+                .log_retention
+                .purge_end_by_time(C::AsyncRuntime::now(), Duration::from_millis(*ms))
+                .unwrap_or(start),
+            LogRetentionPolicy::SlowestReplica { max_entries } => {
+                let end = std::cmp::min(snapshot_end, replicated.unwrap_or(snapshot_end));
+                std::cmp::max(end, snapshot_end.saturating_sub(*max_entries))
+            }
+        };
+        let purge_end = std::cmp::min(purge_end, snapshot_end);
 
         tracing::debug!(
             snapshot_last_log_id = debug(self.state.snapshot_meta.last_log_id),
-            max_keep,
+            policy = debug(policy),
             "try purge: (-oo, {})",
             purge_end
         );
 
-        if st.last_purged_log_id().next_index() + batch_size > purge_end {
+        if start + batch_size > purge_end {
             tracing::debug!(
                 snapshot_last_log_id = debug(self.state.snapshot_meta.last_log_id),
-                max_keep,
+                policy = debug(policy),
                 last_purged_log_id = display(st.last_purged_log_id().display()),
                 batch_size,
                 purge_end,
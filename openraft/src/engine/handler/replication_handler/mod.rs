@@ -18,6 +18,7 @@ use crate::type_config::alias::InstantOf;
 use crate::EffectiveMembership;
 use crate::LogId;
 use crate::LogIdOptionExt;
+use crate::LogRetentionPolicy;
 use crate::Membership;
 use crate::RaftState;
 use crate::RaftTypeConfig;
@@ -43,16 +44,6 @@ where C: RaftTypeConfig
     pub(crate) output: &'x mut EngineOutput<C>,
 }
 
-/// An option about whether to send an RPC to follower/learner even when there is no data to send.
-///
-/// Sending none data serves as a heartbeat.
-#[derive(Debug)]
-#[derive(PartialEq, Eq)]
-pub(crate) enum SendNone {
-    False,
-    True,
-}
-
 impl<'x, C> ReplicationHandler<'x, C>
 where C: RaftTypeConfig
 {
@@ -85,7 +76,7 @@ where C: RaftTypeConfig
 
         self.rebuild_progresses();
         self.rebuild_replication_streams();
-        self.initiate_replication(SendNone::False);
+        self.initiate_replication();
     }
 
     /// Rebuild leader's replication progress to reflect replication changes.
@@ -205,9 +196,43 @@ where C: RaftTypeConfig
             "after updating progress"
         );
 
+        // Unless it is allowed to commit without the leader, an entry is committed only when it
+        // is also flushed to the leader's local log store.
+        let quorum_accepted = if self.config.commit_without_local_flush {
+            quorum_accepted
+        } else {
+            match self.leader.progress.try_get(&self.config.id) {
+                Some(local) => std::cmp::min(quorum_accepted, local.matching),
+                // The leader is not in the membership.
+                None => quorum_accepted,
+            }
+        };
+
+        // A witness stores only the log id of an entry. If there are witnesses, an entry is
+        // committed only when it is also accepted by a voter other than the leader that is not a
+        // witness, thus a committed entry is not lost along with the leader.
+        let quorum_accepted = match self.max_non_witness_matching() {
+            Some(matching) => std::cmp::min(quorum_accepted, matching),
+            None => quorum_accepted,
+        };
+
         self.try_commit_quorum_accepted(quorum_accepted);
     }
 
+    /// Returns the greatest matching log id of the voters except the leader that are not
+    /// witnesses, or `None` if the membership has no witness, or there is no such voter.
+    fn max_non_witness_matching(&self) -> Option<Option<LogId<C::NodeId>>> {
+        let membership = self.state.membership_state.effective().membership();
+
+        membership.witness_ids().next()?;
+
+        membership
+            .voter_ids()
+            .filter(|id| *id != self.config.id && !membership.is_witness(id))
+            .filter_map(|id| self.leader.progress.try_get(&id).map(|p| p.matching))
+            .max()
+    }
+
     /// Commit the log id that is granted(accepted) by a quorum of voters.
     ///
     /// In raft a log that is granted and in the leader term is committed.
@@ -232,7 +257,7 @@ where C: RaftTypeConfig
                 upto: self.state.committed().copied().unwrap(),
             });
 
-            if self.config.snapshot_policy.should_snapshot(&self.state) {
+            if self.config.snapshot_policy.should_snapshot(self.state) {
                 self.snapshot_handler().trigger_snapshot();
             }
         }
@@ -303,6 +328,15 @@ where C: RaftTypeConfig
             }
         };
 
+        // The logs to keep by these policies change when progress is updated or as time goes by.
+        if matches!(
+            self.config.log_retention_policy,
+            LogRetentionPolicy::SlowestReplica { .. } | LogRetentionPolicy::KeepDuration(_)
+        ) {
+            let replicated = self.leader.min_replicated_next_index();
+            self.log_handler().schedule_policy_based_purge(Some(replicated));
+        }
+
         // The purge job may be postponed because a replication task is using them.
         // Thus we just try again to purge when progress is updated.
         self.try_purge_log();
@@ -312,7 +346,7 @@ where C: RaftTypeConfig
         {
             let p = self.leader.progress.get_mut(&target).unwrap();
 
-            let r = p.next_send(self.state.deref(), self.config.max_payload_entries);
+            let r = p.next_send(self.state.deref(), self.config.max_inflight_entries());
             tracing::debug!(next_send_res = debug(&r), "next_send");
 
             if let Ok(inflight) = r {
@@ -341,10 +375,8 @@ where C: RaftTypeConfig
     }
 
     /// Initiate replication for every target that is not sending data in flight.
-    ///
-    /// `send_none` specifies whether to force to send a message even when there is no data to send.
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn initiate_replication(&mut self, send_none: SendNone) {
+    pub(crate) fn initiate_replication(&mut self) {
         tracing::debug!(progress = debug(&self.leader.progress), "{}", func_name!());
 
         for (id, prog_entry) in self.leader.progress.iter_mut() {
@@ -354,7 +386,7 @@ where C: RaftTypeConfig
                 continue;
             }
 
-            let t = prog_entry.next_send(self.state, self.config.max_payload_entries);
+            let t = prog_entry.next_send(self.state, self.config.max_inflight_entries());
             tracing::debug!(target = display(*id), send = debug(&t), "next send");
 
             match t {
@@ -362,19 +394,7 @@ where C: RaftTypeConfig
                     Self::send_to_target(self.output, id, inflight);
                 }
                 Err(e) => {
-                    tracing::debug!(
-                        "no data to replicate for node-{}: current inflight: {:?}, send_none: {:?}",
-                        id,
-                        e,
-                        send_none
-                    );
-
-                    #[allow(clippy::collapsible_if)]
-                    if e == &Inflight::None {
-                        if send_none == SendNone::True {
-                            Self::send_to_target(self.output, id, e);
-                        }
-                    }
+                    tracing::debug!("no data to replicate for node-{}: current inflight: {:?}", id, e);
                 }
             }
         }
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use maplit::btreeset;
 use pretty_assertions::assert_eq;
 
+use crate::engine::handler::replication_handler::ReplicationHandler;
 use crate::engine::testing::UTConfig;
 use crate::engine::Command;
 use crate::engine::Engine;
@@ -56,6 +57,7 @@ fn test_update_matching_no_leader() -> anyhow::Result<()> {
 #[test]
 fn test_update_matching() -> anyhow::Result<()> {
     let mut eng = eng();
+    eng.config.commit_without_local_flush = true;
     eng.vote_handler().become_leading();
 
     let mut rh = eng.replication_handler();
@@ -132,3 +134,96 @@ fn test_update_matching() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+fn start_inflight(rh: &mut ReplicationHandler<UTConfig>, id: u64) -> u64 {
+    let prog_entry = rh.leader.progress.get_mut(&id).unwrap();
+    prog_entry.inflight = Inflight::logs(Some(log_id(1, 1, 1)), Some(log_id(2, 1, 4)));
+    prog_entry.inflight.get_id().unwrap()
+}
+
+#[test]
+fn test_update_matching_wait_for_local_flush() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.vote_handler().become_leading();
+
+    let mut rh = eng.replication_handler();
+    // progress: None, None, (2,3); quorum-ed by followers, but the leader has not flushed
+    {
+        let id1 = start_inflight(&mut rh, 1);
+        rh.update_matching(1, id1, Some(log_id(2, 1, 3)));
+        let id3 = start_inflight(&mut rh, 3);
+        rh.update_matching(3, id3, Some(log_id(2, 1, 3)));
+
+        assert_eq!(None, rh.state.committed());
+        assert_eq!(0, rh.output.take_commands().len());
+    }
+
+    // progress: (2,3), (2,1), (2,3); committed up to the leader's flushed log
+    {
+        rh.update_local_progress(Some(log_id(2, 1, 1)));
+
+        assert_eq!(Some(&log_id(2, 1, 1)), rh.state.committed());
+        assert_eq!(
+            vec![
+                Command::ReplicateCommitted {
+                    committed: Some(log_id(2, 1, 1))
+                },
+                Command::Commit {
+                    seq: 1,
+                    already_committed: None,
+                    upto: log_id(2, 1, 1)
+                }
+            ],
+            rh.output.take_commands()
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_update_matching_wait_for_non_witness() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.membership_state = MembershipState::new(
+        Arc::new(EffectiveMembership::new(Some(log_id(1, 1, 1)), m01())),
+        Arc::new(EffectiveMembership::new(
+            Some(log_id(2, 1, 3)),
+            m123().with_witnesses(btreeset! {3}),
+        )),
+    );
+    eng.vote_handler().become_leading();
+
+    let mut rh = eng.replication_handler();
+    // progress: None, (2,3), (2,3); quorum-ed by the leader and the witness only
+    {
+        rh.update_local_progress(Some(log_id(2, 1, 3)));
+        let id3 = start_inflight(&mut rh, 3);
+        rh.update_matching(3, id3, Some(log_id(2, 1, 3)));
+
+        assert_eq!(None, rh.state.committed());
+        assert_eq!(0, rh.output.take_commands().len());
+    }
+
+    // progress: (2,1), (2,3), (2,3); committed up to the log accepted by a non-witness follower
+    {
+        let id1 = start_inflight(&mut rh, 1);
+        rh.update_matching(1, id1, Some(log_id(2, 1, 1)));
+
+        assert_eq!(Some(&log_id(2, 1, 1)), rh.state.committed());
+        assert_eq!(
+            vec![
+                Command::ReplicateCommitted {
+                    committed: Some(log_id(2, 1, 1))
+                },
+                Command::Commit {
+                    seq: 1,
+                    already_committed: None,
+                    upto: log_id(2, 1, 1)
+                }
+            ],
+            rh.output.take_commands()
+        );
+    }
+
+    Ok(())
+}
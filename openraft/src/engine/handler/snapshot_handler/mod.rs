@@ -51,7 +51,11 @@ where C: RaftTypeConfig
     pub(crate) fn update_snapshot(&mut self, meta: SnapshotMeta<C>) -> bool {
         tracing::info!("update_snapshot: {:?}", meta);
 
-        if meta.last_log_id <= self.state.snapshot_last_log_id().copied() {
+        // A full snapshot built after installing a delta replaces it.
+        let curr = &self.state.snapshot_meta;
+        let replaces_delta = curr.is_delta() && !meta.is_delta() && meta.last_log_id == curr.last_log_id;
+
+        if !replaces_delta && meta.last_log_id <= self.state.snapshot_last_log_id().copied() {
             tracing::info!(
                 "No need to install a smaller snapshot: current snapshot last_log_id({}), new snapshot last_log_id({})",
                 self.state.snapshot_last_log_id().display(),
@@ -24,6 +24,8 @@ fn eng() -> Engine<UTConfig> {
         last_log_id: Some(log_id(2, 1, 2)),
         last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m12()),
         snapshot_id: "1-2-3-4".to_string(),
+        delta_base: None,
+        checksum: None,
     };
     eng
 }
@@ -37,6 +39,8 @@ fn test_update_snapshot_no_update() -> anyhow::Result<()> {
         last_log_id: Some(log_id(2, 1, 2)),
         last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m1234()),
         snapshot_id: "1-2-3-4".to_string(),
+        delta_base: None,
+        checksum: None,
     });
 
     assert_eq!(false, got);
@@ -46,6 +50,8 @@ fn test_update_snapshot_no_update() -> anyhow::Result<()> {
             last_log_id: Some(log_id(2, 1, 2)),
             last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m12()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         eng.state.snapshot_meta
     );
@@ -64,6 +70,8 @@ fn test_update_snapshot_updated() -> anyhow::Result<()> {
         last_log_id: Some(log_id(2, 1, 3)),
         last_membership: StoredMembership::new(Some(log_id(2, 1, 2)), m1234()),
         snapshot_id: "1-2-3-4".to_string(),
+        delta_base: None,
+        checksum: None,
     });
 
     assert_eq!(true, got);
@@ -73,6 +81,8 @@ fn test_update_snapshot_updated() -> anyhow::Result<()> {
             last_log_id: Some(log_id(2, 1, 3)),
             last_membership: StoredMembership::new(Some(log_id(2, 1, 2)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            delta_base: None,
+            checksum: None,
         },
         eng.state.snapshot_meta
     );
@@ -81,3 +91,25 @@ fn test_update_snapshot_updated() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_update_snapshot_replaces_delta() -> anyhow::Result<()> {
+    // A full snapshot with equal `last_log_id` replaces an installed delta.
+    let mut eng = eng();
+    eng.state.snapshot_meta.delta_base = Some(log_id(1, 1, 1));
+
+    let full = SnapshotMeta {
+        last_log_id: Some(log_id(2, 1, 2)),
+        last_membership: StoredMembership::new(Some(log_id(1, 1, 1)), m12()),
+        snapshot_id: "1-2-3-5".to_string(),
+        delta_base: None,
+        checksum: None,
+    };
+
+    let got = eng.snapshot_handler().update_snapshot(full.clone());
+
+    assert_eq!(true, got);
+    assert_eq!(full, eng.state.snapshot_meta);
+
+    Ok(())
+}
@@ -11,8 +11,7 @@ use crate::error::RejectVoteRequest;
 use crate::internal_server_state::InternalServerState;
 use crate::leader::Leading;
 use crate::raft_state::LogStateReader;
-use crate::type_config::alias::InstantOf;
-use crate::Instant;
+use crate::AsyncRuntime;
 use crate::OptionalSend;
 use crate::RaftState;
 use crate::RaftTypeConfig;
@@ -100,15 +99,15 @@ where C: RaftTypeConfig
         if vote > self.state.vote_ref() {
             tracing::info!("vote is changing from {} to {}", self.state.vote_ref(), vote);
 
-            self.state.vote.update(InstantOf::<C>::now(), *vote);
+            self.state.vote.update(C::AsyncRuntime::now(), *vote);
             self.output.push_command(Command::SaveVote { vote: *vote });
         } else {
-            self.state.vote.touch(InstantOf::<C>::now());
+            self.state.vote.touch(C::AsyncRuntime::now());
         }
 
         // Update vote related timer and lease.
 
-        tracing::debug!(now = debug(InstantOf::<C>::now()), "{}", func_name!());
+        tracing::debug!(now = debug(C::AsyncRuntime::now()), "{}", func_name!());
 
         self.update_internal_server_state();
 
@@ -41,10 +41,14 @@ pub(crate) mod time_state;
 mod tests {
     mod append_entries_test;
     mod elect_test;
+    #[cfg(feature = "unsafe-force-reset-membership")]
+    mod force_reset_membership_test;
+    mod handle_transfer_leader_test;
     mod handle_vote_req_test;
     mod handle_vote_resp_test;
     mod initialize_test;
     mod install_full_snapshot_test;
+    mod leader_step_down_by_lost_quorum_test;
     mod log_id_list_test;
     mod startup_test;
     mod trigger_purge_log_test;
@@ -25,10 +25,14 @@ where N: Node + Ord
 {
     type D = ();
     type R = ();
+    type AppError = crate::error::Infallible;
     type NodeId = u64;
     type Node = N;
     type Entry = crate::Entry<Self>;
     type SnapshotData = Cursor<Vec<u8>>;
     type AsyncRuntime = TokioRuntime;
     type Responder = crate::impls::OneshotResponder<Self>;
+    type PayloadCodec = crate::network::NoCompression;
+    type StorageCipher = crate::storage::NoEncryption;
+    type SnapshotTrigger = crate::NoSnapshotTrigger;
 }
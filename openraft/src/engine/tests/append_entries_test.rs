@@ -7,12 +7,16 @@ use crate::core::ServerState;
 use crate::engine::testing::UTConfig;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::Respond;
 use crate::entry::RaftEntry;
 use crate::error::RejectAppendEntries;
+use crate::raft::AppendEntriesResponse;
 use crate::raft_state::LogStateReader;
 use crate::testing::blank_ent;
 use crate::testing::log_id;
+use crate::type_config::alias::AsyncRuntimeOf;
 use crate::utime::UTime;
+use crate::AsyncRuntime;
 use crate::EffectiveMembership;
 use crate::Entry;
 use crate::Membership;
@@ -302,3 +306,45 @@ fn test_append_entries_conflict() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_handle_append_entries_partially_accepted() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let (tx, _rx) = AsyncRuntimeOf::<UTConfig>::oneshot();
+
+    // Only the first of the 3 entries can be appended.
+    let is_ok = eng.handle_append_entries(
+        &Vote::new_committed(2, 1),
+        Some(log_id(2, 1, 3)),
+        vec![blank_ent(2, 1, 4), blank_ent(2, 1, 5), blank_ent(2, 1, 6)],
+        1,
+        Some(tx),
+    );
+
+    assert!(is_ok);
+    assert_eq!(Some(&log_id(2, 1, 4)), eng.state.last_log_id());
+
+    let (dummy_tx, _rx) = AsyncRuntimeOf::<UTConfig>::oneshot();
+    assert_eq!(
+        vec![
+            Command::SaveVote {
+                vote: Vote::new_committed(2, 1)
+            },
+            Command::AppendInputEntries {
+                vote: Vote::new_committed(2, 1),
+                entries: vec![blank_ent(2, 1, 4)]
+            },
+            Command::Respond {
+                when: None,
+                resp: Respond::new(
+                    Ok(AppendEntriesResponse::PartialSuccess(Some(log_id(2, 1, 4)))),
+                    dummy_tx
+                ),
+            },
+        ],
+        eng.output.take_commands()
+    );
+
+    Ok(())
+}
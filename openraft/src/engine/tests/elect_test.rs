@@ -1,6 +1,8 @@
 use std::collections::BTreeSet;
 use std::sync::Arc;
+use std::time::Duration;
 
+use maplit::btreemap;
 use maplit::btreeset;
 use pretty_assertions::assert_eq;
 
@@ -18,6 +20,7 @@ use crate::EffectiveMembership;
 use crate::Entry;
 use crate::LogId;
 use crate::Membership;
+use crate::MembershipState;
 use crate::TokioInstant;
 use crate::Vote;
 
@@ -152,3 +155,141 @@ fn test_elect() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_elect_without_leader_blank_entry() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.id = 1;
+    eng.config.leader_blank_entry = false;
+    let m = Arc::new(EffectiveMembership::new(Some(log_id(0, 1, 1)), m1()));
+    eng.state.membership_state = MembershipState::new(m.clone(), m);
+
+    eng.elect();
+
+    assert_eq!(ServerState::Leader, eng.state.server_state);
+    assert_eq!(
+        Some(log_id(1, 1, 1)),
+        eng.internal_server_state.leading().unwrap().noop_log_id,
+        "the first entry proposed by the leader is used as the noop log"
+    );
+
+    assert_eq!(
+        vec![
+            Command::SaveVote { vote: Vote::new(1, 1) },
+            Command::SaveVote {
+                vote: Vote::new_committed(1, 1)
+            },
+            Command::BecomeLeader,
+            Command::RebuildReplicationStreams { targets: vec![] },
+        ],
+        eng.output.take_commands()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_elect_without_leader_blank_entry_membership_not_committed() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.id = 1;
+    eng.config.leader_blank_entry = false;
+    eng.state
+        .membership_state
+        .set_effective(Arc::new(EffectiveMembership::new(Some(log_id(0, 1, 1)), m1())));
+
+    eng.elect();
+
+    assert_eq!(ServerState::Leader, eng.state.server_state);
+
+    let leading = eng.internal_server_state.leading().unwrap();
+    assert_eq!(
+        leading.noop_log_id.as_ref(),
+        leading.last_log_id(),
+        "a blank entry is appended to commit the membership config"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_election_priority_delay() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.id = 1;
+    eng.config.timer_config.election_timeout = Duration::from_millis(100);
+
+    let set_priorities = |eng: &mut Engine<UTConfig>, priorities| {
+        let m = Membership::new(vec![btreeset! {1,2,3}], None).with_priorities(priorities);
+        eng.state
+            .membership_state
+            .set_effective(Arc::new(EffectiveMembership::new(Some(log_id(0, 1, 1)), m)));
+    };
+
+    tracing::info!("--- no priority: no delay");
+    {
+        set_priorities(&mut eng, btreemap! {});
+        assert_eq!(Duration::default(), eng.election_priority_delay());
+    }
+
+    tracing::info!("--- the highest priority: no delay");
+    {
+        set_priorities(&mut eng, btreemap! {1=>10, 2=>10, 3=>5});
+        assert_eq!(Duration::default(), eng.election_priority_delay());
+    }
+
+    tracing::info!("--- lower priority: delay proportionally");
+    {
+        set_priorities(&mut eng, btreemap! {1=>5, 2=>20});
+        assert_eq!(Duration::from_millis(75), eng.election_priority_delay());
+    }
+
+    tracing::info!("--- no priority set for this node: delay a full election timeout");
+    {
+        set_priorities(&mut eng, btreemap! {2=>20});
+        assert_eq!(Duration::from_millis(100), eng.election_priority_delay());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_election_priority_delay_preferred_zone() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.id = 1;
+    eng.config.timer_config.election_timeout = Duration::from_millis(100);
+    eng.config.preferred_leader_zone = Some("a".to_string());
+
+    let set_membership = |eng: &mut Engine<UTConfig>, priorities, zones| {
+        let m = Membership::new(vec![btreeset! {1,2,3}], None).with_priorities(priorities).with_zones(zones);
+        eng.state
+            .membership_state
+            .set_effective(Arc::new(EffectiveMembership::new(Some(log_id(0, 1, 1)), m)));
+    };
+
+    tracing::info!("--- no voter in the preferred zone: no delay");
+    {
+        set_membership(&mut eng, btreemap! {}, btreemap! {1=>s("b"), 2=>s("b")});
+        assert_eq!(Duration::default(), eng.election_priority_delay());
+    }
+
+    tracing::info!("--- in the preferred zone: no delay");
+    {
+        set_membership(&mut eng, btreemap! {}, btreemap! {1=>s("a"), 2=>s("b")});
+        assert_eq!(Duration::default(), eng.election_priority_delay());
+        assert_eq!((true, 0), eng.leader_rank(&1));
+        assert_eq!((false, 0), eng.leader_rank(&2));
+    }
+
+    tracing::info!("--- out of the preferred zone: delay a full election timeout more");
+    {
+        set_membership(&mut eng, btreemap! {1=>5, 2=>20}, btreemap! {1=>s("b"), 2=>s("a")});
+        assert_eq!(Duration::from_millis(175), eng.election_priority_delay());
+        assert_eq!((false, 5), eng.leader_rank(&1));
+        assert_eq!((true, 20), eng.leader_rank(&2));
+    }
+
+    Ok(())
+}
+
+fn s(x: impl ToString) -> String {
+    x.to_string()
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::testing::UTConfig;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::entry::RaftEntry;
+use crate::error::NotInMembers;
+use crate::raft_state::LogStateReader;
+use crate::testing::log_id;
+use crate::utime::UTime;
+use crate::EffectiveMembership;
+use crate::Entry;
+use crate::LogId;
+use crate::Membership;
+use crate::TokioInstant;
+use crate::Vote;
+
+fn m123() -> Membership<UTConfig> {
+    Membership::<UTConfig>::new(vec![btreeset! {1,2,3}], None)
+}
+
+fn m1() -> Membership<UTConfig> {
+    Membership::<UTConfig>::new(vec![btreeset! {1}], None)
+}
+
+/// Node 1 is a follower of the lost leader 2.
+fn eng() -> Engine<UTConfig> {
+    let mut eng = Engine::<UTConfig>::testing_default(0);
+    eng.state.enable_validation(false); // Disable validation for incomplete state
+
+    eng.config.id = 1;
+    eng.state.vote = UTime::new(TokioInstant::now(), Vote::new_committed(2, 2));
+    eng.state.log_ids = LogIdList::new([log_id(1, 1, 1), log_id(2, 2, 3)]);
+    eng.state
+        .membership_state
+        .set_effective(Arc::new(EffectiveMembership::new(Some(log_id(1, 1, 1)), m123())));
+    eng.state.server_state = eng.calc_server_state();
+
+    assert_eq!(ServerState::Follower, eng.state.server_state);
+
+    eng
+}
+
+#[test]
+fn test_force_reset_membership() -> anyhow::Result<()> {
+    tracing::info!("--- append the membership with a new term, then elect with it");
+    {
+        let mut eng = eng();
+
+        eng.force_reset_membership(Entry::<UTConfig>::new_membership(LogId::default(), m1()))?;
+
+        assert_eq!(Some(log_id(3, 1, 4)), eng.state.get_log_id(4));
+        assert_eq!(Some(&log_id(4, 1, 5)), eng.state.last_log_id());
+        assert_eq!(&m1(), eng.state.membership_state.effective().membership());
+        assert_eq!(Vote::new_committed(4, 1), *eng.state.vote_ref());
+        assert_eq!(ServerState::Leader, eng.state.server_state);
+
+        assert_eq!(
+            vec![
+                Command::SaveVote { vote: Vote::new(3, 1) },
+                Command::AppendInputEntries {
+                    vote: Vote::new(3, 1),
+                    entries: vec![Entry::<UTConfig>::new_membership(log_id(3, 1, 4), m1())],
+                },
+                Command::SaveVote { vote: Vote::new(4, 1) },
+                Command::SaveVote {
+                    vote: Vote::new_committed(4, 1),
+                },
+                Command::BecomeLeader,
+                Command::RebuildReplicationStreams { targets: vec![] },
+                Command::AppendInputEntries {
+                    vote: Vote::new_committed(4, 1),
+                    entries: vec![Entry::<UTConfig>::new_blank(log_id(4, 1, 5))]
+                },
+            ],
+            eng.output.take_commands()
+        );
+    }
+
+    tracing::info!("--- not a voter in the new membership");
+    {
+        let mut eng = eng();
+        let m23 = Membership::<UTConfig>::new(vec![btreeset! {2,3}], None);
+
+        let res = eng.force_reset_membership(Entry::<UTConfig>::new_membership(LogId::default(), m23.clone()));
+
+        assert_eq!(
+            Err(NotInMembers {
+                node_id: 1,
+                membership: m23
+            }),
+            res
+        );
+        assert_eq!(Vote::new_committed(2, 2), *eng.state.vote_ref());
+        assert!(eng.output.take_commands().is_empty());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::testing::UTConfig;
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::raft::TransferLeaderRequest;
+use crate::raft::VoteRequest;
+use crate::testing::log_id;
+use crate::utime::UTime;
+use crate::EffectiveMembership;
+use crate::Membership;
+use crate::TokioInstant;
+use crate::Vote;
+
+fn m123() -> Membership<UTConfig> {
+    Membership::<UTConfig>::new(vec![btreeset! {1,2,3}], None)
+}
+
+/// A follower of leader 2, with a leader lease that has not yet expired.
+fn eng() -> Engine<UTConfig> {
+    let mut eng = Engine::testing_default(0);
+    eng.state.enable_validation(false); // Disable validation for incomplete state
+
+    eng.config.id = 1;
+    eng.state.vote = UTime::new(TokioInstant::now(), Vote::new_committed(2, 2));
+    eng.state.server_state = ServerState::Follower;
+    eng.state.log_ids = LogIdList::new(vec![log_id(1, 1, 1), log_id(2, 2, 3)]);
+    eng.state
+        .membership_state
+        .set_effective(Arc::new(EffectiveMembership::new(Some(log_id(1, 1, 1)), m123())));
+
+    eng
+}
+
+fn req(from_leader: Vote<u64>, to: u64, last_log_id: u64) -> TransferLeaderRequest<UTConfig> {
+    TransferLeaderRequest {
+        from_leader,
+        to,
+        last_log_id: Some(log_id(2, 2, last_log_id)),
+        context: Default::default(),
+    }
+}
+
+#[test]
+fn test_handle_transfer_leader() -> anyhow::Result<()> {
+    tracing::info!("--- from a different leader: ignored");
+    {
+        let mut eng = eng();
+
+        eng.handle_transfer_leader(&req(Vote::new_committed(1, 2), 1, 3));
+
+        assert_eq!(None, eng.transferring_to);
+        assert_eq!(Vote::new_committed(2, 2), *eng.state.vote_ref());
+        assert!(eng.output.take_commands().is_empty());
+    }
+
+    tracing::info!("--- to another node: grant its vote regardless of the leader lease");
+    {
+        let mut eng = eng();
+
+        eng.handle_transfer_leader(&req(Vote::new_committed(2, 2), 3, 3));
+
+        assert_eq!(Some((Vote::new_committed(2, 2), 3)), eng.transferring_to);
+        assert_eq!(Vote::new_committed(2, 2), *eng.state.vote_ref());
+        assert!(eng.output.take_commands().is_empty());
+
+        let resp = eng.handle_vote_req(VoteRequest::new(Vote::new(3, 3), Some(log_id(2, 2, 3))));
+        assert!(resp.vote_granted);
+        assert_eq!(Vote::new(3, 3), *eng.state.vote_ref());
+    }
+
+    tracing::info!("--- lease is not ignored for a node other than the target");
+    {
+        let mut eng = eng();
+
+        eng.handle_transfer_leader(&req(Vote::new_committed(2, 2), 3, 3));
+
+        let resp = eng.handle_vote_req(VoteRequest::new(Vote::new(3, 2), Some(log_id(2, 2, 3))));
+        assert!(!resp.vote_granted);
+        assert_eq!(Vote::new_committed(2, 2), *eng.state.vote_ref());
+    }
+
+    tracing::info!("--- to this node, without all logs of the leader: do not elect");
+    {
+        let mut eng = eng();
+
+        eng.handle_transfer_leader(&req(Vote::new_committed(2, 2), 1, 4));
+
+        assert_eq!(Vote::new_committed(2, 2), *eng.state.vote_ref());
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+    }
+
+    tracing::info!("--- to this node, with all logs of the leader: elect at once");
+    {
+        let mut eng = eng();
+
+        eng.handle_transfer_leader(&req(Vote::new_committed(2, 2), 1, 3));
+
+        assert_eq!(Vote::new(3, 1), *eng.state.vote_ref());
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+    }
+
+    Ok(())
+}
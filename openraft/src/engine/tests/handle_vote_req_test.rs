@@ -45,6 +45,8 @@ fn test_handle_vote_req_rejected_by_leader_lease() -> anyhow::Result<()> {
     let resp = eng.handle_vote_req(VoteRequest {
         vote: Vote::new(3, 2),
         last_log_id: Some(log_id(2, 1, 3)),
+        pre_vote: false,
+        context: Default::default(),
     });
 
     assert_eq!(
@@ -65,6 +67,74 @@ fn test_handle_vote_req_rejected_by_leader_lease() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_handle_vote_req_granted_within_leader_lease_if_disabled() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.reject_vote_within_lease = false;
+    eng.state.vote.update(TokioInstant::now(), Vote::new_committed(2, 1));
+
+    let resp = eng.handle_vote_req(VoteRequest {
+        vote: Vote::new(3, 2),
+        last_log_id: Some(log_id(2, 1, 3)),
+        pre_vote: false,
+        context: Default::default(),
+    });
+
+    assert_eq!(
+        VoteResponse {
+            vote: Vote::new(3, 2),
+            vote_granted: true,
+            last_log_id: None
+        },
+        resp
+    );
+
+    assert_eq!(Vote::new(3, 2), *eng.state.vote_ref());
+    assert!(eng.internal_server_state.is_following());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_vote_req_one_vote_per_term() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.one_vote_per_term = true;
+
+    let resp = eng.handle_vote_req(VoteRequest {
+        vote: Vote::new(2, 2),
+        last_log_id: Some(log_id(2, 1, 3)),
+        pre_vote: false,
+        context: Default::default(),
+    });
+
+    assert_eq!(
+        VoteResponse {
+            vote: Vote::new(2, 1),
+            vote_granted: false,
+            last_log_id: None
+        },
+        resp
+    );
+
+    assert_eq!(Vote::new(2, 1), *eng.state.vote_ref());
+    assert_eq!(0, eng.output.take_commands().len());
+
+    tracing::info!("--- a greater term is granted");
+    {
+        let resp = eng.handle_vote_req(VoteRequest {
+            vote: Vote::new(3, 2),
+            last_log_id: Some(log_id(2, 1, 3)),
+            pre_vote: false,
+            context: Default::default(),
+        });
+
+        assert!(resp.vote_granted);
+        assert_eq!(Vote::new(3, 2), *eng.state.vote_ref());
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_handle_vote_req_reject_smaller_vote() -> anyhow::Result<()> {
     let mut eng = eng();
@@ -72,6 +142,8 @@ fn test_handle_vote_req_reject_smaller_vote() -> anyhow::Result<()> {
     let resp = eng.handle_vote_req(VoteRequest {
         vote: Vote::new(1, 2),
         last_log_id: None,
+        pre_vote: false,
+        context: Default::default(),
     });
 
     assert_eq!(
@@ -100,6 +172,8 @@ fn test_handle_vote_req_reject_smaller_last_log_id() -> anyhow::Result<()> {
     let resp = eng.handle_vote_req(VoteRequest {
         vote: Vote::new(3, 2),
         last_log_id: Some(log_id(1, 1, 3)),
+        pre_vote: false,
+        context: Default::default(),
     });
 
     assert_eq!(
@@ -133,6 +207,8 @@ fn test_handle_vote_req_granted_equal_vote_and_last_log_id() -> anyhow::Result<(
     let resp = eng.handle_vote_req(VoteRequest {
         vote: Vote::new(2, 1),
         last_log_id: Some(log_id(2, 1, 3)),
+        pre_vote: false,
+        context: Default::default(),
     });
 
     assert_eq!(
@@ -166,6 +242,8 @@ fn test_handle_vote_req_granted_greater_vote() -> anyhow::Result<()> {
     let resp = eng.handle_vote_req(VoteRequest {
         vote: Vote::new(3, 1),
         last_log_id: Some(log_id(2, 1, 3)),
+        pre_vote: false,
+        context: Default::default(),
     });
 
     assert_eq!(
@@ -206,6 +284,8 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
         eng.handle_vote_req(VoteRequest {
             vote: Vote::new(3, 1),
             last_log_id: Some(log_id(2, 1, 3)),
+            pre_vote: false,
+            context: Default::default(),
         });
 
         assert_eq!(st, eng.state.server_state);
@@ -230,6 +310,8 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
         eng.handle_vote_req(VoteRequest {
             vote: Vote::new(3, 1),
             last_log_id: Some(log_id(2, 1, 3)),
+            pre_vote: false,
+            context: Default::default(),
         });
 
         assert_eq!(st, eng.state.server_state);
@@ -243,3 +325,53 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
     }
     Ok(())
 }
+
+#[test]
+fn test_handle_vote_req_pre_vote() -> anyhow::Result<()> {
+    tracing::info!("--- granted, without updating the local vote");
+    {
+        let mut eng = eng();
+
+        let resp = eng.handle_vote_req(VoteRequest::new_pre_vote(Vote::new(3, 2), Some(log_id(2, 1, 3))));
+
+        assert_eq!(
+            VoteResponse {
+                vote: Vote::new(2, 1),
+                vote_granted: true,
+                last_log_id: None
+            },
+            resp
+        );
+
+        assert_eq!(Vote::new(2, 1), *eng.state.vote_ref());
+        assert!(eng.internal_server_state.is_leading());
+
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+        assert_eq!(0, eng.output.take_commands().len());
+    }
+
+    tracing::info!("--- rejected by a smaller or equal vote");
+    {
+        let mut eng = eng();
+
+        let resp = eng.handle_vote_req(VoteRequest::new_pre_vote(Vote::new(2, 1), Some(log_id(2, 1, 3))));
+
+        assert!(!resp.vote_granted);
+        assert_eq!(Vote::new(2, 1), *eng.state.vote_ref());
+        assert_eq!(0, eng.output.take_commands().len());
+    }
+
+    tracing::info!("--- rejected by leader lease");
+    {
+        let mut eng = eng();
+        eng.state.vote.update(TokioInstant::now(), Vote::new_committed(2, 1));
+
+        let resp = eng.handle_vote_req(VoteRequest::new_pre_vote(Vote::new(3, 2), Some(log_id(2, 1, 3))));
+
+        assert!(!resp.vote_granted);
+        assert_eq!(Vote::new_committed(2, 1), *eng.state.vote_ref());
+        assert_eq!(0, eng.output.take_commands().len());
+    }
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use std::collections::BTreeSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use maplit::btreeset;
 use pretty_assertions::assert_eq;
@@ -12,6 +13,7 @@ use crate::engine::LogIdList;
 use crate::entry::RaftEntry;
 use crate::progress::entry::ProgressEntry;
 use crate::progress::Inflight;
+use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::raft_state::LogStateReader;
 use crate::testing::log_id;
@@ -273,3 +275,132 @@ fn test_handle_vote_resp() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_handle_vote_resp_duplicate() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.id = 1;
+    eng.state.vote = UTime::new(TokioInstant::now(), Vote::new(2, 1));
+    eng.state
+        .membership_state
+        .set_effective(Arc::new(EffectiveMembership::new(Some(log_id(1, 1, 1)), m12())));
+    eng.vote_handler().become_leading();
+
+    let last_log_id = eng.state.last_log_id().copied();
+
+    eng.internal_server_state.leading_mut().map(|l| {
+        l.initialize_voting(last_log_id, TokioInstant::now());
+        l.voting_mut().unwrap().grant_by(&1)
+    });
+    eng.state.server_state = ServerState::Candidate;
+
+    eng.handle_vote_resp(2, VoteResponse {
+        vote: Vote::new(2, 1),
+        vote_granted: false,
+        last_log_id: None,
+    });
+
+    tracing::info!("--- a hedged request is responded again, only the first response counts");
+
+    eng.handle_vote_resp(2, VoteResponse {
+        vote: Vote::new(2, 1),
+        vote_granted: true,
+        last_log_id: None,
+    });
+
+    assert_eq!(Vote::new(2, 1), *eng.state.vote_ref());
+    assert_eq!(
+        Some(btreeset! {1},),
+        eng.internal_server_state.leading().map(|x| x.voting().unwrap().granters().collect::<BTreeSet<_>>())
+    );
+    assert_eq!(ServerState::Candidate, eng.state.server_state);
+    assert!(eng.output.take_commands().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_resp() -> anyhow::Result<()> {
+    fn pre_voting_eng() -> Engine<UTConfig> {
+        let mut eng = eng();
+        eng.config.id = 1;
+        eng.config.enable_pre_vote = true;
+        eng.state.server_state = ServerState::Follower;
+        eng.state.vote = UTime::new(
+            TokioInstant::now() - Duration::from_millis(1_000),
+            Vote::new_committed(1, 2),
+        );
+        eng.state
+            .membership_state
+            .set_effective(Arc::new(EffectiveMembership::new(Some(log_id(1, 1, 1)), m12())));
+
+        eng.pre_elect();
+
+        assert_eq!(Vote::new_committed(1, 2), *eng.state.vote_ref());
+        assert_eq!(
+            vec![Command::SendVote {
+                vote_req: VoteRequest::new_pre_vote(Vote::new(2, 1), Some(log_id(0, 0, 0)))
+            }],
+            eng.output.take_commands()
+        );
+        eng
+    }
+
+    tracing::info!("--- granted by a quorum: start to elect");
+    {
+        let mut eng = pre_voting_eng();
+
+        eng.handle_pre_vote_resp(2, &Vote::new(2, 1), VoteResponse {
+            vote: Vote::new_committed(1, 2),
+            vote_granted: true,
+            last_log_id: None,
+        });
+
+        assert!(eng.pre_voting.is_none());
+        assert_eq!(Vote::new(2, 1), *eng.state.vote_ref());
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+        assert_eq!(
+            vec![Command::SaveVote { vote: Vote::new(2, 1) }, Command::SendVote {
+                vote_req: VoteRequest::new(Vote::new(2, 1), Some(log_id(0, 0, 0)))
+            },],
+            eng.output.take_commands()
+        );
+    }
+
+    tracing::info!("--- response to another pre-vote: ignore");
+    {
+        let mut eng = pre_voting_eng();
+
+        eng.handle_pre_vote_resp(2, &Vote::new(5, 1), VoteResponse {
+            vote: Vote::new_committed(1, 2),
+            vote_granted: true,
+            last_log_id: None,
+        });
+
+        assert!(eng.pre_voting.is_some());
+        assert_eq!(Vote::new_committed(1, 2), *eng.state.vote_ref());
+        assert!(eng.output.take_commands().is_empty());
+    }
+
+    tracing::info!("--- vote renewed by the leader during pre-vote: do not elect");
+    {
+        let mut eng = pre_voting_eng();
+        eng.state.vote.update(
+            TokioInstant::now() + Duration::from_millis(1),
+            Vote::new_committed(1, 2),
+        );
+
+        eng.handle_pre_vote_resp(2, &Vote::new(2, 1), VoteResponse {
+            vote: Vote::new_committed(1, 2),
+            vote_granted: true,
+            last_log_id: None,
+        });
+
+        assert!(eng.pre_voting.is_none());
+        assert_eq!(Vote::new_committed(1, 2), *eng.state.vote_ref());
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert!(eng.output.take_commands().is_empty());
+    }
+
+    Ok(())
+}
@@ -132,6 +132,8 @@ fn test_initialize() -> anyhow::Result<()> {
                             leader_id: CommittedLeaderId::new(0, 0),
                             index: 0,
                         },),
+                        pre_vote: false,
+                        context: Default::default(),
                     },
                 },
             ],
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::testing::UTConfig;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::testing::log_id;
+use crate::utime::UTime;
+use crate::EffectiveMembership;
+use crate::Membership;
+use crate::TokioInstant;
+use crate::Vote;
+
+fn m12() -> Membership<UTConfig> {
+    Membership::<UTConfig>::new(vec![btreeset! {1,2}], None)
+}
+
+fn eng() -> Engine<UTConfig> {
+    let mut eng = Engine::testing_default(0);
+    eng.state.enable_validation(false); // Disable validation for incomplete state
+
+    eng.config.id = 1;
+    eng.state.vote = UTime::new(TokioInstant::now(), Vote::new_committed(2, 1));
+    eng.state
+        .membership_state
+        .set_effective(Arc::new(EffectiveMembership::new(Some(log_id(1, 1, 1)), m12())));
+    eng.vote_handler().become_leading();
+    eng.output.take_commands();
+
+    assert_eq!(ServerState::Leader, eng.state.server_state);
+
+    eng
+}
+
+#[test]
+fn test_leader_step_down_by_lost_quorum() -> anyhow::Result<()> {
+    tracing::info!("--- leader reverts to candidate without saving vote");
+    {
+        let mut eng = eng();
+
+        eng.leader_step_down_by_lost_quorum();
+
+        assert_eq!(Vote::new(2, 1), *eng.state.vote_ref());
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+        assert!(eng.leader_handler().is_err(), "no longer accepts writes");
+        assert_eq!(vec![Command::QuitLeader], eng.output.take_commands());
+    }
+
+    tracing::info!("--- not a leader: do nothing");
+    {
+        let mut eng = eng();
+        eng.state.vote = UTime::new(TokioInstant::now(), Vote::new(2, 1));
+        eng.state.server_state = ServerState::Candidate;
+
+        eng.leader_step_down_by_lost_quorum();
+
+        assert_eq!(Vote::new(2, 1), *eng.state.vote_ref());
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+        assert!(eng.output.take_commands().is_empty());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_step_down_voluntarily() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    eng.leader_step_down_voluntarily();
+
+    assert_eq!(Vote::new(2, 1), *eng.state.vote_ref());
+    assert_eq!(ServerState::Candidate, eng.state.server_state);
+    assert_eq!(vec![Command::QuitLeader], eng.output.take_commands());
+
+    Ok(())
+}
@@ -53,6 +53,8 @@ fn test_trigger_purge_log_already_scheduled() -> anyhow::Result<()> {
         last_log_id: Some(log_id(1, 0, 3)),
         last_membership: StoredMembership::new(Some(log_id(1, 0, 1)), m12()),
         snapshot_id: "1".to_string(),
+        delta_base: None,
+        checksum: None,
     };
     eng.state.purge_upto = Some(log_id(1, 0, 2));
     eng.state.io_state.purged = Some(log_id(1, 0, 2));
@@ -73,6 +75,8 @@ fn test_trigger_purge_log_delete_only_in_snapshot_logs() -> anyhow::Result<()> {
         last_log_id: Some(log_id(1, 0, 3)),
         last_membership: StoredMembership::new(Some(log_id(1, 0, 1)), m12()),
         snapshot_id: "1".to_string(),
+        delta_base: None,
+        checksum: None,
     };
     eng.state.purge_upto = Some(log_id(1, 0, 2));
     eng.state.io_state.purged = Some(log_id(1, 0, 2));
@@ -101,6 +105,8 @@ fn test_trigger_purge_log_in_used_wont_be_delete() -> anyhow::Result<()> {
         last_log_id: Some(log_id(1, 0, 3)),
         last_membership: StoredMembership::new(Some(log_id(1, 0, 1)), m12()),
         snapshot_id: "1".to_string(),
+        delta_base: None,
+        checksum: None,
     };
     eng.state.purge_upto = Some(log_id(1, 0, 2));
     eng.state.io_state.purged = Some(log_id(1, 0, 2));
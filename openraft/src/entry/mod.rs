@@ -3,15 +3,21 @@
 use std::fmt;
 use std::fmt::Debug;
 
+use anyerror::AnyError;
+
 use crate::log_id::RaftLogId;
 use crate::LogId;
 use crate::Membership;
 use crate::RaftTypeConfig;
 
 pub mod payload;
+mod raw;
 mod traits;
 
 pub use payload::EntryPayload;
+pub use raw::AppDataCodec;
+pub use raw::RawEntry;
+pub use raw::RawPayload;
 pub use traits::FromAppData;
 pub use traits::RaftEntry;
 pub use traits::RaftPayload;
@@ -130,10 +136,10 @@ where C: RaftTypeConfig
 impl<C> FromAppData<C::D> for Entry<C>
 where C: RaftTypeConfig
 {
-    fn from_app_data(d: C::D) -> Self {
-        Entry {
+    fn from_app_data(d: C::D) -> Result<Self, AnyError> {
+        Ok(Entry {
             log_id: LogId::default(),
             payload: EntryPayload::Normal(d),
-        }
+        })
     }
 }
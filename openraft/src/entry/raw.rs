@@ -0,0 +1,314 @@
+//! A log entry that keeps the application data encoded.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::io;
+use std::marker::PhantomData;
+
+use anyerror::AnyError;
+
+use crate::entry::FromAppData;
+use crate::entry::RaftEntry;
+use crate::entry::RaftPayload;
+use crate::log_id::RaftLogId;
+use crate::Entry;
+use crate::EntryPayload;
+use crate::LogId;
+use crate::Membership;
+use crate::OptionalSend;
+use crate::OptionalSync;
+use crate::RaftTypeConfig;
+
+/// Encodes and decodes the application data `D` stored in a [`RawEntry`].
+pub trait AppDataCodec<D>: OptionalSend + OptionalSync + 'static {
+    /// Encode the application data.
+    fn encode(d: &D) -> io::Result<Vec<u8>>;
+
+    /// Decode the application data encoded by [`Self::encode()`].
+    fn decode(buf: &[u8]) -> io::Result<D>;
+}
+
+/// The payload of a [`RawEntry`], in which the application data is kept encoded.
+#[derive(Clone)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum RawPayload<C: RaftTypeConfig> {
+    /// An empty payload committed by a new cluster leader.
+    Blank,
+
+    /// The application data encoded by an [`AppDataCodec`].
+    Normal(Vec<u8>),
+
+    /// A change-membership log entry.
+    Membership(Membership<C>),
+}
+
+/// A Raft log entry that keeps the application data encoded, and decodes it only when it is
+/// applied to the state machine.
+///
+/// With the default [`Entry`], a leader that reads entries from the log store to replicate them
+/// deserializes the application data and then serializes it again to send it. With
+/// `RaftTypeConfig::Entry = RawEntry<Self, X>`, the application data is encoded with `X` once,
+/// when a client write is proposed, and the storage and the network pass the bytes along. The
+/// state machine decodes it with [`RawEntry::decode()`] or [`RawEntry::into_entry()`] when
+/// applying.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    pub log_id: LogId<C::NodeId>,
+
+    /// This entry's payload.
+    pub payload: RawPayload<C>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _p: PhantomData<fn() -> X>,
+}
+
+impl<C, X> RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    /// Create an entry with application data already encoded by `X`.
+    pub fn new_normal(log_id: LogId<C::NodeId>, buf: Vec<u8>) -> Self {
+        Self::new(log_id, RawPayload::Normal(buf))
+    }
+
+    fn new(log_id: LogId<C::NodeId>, payload: RawPayload<C>) -> Self {
+        Self {
+            log_id,
+            payload,
+            _p: PhantomData,
+        }
+    }
+
+    /// Decode the application data, or returns `None` if it is not a normal entry.
+    pub fn decode(&self) -> io::Result<Option<C::D>> {
+        match &self.payload {
+            RawPayload::Normal(buf) => X::decode(buf).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Convert it to an [`Entry`] with the application data decoded.
+    pub fn into_entry(self) -> io::Result<Entry<C>> {
+        let payload = match self.payload {
+            RawPayload::Blank => EntryPayload::Blank,
+            RawPayload::Normal(buf) => EntryPayload::Normal(X::decode(&buf)?),
+            RawPayload::Membership(m) => EntryPayload::Membership(m),
+        };
+        Ok(Entry {
+            log_id: self.log_id,
+            payload,
+        })
+    }
+}
+
+impl<C, X> Clone for RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.log_id, self.payload.clone())
+    }
+}
+
+impl<C, X> PartialEq for RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.log_id == other.log_id && self.payload == other.payload
+    }
+}
+
+impl<C, X> Eq for RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+}
+
+impl<C, X> Debug for RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawEntry").field("log_id", &self.log_id).field("payload", &self.payload).finish()
+    }
+}
+
+impl<C, X> fmt::Display for RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.log_id, self.payload)
+    }
+}
+
+impl<C: RaftTypeConfig> Debug for RawPayload<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RawPayload::Blank => write!(f, "blank"),
+            RawPayload::Normal(buf) => write!(f, "normal({} bytes)", buf.len()),
+            RawPayload::Membership(c) => write!(f, "membership:{:?}", c),
+        }
+    }
+}
+
+impl<C: RaftTypeConfig> fmt::Display for RawPayload<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RawPayload::Blank => write!(f, "blank"),
+            RawPayload::Normal(buf) => write!(f, "normal({} bytes)", buf.len()),
+            RawPayload::Membership(c) => write!(f, "membership:{}", c),
+        }
+    }
+}
+
+impl<C, X> RaftPayload<C> for RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    fn is_blank(&self) -> bool {
+        matches!(self.payload, RawPayload::Blank)
+    }
+
+    fn get_membership(&self) -> Option<&Membership<C>> {
+        if let RawPayload::Membership(m) = &self.payload {
+            Some(m)
+        } else {
+            None
+        }
+    }
+}
+
+impl<C, X> RaftLogId<C::NodeId> for RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    fn get_log_id(&self) -> &LogId<C::NodeId> {
+        &self.log_id
+    }
+
+    fn set_log_id(&mut self, log_id: &LogId<C::NodeId>) {
+        self.log_id = *log_id;
+    }
+}
+
+impl<C, X> RaftEntry<C> for RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    fn new_blank(log_id: LogId<C::NodeId>) -> Self {
+        Self::new(log_id, RawPayload::Blank)
+    }
+
+    fn new_membership(log_id: LogId<C::NodeId>, m: Membership<C>) -> Self {
+        Self::new(log_id, RawPayload::Membership(m))
+    }
+
+    fn size_hint(&self) -> usize {
+        let data = match &self.payload {
+            RawPayload::Normal(buf) => buf.len(),
+            _ => 0,
+        };
+        std::mem::size_of_val(self) + data
+    }
+}
+
+impl<C, X> FromAppData<C::D> for RawEntry<C, X>
+where
+    C: RaftTypeConfig,
+    X: AppDataCodec<C::D>,
+{
+    /// Encode the application data with `X`.
+    fn from_app_data(d: C::D) -> Result<Self, AnyError> {
+        let buf = X::encode(&d).map_err(|e| AnyError::new(&e))?;
+        Ok(Self::new_normal(LogId::default(), buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Cursor;
+
+    use super::AppDataCodec;
+    use super::RawEntry;
+    use super::RawPayload;
+    use crate::entry::FromAppData;
+    use crate::entry::RaftEntry;
+    use crate::entry::RaftPayload;
+    use crate::testing::log_id;
+    use crate::Entry;
+    use crate::EntryPayload;
+    use crate::RaftLogId;
+
+    crate::declare_raft_types!(TC: D = String);
+
+    struct Utf8;
+
+    impl AppDataCodec<String> for Utf8 {
+        fn encode(d: &String) -> io::Result<Vec<u8>> {
+            Ok(d.as_bytes().to_vec())
+        }
+
+        fn decode(buf: &[u8]) -> io::Result<String> {
+            String::from_utf8(buf.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+    }
+
+    #[test]
+    fn test_raw_entry() -> anyhow::Result<()> {
+        let mut ent = RawEntry::<TC, Utf8>::from_app_data("foo".to_string())?;
+        ent.set_log_id(&log_id(1, 1, 2));
+
+        assert_eq!(RawPayload::Normal(b"foo".to_vec()), ent.payload);
+        assert!(!ent.is_blank());
+        assert_eq!(Some("foo".to_string()), ent.decode()?);
+        assert_eq!(std::mem::size_of_val(&ent) + 3, ent.size_hint());
+
+        let e = ent.into_entry()?;
+        assert_eq!(log_id(1, 1, 2), e.log_id);
+        assert_eq!(EntryPayload::<TC>::Normal("foo".to_string()), e.payload);
+
+        let blank = RawEntry::<TC, Utf8>::new_blank(log_id(1, 1, 3));
+        assert!(blank.is_blank());
+        assert_eq!(None, blank.decode()?);
+        assert_eq!(Entry::<TC>::new_blank(log_id(1, 1, 3)), blank.into_entry()?);
+
+        Ok(())
+    }
+
+    struct Unencodable;
+
+    impl AppDataCodec<String> for Unencodable {
+        fn encode(_d: &String) -> io::Result<Vec<u8>> {
+            Err(io::Error::new(io::ErrorKind::InvalidInput, "unencodable"))
+        }
+
+        fn decode(_buf: &[u8]) -> io::Result<String> {
+            unreachable!("nothing is encoded")
+        }
+    }
+
+    #[test]
+    fn test_raw_entry_from_app_data_encode_error() {
+        let res = RawEntry::<TC, Unencodable>::from_app_data("foo".to_string());
+
+        let err = res.unwrap_err();
+        assert!(err.to_string().contains("unencodable"), "got: {}", err);
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 use std::fmt::Display;
 
+use anyerror::AnyError;
+
 use crate::log_id::RaftLogId;
 use crate::LogId;
 use crate::Membership;
@@ -35,6 +37,16 @@ where
     ///
     /// The returned instance must return `Some()` for `Self::get_membership()`.
     fn new_membership(log_id: LogId<C::NodeId>, m: Membership<C>) -> Self;
+
+    /// Returns the approximate number of bytes of this entry when it is sent to another node.
+    ///
+    /// It is used to enforce
+    /// [`Config::replication_rate_limit_bytes_per_sec`](`crate::Config::replication_rate_limit_bytes_per_sec`).
+    /// The default is the in-memory size of the entry, which does not include the data on the
+    /// heap. An application with large entries should return a more accurate size.
+    fn size_hint(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
 }
 
 /// Build a raft log entry from app data.
@@ -42,5 +54,13 @@ where
 /// A concrete Entry should implement this trait to let openraft create an entry when needed.
 pub trait FromAppData<T> {
     /// Build a raft log entry from app data.
-    fn from_app_data(t: T) -> Self;
+    ///
+    /// It is called on the caller side of [`Raft::client_write()`], and an error, e.g., the app
+    /// data can not be encoded, is returned to that client as
+    /// [`ClientWriteError::InvalidAppData`].
+    ///
+    /// [`Raft::client_write()`]: crate::Raft::client_write
+    /// [`ClientWriteError::InvalidAppData`]: crate::error::ClientWriteError::InvalidAppData
+    fn from_app_data(t: T) -> Result<Self, AnyError>
+    where Self: Sized;
 }
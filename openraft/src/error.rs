@@ -15,6 +15,7 @@ use anyerror::AnyError;
 
 pub use self::replication_closed::ReplicationClosed;
 pub use self::streaming_error::StreamingError;
+use crate::display_ext::DisplayOptionExt;
 use crate::network::RPCTypes;
 use crate::raft::AppendEntriesResponse;
 use crate::raft_types::SnapshotSegmentId;
@@ -109,6 +110,18 @@ where
     }
 }
 
+impl<C> RaftError<C, Infallible>
+where C: RaftTypeConfig
+{
+    /// Convert to a `RaftError` with any API error type, since there is no API error in it.
+    pub(crate) fn with_api_error<E>(self) -> RaftError<C, E> {
+        match self {
+            RaftError::APIError(e) => match e {},
+            RaftError::Fatal(f) => RaftError::Fatal(f),
+        }
+    }
+}
+
 impl<C, E> From<StorageError<C::NodeId>> for RaftError<C, E>
 where C: RaftTypeConfig
 {
@@ -117,6 +130,23 @@ where C: RaftTypeConfig
     }
 }
 
+/// An RPC is rejected because it is not authenticated by the [`RpcAuthenticator`].
+///
+/// It is returned only by the RPC handlers of [`Raft`], such as [`Raft::append_entries()`]. A
+/// [`RemoteError`] of it is converted to an [`RPCError::Network`] on the sending side, since the
+/// sender can not do anything with it but retry.
+///
+/// [`RpcAuthenticator`]: crate::network::RpcAuthenticator
+/// [`Raft`]: crate::Raft
+/// [`Raft::append_entries()`]: crate::Raft::append_entries
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[error("{rpc_type} is rejected: {source}")]
+pub struct Unauthenticated {
+    pub rpc_type: RPCTypes,
+    pub source: AnyError,
+}
+
 /// Fatal is unrecoverable and shuts down raft at once.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
@@ -141,6 +171,9 @@ where C: RaftTypeConfig
 pub enum InstallSnapshotError {
     #[error(transparent)]
     SnapshotMismatch(#[from] SnapshotMismatch),
+
+    #[error(transparent)]
+    Unauthenticated(#[from] Unauthenticated),
 }
 
 /// An error related to a is_leader request.
@@ -180,6 +213,119 @@ where C: RaftTypeConfig
     /// When writing a change-membership entry.
     #[error(transparent)]
     ChangeMembershipError(#[from] ChangeMembershipError<C>),
+
+    /// The entry is committed but the state machine rejected it.
+    #[error(transparent)]
+    ApplyError(#[from] ApplyError<C>),
+
+    /// The entry is not committed before the deadline of the request.
+    #[error(transparent)]
+    Timeout(#[from] ClientWriteTimeout<C>),
+
+    /// The application data can not be converted to a log entry.
+    #[error(transparent)]
+    InvalidAppData(#[from] InvalidAppData),
+}
+
+/// The state machine rejected a committed log entry with an application error.
+///
+/// See [`RaftStateMachine::try_apply()`](`crate::storage::RaftStateMachine::try_apply`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("state machine rejected log {log_id}: {error}")]
+pub struct ApplyError<C>
+where C: RaftTypeConfig
+{
+    /// The id of the rejected log entry.
+    pub log_id: LogId<C::NodeId>,
+
+    /// The error returned by the state machine.
+    pub error: C::AppError,
+}
+
+/// A client write is not committed before its deadline, and no response will be sent for it.
+///
+/// The entry may still be committed later, e.g., by the next leader.
+///
+/// See [`Raft::client_write_with_deadline()`](`crate::Raft::client_write_with_deadline`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("log {} is not committed before the deadline", log_id.display())]
+pub struct ClientWriteTimeout<C>
+where C: RaftTypeConfig
+{
+    /// The id of the log entry proposed by the request.
+    ///
+    /// It is `None` if the caller gives up waiting before `RaftCore` reports the log id, e.g.,
+    /// `RaftCore` is too busy to receive the request before the deadline.
+    pub log_id: Option<LogId<C::NodeId>>,
+}
+
+/// The application data of a client write can not be converted to a log entry, e.g., it can not be
+/// encoded, and nothing is proposed.
+///
+/// See [`FromAppData::from_app_data()`](`crate::entry::FromAppData::from_app_data`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[error("invalid app data: {source}")]
+pub struct InvalidAppData {
+    pub source: AnyError,
+}
+
+impl InvalidAppData {
+    pub fn new(source: impl Into<AnyError>) -> Self {
+        Self { source: source.into() }
+    }
+}
+
+/// An error when a node sends its snapshot to another node on behalf of the leader.
+///
+/// See [`Raft::transfer_snapshot()`](`crate::Raft::transfer_snapshot`).
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum TransferSnapshotError<C>
+where C: RaftTypeConfig
+{
+    #[error(transparent)]
+    SnapshotTooOld(#[from] SnapshotTooOld<C>),
+
+    /// Failed to send the snapshot to the target.
+    #[error(transparent)]
+    Network(#[from] NetworkError),
+
+    #[error(transparent)]
+    Unauthenticated(#[from] Unauthenticated),
+}
+
+/// An error when transferring the leadership to another node.
+///
+/// See [`Raft::transfer_leadership()`](`crate::Raft::transfer_leadership`).
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum TransferLeaderError<C>
+where C: RaftTypeConfig
+{
+    #[error(transparent)]
+    ForwardToLeader(#[from] ForwardToLeader<C>),
+
+    #[error(transparent)]
+    NotVoter(#[from] NotVoter<C>),
+
+    #[error(transparent)]
+    Timeout(#[from] TransferLeaderTimeout<C>),
+}
+
+impl<C> TryAsRef<ForwardToLeader<C>> for TransferLeaderError<C>
+where C: RaftTypeConfig
+{
+    fn try_as_ref(&self) -> Option<&ForwardToLeader<C>> {
+        match self {
+            Self::ForwardToLeader(f) => Some(f),
+            _ => None,
+        }
+    }
 }
 
 impl<C> TryAsRef<ForwardToLeader<C>> for ClientWriteError<C>
@@ -205,6 +351,18 @@ pub enum ChangeMembershipError<C: RaftTypeConfig> {
 
     #[error(transparent)]
     LearnerNotFound(#[from] LearnerNotFound<C>),
+
+    #[error(transparent)]
+    InvalidQuorumPolicy(#[from] InvalidQuorumPolicy),
+
+    #[error(transparent)]
+    UnsafeMembershipChange(#[from] UnsafeMembershipChange),
+
+    #[error(transparent)]
+    ChangeMembershipTooFrequent(#[from] ChangeMembershipTooFrequent),
+
+    #[error(transparent)]
+    WitnessCleared(#[from] WitnessCleared<C>),
 }
 
 /// The set of errors which may take place when initializing a pristine Raft node.
@@ -220,6 +378,27 @@ where C: RaftTypeConfig
     NotInMembers(#[from] NotInMembers<C>),
 }
 
+/// The errors returned by [`Bootstrap::run()`](`crate::bootstrap::Bootstrap::run`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BootstrapError<C>
+where C: RaftTypeConfig
+{
+    #[error(transparent)]
+    Fatal(#[from] Fatal<C>),
+
+    /// The chosen node failed to initialize the cluster.
+    #[error(transparent)]
+    Initialize(#[from] RaftError<C, InitializeError<C>>),
+
+    /// The [`Discovery`](`crate::bootstrap::Discovery`) failed to find the seed members.
+    #[error("failed to discover the seed members: {0}")]
+    Discovery(AnyError),
+
+    /// The cluster is not formed within the timeout.
+    #[error("the cluster is not formed within {0:?}")]
+    Timeout(Duration),
+}
+
 /// Error variants related to the Replication.
 #[derive(Debug, thiserror::Error)]
 #[allow(clippy::large_enum_variant)]
@@ -320,6 +499,22 @@ impl<C: RaftTypeConfig, T: Error> RemoteError<C, T> {
     }
 }
 
+impl<C> From<RemoteError<C, RaftError<C, Unauthenticated>>> for RPCError<C, RaftError<C>>
+where C: RaftTypeConfig
+{
+    /// A remote `Unauthenticated` is considered as `RPCError::Network`.
+    fn from(e: RemoteError<C, RaftError<C, Unauthenticated>>) -> Self {
+        match e.source {
+            RaftError::APIError(unauthenticated) => RPCError::Network(NetworkError::new(&unauthenticated)),
+            RaftError::Fatal(fatal) => RPCError::RemoteError(RemoteError {
+                target: e.target,
+                target_node: e.target_node,
+                source: RaftError::Fatal(fatal),
+            }),
+        }
+    }
+}
+
 impl<C, E> From<RemoteError<C, Fatal<C>>> for RemoteError<C, RaftError<C, E>>
 where
     C: RaftTypeConfig,
@@ -564,6 +759,32 @@ pub struct SnapshotMismatch {
     pub got: SnapshotSegmentId,
 }
 
+/// The snapshot of a node does not include the logs the leader asks for.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("snapshot is too old to transfer, expect at least: {}, got: {}", .min_last_log_id.display(), .last_log_id.display())]
+pub struct SnapshotTooOld<C: RaftTypeConfig> {
+    pub min_last_log_id: Option<LogId<C::NodeId>>,
+    pub last_log_id: Option<LogId<C::NodeId>>,
+}
+
+/// The node to transfer the leadership to is not a voter.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("node {node_id} is not a voter and can not become the leader")]
+pub struct NotVoter<C: RaftTypeConfig> {
+    pub node_id: C::NodeId,
+}
+
+/// The target did not become the leader in time, and the leader resumes serving.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("node {to} did not become the leader in {timeout:?}")]
+pub struct TransferLeaderTimeout<C: RaftTypeConfig> {
+    pub to: C::NodeId,
+    pub timeout: Duration,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("not enough for a quorum, cluster: {cluster}, got: {got:?}")]
@@ -610,6 +831,65 @@ where C: RaftTypeConfig
 #[error("new membership can not be empty")]
 pub struct EmptyMembership {}
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[error("invalid quorum policy {policy}: {reason}")]
+pub struct InvalidQuorumPolicy {
+    pub policy: String,
+    pub reason: String,
+}
+
+/// A membership change is refused because it reduces the fault tolerance of the cluster.
+///
+/// See [`Config::membership_change_guard`](`crate::Config::membership_change_guard`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[error("unsafe membership change: {reason}; use change_membership_unguarded() to apply it anyway")]
+pub struct UnsafeMembershipChange {
+    pub reason: String,
+}
+
+/// A membership change is refused because it turns a witness into a node that is not a witness.
+///
+/// The log of a witness contains no application data, and its state machine is empty. To make it a
+/// normal member, remove it from the membership, and add it back as a learner with empty storage.
+///
+/// See [`Membership::is_witness()`](`crate::Membership::is_witness`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("witness {node_id} can not be turned into a normal member; remove it and add it back as a learner")]
+pub struct WitnessCleared<C: RaftTypeConfig> {
+    pub node_id: C::NodeId,
+}
+
+/// A membership change is refused because the leader has started another one within the minimum
+/// interval.
+///
+/// See [`Config::membership_change_min_interval`](`crate::Config::membership_change_min_interval`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[error("the last membership change started {elapsed:?} ago, retry after the minimum interval {interval:?}")]
+pub struct ChangeMembershipTooFrequent {
+    pub elapsed: Duration,
+    pub interval: Duration,
+}
+
+/// Error returned by [`SessionTable::apply()`] for a request of a client session that can not be
+/// applied.
+///
+/// [`SessionTable::apply()`]: crate::storage::SessionTable::apply
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SessionError {
+    /// The session is not registered, or has been evicted. The client has to register a new one.
+    #[error("session {session_id} is not registered or has expired")]
+    SessionExpired { session_id: u64 },
+
+    /// The client has acknowledged the response of this request, thus it is not cached any more.
+    #[error("request {serial} of session {session_id} is stale, acknowledged up to {acked}")]
+    StaleRequest { session_id: u64, serial: u64, acked: u64 },
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[error("infallible")]
@@ -1,6 +1,14 @@
 //! Collection of implementations of usually used traits defined by Openraft
 
+#[cfg(feature = "async-std-runtime")]
+pub use crate::async_runtime::AsyncStdRuntime;
+#[cfg(feature = "glommio-runtime")]
+pub use crate::async_runtime::GlommioRuntime;
+#[cfg(feature = "madsim-runtime")] pub use crate::async_runtime::MadsimRuntime;
+#[cfg(feature = "monoio-runtime")] pub use crate::async_runtime::MonoioRuntime;
+#[cfg(feature = "smol-runtime")] pub use crate::async_runtime::SmolRuntime;
 pub use crate::async_runtime::TokioRuntime;
+#[cfg(feature = "wasm-runtime")] pub use crate::async_runtime::WasmRuntime;
 pub use crate::entry::Entry;
 pub use crate::node::BasicNode;
 pub use crate::node::EmptyNode;
@@ -57,3 +57,38 @@ impl Instant for tokio::time::Instant {
         tokio::time::Instant::now()
     }
 }
+
+#[cfg(feature = "monoio-runtime")]
+pub type MonoioInstant = monoio::time::Instant;
+
+#[cfg(feature = "monoio-runtime")]
+impl Instant for monoio::time::Instant {
+    #[inline]
+    fn now() -> Self {
+        monoio::time::Instant::now()
+    }
+}
+
+/// `std::time::Instant::now()` panics on `wasm32-unknown-unknown`, where the clock is provided by
+/// the JavaScript `performance.now()`. On other targets it is an alias of [`std::time::Instant`].
+#[cfg(feature = "wasm-runtime")]
+pub type WasmInstant = web_time::Instant;
+
+#[cfg(all(feature = "wasm-runtime", target_arch = "wasm32"))]
+impl Instant for web_time::Instant {
+    #[inline]
+    fn now() -> Self {
+        web_time::Instant::now()
+    }
+}
+
+/// [`std::time::Instant`] is used by runtimes that do not provide their own clock type, such as
+/// `async-std`.
+pub type StdInstant = std::time::Instant;
+
+impl Instant for std::time::Instant {
+    #[inline]
+    fn now() -> Self {
+        std::time::Instant::now()
+    }
+}
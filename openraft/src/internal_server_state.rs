@@ -1,10 +1,11 @@
 use crate::leader::voting::Voting;
 use crate::leader::Leading;
+use crate::membership::PolicyQuorum;
 use crate::quorum::Joint;
 use crate::RaftTypeConfig;
 
 /// The quorum set type used by `Leader`.
-pub(crate) type LeaderQuorumSet<NID> = Joint<NID, Vec<NID>, Vec<Vec<NID>>>;
+pub(crate) type LeaderQuorumSet<NID> = Joint<NID, PolicyQuorum<NID>, Vec<PolicyQuorum<NID>>>;
 
 /// In openraft there are only two state for a server:
 /// Leading(raft leader or raft candidate) and following(raft follower or raft learner):
@@ -7,7 +7,7 @@ use crate::progress::VecProgress;
 use crate::quorum::QuorumSet;
 use crate::type_config::alias::InstantOf;
 use crate::type_config::alias::LogIdOf;
-use crate::Instant;
+use crate::AsyncRuntime;
 use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::RaftLogId;
@@ -106,6 +106,13 @@ where
         self.last_log_id.as_ref()
     }
 
+    /// Return the smallest next log index to replicate to any follower or learner.
+    ///
+    /// The logs before it are replicated to every node.
+    pub(crate) fn min_replicated_next_index(&self) -> u64 {
+        self.progress.iter().map(|(_, p)| p.matching.next_index()).min().unwrap_or_default()
+    }
+
     /// Assign log ids to the entries.
     ///
     /// Return `()` if successful.
@@ -171,7 +178,7 @@ where
 
         // Safe unwrap: voted_for() is always non-None in Openraft
         let node_id = self.vote.leader_id().voted_for().unwrap();
-        let now = Instant::now();
+        let now = C::AsyncRuntime::now();
 
         tracing::debug!(
             leader_id = display(node_id),
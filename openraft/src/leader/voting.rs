@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::fmt;
 
 use crate::display_ext::DisplayOptionExt;
@@ -27,6 +28,11 @@ where
 
     /// Which nodes have granted the the vote at certain time point.
     progress: VecProgress<C::NodeId, bool, bool, QS>,
+
+    /// The nodes that have responded, either granted or not.
+    ///
+    /// With vote hedging, a node may respond more than once to the same vote.
+    responded: BTreeSet<C::NodeId>,
 }
 
 impl<C, QS> fmt::Display for Voting<C, QS>
@@ -62,9 +68,14 @@ where
             vote,
             last_log_id,
             progress: VecProgress::new(quorum_set, [], false),
+            responded: BTreeSet::new(),
         }
     }
 
+    pub(crate) fn starting_time(&self) -> &InstantOf<C> {
+        &self.starting_time
+    }
+
     pub(crate) fn vote_ref(&self) -> &Vote<C::NodeId> {
         &self.vote
     }
@@ -73,6 +84,11 @@ where
         &self.progress
     }
 
+    /// Record a response from a node, returns `false` if it is a duplicate.
+    pub(crate) fn record_response(&mut self, target: C::NodeId) -> bool {
+        self.responded.insert(target)
+    }
+
     /// Grant the vote by a node.
     pub(crate) fn grant_by(&mut self, target: &C::NodeId) -> bool {
         let granted = *self.progress.update(target, true).expect("target not in quorum set");
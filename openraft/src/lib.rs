@@ -51,6 +51,7 @@ pub(crate) mod type_config;
 pub(crate) mod utime;
 
 pub mod async_runtime;
+pub mod bootstrap;
 #[cfg(feature = "compat")] pub mod compat;
 pub mod docs;
 pub mod entry;
@@ -60,6 +61,7 @@ pub mod instant;
 pub mod log_id;
 pub mod metrics;
 pub mod network;
+#[cfg(feature = "protobuf")] pub mod protobuf;
 pub mod raft;
 pub mod storage;
 pub mod testing;
@@ -71,22 +73,42 @@ pub use anyerror::AnyError;
 pub use openraft_macros::add_async_trait;
 
 pub use crate::async_runtime::AsyncRuntime;
+#[cfg(feature = "async-std-runtime")]
+pub use crate::async_runtime::AsyncStdRuntime;
+#[cfg(feature = "glommio-runtime")]
+pub use crate::async_runtime::GlommioRuntime;
+#[cfg(feature = "madsim-runtime")] pub use crate::async_runtime::MadsimRuntime;
+#[cfg(feature = "monoio-runtime")] pub use crate::async_runtime::MonoioRuntime;
+#[cfg(feature = "smol-runtime")] pub use crate::async_runtime::SmolRuntime;
 pub use crate::async_runtime::TokioRuntime;
+#[cfg(feature = "wasm-runtime")] pub use crate::async_runtime::WasmRuntime;
 pub use crate::change_members::ChangeMembers;
+pub use crate::config::BackoffConfig;
 pub use crate::config::Config;
 pub use crate::config::ConfigError;
+pub use crate::config::ErrorPolicy;
+pub use crate::config::LogCorruptionPolicy;
+pub use crate::config::LogRetentionPolicy;
+pub use crate::config::NoSnapshotTrigger;
 pub use crate::config::SnapshotPolicy;
+pub use crate::config::SnapshotTrigger;
+pub use crate::config::SnapshotTriggerContext;
 pub use crate::core::ServerState;
 pub use crate::entry::Entry;
 pub use crate::entry::EntryPayload;
 pub use crate::instant::Instant;
+#[cfg(feature = "monoio-runtime")] pub use crate::instant::MonoioInstant;
+pub use crate::instant::StdInstant;
 pub use crate::instant::TokioInstant;
+#[cfg(feature = "wasm-runtime")] pub use crate::instant::WasmInstant;
 pub use crate::log_id::LogId;
 pub use crate::log_id::LogIdOptionExt;
 pub use crate::log_id::LogIndexOptionExt;
 pub use crate::log_id::RaftLogId;
 pub use crate::membership::EffectiveMembership;
 pub use crate::membership::Membership;
+pub use crate::membership::MembershipRecord;
+pub use crate::membership::QuorumPolicy;
 pub use crate::membership::StoredMembership;
 pub use crate::metrics::RaftMetrics;
 pub use crate::network::RPCTypes;
@@ -110,6 +132,7 @@ pub use crate::storage::StorageHelper;
 pub use crate::storage_error::DefensiveError;
 pub use crate::storage_error::ErrorSubject;
 pub use crate::storage_error::ErrorVerb;
+pub use crate::storage_error::LogCorruption;
 pub use crate::storage_error::StorageError;
 pub use crate::storage_error::StorageIOError;
 pub use crate::storage_error::ToStorageResult;
@@ -185,10 +208,10 @@ impl<T> AppData for T where T: OptionalSend + OptionalSync + 'static + OptionalS
 /// their storage layer, up through Raft, and back into their application for returning
 /// data to clients.
 ///
-/// This type must encapsulate both success and error responses, as application specific logic
-/// related to the success or failure of a client request — application specific validation logic,
-/// enforcing of data constraints, and anything of that nature — are expressly out of the realm of
-/// the Raft consensus protocol.
+/// Application specific logic related to the success or failure of a client request —
+/// application specific validation logic, enforcing of data constraints, and anything of that
+/// nature — are expressly out of the realm of the Raft consensus protocol. Thus this type may
+/// encapsulate both success and error responses, or a failure can be returned as an [`AppError`].
 ///
 /// ## Note
 ///
@@ -196,3 +219,22 @@ impl<T> AppData for T where T: OptionalSend + OptionalSync + 'static + OptionalS
 pub trait AppDataResponse: OptionalSend + OptionalSync + 'static + OptionalSerde {}
 
 impl<T> AppDataResponse for T where T: OptionalSend + OptionalSync + 'static + OptionalSerde {}
+
+/// A trait defining the application specific error of applying a client request.
+///
+/// It is returned by [`RaftStateMachine::try_apply()`] when the state machine rejects a log
+/// entry, and is returned to the client in
+/// [`ClientWriteError::ApplyError`](`crate::error::ClientWriteError::ApplyError`).
+///
+/// ## Note
+///
+/// The trait is automatically implemented for all types which satisfy its supertraits.
+///
+/// [`RaftStateMachine::try_apply()`]: crate::storage::RaftStateMachine::try_apply
+pub trait AppError:
+    std::error::Error + Clone + PartialEq + Eq + OptionalSend + OptionalSync + 'static + OptionalSerde
+{
+}
+
+impl<T> AppError for T where T: std::error::Error + Clone + PartialEq + Eq + OptionalSend + OptionalSync + 'static + OptionalSerde
+{}
@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use crate::display_ext::DisplayOptionExt;
 use crate::log_id::RaftLogId;
+use crate::membership::PolicyQuorum;
 use crate::quorum::Joint;
 use crate::quorum::QuorumSet;
 use crate::LogId;
@@ -26,7 +27,10 @@ where C: RaftTypeConfig
     stored_membership: Arc<StoredMembership<C>>,
 
     /// The quorum set built from `membership`.
-    quorum_set: Joint<C::NodeId, Vec<C::NodeId>, Vec<Vec<C::NodeId>>>,
+    quorum_set: Joint<C::NodeId, PolicyQuorum<C::NodeId>, Vec<PolicyQuorum<C::NodeId>>>,
+
+    /// Cache of the voter ids of every config.
+    joint_config: Vec<Vec<C::NodeId>>,
 
     /// Cache of union of all members
     voter_ids: BTreeSet<C::NodeId>,
@@ -73,16 +77,17 @@ where C: RaftTypeConfig
         let voter_ids = membership.voter_ids().collect();
 
         let configs = membership.get_joint_config();
-        let mut joint = vec![];
+        let mut joint_config = vec![];
         for c in configs {
-            joint.push(c.iter().copied().collect::<Vec<_>>());
+            joint_config.push(c.iter().copied().collect::<Vec<_>>());
         }
 
-        let quorum_set = Joint::from(joint);
+        let quorum_set = membership.to_quorum_set();
 
         Self {
             stored_membership: Arc::new(StoredMembership::new(log_id, membership)),
             quorum_set,
+            joint_config,
             voter_ids,
         }
     }
@@ -133,12 +138,27 @@ where C: RaftTypeConfig
         self.membership().nodes()
     }
 
+    /// Returns the election priority of a node, `0` if it is not set.
+    pub fn election_priority(&self, node_id: &C::NodeId) -> u64 {
+        self.membership().election_priority(node_id)
+    }
+
+    /// Returns true if the node is a witness, which never becomes the leader.
+    pub fn is_witness(&self, node_id: &C::NodeId) -> bool {
+        self.membership().is_witness(node_id)
+    }
+
+    /// Returns the highest election priority among the voters.
+    pub(crate) fn max_voter_priority(&self) -> u64 {
+        self.membership().max_voter_priority()
+    }
+
     /// Returns reference to the joint config.
     ///
     /// Membership is defined by a joint of multiple configs.
     /// Each config is a vec of node-id.
     pub fn get_joint_config(&self) -> &Vec<Vec<C::NodeId>> {
-        self.quorum_set.children()
+        &self.joint_config
     }
 }
 
@@ -1,11 +1,16 @@
 use core::fmt;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::sync::Arc;
 
 use crate::error::ChangeMembershipError;
 use crate::error::EmptyMembership;
+use crate::error::InvalidQuorumPolicy;
 use crate::error::LearnerNotFound;
+use crate::error::WitnessCleared;
 use crate::membership::IntoNodes;
+use crate::membership::PolicyQuorum;
+use crate::membership::QuorumPolicy;
 use crate::quorum::AsJoint;
 use crate::quorum::FindCoherent;
 use crate::quorum::Joint;
@@ -16,7 +21,8 @@ use crate::RaftTypeConfig;
 /// The membership configuration of the cluster.
 ///
 /// It could be a joint of one, two or more configs, i.e., a quorum is a node set that is superset
-/// of a majority of every config.
+/// of a quorum of every config. By default a quorum of a config is a majority of it, see
+/// [`QuorumPolicy`].
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct Membership<C>
@@ -31,6 +37,30 @@ where C: RaftTypeConfig
     ///
     /// A node-id key that is in `nodes` but is not in `configs` is a **learner**.
     nodes: BTreeMap<C::NodeId, C::Node>,
+
+    /// Election priority of nodes. A node that is not in it has the lowest priority `0`.
+    ///
+    /// See [`Membership::election_priority()`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    priorities: BTreeMap<C::NodeId, u64>,
+
+    /// Voters that only take part in voting and committing, without storing application data.
+    ///
+    /// See [`Membership::is_witness()`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    witnesses: BTreeSet<C::NodeId>,
+
+    /// The failure zone of nodes, e.g., an availability zone or a rack.
+    ///
+    /// See [`Membership::zone()`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    zones: BTreeMap<C::NodeId, String>,
+
+    /// Defines the quorums of every config.
+    ///
+    /// See [`QuorumPolicy`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    quorum_policy: QuorumPolicy<C::NodeId>,
 }
 
 impl<C> From<BTreeMap<C::NodeId, C::Node>> for Membership<C>
@@ -87,7 +117,25 @@ where C: RaftTypeConfig
                 write!(f, "None")?;
             }
         }
-        write!(f, "]}}")?;
+        write!(f, "]")?;
+
+        if !self.priorities.is_empty() {
+            write!(f, ", priorities:{:?}", self.priorities)?;
+        }
+
+        if !self.witnesses.is_empty() {
+            write!(f, ", witnesses:{:?}", self.witnesses)?;
+        }
+
+        if !self.zones.is_empty() {
+            write!(f, ", zones:{:?}", self.zones)?;
+        }
+
+        if self.quorum_policy != QuorumPolicy::Majority {
+            write!(f, ", quorum_policy:{}", self.quorum_policy)?;
+        }
+
+        write!(f, "}}")?;
         Ok(())
     }
 }
@@ -113,7 +161,14 @@ where C: RaftTypeConfig
         let voter_ids = config.as_joint().ids().collect::<BTreeSet<_>>();
         let nodes = Self::extend_nodes(nodes.into_nodes(), &voter_ids.into_nodes());
 
-        Membership { configs: config, nodes }
+        Membership {
+            configs: config,
+            nodes,
+            priorities: BTreeMap::new(),
+            witnesses: BTreeSet::new(),
+            zones: BTreeMap::new(),
+            quorum_policy: QuorumPolicy::Majority,
+        }
     }
 
     /// Returns reference to the joint config.
@@ -146,6 +201,118 @@ where C: RaftTypeConfig
     pub fn learner_ids(&self) -> impl Iterator<Item = C::NodeId> + '_ {
         self.nodes.keys().filter(|x| !self.is_voter(x)).copied()
     }
+
+    /// Returns the election priority of a node, `0` if it is not set.
+    ///
+    /// A voter with a lower priority than the highest one waits longer before starting an
+    /// election, and the leader hands over its leadership to a voter with a higher priority once
+    /// the voter has caught up. Priorities are set with [`ChangeMembers::SetPriorities`].
+    pub fn election_priority(&self, node_id: &C::NodeId) -> u64 {
+        self.priorities.get(node_id).copied().unwrap_or_default()
+    }
+
+    /// Returns an Iterator of all nodes that have a non-zero election priority.
+    pub fn priorities(&self) -> impl Iterator<Item = (&C::NodeId, &u64)> {
+        self.priorities.iter()
+    }
+
+    /// Returns a copy with the election priorities of nodes set.
+    ///
+    /// Priorities of nodes that are not in this membership are ignored.
+    pub fn with_priorities(mut self, priorities: BTreeMap<C::NodeId, u64>) -> Self {
+        self.priorities.extend(priorities);
+        self.prune_node_attrs();
+        self
+    }
+
+    /// Returns true if the node is a witness.
+    ///
+    /// A witness is a voter that grants votes and acknowledges logs for the commit quorum, but
+    /// never becomes the leader. The leader replicates only the log ids and membership configs to
+    /// it: every other entry is sent as a blank entry, and a snapshot is sent with empty data.
+    /// Thus the state machine of a witness stays empty, and it has to accept an empty snapshot.
+    ///
+    /// Since a witness does not store the payload, the leader commits an entry only when a voter
+    /// other than itself that is not a witness has accepted it too, if there is such a voter.
+    ///
+    /// Witnesses are set with [`ChangeMembers::SetWitnesses`].
+    pub fn is_witness(&self, node_id: &C::NodeId) -> bool {
+        self.witnesses.contains(node_id)
+    }
+
+    /// Returns an Iterator of all witness node ids.
+    pub fn witness_ids(&self) -> impl Iterator<Item = C::NodeId> + '_ {
+        self.witnesses.iter().copied()
+    }
+
+    /// Returns a copy with `witnesses` added to the witnesses.
+    ///
+    /// A witness stays a witness until it leaves this membership. Nodes that are not in this
+    /// membership are ignored.
+    pub fn with_witnesses(mut self, witnesses: BTreeSet<C::NodeId>) -> Self {
+        self.witnesses.extend(witnesses);
+        self.prune_node_attrs();
+        self
+    }
+
+    /// Returns the failure zone of a node, e.g., an availability zone or a rack, if it is set.
+    ///
+    /// Nodes in the same zone are expected to fail together. Zones are set with
+    /// [`ChangeMembers::SetZones`], and are used by the guard of membership changes, see
+    /// [`Config::membership_change_guard`](`crate::Config::membership_change_guard`).
+    pub fn zone(&self, node_id: &C::NodeId) -> Option<&str> {
+        self.zones.get(node_id).map(|z| z.as_str())
+    }
+
+    /// Returns an Iterator of all nodes that have a zone.
+    pub fn zones(&self) -> impl Iterator<Item = (&C::NodeId, &String)> {
+        self.zones.iter()
+    }
+
+    /// Returns a copy with the zones of nodes set. An empty zone removes the zone of a node.
+    ///
+    /// Zones of nodes that are not in this membership are ignored.
+    pub fn with_zones(mut self, zones: BTreeMap<C::NodeId, String>) -> Self {
+        self.zones.extend(zones);
+        self.prune_node_attrs();
+        self
+    }
+
+    /// Returns the zones whose failure leaves the voters without a quorum.
+    ///
+    /// A zone is critical if, in any config, the voters out of the zone are not a quorum, e.g., a
+    /// zone that holds a majority of the voters.
+    pub(crate) fn critical_zones(&self) -> BTreeSet<String> {
+        let zones = self.zones.values().collect::<BTreeSet<_>>();
+
+        let mut critical = BTreeSet::new();
+        for zone in zones {
+            for c in self.get_joint_config() {
+                let voters = c.iter().copied().collect::<Vec<_>>();
+                let rest = c.iter().filter(|id| self.zone(id) != Some(zone.as_str())).copied().collect();
+
+                if !self.quorum_policy.is_quorum_of(&voters, &rest) {
+                    critical.insert(zone.clone());
+                    break;
+                }
+            }
+        }
+        critical
+    }
+
+    /// Returns the policy that defines the quorums of every config.
+    pub fn quorum_policy(&self) -> &QuorumPolicy<C::NodeId> {
+        &self.quorum_policy
+    }
+
+    /// Returns a copy with the quorum policy replaced by `policy`.
+    ///
+    /// The policy is not validated until this membership is proposed with
+    /// [`ChangeMembers::SetQuorumPolicy`].
+    pub fn with_quorum_policy(mut self, policy: QuorumPolicy<C::NodeId>) -> Self {
+        self.quorum_policy = policy;
+        self
+    }
 }
 
 impl<C> Membership<C>
@@ -171,7 +338,28 @@ where C: RaftTypeConfig
     pub(crate) fn new_unchecked<T>(configs: Vec<BTreeSet<C::NodeId>>, nodes: T) -> Self
     where T: IntoNodes<C::NodeId, C::Node> {
         let nodes = nodes.into_nodes();
-        Membership { configs, nodes }
+        Membership {
+            configs,
+            nodes,
+            priorities: BTreeMap::new(),
+            witnesses: BTreeSet::new(),
+            zones: BTreeMap::new(),
+            quorum_policy: QuorumPolicy::Majority,
+        }
+    }
+
+    /// Returns the highest election priority among the voters.
+    pub(crate) fn max_voter_priority(&self) -> u64 {
+        self.voter_ids().map(|id| self.election_priority(&id)).max().unwrap_or_default()
+    }
+
+    /// Remove the priorities, witness flags and zones of nodes that are no longer in this
+    /// membership, zero priorities and empty zones.
+    fn prune_node_attrs(&mut self) {
+        let nodes = &self.nodes;
+        self.priorities.retain(|id, p| *p > 0 && nodes.contains_key(id));
+        self.witnesses.retain(|id| nodes.contains_key(id));
+        self.zones.retain(|id, z| !z.is_empty() && nodes.contains_key(id));
     }
 
     /// Extends nodes btreemap with another.
@@ -197,9 +385,21 @@ where C: RaftTypeConfig
     /// Ensure the membership config is valid:
     /// - No empty sub-config in it.
     /// - Every voter has a corresponding Node.
+    /// - Every two quorums of every config intersect.
     pub(crate) fn ensure_valid(&self) -> Result<(), ChangeMembershipError<C>> {
         self.ensure_non_empty_config()?;
         self.ensure_voter_nodes().map_err(|nid| LearnerNotFound { node_id: nid })?;
+        self.ensure_valid_quorum_policy()?;
+        Ok(())
+    }
+
+    /// Ensures that the quorum policy defines valid quorums for every config.
+    pub(crate) fn ensure_valid_quorum_policy(&self) -> Result<(), InvalidQuorumPolicy> {
+        for c in self.get_joint_config().iter() {
+            let voters = c.iter().copied().collect::<Vec<_>>();
+            self.quorum_policy.ensure_valid(&voters)?;
+        }
+
         Ok(())
     }
 
@@ -265,7 +465,16 @@ where C: RaftTypeConfig
             }
         };
 
-        Membership::new_unchecked(config, nodes)
+        let mut m = Membership {
+            configs: config,
+            nodes,
+            priorities: self.priorities.clone(),
+            witnesses: self.witnesses.clone(),
+            zones: self.zones.clone(),
+            quorum_policy: self.quorum_policy.clone(),
+        };
+        m.prune_node_attrs();
+        m
     }
 
     /// Apply a change-membership request and return a new instance.
@@ -283,7 +492,7 @@ where C: RaftTypeConfig
 
         let last = self.get_joint_config().last().unwrap().clone();
 
-        let new_membership = match change {
+        let mut new_membership = match change {
             ChangeMembers::AddVoterIds(add_voter_ids) => {
                 let new_voter_ids = last.union(&add_voter_ids).copied().collect::<BTreeSet<_>>();
                 self.next_coherent(new_voter_ids, retain)
@@ -301,6 +510,12 @@ where C: RaftTypeConfig
                 self.next_coherent(new_voter_ids, retain)
             }
             ChangeMembers::ReplaceAllVoters(all_voter_ids) => self.next_coherent(all_voter_ids, retain),
+            ChangeMembers::ReplaceVoter(old_voter_id, new_voter_id) => {
+                let mut new_voter_ids = last;
+                new_voter_ids.remove(&old_voter_id);
+                new_voter_ids.insert(new_voter_id);
+                self.next_coherent(new_voter_ids, retain)
+            }
             ChangeMembers::AddNodes(add_nodes) => {
                 // When adding nodes, do not override existing node
                 for (node_id, node) in add_nodes.into_iter() {
@@ -314,6 +529,14 @@ where C: RaftTypeConfig
                 }
                 self
             }
+            ChangeMembers::UpdateNodes(update_nodes) => {
+                for (node_id, node) in update_nodes.into_iter() {
+                    if let Some(n) = self.nodes.get_mut(&node_id) {
+                        *n = node;
+                    }
+                }
+                self
+            }
             ChangeMembers::RemoveNodes(remove_node_ids) => {
                 for node_id in remove_node_ids.iter() {
                     self.nodes.remove(node_id);
@@ -324,8 +547,26 @@ where C: RaftTypeConfig
                 self.nodes = all_nodes;
                 self
             }
+            ChangeMembers::SetPriorities(priorities) => self.with_priorities(priorities),
+            ChangeMembers::SetWitnesses(witnesses) => {
+                if let Some(node_id) = self.witnesses.difference(&witnesses).next() {
+                    return Err(WitnessCleared { node_id: *node_id }.into());
+                }
+                self.with_witnesses(witnesses)
+            }
+            ChangeMembers::SetZones(zones) => self.with_zones(zones),
+            ChangeMembers::SetQuorumPolicy(policy) => {
+                for c in self.get_joint_config().iter() {
+                    let voters = c.iter().copied().collect::<Vec<_>>();
+                    policy.ensure_intersect(&self.quorum_policy, &voters)?;
+                }
+                self.with_quorum_policy(policy)
+            }
         };
 
+        // Nodes may have been removed.
+        new_membership.prune_node_attrs();
+
         tracing::debug!(new_membership = display(&new_membership), "new membership");
 
         new_membership.ensure_valid()?;
@@ -333,11 +574,13 @@ where C: RaftTypeConfig
         Ok(new_membership)
     }
 
-    /// Build a QuorumSet from current joint config
-    pub(crate) fn to_quorum_set(&self) -> Joint<C::NodeId, Vec<C::NodeId>, Vec<Vec<C::NodeId>>> {
+    /// Build a QuorumSet from current joint config and the quorum policy.
+    pub(crate) fn to_quorum_set(&self) -> Joint<C::NodeId, PolicyQuorum<C::NodeId>, Vec<PolicyQuorum<C::NodeId>>> {
+        let policy = Arc::new(self.quorum_policy.clone());
+
         let mut qs = vec![];
         for c in self.get_joint_config().iter() {
-            qs.push(c.iter().copied().collect::<Vec<_>>());
+            qs.push(PolicyQuorum::new(c.iter().copied().collect::<Vec<_>>(), policy.clone()));
         }
         Joint::new(qs)
     }
@@ -352,6 +595,7 @@ mod tests {
     use crate::error::ChangeMembershipError;
     use crate::error::EmptyMembership;
     use crate::error::LearnerNotFound;
+    use crate::membership::QuorumPolicy;
     use crate::ChangeMembers;
     use crate::Membership;
 
@@ -360,6 +604,10 @@ mod tests {
         let m = Membership::<UTConfig> {
             configs: vec![btreeset! {1,2}],
             nodes: btreemap! {1=>()},
+            priorities: btreemap! {},
+            witnesses: btreeset! {},
+            zones: btreemap! {},
+            quorum_policy: QuorumPolicy::Majority,
         };
         assert_eq!(Err(2), m.ensure_voter_nodes());
         Ok(())
@@ -370,6 +618,10 @@ mod tests {
         let m = || Membership::<UTConfig> {
             configs: vec![btreeset! {1,2}],
             nodes: btreemap! {1=>(),2=>(),3=>()},
+            priorities: btreemap! {},
+            witnesses: btreeset! {},
+            zones: btreemap! {},
+            quorum_policy: QuorumPolicy::Majority,
         };
 
         // Add: no such learner
@@ -387,7 +639,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}, btreeset! {1,2,3}],
-                    nodes: btreemap! {1=>(),2=>(),3=>()}
+                    nodes: btreemap! {1=>(),2=>(),3=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -399,7 +655,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}, btreeset! {1,2,5}],
-                    nodes: btreemap! {1=>(),2=>(),3=>(),5=>()}
+                    nodes: btreemap! {1=>(),2=>(),3=>(),5=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -411,7 +671,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}],
-                    nodes: btreemap! {1=>(),2=>(),3=>()}
+                    nodes: btreemap! {1=>(),2=>(),3=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -429,7 +693,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}, btreeset! {2}],
-                    nodes: btreemap! {1=>(),2=>(),3=>()}
+                    nodes: btreemap! {1=>(),2=>(),3=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -441,7 +709,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}, btreeset! {2}],
-                    nodes: btreemap! {1=>(),2=>(),3=>()}
+                    nodes: btreemap! {1=>(),2=>(),3=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -452,12 +724,20 @@ mod tests {
             let mem = Membership::<UTConfig> {
                 configs: vec![btreeset! {1,2}, btreeset! {2}],
                 nodes: btreemap! {1=>(),2=>(),3=>()},
+                priorities: btreemap! {},
+                witnesses: btreeset! {},
+                zones: btreemap! {},
+                quorum_policy: QuorumPolicy::Majority,
             };
             let res = mem.change(ChangeMembers::RemoveVoters(btreeset! {1}), false);
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {2}],
-                    nodes: btreemap! {2=>(),3=>()}
+                    nodes: btreemap! {2=>(),3=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -469,7 +749,36 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}, btreeset! {2}],
-                    nodes: btreemap! {1=>(),2=>(),3=>()}
+                    nodes: btreemap! {1=>(),2=>(),3=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
+                }),
+                res
+            );
+        }
+
+        // ReplaceVoter: not a learner
+        {
+            let res = m().change(ChangeMembers::ReplaceVoter(1, 4), false);
+            assert_eq!(
+                Err(ChangeMembershipError::LearnerNotFound(LearnerNotFound { node_id: 4 })),
+                res
+            );
+        }
+
+        // ReplaceVoter: OK, not retain
+        {
+            let res = m().change(ChangeMembers::ReplaceVoter(1, 3), false);
+            assert_eq!(
+                Ok(Membership::<UTConfig> {
+                    configs: vec![btreeset! {1,2}, btreeset! {2,3}],
+                    nodes: btreemap! {1=>(),2=>(),3=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -481,7 +790,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}],
-                    nodes: btreemap! {1=>(),2=>(),3=>()}
+                    nodes: btreemap! {1=>(),2=>(),3=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -493,7 +806,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}],
-                    nodes: btreemap! {1=>(),2=>(),3=>()}
+                    nodes: btreemap! {1=>(),2=>(),3=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -505,7 +822,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}],
-                    nodes: btreemap! {1=>(),2=>(),3=>(), 4=>()}
+                    nodes: btreemap! {1=>(),2=>(),3=>(), 4=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -516,13 +837,46 @@ mod tests {
             let m = || Membership::<UTConfig<u64>> {
                 configs: vec![btreeset! {1,2}],
                 nodes: btreemap! {1=>1,2=>2,3=>3},
+                priorities: btreemap! {},
+                witnesses: btreeset! {},
+                zones: btreemap! {},
+                quorum_policy: QuorumPolicy::Majority,
             };
 
             let res = m().change(ChangeMembers::SetNodes(btreemap! {3=>30, 4=>40}), false);
             assert_eq!(
                 Ok(Membership::<UTConfig<u64>> {
                     configs: vec![btreeset! {1,2}],
-                    nodes: btreemap! {1=>1,2=>2,3=>30, 4=>40}
+                    nodes: btreemap! {1=>1,2=>2,3=>30, 4=>40},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
+                }),
+                res
+            );
+        }
+
+        // UpdateNodes: Ok, unknown node is ignored
+        {
+            let m = || Membership::<UTConfig<u64>> {
+                configs: vec![btreeset! {1,2}],
+                nodes: btreemap! {1=>1,2=>2,3=>3},
+                priorities: btreemap! {},
+                witnesses: btreeset! {},
+                zones: btreemap! {},
+                quorum_policy: QuorumPolicy::Majority,
+            };
+
+            let res = m().change(ChangeMembers::UpdateNodes(btreemap! {2=>20, 3=>30, 4=>40}), false);
+            assert_eq!(
+                Ok(Membership::<UTConfig<u64>> {
+                    configs: vec![btreeset! {1,2}],
+                    nodes: btreemap! {1=>1,2=>20,3=>30},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -543,7 +897,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}],
-                    nodes: btreemap! {1=>(),2=>()}
+                    nodes: btreemap! {1=>(),2=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
@@ -555,7 +913,11 @@ mod tests {
             assert_eq!(
                 Ok(Membership::<UTConfig> {
                     configs: vec![btreeset! {1,2}],
-                    nodes: btreemap! {1=>(),2=>(),4=>()}
+                    nodes: btreemap! {1=>(),2=>(),4=>()},
+                    priorities: btreemap! {},
+                    witnesses: btreeset! {},
+                    zones: btreemap! {},
+                    quorum_policy: QuorumPolicy::Majority
                 }),
                 res
             );
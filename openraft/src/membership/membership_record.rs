@@ -0,0 +1,36 @@
+use std::fmt;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::LogId;
+use crate::Membership;
+use crate::RaftTypeConfig;
+
+/// A committed membership config, and when it was applied to the state machine of this node.
+///
+/// It is returned by [`Raft::membership_history()`](`crate::Raft::membership_history`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct MembershipRecord<C: RaftTypeConfig> {
+    /// The id of the log entry that contains the config.
+    pub log_id: LogId<C::NodeId>,
+
+    /// The membership config.
+    pub membership: Membership<C>,
+
+    /// The wall clock time this node applied the config, or loaded it on startup.
+    pub applied_at: SystemTime,
+}
+
+impl<C> fmt::Display for MembershipRecord<C>
+where C: RaftTypeConfig
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = self.applied_at.duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or_default();
+        write!(
+            f,
+            "MembershipRecord{{log_id:{}, membership:{}, applied_at:{}ms}}",
+            self.log_id, self.membership, millis
+        )
+    }
+}
@@ -6,6 +6,8 @@ use maplit::btreemap;
 use maplit::btreeset;
 
 use crate::engine::testing::UTConfig;
+use crate::error::ChangeMembershipError;
+use crate::error::WitnessCleared;
 use crate::membership::IntoNodes;
 use crate::ChangeMembers;
 use crate::Membership;
@@ -42,6 +44,12 @@ fn test_membership_summary() -> anyhow::Result<()> {
     let m = Membership::<UTConfig>::new(vec![btreeset! {1,2}, btreeset! {3}], Some(btreeset! {4}));
     assert_eq!("{voters:[{1:(),2:()},{3:()}], learners:[4:()]}", m.to_string());
 
+    let m = m.with_priorities(btreemap! {1=>10, 4=>1});
+    assert_eq!(
+        "{voters:[{1:(),2:()},{3:()}], learners:[4:()], priorities:{1: 10, 4: 1}}",
+        m.to_string()
+    );
+
     let m = Membership::<UTConfig<TestNode>>::new_unchecked(vec![btreeset! {1,2}, btreeset! {3}], btreemap! {
         1=>node("127.0.0.1", "k1"),
         2=>node("127.0.0.2", "k2"),
@@ -293,3 +301,129 @@ fn test_membership_next_coherent_with_nodes() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_membership_election_priority() -> anyhow::Result<()> {
+    let m = Membership::<UTConfig>::new(vec![btreeset! {1,2,3}], Some(btreeset! {4}));
+
+    assert_eq!(0, m.election_priority(&1));
+    assert_eq!(0, m.max_voter_priority());
+
+    // Priorities of unknown nodes and zero priorities are not kept.
+    let m = m.with_priorities(btreemap! {1=>10, 2=>0, 4=>20, 5=>30});
+    assert_eq!(10, m.election_priority(&1));
+    assert_eq!(0, m.election_priority(&2));
+    assert_eq!(20, m.election_priority(&4));
+    assert_eq!(vec![(&1, &10), (&4, &20)], m.priorities().collect::<Vec<_>>());
+    assert_eq!(10, m.max_voter_priority(), "learner priority is not counted");
+
+    // SetPriorities: 0 removes a priority
+    let m = m.change(ChangeMembers::SetPriorities(btreemap! {1=>0, 3=>5}), true)?;
+    assert_eq!(vec![(&3, &5), (&4, &20)], m.priorities().collect::<Vec<_>>());
+
+    // Removing a node removes its priority
+    let m = m.change(ChangeMembers::RemoveNodes(btreeset! {4}), true)?;
+    assert_eq!(vec![(&3, &5)], m.priorities().collect::<Vec<_>>());
+
+    // A removed voter that is not retained loses its priority, once it leaves the joint config
+    let m = m.change(ChangeMembers::RemoveVoters(btreeset! {3}), false)?;
+    assert_eq!(5, m.election_priority(&3));
+
+    let m = m.change(ChangeMembers::RemoveVoters(btreeset! {3}), false)?;
+    assert_eq!(0, m.election_priority(&3));
+
+    Ok(())
+}
+
+#[test]
+fn test_membership_witness() -> anyhow::Result<()> {
+    let m = Membership::<UTConfig>::new(vec![btreeset! {1,2,3}], Some(btreeset! {4}));
+    assert!(!m.is_witness(&3));
+
+    // Unknown nodes are ignored
+    let m = m.change(ChangeMembers::SetWitnesses(btreeset! {3,5}), true)?;
+    assert!(m.is_witness(&3));
+    assert_eq!(vec![3], m.witness_ids().collect::<Vec<_>>());
+    assert_eq!(
+        "{voters:[{1:(),2:(),3:()}], learners:[4:()], witnesses:{3}}",
+        m.to_string()
+    );
+
+    // A witness can not be turned into a normal member
+    let res = m.clone().change(ChangeMembers::SetWitnesses(btreeset! {2}), true);
+    assert_eq!(
+        Err(ChangeMembershipError::WitnessCleared(WitnessCleared { node_id: 3 })),
+        res
+    );
+
+    let m = m.change(ChangeMembers::SetWitnesses(btreeset! {2,3}), true)?;
+    assert_eq!(vec![2, 3], m.witness_ids().collect::<Vec<_>>());
+
+    // A witness that leaves the membership is no longer a witness
+    let m = m.change(ChangeMembers::RemoveVoters(btreeset! {3}), false)?;
+    let m = m.change(ChangeMembers::RemoveVoters(btreeset! {3}), false)?;
+    assert!(!m.is_witness(&3));
+    assert_eq!(vec![2], m.witness_ids().collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[test]
+fn test_membership_zone() -> anyhow::Result<()> {
+    let m = Membership::<UTConfig>::new(vec![btreeset! {1,2,3}], Some(btreeset! {4}));
+    assert_eq!(None, m.zone(&1));
+
+    // Zones of unknown nodes and empty zones are not kept.
+    let m = m.change(
+        ChangeMembers::SetZones(btreemap! {1=>s("a"), 2=>s(""), 4=>s("b"), 5=>s("c")}),
+        true,
+    )?;
+    assert_eq!(Some("a"), m.zone(&1));
+    assert_eq!(None, m.zone(&2));
+    assert_eq!(Some("b"), m.zone(&4));
+    assert_eq!(
+        "{voters:[{1:(),2:(),3:()}], learners:[4:()], zones:{1: \"a\", 4: \"b\"}}",
+        m.to_string()
+    );
+
+    // An empty zone removes a zone
+    let m = m.change(ChangeMembers::SetZones(btreemap! {1=>s("")}), true)?;
+    assert_eq!(vec![(&4, &s("b"))], m.zones().collect::<Vec<_>>());
+
+    // Removing a node removes its zone
+    let m = m.change(ChangeMembers::RemoveNodes(btreeset! {4}), true)?;
+    assert_eq!(0, m.zones().count());
+
+    Ok(())
+}
+
+#[test]
+fn test_membership_critical_zones() -> anyhow::Result<()> {
+    let m = Membership::<UTConfig>::new(vec![btreeset! {1,2,3}], Some(btreeset! {4}));
+    assert!(m.critical_zones().is_empty());
+
+    // Zone a holds a majority of the voters.
+    let m = m.change(
+        ChangeMembers::SetZones(btreemap! {1=>s("a"), 2=>s("a"), 3=>s("b"), 4=>s("c")}),
+        true,
+    )?;
+    assert_eq!(btreeset! {s("a")}, m.critical_zones());
+
+    // Every zone holds a minority of the voters.
+    let m = m.change(ChangeMembers::SetZones(btreemap! {2=>s("c")}), true)?;
+    assert!(m.critical_zones().is_empty());
+
+    // In a joint config, a zone is critical if it is critical in any config.
+    let m = Membership::<UTConfig>::new(vec![btreeset! {1,2,3}, btreeset! {1,4}], None);
+    let m = m.change(
+        ChangeMembers::SetZones(btreemap! {1=>s("a"), 2=>s("b"), 3=>s("c")}),
+        true,
+    )?;
+    assert_eq!(btreeset! {s("a")}, m.critical_zones());
+
+    Ok(())
+}
+
+fn s(x: impl ToString) -> String {
+    x.to_string()
+}
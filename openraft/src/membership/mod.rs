@@ -1,6 +1,8 @@
 mod effective_membership;
 mod into_nodes;
 #[allow(clippy::module_inception)] mod membership;
+mod membership_record;
+mod quorum_policy;
 mod stored_membership;
 
 #[cfg(feature = "bench")]
@@ -9,8 +11,12 @@ mod bench;
 
 #[cfg(test)] mod effective_membership_test;
 #[cfg(test)] mod membership_test;
+#[cfg(test)] mod quorum_policy_test;
 
 pub use effective_membership::EffectiveMembership;
 pub use into_nodes::IntoNodes;
 pub use membership::Membership;
+pub use membership_record::MembershipRecord;
+pub(crate) use quorum_policy::PolicyQuorum;
+pub use quorum_policy::QuorumPolicy;
 pub use stored_membership::StoredMembership;
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::InvalidQuorumPolicy;
+use crate::quorum::QuorumSet;
+use crate::NodeId;
+
+/// The maximum number of voters in a config, for which the intersection of quorums is checked by
+/// enumerating every node set.
+const MAX_VOTERS_TO_CHECK: usize = 16;
+
+/// Defines which node sets of a config are quorums.
+///
+/// A policy is stored in [`Membership`](`crate::Membership`) and is applied to every config of a
+/// joint membership. It is used both for electing a leader and for committing logs. Set it with
+/// [`ChangeMembers::SetQuorumPolicy`](`crate::ChangeMembers::SetQuorumPolicy`).
+///
+/// Every two quorums defined by a policy must intersect, otherwise two leaders could be elected
+/// in the same term. This is checked when the policy is set. Except for [`QuorumPolicy::Majority`]
+/// and [`QuorumPolicy::MajorityWith`], it is checked by enumerating every node set, thus such a
+/// policy can not be used with more than 16 voters in a config.
+#[derive(Debug, Clone, Default)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum QuorumPolicy<NID: NodeId> {
+    /// A quorum is a majority of the voters.
+    #[default]
+    Majority,
+
+    /// A quorum is a set of voters whose sum of weights is greater than half of the total.
+    ///
+    /// A voter that is not in the map has a weight of `1`.
+    Weighted(BTreeMap<NID, u64>),
+
+    /// Voters are arranged in rows. A quorum is a set of voters that includes every voter of a
+    /// row, and at least one voter of every row.
+    ///
+    /// A voter that is not in any row forms a row by itself. A voter can not be in more than
+    /// one row.
+    Grid(Vec<BTreeSet<NID>>),
+
+    /// A quorum is a majority of the voters that includes every voter in this set.
+    MajorityWith(BTreeSet<NID>),
+}
+
+impl<NID: NodeId> fmt::Display for QuorumPolicy<NID> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuorumPolicy::Majority => write!(f, "majority"),
+            QuorumPolicy::Weighted(weights) => write!(f, "weighted:{:?}", weights),
+            QuorumPolicy::Grid(rows) => write!(f, "grid:{:?}", rows),
+            QuorumPolicy::MajorityWith(required) => write!(f, "majority-with:{:?}", required),
+        }
+    }
+}
+
+impl<NID: NodeId> QuorumPolicy<NID> {
+    /// Returns true if `granted` is a quorum of `voters`.
+    pub(crate) fn is_quorum_of(&self, voters: &[NID], granted: &BTreeSet<NID>) -> bool {
+        match self {
+            QuorumPolicy::Majority => granted.len() * 2 > voters.len(),
+            QuorumPolicy::Weighted(weights) => {
+                let weight = |id: &NID| weights.get(id).copied().unwrap_or(1) as u128;
+
+                let total: u128 = voters.iter().map(weight).sum();
+                let got: u128 = granted.iter().map(weight).sum();
+                got * 2 > total
+            }
+            QuorumPolicy::Grid(_) => {
+                let rows = self.grid_rows(voters);
+
+                rows.iter().all(|row| row.iter().any(|id| granted.contains(id)))
+                    && rows.iter().any(|row| row.iter().all(|id| granted.contains(id)))
+            }
+            QuorumPolicy::MajorityWith(required) => {
+                granted.len() * 2 > voters.len()
+                    && voters.iter().filter(|id| required.contains(id)).all(|id| granted.contains(id))
+            }
+        }
+    }
+
+    /// Returns the non-empty rows of a grid policy restricted to `voters`, with every voter that
+    /// is not in any row as a row by itself.
+    fn grid_rows(&self, voters: &[NID]) -> Vec<Vec<NID>> {
+        let QuorumPolicy::Grid(rows) = self else {
+            return voters.iter().map(|id| vec![*id]).collect();
+        };
+
+        let mut res = rows
+            .iter()
+            .map(|row| voters.iter().filter(|id| row.contains(id)).copied().collect::<Vec<_>>())
+            .filter(|row| !row.is_empty())
+            .collect::<Vec<_>>();
+
+        for id in voters {
+            if !rows.iter().any(|row| row.contains(id)) {
+                res.push(vec![*id]);
+            }
+        }
+
+        res
+    }
+
+    /// Ensure that this policy defines valid quorums for a config of `voters`:
+    /// - The set of all voters is a quorum;
+    /// - Every two quorums intersect.
+    pub(crate) fn ensure_valid(&self, voters: &[NID]) -> Result<(), InvalidQuorumPolicy> {
+        let invalid = |reason: String| InvalidQuorumPolicy {
+            policy: self.to_string(),
+            reason,
+        };
+
+        if let QuorumPolicy::Grid(rows) = self {
+            for id in voters {
+                if rows.iter().filter(|row| row.contains(id)).count() > 1 {
+                    return Err(invalid(format!("node {} is in more than one row", id)));
+                }
+            }
+        }
+
+        let all = voters.iter().copied().collect::<BTreeSet<_>>();
+        if !self.is_quorum_of(voters, &all) {
+            return Err(invalid(format!("all voters {:?} are not a quorum", voters)));
+        }
+
+        if let Some((a, b)) = Self::find_disjoint_quorums(self, self, voters).map_err(invalid)? {
+            return Err(invalid(format!("quorums {:?} and {:?} do not intersect", a, b)));
+        }
+
+        Ok(())
+    }
+
+    /// Ensure that every quorum of this policy intersects every quorum of the `prev` policy in a
+    /// config of `voters`, so that the policy can be changed without a joint config.
+    pub(crate) fn ensure_intersect(&self, prev: &Self, voters: &[NID]) -> Result<(), InvalidQuorumPolicy> {
+        let invalid = |reason: String| InvalidQuorumPolicy {
+            policy: self.to_string(),
+            reason,
+        };
+
+        if let Some((a, b)) = Self::find_disjoint_quorums(self, prev, voters).map_err(invalid)? {
+            return Err(invalid(format!(
+                "quorum {:?} does not intersect quorum {:?} of {}",
+                a, b, prev
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of voters in `alive` that can fail, in any combination, while the other
+    /// voters in `alive` still form a quorum of `voters`.
+    ///
+    /// It is `0` if `alive` is not a quorum. Every node set is enumerated thus for more than
+    /// [`MAX_VOTERS_TO_CHECK`] alive voters it is computed as if the policy is a majority.
+    pub(crate) fn fault_tolerance(&self, voters: &[NID], alive: &BTreeSet<NID>) -> usize {
+        let alive = voters.iter().filter(|id| alive.contains(id)).copied().collect::<Vec<_>>();
+
+        if matches!(self, QuorumPolicy::Majority) || alive.len() > MAX_VOTERS_TO_CHECK {
+            return alive.len().saturating_sub(voters.len() / 2 + 1);
+        }
+
+        // The fewest failures that leave no quorum.
+        let mut min_failures = alive.len();
+
+        for bits in 0..(1u32 << alive.len()) {
+            let rest = alive.iter().enumerate().filter(|(i, _)| bits & (1 << i) == 0).map(|(_, id)| *id);
+            if !self.is_quorum_of(voters, &rest.collect()) {
+                min_failures = std::cmp::min(min_failures, bits.count_ones() as usize);
+            }
+        }
+
+        min_failures.saturating_sub(1)
+    }
+
+    /// Find a quorum of `x` and a quorum of `y` that do not intersect.
+    ///
+    /// Two disjoint quorums exist if and only if the complement of some quorum of `x` is a quorum
+    /// of `y`. Every node set is enumerated thus a config with more than
+    /// [`MAX_VOTERS_TO_CHECK`] voters can not be checked and an error is returned, unless every
+    /// quorum of both policies is a majority.
+    fn find_disjoint_quorums(
+        x: &Self,
+        y: &Self,
+        voters: &[NID],
+    ) -> Result<Option<(BTreeSet<NID>, BTreeSet<NID>)>, String> {
+        // Two majorities always intersect.
+        if x.is_majority() && y.is_majority() {
+            return Ok(None);
+        }
+
+        if voters.len() > MAX_VOTERS_TO_CHECK {
+            return Err(format!(
+                "the intersection of quorums can not be checked for more than {} voters",
+                MAX_VOTERS_TO_CHECK
+            ));
+        }
+
+        for bits in 0..(1u32 << voters.len()) {
+            let (a, b): (Vec<_>, Vec<_>) = voters.iter().enumerate().partition(|(i, _)| bits & (1 << i) != 0);
+
+            let a = a.into_iter().map(|(_, id)| *id).collect::<BTreeSet<_>>();
+            let b = b.into_iter().map(|(_, id)| *id).collect::<BTreeSet<_>>();
+
+            if x.is_quorum_of(voters, &a) && y.is_quorum_of(voters, &b) {
+                return Ok(Some((a, b)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns true if every quorum of this policy is a majority of the voters.
+    fn is_majority(&self) -> bool {
+        matches!(self, QuorumPolicy::Majority | QuorumPolicy::MajorityWith(_))
+    }
+}
+
+/// A quorum set of a single config, defined by a [`QuorumPolicy`].
+#[derive(Debug, Clone, Default)]
+#[derive(PartialEq, Eq)]
+pub(crate) struct PolicyQuorum<NID: NodeId> {
+    voters: Vec<NID>,
+    policy: Arc<QuorumPolicy<NID>>,
+}
+
+impl<NID: NodeId> PolicyQuorum<NID> {
+    pub(crate) fn new(voters: Vec<NID>, policy: Arc<QuorumPolicy<NID>>) -> Self {
+        Self { voters, policy }
+    }
+}
+
+impl<NID: NodeId> QuorumSet<NID> for PolicyQuorum<NID> {
+    type Iter = std::collections::btree_set::IntoIter<NID>;
+
+    fn is_quorum<'a, I: Iterator<Item = &'a NID> + Clone>(&self, ids: I) -> bool {
+        if let QuorumPolicy::Majority = self.policy.as_ref() {
+            return self.voters.as_slice().is_quorum(ids);
+        }
+
+        let granted = ids.filter(|id| self.voters.contains(id)).copied().collect::<BTreeSet<_>>();
+        self.policy.is_quorum_of(&self.voters, &granted)
+    }
+
+    fn ids(&self) -> Self::Iter {
+        self.voters.iter().copied().collect::<BTreeSet<_>>().into_iter()
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use maplit::btreemap;
+use maplit::btreeset;
+
+use crate::engine::testing::UTConfig;
+use crate::error::ChangeMembershipError;
+use crate::membership::PolicyQuorum;
+use crate::membership::QuorumPolicy;
+use crate::quorum::QuorumSet;
+use crate::ChangeMembers;
+use crate::Membership;
+
+fn is_quorum(policy: &QuorumPolicy<u64>, voters: &[u64], granted: BTreeSet<u64>) -> bool {
+    PolicyQuorum::new(voters.to_vec(), Arc::new(policy.clone())).is_quorum(granted.iter())
+}
+
+#[test]
+fn test_quorum_policy_is_quorum() -> anyhow::Result<()> {
+    let v = [1, 2, 3, 4, 5];
+
+    tracing::info!("--- majority");
+    {
+        let p = QuorumPolicy::Majority;
+        assert!(!is_quorum(&p, &v, btreeset! {1,2}));
+        assert!(is_quorum(&p, &v, btreeset! {1,2,3}));
+        assert!(!is_quorum(&p, &v, btreeset! {1,2,6,7}), "non-voters are ignored");
+    }
+
+    tracing::info!("--- weighted");
+    {
+        let p = QuorumPolicy::Weighted(btreemap! {1=>3, 2=>2});
+        // total: 3+2+1+1+1 = 8
+        assert!(is_quorum(&p, &v, btreeset! {1,2}));
+        assert!(!is_quorum(&p, &v, btreeset! {1,3}));
+        assert!(is_quorum(&p, &v, btreeset! {1,3,4}));
+        assert!(!is_quorum(&p, &v, btreeset! {3,4,5}));
+        assert!(is_quorum(&p, &v, btreeset! {2,3,4,5}));
+    }
+
+    tracing::info!("--- grid");
+    {
+        let p = QuorumPolicy::Grid(vec![btreeset! {1,2}, btreeset! {3,4}, btreeset! {9}]);
+        // rows: {1,2}, {3,4}, {5}
+        assert!(is_quorum(&p, &v, btreeset! {1,2,3,5}));
+        assert!(is_quorum(&p, &v, btreeset! {1,3,5}), "row {{5}} is full");
+        assert!(!is_quorum(&p, &v, btreeset! {1,2,3,4}), "no voter in row {{5}}");
+        assert!(!is_quorum(&p, &v, btreeset! {1,3}));
+    }
+
+    tracing::info!("--- majority with");
+    {
+        let p = QuorumPolicy::MajorityWith(btreeset! {1,9});
+        assert!(is_quorum(&p, &v, btreeset! {1,2,3}));
+        assert!(!is_quorum(&p, &v, btreeset! {2,3,4,5}));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_quorum_policy_ensure_valid() -> anyhow::Result<()> {
+    let v = [1, 2, 3, 4];
+
+    assert!(QuorumPolicy::Majority.ensure_valid(&v).is_ok());
+    assert!(QuorumPolicy::Weighted(btreemap! {1=>2}).ensure_valid(&v).is_ok());
+    assert!(QuorumPolicy::Grid(vec![btreeset! {1,2}, btreeset! {3,4}]).ensure_valid(&v).is_ok());
+    assert!(QuorumPolicy::MajorityWith(btreeset! {1}).ensure_valid(&v).is_ok());
+
+    let res = QuorumPolicy::Weighted(btreemap! {1=>0, 2=>0, 3=>0, 4=>0}).ensure_valid(&v);
+    assert!(res.is_err(), "no voter has a weight");
+
+    let res = QuorumPolicy::Grid(vec![btreeset! {1,2}, btreeset! {2,3}]).ensure_valid(&v);
+    assert_eq!(
+        "invalid quorum policy grid:[{1, 2}, {2, 3}]: node 2 is in more than one row",
+        res.unwrap_err().to_string()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_quorum_policy_ensure_intersect() -> anyhow::Result<()> {
+    let v = [1, 2, 3];
+
+    let weighted = QuorumPolicy::Weighted(btreemap! {1=>3});
+    assert!(weighted.ensure_valid(&v).is_ok());
+
+    // {1} is a quorum of the weighted policy, {2,3} is a majority.
+    let res = weighted.ensure_intersect(&QuorumPolicy::Majority, &v);
+    assert!(res.is_err());
+
+    let with_1 = QuorumPolicy::MajorityWith(btreeset! {1});
+    assert!(with_1.ensure_intersect(&QuorumPolicy::Majority, &v).is_ok());
+    assert!(weighted.ensure_intersect(&with_1, &v).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_quorum_policy_fault_tolerance() -> anyhow::Result<()> {
+    let v = [1, 2, 3, 4, 5];
+
+    let p = QuorumPolicy::Majority;
+    assert_eq!(2, p.fault_tolerance(&v, &btreeset! {1,2,3,4,5}));
+    assert_eq!(
+        1,
+        p.fault_tolerance(&v, &btreeset! {1,2,3,4,6}),
+        "non-voters are ignored"
+    );
+    assert_eq!(0, p.fault_tolerance(&v, &btreeset! {1,2,3}));
+    assert_eq!(0, p.fault_tolerance(&v, &btreeset! {1,2}), "not a quorum");
+
+    // total: 4+1+1+1+1 = 8; losing 1 leaves 4
+    let p = QuorumPolicy::Weighted(btreemap! {1=>4});
+    assert_eq!(0, p.fault_tolerance(&v, &btreeset! {1,2,3,4,5}));
+
+    let p = QuorumPolicy::MajorityWith(btreeset! {1});
+    assert_eq!(0, p.fault_tolerance(&v, &btreeset! {1,2,3,4,5}));
+
+    let p = QuorumPolicy::Weighted(btreemap! {1=>2});
+    // total: 2+1+1+1+1 = 6; losing 1 and any other voter leaves 3
+    assert_eq!(1, p.fault_tolerance(&v, &btreeset! {1,2,3,4,5}));
+
+    Ok(())
+}
+
+#[test]
+fn test_membership_set_quorum_policy() -> anyhow::Result<()> {
+    let m = Membership::<UTConfig>::new(vec![btreeset! {1,2,3}], None);
+
+    tracing::info!("--- a policy whose quorums do not intersect the current ones");
+    {
+        let res = m.clone().change(
+            ChangeMembers::SetQuorumPolicy(QuorumPolicy::Weighted(btreemap! {1=>3})),
+            true,
+        );
+        assert!(matches!(res, Err(ChangeMembershipError::InvalidQuorumPolicy(_))));
+    }
+
+    tracing::info!("--- change in two steps");
+    {
+        let p = QuorumPolicy::MajorityWith(btreeset! {1});
+        let m = m.change(ChangeMembers::SetQuorumPolicy(p.clone()), true)?;
+        assert_eq!(&p, m.quorum_policy());
+        assert_eq!(
+            "{voters:[{1:(),2:(),3:()}], learners:[], quorum_policy:majority-with:{1}}",
+            m.to_string()
+        );
+
+        let p = QuorumPolicy::Weighted(btreemap! {1=>3});
+        let m = m.change(ChangeMembers::SetQuorumPolicy(p.clone()), true)?;
+        assert_eq!(&p, m.quorum_policy());
+
+        let qs = m.to_quorum_set();
+        assert!(qs.is_quorum([1].iter()));
+        assert!(!qs.is_quorum([2, 3].iter()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_quorum_policy_too_many_voters_to_check() -> anyhow::Result<()> {
+    let v = (1..=17).collect::<Vec<u64>>();
+
+    let majority = QuorumPolicy::Majority;
+    let with_1 = QuorumPolicy::MajorityWith(btreeset! {1});
+    assert!(majority.ensure_valid(&v).is_ok());
+    assert!(with_1.ensure_valid(&v).is_ok());
+    assert!(with_1.ensure_intersect(&majority, &v).is_ok());
+    assert!(majority.ensure_intersect(&with_1, &v).is_ok());
+
+    // {1} is a quorum, whose complement is a majority: it can not be checked.
+    let weighted = QuorumPolicy::Weighted(btreemap! {1=>17});
+    let res = weighted.ensure_intersect(&majority, &v);
+    assert_eq!(
+        "invalid quorum policy weighted:{1: 17}: the intersection of quorums can not be checked for more than 16 voters",
+        res.unwrap_err().to_string()
+    );
+    assert!(weighted.ensure_valid(&v).is_err());
+
+    let m = Membership::<UTConfig>::new(vec![v.iter().copied().collect()], None);
+    let res = m.change(ChangeMembers::SetQuorumPolicy(weighted), true);
+    assert!(matches!(res, Err(ChangeMembershipError::InvalidQuorumPolicy(_))));
+
+    Ok(())
+}
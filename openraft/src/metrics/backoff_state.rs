@@ -0,0 +1,22 @@
+use std::fmt;
+
+/// The backoff state of a replication target that returned an
+/// [`Unreachable`](`crate::error::Unreachable`) error.
+///
+/// It is reported in [`RaftMetrics::backoff`](`crate::RaftMetrics::backoff`) by a leader until an
+/// RPC to the target succeeds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct BackoffState {
+    /// The number of consecutive retries delayed by the backoff.
+    pub attempts: u64,
+
+    /// The delay in milliseconds before the latest retry.
+    pub delay_millis: u64,
+}
+
+impl fmt::Display for BackoffState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{attempts:{}, delay:{} ms}}", self.attempts, self.delay_millis)
+    }
+}
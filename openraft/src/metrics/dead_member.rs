@@ -0,0 +1,42 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::RaftTypeConfig;
+use crate::StoredMembership;
+
+/// A member that the leader is about to remove, because it has been unreachable for
+/// [`Config::auto_remove_dead_member_duration`].
+///
+/// It is received from [`Raft::subscribe_dead_members()`](`crate::Raft::subscribe_dead_members`)
+/// before the membership change removing it is proposed. The removal may still fail, e.g., if
+/// the leadership is lost before the change is committed.
+///
+/// [`Config::auto_remove_dead_member_duration`]: `crate::Config::auto_remove_dead_member_duration`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct DeadMember<C: RaftTypeConfig> {
+    /// The id of the member to remove.
+    pub id: C::NodeId,
+
+    /// Whether the member is a voter.
+    pub voter: bool,
+
+    /// How long the leader has not heard from the member.
+    pub unreachable_for: Duration,
+
+    /// The effective membership config the member is removed from.
+    pub membership_config: Arc<StoredMembership<C>>,
+}
+
+impl<C> fmt::Display for DeadMember<C>
+where C: RaftTypeConfig
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DeadMember{{id:{}, voter:{}, unreachable_for:{:?}, membership:{}}}",
+            self.id, self.voter, self.unreachable_for, self.membership_config,
+        )
+    }
+}
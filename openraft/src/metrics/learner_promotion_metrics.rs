@@ -0,0 +1,63 @@
+use std::fmt;
+
+use crate::display_ext::DisplayOption;
+use crate::NodeId;
+
+/// The learners that are automatically promoted to voters once they have caught up with the
+/// leader.
+///
+/// It is reported in
+/// [`RaftMetrics::learner_promotion`](`crate::RaftMetrics::learner_promotion`), and counts all
+/// the promotions committed by this node as a leader since it started. See
+/// [`Config::auto_promote_learner_duration`](`crate::Config::auto_promote_learner_duration`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct LearnerPromotionMetrics<NID: NodeId> {
+    /// The number of learners that have been promoted to voters.
+    pub promoted: u64,
+
+    /// The last learner that has been promoted to a voter.
+    pub last_promoted: Option<NID>,
+}
+
+impl<NID: NodeId> LearnerPromotionMetrics<NID> {
+    /// Record that the learner `id` has been promoted to a voter.
+    pub(crate) fn record_promotion(&mut self, id: NID) {
+        self.promoted += 1;
+        self.last_promoted = Some(id);
+    }
+}
+
+impl<NID: NodeId> fmt::Display for LearnerPromotionMetrics<NID> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{promoted:{}, last_promoted:{}}}",
+            self.promoted,
+            DisplayOption(&self.last_promoted)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::LearnerPromotionMetrics;
+
+    #[test]
+    fn test_learner_promotion_metrics_record_promotion() {
+        let mut m = LearnerPromotionMetrics::<u64>::default();
+        assert_eq!("{promoted:0, last_promoted:None}", m.to_string());
+
+        m.record_promotion(3);
+        m.record_promotion(5);
+
+        assert_eq!(
+            LearnerPromotionMetrics {
+                promoted: 2,
+                last_promoted: Some(5),
+            },
+            m
+        );
+        assert_eq!("{promoted:2, last_promoted:5}", m.to_string());
+    }
+}
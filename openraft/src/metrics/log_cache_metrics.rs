@@ -0,0 +1,33 @@
+use std::fmt;
+
+/// The state of the in-memory cache of the latest log entries, which is consulted by the
+/// replication streams before reading the log store.
+///
+/// It is reported in [`RaftMetrics::log_cache`](`crate::RaftMetrics::log_cache`), and is all zero
+/// unless the cache is enabled with [`Raft::enable_log_cache()`](`crate::Raft::enable_log_cache`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct LogCacheMetrics {
+    /// The number of entries in the cache.
+    pub entries: u64,
+
+    /// The total [`size_hint()`](`crate::entry::RaftEntry::size_hint`) of the entries in the
+    /// cache.
+    pub bytes: u64,
+
+    /// The number of reads served by the cache.
+    pub hits: u64,
+
+    /// The number of reads that fall back to the log store.
+    pub misses: u64,
+}
+
+impl fmt::Display for LogCacheMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{entries:{}, bytes:{}, hits:{}, misses:{}}}",
+            self.entries, self.bytes, self.hits, self.misses
+        )
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt;
+
+use crate::display_ext::DisplayOption;
+use crate::LogId;
+use crate::LogRetentionPolicy;
+use crate::NodeId;
+
+/// The state of the [`LogRetentionPolicy`] that decides which logs to purge.
+///
+/// It is reported in [`RaftMetrics::log_retention`](`crate::RaftMetrics::log_retention`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct LogRetentionMetrics<NID: NodeId> {
+    /// The configured policy.
+    pub policy: LogRetentionPolicy,
+
+    /// The log id up to which the policy allows to purge, inclusive.
+    ///
+    /// The logs may not be purged yet, if they are in use by replication.
+    pub purge_upto: Option<LogId<NID>>,
+
+    /// The total [`size_hint()`](`crate::entry::RaftEntry::size_hint`) of the logs appended
+    /// since startup and not yet purged.
+    pub bytes: u64,
+}
+
+impl<NID: NodeId> fmt::Display for LogRetentionMetrics<NID> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{policy:{:?}, purge_upto:{}, bytes:{}}}",
+            self.policy,
+            DisplayOption(&self.purge_upto),
+            self.bytes
+        )
+    }
+}
@@ -26,10 +26,25 @@
 //! Metrics is not a stream thus it only guarantees to provide the latest state but
 //! not every change of the state.
 //! Because internally, `watch::channel()` only stores one last state.
+//!
+//! To receive every change of the server state, such as to start or stop leader-only background
+//! jobs, subscribe to [`RoleChange`]s with
+//! [`Raft::subscribe_role_changes()`](`crate::Raft::subscribe_role_changes`).
 
+mod backoff_state;
+mod dead_member;
+mod learner_promotion_metrics;
+mod log_cache_metrics;
+mod log_retention_metrics;
 mod metric;
 mod raft_metrics;
+mod read_index_metrics;
+mod role_change;
+mod rtt_estimate;
+mod snapshot_build_metrics;
 mod wait;
+mod write_batch_metrics;
+mod zone_health;
 
 mod metric_display;
 mod wait_condition;
@@ -37,14 +52,29 @@ mod wait_condition;
 
 use std::collections::BTreeMap;
 
+pub use backoff_state::BackoffState;
+pub use dead_member::DeadMember;
+pub use learner_promotion_metrics::LearnerPromotionMetrics;
+pub use log_cache_metrics::LogCacheMetrics;
+pub use log_retention_metrics::LogRetentionMetrics;
 pub use metric::Metric;
 pub use raft_metrics::RaftDataMetrics;
 pub use raft_metrics::RaftMetrics;
 pub use raft_metrics::RaftServerMetrics;
+pub use read_index_metrics::ReadIndexMetrics;
+pub use role_change::RoleChange;
+pub use rtt_estimate::RttEstimate;
+pub use snapshot_build_metrics::SnapshotBuildMetrics;
 pub use wait::Wait;
 pub use wait::WaitError;
 pub(crate) use wait_condition::Condition;
+pub use write_batch_metrics::WriteBatchMetrics;
+pub use zone_health::ZoneHealth;
 
 use crate::LogId;
 
 pub(crate) type ReplicationMetrics<NID> = BTreeMap<NID, Option<LogId<NID>>>;
+pub(crate) type BackoffMetrics<NID> = BTreeMap<NID, BackoffState>;
+pub(crate) type RttMetrics<NID> = BTreeMap<NID, RttEstimate>;
+pub(crate) type BatchSizeMetrics<NID> = BTreeMap<NID, u64>;
+pub(crate) type ZoneMetrics = BTreeMap<String, ZoneHealth>;
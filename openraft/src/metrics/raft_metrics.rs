@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::fmt;
 use std::sync::Arc;
 
@@ -5,7 +6,17 @@ use crate::core::ServerState;
 use crate::display_ext::DisplayOption;
 use crate::display_ext::DisplayOptionExt;
 use crate::error::Fatal;
+use crate::metrics::BackoffMetrics;
+use crate::metrics::BatchSizeMetrics;
+use crate::metrics::LearnerPromotionMetrics;
+use crate::metrics::LogCacheMetrics;
+use crate::metrics::LogRetentionMetrics;
+use crate::metrics::ReadIndexMetrics;
 use crate::metrics::ReplicationMetrics;
+use crate::metrics::RttMetrics;
+use crate::metrics::SnapshotBuildMetrics;
+use crate::metrics::WriteBatchMetrics;
+use crate::metrics::ZoneMetrics;
 use crate::LogId;
 use crate::RaftTypeConfig;
 use crate::StoredMembership;
@@ -69,6 +80,13 @@ pub struct RaftMetrics<C: RaftTypeConfig> {
     /// being partitioned from the cluster.
     pub millis_since_quorum_ack: Option<u64>,
 
+    /// The election timeout in milliseconds this node currently uses.
+    ///
+    /// It is tuned to the round-trip time of heartbeats if
+    /// [`Config::adaptive_election_timeout`](`crate::Config::adaptive_election_timeout`) is
+    /// enabled.
+    pub election_timeout_ms: u64,
+
     /// The current membership config of the cluster.
     pub membership_config: Arc<StoredMembership<C>>,
 
@@ -77,6 +95,54 @@ pub struct RaftMetrics<C: RaftTypeConfig> {
     // ---
     /// The replication states. It is Some() only when this node is leader.
     pub replication: Option<ReplicationMetrics<C::NodeId>>,
+
+    /// The backoff states of the replication targets that are unreachable. It is Some() only when
+    /// this node is leader, and a target is included only until an RPC to it succeeds.
+    pub backoff: Option<BackoffMetrics<C::NodeId>>,
+
+    /// The number of log entries per AppendEntries RPC to every replication target, adapted to
+    /// the observed latency. It is Some() only when this node is leader, and is empty unless
+    /// [`Config::adaptive_batch_target_latency`](`crate::Config::adaptive_batch_target_latency`)
+    /// is set.
+    pub batch_size: Option<BatchSizeMetrics<C::NodeId>>,
+
+    /// The replication targets that are quarantined because their replication lag kept growing.
+    /// It is Some() only when this node is leader, and is empty unless
+    /// [`Config::quarantine_lag_growth_duration`](`crate::Config::quarantine_lag_growth_duration`)
+    /// is set.
+    pub quarantined: Option<BTreeSet<C::NodeId>>,
+
+    /// The health of the nodes in every zone, see [`Membership::zone()`]. It is Some() only when
+    /// this node is leader, and is empty unless zones are set.
+    ///
+    /// [`Membership::zone()`]: `crate::Membership::zone`
+    pub zones: Option<ZoneMetrics>,
+
+    /// The estimated round-trip time of the RPCs to every node this node has successfully sent an
+    /// RPC to, such as the followers of a leader or the voters of a candidate.
+    pub rtt: RttMetrics<C::NodeId>,
+
+    /// The hits and misses of the cache of the latest log entries read by replication.
+    pub log_cache: LogCacheMetrics,
+
+    /// The heartbeat rounds sent to confirm the leadership for linearizable reads, and the number
+    /// of reads served by every round.
+    pub read_index: ReadIndexMetrics,
+
+    /// The batches of client writes coalesced by this node as a leader before appending them to
+    /// its log.
+    pub write_batch: WriteBatchMetrics,
+
+    /// The learners that have been automatically promoted to voters by this node as a leader.
+    pub learner_promotion: LearnerPromotionMetrics<C::NodeId>,
+
+    /// The policy that decides which logs to purge and its current state.
+    pub log_retention: LogRetentionMetrics<C::NodeId>,
+
+    /// The progress of the snapshot being built. It is `None` if no snapshot is being built.
+    ///
+    /// It is refreshed whenever `RaftCore` handles an event, such as a tick.
+    pub snapshot_building: Option<SnapshotBuildMetrics>,
 }
 
 impl<C> fmt::Display for RaftMetrics<C>
@@ -111,6 +177,64 @@ where C: RaftTypeConfig
                 .unwrap_or_default(),
         )?;
 
+        if let Some(backoff) = self.backoff.as_ref().filter(|x| !x.is_empty()) {
+            write!(
+                f,
+                ", backoff:{{{}}}",
+                backoff.iter().map(|(k, v)| format!("{}:{}", k, v)).collect::<Vec<_>>().join(",")
+            )?;
+        }
+
+        if let Some(batch_size) = self.batch_size.as_ref().filter(|x| !x.is_empty()) {
+            write!(
+                f,
+                ", batch_size:{{{}}}",
+                batch_size.iter().map(|(k, v)| format!("{}:{}", k, v)).collect::<Vec<_>>().join(",")
+            )?;
+        }
+
+        if let Some(quarantined) = self.quarantined.as_ref().filter(|x| !x.is_empty()) {
+            write!(f, ", quarantined:{:?}", quarantined)?;
+        }
+
+        if let Some(zones) = self.zones.as_ref().filter(|x| !x.is_empty()) {
+            write!(
+                f,
+                ", zones:{{{}}}",
+                zones.iter().map(|(k, v)| format!("{}:{}", k, v)).collect::<Vec<_>>().join(",")
+            )?;
+        }
+
+        if !self.rtt.is_empty() {
+            write!(
+                f,
+                ", rtt:{{{}}}",
+                self.rtt.iter().map(|(k, v)| format!("{}:{}", k, v)).collect::<Vec<_>>().join(",")
+            )?;
+        }
+
+        if self.log_cache != LogCacheMetrics::default() {
+            write!(f, ", log_cache:{}", self.log_cache)?;
+        }
+
+        if self.read_index != ReadIndexMetrics::default() {
+            write!(f, ", read_index:{}", self.read_index)?;
+        }
+
+        if self.write_batch != WriteBatchMetrics::default() {
+            write!(f, ", write_batch:{}", self.write_batch)?;
+        }
+
+        if self.learner_promotion != LearnerPromotionMetrics::default() {
+            write!(f, ", learner_promotion:{}", self.learner_promotion)?;
+        }
+
+        write!(f, ", log_retention:{}", self.log_retention)?;
+
+        if let Some(building) = &self.snapshot_building {
+            write!(f, ", snapshot_building:{}", building)?;
+        }
+
         write!(f, "}}")?;
         Ok(())
     }
@@ -134,8 +258,20 @@ where C: RaftTypeConfig
             state: ServerState::Follower,
             current_leader: None,
             millis_since_quorum_ack: None,
+            election_timeout_ms: 0,
             membership_config: Arc::new(StoredMembership::default()),
             replication: None,
+            backoff: None,
+            batch_size: None,
+            quarantined: None,
+            zones: None,
+            rtt: Default::default(),
+            log_cache: Default::default(),
+            read_index: Default::default(),
+            write_batch: Default::default(),
+            learner_promotion: Default::default(),
+            log_retention: Default::default(),
+            snapshot_building: None,
         }
     }
 }
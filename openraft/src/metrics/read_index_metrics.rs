@@ -0,0 +1,69 @@
+use std::fmt;
+
+/// The statistics of the linearizable reads that confirm the leadership with a heartbeat round.
+///
+/// Read requests that arrive while a round is in flight are coalesced and served by the next
+/// round. It is reported in [`RaftMetrics::read_index`](`crate::RaftMetrics::read_index`), and
+/// counts all the rounds since this node started.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ReadIndexMetrics {
+    /// The number of rounds sent to confirm the leadership.
+    pub rounds: u64,
+
+    /// The number of read requests served by these rounds.
+    pub reads: u64,
+
+    /// The number of read requests served by the last round.
+    pub last_batch_size: u64,
+
+    /// The largest number of read requests served by one round.
+    pub max_batch_size: u64,
+}
+
+impl ReadIndexMetrics {
+    /// Record a round that serves `batch_size` read requests.
+    pub(crate) fn record_round(&mut self, batch_size: u64) {
+        self.rounds += 1;
+        self.reads += batch_size;
+        self.last_batch_size = batch_size;
+        self.max_batch_size = std::cmp::max(self.max_batch_size, batch_size);
+    }
+}
+
+impl fmt::Display for ReadIndexMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{rounds:{}, reads:{}, last_batch_size:{}, max_batch_size:{}}}",
+            self.rounds, self.reads, self.last_batch_size, self.max_batch_size
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::ReadIndexMetrics;
+
+    #[test]
+    fn test_read_index_metrics_record_round() {
+        let mut m = ReadIndexMetrics::default();
+
+        m.record_round(3);
+        m.record_round(1);
+
+        assert_eq!(
+            ReadIndexMetrics {
+                rounds: 2,
+                reads: 4,
+                last_batch_size: 1,
+                max_batch_size: 3,
+            },
+            m
+        );
+        assert_eq!(
+            "{rounds:2, reads:4, last_batch_size:1, max_batch_size:3}",
+            m.to_string()
+        );
+    }
+}
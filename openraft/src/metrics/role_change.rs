@@ -0,0 +1,67 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::display_ext::DisplayOption;
+use crate::RaftTypeConfig;
+use crate::ServerState;
+use crate::StoredMembership;
+use crate::Vote;
+
+/// A change of the server state of a Raft node, e.g., it becomes the leader, or it steps down to
+/// a follower.
+///
+/// It is received from [`Raft::subscribe_role_changes()`](`crate::Raft::subscribe_role_changes`).
+/// A leader that is elected again in a later term is reported as another change, with `prev` set
+/// to [`ServerState::Leader`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct RoleChange<C: RaftTypeConfig> {
+    /// The id of this node.
+    pub id: C::NodeId,
+
+    /// The server state before this change.
+    pub prev: ServerState,
+
+    /// The server state after this change.
+    pub state: ServerState,
+
+    /// The vote of this node after this change.
+    pub vote: Vote<C::NodeId>,
+
+    /// The leader known to this node after this change.
+    pub current_leader: Option<C::NodeId>,
+
+    /// The effective membership config after this change.
+    pub membership_config: Arc<StoredMembership<C>>,
+}
+
+impl<C> RoleChange<C>
+where C: RaftTypeConfig
+{
+    /// Returns the term of the vote of this node after this change.
+    pub fn term(&self) -> u64 {
+        self.vote.leader_id().get_term()
+    }
+
+    /// Returns `true` if this node becomes the leader.
+    pub fn is_leader(&self) -> bool {
+        self.state == ServerState::Leader
+    }
+}
+
+impl<C> fmt::Display for RoleChange<C>
+where C: RaftTypeConfig
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RoleChange{{id:{}, {:?}->{:?}, vote:{}, leader:{}, membership:{}}}",
+            self.id,
+            self.prev,
+            self.state,
+            self.vote,
+            DisplayOption(&self.current_leader),
+            self.membership_config,
+        )
+    }
+}
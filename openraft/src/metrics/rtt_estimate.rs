@@ -0,0 +1,72 @@
+use std::fmt;
+use std::time::Duration;
+
+/// The estimated round-trip time of the RPCs to a node.
+///
+/// It is reported in [`RaftMetrics::rtt`](`crate::RaftMetrics::rtt`) for every node to which this
+/// node has successfully sent an RPC, and can be used to tune timeouts to the actual network
+/// latency.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct RttEstimate {
+    /// The smoothed RTT in microseconds, an exponentially weighted moving average of the samples
+    /// with a weight of 1/8 for the latest one, as TCP does.
+    pub smoothed_micros: u64,
+
+    /// The RTT of the latest successful RPC in microseconds.
+    pub latest_micros: u64,
+
+    /// The number of successful RPCs sampled.
+    pub samples: u64,
+}
+
+impl RttEstimate {
+    /// The smoothed RTT.
+    pub fn smoothed(&self) -> Duration {
+        Duration::from_micros(self.smoothed_micros)
+    }
+
+    /// Add the RTT of a successful RPC.
+    pub(crate) fn update(&mut self, rtt: Duration) {
+        let micros = rtt.as_micros() as u64;
+
+        self.smoothed_micros = if self.samples == 0 {
+            micros
+        } else {
+            (self.smoothed_micros * 7 + micros) / 8
+        };
+        self.latest_micros = micros;
+        self.samples += 1;
+    }
+}
+
+impl fmt::Display for RttEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{smoothed:{} us, latest:{} us}}",
+            self.smoothed_micros, self.latest_micros
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::metrics::RttEstimate;
+
+    #[test]
+    fn test_rtt_estimate_update() {
+        let mut e = RttEstimate::default();
+
+        e.update(Duration::from_micros(800));
+        assert_eq!(800, e.smoothed_micros, "the first sample is taken as is");
+
+        e.update(Duration::from_micros(1_600));
+        assert_eq!(900, e.smoothed_micros);
+        assert_eq!(1_600, e.latest_micros);
+        assert_eq!(2, e.samples);
+        assert_eq!(Duration::from_micros(900), e.smoothed());
+    }
+}
@@ -0,0 +1,23 @@
+use std::fmt;
+
+/// The progress of the snapshot being built.
+///
+/// It is reported in [`RaftMetrics::snapshot_building`](`crate::RaftMetrics::snapshot_building`).
+/// The values are reported by the
+/// [`RaftSnapshotBuilder`](`crate::storage::RaftSnapshotBuilder`) implementation, and stay zero if
+/// it does not report progress.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SnapshotBuildMetrics {
+    /// The number of entries written into the snapshot so far.
+    pub entries: u64,
+
+    /// The number of bytes written into the snapshot so far.
+    pub bytes: u64,
+}
+
+impl fmt::Display for SnapshotBuildMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{entries:{}, bytes:{}}}", self.entries, self.bytes)
+    }
+}
@@ -8,9 +8,7 @@ use crate::metrics::Condition;
 use crate::metrics::Metric;
 use crate::metrics::RaftMetrics;
 use crate::type_config::alias::AsyncRuntimeOf;
-use crate::type_config::alias::InstantOf;
 use crate::AsyncRuntime;
-use crate::Instant;
 use crate::LogId;
 use crate::OptionalSend;
 use crate::RaftTypeConfig;
@@ -40,7 +38,7 @@ where C: RaftTypeConfig
     #[tracing::instrument(level = "trace", skip(self, func), fields(msg=%msg.to_string()))]
     pub async fn metrics<T>(&self, func: T, msg: impl ToString) -> Result<RaftMetrics<C>, WaitError>
     where T: Fn(&RaftMetrics<C>) -> bool + OptionalSend {
-        let timeout_at = InstantOf::<C>::now() + self.timeout;
+        let timeout_at = C::AsyncRuntime::now() + self.timeout;
 
         let mut rx = self.rx.clone();
         loop {
@@ -53,7 +51,7 @@ where C: RaftTypeConfig
                 return Ok(latest);
             }
 
-            let now = InstantOf::<C>::now();
+            let now = C::AsyncRuntime::now();
             if now >= timeout_at {
                 return Err(WaitError::Timeout(
                     self.timeout,
@@ -260,10 +260,22 @@ where C: RaftTypeConfig {
 
         current_leader: None,
         millis_since_quorum_ack: None,
+        election_timeout_ms: 0,
         membership_config: Arc::new(StoredMembership::new(None, Membership::new(vec![btreeset! {}], None))),
 
         snapshot: None,
         replication: None,
+        backoff: None,
+        batch_size: None,
+        quarantined: None,
+        zones: None,
+        rtt: Default::default(),
+        log_cache: Default::default(),
+        read_index: Default::default(),
+        write_batch: Default::default(),
+        learner_promotion: Default::default(),
+        log_retention: Default::default(),
+        snapshot_building: None,
     };
     let (tx, rx) = watch::channel(init.clone());
     let w = Wait {
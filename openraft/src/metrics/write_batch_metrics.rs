@@ -0,0 +1,42 @@
+use std::fmt;
+
+/// The statistics of the client writes coalesced by a leader before appending them to its log.
+///
+/// It is reported in [`RaftMetrics::write_batch`](`crate::RaftMetrics::write_batch`), counts all
+/// the batches since this node started, and stays empty unless
+/// [`Config::write_coalesce_window`](`crate::Config::write_coalesce_window`) is set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct WriteBatchMetrics {
+    /// The number of batches appended to the log.
+    pub batches: u64,
+
+    /// The number of client writes appended by these batches.
+    pub writes: u64,
+
+    /// The number of client writes in the last batch.
+    pub last_batch_size: u64,
+
+    /// The largest number of client writes in one batch.
+    pub max_batch_size: u64,
+}
+
+impl WriteBatchMetrics {
+    /// Record a batch of `batch_size` client writes.
+    pub(crate) fn record_batch(&mut self, batch_size: u64) {
+        self.batches += 1;
+        self.writes += batch_size;
+        self.last_batch_size = batch_size;
+        self.max_batch_size = std::cmp::max(self.max_batch_size, batch_size);
+    }
+}
+
+impl fmt::Display for WriteBatchMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{batches:{}, writes:{}, last_batch_size:{}, max_batch_size:{}}}",
+            self.batches, self.writes, self.last_batch_size, self.max_batch_size
+        )
+    }
+}
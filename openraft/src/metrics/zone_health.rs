@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// The health of the nodes in a zone, as seen by the leader.
+///
+/// It is reported in [`RaftMetrics::zones`](`crate::RaftMetrics::zones`) for every zone set with
+/// [`ChangeMembers::SetZones`](`crate::ChangeMembers::SetZones`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ZoneHealth {
+    /// The number of voters in the zone.
+    pub voters: u64,
+
+    /// The number of learners in the zone.
+    pub learners: u64,
+
+    /// The number of nodes in the zone the leader has heard from within the leader lease,
+    /// including the leader itself.
+    pub alive: u64,
+
+    /// Whether the failure of the zone leaves the voters without a quorum.
+    pub critical: bool,
+}
+
+impl fmt::Display for ZoneHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{voters:{}, learners:{}, alive:{}, critical:{}}}",
+            self.voters, self.learners, self.alive, self.critical
+        )
+    }
+}
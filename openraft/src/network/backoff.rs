@@ -2,18 +2,84 @@ use std::time::Duration;
 
 use crate::OptionalSend;
 
-/// A backoff instance that is an infinite iterator of durations to sleep before next retry, when a
+/// Decides how long to wait before sending the next RPC to a node that returned an
+/// [`Unreachable`](`crate::error::Unreachable`) error.
+///
+/// An instance is created when a replication stream encounters the first `Unreachable` error, and
+/// [`next_delay()`](`Self::next_delay`) is called before every retry. The instance is dropped
+/// when an RPC succeeds, thus it is free to keep state across consecutive failures, such as the
+/// number of failures so far.
+///
+/// Openraft provides [`ConstantBackoff`], [`ExponentialBackoff`], [`JitteredBackoff`] and
+/// [`CircuitBreakerBackoff`], which can be selected with [`Config::backoff_policy`]. An
+/// application defined policy is returned by [`RaftNetwork::backoff()`] with
+/// [`Backoff::from_policy()`].
+///
+/// [`ConstantBackoff`]: crate::network::ConstantBackoff
+/// [`ExponentialBackoff`]: crate::network::ExponentialBackoff
+/// [`JitteredBackoff`]: crate::network::JitteredBackoff
+/// [`CircuitBreakerBackoff`]: crate::network::CircuitBreakerBackoff
+/// [`Config::backoff_policy`]: crate::Config::backoff_policy
+/// [`RaftNetwork::backoff()`]: crate::network::RaftNetwork::backoff
+pub trait BackoffPolicy: OptionalSend + 'static {
+    /// Returns the duration to wait before the next retry.
+    fn next_delay(&mut self) -> Duration;
+}
+
+/// Adapts an iterator of durations to [`BackoffPolicy`].
+struct IterPolicy<I>(I);
+
+impl<I> BackoffPolicy for IterPolicy<I>
+where I: Iterator<Item = Duration> + OptionalSend + 'static
+{
+    fn next_delay(&mut self) -> Duration {
+        self.0.next().unwrap_or_else(|| {
+            tracing::warn!("backoff exhausted, using default");
+            Duration::from_millis(500)
+        })
+    }
+}
+
+/// A backoff instance that returns durations to sleep before next retry, when a
 /// [`Unreachable`](`crate::error::Unreachable`) occurs.
+///
+/// [`Backoff::default()`] does not have a policy of its own: Openraft replaces it with the one
+/// configured by [`Config::backoff_policy`](`crate::Config::backoff_policy`).
+#[derive(Default)]
 pub struct Backoff {
-    #[cfg(not(feature = "singlethreaded"))]
-    inner: Box<dyn Iterator<Item = Duration> + Send + 'static>,
-    #[cfg(feature = "singlethreaded")]
-    inner: Box<dyn Iterator<Item = Duration> + 'static>,
+    inner: Option<Box<dyn BackoffPolicy>>,
+
+    /// The number of delays returned so far.
+    attempts: u64,
 }
 
 impl Backoff {
+    /// Build a backoff from an infinite iterator that returns the ith sleep interval before the
+    /// ith retry.
     pub fn new(iter: impl Iterator<Item = Duration> + OptionalSend + 'static) -> Self {
-        Self { inner: Box::new(iter) }
+        Self::from_policy(IterPolicy(iter))
+    }
+
+    /// Build a backoff from a [`BackoffPolicy`].
+    pub fn from_policy(policy: impl BackoffPolicy) -> Self {
+        Self {
+            inner: Some(Box::new(policy)),
+            attempts: 0,
+        }
+    }
+
+    /// Returns `self` if it has a policy, otherwise build one with `f`.
+    pub(crate) fn or_else(self, f: impl FnOnce() -> Backoff) -> Self {
+        if self.inner.is_some() {
+            self
+        } else {
+            f()
+        }
+    }
+
+    /// The number of retries this backoff has delayed.
+    pub fn attempts(&self) -> u64 {
+        self.attempts
     }
 }
 
@@ -21,6 +87,8 @@ impl Iterator for Backoff {
     type Item = Duration;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next()
+        let d = self.inner.as_mut()?.next_delay();
+        self.attempts += 1;
+        Some(d)
     }
 }
@@ -0,0 +1,184 @@
+//! The [`BackoffPolicy`] implementations provided by Openraft.
+
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use crate::network::BackoffPolicy;
+
+/// Waits the same duration before every retry.
+#[derive(Debug, Clone)]
+pub struct ConstantBackoff {
+    delay: Duration,
+}
+
+impl ConstantBackoff {
+    pub fn new(delay: Duration) -> Self {
+        Self { delay }
+    }
+}
+
+impl BackoffPolicy for ConstantBackoff {
+    fn next_delay(&mut self) -> Duration {
+        self.delay
+    }
+}
+
+/// Waits `min` before the first retry, and doubles the delay on every retry up to `max`.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    next: Duration,
+    max: Duration,
+}
+
+impl ExponentialBackoff {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            next: min.min(max),
+            max,
+        }
+    }
+}
+
+impl BackoffPolicy for ExponentialBackoff {
+    fn next_delay(&mut self) -> Duration {
+        let d = self.next;
+        self.next = self.next.saturating_mul(2).min(self.max);
+        d
+    }
+}
+
+/// An [`ExponentialBackoff`] with every delay randomized to a value between half of it and it.
+///
+/// The randomization spreads the retries of the replication streams that become unreachable at
+/// the same time, e.g., when a leader is partitioned from all of its followers.
+#[derive(Debug, Clone)]
+pub struct JitteredBackoff {
+    exponential: ExponentialBackoff,
+    rng: StdRng,
+}
+
+impl JitteredBackoff {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            exponential: ExponentialBackoff::new(min, max),
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+impl BackoffPolicy for JitteredBackoff {
+    fn next_delay(&mut self) -> Duration {
+        let d = self.exponential.next_delay();
+        self.rng.gen_range(d / 2..=d)
+    }
+}
+
+/// Retries every `retry_interval` until `threshold` consecutive failures, then opens the circuit:
+/// only one probe is sent every `open_duration`, until an RPC succeeds and closes the circuit.
+///
+/// It keeps a node that is down for a long time, e.g., for maintenance, from being probed at a
+/// high rate, while a short network glitch is still recovered from quickly.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerBackoff {
+    threshold: u64,
+    retry_interval: Duration,
+    open_duration: Duration,
+    failures: u64,
+}
+
+impl CircuitBreakerBackoff {
+    pub fn new(threshold: u64, retry_interval: Duration, open_duration: Duration) -> Self {
+        Self {
+            threshold,
+            retry_interval,
+            open_duration,
+            failures: 0,
+        }
+    }
+
+    /// Whether the circuit is open, i.e., there have been at least `threshold` failures.
+    pub fn is_open(&self) -> bool {
+        self.failures >= self.threshold
+    }
+}
+
+impl BackoffPolicy for CircuitBreakerBackoff {
+    fn next_delay(&mut self) -> Duration {
+        self.failures += 1;
+        if self.is_open() {
+            self.open_duration
+        } else {
+            self.retry_interval
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::network::Backoff;
+    use crate::network::BackoffPolicy;
+    use crate::network::CircuitBreakerBackoff;
+    use crate::network::ConstantBackoff;
+    use crate::network::ExponentialBackoff;
+    use crate::network::JitteredBackoff;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_constant() {
+        let got = Backoff::from_policy(ConstantBackoff::new(ms(300))).take(3).collect::<Vec<_>>();
+        assert_eq!(vec![ms(300), ms(300), ms(300)], got);
+    }
+
+    #[test]
+    fn test_exponential() {
+        let got = Backoff::from_policy(ExponentialBackoff::new(ms(100), ms(500))).take(5).collect::<Vec<_>>();
+        assert_eq!(vec![ms(100), ms(200), ms(400), ms(500), ms(500)], got);
+    }
+
+    #[test]
+    fn test_jittered() {
+        let mut exp = ExponentialBackoff::new(ms(100), ms(1_000));
+        let mut jittered = JitteredBackoff::new(ms(100), ms(1_000));
+
+        for _ in 0..10 {
+            let max = exp.next_delay();
+            let d = jittered.next_delay();
+            assert!(d >= max / 2 && d <= max, "{:?} in [{:?}, {:?}]", d, max / 2, max);
+        }
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let mut b = CircuitBreakerBackoff::new(3, ms(100), ms(10_000));
+
+        assert_eq!(ms(100), b.next_delay());
+        assert_eq!(ms(100), b.next_delay());
+        assert!(!b.is_open());
+
+        assert_eq!(ms(10_000), b.next_delay());
+        assert!(b.is_open());
+        assert_eq!(ms(10_000), b.next_delay());
+    }
+
+    #[test]
+    fn test_backoff_attempts() {
+        let mut b = Backoff::from_policy(ConstantBackoff::new(ms(1)));
+        assert_eq!(0, b.attempts());
+
+        b.next();
+        b.next();
+        assert_eq!(2, b.attempts());
+
+        let mut b = Backoff::default();
+        assert_eq!(None, b.next(), "default backoff has no policy");
+        assert_eq!(0, b.attempts());
+    }
+}
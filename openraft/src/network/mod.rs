@@ -1,8 +1,14 @@
 //! The Raft network interface.
 
 mod backoff;
+mod backoff_policy;
+pub(crate) mod network_observer;
+mod payload_codec;
+mod rpc_authenticator;
+mod rpc_context;
 mod rpc_option;
 mod rpc_type;
+mod snapshot_resume;
 
 pub mod v1;
 pub mod v2;
@@ -10,7 +16,30 @@ pub mod v2;
 pub mod snapshot_transport;
 
 pub use backoff::Backoff;
+pub use backoff::BackoffPolicy;
+pub use backoff_policy::CircuitBreakerBackoff;
+pub use backoff_policy::ConstantBackoff;
+pub use backoff_policy::ExponentialBackoff;
+pub use backoff_policy::JitteredBackoff;
+pub use network_observer::NetworkObserver;
+pub use network_observer::RPCErrorKind;
+pub use network_observer::RPCEvent;
+pub use payload_codec::can_decode_payload;
+pub use payload_codec::decode_payload;
+pub use payload_codec::encode_payload;
+#[cfg(feature = "compression-lz4")] pub use payload_codec::Lz4;
+pub use payload_codec::NoCompression;
+pub use payload_codec::PayloadCodec;
+#[cfg(feature = "compression-zstd")] pub use payload_codec::Zstd;
+pub use payload_codec::MIN_COMPRESS_SIZE;
+pub use payload_codec::NO_COMPRESSION;
+pub use rpc_authenticator::AuthRpc;
+pub(crate) use rpc_authenticator::RpcAuth;
+pub use rpc_authenticator::RpcAuthenticator;
+pub use rpc_authenticator::AUTH_TOKEN_KEY;
+pub use rpc_context::RpcContext;
 pub use rpc_option::RPCOption;
 pub use rpc_type::RPCTypes;
+pub use snapshot_resume::SnapshotResume;
 pub use v1::RaftNetwork;
 pub use v1::RaftNetworkFactory;
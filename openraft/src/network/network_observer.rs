@@ -0,0 +1,285 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::error::RPCError;
+use crate::error::StreamingError;
+use crate::metrics::RttMetrics;
+use crate::network::RPCOption;
+use crate::network::RPCTypes;
+use crate::OptionalSend;
+use crate::OptionalSync;
+use crate::RaftTypeConfig;
+
+/// Receives a callback for every RPC sent to a target node, e.g., to export network metrics.
+///
+/// An observer is registered for a target by returning it from
+/// [`RaftNetworkV2::observer()`](`crate::network::v2::RaftNetworkV2::observer`). The callback is
+/// called in the task that sends the RPC, it should return quickly and must not block.
+pub trait NetworkObserver<C>: OptionalSend + OptionalSync + 'static
+where C: RaftTypeConfig
+{
+    /// Called when an RPC to the target completes, successfully or not.
+    fn on_rpc(&self, event: &RPCEvent<C>);
+}
+
+/// The class of the error of a failed RPC, reported in [`RPCEvent::error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RPCErrorKind {
+    /// The RPC did not complete in time.
+    Timeout,
+    /// The target is unreachable.
+    Unreachable,
+    /// The payload is too large for the target.
+    PayloadTooLarge,
+    /// The RPC failed because of other network errors.
+    Network,
+    /// The target returned an error.
+    Remote,
+}
+
+impl RPCErrorKind {
+    /// Returns the class of a snapshot transmission error, or `None` if it is not a network
+    /// error, e.g., the transmission is canceled or the local snapshot can not be read.
+    pub fn of_streaming<C, E>(err: &StreamingError<C, E>) -> Option<Self>
+    where
+        C: RaftTypeConfig,
+        E: Error,
+    {
+        match err {
+            StreamingError::Closed(_) => None,
+            StreamingError::StorageError(_) => None,
+            StreamingError::Timeout(_) => Some(Self::Timeout),
+            StreamingError::Unreachable(_) => Some(Self::Unreachable),
+            StreamingError::Network(_) => Some(Self::Network),
+            StreamingError::RemoteError(_) => Some(Self::Remote),
+        }
+    }
+}
+
+impl<C, E> From<&RPCError<C, E>> for RPCErrorKind
+where
+    C: RaftTypeConfig,
+    E: Error,
+{
+    fn from(err: &RPCError<C, E>) -> Self {
+        match err {
+            RPCError::Timeout(_) => Self::Timeout,
+            RPCError::Unreachable(_) => Self::Unreachable,
+            RPCError::PayloadTooLarge(_) => Self::PayloadTooLarge,
+            RPCError::Network(_) => Self::Network,
+            RPCError::RemoteError(_) => Self::Remote,
+        }
+    }
+}
+
+impl fmt::Display for RPCErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// An RPC sent to a target node, reported to a [`NetworkObserver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RPCEvent<C>
+where C: RaftTypeConfig
+{
+    /// The node the RPC is sent to.
+    pub target: C::NodeId,
+
+    pub rpc_type: RPCTypes,
+
+    /// The size of the request in bytes.
+    ///
+    /// It is the size reported by the transport with [`RPCOption::report_message_sizes()`]. If the
+    /// transport does not report it, it is estimated with the size hints of the log entries.
+    pub request_bytes: u64,
+
+    /// The size of the response in bytes, as reported by the transport with
+    /// [`RPCOption::report_message_sizes()`], or 0 if the transport does not report it.
+    pub response_bytes: u64,
+
+    /// The time elapsed from sending the request to receiving the response or the error.
+    pub rtt: Duration,
+
+    /// The class of the error if the RPC failed.
+    pub error: Option<RPCErrorKind>,
+}
+
+/// The message sizes of an RPC reported by the transport, shared by the clones of an
+/// [`RPCOption`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MessageSizes {
+    sizes: Arc<Mutex<Option<(u64, u64)>>>,
+}
+
+impl MessageSizes {
+    pub(crate) fn set(&self, request_bytes: u64, response_bytes: u64) {
+        *self.sizes.lock().unwrap() = Some((request_bytes, response_bytes));
+    }
+
+    pub(crate) fn take(&self) -> Option<(u64, u64)> {
+        self.sizes.lock().unwrap().take()
+    }
+}
+
+/// Records the RPCs sent to a target: reports them to the [`NetworkObserver`] of the target and
+/// updates the RTT estimate of the target in the shared [`RttMetrics`].
+///
+/// A clone has its own [`MessageSizes`], so that it can be used by another task sending RPCs to
+/// the same target concurrently.
+pub(crate) struct RpcRecorder<C>
+where C: RaftTypeConfig
+{
+    target: C::NodeId,
+    observer: Option<Arc<dyn NetworkObserver<C>>>,
+    rtt: Arc<Mutex<RttMetrics<C::NodeId>>>,
+    sizes: MessageSizes,
+}
+
+impl<C> Clone for RpcRecorder<C>
+where C: RaftTypeConfig
+{
+    fn clone(&self) -> Self {
+        Self::new(self.target, self.observer.clone(), self.rtt.clone())
+    }
+}
+
+impl<C> RpcRecorder<C>
+where C: RaftTypeConfig
+{
+    pub(crate) fn new(
+        target: C::NodeId,
+        observer: Option<Arc<dyn NetworkObserver<C>>>,
+        rtt: Arc<Mutex<RttMetrics<C::NodeId>>>,
+    ) -> Self {
+        Self {
+            target,
+            observer,
+            rtt,
+            sizes: MessageSizes::default(),
+        }
+    }
+
+    /// Build an [`RPCOption`] to which the transport reports the message sizes of an RPC.
+    pub(crate) fn option(&self, hard_ttl: Duration) -> RPCOption {
+        let mut option = RPCOption::new(hard_ttl);
+        option.message_sizes = self.sizes.clone();
+        option
+    }
+
+    /// Record a completed RPC.
+    ///
+    /// `request_bytes_hint` is used if the transport does not report the message sizes. The RTT
+    /// estimate is only updated by successful RPCs except `InstallSnapshot`, which takes as long as
+    /// the snapshot data it sends.
+    pub(crate) fn record(
+        &self,
+        rpc_type: RPCTypes,
+        request_bytes_hint: u64,
+        rtt: Duration,
+        error: Option<RPCErrorKind>,
+    ) {
+        let (request_bytes, response_bytes) = self.sizes.take().unwrap_or((request_bytes_hint, 0));
+
+        if error.is_none() && rpc_type != RPCTypes::InstallSnapshot {
+            let mut metrics = self.rtt.lock().unwrap();
+            metrics.entry(self.target).or_default().update(rtt);
+        }
+
+        tracing::debug!(
+            target = display(self.target),
+            rpc_type = display(rpc_type),
+            request_bytes,
+            response_bytes,
+            rtt = debug(rtt),
+            error = debug(error),
+            "rpc done"
+        );
+
+        if let Some(observer) = &self.observer {
+            observer.on_rpc(&RPCEvent {
+                target: self.target,
+                rpc_type,
+                request_bytes,
+                response_bytes,
+                rtt,
+                error,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use crate::engine::testing::UTConfig;
+    use crate::network::network_observer::RpcRecorder;
+    use crate::network::NetworkObserver;
+    use crate::network::RPCErrorKind;
+    use crate::network::RPCEvent;
+    use crate::network::RPCTypes;
+
+    #[derive(Default)]
+    struct Events {
+        events: Mutex<Vec<RPCEvent<UTConfig>>>,
+    }
+
+    impl NetworkObserver<UTConfig> for Events {
+        fn on_rpc(&self, event: &RPCEvent<UTConfig>) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_rpc_recorder() {
+        let observer = Arc::new(Events::default());
+        let rtt = Arc::new(Mutex::new(Default::default()));
+        let recorder = RpcRecorder::<UTConfig>::new(2, Some(observer.clone()), rtt.clone());
+
+        // Sizes reported by the transport
+        let option = recorder.option(Duration::from_millis(100));
+        option.report_message_sizes(100, 10);
+        recorder.record(RPCTypes::AppendEntries, 80, Duration::from_millis(8), None);
+
+        // Not reported, and failed
+        recorder.record(
+            RPCTypes::Vote,
+            0,
+            Duration::from_millis(100),
+            Some(RPCErrorKind::Timeout),
+        );
+
+        let events = observer.events.lock().unwrap();
+        assert_eq!(
+            vec![
+                RPCEvent {
+                    target: 2,
+                    rpc_type: RPCTypes::AppendEntries,
+                    request_bytes: 100,
+                    response_bytes: 10,
+                    rtt: Duration::from_millis(8),
+                    error: None,
+                },
+                RPCEvent {
+                    target: 2,
+                    rpc_type: RPCTypes::Vote,
+                    request_bytes: 0,
+                    response_bytes: 0,
+                    rtt: Duration::from_millis(100),
+                    error: Some(RPCErrorKind::Timeout),
+                },
+            ],
+            *events
+        );
+
+        let rtt = rtt.lock().unwrap();
+        assert_eq!(1, rtt[&2].samples, "a failed RPC does not update RTT");
+        assert_eq!(8_000, rtt[&2].smoothed_micros);
+    }
+}
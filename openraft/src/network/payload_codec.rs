@@ -0,0 +1,205 @@
+//! Compression of the payload of the replication RPCs.
+
+use std::io;
+
+use crate::OptionalSend;
+use crate::OptionalSync;
+
+/// The name of the algorithm of [`NoCompression`].
+pub const NO_COMPRESSION: &str = "none";
+
+/// A payload smaller than this is sent uncompressed by [`encode_payload()`], such as a heartbeat
+/// without any entry, because it barely shrinks.
+pub const MIN_COMPRESS_SIZE: usize = 512;
+
+/// Compresses the payload of `AppendEntries` and `InstallSnapshot` RPCs, i.e., the log entries
+/// and the snapshot chunks, after a network implementation serializes them.
+///
+/// It is configured by [`RaftTypeConfig::PayloadCodec`], and the default is [`NoCompression`].
+/// Openraft does not serialize the RPCs, thus the codec is applied by a [`RaftNetwork`]
+/// implementation, such as `openraft-grpc`, `openraft-tcp` and `openraft-quic`, with
+/// [`encode_payload()`] and [`decode_payload()`].
+///
+/// The name of the algorithm is sent along with the payload in the header of the RPC, and the
+/// receiver decodes it by the name. Thus nodes configured with different codecs can still talk to
+/// each other, as long as the receiver is built with the feature flag of the sender's algorithm.
+///
+/// [`RaftTypeConfig::PayloadCodec`]: crate::RaftTypeConfig::PayloadCodec
+/// [`RaftNetwork`]: crate::network::RaftNetwork
+pub trait PayloadCodec: OptionalSend + OptionalSync + 'static {
+    /// The name of the algorithm, which is sent in the RPC header, such as `"lz4"`.
+    const NAME: &'static str;
+
+    /// Compress a serialized payload.
+    fn encode(data: &[u8]) -> io::Result<Vec<u8>>;
+
+    /// Decompress a payload compressed by [`Self::encode()`].
+    fn decode(data: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+/// Sends the payload as is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoCompression;
+
+impl PayloadCodec for NoCompression {
+    const NAME: &'static str = NO_COMPRESSION;
+
+    fn encode(data: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(data.to_vec())
+    }
+
+    fn decode(data: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(data.to_vec())
+    }
+}
+
+/// Compresses with the LZ4 block format, which is fast and suits a LAN.
+///
+/// It is enabled by feature flag [`compression-lz4`].
+///
+/// [`compression-lz4`]: crate::docs::feature_flags#feature-flag-compression-lz4
+#[cfg(feature = "compression-lz4")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lz4;
+
+#[cfg(feature = "compression-lz4")]
+impl PayloadCodec for Lz4 {
+    const NAME: &'static str = "lz4";
+
+    fn encode(data: &[u8]) -> io::Result<Vec<u8>> {
+        lz4::block::compress(data, None, true)
+    }
+
+    fn decode(data: &[u8]) -> io::Result<Vec<u8>> {
+        lz4::block::decompress(data, None)
+    }
+}
+
+/// Compresses with Zstandard, which has a better ratio than [`Lz4`] and suits a WAN.
+///
+/// It is enabled by feature flag [`compression-zstd`].
+///
+/// [`compression-zstd`]: crate::docs::feature_flags#feature-flag-compression-zstd
+#[cfg(feature = "compression-zstd")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Zstd;
+
+#[cfg(feature = "compression-zstd")]
+impl PayloadCodec for Zstd {
+    const NAME: &'static str = "zstd";
+
+    fn encode(data: &[u8]) -> io::Result<Vec<u8>> {
+        zstd::bulk::compress(data, zstd::DEFAULT_COMPRESSION_LEVEL)
+    }
+
+    fn decode(data: &[u8]) -> io::Result<Vec<u8>> {
+        zstd::stream::decode_all(data)
+    }
+}
+
+/// Compress a serialized payload with `P`, returns the name of the algorithm to send in the RPC
+/// header along with the payload.
+///
+/// A payload smaller than [`MIN_COMPRESS_SIZE`] is not compressed, and the name is
+/// [`NO_COMPRESSION`].
+pub fn encode_payload<P>(data: Vec<u8>) -> io::Result<(&'static str, Vec<u8>)>
+where P: PayloadCodec {
+    if P::NAME == NO_COMPRESSION || data.len() < MIN_COMPRESS_SIZE {
+        return Ok((NO_COMPRESSION, data));
+    }
+    Ok((P::NAME, P::encode(&data)?))
+}
+
+/// Returns `true` if a payload compressed with the algorithm `name` can be decoded by
+/// [`decode_payload()`], i.e., the feature flag of the algorithm is enabled.
+pub fn can_decode_payload(name: &str) -> bool {
+    match name {
+        "" | NO_COMPRESSION => true,
+        #[cfg(feature = "compression-lz4")]
+        Lz4::NAME => true,
+        #[cfg(feature = "compression-zstd")]
+        Zstd::NAME => true,
+        _ => false,
+    }
+}
+
+/// Decompress a payload with the algorithm named in the RPC header.
+///
+/// Every algorithm enabled by feature flags can be decoded, no matter which [`PayloadCodec`] is
+/// configured on this node. An empty name is treated as [`NO_COMPRESSION`].
+pub fn decode_payload(name: &str, data: Vec<u8>) -> io::Result<Vec<u8>> {
+    match name {
+        "" | NO_COMPRESSION => Ok(data),
+        #[cfg(feature = "compression-lz4")]
+        Lz4::NAME => Lz4::decode(&data),
+        #[cfg(feature = "compression-zstd")]
+        Zstd::NAME => Zstd::decode(&data),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("unsupported payload compression: {}", name),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::can_decode_payload;
+    use super::decode_payload;
+    use super::encode_payload;
+    use super::NoCompression;
+    use super::NO_COMPRESSION;
+
+    fn payload() -> Vec<u8> {
+        br#"{"log_id":{"leader_id":{"term":1,"node_id":1},"index":1},"payload":"foo"}"#.repeat(20)
+    }
+
+    #[test]
+    fn test_no_compression() -> anyhow::Result<()> {
+        let (name, encoded) = encode_payload::<NoCompression>(payload())?;
+        assert_eq!(NO_COMPRESSION, name);
+        assert_eq!(payload(), encoded);
+        assert_eq!(payload(), decode_payload(name, encoded)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_unknown_algorithm() -> anyhow::Result<()> {
+        assert_eq!(b"foo".to_vec(), decode_payload("", b"foo".to_vec())?);
+
+        let res = decode_payload("brotli", b"foo".to_vec());
+        assert_eq!(std::io::ErrorKind::Unsupported, res.unwrap_err().kind());
+
+        assert!(can_decode_payload(""));
+        assert!(can_decode_payload(NO_COMPRESSION));
+        assert!(!can_decode_payload("brotli"));
+        Ok(())
+    }
+
+    #[cfg(feature = "compression-lz4")]
+    #[test]
+    fn test_lz4() -> anyhow::Result<()> {
+        use super::Lz4;
+
+        let (name, encoded) = encode_payload::<Lz4>(payload())?;
+        assert_eq!("lz4", name);
+        assert!(encoded.len() < payload().len());
+        assert_eq!(payload(), decode_payload(name, encoded)?);
+
+        let (name, _) = encode_payload::<Lz4>(b"foo".to_vec())?;
+        assert_eq!(NO_COMPRESSION, name, "small payload is not compressed");
+        Ok(())
+    }
+
+    #[cfg(feature = "compression-zstd")]
+    #[test]
+    fn test_zstd() -> anyhow::Result<()> {
+        use super::Zstd;
+
+        let (name, encoded) = encode_payload::<Zstd>(payload())?;
+        assert_eq!("zstd", name);
+        assert!(encoded.len() < payload().len());
+        assert_eq!(payload(), decode_payload(name, encoded)?);
+        assert!(can_decode_payload(name));
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use std::sync::Arc;
+
+use anyerror::AnyError;
+
+use crate::error::Unauthenticated;
+use crate::network::RPCTypes;
+use crate::network::RpcContext;
+use crate::raft::AppendEntriesRequest;
+use crate::raft::TransferLeaderRequest;
+use crate::raft::TransferSnapshotRequest;
+use crate::raft::VoteRequest;
+use crate::OptionalSend;
+use crate::OptionalSync;
+use crate::RaftTypeConfig;
+use crate::SnapshotMeta;
+use crate::Vote;
+
+/// The key of the authentication token in the [`RpcContext`] of an RPC.
+pub const AUTH_TOKEN_KEY: &str = "openraft-auth-token";
+
+/// Signs every RPC sent by this node and verifies every RPC received, so that a node rejects the
+/// messages from a node that is removed from the cluster, or from a misconfigured peer that claims
+/// a reused node id.
+///
+/// An authenticator is registered by returning it from
+/// [`RaftNetworkFactory::authenticator()`](`crate::network::RaftNetworkFactory::authenticator`).
+/// The token returned by [`Self::sign()`] is sent in the [`RpcContext`] of the request with key
+/// [`AUTH_TOKEN_KEY`], and is passed to [`Self::verify()`] on the target. E.g., the token is an
+/// HMAC of the fields of the RPC with a key shared by the sender and the target, and a removed
+/// node's key is revoked.
+///
+/// A snapshot is authenticated by its vote and meta: the same token is sent with every chunk.
+/// Verification happens in [`Raft::append_entries()`], [`Raft::vote()`] and
+/// [`Raft::install_snapshot()`]. A snapshot received by the application with
+/// [`RaftNetworkV2::full_snapshot()`] has to be verified by the application with the token in
+/// [`RPCOption::context()`], before calling [`Raft::install_full_snapshot()`].
+///
+/// [`Raft::append_entries()`]: crate::Raft::append_entries
+/// [`Raft::vote()`]: crate::Raft::vote
+/// [`Raft::install_snapshot()`]: crate::Raft::install_snapshot
+/// [`Raft::install_full_snapshot()`]: crate::Raft::install_full_snapshot
+/// [`RaftNetworkV2::full_snapshot()`]: crate::network::v2::RaftNetworkV2::full_snapshot
+/// [`RPCOption::context()`]: crate::network::RPCOption::context
+pub trait RpcAuthenticator<C>: OptionalSend + OptionalSync + 'static
+where C: RaftTypeConfig
+{
+    /// Returns the token to send with an RPC to `target`.
+    fn sign(&self, target: C::NodeId, rpc: AuthRpc<'_, C>) -> String;
+
+    /// Verify the token received with an RPC, `None` if the RPC carries no token.
+    ///
+    /// An error rejects the RPC with an [`Unauthenticated`] error.
+    ///
+    /// [`Unauthenticated`]: crate::error::Unauthenticated
+    fn verify(&self, rpc: AuthRpc<'_, C>, token: Option<&str>) -> Result<(), AnyError>;
+}
+
+/// An RPC to sign or to verify by a [`RpcAuthenticator`].
+pub enum AuthRpc<'a, C>
+where C: RaftTypeConfig
+{
+    AppendEntries(&'a AppendEntriesRequest<C>),
+    Vote(&'a VoteRequest<C>),
+    InstallSnapshot {
+        vote: &'a Vote<C::NodeId>,
+        meta: &'a SnapshotMeta<C>,
+    },
+    /// Ask a node to send its snapshot to another node on behalf of the leader.
+    TransferSnapshot(&'a TransferSnapshotRequest<C>),
+    /// Ask a node to take part in a leadership transfer.
+    TransferLeader(&'a TransferLeaderRequest<C>),
+}
+
+impl<'a, C> AuthRpc<'a, C>
+where C: RaftTypeConfig
+{
+    pub fn rpc_type(&self) -> RPCTypes {
+        match self {
+            AuthRpc::AppendEntries(_) => RPCTypes::AppendEntries,
+            AuthRpc::Vote(_) => RPCTypes::Vote,
+            AuthRpc::InstallSnapshot { .. } => RPCTypes::InstallSnapshot,
+            AuthRpc::TransferSnapshot(_) => RPCTypes::InstallSnapshot,
+            AuthRpc::TransferLeader(_) => RPCTypes::Vote,
+        }
+    }
+
+    /// The vote of the sender carried in the RPC.
+    pub fn vote(&self) -> &'a Vote<C::NodeId> {
+        match self {
+            AuthRpc::AppendEntries(req) => &req.vote,
+            AuthRpc::Vote(req) => &req.vote,
+            AuthRpc::InstallSnapshot { vote, .. } => vote,
+            AuthRpc::TransferSnapshot(req) => &req.vote,
+            AuthRpc::TransferLeader(req) => &req.from_leader,
+        }
+    }
+
+    /// The node that claims to send the RPC, i.e., the leader or candidate in the vote.
+    pub fn sender(&self) -> Option<C::NodeId> {
+        self.vote().leader_id().voted_for()
+    }
+}
+
+/// The optional [`RpcAuthenticator`] of a Raft node.
+pub(crate) struct RpcAuth<C>
+where C: RaftTypeConfig
+{
+    authenticator: Option<Arc<dyn RpcAuthenticator<C>>>,
+}
+
+impl<C> Clone for RpcAuth<C>
+where C: RaftTypeConfig
+{
+    fn clone(&self) -> Self {
+        Self {
+            authenticator: self.authenticator.clone(),
+        }
+    }
+}
+
+impl<C> RpcAuth<C>
+where C: RaftTypeConfig
+{
+    pub(crate) fn new(authenticator: Option<Arc<dyn RpcAuthenticator<C>>>) -> Self {
+        Self { authenticator }
+    }
+
+    pub(crate) fn sign_append_entries(&self, target: C::NodeId, req: &mut AppendEntriesRequest<C>) {
+        if let Some(a) = &self.authenticator {
+            let token = a.sign(target, AuthRpc::AppendEntries(req));
+            req.context.set(AUTH_TOKEN_KEY, token);
+        }
+    }
+
+    pub(crate) fn sign_vote(&self, target: C::NodeId, req: &mut VoteRequest<C>) {
+        if let Some(a) = &self.authenticator {
+            let token = a.sign(target, AuthRpc::Vote(req));
+            req.context.set(AUTH_TOKEN_KEY, token);
+        }
+    }
+
+    pub(crate) fn sign_transfer_snapshot(&self, target: C::NodeId, req: &mut TransferSnapshotRequest<C>) {
+        if let Some(a) = &self.authenticator {
+            let token = a.sign(target, AuthRpc::TransferSnapshot(req));
+            req.context.set(AUTH_TOKEN_KEY, token);
+        }
+    }
+
+    pub(crate) fn sign_transfer_leader(&self, target: C::NodeId, req: &mut TransferLeaderRequest<C>) {
+        if let Some(a) = &self.authenticator {
+            let token = a.sign(target, AuthRpc::TransferLeader(req));
+            req.context.set(AUTH_TOKEN_KEY, token);
+        }
+    }
+
+    /// Sign a snapshot, the token is put in `context`, which is sent with every chunk.
+    pub(crate) fn sign_snapshot(
+        &self,
+        target: C::NodeId,
+        vote: &Vote<C::NodeId>,
+        meta: &SnapshotMeta<C>,
+        context: &mut RpcContext,
+    ) {
+        if let Some(a) = &self.authenticator {
+            let token = a.sign(target, AuthRpc::InstallSnapshot { vote, meta });
+            context.set(AUTH_TOKEN_KEY, token);
+        }
+    }
+
+    pub(crate) fn verify(&self, rpc: AuthRpc<'_, C>, context: &RpcContext) -> Result<(), Unauthenticated> {
+        let Some(a) = &self.authenticator else {
+            return Ok(());
+        };
+
+        let rpc_type = rpc.rpc_type();
+        a.verify(rpc, context.get(AUTH_TOKEN_KEY)).map_err(|source| {
+            tracing::warn!(
+                rpc_type = display(rpc_type),
+                error = display(&source),
+                "reject unauthenticated RPC"
+            );
+            Unauthenticated { rpc_type, source }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyerror::AnyError;
+
+    use crate::engine::testing::UTConfig;
+    use crate::error::Unauthenticated;
+    use crate::network::rpc_authenticator::RpcAuth;
+    use crate::network::AuthRpc;
+    use crate::network::RPCTypes;
+    use crate::network::RpcAuthenticator;
+    use crate::network::AUTH_TOKEN_KEY;
+    use crate::raft::VoteRequest;
+    use crate::Vote;
+
+    /// Signs with the sender and the target, and accepts only RPCs from node 1 to node 2.
+    struct Auth;
+
+    impl RpcAuthenticator<UTConfig> for Auth {
+        fn sign(&self, target: u64, rpc: AuthRpc<'_, UTConfig>) -> String {
+            format!("{:?}->{}", rpc.sender(), target)
+        }
+
+        fn verify(&self, _rpc: AuthRpc<'_, UTConfig>, token: Option<&str>) -> Result<(), AnyError> {
+            if token == Some("Some(1)->2") {
+                Ok(())
+            } else {
+                Err(AnyError::error(format!("bad token: {:?}", token)))
+            }
+        }
+    }
+
+    #[test]
+    fn test_rpc_auth() {
+        let auth = RpcAuth::<UTConfig>::new(Some(std::sync::Arc::new(Auth)));
+
+        let mut req = VoteRequest::<UTConfig>::new(Vote::new(1, 1), None);
+        auth.sign_vote(2, &mut req);
+        assert_eq!(Some("Some(1)->2"), req.context.get(AUTH_TOKEN_KEY));
+        assert!(auth.verify(AuthRpc::Vote(&req), &req.context).is_ok());
+
+        let mut req = VoteRequest::<UTConfig>::new(Vote::new(1, 3), None);
+        auth.sign_vote(2, &mut req);
+        assert_eq!(
+            Err(Unauthenticated {
+                rpc_type: RPCTypes::Vote,
+                source: AnyError::error("bad token: Some(\"Some(3)->2\")"),
+            }),
+            auth.verify(AuthRpc::Vote(&req), &req.context)
+        );
+
+        // Without an authenticator, nothing is signed and every RPC is accepted.
+        let auth = RpcAuth::<UTConfig>::new(None);
+        let mut req = VoteRequest::<UTConfig>::new(Vote::new(1, 3), None);
+        auth.sign_vote(2, &mut req);
+        assert!(req.context.is_empty());
+        assert!(auth.verify(AuthRpc::Vote(&req), &req.context).is_ok());
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+
+/// A carrier of the context propagated with an RPC from the sender to the receiver, such as the
+/// OpenTelemetry trace context of the span in which the RPC is sent.
+///
+/// It is a map of string keys to string values, the form the text map propagators, e.g., the W3C
+/// `traceparent` propagator, read and write. It is sent in the `context` field of the
+/// [`AppendEntriesRequest`], [`VoteRequest`] and [`InstallSnapshotRequest`]:
+/// the sender fills it in [`RaftNetworkFactory::inject_context()`] and the receiver reads it in
+/// [`RaftNetworkFactory::extract_context()`].
+///
+/// An empty context is sent if neither is implemented.
+///
+/// [`AppendEntriesRequest`]: crate::raft::AppendEntriesRequest
+/// [`VoteRequest`]: crate::raft::VoteRequest
+/// [`InstallSnapshotRequest`]: crate::raft::InstallSnapshotRequest
+/// [`RaftNetworkFactory::inject_context()`]: crate::network::RaftNetworkFactory::inject_context
+/// [`RaftNetworkFactory::extract_context()`]: crate::network::RaftNetworkFactory::extract_context
+#[derive(Debug, Clone, Default)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct RpcContext {
+    fields: BTreeMap<String, String>,
+}
+
+impl RpcContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value of `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(|v| v.as_str())
+    }
+
+    /// Set the value of `key`, replacing the previous value.
+    pub fn set(&mut self, key: impl ToString, value: impl ToString) {
+        self.fields.insert(key.to_string(), value.to_string());
+    }
+
+    /// Returns all the key-value pairs, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::network::RpcContext;
+
+    #[test]
+    fn test_rpc_context() {
+        let mut ctx = RpcContext::new();
+        assert!(ctx.is_empty());
+        assert_eq!(None, ctx.get("traceparent"));
+
+        ctx.set("traceparent", "00-foo-bar-01");
+        ctx.set("tracestate", "a=1");
+        ctx.set("traceparent", "00-foo-baz-01");
+
+        assert_eq!(Some("00-foo-baz-01"), ctx.get("traceparent"));
+        assert_eq!(
+            vec![("traceparent", "00-foo-baz-01"), ("tracestate", "a=1")],
+            ctx.iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,5 +1,9 @@
 use std::time::Duration;
 
+use crate::network::network_observer::MessageSizes;
+use crate::network::RpcContext;
+use crate::network::SnapshotResume;
+
 /// An additional argument to the [`RaftNetwork`] methods to allow applications to customize
 /// networking behaviors.
 ///
@@ -13,6 +17,21 @@ pub struct RPCOption {
 
     /// The size of the snapshot chunk.
     pub(crate) snapshot_chunk_size: Option<usize>,
+
+    /// The progress of the snapshot transmission to resume from.
+    pub(crate) snapshot_resume: Option<SnapshotResume>,
+
+    /// Whether to compress the snapshot chunks.
+    pub(crate) compress_snapshot: bool,
+
+    /// The maximum bytes per second to send snapshot data.
+    pub(crate) replication_rate_limit: Option<u64>,
+
+    /// The context to propagate to the target.
+    pub(crate) context: RpcContext,
+
+    /// The message sizes reported by the transport.
+    pub(crate) message_sizes: MessageSizes,
 }
 
 impl RPCOption {
@@ -20,6 +39,11 @@ impl RPCOption {
         Self {
             hard_ttl,
             snapshot_chunk_size: None,
+            snapshot_resume: None,
+            compress_snapshot: false,
+            replication_rate_limit: None,
+            context: RpcContext::default(),
+            message_sizes: MessageSizes::default(),
         }
     }
 
@@ -47,8 +71,71 @@ impl RPCOption {
         self.hard_ttl
     }
 
+    /// Override the timeout of this call, i.e., [`hard_ttl()`] and [`soft_ttl()`].
+    ///
+    /// Openraft builds an `RPCOption` with the timeout configured for the type of the RPC, such
+    /// as [`Config::vote_timeout`]. An implementation that forwards the call, e.g., a
+    /// `full_snapshot()` that sends the chunks with [`Chunked`], can use this to give the
+    /// forwarded call a different timeout.
+    ///
+    /// [`hard_ttl()`]: `Self::hard_ttl`
+    /// [`soft_ttl()`]: `Self::soft_ttl`
+    /// [`Config::vote_timeout`]: `crate::Config::vote_timeout`
+    /// [`Chunked`]: `crate::network::snapshot_transport::Chunked`
+    pub fn with_hard_ttl(mut self, hard_ttl: Duration) -> Self {
+        self.hard_ttl = hard_ttl;
+        self
+    }
+
     /// Get the recommended size of the snapshot chunk for transport.
     pub fn snapshot_chunk_size(&self) -> Option<usize> {
         self.snapshot_chunk_size
     }
+
+    /// Get the progress of transmitting a snapshot to the target, to resume an interrupted
+    /// transmission from.
+    ///
+    /// It is set by Openraft when calling `full_snapshot()`.
+    pub fn snapshot_resume(&self) -> Option<&SnapshotResume> {
+        self.snapshot_resume.as_ref()
+    }
+
+    /// Whether to compress the snapshot chunks, as configured by [`Config::compress_snapshot`].
+    ///
+    /// It is set by Openraft when calling `full_snapshot()`, and the default chunked transport
+    /// compresses the chunks with Zstandard if the target accepts.
+    ///
+    /// [`Config::compress_snapshot`]: `crate::Config::compress_snapshot`
+    pub fn compress_snapshot(&self) -> bool {
+        self.compress_snapshot
+    }
+
+    /// Get the maximum bytes per second to send the snapshot data, if the replication to the
+    /// target is rate limited.
+    ///
+    /// It is set by Openraft when calling `full_snapshot()`. The default chunked transport
+    /// enforces it, and an application defined `full_snapshot()` should too.
+    pub fn replication_rate_limit(&self) -> Option<u64> {
+        self.replication_rate_limit
+    }
+
+    /// Get the context to propagate to the target, such as the trace context.
+    ///
+    /// It is set by Openraft when calling `full_snapshot()`, and the default chunked transport
+    /// sends it with every chunk. See [`RpcContext`].
+    pub fn context(&self) -> &RpcContext {
+        &self.context
+    }
+
+    /// Report the sizes in bytes of the request and the response of the RPC sent with this option,
+    /// e.g., the sizes of the serialized messages.
+    ///
+    /// A transport calls it after the RPC completes, so that the sizes are reported to the
+    /// [`NetworkObserver`] of the target. On a stream, the sizes of every request and its response
+    /// should be reported.
+    ///
+    /// [`NetworkObserver`]: crate::network::NetworkObserver
+    pub fn report_message_sizes(&self, request_bytes: u64, response_bytes: u64) {
+        self.message_sizes.set(request_bytes, response_bytes);
+    }
 }
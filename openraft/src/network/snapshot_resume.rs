@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::SnapshotId;
+use crate::SnapshotSegmentId;
+
+/// The progress of transmitting a snapshot to a target, with which an interrupted transmission
+/// resumes from where it stopped instead of from the beginning.
+///
+/// It records the offset up to which the target has acknowledged to have durably received the
+/// snapshot data. It is kept by the replication stream of a target and is passed to
+/// [`RaftNetworkV2::full_snapshot()`] in [`RPCOption::snapshot_resume()`]:
+/// a transport starts sending a snapshot from [`Self::offset_of()`] and calls [`Self::update()`]
+/// every time the target acknowledges a segment.
+///
+/// The recorded offset is just a hint: if the target no longer has the data, e.g., it restarted,
+/// it replies with a [`SnapshotMismatch`] error and the transport restarts from the offset it
+/// expects.
+///
+/// Cloned instances share the same state.
+///
+/// [`RaftNetworkV2::full_snapshot()`]: crate::network::v2::RaftNetworkV2::full_snapshot
+/// [`RPCOption::snapshot_resume()`]: crate::network::RPCOption::snapshot_resume
+/// [`SnapshotMismatch`]: crate::error::SnapshotMismatch
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotResume {
+    durable: Arc<Mutex<Option<SnapshotSegmentId>>>,
+}
+
+impl SnapshotResume {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the offset to resume transmitting snapshot `snapshot_id` from.
+    ///
+    /// It is `0` if no data of this snapshot has been acknowledged by the target.
+    pub fn offset_of(&self, snapshot_id: &SnapshotId) -> u64 {
+        let durable = self.durable.lock().unwrap();
+        match &*durable {
+            Some(seg) if &seg.id == snapshot_id => seg.offset,
+            _ => 0,
+        }
+    }
+
+    /// Returns the last segment acknowledged by the target, if there is an unfinished
+    /// transmission.
+    pub fn get(&self) -> Option<SnapshotSegmentId> {
+        self.durable.lock().unwrap().clone()
+    }
+
+    /// Record that the target has durably received the snapshot data before `segment.offset`.
+    pub fn update(&self, segment: SnapshotSegmentId) {
+        *self.durable.lock().unwrap() = Some(segment);
+    }
+
+    /// Clear the progress, e.g., when a snapshot is completely transmitted.
+    pub fn reset(&self) {
+        *self.durable.lock().unwrap() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::network::SnapshotResume;
+    use crate::SnapshotSegmentId;
+
+    #[test]
+    fn test_snapshot_resume() {
+        let resume = SnapshotResume::new();
+        assert_eq!(0, resume.offset_of(&"a".to_string()));
+
+        resume.clone().update(SnapshotSegmentId::from(("a", 10)));
+        assert_eq!(10, resume.offset_of(&"a".to_string()), "clones share state");
+        assert_eq!(0, resume.offset_of(&"b".to_string()), "another snapshot starts from 0");
+        assert_eq!(Some(SnapshotSegmentId::from(("a", 10))), resume.get());
+
+        resume.reset();
+        assert_eq!(0, resume.offset_of(&"a".to_string()));
+        assert_eq!(None, resume.get());
+    }
+}
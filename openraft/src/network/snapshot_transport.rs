@@ -17,9 +17,11 @@ use crate::error::RPCError;
 use crate::error::RaftError;
 use crate::error::ReplicationClosed;
 use crate::error::StreamingError;
+use crate::network::decode_payload;
 use crate::network::RPCOption;
 use crate::raft::InstallSnapshotRequest;
 use crate::raft::SnapshotResponse;
+use crate::replication::rate_limiter::RateLimiter;
 use crate::type_config::alias::AsyncRuntimeOf;
 use crate::AsyncRuntime;
 use crate::ErrorSubject;
@@ -30,6 +32,7 @@ use crate::RaftNetwork;
 use crate::RaftTypeConfig;
 use crate::Snapshot;
 use crate::SnapshotId;
+use crate::SnapshotSegmentId;
 use crate::StorageError;
 use crate::StorageIOError;
 use crate::ToStorageResult;
@@ -49,6 +52,10 @@ pub trait SnapshotTransport<C: RaftTypeConfig> {
     /// `cancel` is a future that is polled by this function to check if the caller decides to
     /// cancel.
     /// It return `Ready` if the caller decide to cancel this snapshot transmission.
+    ///
+    /// If [`RPCOption::snapshot_resume()`] is set, the transmission starts from the offset
+    /// acknowledged by the target in a previous transmission of the same snapshot, and records
+    /// the progress in it.
     // TODO: consider removing dependency on RaftNetwork
     async fn send_snapshot<Net>(
         net: &mut Net,
@@ -109,11 +116,37 @@ where C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io:
     where
         Net: RaftNetwork<C> + ?Sized,
     {
+        // Let the target verify the entire received data before installing it.
+        if snapshot.meta.checksum.is_none() {
+            let checksum = snapshot_checksum(snapshot.snapshot.as_mut())
+                .await
+                .sto_res(|| (ErrorSubject::Snapshot(Some(snapshot.meta.signature())), ErrorVerb::Read))?;
+            snapshot.meta.checksum = Some(checksum);
+        }
+
         let subject_verb = || (ErrorSubject::Snapshot(Some(snapshot.meta.signature())), ErrorVerb::Read);
 
-        let mut offset = 0;
         let end = snapshot.snapshot.seek(SeekFrom::End(0)).await.sto_res(subject_verb)?;
 
+        // Resume from the offset the target has acknowledged in a previous transmission.
+        let resume = option.snapshot_resume().cloned();
+        let snapshot_id = snapshot.meta.snapshot_id.clone();
+        let mut offset = resume.as_ref().map_or(0, |r| r.offset_of(&snapshot_id));
+        if offset > end {
+            offset = 0;
+        }
+        if offset > 0 {
+            tracing::info!(offset, end, "resume sending snapshot {}", snapshot_id);
+        }
+
+        let mut rate_limiter = option.replication_rate_limit().and_then(RateLimiter::<C>::new);
+
+        // Compression is negotiated with the first chunk: if the target does not acknowledge it,
+        // the rest is sent uncompressed. A single chunk snapshot is never compressed, because
+        // the target can not send it back once installed.
+        let mut compress = option.compress_snapshot() && cfg!(feature = "compression-zstd");
+        let mut compress_acked = false;
+
         let mut c = std::pin::pin!(cancel);
         loop {
             // If canceled, return at once
@@ -140,15 +173,31 @@ where C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io:
             }
 
             let n_read = buf.len();
-
             let done = (offset + n_read as u64) == end;
+
+            let compressing = compress && (compress_acked || !done);
+            let (compression, data) = if compressing {
+                let (name, data) = compress_chunk(&buf).sto_res(subject_verb)?;
+                (Some(name), data)
+            } else {
+                (None, buf)
+            };
+
+            if let Some(l) = &mut rate_limiter {
+                l.acquire(data.len() as u64).await;
+            }
+
             let req = InstallSnapshotRequest {
                 vote,
                 meta: snapshot.meta.clone(),
                 offset,
-                data: buf,
+                data,
                 done,
-            };
+                checksum: None,
+                compression,
+                context: option.context().clone(),
+            }
+            .with_checksum();
 
             // Send the RPC over to the target.
             tracing::debug!(
@@ -183,13 +232,23 @@ where C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io:
                                         //
                                         match snapshot_err {
                                             InstallSnapshotError::SnapshotMismatch(mismatch) => {
-                                                //
+                                                // The target reports the offset it has durably
+                                                // received, continue from there.
+                                                offset = if mismatch.expect.id == snapshot_id
+                                                    && mismatch.expect.offset <= end
+                                                {
+                                                    mismatch.expect.offset
+                                                } else {
+                                                    0
+                                                };
+
                                                 tracing::warn!(
                                                     mismatch = display(&mismatch),
-                                                    "snapshot mismatch, reset offset and retry"
+                                                    "snapshot mismatch, retry from offset {}",
+                                                    offset
                                                 );
-                                                offset = 0;
                                             }
+                                            InstallSnapshotError::Unauthenticated(_) => {}
                                         }
                                     }
                                 }
@@ -210,35 +269,91 @@ where C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io:
                 return Ok(SnapshotResponse::new(resp.vote));
             }
 
+            if compressing && resp.compression.is_none() {
+                tracing::info!(
+                    offset,
+                    "target does not decode compressed snapshot chunk, send the snapshot uncompressed"
+                );
+                compress = false;
+                continue;
+            }
+            compress_acked = compressing;
+
             if done {
+                if let Some(r) = &resume {
+                    r.reset();
+                }
                 return Ok(SnapshotResponse::new(resp.vote));
             }
 
             offset += n_read as u64;
+
+            if let Some(r) = &resume {
+                r.update(SnapshotSegmentId::from((&snapshot_id, offset)));
+            }
         }
     }
 
     async fn receive_snapshot(
         streaming: &mut Option<Streaming<C>>,
         raft: &Raft<C>,
-        req: InstallSnapshotRequest<C>,
+        mut req: InstallSnapshotRequest<C>,
     ) -> Result<Option<Snapshot<C>>, RaftError<C, InstallSnapshotError>> {
         let snapshot_id = &req.meta.snapshot_id;
         let snapshot_meta = req.meta.clone();
         let done = req.done;
+        let offset = req.offset;
 
         tracing::info!(req = display(&req), "{}", func_name!());
 
         let curr_id = streaming.as_ref().map(|s| s.snapshot_id());
 
-        if curr_id != Some(snapshot_id) {
+        if !req.is_checksum_valid() {
+            // The chunk is corrupted in transit: let the leader send it again from the offset
+            // this node has received.
+            tracing::warn!(req = display(&req), "snapshot chunk checksum mismatch");
+            return Err(resend_from_received(streaming, &req));
+        }
+
+        if let Some(compression) = req.compression.take() {
+            match decode_payload(&compression, std::mem::take(&mut req.data)) {
+                Ok(data) => req.data = data,
+                Err(e) => {
+                    tracing::warn!(
+                        error = display(&e),
+                        "failed to decompress snapshot chunk: {}",
+                        compression
+                    );
+                    return Err(resend_from_received(streaming, &req));
+                }
+            }
+        }
+
+        if curr_id == Some(snapshot_id) {
+            // Data before `req.offset` is missing, e.g., the previous chunks were lost.
+            // Report the offset this node has received to let the leader resume from there.
+            let received = streaming.as_ref().unwrap().offset();
+            if req.offset > received {
+                let mismatch = InstallSnapshotError::SnapshotMismatch(crate::error::SnapshotMismatch {
+                    expect: SnapshotSegmentId {
+                        id: snapshot_id.clone(),
+                        offset: received,
+                    },
+                    got: SnapshotSegmentId {
+                        id: snapshot_id.clone(),
+                        offset: req.offset,
+                    },
+                });
+                return Err(RaftError::APIError(mismatch));
+            }
+        } else {
             if req.offset != 0 {
                 let mismatch = InstallSnapshotError::SnapshotMismatch(crate::error::SnapshotMismatch {
-                    expect: crate::SnapshotSegmentId {
+                    expect: SnapshotSegmentId {
                         id: snapshot_id.clone(),
                         offset: 0,
                     },
-                    got: crate::SnapshotSegmentId {
+                    got: SnapshotSegmentId {
                         id: snapshot_id.clone(),
                         offset: req.offset,
                     },
@@ -271,6 +386,36 @@ where C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io:
                 StorageError::from(io_err)
             })?;
 
+            if let Some(expect) = snapshot_meta.checksum {
+                let got = snapshot_checksum(data.as_mut()).await.map_err(|e| {
+                    let io_err = StorageIOError::read_snapshot(Some(snapshot_meta.signature()), &e);
+                    StorageError::from(io_err)
+                })?;
+
+                if got != expect {
+                    // The received data is corrupted: discard it and let the leader send the
+                    // snapshot again from the start.
+                    tracing::warn!(
+                        "snapshot checksum mismatch: expect: {:x}, got: {:x}; meta: {}",
+                        expect,
+                        got,
+                        snapshot_meta
+                    );
+
+                    let mismatch = InstallSnapshotError::SnapshotMismatch(crate::error::SnapshotMismatch {
+                        expect: SnapshotSegmentId {
+                            id: snapshot_meta.snapshot_id.clone(),
+                            offset: 0,
+                        },
+                        got: SnapshotSegmentId {
+                            id: snapshot_meta.snapshot_id.clone(),
+                            offset,
+                        },
+                    });
+                    return Err(RaftError::APIError(mismatch));
+                }
+            }
+
             tracing::info!("finished streaming snapshot: {:?}", snapshot_meta);
             return Ok(Some(Snapshot::new(snapshot_meta, data)));
         }
@@ -279,6 +424,72 @@ where C::SnapshotData: tokio::io::AsyncRead + tokio::io::AsyncWrite + tokio::io:
     }
 }
 
+/// Build an error that asks the leader to send the snapshot again from the offset this node has
+/// received, when the chunk `req` is corrupted.
+fn resend_from_received<C>(
+    streaming: &Option<Streaming<C>>,
+    req: &InstallSnapshotRequest<C>,
+) -> RaftError<C, InstallSnapshotError>
+where
+    C: RaftTypeConfig,
+{
+    let snapshot_id = &req.meta.snapshot_id;
+    let received = match streaming {
+        Some(s) if s.snapshot_id() == snapshot_id => s.offset(),
+        _ => 0,
+    };
+
+    let mismatch = InstallSnapshotError::SnapshotMismatch(crate::error::SnapshotMismatch {
+        expect: SnapshotSegmentId {
+            id: snapshot_id.clone(),
+            offset: received,
+        },
+        got: SnapshotSegmentId {
+            id: snapshot_id.clone(),
+            offset: req.offset,
+        },
+    });
+    RaftError::APIError(mismatch)
+}
+
+/// Compress a snapshot chunk with Zstandard, returns the name of the algorithm and the compressed
+/// data.
+#[cfg(feature = "compression-zstd")]
+fn compress_chunk(data: &[u8]) -> Result<(String, Vec<u8>), std::io::Error> {
+    use crate::network::PayloadCodec;
+    use crate::network::Zstd;
+
+    Ok((Zstd::NAME.to_string(), Zstd::encode(data)?))
+}
+
+#[cfg(not(feature = "compression-zstd"))]
+fn compress_chunk(_data: &[u8]) -> Result<(String, Vec<u8>), std::io::Error> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "snapshot compression requires feature flag compression-zstd",
+    ))
+}
+
+/// Computes the CRC32 of the entire snapshot data, then restores the position of `data`.
+async fn snapshot_checksum<D>(data: &mut D) -> Result<u32, std::io::Error>
+where D: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin + ?Sized {
+    let pos = data.stream_position().await?;
+    data.seek(SeekFrom::Start(0)).await?;
+
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = data.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    data.seek(SeekFrom::Start(pos)).await?;
+    Ok(hasher.finalize())
+}
+
 /// The Raft node is streaming in a snapshot from the leader.
 pub struct Streaming<C>
 where C: RaftTypeConfig
@@ -308,6 +519,13 @@ where C: RaftTypeConfig
         &self.snapshot_id
     }
 
+    /// The offset up to which the snapshot data has been received and flushed.
+    ///
+    /// A leader resumes an interrupted transmission from this offset.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
     /// Consumes the `Streaming` and returns the snapshot data.
     pub fn into_snapshot_data(self) -> Box<C::SnapshotData> {
         self.snapshot_data
@@ -344,6 +562,14 @@ where
                 err,
             ));
         }
+        // Flush so that `offset` reports data that has actually been received.
+        if let Err(err) = self.snapshot_data.as_mut().flush().await {
+            return Err(StorageError::from_io_error(
+                ErrorSubject::Snapshot(Some(req.meta.signature())),
+                ErrorVerb::Write,
+                err,
+            ));
+        }
         self.offset += req.data.len() as u64;
         Ok(req.done)
     }
@@ -362,6 +588,7 @@ mod tests {
     use crate::network::snapshot_transport::Chunked;
     use crate::network::snapshot_transport::SnapshotTransport;
     use crate::network::RPCOption;
+    use crate::network::SnapshotResume;
     use crate::raft::AppendEntriesRequest;
     use crate::raft::AppendEntriesResponse;
     use crate::raft::InstallSnapshotRequest;
@@ -372,12 +599,38 @@ mod tests {
     use crate::RaftTypeConfig;
     use crate::Snapshot;
     use crate::SnapshotMeta;
+    use crate::SnapshotSegmentId;
     use crate::StoredMembership;
     use crate::Vote;
 
     struct Network {
         received_offset: Vec<u64>,
         match_cnt: u64,
+
+        /// The offset the target expects in a returned mismatch error.
+        expect_offset: u64,
+
+        /// The resume state seen by every RPC.
+        resume_seen: Vec<Option<SnapshotSegmentId>>,
+
+        /// The compression of every RPC.
+        compressions: Vec<Option<String>>,
+
+        /// Whether the target decodes compressed chunks.
+        decode_compression: bool,
+    }
+
+    impl Network {
+        fn new(match_cnt: u64) -> Self {
+            Self {
+                received_offset: vec![],
+                match_cnt,
+                expect_offset: 0,
+                resume_seen: vec![],
+                compressions: vec![],
+                decode_compression: true,
+            }
+        }
     }
 
     impl<C> RaftNetwork<C> for Network
@@ -402,22 +655,28 @@ mod tests {
         async fn install_snapshot(
             &mut self,
             rpc: InstallSnapshotRequest<C>,
-            _option: RPCOption,
+            option: RPCOption,
         ) -> Result<InstallSnapshotResponse<C>, RPCError<C, RaftError<C, InstallSnapshotError>>> {
             // A fake implementation to test the Chunked::send_snapshot.
 
+            assert!(rpc.checksum.is_some(), "every chunk has a checksum");
+            assert!(rpc.is_checksum_valid());
+            assert_eq!(Some(crc32fast::hash(&[1, 2, 3])), rpc.meta.checksum);
+
             self.received_offset.push(rpc.offset);
+            self.resume_seen.push(option.snapshot_resume().and_then(|r| r.get()));
+            self.compressions.push(rpc.compression.clone());
 
             // For the second last time, return a mismatch error.
             // Then return Ok for the reset of the time.
             self.match_cnt = self.match_cnt.saturating_sub(1);
             if self.match_cnt == 1 {
                 let mismatch = SnapshotMismatch {
-                    expect: crate::SnapshotSegmentId {
+                    expect: SnapshotSegmentId {
                         id: rpc.meta.snapshot_id.clone(),
-                        offset: 0,
+                        offset: self.expect_offset,
                     },
-                    got: crate::SnapshotSegmentId {
+                    got: SnapshotSegmentId {
                         id: rpc.meta.snapshot_id.clone(),
                         offset: rpc.offset,
                     },
@@ -425,7 +684,10 @@ mod tests {
                 let err = RaftError::APIError(InstallSnapshotError::SnapshotMismatch(mismatch));
                 Err(RPCError::RemoteError(crate::error::RemoteError::new(0, err)))
             } else {
-                Ok(InstallSnapshotResponse { vote: rpc.vote })
+                Ok(InstallSnapshotResponse {
+                    vote: rpc.vote,
+                    compression: rpc.compression.filter(|_| self.decode_compression),
+                })
             }
         }
     }
@@ -434,25 +696,169 @@ mod tests {
     /// if a [`SnapshotMismatch`] error is received.
     #[tokio::test]
     async fn test_chunked_reset_offset_if_snapshot_id_mismatch() {
-        let mut net = Network {
-            received_offset: vec![],
-            // When match_cnt == 1, return a mismatch error.
-            // For other times, return Ok.
-            match_cnt: 4,
+        // When match_cnt == 1, return a mismatch error.
+        // For other times, return Ok.
+        let mut net = Network::new(4);
+
+        send(&mut net, None).await;
+
+        assert_eq!(net.received_offset, vec![0, 1, 2, 0, 1, 2]);
+    }
+
+    /// Test that `Chunked` continues from the offset reported by the target in a
+    /// [`SnapshotMismatch`] error.
+    #[tokio::test]
+    async fn test_chunked_continue_from_expected_offset() {
+        let mut net = Network::new(4);
+        net.expect_offset = 1;
+
+        send(&mut net, None).await;
+
+        assert_eq!(net.received_offset, vec![0, 1, 2, 1, 2]);
+    }
+
+    /// Test that `Chunked` resumes from the offset acknowledged in a previous transmission, and
+    /// records the progress.
+    #[tokio::test]
+    async fn test_chunked_resume() {
+        let resume = SnapshotResume::new();
+
+        // No previous transmission: start from 0 and record every acknowledged chunk.
+        let mut net = Network::new(0);
+        send(&mut net, Some(resume.clone())).await;
+
+        assert_eq!(net.received_offset, vec![0, 1, 2]);
+        assert_eq!(net.resume_seen, vec![
+            None,
+            Some(SnapshotSegmentId::from(("1-1-1-1", 1))),
+            Some(SnapshotSegmentId::from(("1-1-1-1", 2))),
+        ]);
+        assert_eq!(None, resume.get(), "reset when finished");
+
+        // A previous transmission of the same snapshot was interrupted at offset 2.
+        resume.update(SnapshotSegmentId::from(("1-1-1-1", 2)));
+        let mut net = Network::new(0);
+        send(&mut net, Some(resume.clone())).await;
+
+        assert_eq!(net.received_offset, vec![2]);
+
+        // A previous transmission of another snapshot does not affect this one.
+        resume.update(SnapshotSegmentId::from(("2-2-2-2", 2)));
+        let mut net = Network::new(0);
+        send(&mut net, Some(resume.clone())).await;
+
+        assert_eq!(net.received_offset, vec![0, 1, 2]);
+    }
+
+    /// Test that `Chunked` sends the chunks no faster than the rate limit.
+    #[tokio::test]
+    async fn test_chunked_rate_limit() {
+        let mut net = Network::new(0);
+
+        let mut opt = rpc_option(None);
+        // 3 bytes are sent in 2 chunks, the second waits for the first one.
+        opt.snapshot_chunk_size = Some(2);
+        opt.replication_rate_limit = Some(10);
+
+        let now = std::time::Instant::now();
+        send_with_option(&mut net, opt).await;
+
+        assert_eq!(net.received_offset, vec![0, 2]);
+        assert!(now.elapsed() >= Duration::from_millis(200));
+    }
+
+    /// Test that `Chunked` compresses the chunks if the target acknowledges the compression.
+    #[cfg(feature = "compression-zstd")]
+    #[tokio::test]
+    async fn test_chunked_compression() {
+        let mut net = Network::new(0);
+
+        let mut opt = rpc_option(None);
+        opt.compress_snapshot = true;
+        send_with_option(&mut net, opt).await;
+
+        assert_eq!(net.received_offset, vec![0, 1, 2]);
+        assert_eq!(net.compressions, vec![Some("zstd".to_string()); 3]);
+    }
+
+    /// Test that `Chunked` sends the snapshot uncompressed from the first chunk, if the target does
+    /// not acknowledge the compression.
+    #[cfg(feature = "compression-zstd")]
+    #[tokio::test]
+    async fn test_chunked_compression_not_acknowledged() {
+        let mut net = Network::new(0);
+        net.decode_compression = false;
+
+        let mut opt = rpc_option(None);
+        opt.compress_snapshot = true;
+        send_with_option(&mut net, opt).await;
+
+        assert_eq!(net.received_offset, vec![0, 0, 1, 2]);
+        assert_eq!(net.compressions, vec![Some("zstd".to_string()), None, None, None]);
+    }
+
+    /// Test that `Chunked` does not compress a snapshot of a single chunk.
+    #[cfg(feature = "compression-zstd")]
+    #[tokio::test]
+    async fn test_chunked_compression_single_chunk() {
+        let mut net = Network::new(0);
+
+        let mut opt = rpc_option(None);
+        opt.snapshot_chunk_size = Some(3);
+        opt.compress_snapshot = true;
+        send_with_option(&mut net, opt).await;
+
+        assert_eq!(net.received_offset, vec![0]);
+        assert_eq!(net.compressions, vec![None]);
+    }
+
+    /// Test that a chunk whose data is modified after it is built fails the checksum validation.
+    #[test]
+    fn test_chunk_checksum() {
+        let req = InstallSnapshotRequest::<UTConfig> {
+            vote: Vote::new(1, 0),
+            meta: SnapshotMeta::default(),
+            offset: 0,
+            data: vec![1, 2, 3],
+            done: true,
+            checksum: None,
+            compression: None,
+            context: Default::default(),
         };
+        assert!(req.is_checksum_valid(), "not checked without a checksum");
 
+        let mut req = req.with_checksum();
+        assert!(req.is_checksum_valid());
+
+        req.data[1] = 5;
+        assert!(!req.is_checksum_valid());
+    }
+
+    fn rpc_option(resume: Option<SnapshotResume>) -> RPCOption {
         let mut opt = RPCOption::new(Duration::from_millis(100));
         opt.snapshot_chunk_size = Some(1);
+        opt.snapshot_resume = resume;
+        opt
+    }
+
+    /// Send snapshot `1-1-1-1` of 3 bytes with chunk size 1.
+    async fn send(net: &mut Network, resume: Option<SnapshotResume>) {
+        send_with_option(net, rpc_option(resume)).await
+    }
+
+    async fn send_with_option(net: &mut Network, opt: RPCOption) {
         let cancel = futures::future::pending();
 
         Chunked::send_snapshot(
-            &mut net,
+            net,
             Vote::new(1, 0),
             Snapshot::<UTConfig>::new(
                 SnapshotMeta {
                     last_log_id: None,
                     last_membership: StoredMembership::default(),
                     snapshot_id: "1-1-1-1".to_string(),
+                    delta_base: None,
+                    checksum: None,
                 },
                 Box::new(Cursor::new(vec![1, 2, 3])),
             ),
@@ -461,7 +867,5 @@ mod tests {
         )
         .await
         .unwrap();
-
-        assert_eq!(net.received_offset, vec![0, 1, 2, 0, 1, 2]);
     }
 }
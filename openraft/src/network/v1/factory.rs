@@ -1,6 +1,10 @@
+use std::sync::Arc;
+
 use openraft_macros::add_async_trait;
 
 use crate::network::v2::RaftNetworkV2;
+use crate::network::RpcAuthenticator;
+use crate::network::RpcContext;
 use crate::OptionalSend;
 use crate::OptionalSync;
 use crate::RaftTypeConfig;
@@ -30,4 +34,72 @@ where C: RaftTypeConfig
     /// The method is intentionally async to give the implementation a chance to use asynchronous
     /// sync primitives to serialize access to the common internal object, if needed.
     async fn new_client(&mut self, target: C::NodeId, node: &C::Node) -> Self::Network;
+
+    /// Called when the node of `target` is updated in the membership, e.g., by
+    /// [`Raft::update_node()`], before a client to it is created with the updated node.
+    ///
+    /// Openraft creates new clients with [`Self::new_client()`] after such a change, and stops
+    /// using the old ones. An implementation that caches connections by node id should drop the
+    /// connection to `target` here, so that the new clients connect to the updated address.
+    ///
+    /// By default it does nothing.
+    ///
+    /// [`Raft::update_node()`]: crate::Raft::update_node
+    fn node_updated(&mut self, target: C::NodeId, node: &C::Node) {
+        let _ = (target, node);
+    }
+
+    /// Fill the context to propagate to the target with a request, e.g., the trace context of the
+    /// current span.
+    ///
+    /// Openraft calls it in the span in which a request is built, and the context is sent in the
+    /// `context` field of the request. For a snapshot, it is also in
+    /// [`RPCOption::context()`] passed to `full_snapshot()`. The target reads it in
+    /// [`Self::extract_context()`].
+    ///
+    /// By default it does nothing. An implementation with OpenTelemetry injects the context of
+    /// the current span with the global text map propagator, e.g.:
+    ///
+    /// ```ignore
+    /// fn inject_context(context: &mut RpcContext) {
+    ///     let cx = tracing::Span::current().context();
+    ///     let mut carrier = HashMap::new();
+    ///     global::get_text_map_propagator(|p| p.inject_context(&cx, &mut carrier));
+    ///     carrier.into_iter().for_each(|(k, v)| context.set(k, v));
+    /// }
+    /// ```
+    ///
+    /// [`RPCOption::context()`]: crate::network::RPCOption::context
+    fn inject_context(context: &mut RpcContext) {
+        let _ = context;
+    }
+
+    /// Read the context propagated with a received request into the span in which the request
+    /// is handled, e.g., set the remote trace context as the parent of the span.
+    ///
+    /// Openraft calls it when `RaftCore` starts to handle an `AppendEntries`, a `Vote`, or the
+    /// snapshot completed by the last `InstallSnapshot` chunk, with the newly created span.
+    ///
+    /// By default it does nothing. An implementation with OpenTelemetry extracts the context with
+    /// the global text map propagator, e.g.:
+    ///
+    /// ```ignore
+    /// fn extract_context(context: &RpcContext, span: &tracing::Span) {
+    ///     let carrier: HashMap<_, _> = context.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    ///     let cx = global::get_text_map_propagator(|p| p.extract(&carrier));
+    ///     span.set_parent(cx);
+    /// }
+    /// ```
+    fn extract_context(context: &RpcContext, span: &tracing::Span) {
+        let _ = (context, span);
+    }
+
+    /// Returns the [`RpcAuthenticator`] that signs the RPCs sent by this node and verifies the
+    /// RPCs it receives.
+    ///
+    /// It is called once when `Raft` is created. By default there is no authenticator, and RPCs
+    /// are neither signed nor verified.
+    fn authenticator(&self) -> Option<Arc<dyn RpcAuthenticator<C>>> {
+        None
+    }
 }
@@ -1,17 +1,35 @@
 use std::future::Future;
+use std::sync::Arc;
 
+use crate::async_runtime::Mpsc;
+use crate::async_runtime::MpscReceiver;
+use crate::async_runtime::MpscSender;
 use crate::error::decompose::DecomposeResult;
+use crate::error::CheckIsLeaderError;
+use crate::error::ClientWriteError;
 use crate::error::RPCError;
+use crate::error::RaftError;
 use crate::error::ReplicationClosed;
 use crate::error::StreamingError;
+use crate::error::TransferSnapshotError;
 use crate::network::v2::RaftNetworkV2;
 use crate::network::Backoff;
+use crate::network::NetworkObserver;
 use crate::network::RPCOption;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
+use crate::raft::ClientWriteResponse;
+use crate::raft::ReadPolicy;
 use crate::raft::SnapshotResponse;
+use crate::raft::TransferLeaderRequest;
+use crate::raft::TransferSnapshotRequest;
+use crate::raft::TransferSnapshotResponse;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
+use crate::type_config::alias::LogIdOf;
+use crate::type_config::alias::MpscOf;
+use crate::type_config::alias::MpscReceiverOf;
+use crate::type_config::alias::MpscSenderOf;
 use crate::OptionalSend;
 use crate::RaftNetwork;
 use crate::RaftTypeConfig;
@@ -32,10 +50,62 @@ where
         RaftNetwork::<C>::append_entries(self, rpc, option).await.decompose_infallible()
     }
 
+    async fn append_entries_stream(
+        &mut self,
+        requests: MpscReceiverOf<C, AppendEntriesRequest<C>>,
+        responses: MpscSenderOf<C, Result<AppendEntriesResponse<C>, RPCError<C>>>,
+        option: RPCOption,
+    ) {
+        // Convert the responses of the V1 stream, which carry an infallible `RaftError`.
+        let (tx, mut rx) = MpscOf::<C>::channel(1);
+
+        let forward = async move {
+            while let Some(res) = rx.recv().await {
+                let res: Result<_, RPCError<C, RaftError<C>>> = res;
+                if responses.send(res.decompose_infallible()).await.is_err() {
+                    return;
+                }
+            }
+        };
+
+        futures::join!(
+            RaftNetwork::<C>::append_entries_stream(self, requests, tx, option),
+            forward
+        );
+    }
+
     async fn vote(&mut self, rpc: VoteRequest<C>, option: RPCOption) -> Result<VoteResponse<C>, RPCError<C>> {
         RaftNetwork::<C>::vote(self, rpc, option).await.decompose_infallible()
     }
 
+    async fn forward_client_write(
+        &mut self,
+        app_data: C::D,
+        option: RPCOption,
+    ) -> Result<ClientWriteResponse<C>, RPCError<C, RaftError<C, ClientWriteError<C>>>> {
+        RaftNetwork::<C>::forward_client_write(self, app_data, option).await
+    }
+
+    async fn get_read_log_id(
+        &mut self,
+        read_policy: ReadPolicy,
+        option: RPCOption,
+    ) -> Result<Option<LogIdOf<C>>, RPCError<C, RaftError<C, CheckIsLeaderError<C>>>> {
+        RaftNetwork::<C>::get_read_log_id(self, read_policy, option).await
+    }
+
+    async fn transfer_snapshot(
+        &mut self,
+        rpc: TransferSnapshotRequest<C>,
+        option: RPCOption,
+    ) -> Result<TransferSnapshotResponse<C>, RPCError<C, RaftError<C, TransferSnapshotError<C>>>> {
+        RaftNetwork::<C>::transfer_snapshot(self, rpc, option).await
+    }
+
+    async fn transfer_leader(&mut self, rpc: TransferLeaderRequest<C>, option: RPCOption) -> Result<(), RPCError<C>> {
+        RaftNetwork::<C>::transfer_leader(self, rpc, option).await.decompose_infallible()
+    }
+
     async fn full_snapshot(
         &mut self,
         vote: Vote<C::NodeId>,
@@ -53,4 +123,12 @@ where
     fn backoff(&self) -> Backoff {
         RaftNetwork::<C>::backoff(self)
     }
+
+    fn replication_rate_limit(&self) -> Option<u64> {
+        RaftNetwork::<C>::replication_rate_limit(self)
+    }
+
+    fn observer(&self) -> Option<Arc<dyn NetworkObserver<C>>> {
+        RaftNetwork::<C>::observer(self)
+    }
 }
@@ -1,18 +1,35 @@
 use std::future::Future;
-use std::time::Duration;
+use std::sync::Arc;
 
+use anyerror::AnyError;
 use openraft_macros::add_async_trait;
 
+use crate::async_runtime::MpscReceiver;
+use crate::async_runtime::MpscSender;
+use crate::error::CheckIsLeaderError;
+use crate::error::ClientWriteError;
+use crate::error::NetworkError;
 use crate::error::RPCError;
+use crate::error::RaftError;
 use crate::error::ReplicationClosed;
 use crate::error::StreamingError;
+use crate::error::TransferSnapshotError;
 use crate::network::Backoff;
+use crate::network::NetworkObserver;
 use crate::network::RPCOption;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
+use crate::raft::ClientWriteResponse;
+use crate::raft::ReadPolicy;
 use crate::raft::SnapshotResponse;
+use crate::raft::TransferLeaderRequest;
+use crate::raft::TransferSnapshotRequest;
+use crate::raft::TransferSnapshotResponse;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
+use crate::type_config::alias::LogIdOf;
+use crate::type_config::alias::MpscReceiverOf;
+use crate::type_config::alias::MpscSenderOf;
 use crate::OptionalSend;
 use crate::OptionalSync;
 use crate::RaftTypeConfig;
@@ -48,9 +65,107 @@ where C: RaftTypeConfig
         option: RPCOption,
     ) -> Result<AppendEntriesResponse<C>, RPCError<C>>;
 
+    /// Send AppendEntries RPCs to the target over a long-lived stream.
+    ///
+    /// A replication task opens a stream by calling this method, then every request received
+    /// from `requests` should be sent to the target, and its response or error sent to
+    /// `responses`, in the order of the requests. A transport that supports bidirectional
+    /// streaming, such as gRPC or QUIC, can override it to keep one stream to the target instead
+    /// of setting up every RPC, and to push the responses back as soon as they arrive.
+    ///
+    /// It should return when `requests` or `responses` is closed, or after an error is sent to
+    /// `responses`: Openraft closes the stream on an error and opens a new one for the next
+    /// request. `option` applies to every request on the stream.
+    ///
+    /// By default it sends the requests one by one with [`append_entries()`].
+    ///
+    /// [`append_entries()`]: Self::append_entries
+    async fn append_entries_stream(
+        &mut self,
+        mut requests: MpscReceiverOf<C, AppendEntriesRequest<C>>,
+        responses: MpscSenderOf<C, Result<AppendEntriesResponse<C>, RPCError<C>>>,
+        option: RPCOption,
+    ) {
+        while let Some(rpc) = requests.recv().await {
+            let res = self.append_entries(rpc, option.clone()).await;
+            let is_err = res.is_err();
+
+            if responses.send(res).await.is_err() || is_err {
+                return;
+            }
+        }
+    }
+
     /// Send a RequestVote RPC to the target.
     async fn vote(&mut self, rpc: VoteRequest<C>, option: RPCOption) -> Result<VoteResponse<C>, RPCError<C>>;
 
+    /// Forward a client write request to the target, which is believed to be the leader.
+    ///
+    /// It is used by [`Raft::client_write_forwarded()`] to submit a write received by a node
+    /// that is not the leader. The target should call [`Raft::client_write()`] with `app_data`
+    /// and return its result, with the error wrapped in [`RPCError::RemoteError`]. The timeout
+    /// is [`Config::forward_client_write_timeout`](`crate::Config::forward_client_write_timeout`).
+    ///
+    /// By default it returns a [`NetworkError`], i.e., forwarding is not supported.
+    ///
+    /// [`Raft::client_write_forwarded()`]: crate::Raft::client_write_forwarded
+    /// [`Raft::client_write()`]: crate::Raft::client_write
+    /// [`NetworkError`]: crate::error::NetworkError
+    async fn forward_client_write(
+        &mut self,
+        _app_data: C::D,
+        _option: RPCOption,
+    ) -> Result<ClientWriteResponse<C>, RPCError<C, RaftError<C, ClientWriteError<C>>>> {
+        Err(RPCError::Network(NetworkError::new(&AnyError::error(
+            "forward_client_write is not supported",
+        ))))
+    }
+
+    /// Ask the target, which is believed to be the leader, for the log id up to which the state
+    /// machine should apply to serve a linearizable read.
+    ///
+    /// It is used by [`Raft::ensure_linearizable_on_follower()`] to serve a read on a node that
+    /// is not the leader. The target should call [`Raft::get_read_log_id_with()`] with
+    /// `read_policy` and return the `read_log_id` it returns, with the error wrapped in
+    /// [`RPCError::RemoteError`]. The timeout is
+    /// [`Config::forward_client_write_timeout`](`crate::Config::forward_client_write_timeout`).
+    ///
+    /// By default it returns a [`NetworkError`], i.e., reads can not be served by a follower.
+    ///
+    /// [`Raft::ensure_linearizable_on_follower()`]: crate::Raft::ensure_linearizable_on_follower
+    /// [`Raft::get_read_log_id_with()`]: crate::Raft::get_read_log_id_with
+    /// [`NetworkError`]: crate::error::NetworkError
+    async fn get_read_log_id(
+        &mut self,
+        _read_policy: ReadPolicy,
+        _option: RPCOption,
+    ) -> Result<Option<LogIdOf<C>>, RPCError<C, RaftError<C, CheckIsLeaderError<C>>>> {
+        Err(RPCError::Network(NetworkError::new(&AnyError::error(
+            "get_read_log_id is not supported",
+        ))))
+    }
+
+    /// Ask the target to send its snapshot to another node, on behalf of the leader.
+    ///
+    /// It is used by a leader with
+    /// [`Config::delegate_snapshot_transfer`](`crate::Config::delegate_snapshot_transfer`)
+    /// enabled. The target should call [`Raft::transfer_snapshot()`] with `rpc` and return its
+    /// result, with the error wrapped in [`RPCError::RemoteError`].
+    ///
+    /// By default it returns a [`NetworkError`], i.e., the leader sends the snapshot by itself.
+    ///
+    /// [`Raft::transfer_snapshot()`]: crate::Raft::transfer_snapshot
+    /// [`NetworkError`]: crate::error::NetworkError
+    async fn transfer_snapshot(
+        &mut self,
+        _rpc: TransferSnapshotRequest<C>,
+        _option: RPCOption,
+    ) -> Result<TransferSnapshotResponse<C>, RPCError<C, RaftError<C, TransferSnapshotError<C>>>> {
+        Err(RPCError::Network(NetworkError::new(&AnyError::error(
+            "transfer_snapshot is not supported",
+        ))))
+    }
+
     /// Send a complete Snapshot to the target.
     ///
     /// This method is responsible to fragment the snapshot and send it to the target node.
@@ -66,6 +181,16 @@ where C: RaftTypeConfig
     /// with this vote.
     ///
     /// `cancel` get `Ready` when the caller decides to cancel this snapshot transmission.
+    ///
+    /// [`RPCOption::snapshot_resume()`] holds the progress of the previous transmission to this
+    /// target. An implementation that supports resuming should start from
+    /// [`SnapshotResume::offset_of()`] and record every segment the target acknowledges with
+    /// [`SnapshotResume::update()`], so that a transmission interrupted by a network failure does
+    /// not start over. The receiver can use [`Streaming::offset()`] to tell the received offset.
+    ///
+    /// [`SnapshotResume::offset_of()`]: crate::network::SnapshotResume::offset_of
+    /// [`SnapshotResume::update()`]: crate::network::SnapshotResume::update
+    /// [`Streaming::offset()`]: crate::network::snapshot_transport::Streaming::offset
     async fn full_snapshot(
         &mut self,
         vote: Vote<C::NodeId>,
@@ -74,6 +199,23 @@ where C: RaftTypeConfig
         option: RPCOption,
     ) -> Result<SnapshotResponse<C>, StreamingError<C>>;
 
+    /// Ask the target to take part in a leadership transfer.
+    ///
+    /// It is sent by a leader to every voter when [`Raft::transfer_leadership()`] is called. The
+    /// target should call [`Raft::handle_transfer_leader()`] with `rpc`.
+    ///
+    /// By default it returns a [`NetworkError`], i.e., the leadership can not be transferred and
+    /// [`Raft::transfer_leadership()`] times out.
+    ///
+    /// [`Raft::transfer_leadership()`]: crate::Raft::transfer_leadership
+    /// [`Raft::handle_transfer_leader()`]: crate::Raft::handle_transfer_leader
+    /// [`NetworkError`]: crate::error::NetworkError
+    async fn transfer_leader(&mut self, _rpc: TransferLeaderRequest<C>, _option: RPCOption) -> Result<(), RPCError<C>> {
+        Err(RPCError::Network(NetworkError::new(&AnyError::error(
+            "transfer_leader is not supported",
+        ))))
+    }
+
     /// Build a backoff instance if the target node is temporarily(or permanently) unreachable.
     ///
     /// When a [`Unreachable`](`crate::error::Unreachable`) error is returned from the `Network`
@@ -81,11 +223,33 @@ where C: RaftTypeConfig
     /// for a while and retries. The duration of the sleep is determined by the backoff
     /// instance.
     ///
-    /// The backoff is an infinite iterator that returns the ith sleep interval before the ith
+    /// The backoff is built from a [`BackoffPolicy`] that returns the sleep interval before every
     /// retry. The returned instance will be dropped if a successful RPC is made.
     ///
-    /// By default it returns a constant backoff of 500 ms.
+    /// By default it returns [`Backoff::default()`], which uses the policy configured by
+    /// [`Config::backoff_policy`](`crate::Config::backoff_policy`), a constant backoff of 500 ms
+    /// by default.
+    ///
+    /// [`BackoffPolicy`]: crate::network::BackoffPolicy
     fn backoff(&self) -> Backoff {
-        Backoff::new(std::iter::repeat(Duration::from_millis(500)))
+        Backoff::default()
+    }
+
+    /// The maximum bytes per second to replicate to the target node.
+    ///
+    /// By default it returns `None`, which uses
+    /// [`Config::replication_rate_limit_bytes_per_sec`](`crate::Config::replication_rate_limit_bytes_per_sec`).
+    /// `Some(0)` means unlimited.
+    fn replication_rate_limit(&self) -> Option<u64> {
+        None
+    }
+
+    /// The observer to receive a callback for every RPC sent to the target node.
+    ///
+    /// It is called once when a connection to the target is set up, such as when a replication
+    /// stream is spawned or a vote request is sent. By default it returns `None`, i.e., there is
+    /// no observer.
+    fn observer(&self) -> Option<Arc<dyn NetworkObserver<C>>> {
+        None
     }
 }
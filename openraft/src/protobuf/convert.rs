@@ -0,0 +1,776 @@
+//! Conversions between the RPC types and the protobuf messages.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use crate::network::RpcContext;
+use crate::protobuf as pb;
+use crate::protobuf::ProtobufError;
+use crate::protobuf::ProtobufTypeConfig;
+use crate::raft::AppendEntriesRequest;
+use crate::raft::AppendEntriesResponse;
+use crate::raft::InstallSnapshotRequest;
+use crate::raft::InstallSnapshotResponse;
+use crate::raft::SnapshotResponse;
+use crate::raft::VoteRequest;
+use crate::raft::VoteResponse;
+use crate::BasicNode;
+use crate::CommittedLeaderId;
+use crate::Entry;
+use crate::EntryPayload;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::QuorumPolicy;
+use crate::SnapshotMeta;
+use crate::StoredMembership;
+use crate::Vote;
+
+fn required<T>(v: Option<T>, field: &'static str) -> Result<T, ProtobufError> {
+    v.ok_or(ProtobufError::MissingField(field))
+}
+
+fn opt_log_id(v: Option<pb::LogId>) -> Result<Option<LogId<u64>>, ProtobufError> {
+    v.map(LogId::try_from).transpose()
+}
+
+fn context_from_map(fields: impl IntoIterator<Item = (String, String)>) -> RpcContext {
+    let mut context = RpcContext::new();
+    for (k, v) in fields {
+        context.set(k, v);
+    }
+    context
+}
+
+fn context_to_map(context: &RpcContext) -> BTreeMap<String, String> {
+    context.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[cfg(not(feature = "single-term-leader"))]
+impl From<LeaderId<u64>> for pb::LeaderId {
+    fn from(v: LeaderId<u64>) -> Self {
+        pb::LeaderId {
+            term: v.term,
+            node_id: Some(v.node_id),
+        }
+    }
+}
+
+#[cfg(not(feature = "single-term-leader"))]
+impl TryFrom<pb::LeaderId> for LeaderId<u64> {
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::LeaderId) -> Result<Self, Self::Error> {
+        let node_id = required(v.node_id, "leader_id.node_id")?;
+        Ok(LeaderId::new(v.term, node_id))
+    }
+}
+
+#[cfg(feature = "single-term-leader")]
+impl From<LeaderId<u64>> for pb::LeaderId {
+    fn from(v: LeaderId<u64>) -> Self {
+        pb::LeaderId {
+            term: v.term,
+            node_id: v.voted_for,
+        }
+    }
+}
+
+#[cfg(feature = "single-term-leader")]
+impl TryFrom<pb::LeaderId> for LeaderId<u64> {
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::LeaderId) -> Result<Self, Self::Error> {
+        Ok(LeaderId {
+            term: v.term,
+            voted_for: v.node_id,
+        })
+    }
+}
+
+#[cfg(feature = "single-term-leader")]
+impl From<CommittedLeaderId<u64>> for pb::LeaderId {
+    fn from(v: CommittedLeaderId<u64>) -> Self {
+        pb::LeaderId {
+            term: v.term,
+            node_id: None,
+        }
+    }
+}
+
+/// Converts the leader id in a [`pb::LogId`].
+fn committed_leader_id(v: pb::LeaderId) -> Result<CommittedLeaderId<u64>, ProtobufError> {
+    #[cfg(not(feature = "single-term-leader"))]
+    {
+        LeaderId::try_from(v)
+    }
+
+    #[cfg(feature = "single-term-leader")]
+    {
+        Ok(CommittedLeaderId::new(v.term, 0))
+    }
+}
+
+impl From<Vote<u64>> for pb::Vote {
+    fn from(v: Vote<u64>) -> Self {
+        pb::Vote {
+            leader_id: Some(v.leader_id.into()),
+            committed: v.committed,
+        }
+    }
+}
+
+impl TryFrom<pb::Vote> for Vote<u64> {
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::Vote) -> Result<Self, Self::Error> {
+        Ok(Vote {
+            leader_id: required(v.leader_id, "vote.leader_id")?.try_into()?,
+            committed: v.committed,
+        })
+    }
+}
+
+impl From<LogId<u64>> for pb::LogId {
+    fn from(v: LogId<u64>) -> Self {
+        pb::LogId {
+            leader_id: Some(v.leader_id.into()),
+            index: v.index,
+        }
+    }
+}
+
+impl TryFrom<pb::LogId> for LogId<u64> {
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::LogId) -> Result<Self, Self::Error> {
+        let leader_id = committed_leader_id(required(v.leader_id, "log_id.leader_id")?)?;
+        Ok(LogId::new(leader_id, v.index))
+    }
+}
+
+impl From<BasicNode> for pb::BasicNode {
+    fn from(v: BasicNode) -> Self {
+        pb::BasicNode { addr: v.addr }
+    }
+}
+
+impl From<pb::BasicNode> for BasicNode {
+    fn from(v: pb::BasicNode) -> Self {
+        BasicNode { addr: v.addr }
+    }
+}
+
+impl<C> From<Membership<C>> for pb::Membership
+where C: ProtobufTypeConfig
+{
+    fn from(v: Membership<C>) -> Self {
+        pb::Membership {
+            configs: v
+                .get_joint_config()
+                .iter()
+                .map(|c| pb::NodeIdSet {
+                    node_ids: c.iter().copied().collect(),
+                })
+                .collect(),
+            nodes: v.nodes().map(|(id, node)| (*id, C::encode_node(node))).collect(),
+            priorities: v.priorities().map(|(id, p)| (*id, *p)).collect(),
+            witnesses: v.witness_ids().collect(),
+            quorum_policy: match v.quorum_policy() {
+                QuorumPolicy::Majority => None,
+                p => Some(p.clone().into()),
+            },
+            zones: v.zones().map(|(id, z)| (*id, z.clone())).collect(),
+        }
+    }
+}
+
+impl<C> TryFrom<pb::Membership> for Membership<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::Membership) -> Result<Self, Self::Error> {
+        let configs = v.configs.into_iter().map(|c| c.node_ids.into_iter().collect::<BTreeSet<_>>()).collect();
+
+        let mut nodes = BTreeMap::new();
+        for (id, buf) in v.nodes {
+            let node = C::decode_node(&buf).map_err(|source| ProtobufError::Decode {
+                field: "membership.nodes",
+                source,
+            })?;
+            nodes.insert(id, node);
+        }
+
+        // Keep the membership as it is, the same as when it is deserialized with serde.
+        Ok(Membership::new_unchecked(configs, nodes)
+            .with_priorities(v.priorities)
+            .with_witnesses(v.witnesses.into_iter().collect())
+            .with_zones(v.zones)
+            .with_quorum_policy(v.quorum_policy.map(QuorumPolicy::from).unwrap_or_default()))
+    }
+}
+
+impl From<QuorumPolicy<u64>> for pb::QuorumPolicy {
+    fn from(v: QuorumPolicy<u64>) -> Self {
+        use pb::quorum_policy::Policy;
+
+        let node_id_set = |ids: BTreeSet<u64>| pb::NodeIdSet {
+            node_ids: ids.into_iter().collect(),
+        };
+
+        let policy = match v {
+            QuorumPolicy::Majority => Policy::Majority(pb::Empty {}),
+            QuorumPolicy::Weighted(weights) => Policy::Weighted(pb::quorum_policy::Weighted { weights }),
+            QuorumPolicy::Grid(rows) => Policy::Grid(pb::quorum_policy::Grid {
+                rows: rows.into_iter().map(node_id_set).collect(),
+            }),
+            QuorumPolicy::MajorityWith(ids) => Policy::MajorityWith(node_id_set(ids)),
+        };
+
+        pb::QuorumPolicy { policy: Some(policy) }
+    }
+}
+
+impl From<pb::QuorumPolicy> for QuorumPolicy<u64> {
+    fn from(v: pb::QuorumPolicy) -> Self {
+        use pb::quorum_policy::Policy;
+
+        let id_set = |s: pb::NodeIdSet| s.node_ids.into_iter().collect::<BTreeSet<_>>();
+
+        match v.policy {
+            None | Some(Policy::Majority(_)) => QuorumPolicy::Majority,
+            Some(Policy::Weighted(w)) => QuorumPolicy::Weighted(w.weights),
+            Some(Policy::Grid(g)) => QuorumPolicy::Grid(g.rows.into_iter().map(id_set).collect()),
+            Some(Policy::MajorityWith(s)) => QuorumPolicy::MajorityWith(id_set(s)),
+        }
+    }
+}
+
+impl<C> From<StoredMembership<C>> for pb::StoredMembership
+where C: ProtobufTypeConfig
+{
+    fn from(v: StoredMembership<C>) -> Self {
+        pb::StoredMembership {
+            log_id: v.log_id().map(pb::LogId::from),
+            membership: Some(v.membership().clone().into()),
+        }
+    }
+}
+
+impl<C> TryFrom<pb::StoredMembership> for StoredMembership<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::StoredMembership) -> Result<Self, Self::Error> {
+        Ok(StoredMembership::new(
+            opt_log_id(v.log_id)?,
+            required(v.membership, "stored_membership.membership")?.try_into()?,
+        ))
+    }
+}
+
+impl<C> From<Entry<C>> for pb::Entry
+where C: ProtobufTypeConfig
+{
+    fn from(v: Entry<C>) -> Self {
+        let payload = match v.payload {
+            EntryPayload::Blank => pb::entry::Payload::Blank(pb::Empty {}),
+            EntryPayload::Normal(data) => pb::entry::Payload::Normal(C::encode_data(&data)),
+            EntryPayload::Membership(m) => pb::entry::Payload::Membership(m.into()),
+        };
+
+        pb::Entry {
+            log_id: Some(v.log_id.into()),
+            payload: Some(payload),
+        }
+    }
+}
+
+impl<C> TryFrom<pb::Entry> for Entry<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::Entry) -> Result<Self, Self::Error> {
+        let payload = match required(v.payload, "entry.payload")? {
+            pb::entry::Payload::Blank(_) => EntryPayload::Blank,
+            pb::entry::Payload::Normal(buf) => {
+                let data = C::decode_data(&buf).map_err(|source| ProtobufError::Decode {
+                    field: "entry.payload.normal",
+                    source,
+                })?;
+                EntryPayload::Normal(data)
+            }
+            pb::entry::Payload::Membership(m) => EntryPayload::Membership(m.try_into()?),
+        };
+
+        Ok(Entry {
+            log_id: required(v.log_id, "entry.log_id")?.try_into()?,
+            payload,
+        })
+    }
+}
+
+impl<C> From<AppendEntriesRequest<C>> for pb::AppendEntriesRequest
+where C: ProtobufTypeConfig
+{
+    fn from(v: AppendEntriesRequest<C>) -> Self {
+        pb::AppendEntriesRequest {
+            vote: Some(v.vote.into()),
+            prev_log_id: v.prev_log_id.map(pb::LogId::from),
+            entries: v.entries.into_iter().map(pb::Entry::from).collect(),
+            leader_commit: v.leader_commit.map(pb::LogId::from),
+            context: context_to_map(&v.context),
+        }
+    }
+}
+
+impl<C> TryFrom<pb::AppendEntriesRequest> for AppendEntriesRequest<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::AppendEntriesRequest) -> Result<Self, Self::Error> {
+        Ok(AppendEntriesRequest {
+            vote: required(v.vote, "append_entries_request.vote")?.try_into()?,
+            prev_log_id: opt_log_id(v.prev_log_id)?,
+            entries: v.entries.into_iter().map(Entry::try_from).collect::<Result<_, _>>()?,
+            leader_commit: opt_log_id(v.leader_commit)?,
+            context: context_from_map(v.context),
+        })
+    }
+}
+
+impl<C> From<AppendEntriesResponse<C>> for pb::AppendEntriesResponse
+where C: ProtobufTypeConfig
+{
+    fn from(v: AppendEntriesResponse<C>) -> Self {
+        use pb::append_entries_response::Result;
+
+        let result = match v {
+            AppendEntriesResponse::Success => Result::Success(pb::Empty {}),
+            AppendEntriesResponse::PartialSuccess(matching) => Result::PartialSuccess(pb::PartialSuccess {
+                matching: matching.map(pb::LogId::from),
+            }),
+            AppendEntriesResponse::Conflict => Result::Conflict(pb::Empty {}),
+            AppendEntriesResponse::HigherVote(vote) => Result::HigherVote(vote.into()),
+        };
+
+        pb::AppendEntriesResponse { result: Some(result) }
+    }
+}
+
+impl<C> TryFrom<pb::AppendEntriesResponse> for AppendEntriesResponse<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::AppendEntriesResponse) -> Result<Self, Self::Error> {
+        use pb::append_entries_response::Result;
+
+        let resp = match required(v.result, "append_entries_response.result")? {
+            Result::Success(_) => AppendEntriesResponse::Success,
+            Result::PartialSuccess(p) => AppendEntriesResponse::PartialSuccess(opt_log_id(p.matching)?),
+            Result::Conflict(_) => AppendEntriesResponse::Conflict,
+            Result::HigherVote(vote) => AppendEntriesResponse::HigherVote(vote.try_into()?),
+        };
+        Ok(resp)
+    }
+}
+
+impl<C> From<VoteRequest<C>> for pb::VoteRequest
+where C: ProtobufTypeConfig
+{
+    fn from(v: VoteRequest<C>) -> Self {
+        pb::VoteRequest {
+            vote: Some(v.vote.into()),
+            last_log_id: v.last_log_id.map(pb::LogId::from),
+            context: context_to_map(&v.context),
+            pre_vote: v.pre_vote,
+        }
+    }
+}
+
+impl<C> TryFrom<pb::VoteRequest> for VoteRequest<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::VoteRequest) -> Result<Self, Self::Error> {
+        Ok(VoteRequest {
+            vote: required(v.vote, "vote_request.vote")?.try_into()?,
+            last_log_id: opt_log_id(v.last_log_id)?,
+            pre_vote: v.pre_vote,
+            context: context_from_map(v.context),
+        })
+    }
+}
+
+impl<C> From<VoteResponse<C>> for pb::VoteResponse
+where C: ProtobufTypeConfig
+{
+    fn from(v: VoteResponse<C>) -> Self {
+        pb::VoteResponse {
+            vote: Some(v.vote.into()),
+            vote_granted: v.vote_granted,
+            last_log_id: v.last_log_id.map(pb::LogId::from),
+        }
+    }
+}
+
+impl<C> TryFrom<pb::VoteResponse> for VoteResponse<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::VoteResponse) -> Result<Self, Self::Error> {
+        Ok(VoteResponse {
+            vote: required(v.vote, "vote_response.vote")?.try_into()?,
+            vote_granted: v.vote_granted,
+            last_log_id: opt_log_id(v.last_log_id)?,
+        })
+    }
+}
+
+impl<C> From<SnapshotMeta<C>> for pb::SnapshotMeta
+where C: ProtobufTypeConfig
+{
+    fn from(v: SnapshotMeta<C>) -> Self {
+        pb::SnapshotMeta {
+            last_log_id: v.last_log_id.map(pb::LogId::from),
+            last_membership: Some(v.last_membership.into()),
+            snapshot_id: v.snapshot_id,
+            delta_base: v.delta_base.map(pb::LogId::from),
+            checksum: v.checksum,
+        }
+    }
+}
+
+impl<C> TryFrom<pb::SnapshotMeta> for SnapshotMeta<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::SnapshotMeta) -> Result<Self, Self::Error> {
+        Ok(SnapshotMeta {
+            last_log_id: opt_log_id(v.last_log_id)?,
+            last_membership: required(v.last_membership, "snapshot_meta.last_membership")?.try_into()?,
+            snapshot_id: v.snapshot_id,
+            delta_base: opt_log_id(v.delta_base)?,
+            checksum: v.checksum,
+        })
+    }
+}
+
+impl<C> From<InstallSnapshotRequest<C>> for pb::InstallSnapshotRequest
+where C: ProtobufTypeConfig
+{
+    fn from(v: InstallSnapshotRequest<C>) -> Self {
+        pb::InstallSnapshotRequest {
+            vote: Some(v.vote.into()),
+            meta: Some(v.meta.into()),
+            offset: v.offset,
+            data: v.data,
+            done: v.done,
+            context: context_to_map(&v.context),
+            checksum: v.checksum,
+            compression: v.compression,
+        }
+    }
+}
+
+impl<C> TryFrom<pb::InstallSnapshotRequest> for InstallSnapshotRequest<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::InstallSnapshotRequest) -> Result<Self, Self::Error> {
+        Ok(InstallSnapshotRequest {
+            vote: required(v.vote, "install_snapshot_request.vote")?.try_into()?,
+            meta: required(v.meta, "install_snapshot_request.meta")?.try_into()?,
+            offset: v.offset,
+            data: v.data,
+            done: v.done,
+            checksum: v.checksum,
+            compression: v.compression,
+            context: context_from_map(v.context),
+        })
+    }
+}
+
+impl<C> From<InstallSnapshotResponse<C>> for pb::InstallSnapshotResponse
+where C: ProtobufTypeConfig
+{
+    fn from(v: InstallSnapshotResponse<C>) -> Self {
+        pb::InstallSnapshotResponse {
+            vote: Some(v.vote.into()),
+            compression: v.compression,
+        }
+    }
+}
+
+impl<C> TryFrom<pb::InstallSnapshotResponse> for InstallSnapshotResponse<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::InstallSnapshotResponse) -> Result<Self, Self::Error> {
+        Ok(InstallSnapshotResponse {
+            vote: required(v.vote, "install_snapshot_response.vote")?.try_into()?,
+            compression: v.compression,
+        })
+    }
+}
+
+impl<C> From<SnapshotResponse<C>> for pb::SnapshotResponse
+where C: ProtobufTypeConfig
+{
+    fn from(v: SnapshotResponse<C>) -> Self {
+        pb::SnapshotResponse {
+            vote: Some(v.vote.into()),
+        }
+    }
+}
+
+impl<C> TryFrom<pb::SnapshotResponse> for SnapshotResponse<C>
+where C: ProtobufTypeConfig
+{
+    type Error = ProtobufError;
+
+    fn try_from(v: pb::SnapshotResponse) -> Result<Self, Self::Error> {
+        Ok(SnapshotResponse {
+            vote: required(v.vote, "snapshot_response.vote")?.try_into()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::io::Cursor;
+
+    use anyerror::AnyError;
+    use maplit::btreemap;
+    use maplit::btreeset;
+    use prost::Message;
+
+    use crate::declare_raft_types;
+    use crate::network::RpcContext;
+    use crate::protobuf as pb;
+    use crate::protobuf::ProtobufError;
+    use crate::protobuf::ProtobufTypeConfig;
+    use crate::raft::AppendEntriesRequest;
+    use crate::raft::AppendEntriesResponse;
+    use crate::raft::InstallSnapshotRequest;
+    use crate::raft::InstallSnapshotResponse;
+    use crate::raft::VoteRequest;
+    use crate::raft::VoteResponse;
+    use crate::testing::log_id;
+    use crate::BasicNode;
+    use crate::Entry;
+    use crate::EntryPayload;
+    use crate::Membership;
+    use crate::QuorumPolicy;
+    use crate::SnapshotMeta;
+    use crate::StoredMembership;
+    use crate::TokioRuntime;
+    use crate::Vote;
+
+    declare_raft_types!(
+        PbConfig:
+            D = u64,
+            R = (),
+            NodeId = u64,
+            Node = BasicNode,
+            Entry = Entry<Self>,
+            SnapshotData = Cursor<Vec<u8>>,
+            AsyncRuntime = TokioRuntime,
+    );
+
+    impl ProtobufTypeConfig for PbConfig {
+        fn encode_data(data: &u64) -> Vec<u8> {
+            data.to_le_bytes().to_vec()
+        }
+
+        fn decode_data(buf: &[u8]) -> Result<u64, AnyError> {
+            let b = <[u8; 8]>::try_from(buf).map_err(|e| AnyError::new(&e))?;
+            Ok(u64::from_le_bytes(b))
+        }
+
+        fn encode_node(node: &BasicNode) -> Vec<u8> {
+            pb::BasicNode::from(node.clone()).encode_to_vec()
+        }
+
+        fn decode_node(buf: &[u8]) -> Result<BasicNode, AnyError> {
+            let node = pb::BasicNode::decode(buf).map_err(|e| AnyError::new(&e))?;
+            Ok(node.into())
+        }
+    }
+
+    /// Encode `v` to bytes and decode it back.
+    fn round_trip<T, M>(v: T) -> Result<T, ProtobufError>
+    where
+        M: Message + Default + From<T>,
+        T: TryFrom<M, Error = ProtobufError>,
+    {
+        let buf = M::from(v).encode_to_vec();
+        T::try_from(M::decode(buf.as_slice()).unwrap())
+    }
+
+    fn membership() -> Membership<PbConfig> {
+        Membership::new(vec![btreeset! {1, 2}, btreeset! {2, 3}], btreemap! {
+            1 => BasicNode::new("a"),
+            2 => BasicNode::new("b"),
+            3 => BasicNode::new("c"),
+            4 => BasicNode::new("learner"),
+        })
+        .with_priorities(btreemap! {1 => 10, 3 => 5})
+        .with_witnesses(btreeset! {3})
+        .with_zones(btreemap! {1 => "z1".to_string(), 4 => "z2".to_string()})
+        .with_quorum_policy(QuorumPolicy::Weighted(btreemap! {1 => 3}))
+    }
+
+    fn context() -> RpcContext {
+        let mut context = RpcContext::new();
+        context.set("traceparent", "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01");
+        context
+    }
+
+    #[test]
+    fn test_append_entries_round_trip() -> anyhow::Result<()> {
+        let req = AppendEntriesRequest::<PbConfig> {
+            vote: Vote::new_committed(3, 1),
+            prev_log_id: Some(log_id(1, 1, 2)),
+            entries: vec![
+                Entry {
+                    log_id: log_id(3, 1, 3),
+                    payload: EntryPayload::Blank,
+                },
+                Entry {
+                    log_id: log_id(3, 1, 4),
+                    payload: EntryPayload::Normal(42),
+                },
+                Entry {
+                    log_id: log_id(3, 1, 5),
+                    payload: EntryPayload::Membership(membership()),
+                },
+            ],
+            leader_commit: None,
+            context: context(),
+        };
+
+        let got = round_trip::<_, pb::AppendEntriesRequest>(req.clone())?;
+        assert_eq!(req.vote, got.vote);
+        assert_eq!(req.prev_log_id, got.prev_log_id);
+        assert_eq!(req.entries, got.entries);
+        assert_eq!(req.leader_commit, got.leader_commit);
+        assert_eq!(req.context, got.context);
+
+        for resp in [
+            AppendEntriesResponse::<PbConfig>::Success,
+            AppendEntriesResponse::PartialSuccess(None),
+            AppendEntriesResponse::PartialSuccess(Some(log_id(3, 1, 4))),
+            AppendEntriesResponse::Conflict,
+            AppendEntriesResponse::HigherVote(Vote::new(4, 2)),
+        ] {
+            let want = resp.to_string();
+            assert_eq!(want, round_trip::<_, pb::AppendEntriesResponse>(resp)?.to_string());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vote_round_trip() -> anyhow::Result<()> {
+        let req = VoteRequest::<PbConfig>::new(Vote::new(5, 2), Some(log_id(3, 1, 4)));
+        let got = round_trip::<_, pb::VoteRequest>(req.clone())?;
+        assert_eq!(req, got);
+
+        let req = VoteRequest::<PbConfig>::new_pre_vote(Vote::new(5, 2), Some(log_id(3, 1, 4)));
+        let got = round_trip::<_, pb::VoteRequest>(req.clone())?;
+        assert_eq!(req, got);
+
+        let resp = VoteResponse::<PbConfig> {
+            vote: Vote::new(5, 2),
+            vote_granted: true,
+            last_log_id: None,
+        };
+        let got = round_trip::<_, pb::VoteResponse>(resp.clone())?;
+        assert_eq!(resp, got);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_install_snapshot_round_trip() -> anyhow::Result<()> {
+        let req = InstallSnapshotRequest::<PbConfig> {
+            vote: Vote::new_committed(3, 1),
+            meta: SnapshotMeta {
+                last_log_id: Some(log_id(3, 1, 5)),
+                last_membership: StoredMembership::new(Some(log_id(3, 1, 5)), membership()),
+                snapshot_id: "snap-1".to_string(),
+                delta_base: Some(log_id(2, 1, 3)),
+                checksum: Some(0x1234_5678),
+            },
+            offset: 1024,
+            data: b"foo".to_vec(),
+            done: true,
+            checksum: None,
+            compression: Some("zstd".to_string()),
+            context: context(),
+        }
+        .with_checksum();
+
+        let got = round_trip::<_, pb::InstallSnapshotRequest>(req.clone())?;
+        assert_eq!(req, got);
+
+        let resp = || InstallSnapshotResponse::<PbConfig> {
+            vote: Vote::new_committed(3, 1),
+            compression: Some("zstd".to_string()),
+        };
+        let got = round_trip::<_, pb::InstallSnapshotResponse>(resp())?;
+        assert_eq!(resp(), got);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_incomplete_message() -> anyhow::Result<()> {
+        let res = VoteResponse::<PbConfig>::try_from(pb::VoteResponse::default());
+        assert_eq!(Err(ProtobufError::MissingField("vote_response.vote")), res.map(|_| ()));
+
+        let entry = pb::Entry {
+            log_id: Some(log_id(1, 1, 1).into()),
+            payload: Some(pb::entry::Payload::Normal(vec![1, 2])),
+        };
+        let res = Entry::<PbConfig>::try_from(entry);
+        assert!(matches!(
+            res,
+            Err(ProtobufError::Decode {
+                field: "entry.payload.normal",
+                ..
+            })
+        ));
+
+        let membership = pb::Membership {
+            configs: vec![],
+            nodes: BTreeMap::from([(1, vec![0xff])]),
+            priorities: BTreeMap::new(),
+            witnesses: vec![],
+            quorum_policy: None,
+            zones: BTreeMap::new(),
+        };
+        let res = Membership::<PbConfig>::try_from(membership);
+        assert!(matches!(
+            res,
+            Err(ProtobufError::Decode {
+                field: "membership.nodes",
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+//! The prost messages defined in `proto/openraft.proto`.
+
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct Empty {}
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct LeaderId {
+    #[prost(uint64, tag = "1")]
+    pub term: u64,
+    #[prost(uint64, optional, tag = "2")]
+    pub node_id: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct Vote {
+    #[prost(message, optional, tag = "1")]
+    pub leader_id: Option<LeaderId>,
+    #[prost(bool, tag = "2")]
+    pub committed: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct LogId {
+    #[prost(message, optional, tag = "1")]
+    pub leader_id: Option<LeaderId>,
+    #[prost(uint64, tag = "2")]
+    pub index: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct NodeIdSet {
+    #[prost(uint64, repeated, tag = "1")]
+    pub node_ids: Vec<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Membership {
+    #[prost(message, repeated, tag = "1")]
+    pub configs: Vec<NodeIdSet>,
+    #[prost(btree_map = "uint64, bytes", tag = "2")]
+    pub nodes: BTreeMap<u64, Vec<u8>>,
+    #[prost(btree_map = "uint64, uint64", tag = "3")]
+    pub priorities: BTreeMap<u64, u64>,
+    #[prost(uint64, repeated, tag = "4")]
+    pub witnesses: Vec<u64>,
+    #[prost(message, optional, tag = "5")]
+    pub quorum_policy: Option<QuorumPolicy>,
+    #[prost(btree_map = "uint64, string", tag = "6")]
+    pub zones: BTreeMap<u64, String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QuorumPolicy {
+    #[prost(oneof = "quorum_policy::Policy", tags = "1, 2, 3, 4")]
+    pub policy: Option<quorum_policy::Policy>,
+}
+
+pub mod quorum_policy {
+    use std::collections::BTreeMap;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Weighted {
+        #[prost(btree_map = "uint64, uint64", tag = "1")]
+        pub weights: BTreeMap<u64, u64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Grid {
+        #[prost(message, repeated, tag = "1")]
+        pub rows: Vec<super::NodeIdSet>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Policy {
+        #[prost(message, tag = "1")]
+        Majority(super::Empty),
+        #[prost(message, tag = "2")]
+        Weighted(Weighted),
+        #[prost(message, tag = "3")]
+        Grid(Grid),
+        #[prost(message, tag = "4")]
+        MajorityWith(super::NodeIdSet),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StoredMembership {
+    #[prost(message, optional, tag = "1")]
+    pub log_id: Option<LogId>,
+    #[prost(message, optional, tag = "2")]
+    pub membership: Option<Membership>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BasicNode {
+    #[prost(string, tag = "1")]
+    pub addr: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Entry {
+    #[prost(message, optional, tag = "1")]
+    pub log_id: Option<LogId>,
+    #[prost(oneof = "entry::Payload", tags = "2, 3, 4")]
+    pub payload: Option<entry::Payload>,
+}
+
+pub mod entry {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Payload {
+        #[prost(message, tag = "2")]
+        Blank(super::Empty),
+        #[prost(bytes, tag = "3")]
+        Normal(Vec<u8>),
+        #[prost(message, tag = "4")]
+        Membership(super::Membership),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AppendEntriesRequest {
+    #[prost(message, optional, tag = "1")]
+    pub vote: Option<Vote>,
+    #[prost(message, optional, tag = "2")]
+    pub prev_log_id: Option<LogId>,
+    #[prost(message, repeated, tag = "3")]
+    pub entries: Vec<Entry>,
+    #[prost(message, optional, tag = "4")]
+    pub leader_commit: Option<LogId>,
+    #[prost(btree_map = "string, string", tag = "5")]
+    pub context: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct PartialSuccess {
+    #[prost(message, optional, tag = "1")]
+    pub matching: Option<LogId>,
+}
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct AppendEntriesResponse {
+    #[prost(oneof = "append_entries_response::Result", tags = "1, 2, 3, 4")]
+    pub result: Option<append_entries_response::Result>,
+}
+
+pub mod append_entries_response {
+    #[derive(Clone, Copy, PartialEq, prost::Oneof)]
+    pub enum Result {
+        #[prost(message, tag = "1")]
+        Success(super::Empty),
+        #[prost(message, tag = "2")]
+        PartialSuccess(super::PartialSuccess),
+        #[prost(message, tag = "3")]
+        Conflict(super::Empty),
+        #[prost(message, tag = "4")]
+        HigherVote(super::Vote),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VoteRequest {
+    #[prost(message, optional, tag = "1")]
+    pub vote: Option<Vote>,
+    #[prost(message, optional, tag = "2")]
+    pub last_log_id: Option<LogId>,
+    #[prost(btree_map = "string, string", tag = "3")]
+    pub context: BTreeMap<String, String>,
+    #[prost(bool, tag = "4")]
+    pub pre_vote: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct VoteResponse {
+    #[prost(message, optional, tag = "1")]
+    pub vote: Option<Vote>,
+    #[prost(bool, tag = "2")]
+    pub vote_granted: bool,
+    #[prost(message, optional, tag = "3")]
+    pub last_log_id: Option<LogId>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SnapshotMeta {
+    #[prost(message, optional, tag = "1")]
+    pub last_log_id: Option<LogId>,
+    #[prost(message, optional, tag = "2")]
+    pub last_membership: Option<StoredMembership>,
+    #[prost(string, tag = "3")]
+    pub snapshot_id: String,
+    #[prost(message, optional, tag = "4")]
+    pub delta_base: Option<LogId>,
+    #[prost(uint32, optional, tag = "5")]
+    pub checksum: Option<u32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InstallSnapshotRequest {
+    #[prost(message, optional, tag = "1")]
+    pub vote: Option<Vote>,
+    #[prost(message, optional, tag = "2")]
+    pub meta: Option<SnapshotMeta>,
+    #[prost(uint64, tag = "3")]
+    pub offset: u64,
+    #[prost(bytes = "vec", tag = "4")]
+    pub data: Vec<u8>,
+    #[prost(bool, tag = "5")]
+    pub done: bool,
+    #[prost(btree_map = "string, string", tag = "6")]
+    pub context: BTreeMap<String, String>,
+    #[prost(uint32, optional, tag = "7")]
+    pub checksum: Option<u32>,
+    #[prost(string, optional, tag = "8")]
+    pub compression: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InstallSnapshotResponse {
+    #[prost(message, optional, tag = "1")]
+    pub vote: Option<Vote>,
+    #[prost(string, optional, tag = "2")]
+    pub compression: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct SnapshotResponse {
+    #[prost(message, optional, tag = "1")]
+    pub vote: Option<Vote>,
+}
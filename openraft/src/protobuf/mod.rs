@@ -0,0 +1,73 @@
+//! The protobuf messages of the RPC types, enabled by feature flag `protobuf`.
+//!
+//! The messages are defined in [`openraft.proto`], the canonical wire format of the RPCs, with
+//! which a transport can exchange RPCs with a peer built with another version of Openraft, or an
+//! implementation not written in Rust. A transport converts an RPC type to its message with
+//! `From` and back with `TryFrom`, which fails with a [`ProtobufError`] if the message is
+//! incomplete, and encodes the message with [`prost::Message`].
+//!
+//! The conversions are provided for a [`ProtobufTypeConfig`], which defines how the application
+//! data and node are encoded.
+//!
+//! [`openraft.proto`]: https://github.com/datafuselabs/openraft/blob/main/openraft/proto/openraft.proto
+
+mod convert;
+mod messages;
+
+use anyerror::AnyError;
+pub use messages::append_entries_response;
+pub use messages::entry;
+pub use messages::quorum_policy;
+pub use messages::AppendEntriesRequest;
+pub use messages::AppendEntriesResponse;
+pub use messages::BasicNode;
+pub use messages::Empty;
+pub use messages::Entry;
+pub use messages::InstallSnapshotRequest;
+pub use messages::InstallSnapshotResponse;
+pub use messages::LeaderId;
+pub use messages::LogId;
+pub use messages::Membership;
+pub use messages::NodeIdSet;
+pub use messages::PartialSuccess;
+pub use messages::QuorumPolicy;
+pub use messages::SnapshotMeta;
+pub use messages::SnapshotResponse;
+pub use messages::StoredMembership;
+pub use messages::Vote;
+pub use messages::VoteRequest;
+pub use messages::VoteResponse;
+
+use crate::RaftTypeConfig;
+
+/// A [`RaftTypeConfig`] whose RPC types can be converted to and from the protobuf messages.
+///
+/// The node id is a `u64` on the wire. The application data `D` and `Node` are carried as opaque
+/// bytes, encoded and decoded by the methods of this trait. E.g., `Node = BasicNode` can be
+/// encoded with the [`BasicNode`] message.
+pub trait ProtobufTypeConfig: RaftTypeConfig<NodeId = u64, Entry = crate::Entry<Self>> {
+    /// Encode the application data of a normal log entry.
+    fn encode_data(data: &Self::D) -> Vec<u8>;
+
+    /// Decode the application data of a normal log entry.
+    fn decode_data(buf: &[u8]) -> Result<Self::D, AnyError>;
+
+    /// Encode a node of a membership config.
+    fn encode_node(node: &Self::Node) -> Vec<u8>;
+
+    /// Decode a node of a membership config.
+    fn decode_node(buf: &[u8]) -> Result<Self::Node, AnyError>;
+}
+
+/// Error converting a protobuf message to an RPC type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(thiserror::Error)]
+pub enum ProtobufError {
+    /// A required field is absent in the message.
+    #[error("missing field `{0}`")]
+    MissingField(&'static str),
+
+    /// The application data or node in a field can not be decoded.
+    #[error("failed to decode field `{field}`: {source}")]
+    Decode { field: &'static str, source: AnyError },
+}
@@ -2,7 +2,8 @@
 //! Blocking mode write API blocks until the write operation is completed,
 //! where [`RaftTypeConfig::Responder`] is a [`OneshotResponder`].
 
-use maplit::btreemap;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 
 use crate::core::raft_msg::RaftMsg;
 use crate::error::ClientWriteError;
@@ -44,17 +45,45 @@ where C: RaftTypeConfig<Responder = OneshotResponder<C>>
     ///
     /// If it loses leadership or crashed before committing the second **uniform** config log, the
     /// cluster is left in the **joint** config.
+    ///
+    /// If [`Config::membership_change_guard`] is enabled, a change that reduces the fault
+    /// tolerance of the cluster fails with an [`UnsafeMembershipChange`] error, and
+    /// [`Raft::change_membership_unguarded()`] has to be used to apply it anyway.
+    ///
+    /// [`Config::membership_change_guard`]: crate::Config::membership_change_guard
+    /// [`UnsafeMembershipChange`]: crate::error::UnsafeMembershipChange
     #[tracing::instrument(level = "info", skip_all)]
     pub async fn change_membership(
         &self,
         members: impl Into<ChangeMembers<C::NodeId, C::Node>>,
         retain: bool,
     ) -> Result<ClientWriteResponse<C>, RaftError<C, ClientWriteError<C>>> {
-        let changes: ChangeMembers<C::NodeId, C::Node> = members.into();
+        self.do_change_membership(members.into(), retain, true).await
+    }
 
+    /// Propose a cluster configuration change like [`Raft::change_membership()`], without the
+    /// check of [`Config::membership_change_guard`].
+    ///
+    /// [`Config::membership_change_guard`]: crate::Config::membership_change_guard
+    #[tracing::instrument(level = "info", skip_all)]
+    pub async fn change_membership_unguarded(
+        &self,
+        members: impl Into<ChangeMembers<C::NodeId, C::Node>>,
+        retain: bool,
+    ) -> Result<ClientWriteResponse<C>, RaftError<C, ClientWriteError<C>>> {
+        self.do_change_membership(members.into(), retain, false).await
+    }
+
+    async fn do_change_membership(
+        &self,
+        changes: ChangeMembers<C::NodeId, C::Node>,
+        retain: bool,
+        guard: bool,
+    ) -> Result<ClientWriteResponse<C>, RaftError<C, ClientWriteError<C>>> {
         tracing::info!(
             changes = debug(&changes),
             retain = display(retain),
+            guard = display(guard),
             "change_membership: start to commit joint config"
         );
 
@@ -68,6 +97,7 @@ where C: RaftTypeConfig<Responder = OneshotResponder<C>>
                 RaftMsg::ChangeMembership {
                     changes: changes.clone(),
                     retain,
+                    guard,
                     tx,
                 },
                 rx,
@@ -92,7 +122,14 @@ where C: RaftTypeConfig<Responder = OneshotResponder<C>>
 
         let (tx, rx) = oneshot_channel::<C>();
 
-        let res = self.inner.call_core(RaftMsg::ChangeMembership { changes, retain, tx }, rx).await;
+        // The change has been checked in the first step; leaving the joint config is not refused.
+        let msg = RaftMsg::ChangeMembership {
+            changes,
+            retain,
+            guard: false,
+            tx,
+        };
+        let res = self.inner.call_core(msg, rx).await;
 
         if let Err(e) = &res {
             tracing::error!("the second step error: {}", e);
@@ -104,6 +141,30 @@ where C: RaftTypeConfig<Responder = OneshotResponder<C>>
         Ok(res)
     }
 
+    /// Remove this leader from the voters and keep it as a learner, e.g., to drain it before
+    /// maintenance.
+    ///
+    /// It commits the membership change with [`Raft::change_membership()`], then hands over the
+    /// leadership with [`Raft::step_down()`] if this node is still the leader. A failure of the
+    /// latter is only logged: a leader that is no longer a voter steps down by itself.
+    ///
+    /// It fails with a [`ForwardToLeader`] error if this node is not the leader. In such a case
+    /// the application should ask the leader to remove this node from the voters with
+    /// [`ChangeMembers::RemoveVoters`].
+    ///
+    /// [`ForwardToLeader`]: crate::error::ForwardToLeader
+    #[tracing::instrument(level = "info", skip_all)]
+    pub async fn demote_self_to_learner(&self) -> Result<ClientWriteResponse<C>, RaftError<C, ClientWriteError<C>>> {
+        let id = self.inner.id;
+        let resp = self.change_membership(ChangeMembers::RemoveVoters(BTreeSet::from([id])), true).await?;
+
+        if let Err(e) = self.step_down(true).await {
+            tracing::warn!(error = display(&e), "failed to step down after demoted to learner");
+        }
+
+        Ok(resp)
+    }
+
     /// Add a new learner raft node, optionally, blocking until up-to-speed.
     ///
     /// - Add a node as learner into the cluster.
@@ -118,6 +179,11 @@ where C: RaftTypeConfig<Responder = OneshotResponder<C>>
     ///
     /// If the node to add is already a voter or learner, it will still re-add it.
     ///
+    /// If [`Config::auto_promote_learner_duration`](`crate::Config::auto_promote_learner_duration`)
+    /// is set, the leader promotes the learner to a voter once it has kept up with the leader for
+    /// that long. The promotion is reported in
+    /// [`RaftMetrics::learner_promotion`](`crate::RaftMetrics::learner_promotion`).
+    ///
     /// A `node` is able to store the network address of a node. Thus an application does not
     /// need another store for mapping node-id to ip-addr when implementing the RaftNetwork.
     #[tracing::instrument(level = "debug", skip(self, id), fields(target=display(id)))]
@@ -129,11 +195,7 @@ where C: RaftTypeConfig<Responder = OneshotResponder<C>>
     ) -> Result<ClientWriteResponse<C>, RaftError<C, ClientWriteError<C>>> {
         let (tx, rx) = oneshot_channel::<C>();
 
-        let msg = RaftMsg::ChangeMembership {
-            changes: ChangeMembers::AddNodes(btreemap! {id=>node}),
-            retain: true,
-            tx,
-        };
+        let msg = RaftMsg::AddLearner { id, node, tx };
 
         let resp = self.inner.call_core(msg, rx).await?;
 
@@ -166,6 +228,54 @@ where C: RaftTypeConfig<Responder = OneshotResponder<C>>
 
         Ok(resp)
     }
+
+    /// Replace the voter `old` with a new node `new`, e.g., to move a member to another machine.
+    ///
+    /// - It adds `new` as a learner with [`Raft::add_learner()`] and blocks until its logs are up
+    ///   to date.
+    /// - Then it swaps `new` for `old` in one joint membership change with
+    ///   [`ChangeMembers::ReplaceVoter`], and `old` is removed from the cluster.
+    ///
+    /// If it fails after the first step, `new` is left as a learner and it is safe to call it
+    /// again.
+    #[tracing::instrument(level = "info", skip(self, node))]
+    pub async fn replace_node(
+        &self,
+        old: C::NodeId,
+        new: C::NodeId,
+        node: C::Node,
+    ) -> Result<ClientWriteResponse<C>, RaftError<C, ClientWriteError<C>>> {
+        self.add_learner(new, node, true).await?;
+
+        tracing::info!(
+            "replace_node: learner {} is up to date, replace voter {} with it",
+            new,
+            old
+        );
+
+        self.change_membership(ChangeMembers::ReplaceVoter(old, new), false).await
+    }
+
+    /// Update the node of a member, e.g., when its address changes, without changing the voters
+    /// and learners.
+    ///
+    /// The updated node is replicated with a membership log entry by
+    /// [`ChangeMembers::UpdateNodes`], and nothing is changed if `node_id` is not a member. Every
+    /// node that receives it passes the updated node to
+    /// [`RaftNetworkFactory::node_updated()`], and then creates new clients to it with
+    /// [`RaftNetworkFactory::new_client()`].
+    ///
+    /// [`RaftNetworkFactory::node_updated()`]: crate::network::RaftNetworkFactory::node_updated
+    /// [`RaftNetworkFactory::new_client()`]: crate::network::RaftNetworkFactory::new_client
+    #[tracing::instrument(level = "info", skip(self, node))]
+    pub async fn update_node(
+        &self,
+        node_id: C::NodeId,
+        node: C::Node,
+    ) -> Result<ClientWriteResponse<C>, RaftError<C, ClientWriteError<C>>> {
+        let nodes = BTreeMap::from([(node_id, node)]);
+        self.change_membership(ChangeMembers::UpdateNodes(nodes), true).await
+    }
 }
 
 fn oneshot_channel<C>() -> (OneshotResponder<C>, OneshotReceiverOf<C, ClientWriteResult<C>>)
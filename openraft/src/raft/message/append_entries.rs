@@ -2,6 +2,7 @@ use std::fmt;
 
 use crate::display_ext::DisplayOptionExt;
 use crate::display_ext::DisplaySlice;
+use crate::network::RpcContext;
 use crate::LogId;
 use crate::RaftTypeConfig;
 use crate::Vote;
@@ -22,6 +23,12 @@ pub struct AppendEntriesRequest<C: RaftTypeConfig> {
 
     /// The leader's committed log id.
     pub leader_commit: Option<LogId<C::NodeId>>,
+
+    /// The context propagated from the sender, such as the trace context.
+    ///
+    /// See [`RpcContext`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub context: RpcContext,
 }
 
 impl<C: RaftTypeConfig> fmt::Debug for AppendEntriesRequest<C>
@@ -33,6 +40,7 @@ where C::D: fmt::Debug
             .field("prev_log_id", &self.prev_log_id)
             .field("entries", &self.entries)
             .field("leader_commit", &self.leader_commit)
+            .field("context", &self.context)
             .finish()
     }
 }
@@ -72,6 +80,9 @@ pub enum AppendEntriesResponse<C: RaftTypeConfig> {
     /// For example, it tries to send log entries `[1-2..3-10]`, the application is allowed to send
     /// just `[1-2..1-3]` and return `PartialSuccess(1-3)`,
     ///
+    /// A follower also replies a partial success if its log storage can only append a prefix of
+    /// the entries, see [`RaftLogStorage::appendable_count()`].
+    ///
     /// ### Caution
     ///
     /// The returned matching log id must be **greater than or equal to** the first log
@@ -82,6 +93,7 @@ pub enum AppendEntriesResponse<C: RaftTypeConfig> {
     ///
     /// [`RPCError`]: crate::error::RPCError
     /// [`RaftNetwork::append_entries`]: crate::network::RaftNetwork::append_entries
+    /// [`RaftLogStorage::appendable_count()`]: crate::storage::RaftLogStorage::appendable_count
     PartialSuccess(Option<LogId<C::NodeId>>),
 
     /// The first log id([`AppendEntriesRequest::prev_log_id`]) of the entries to send does not
@@ -1,5 +1,6 @@
 use std::fmt;
 
+use crate::network::RpcContext;
 use crate::RaftTypeConfig;
 use crate::SnapshotMeta;
 use crate::Vote;
@@ -17,10 +18,52 @@ pub struct InstallSnapshotRequest<C: RaftTypeConfig> {
     /// The byte offset where this chunk of data is positioned in the snapshot file.
     pub offset: u64,
     /// The raw bytes of the snapshot chunk, starting at `offset`.
+    ///
+    /// If `compression` is set, it is the compressed raw bytes, and `offset` is still the
+    /// position of the uncompressed bytes.
     pub data: Vec<u8>,
 
     /// Will be `true` if this is the last chunk in the snapshot.
     pub done: bool,
+
+    /// The CRC32 checksum of `data`, set with [`Self::with_checksum()`].
+    ///
+    /// A receiver rejects a chunk whose data does not match it, and the leader sends the chunk
+    /// again. It is not checked if it is `None`, e.g., sent by an older version.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub checksum: Option<u32>,
+
+    /// The name of the algorithm that compressed `data`, such as `"zstd"`; `None` if `data` is
+    /// not compressed.
+    ///
+    /// The receiver acknowledges it in [`InstallSnapshotResponse::compression`] if it decodes
+    /// `data`, otherwise the leader sends the chunks uncompressed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compression: Option<String>,
+
+    /// The context propagated from the sender, such as the trace context.
+    ///
+    /// See [`RpcContext`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub context: RpcContext,
+}
+
+impl<C: RaftTypeConfig> InstallSnapshotRequest<C> {
+    /// Set the checksum of the chunk data.
+    pub fn with_checksum(mut self) -> Self {
+        self.checksum = Some(crc32fast::hash(&self.data));
+        self
+    }
+
+    /// Returns `false` if the chunk data does not match the checksum, i.e., it is corrupted.
+    ///
+    /// A chunk without a checksum is always valid.
+    pub fn is_checksum_valid(&self) -> bool {
+        match self.checksum {
+            Some(checksum) => checksum == crc32fast::hash(&self.data),
+            None => true,
+        }
+    }
 }
 
 impl<C: RaftTypeConfig> fmt::Display for InstallSnapshotRequest<C> {
@@ -45,6 +88,13 @@ impl<C: RaftTypeConfig> fmt::Display for InstallSnapshotRequest<C> {
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct InstallSnapshotResponse<C: RaftTypeConfig> {
     pub vote: Vote<C::NodeId>,
+
+    /// The compression algorithm of the request that this node has decoded.
+    ///
+    /// It is `None` if the request is not compressed, or this node can not decode it, e.g., it is
+    /// built without the feature flag of the algorithm.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compression: Option<String>,
 }
 
 /// The response to `Raft::install_full_snapshot` API.
@@ -67,6 +117,9 @@ impl<C> From<SnapshotResponse<C>> for InstallSnapshotResponse<C>
 where C: RaftTypeConfig
 {
     fn from(snap_resp: SnapshotResponse<C>) -> Self {
-        Self { vote: snap_resp.vote }
+        Self {
+            vote: snap_resp.vote,
+            compression: None,
+        }
     }
 }
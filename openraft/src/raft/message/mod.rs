@@ -5,6 +5,9 @@
 
 mod append_entries;
 mod install_snapshot;
+mod step;
+mod transfer_leader;
+mod transfer_snapshot;
 mod vote;
 
 mod client_write;
@@ -16,5 +19,10 @@ pub use client_write::ClientWriteResult;
 pub use install_snapshot::InstallSnapshotRequest;
 pub use install_snapshot::InstallSnapshotResponse;
 pub use install_snapshot::SnapshotResponse;
+pub use step::StepRequest;
+pub use step::StepResponse;
+pub use transfer_leader::TransferLeaderRequest;
+pub use transfer_snapshot::TransferSnapshotRequest;
+pub use transfer_snapshot::TransferSnapshotResponse;
 pub use vote::VoteRequest;
 pub use vote::VoteResponse;
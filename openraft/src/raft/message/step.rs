@@ -0,0 +1,61 @@
+use std::fmt;
+
+use crate::raft::message::AppendEntriesRequest;
+use crate::raft::message::AppendEntriesResponse;
+use crate::raft::message::TransferLeaderRequest;
+use crate::raft::message::VoteRequest;
+use crate::raft::message::VoteResponse;
+use crate::RaftTypeConfig;
+
+/// An RPC received from another node, to be handled by [`Raft::step()`](`crate::Raft::step`).
+///
+/// A snapshot is not a single message, it is received with
+/// [`Raft::install_full_snapshot()`](`crate::Raft::install_full_snapshot`).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum StepRequest<C: RaftTypeConfig> {
+    AppendEntries(AppendEntriesRequest<C>),
+    Vote(VoteRequest<C>),
+    TransferLeader(TransferLeaderRequest<C>),
+}
+
+impl<C: RaftTypeConfig> fmt::Debug for StepRequest<C>
+where C::D: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AppendEntries(rpc) => f.debug_tuple("AppendEntries").field(rpc).finish(),
+            Self::Vote(rpc) => f.debug_tuple("Vote").field(rpc).finish(),
+            Self::TransferLeader(rpc) => f.debug_tuple("TransferLeader").field(rpc).finish(),
+        }
+    }
+}
+
+impl<C: RaftTypeConfig> fmt::Display for StepRequest<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AppendEntries(rpc) => write!(f, "AppendEntries: {}", rpc),
+            Self::Vote(rpc) => write!(f, "Vote: {}", rpc),
+            Self::TransferLeader(rpc) => write!(f, "TransferLeader: {}", rpc),
+        }
+    }
+}
+
+/// The response to a [`StepRequest`], of the same kind as the request.
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum StepResponse<C: RaftTypeConfig> {
+    AppendEntries(AppendEntriesResponse<C>),
+    Vote(VoteResponse<C>),
+    TransferLeader,
+}
+
+impl<C: RaftTypeConfig> fmt::Display for StepResponse<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AppendEntries(resp) => write!(f, "AppendEntries: {}", resp),
+            Self::Vote(resp) => write!(f, "Vote: {}", resp),
+            Self::TransferLeader => write!(f, "TransferLeader"),
+        }
+    }
+}
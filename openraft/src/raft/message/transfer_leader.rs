@@ -0,0 +1,45 @@
+use std::fmt;
+
+use crate::display_ext::DisplayOptionExt;
+use crate::network::RpcContext;
+use crate::LogId;
+use crate::RaftTypeConfig;
+use crate::Vote;
+
+/// An RPC sent by the leader to every voter, to hand over the leadership to another node.
+///
+/// The target node starts an election at once, if it has all the logs of the leader. The other
+/// voters grant the vote of the target even if the leader lease has not yet expired.
+///
+/// See [`Raft::transfer_leadership()`](`crate::Raft::transfer_leadership`).
+#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct TransferLeaderRequest<C: RaftTypeConfig> {
+    /// The vote of the leader that hands over its leadership.
+    pub from_leader: Vote<C::NodeId>,
+
+    /// The node to become the new leader.
+    pub to: C::NodeId,
+
+    /// The last log id of the leader, which the target must have.
+    pub last_log_id: Option<LogId<C::NodeId>>,
+
+    /// The context propagated from the sender, such as the trace context.
+    ///
+    /// See [`RpcContext`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub context: RpcContext,
+}
+
+impl<C: RaftTypeConfig> fmt::Display for TransferLeaderRequest<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TransferLeaderRequest {{ from_leader:{}, to:{}, last_log_id:{} }}",
+            self.from_leader,
+            self.to,
+            self.last_log_id.display()
+        )
+    }
+}
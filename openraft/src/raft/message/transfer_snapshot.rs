@@ -0,0 +1,71 @@
+use std::fmt;
+
+use crate::display_ext::DisplayOptionExt;
+use crate::network::RpcContext;
+use crate::LogId;
+use crate::RaftTypeConfig;
+use crate::Vote;
+
+/// An RPC sent by the leader to ask a node to send its snapshot to a lagging node, on behalf of
+/// the leader.
+///
+/// See [`Config::delegate_snapshot_transfer`](`crate::Config::delegate_snapshot_transfer`).
+#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct TransferSnapshotRequest<C: RaftTypeConfig> {
+    /// The vote of the leader, the snapshot is sent to the target with this vote.
+    pub vote: Vote<C::NodeId>,
+
+    /// The node to send the snapshot to.
+    pub target: C::NodeId,
+
+    /// The node info of the target, to connect to it.
+    pub target_node: C::Node,
+
+    /// The snapshot to send must include at least this log id, so that the leader has the logs
+    /// after it.
+    pub min_last_log_id: Option<LogId<C::NodeId>>,
+
+    /// The context propagated from the sender, such as the trace context.
+    ///
+    /// See [`RpcContext`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub context: RpcContext,
+}
+
+impl<C: RaftTypeConfig> fmt::Display for TransferSnapshotRequest<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TransferSnapshotRequest {{ vote:{}, target:{}, min_last_log_id:{} }}",
+            self.vote,
+            self.target,
+            self.min_last_log_id.display()
+        )
+    }
+}
+
+/// The response to a [`TransferSnapshotRequest`].
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct TransferSnapshotResponse<C: RaftTypeConfig> {
+    /// The vote of the target after receiving the snapshot, or the vote of the requested node if
+    /// it is greater than the leader's.
+    pub vote: Vote<C::NodeId>,
+
+    /// The last log id of the snapshot sent to the target.
+    pub last_log_id: Option<LogId<C::NodeId>>,
+}
+
+impl<C: RaftTypeConfig> fmt::Display for TransferSnapshotResponse<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TransferSnapshotResponse {{ vote:{}, last_log_id:{} }}",
+            self.vote,
+            self.last_log_id.display()
+        )
+    }
+}
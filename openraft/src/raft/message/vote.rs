@@ -1,6 +1,7 @@
 use std::fmt;
 
 use crate::display_ext::DisplayOptionExt;
+use crate::network::RpcContext;
 use crate::LogId;
 use crate::RaftTypeConfig;
 use crate::Vote;
@@ -11,13 +12,32 @@ use crate::Vote;
 pub struct VoteRequest<C: RaftTypeConfig> {
     pub vote: Vote<C::NodeId>,
     pub last_log_id: Option<LogId<C::NodeId>>,
+
+    /// Whether it is a pre-vote request.
+    ///
+    /// A pre-vote request asks whether the vote would be granted. The receiver does not update
+    /// its own vote when handling it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pre_vote: bool,
+
+    /// The context propagated from the sender, such as the trace context.
+    ///
+    /// See [`RpcContext`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub context: RpcContext,
 }
 
 impl<C> fmt::Display for VoteRequest<C>
 where C: RaftTypeConfig
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{{vote:{}, last_log:{}}}", self.vote, self.last_log_id.display(),)
+        write!(
+            f,
+            "{{{}vote:{}, last_log:{}}}",
+            if self.pre_vote { "pre-" } else { "" },
+            self.vote,
+            self.last_log_id.display(),
+        )
     }
 }
 
@@ -25,7 +45,20 @@ impl<C> VoteRequest<C>
 where C: RaftTypeConfig
 {
     pub fn new(vote: Vote<C::NodeId>, last_log_id: Option<LogId<C::NodeId>>) -> Self {
-        Self { vote, last_log_id }
+        Self {
+            vote,
+            last_log_id,
+            pre_vote: false,
+            context: RpcContext::default(),
+        }
+    }
+
+    /// Create a pre-vote request, which does not change the vote of the receiver.
+    pub fn new_pre_vote(vote: Vote<C::NodeId>, last_log_id: Option<LogId<C::NodeId>>) -> Self {
+        Self {
+            pre_vote: true,
+            ..Self::new(vote, last_log_id)
+        }
     }
 }
 
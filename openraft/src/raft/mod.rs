@@ -13,6 +13,8 @@ mod external_request;
 mod impl_raft_blocking_write;
 pub(crate) mod message;
 mod raft_inner;
+mod read_policy;
+mod replication_status;
 pub mod responder;
 mod runtime_config_handle;
 pub mod trigger;
@@ -28,7 +30,9 @@ use std::fmt::Debug;
 use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::SystemTime;
 
+use anyerror::AnyError;
 use core_state::CoreState;
 pub use message::AppendEntriesRequest;
 pub use message::AppendEntriesResponse;
@@ -37,8 +41,16 @@ pub use message::ClientWriteResult;
 pub use message::InstallSnapshotRequest;
 pub use message::InstallSnapshotResponse;
 pub use message::SnapshotResponse;
+pub use message::StepRequest;
+pub use message::StepResponse;
+pub use message::TransferLeaderRequest;
+pub use message::TransferSnapshotRequest;
+pub use message::TransferSnapshotResponse;
 pub use message::VoteRequest;
 pub use message::VoteResponse;
+pub use read_policy::ReadPolicy;
+pub use replication_status::ReplicationStatus;
+pub use replication_status::SnapshotTransferStatus;
 use tokio::sync::mpsc;
 use tokio::sync::watch;
 use tokio::sync::Mutex;
@@ -46,52 +58,94 @@ use tracing::trace_span;
 use tracing::Instrument;
 use tracing::Level;
 
+use crate::async_runtime::mpsc::TrySendError;
 use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::Broadcast;
+use crate::async_runtime::BroadcastSender;
+use crate::async_runtime::CancellationToken;
+use crate::async_runtime::Mpsc;
+use crate::async_runtime::MpscSender;
+use crate::async_runtime::Semaphore;
 use crate::config::Config;
 use crate::config::RuntimeConfig;
 use crate::core::command_state::CommandState;
+use crate::core::log_cache::LogCache;
 use crate::core::raft_msg::external_command::ExternalCommand;
 use crate::core::raft_msg::RaftMsg;
 use crate::core::replication_lag;
 use crate::core::sm::worker;
+use crate::core::MembershipHistory;
 use crate::core::RaftCore;
 use crate::core::Tick;
 use crate::engine::Engine;
 use crate::engine::EngineConfig;
+use crate::entry::FromAppData;
 use crate::error::CheckIsLeaderError;
 use crate::error::ClientWriteError;
+use crate::error::ClientWriteTimeout;
 use crate::error::Fatal;
+use crate::error::ForwardToLeader;
 use crate::error::Infallible;
 use crate::error::InitializeError;
+use crate::error::InvalidAppData;
+use crate::error::RPCError;
 use crate::error::RaftError;
+use crate::error::RemoteError;
+use crate::error::TransferLeaderError;
+use crate::error::TransferSnapshotError;
+use crate::error::Unauthenticated;
 use crate::membership::IntoNodes;
+use crate::metrics::DeadMember;
 use crate::metrics::RaftDataMetrics;
 use crate::metrics::RaftMetrics;
 use crate::metrics::RaftServerMetrics;
+use crate::metrics::RoleChange;
 use crate::metrics::Wait;
 use crate::metrics::WaitError;
+use crate::network::can_decode_payload;
+use crate::network::AuthRpc;
+use crate::network::RpcAuth;
+use crate::network::RpcContext;
 use crate::raft::raft_inner::RaftInner;
 use crate::raft::responder::Responder;
 pub use crate::raft::runtime_config_handle::RuntimeConfigHandle;
 use crate::raft::trigger::Trigger;
 use crate::storage::RaftLogStorage;
 use crate::storage::RaftStateMachine;
+use crate::storage::StorageCipher;
 use crate::type_config::alias::AsyncRuntimeOf;
+use crate::type_config::alias::BroadcastOf;
+use crate::type_config::alias::BroadcastReceiverOf;
+use crate::type_config::alias::CancellationTokenOf;
+use crate::type_config::alias::InstantOf;
 use crate::type_config::alias::JoinErrorOf;
+use crate::type_config::alias::MpscOf;
 use crate::type_config::alias::ResponderOf;
 use crate::type_config::alias::ResponderReceiverOf;
+use crate::type_config::alias::SemaphoreOf;
 use crate::type_config::alias::SnapshotDataOf;
 use crate::AsyncRuntime;
 use crate::LogId;
 use crate::LogIdOptionExt;
+use crate::MembershipRecord;
 use crate::OptionalSend;
 use crate::RaftNetworkFactory;
 use crate::RaftState;
 pub use crate::RaftTypeConfig;
+use crate::ServerState;
 use crate::Snapshot;
 use crate::StorageHelper;
+use crate::StorageIOError;
 use crate::Vote;
 
+/// The number of server state changes buffered for every subscriber, see
+/// [`Raft::subscribe_role_changes()`].
+const ROLE_CHANGE_CHANNEL_SIZE: usize = 64;
+
+/// The number of dead member removals buffered for every subscriber, see
+/// [`Raft::subscribe_dead_members()`].
+const DEAD_MEMBER_CHANNEL_SIZE: usize = 16;
+
 /// Define types for a Raft type configuration.
 ///
 /// Since Rust has some limitations when deriving traits for types with generic arguments
@@ -118,12 +172,16 @@ use crate::Vote;
 /// Types can be omitted, and the following default type will be used:
 /// - `D`:            `String`
 /// - `R`:            `String`
+/// - `AppError`:     `::openraft::error::Infallible`
 /// - `NodeId`:       `u64`
 /// - `Node`:         `::openraft::impls::BasicNode`
 /// - `Entry`:        `::openraft::impls::Entry<Self>`
 /// - `SnapshotData`: `Cursor<Vec<u8>>`
 /// - `Responder`:    `::openraft::impls::OneshotResponder<Self>`
 /// - `AsyncRuntime`: `::openraft::impls::TokioRuntime`
+/// - `PayloadCodec`: `::openraft::network::NoCompression`
+/// - `StorageCipher`: `::openraft::storage::NoEncryption`
+/// - `SnapshotTrigger`: `::openraft::NoSnapshotTrigger`
 ///
 /// For example, to declare with only `D` and `R` types:
 /// ```ignore
@@ -165,12 +223,16 @@ macro_rules! declare_raft_types {
                 // Default types:
                 (D            , , String                                ),
                 (R            , , String                                ),
+                (AppError     , , $crate::error::Infallible             ),
                 (NodeId       , , u64                                   ),
                 (Node         , , $crate::impls::BasicNode              ),
                 (Entry        , , $crate::impls::Entry<Self>            ),
                 (SnapshotData , , Cursor<Vec<u8>>                       ),
                 (Responder    , , $crate::impls::OneshotResponder<Self> ),
                 (AsyncRuntime , , $crate::impls::TokioRuntime           ),
+                (PayloadCodec , , $crate::network::NoCompression        ),
+                (StorageCipher, , $crate::storage::NoEncryption         ),
+                (SnapshotTrigger, , $crate::NoSnapshotTrigger           ),
             );
 
         }
@@ -206,6 +268,9 @@ where C: RaftTypeConfig
 impl<C> Raft<C>
 where C: RaftTypeConfig
 {
+    /// The maximum number of times [`Raft::client_write_forwarded()`] forwards a request.
+    pub const MAX_FORWARD_HOPS: usize = 3;
+
     /// Create and spawn a new Raft task.
     ///
     /// ### `id`
@@ -225,6 +290,10 @@ where C: RaftTypeConfig
     /// An implementation of the [`RaftLogStorage`] and [`RaftStateMachine`] trait which will be
     /// used by Raft for data storage.
     ///
+    /// If [`RaftTypeConfig::StorageCipher`] is enabled, both of them must report
+    /// `supports_storage_cipher()`, otherwise it returns a [`Fatal::StorageError`] without
+    /// starting, so that nothing is persisted in plaintext.
+    ///
     /// [`RaftNetworkFactory`]: crate::network::RaftNetworkFactory
     #[tracing::instrument(level="debug", skip_all, fields(cluster=%config.cluster_name))]
     pub async fn new<LS, N, SM>(
@@ -239,17 +308,30 @@ where C: RaftTypeConfig
         LS: RaftLogStorage<C>,
         SM: RaftStateMachine<C>,
     {
-        let (tx_api, rx_api) = mpsc::unbounded_channel();
+        if C::StorageCipher::ENABLED
+            && !(log_store.supports_storage_cipher() && state_machine.supports_storage_cipher())
+        {
+            let e = StorageIOError::write(AnyError::error(
+                "StorageCipher is configured but the log store or the state machine does not support it",
+            ));
+            return Err(Fatal::StorageError(e.into()));
+        }
+
+        let (tx_api, rx_api) = MpscOf::<C>::channel(config.api_channel_size as usize);
         let (tx_notify, rx_notify) = mpsc::unbounded_channel();
         let (tx_metrics, rx_metrics) = watch::channel(RaftMetrics::new_initial(id));
         let (tx_data_metrics, rx_data_metrics) = watch::channel(RaftDataMetrics::default());
         let (tx_server_metrics, rx_server_metrics) = watch::channel(RaftServerMetrics::default());
-        let (tx_shutdown, rx_shutdown) = C::AsyncRuntime::oneshot();
+        let (tx_applied, _rx_applied) = BroadcastOf::<C>::channel(config.applied_channel_size as usize);
+        let (tx_role_change, _rx_role_change) = BroadcastOf::<C>::channel(ROLE_CHANGE_CHANNEL_SIZE);
+        let (tx_dead_member, _rx_dead_member) = BroadcastOf::<C>::channel(DEAD_MEMBER_CHANNEL_SIZE);
+        let cancel = CancellationTokenOf::<C>::new();
 
         let tick_handle = Tick::spawn(
             Duration::from_millis(config.heartbeat_interval * 3 / 2),
             tx_notify.clone(),
             config.enable_tick,
+            cancel.child_token(),
         );
 
         let runtime_config = Arc::new(RuntimeConfig::new(&config));
@@ -265,13 +347,28 @@ where C: RaftTypeConfig
         let eng_config = EngineConfig::new(id, config.as_ref());
 
         let state = {
-            let mut helper = StorageHelper::new(&mut log_store, &mut state_machine);
+            let mut helper = StorageHelper::new(&mut log_store, &mut state_machine)
+                .log_corruption_policy(config.log_corruption_policy);
             helper.get_initial_state().await?
         };
 
+        let membership_history = MembershipHistory::new(config.membership_history_size as usize);
+        let committed = state.membership_state.committed();
+        if let Some(log_id) = committed.log_id() {
+            membership_history.record(*log_id, committed.membership().clone(), SystemTime::now());
+        }
+
         let engine = Engine::new(state, eng_config);
 
-        let sm_handle = worker::Worker::spawn(state_machine, tx_notify.clone());
+        let sm_handle = worker::Worker::spawn(
+            state_machine,
+            tx_notify.clone(),
+            membership_history.clone(),
+            config.offload_snapshot_build,
+        );
+
+        let auth = RpcAuth::new(network.authenticator());
+        let log_cache = LogCache::default();
 
         let core: RaftCore<C, N, LS, SM> = RaftCore {
             id,
@@ -286,6 +383,26 @@ where C: RaftTypeConfig
             client_resp_channels: BTreeMap::new(),
 
             leader_data: None,
+            leader_transfer: None,
+            read_index: Default::default(),
+            write_batch: Default::default(),
+            write_deadlines: Default::default(),
+            learner_promotion: Default::default(),
+            step_down_until: None,
+            election_backoff: config.new_rand_election_backoff::<AsyncRuntimeOf<C>>(),
+            election_timeout_range: (config.election_timeout_min, config.election_timeout_max),
+
+            rtt: Default::default(),
+
+            log_cache: log_cache.clone(),
+            membership_history: membership_history.clone(),
+
+            auth: auth.clone(),
+
+            snapshot_transmission_semaphore: Arc::new(SemaphoreOf::<C>::new(
+                config.max_concurrent_snapshot_transmissions as usize,
+            )),
+            snapshot_building: None,
 
             tx_api: tx_api.clone(),
             rx_api,
@@ -296,14 +413,25 @@ where C: RaftTypeConfig
             tx_metrics,
             tx_data_metrics,
             tx_server_metrics,
+            tx_applied: tx_applied.clone(),
+            tx_role_change: tx_role_change.clone(),
+            tx_dead_member: tx_dead_member.clone(),
+            reported_role: (ServerState::default(), Vote::default()),
+            reported_nodes: BTreeMap::new(),
+
+            cancel: cancel.child_token(),
 
             command_state: CommandState::default(),
+            waiting_sm_commands: Vec::new(),
             span: core_span,
 
             _p: Default::default(),
         };
 
-        let core_handle = C::AsyncRuntime::spawn(core.main(rx_shutdown).instrument(trace_span!("spawn").or_current()));
+        let core_handle = C::AsyncRuntime::spawn_named(
+            &format!("raft-core-{}", id),
+            core.main().instrument(trace_span!("spawn").or_current()),
+        );
 
         let inner = RaftInner {
             id,
@@ -314,10 +442,19 @@ where C: RaftTypeConfig
             rx_metrics,
             rx_data_metrics,
             rx_server_metrics,
-            tx_shutdown: Mutex::new(Some(tx_shutdown)),
+            tx_applied,
+            tx_role_change,
+            tx_dead_member,
+            auth,
+            log_cache,
+            membership_history,
+            cancel,
             core_state: Mutex::new(CoreState::Running(core_handle)),
 
             snapshot: Mutex::new(None),
+
+            #[cfg(not(feature = "singlethreaded"))]
+            runtime_handle: None,
         };
 
         Ok(Self { inner: Arc::new(inner) })
@@ -343,6 +480,23 @@ where C: RaftTypeConfig
         &self.inner.config
     }
 
+    /// Enable an in-memory cache of the latest appended log entries, so that the replication
+    /// streams of a leader send the entries from memory instead of reading them back from the
+    /// [`RaftLogStorage`].
+    ///
+    /// The cache holds at most `max_entries` entries, whose
+    /// [`size_hint()`](`crate::entry::RaftEntry::size_hint`) is at most `max_bytes` in total; the
+    /// oldest entries are evicted first. Only the entries appended after this call are cached.
+    /// The hits and misses are reported in
+    /// [`RaftMetrics::log_cache`](`crate::RaftMetrics::log_cache`).
+    ///
+    /// It requires `C::Entry` to be `Clone`, because an entry is cloned into the cache and cloned
+    /// again for every replication target.
+    pub fn enable_log_cache(&self, max_entries: usize, max_bytes: u64)
+    where C::Entry: Clone {
+        self.inner.log_cache.enable(max_entries, max_bytes, C::Entry::clone);
+    }
+
     /// Return a handle to manually trigger raft actions, such as elect or build snapshot.
     ///
     /// Example:
@@ -359,11 +513,54 @@ where C: RaftTypeConfig
     /// These RPCs are sent by the cluster leader to replicate log entries (§5.3), and are also
     /// used as heartbeats (§5.2).
     #[tracing::instrument(level = "debug", skip(self, rpc))]
-    pub async fn append_entries(&self, rpc: AppendEntriesRequest<C>) -> Result<AppendEntriesResponse<C>, RaftError<C>> {
+    pub async fn append_entries(
+        &self,
+        rpc: AppendEntriesRequest<C>,
+    ) -> Result<AppendEntriesResponse<C>, RaftError<C, Unauthenticated>> {
         tracing::debug!(rpc = display(&rpc), "Raft::append_entries");
 
+        self.inner.auth.verify(AuthRpc::AppendEntries(&rpc), &rpc.context).map_err(RaftError::APIError)?;
+
         let (tx, rx) = C::AsyncRuntime::oneshot();
-        self.inner.call_core(RaftMsg::AppendEntries { rpc, tx }, rx).await
+        self.inner
+            .call_core(RaftMsg::AppendEntries { rpc, tx }, rx)
+            .await
+            .map_err(RaftError::with_api_error)
+    }
+
+    /// Handle an RPC received from another node, and return the response to send back.
+    ///
+    /// It dispatches the [`StepRequest`] to [`Raft::append_entries()`], [`Raft::vote()`] or
+    /// [`Raft::handle_transfer_leader()`]. Together with [`Raft::tick()`], it lets an application
+    /// drive this node from its own event loop, or from a deterministic test harness.
+    #[tracing::instrument(level = "debug", skip(self, msg))]
+    pub async fn step(&self, msg: StepRequest<C>) -> Result<StepResponse<C>, RaftError<C, Unauthenticated>> {
+        tracing::debug!(msg = display(&msg), "Raft::step()");
+
+        match msg {
+            StepRequest::AppendEntries(rpc) => self.append_entries(rpc).await.map(StepResponse::AppendEntries),
+            StepRequest::Vote(rpc) => self.vote(rpc).await.map(StepResponse::Vote),
+            StepRequest::TransferLeader(rpc) => {
+                self.handle_transfer_leader(rpc).await?;
+                Ok(StepResponse::TransferLeader)
+            }
+        }
+    }
+
+    /// Check every timer of this node once, e.g., to start an election if the election timeout
+    /// has passed, or to send heartbeats if this node is the leader.
+    ///
+    /// It does the same as a tick emitted by the timer task of this node every
+    /// `heartbeat_interval * 1.5` milliseconds. An application that drives this node from its
+    /// own scheduler disables the timer task with [`Config::enable_tick`] set to `false`, and
+    /// calls this method instead. The timers still compare against the clock of
+    /// [`RaftTypeConfig::AsyncRuntime`], which a deterministic test harness may pause and advance.
+    ///
+    /// It returns once the tick is queued, or an error if `RaftCore` has stopped.
+    ///
+    /// [`Config::enable_tick`]: crate::Config::enable_tick
+    pub async fn tick(&self) -> Result<(), Fatal<C>> {
+        self.inner.send_external_command(ExternalCommand::Tick, "tick").await
     }
 
     /// Submit a VoteRequest (RequestVote in the spec) RPC to this Raft node.
@@ -371,11 +568,13 @@ where C: RaftTypeConfig
     /// These RPCs are sent by cluster peers which are in candidate state attempting to gather votes
     /// (§5.2).
     #[tracing::instrument(level = "debug", skip(self, rpc))]
-    pub async fn vote(&self, rpc: VoteRequest<C>) -> Result<VoteResponse<C>, RaftError<C>> {
+    pub async fn vote(&self, rpc: VoteRequest<C>) -> Result<VoteResponse<C>, RaftError<C, Unauthenticated>> {
         tracing::info!(rpc = display(&rpc), "Raft::vote()");
 
+        self.inner.auth.verify(AuthRpc::Vote(&rpc), &rpc.context).map_err(RaftError::APIError)?;
+
         let (tx, rx) = C::AsyncRuntime::oneshot();
-        self.inner.call_core(RaftMsg::RequestVote { rpc, tx }, rx).await
+        self.inner.call_core(RaftMsg::RequestVote { rpc, tx }, rx).await.map_err(RaftError::with_api_error)
     }
 
     /// Get the latest snapshot from the state machine.
@@ -414,8 +613,23 @@ where C: RaftTypeConfig
     ) -> Result<SnapshotResponse<C>, Fatal<C>> {
         tracing::info!("Raft::install_full_snapshot()");
 
+        self.do_install_full_snapshot(vote, snapshot, RpcContext::default()).await
+    }
+
+    async fn do_install_full_snapshot(
+        &self,
+        vote: Vote<C::NodeId>,
+        snapshot: Snapshot<C>,
+        context: RpcContext,
+    ) -> Result<SnapshotResponse<C>, Fatal<C>> {
         let (tx, rx) = C::AsyncRuntime::oneshot();
-        let res = self.inner.call_core(RaftMsg::InstallFullSnapshot { vote, snapshot, tx }, rx).await;
+        let msg = RaftMsg::InstallFullSnapshot {
+            vote,
+            snapshot,
+            context,
+            tx,
+        };
+        let res = self.inner.call_core(msg, rx).await;
         match res {
             Ok(x) => Ok(x),
             Err(e) => {
@@ -425,6 +639,134 @@ where C: RaftTypeConfig
         }
     }
 
+    /// Send the snapshot of this node to another node on behalf of the leader.
+    ///
+    /// The leader asks an up-to-date node to send a snapshot to a lagging node with this RPC,
+    /// when [`Config::delegate_snapshot_transfer`] is enabled. This node sends its latest snapshot
+    /// to `rpc.target` with [`RaftNetworkV2::full_snapshot()`], with the vote of the leader.
+    ///
+    /// It returns [`SnapshotTooOld`] if the snapshot does not include `rpc.min_last_log_id`. If
+    /// the vote of this node is greater than the leader's, nothing is sent and the vote of this
+    /// node is returned.
+    ///
+    /// [`Config::delegate_snapshot_transfer`]: crate::Config::delegate_snapshot_transfer
+    /// [`RaftNetworkV2::full_snapshot()`]: crate::network::v2::RaftNetworkV2::full_snapshot
+    /// [`SnapshotTooOld`]: crate::error::SnapshotTooOld
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn transfer_snapshot(
+        &self,
+        rpc: TransferSnapshotRequest<C>,
+    ) -> Result<TransferSnapshotResponse<C>, RaftError<C, TransferSnapshotError<C>>> {
+        tracing::info!(rpc = display(&rpc), "Raft::transfer_snapshot()");
+
+        self.inner
+            .auth
+            .verify(AuthRpc::TransferSnapshot(&rpc), &rpc.context)
+            .map_err(|e| RaftError::APIError(e.into()))?;
+
+        let (tx, rx) = C::AsyncRuntime::oneshot();
+        self.inner.call_core(RaftMsg::TransferSnapshot { rpc, tx }, rx).await
+    }
+
+    /// Hand over the leadership of this node to `to`.
+    ///
+    /// This node stops accepting new proposals at once: client writes are rejected with a
+    /// [`ForwardToLeader`] error without a leader. It waits until `to` has replicated all the logs,
+    /// then asks it to start an election with a [`TransferLeaderRequest`], which is also sent to
+    /// the other voters so that they grant the vote of `to` even if the leader lease has not yet
+    /// expired.
+    ///
+    /// It returns `Ok(())` once `to` is elected as the leader. If it does not happen within
+    /// [`Config::transfer_leader_timeout()`], this node resumes serving as the leader, if it still
+    /// is, and a [`TransferLeaderTimeout`] error is returned.
+    ///
+    /// [`ForwardToLeader`]: crate::error::ForwardToLeader
+    /// [`Config::transfer_leader_timeout()`]: crate::Config::transfer_leader_timeout
+    /// [`TransferLeaderTimeout`]: crate::error::TransferLeaderTimeout
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn transfer_leadership(&self, to: C::NodeId) -> Result<(), RaftError<C, TransferLeaderError<C>>> {
+        let (tx, rx) = C::AsyncRuntime::oneshot();
+        self.inner.call_core(RaftMsg::TransferLeader { to, tx }, rx).await
+    }
+
+    /// Stop serving as the leader, e.g., to drain this node before maintenance.
+    ///
+    /// This node stops accepting new proposals at once. If `transfer` is true, the leadership is
+    /// handed over to the voter that has replicated the most logs, just like
+    /// [`Raft::transfer_leadership()`]. If `transfer` is false, or there is no other voter, or the
+    /// transfer does not finish within [`Config::transfer_leader_timeout()`], this node reverts to
+    /// a follower at once and does not start an election for a leader lease plus an election
+    /// timeout, leaving the other voters the chance to elect a new leader.
+    ///
+    /// It returns `Ok(())` once this node is no longer the leader. It fails with a
+    /// [`ForwardToLeader`] error if this node is not the leader, or another leadership transfer is
+    /// in progress.
+    ///
+    /// This node is still a voter. To remove it from the voters, use
+    /// [`Raft::demote_self_to_learner()`].
+    ///
+    /// [`ForwardToLeader`]: crate::error::ForwardToLeader
+    /// [`Config::transfer_leader_timeout()`]: crate::Config::transfer_leader_timeout
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn step_down(&self, transfer: bool) -> Result<(), RaftError<C, TransferLeaderError<C>>> {
+        let (tx, rx) = C::AsyncRuntime::oneshot();
+        self.inner.call_core(RaftMsg::StepDown { transfer, tx }, rx).await
+    }
+
+    /// Get the replication progress of every voter and learner other than this leader.
+    ///
+    /// It tells how far every node is behind this leader, in entries and in bytes, the snapshot
+    /// being sent to it, and the estimated time for it to catch up, e.g., to decide if a learner
+    /// can be promoted with [`Raft::change_membership()`], or if a voter can be removed. See
+    /// [`ReplicationStatus`].
+    ///
+    /// It fails with a [`ForwardToLeader`] error if this node is not the leader.
+    ///
+    /// [`ForwardToLeader`]: crate::error::ForwardToLeader
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn replication_status(
+        &self,
+    ) -> Result<BTreeMap<C::NodeId, ReplicationStatus<C>>, RaftError<C, ForwardToLeader<C>>> {
+        let (tx, rx) = C::AsyncRuntime::oneshot();
+        self.inner.call_core(RaftMsg::GetReplicationStatus { tx }, rx).await
+    }
+
+    /// Get the latest committed membership configs applied to the state machine of this node,
+    /// the oldest first, to audit when and how the cluster topology changed.
+    ///
+    /// At most [`Config::membership_history_size`] configs are kept. The history is kept in
+    /// memory and starts with the committed config loaded on startup. When a snapshot is
+    /// installed, only the last config in it is recorded. A joint config is recorded as well as
+    /// the uniform config following it.
+    ///
+    /// It can be called on any node and does not communicate with `RaftCore`.
+    pub fn membership_history(&self) -> Vec<MembershipRecord<C>> {
+        self.inner.membership_history.records()
+    }
+
+    /// Handle a [`TransferLeaderRequest`] sent by the leader.
+    ///
+    /// If the vote of the leader is still the vote of this node, this node grants the vote of
+    /// `rpc.to` even if the leader lease has not yet expired. If this node is `rpc.to` and it has
+    /// all the logs of the leader, it starts an election at once.
+    ///
+    /// See [`Raft::transfer_leadership()`].
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn handle_transfer_leader(
+        &self,
+        rpc: TransferLeaderRequest<C>,
+    ) -> Result<(), RaftError<C, Unauthenticated>> {
+        tracing::info!(rpc = display(&rpc), "Raft::handle_transfer_leader()");
+
+        self.inner.auth.verify(AuthRpc::TransferLeader(&rpc), &rpc.context).map_err(RaftError::APIError)?;
+
+        let (tx, rx) = C::AsyncRuntime::oneshot();
+        self.inner
+            .call_core(RaftMsg::HandleTransferLeader { rpc, tx }, rx)
+            .await
+            .map_err(RaftError::with_api_error)
+    }
+
     /// Receive an `InstallSnapshotRequest`.
     ///
     /// These RPCs are sent by the cluster leader in order to bring a new node or a slow node
@@ -442,9 +784,19 @@ where C: RaftTypeConfig
     {
         tracing::debug!(req = display(&req), "Raft::install_snapshot()");
 
+        let rpc = AuthRpc::InstallSnapshot {
+            vote: &req.vote,
+            meta: &req.meta,
+        };
+        self.inner.auth.verify(rpc, &req.context).map_err(|e| RaftError::APIError(e.into()))?;
+
         let req_vote = req.vote;
+        let context = req.context.clone();
         let my_vote = self.with_raft_state(|state| *state.vote_ref()).await?;
-        let resp = InstallSnapshotResponse { vote: my_vote };
+        let mut resp = InstallSnapshotResponse {
+            vote: my_vote,
+            compression: None,
+        };
 
         // Check vote.
         // It is not mandatory because it is just a read operation
@@ -458,6 +810,16 @@ where C: RaftTypeConfig
             }
         }
 
+        // A chunk compressed by an unknown algorithm is ignored, and the response without
+        // `compression` lets the leader send it again uncompressed.
+        if let Some(compression) = &req.compression {
+            if !can_decode_payload(compression) {
+                tracing::warn!("snapshot chunk compression is not supported: {}", compression);
+                return Ok(resp);
+            }
+        }
+        let compression = req.compression.clone();
+
         let finished_snapshot = {
             use crate::network::snapshot_transport::Chunked;
             use crate::network::snapshot_transport::SnapshotTransport;
@@ -467,9 +829,13 @@ where C: RaftTypeConfig
         };
 
         if let Some(snapshot) = finished_snapshot {
-            let resp = self.install_full_snapshot(req_vote, snapshot).await?;
-            return Ok(resp.into());
+            let resp = self.do_install_full_snapshot(req_vote, snapshot, context).await?;
+            let mut resp = InstallSnapshotResponse::from(resp);
+            resp.compression = compression;
+            return Ok(resp);
         }
+
+        resp.compression = compression;
         Ok(resp)
     }
 
@@ -492,7 +858,16 @@ where C: RaftTypeConfig
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn is_leader(&self) -> Result<(), RaftError<C, CheckIsLeaderError<C>>> {
         let (tx, rx) = C::AsyncRuntime::oneshot();
-        let _ = self.inner.call_core(RaftMsg::CheckIsLeaderRequest { tx }, rx).await?;
+        let _ = self
+            .inner
+            .call_core(
+                RaftMsg::CheckIsLeaderRequest {
+                    read_policy: ReadPolicy::ReadIndex,
+                    tx,
+                },
+                rx,
+            )
+            .await?;
         Ok(())
     }
 
@@ -521,7 +896,26 @@ where C: RaftTypeConfig
     /// Read more about how it works: [Read Operation](crate::docs::protocol::read)
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn ensure_linearizable(&self) -> Result<Option<LogId<C::NodeId>>, RaftError<C, CheckIsLeaderError<C>>> {
-        let (read_log_id, applied) = self.get_read_log_id().await?;
+        self.ensure_linearizable_with(ReadPolicy::ReadIndex).await
+    }
+
+    /// The same as [`ensure_linearizable()`](Raft::ensure_linearizable), but confirms the
+    /// leadership as specified by `read_policy`.
+    ///
+    /// With [`ReadPolicy::LeaseRead`], a leader whose lease has not expired returns at once
+    /// without sending heartbeats.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// my_raft.ensure_linearizable_with(ReadPolicy::LeaseRead).await?;
+    /// // Proceed with the state machine read
+    /// ```
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn ensure_linearizable_with(
+        &self,
+        read_policy: ReadPolicy,
+    ) -> Result<Option<LogId<C::NodeId>>, RaftError<C, CheckIsLeaderError<C>>> {
+        let (read_log_id, applied) = self.get_read_log_id_with(read_policy).await?;
 
         if read_log_id.index() > applied.index() {
             self.wait(None)
@@ -537,6 +931,105 @@ where C: RaftTypeConfig
         Ok(read_log_id)
     }
 
+    /// Ensures a read operation performed on this node following this method is linearizable,
+    /// even if this node is not the leader.
+    ///
+    /// If this node is not the leader, it gets the read log id from the leader with
+    /// [`RaftNetworkV2::get_read_log_id()`], which confirms its leadership as specified by
+    /// `read_policy`, and then waits until the local state machine applies up to it. Thus reads
+    /// can be served by followers and learners, to offload read traffic from the leader.
+    /// If the remote node is not the leader either, it follows the [`ForwardToLeader`] returned
+    /// by the remote, for at most [`MAX_FORWARD_HOPS`](Self::MAX_FORWARD_HOPS) times.
+    ///
+    /// If this node is the leader, it is the same as
+    /// [`ensure_linearizable_with()`](Raft::ensure_linearizable_with).
+    ///
+    /// Returns:
+    /// - `Ok(read_log_id)` when the local state machine has applied up to `read_log_id`.
+    /// - `Err(RaftError<CheckIsLeaderError>)` returned by the leader, or the last
+    ///   [`ForwardToLeader`] if the leader can not be reached.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// my_raft.ensure_linearizable_on_follower(ReadPolicy::ReadIndex).await?;
+    /// // Proceed with the local state machine read
+    /// ```
+    ///
+    /// [`ForwardToLeader`]: crate::error::ForwardToLeader
+    /// [`RaftNetworkV2::get_read_log_id()`]: crate::network::v2::RaftNetworkV2::get_read_log_id
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn ensure_linearizable_on_follower(
+        &self,
+        read_policy: ReadPolicy,
+    ) -> Result<Option<LogId<C::NodeId>>, RaftError<C, CheckIsLeaderError<C>>> {
+        let res = self.ensure_linearizable_with(read_policy).await;
+
+        let mut forward = match res {
+            Err(RaftError::APIError(CheckIsLeaderError::ForwardToLeader(f))) => f,
+            _ => return res,
+        };
+
+        for _ in 0..Self::MAX_FORWARD_HOPS {
+            let (Some(target), Some(node)) = (forward.leader_id, forward.leader_node.clone()) else {
+                break;
+            };
+
+            if target == self.inner.id {
+                break;
+            }
+
+            let (tx, rx) = C::AsyncRuntime::oneshot();
+            let msg = RaftMsg::ForwardGetReadLogId {
+                target,
+                node,
+                read_policy,
+                tx,
+            };
+            self.inner.send_msg(msg).await?;
+            let res = self.inner.recv_msg(rx).await?;
+
+            match res {
+                Ok(read_log_id) => {
+                    self.wait(None)
+                        .applied_index_at_least(read_log_id.index(), "ensure_linearizable_on_follower")
+                        .await
+                        .map_err(|e| match e {
+                            WaitError::Timeout(_, _) => {
+                                unreachable!("did not specify timeout")
+                            }
+                            WaitError::ShuttingDown => Fatal::Stopped,
+                        })?;
+                    return Ok(read_log_id);
+                }
+                Err(RPCError::RemoteError(RemoteError {
+                    source: RaftError::APIError(CheckIsLeaderError::ForwardToLeader(f)),
+                    ..
+                })) => {
+                    tracing::debug!(
+                        target = display(target),
+                        "read log id request is forwarded again: {}",
+                        f
+                    );
+                    forward = f;
+                }
+                Err(RPCError::RemoteError(RemoteError {
+                    source: RaftError::APIError(e),
+                    ..
+                })) => return Err(RaftError::APIError(e)),
+                Err(e) => {
+                    tracing::warn!(
+                        target = display(target),
+                        error = display(&e),
+                        "failed to get read log id from the leader"
+                    );
+                    break;
+                }
+            }
+        }
+
+        Err(RaftError::APIError(CheckIsLeaderError::ForwardToLeader(forward)))
+    }
+
     /// Ensures this node is leader and returns the log id up to which the state machine should
     /// apply to ensure a read can be linearizable across the cluster.
     ///
@@ -569,9 +1062,20 @@ where C: RaftTypeConfig
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_read_log_id(
         &self,
+    ) -> Result<(Option<LogId<C::NodeId>>, Option<LogId<C::NodeId>>), RaftError<C, CheckIsLeaderError<C>>> {
+        self.get_read_log_id_with(ReadPolicy::ReadIndex).await
+    }
+
+    /// The same as [`get_read_log_id()`](Raft::get_read_log_id), but confirms the leadership as
+    /// specified by `read_policy`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_read_log_id_with(
+        &self,
+        read_policy: ReadPolicy,
     ) -> Result<(Option<LogId<C::NodeId>>, Option<LogId<C::NodeId>>), RaftError<C, CheckIsLeaderError<C>>> {
         let (tx, rx) = C::AsyncRuntime::oneshot();
-        let (read_log_id, applied) = self.inner.call_core(RaftMsg::CheckIsLeaderRequest { tx }, rx).await?;
+        let (read_log_id, applied) =
+            self.inner.call_core(RaftMsg::CheckIsLeaderRequest { read_policy, tx }, rx).await?;
         Ok((read_log_id, applied))
     }
 
@@ -618,13 +1122,147 @@ where C: RaftTypeConfig
     /// It is same as [`Raft::client_write`] but does not wait for the response.
     #[tracing::instrument(level = "debug", skip(self, app_data))]
     pub async fn client_write_ff(&self, app_data: C::D) -> Result<ResponderReceiverOf<C>, Fatal<C>> {
+        self.submit_client_write(app_data, None).await
+    }
+
+    /// Build the log entry from `app_data` and send it to `RaftCore`.
+    ///
+    /// The entry is built on the caller side, so that if the application data can not be
+    /// converted, only this request fails with [`ClientWriteError::InvalidAppData`], which is sent
+    /// to the returned receiver.
+    async fn submit_client_write(
+        &self,
+        app_data: C::D,
+        deadline: Option<InstantOf<C>>,
+    ) -> Result<ResponderReceiverOf<C>, Fatal<C>> {
         let (app_data, tx, rx) = ResponderOf::<C>::from_app_data(app_data);
 
-        self.inner.send_msg(RaftMsg::ClientWriteRequest { app_data, tx }).await?;
+        let entry = match C::Entry::from_app_data(app_data) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!("failed to build log entry from app data: {}", e);
+                tx.send(Err(ClientWriteError::InvalidAppData(InvalidAppData::new(e))));
+                return Ok(rx);
+            }
+        };
+
+        self.inner.send_msg(RaftMsg::ClientWriteRequest { entry, tx, deadline }).await?;
 
         Ok(rx)
     }
 
+    /// Submit a mutating client request like [`Raft::client_write`], that fails with a
+    /// [`ClientWriteTimeout`] error if the entry is not committed by `deadline`.
+    ///
+    /// When the leader can not commit the entry in time, e.g., because it lost its quorum, the
+    /// response channel of the entry is dropped at the deadline, instead of the caller waiting
+    /// for a leader change that may take arbitrarily long. The entry may still be committed
+    /// later, so a retry has to be idempotent, as with [`Raft::client_write`].
+    ///
+    /// The caller does not wait beyond `deadline` either, even if `RaftCore` is too busy to
+    /// receive the request or to respond, in which case the log id in the error is `None`.
+    #[tracing::instrument(level = "debug", skip(self, app_data))]
+    pub async fn client_write_with_deadline<E>(
+        &self,
+        app_data: C::D,
+        deadline: InstantOf<C>,
+    ) -> Result<ClientWriteResponse<C>, RaftError<C, ClientWriteError<C>>>
+    where
+        ResponderReceiverOf<C>: Future<Output = Result<ClientWriteResult<C>, E>> + OptionalSend,
+        E: Error + OptionalSend,
+    {
+        let timeout = || RaftError::APIError(ClientWriteError::Timeout(ClientWriteTimeout { log_id: None }));
+
+        let submit = self.submit_client_write(app_data, Some(deadline));
+        let rx = C::AsyncRuntime::timeout_at(deadline, submit).await.map_err(|_| timeout())??;
+
+        let recv = self.inner.recv_msg(rx);
+        let res: ClientWriteResult<C> = C::AsyncRuntime::timeout_at(deadline, recv).await.map_err(|_| timeout())??;
+
+        let client_write_response = res.map_err(|e| RaftError::APIError(e))?;
+        Ok(client_write_response)
+    }
+
+    /// Submit a mutating client request like [`Raft::client_write`], and if this node is not the
+    /// leader, forward it to the leader.
+    ///
+    /// When the local write returns a [`ForwardToLeader`] with a known leader, the request is sent
+    /// to the leader with [`RaftNetworkV2::forward_client_write()`]. If the remote node is not the
+    /// leader either, it follows the [`ForwardToLeader`] returned by the remote, for at most
+    /// [`MAX_FORWARD_HOPS`](Self::MAX_FORWARD_HOPS) times.
+    ///
+    /// An error returned by the leader is returned as is. If the leader can not be reached, the
+    /// last [`ForwardToLeader`] is returned, so that the application can redirect the client.
+    ///
+    /// [`ForwardToLeader`]: crate::error::ForwardToLeader
+    /// [`RaftNetworkV2::forward_client_write()`]: crate::network::v2::RaftNetworkV2::forward_client_write
+    #[tracing::instrument(level = "debug", skip(self, app_data))]
+    pub async fn client_write_forwarded<E>(
+        &self,
+        app_data: C::D,
+    ) -> Result<ClientWriteResponse<C>, RaftError<C, ClientWriteError<C>>>
+    where
+        C::D: Clone,
+        ResponderReceiverOf<C>: Future<Output = Result<ClientWriteResult<C>, E>>,
+        E: Error + OptionalSend,
+    {
+        let res = self.client_write(app_data.clone()).await;
+
+        let mut forward = match res {
+            Err(RaftError::APIError(ClientWriteError::ForwardToLeader(f))) => f,
+            _ => return res,
+        };
+
+        for _ in 0..Self::MAX_FORWARD_HOPS {
+            let (Some(target), Some(node)) = (forward.leader_id, forward.leader_node.clone()) else {
+                break;
+            };
+
+            if target == self.inner.id {
+                break;
+            }
+
+            let (tx, rx) = C::AsyncRuntime::oneshot();
+            let msg = RaftMsg::ForwardClientWrite {
+                target,
+                node,
+                app_data: app_data.clone(),
+                tx,
+            };
+            self.inner.send_msg(msg).await?;
+            let res = self.inner.recv_msg(rx).await?;
+
+            match res {
+                Ok(resp) => return Ok(resp),
+                Err(RPCError::RemoteError(RemoteError {
+                    source: RaftError::APIError(ClientWriteError::ForwardToLeader(f)),
+                    ..
+                })) => {
+                    tracing::debug!(
+                        target = display(target),
+                        "forwarded client write is forwarded again: {}",
+                        f
+                    );
+                    forward = f;
+                }
+                Err(RPCError::RemoteError(RemoteError {
+                    source: RaftError::APIError(e),
+                    ..
+                })) => return Err(RaftError::APIError(e)),
+                Err(e) => {
+                    tracing::warn!(
+                        target = display(target),
+                        error = display(&e),
+                        "failed to forward client write"
+                    );
+                    break;
+                }
+            }
+        }
+
+        Err(RaftError::APIError(ClientWriteError::ForwardToLeader(forward)))
+    }
+
     /// Return `true` if this node is already initialized and can not be initialized again with
     /// [`Raft::initialize`]
     pub async fn is_initialized(&self) -> Result<bool, Fatal<C>> {
@@ -669,6 +1307,45 @@ where C: RaftTypeConfig
             .await
     }
 
+    /// Replace the membership of this node with `members` locally, without consensus.
+    ///
+    /// **This is unsafe**: it is meant for recovering a cluster that has permanently lost a quorum
+    /// of its voters, from the surviving nodes, like `etcd --force-new-cluster` does. Committed
+    /// logs that are not on this node are lost, and if the lost voters come back, the cluster may
+    /// have two leaders.
+    ///
+    /// The new membership is appended as a log entry with a new term of this node, then this node
+    /// starts an election with it, so that it becomes the leader once a quorum of `members`
+    /// grants it, and the new membership is committed. To recover a cluster:
+    ///
+    /// - Stop the lost nodes permanently;
+    /// - Call this method on the surviving node with the most logs, with `members` containing only
+    ///   the surviving nodes;
+    /// - Add new nodes with [`Raft::add_learner()`] and [`Raft::change_membership()`].
+    ///
+    /// It fails with [`NotInMembers`](`crate::error::NotInMembers`) if this node is not a voter
+    /// in `members`. It is available only with the feature `unsafe-force-reset-membership`.
+    #[cfg(feature = "unsafe-force-reset-membership")]
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn force_reset_membership<T>(
+        &self,
+        members: T,
+    ) -> Result<(), RaftError<C, crate::error::NotInMembers<C>>>
+    where
+        T: IntoNodes<C::NodeId, C::Node> + Debug,
+    {
+        let (tx, rx) = C::AsyncRuntime::oneshot();
+        self.inner
+            .call_core(
+                RaftMsg::ForceResetMembership {
+                    members: members.into_nodes(),
+                    tx,
+                },
+                rx,
+            )
+            .await
+    }
+
     /// Returns Ok() with the latest known matched log id if it should quit waiting: leader change,
     /// node removed, or replication becomes upto date.
     ///
@@ -775,7 +1452,16 @@ where C: RaftTypeConfig
     pub fn external_request<F>(&self, req: F)
     where F: FnOnce(&RaftState<C>) + OptionalSend + 'static {
         let req: BoxCoreFn<C> = Box::new(req);
-        let _ignore_error = self.inner.tx_api.send(RaftMsg::ExternalCoreRequest { req });
+        let res = self.inner.tx_api.try_send(RaftMsg::ExternalCoreRequest { req });
+
+        // The API channel is full: deliver the request once there is room, without blocking the
+        // caller.
+        if let Err(TrySendError::Full(msg)) = res {
+            let tx_api = self.inner.tx_api.clone();
+            self.inner.spawn(async move {
+                let _ignore_error = tx_api.send(msg).await;
+            });
+        }
     }
 
     /// Get a handle to the metrics channel.
@@ -793,6 +1479,74 @@ where C: RaftTypeConfig
         self.inner.rx_server_metrics.clone()
     }
 
+    /// Subscribe to the log ids of the entries applied to the state machine.
+    ///
+    /// Every subscriber receives every log id applied after it subscribed, in log order. A
+    /// subscriber that falls more than [`Config::applied_channel_size`] entries behind skips the
+    /// oldest ones and receives a [`RecvError::Lagged`] instead. Entries that are installed with a
+    /// snapshot are not reported.
+    ///
+    /// Unlike [`Raft::metrics`], which only keeps the last value, no applied entry is coalesced:
+    ///
+    /// ```ignore
+    /// let mut rx = raft.subscribe_applied();
+    /// while let Ok(log_id) = rx.recv().await {
+    ///     println!("applied: {}", log_id);
+    /// }
+    /// ```
+    ///
+    /// [`RecvError::Lagged`]: crate::async_runtime::broadcast::RecvError::Lagged
+    pub fn subscribe_applied(&self) -> BroadcastReceiverOf<C, LogId<C::NodeId>> {
+        self.inner.tx_applied.subscribe()
+    }
+
+    /// Subscribe to the changes of the server state of this node, e.g., to start or stop
+    /// leader-only background jobs without polling [`Raft::metrics`].
+    ///
+    /// Every subscriber receives a [`RoleChange`] when this node becomes the leader, including
+    /// when it is elected again in a later term, and when it changes to any other server state.
+    /// The changes are observed each time `RaftCore` reports metrics, thus a state held only
+    /// briefly, such as a candidate that is elected at once, may not be reported. A subscriber
+    /// that falls far behind skips the oldest changes and receives a [`RecvError::Lagged`]
+    /// instead.
+    ///
+    /// ```ignore
+    /// let mut rx = raft.subscribe_role_changes();
+    /// while let Ok(change) = rx.recv().await {
+    ///     if change.is_leader() {
+    ///         start_leader_jobs(change.term());
+    ///     } else {
+    ///         stop_leader_jobs();
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`RecvError::Lagged`]: crate::async_runtime::broadcast::RecvError::Lagged
+    pub fn subscribe_role_changes(&self) -> BroadcastReceiverOf<C, RoleChange<C>> {
+        self.inner.tx_role_change.subscribe()
+    }
+
+    /// Subscribe to the members this node removes as the leader, because they have been
+    /// unreachable for [`Config::auto_remove_dead_member_duration`].
+    ///
+    /// Every subscriber receives a [`DeadMember`] before the membership change removing it is
+    /// proposed, e.g., to release the resources of a dead machine. Nothing is sent if automatic
+    /// removal is disabled. A subscriber that falls far behind skips the oldest ones and receives
+    /// a [`RecvError::Lagged`] instead.
+    ///
+    /// ```ignore
+    /// let mut rx = raft.subscribe_dead_members();
+    /// while let Ok(dead) = rx.recv().await {
+    ///     decommission(dead.id);
+    /// }
+    /// ```
+    ///
+    /// [`Config::auto_remove_dead_member_duration`]: `crate::Config::auto_remove_dead_member_duration`
+    /// [`RecvError::Lagged`]: crate::async_runtime::broadcast::RecvError::Lagged
+    pub fn subscribe_dead_members(&self) -> BroadcastReceiverOf<C, DeadMember<C>> {
+        self.inner.tx_dead_member.subscribe()
+    }
+
     /// Get a handle to wait for the metrics to satisfy some condition.
     ///
     /// If `timeout` is `None`, then it will wait forever(10 years).
@@ -826,14 +1580,14 @@ where C: RaftTypeConfig
 
     /// Shutdown this Raft node.
     ///
-    /// It sends a shutdown signal and waits until `RaftCore` returns.
+    /// It cancels all of the tasks of this Raft node and waits until `RaftCore` and the tick loop
+    /// return. `RaftCore` waits for its replication streams to quit before returning.
     pub async fn shutdown(&self) -> Result<(), JoinErrorOf<C>> {
-        if let Some(tx) = self.inner.tx_shutdown.lock().await.take() {
-            // A failure to send means the RaftCore is already shutdown. Continue to check the task
-            // return value.
-            let send_res = tx.send(());
-            tracing::info!("sending shutdown signal to RaftCore, sending res: {:?}", send_res);
-        }
+        // Cancelling the root token stops all of the tasks: `RaftCore`, tick, replication streams
+        // and snapshot transmissions. Cancelling is idempotent, thus it is safe to call it twice.
+        self.inner.cancel.cancel();
+        tracing::info!("cancelled all tasks of this Raft node");
+
         self.inner.join_core_task().await;
         if let Some(join_handle) = self.inner.tick_handle.shutdown() {
             let _ = join_handle.await;
@@ -844,3 +1598,40 @@ where C: RaftTypeConfig
         Ok(())
     }
 }
+
+#[cfg(not(feature = "singlethreaded"))]
+impl<C> Raft<C>
+where C: RaftTypeConfig<AsyncRuntime = crate::TokioRuntime>
+{
+    /// Create and spawn a new Raft task onto the `tokio` runtime of `handle`.
+    ///
+    /// It is the same as [`Raft::new`], except that all of the tasks of this Raft node, such as
+    /// `RaftCore`, replication streams and the state machine worker, run on the given runtime
+    /// instead of the runtime of the caller. This allows an application running multiple `tokio`
+    /// runtimes to dedicate one of them to Raft.
+    ///
+    /// The returned `Raft` handle can be used from any runtime.
+    pub async fn new_on<LS, N, SM>(
+        handle: &tokio::runtime::Handle,
+        id: C::NodeId,
+        config: Arc<Config>,
+        network: N,
+        log_store: LS,
+        state_machine: SM,
+    ) -> Result<Self, Fatal<C>>
+    where
+        N: RaftNetworkFactory<C>,
+        LS: RaftLogStorage<C>,
+        SM: RaftStateMachine<C>,
+    {
+        // Tasks spawned by a task inherit its runtime, thus building Raft in a task on `handle` is
+        // enough for all the tasks spawned by `RaftCore`.
+        let fu = Self::new(id, config, network, log_store, state_machine).in_current_span();
+        let mut raft = handle.spawn(fu).await.map_err(|_| Fatal::Panicked)??;
+
+        let inner = Arc::get_mut(&mut raft.inner).expect("a newly created Raft is not shared");
+        inner.runtime_handle = Some(handle.clone());
+
+        Ok(raft)
+    }
+}
@@ -3,24 +3,33 @@ use std::fmt::Debug;
 use std::future::Future;
 use std::sync::Arc;
 
-use tokio::sync::mpsc;
 use tokio::sync::watch;
 use tokio::sync::Mutex;
 use tracing::Level;
 
+use crate::async_runtime::CancellationToken;
+use crate::async_runtime::MpscSender;
 use crate::config::RuntimeConfig;
+use crate::core::log_cache::LogCache;
 use crate::core::raft_msg::external_command::ExternalCommand;
 use crate::core::raft_msg::RaftMsg;
+use crate::core::MembershipHistory;
 use crate::core::TickHandle;
 use crate::error::Fatal;
 use crate::error::RaftError;
+use crate::metrics::DeadMember;
 use crate::metrics::RaftDataMetrics;
 use crate::metrics::RaftServerMetrics;
+use crate::metrics::RoleChange;
+use crate::network::RpcAuth;
 use crate::raft::core_state::CoreState;
+use crate::type_config::alias::BroadcastSenderOf;
+use crate::type_config::alias::CancellationTokenOf;
+use crate::type_config::alias::MpscSenderOf;
 use crate::type_config::alias::OneshotReceiverOf;
-use crate::type_config::alias::OneshotSenderOf;
 use crate::AsyncRuntime;
 use crate::Config;
+use crate::LogId;
 use crate::OptionalSend;
 use crate::RaftMetrics;
 use crate::RaftTypeConfig;
@@ -34,26 +43,66 @@ where C: RaftTypeConfig
     pub(in crate::raft) config: Arc<Config>,
     pub(in crate::raft) runtime_config: Arc<RuntimeConfig>,
     pub(in crate::raft) tick_handle: TickHandle<C>,
-    pub(in crate::raft) tx_api: mpsc::UnboundedSender<RaftMsg<C>>,
+    pub(in crate::raft) tx_api: MpscSenderOf<C, RaftMsg<C>>,
     pub(in crate::raft) rx_metrics: watch::Receiver<RaftMetrics<C>>,
     pub(in crate::raft) rx_data_metrics: watch::Receiver<RaftDataMetrics<C>>,
     pub(in crate::raft) rx_server_metrics: watch::Receiver<RaftServerMetrics<C>>,
+    pub(in crate::raft) tx_applied: BroadcastSenderOf<C, LogId<C::NodeId>>,
+    pub(in crate::raft) tx_role_change: BroadcastSenderOf<C, RoleChange<C>>,
+    pub(in crate::raft) tx_dead_member: BroadcastSenderOf<C, DeadMember<C>>,
 
-    // TODO(xp): it does not need to be a async mutex.
-    #[allow(clippy::type_complexity)]
-    pub(in crate::raft) tx_shutdown: Mutex<Option<OneshotSenderOf<C, ()>>>,
+    /// Verifies the RPCs received.
+    pub(in crate::raft) auth: RpcAuth<C>,
+
+    /// The cache of the latest log entries, shared with `RaftCore`.
+    pub(in crate::raft) log_cache: LogCache<C>,
+
+    /// The latest committed membership configs, shared with the state machine worker.
+    pub(in crate::raft) membership_history: MembershipHistory<C>,
+
+    /// The root of the cancellation tokens of all of the tasks of this Raft node.
+    ///
+    /// It is cancelled by [`Raft::shutdown`](`crate::Raft::shutdown`) or when the last `Raft`
+    /// handle is dropped.
+    pub(in crate::raft) cancel: CancellationTokenOf<C>,
     pub(in crate::raft) core_state: Mutex<CoreState<C>>,
 
     /// The ongoing snapshot transmission.
     pub(in crate::raft) snapshot: Mutex<Option<crate::network::snapshot_transport::Streaming<C>>>,
+
+    /// The `tokio` runtime the tasks are spawned onto, if created with
+    /// [`Raft::new_on`](`crate::Raft::new_on`).
+    #[cfg(not(feature = "singlethreaded"))]
+    pub(in crate::raft) runtime_handle: Option<tokio::runtime::Handle>,
+}
+
+impl<C> Drop for RaftInner<C>
+where C: RaftTypeConfig
+{
+    /// Stop all of the tasks when there is no `Raft` handle left.
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
 }
 
 impl<C> RaftInner<C>
 where C: RaftTypeConfig
 {
+    /// Spawn a detached task onto the runtime `RaftCore` runs on.
+    pub(in crate::raft) fn spawn<F>(&self, future: F)
+    where F: Future<Output = ()> + OptionalSend + 'static {
+        #[cfg(not(feature = "singlethreaded"))]
+        if let Some(handle) = &self.runtime_handle {
+            let _handle = handle.spawn(future);
+            return;
+        }
+
+        let _handle = C::AsyncRuntime::spawn(future);
+    }
+
     /// Send a RaftMsg to RaftCore
     pub(crate) async fn send_msg(&self, mes: RaftMsg<C>) -> Result<(), Fatal<C>> {
-        let send_res = self.tx_api.send(mes);
+        let send_res = self.tx_api.send(mes).await;
 
         if let Err(e) = send_res {
             let fatal = self.get_core_stopped_error("sending RaftMsg to RaftCore", Some(e.0.to_string())).await;
@@ -74,6 +123,14 @@ where C: RaftTypeConfig
         match recv_res {
             Ok(x) => Ok(x),
             Err(_) => {
+                // RaftCore drops the pending responses when it restarts on a storage error, while it
+                // keeps running. Do not wait for it to quit.
+                let running_state = self.rx_metrics.borrow().running_state.clone();
+                if let Err(fatal) = running_state {
+                    tracing::error!(error = debug(&fatal), "error when {}", func_name!());
+                    return Err(fatal);
+                }
+
                 let fatal = self.get_core_stopped_error("receiving rx from RaftCore", None::<&'static str>).await;
                 tracing::error!(error = debug(&fatal), "error when {}", func_name!());
                 Err(fatal)
@@ -121,7 +178,7 @@ where C: RaftTypeConfig
         cmd: ExternalCommand<C>,
         cmd_desc: impl fmt::Display + Default,
     ) -> Result<(), Fatal<C>> {
-        let send_res = self.tx_api.send(RaftMsg::ExternalCommand { cmd });
+        let send_res = self.tx_api.send(RaftMsg::ExternalCommand { cmd }).await;
 
         if send_res.is_err() {
             let fatal = self.get_core_stopped_error("sending external command to RaftCore", Some(cmd_desc)).await;
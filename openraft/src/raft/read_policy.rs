@@ -0,0 +1,26 @@
+/// Defines how a leader confirms its leadership before serving a linearizable read.
+///
+/// See: [Read Operation](crate::docs::protocol::read)
+#[derive(Debug, Clone, Copy, Default)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum ReadPolicy {
+    /// Confirm the leadership by sending heartbeats to a quorum and waiting for the responses.
+    #[default]
+    ReadIndex,
+
+    /// Confirm the leadership with the leader lease, without a heartbeat round.
+    ///
+    /// The lease lasts for `leader_lease - lease_read_clock_drift` since the last heartbeat
+    /// acknowledged by a quorum was sent, during which no other node can be elected. See
+    /// [`Config::leader_lease`](`crate::Config::leader_lease`).
+    /// It falls back to [`ReadPolicy::ReadIndex`] if the lease has expired, or if
+    /// [`Config::reject_vote_within_lease`](`crate::Config::reject_vote_within_lease`) is
+    /// disabled.
+    ///
+    /// It relies on the clocks of the nodes not drifting apart more than
+    /// [`Config::lease_read_clock_drift`](`crate::Config::lease_read_clock_drift`).
+    /// An election triggered with [`Trigger::elect()`](`crate::raft::trigger::Trigger::elect`)
+    /// ignores the lease and must not be used together with lease reads.
+    LeaseRead,
+}
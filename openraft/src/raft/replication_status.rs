@@ -0,0 +1,82 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::display_ext::DisplayOptionExt;
+use crate::LogId;
+use crate::RaftTypeConfig;
+
+/// The replication progress of a voter or a learner, as seen by the leader.
+///
+/// It is returned by [`Raft::replication_status()`](`crate::Raft::replication_status`), e.g., to
+/// decide if a learner has caught up and can be promoted, or if a voter can be removed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct ReplicationStatus<C: RaftTypeConfig> {
+    /// Whether the node is a voter, otherwise it is a learner.
+    pub voter: bool,
+
+    /// The last log id known to be replicated to the node.
+    pub matched: Option<LogId<C::NodeId>>,
+
+    /// The number of log entries the node is behind the last log of the leader.
+    pub lag_entries: u64,
+
+    /// The size in bytes of the log entries the node is behind.
+    ///
+    /// Only the entries appended since this node started are counted.
+    pub lag_bytes: u64,
+
+    /// The snapshot being sent to the node. It is `None` if no snapshot is being sent.
+    pub snapshot: Option<SnapshotTransferStatus<C>>,
+
+    /// The estimated time for the node to catch up with the leader, at the rate its lag has
+    /// decreased since the previous call to `Raft::replication_status()`.
+    ///
+    /// It is zero if the node has caught up, and `None` if its lag has not decreased.
+    pub catch_up_time: Option<Duration>,
+}
+
+impl<C> fmt::Display for ReplicationStatus<C>
+where C: RaftTypeConfig
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{voter:{}, matched:{}, lag_entries:{}, lag_bytes:{}",
+            self.voter,
+            self.matched.display(),
+            self.lag_entries,
+            self.lag_bytes
+        )?;
+
+        if let Some(snapshot) = &self.snapshot {
+            write!(f, ", snapshot:{}", snapshot)?;
+        }
+
+        write!(f, ", catch_up_time:{:?}}}", self.catch_up_time)
+    }
+}
+
+/// The progress of sending a snapshot to a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct SnapshotTransferStatus<C: RaftTypeConfig> {
+    /// The last log id included in the snapshot.
+    pub last_log_id: Option<LogId<C::NodeId>>,
+
+    /// The number of bytes of the snapshot acknowledged by the node so far.
+    pub acked_bytes: u64,
+}
+
+impl<C> fmt::Display for SnapshotTransferStatus<C>
+where C: RaftTypeConfig
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{last_log_id:{}, acked_bytes:{}}}",
+            self.last_log_id.display(),
+            self.acked_bytes
+        )
+    }
+}
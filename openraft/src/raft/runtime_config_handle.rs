@@ -1,9 +1,13 @@
 //! RuntimeConfigHandle is an interface to change Raft runtime config.
 
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 
+use crate::core::raft_msg::external_command::ExternalCommand;
+use crate::error::Fatal;
 use crate::raft::RaftInner;
 use crate::RaftTypeConfig;
+use crate::SnapshotPolicy;
 
 /// RuntimeConfigHandle is an interface to update runtime config.
 ///
@@ -25,6 +29,12 @@ where C: RaftTypeConfig
     /// Enable or disable raft internal ticker.
     ///
     /// Disabling tick will disable election and heartbeat.
+    ///
+    /// It has no effect if [`Config::enable_tick`] is `false`, in which case no internal ticker is
+    /// spawned and the node is driven by [`Raft::tick()`].
+    ///
+    /// [`Config::enable_tick`]: crate::Config::enable_tick
+    /// [`Raft::tick()`]: crate::Raft::tick
     pub fn tick(&self, enabled: bool) {
         self.raft_inner.tick_handle.enable(enabled);
     }
@@ -42,4 +52,83 @@ where C: RaftTypeConfig
     pub fn elect(&self, enabled: bool) {
         self.raft_inner.runtime_config.enable_elect.store(enabled, Ordering::Relaxed);
     }
+
+    /// Set the timeout for vote RPCs sent after this call.
+    pub fn vote_timeout(&self, timeout: Duration) {
+        self.raft_inner.runtime_config.vote_timeout.store(timeout.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Set the timeout for append-entries RPCs sent after this call.
+    pub fn append_entries_timeout(&self, timeout: Duration) {
+        self.raft_inner
+            .runtime_config
+            .append_entries_timeout
+            .store(timeout.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Set the timeout for install-snapshot RPCs sent after this call.
+    ///
+    /// A snapshot transmission that is already running keeps using the previous timeout.
+    pub fn install_snapshot_timeout(&self, timeout: Duration) {
+        self.raft_inner
+            .runtime_config
+            .install_snapshot_timeout
+            .store(timeout.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Replace the policy to decide when to build a snapshot, and return at once.
+    ///
+    /// A snapshot is triggered at once if the logs committed so far satisfy the new policy.
+    ///
+    /// Returns error when RaftCore has [`Fatal`] error, e.g. shut down or having storage error.
+    pub async fn snapshot_policy(&self, policy: SnapshotPolicy) -> Result<(), Fatal<C>> {
+        self.raft_inner
+            .send_external_command(ExternalCommand::SetSnapshotPolicy { policy }, "set_snapshot_policy")
+            .await
+    }
+
+    /// Enable or disable appending a blank entry when this node becomes the leader.
+    ///
+    /// It takes effect from the next election. See [`Config::leader_blank_entry`].
+    ///
+    /// Returns error when RaftCore has [`Fatal`] error, e.g. shut down or having storage error.
+    ///
+    /// [`Config::leader_blank_entry`]: crate::Config::leader_blank_entry
+    pub async fn leader_blank_entry(&self, enabled: bool) -> Result<(), Fatal<C>> {
+        self.raft_inner
+            .send_external_command(
+                ExternalCommand::SetLeaderBlankEntry { enabled },
+                "set_leader_blank_entry",
+            )
+            .await
+    }
+
+    /// Enable or disable rejecting vote requests within the leader lease.
+    ///
+    /// See [`Config::reject_vote_within_lease`].
+    ///
+    /// Returns error when RaftCore has [`Fatal`] error, e.g. shut down or having storage error.
+    ///
+    /// [`Config::reject_vote_within_lease`]: crate::Config::reject_vote_within_lease
+    pub async fn reject_vote_within_lease(&self, enabled: bool) -> Result<(), Fatal<C>> {
+        self.raft_inner
+            .send_external_command(
+                ExternalCommand::SetRejectVoteWithinLease { enabled },
+                "set_reject_vote_within_lease",
+            )
+            .await
+    }
+
+    /// Enable or disable granting at most one vote in a term.
+    ///
+    /// See [`Config::one_vote_per_term`].
+    ///
+    /// Returns error when RaftCore has [`Fatal`] error, e.g. shut down or having storage error.
+    ///
+    /// [`Config::one_vote_per_term`]: crate::Config::one_vote_per_term
+    pub async fn one_vote_per_term(&self, enabled: bool) -> Result<(), Fatal<C>> {
+        self.raft_inner
+            .send_external_command(ExternalCommand::SetOneVotePerTerm { enabled }, "set_one_vote_per_term")
+            .await
+    }
 }
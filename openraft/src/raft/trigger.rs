@@ -1,8 +1,12 @@
 //! Trigger an action to RaftCore by external caller.
 
 use crate::core::raft_msg::external_command::ExternalCommand;
+use crate::core::raft_msg::RaftMsg;
 use crate::error::Fatal;
 use crate::raft::RaftInner;
+use crate::storage::SnapshotBuildProgress;
+use crate::type_config::alias::AsyncRuntimeOf;
+use crate::AsyncRuntime;
 use crate::RaftTypeConfig;
 
 /// Trigger is an interface to trigger an action to RaftCore by external caller.
@@ -54,16 +58,26 @@ where C: RaftTypeConfig
 
     /// Trigger to build a snapshot at once and return at once.
     ///
+    /// It returns a [`SnapshotBuildProgress`] handle of the snapshot being built, to watch the
+    /// progress or to cancel it. If a snapshot is already being built, the handle of it is
+    /// returned. The progress is also reported in [`RaftMetrics::snapshot_building`].
+    ///
     /// Returns error when RaftCore has [`Fatal`] error, e.g. shut down or having storage error.
-    pub async fn snapshot(&self) -> Result<(), Fatal<C>> {
-        self.raft_inner.send_external_command(ExternalCommand::Snapshot, "trigger_snapshot").await
+    ///
+    /// [`RaftMetrics::snapshot_building`]: crate::RaftMetrics::snapshot_building
+    pub async fn snapshot(&self) -> Result<SnapshotBuildProgress<C>, Fatal<C>> {
+        let (tx, rx) = AsyncRuntimeOf::<C>::oneshot();
+        let cmd = ExternalCommand::Snapshot { tx };
+        let res = self.raft_inner.call_core(RaftMsg::ExternalCommand { cmd }, rx).await;
+        // Safe unwrap: `RaftError<Infallible>` must be a Fatal.
+        res.map_err(|e| e.into_fatal().unwrap())
     }
 
     /// Initiate the log purge up to and including the given `upto` log index.
     ///
     /// Logs that are not included in a snapshot will **NOT** be purged.
     /// In such scenario it will delete as many log as possible.
-    /// The [`max_in_snapshot_log_to_keep`] config is not taken into account
+    /// The [`log_retention_policy`] config is not taken into account
     /// when purging logs.
     ///
     /// It returns error only when RaftCore has [`Fatal`] error, e.g. shut down or having storage
@@ -73,7 +87,7 @@ where C: RaftTypeConfig
     /// is a leader and a replication task has been replicating the logs to a follower, the logs
     /// can't be purged until the replication task is finished.
     ///
-    /// [`max_in_snapshot_log_to_keep`]: `crate::Config::max_in_snapshot_log_to_keep`
+    /// [`log_retention_policy`]: `crate::Config::log_retention_policy`
     pub async fn purge_log(&self, upto: u64) -> Result<(), Fatal<C>> {
         self.raft_inner.send_external_command(ExternalCommand::PurgeLog { upto }, "purge_log").await
     }
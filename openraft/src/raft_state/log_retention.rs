@@ -0,0 +1,217 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::type_config::alias::InstantOf;
+use crate::RaftTypeConfig;
+
+/// Tracks the size and the append time of the logs, for
+/// [`LogRetentionPolicy`](`crate::LogRetentionPolicy`) to decide which logs to purge.
+///
+/// A mark is added for every batch of appended logs, and is removed when the logs are truncated or
+/// purged. The logs loaded from storage at startup have no mark: they are counted as zero bytes,
+/// and are purged by time along with the first batch appended after them.
+#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq)]
+pub(crate) struct LogRetention<C>
+where C: RaftTypeConfig
+{
+    marks: VecDeque<Mark<C>>,
+
+    /// The accumulated bytes of the logs before the first mark.
+    base_bytes: u64,
+}
+
+#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq)]
+struct Mark<C>
+where C: RaftTypeConfig
+{
+    /// The index of the last log in the batch.
+    index: u64,
+
+    /// The accumulated bytes of the logs up to `index`, inclusive.
+    bytes: u64,
+
+    appended_at: InstantOf<C>,
+}
+
+impl<C> Default for LogRetention<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            marks: VecDeque::new(),
+            base_bytes: 0,
+        }
+    }
+}
+
+impl<C> LogRetention<C>
+where C: RaftTypeConfig
+{
+    fn last_bytes(&self) -> u64 {
+        self.marks.back().map(|m| m.bytes).unwrap_or(self.base_bytes)
+    }
+
+    /// Record a batch of logs ending at `last_index` with a total size of `bytes`.
+    pub(crate) fn append(&mut self, last_index: u64, bytes: u64, now: InstantOf<C>) {
+        let bytes = self.last_bytes() + bytes;
+        self.marks.push_back(Mark {
+            index: last_index,
+            bytes,
+            appended_at: now,
+        });
+    }
+
+    /// Remove the marks of the logs since `since`, inclusive.
+    ///
+    /// A batch that is partially truncated is removed too, and its remaining logs are no longer
+    /// counted.
+    pub(crate) fn truncate(&mut self, since: u64) {
+        while self.marks.back().map(|m| m.index >= since).unwrap_or(false) {
+            self.marks.pop_back();
+        }
+    }
+
+    /// Remove the marks of the logs up to `upto`, inclusive.
+    pub(crate) fn purge(&mut self, upto: u64) {
+        while self.marks.front().map(|m| m.index <= upto).unwrap_or(false) {
+            let m = self.marks.pop_front().unwrap();
+            self.base_bytes = m.bytes;
+        }
+    }
+
+    /// The total size of the tracked logs.
+    pub(crate) fn bytes(&self) -> u64 {
+        self.last_bytes() - self.base_bytes
+    }
+
+    /// The accumulated bytes of the batches that end before `end`.
+    fn bytes_before(&self, end: u64) -> u64 {
+        let n = self.marks.partition_point(|m| m.index < end);
+        if n == 0 {
+            self.base_bytes
+        } else {
+            self.marks[n - 1].bytes
+        }
+    }
+
+    /// The total size of the logs in range `[start, end)`.
+    ///
+    /// A batch is counted only when it ends before `end`.
+    pub(crate) fn bytes_between(&self, start: u64, end: u64) -> u64 {
+        self.bytes_before(end).saturating_sub(self.bytes_before(start))
+    }
+
+    /// When the batch including the log at `index` is appended.
+    ///
+    /// Returns `None` if the log is not tracked.
+    pub(crate) fn appended_at(&self, index: u64) -> Option<InstantOf<C>> {
+        let i = self.marks.partition_point(|m| m.index < index);
+        self.marks.get(i).map(|m| m.appended_at)
+    }
+
+    /// Returns the smallest index since which the logs before `end` are at most `max_bytes`.
+    ///
+    /// Returns `None` if the logs since the first tracked one are within `max_bytes`.
+    pub(crate) fn purge_end_by_bytes(&self, end: u64, max_bytes: u64) -> Option<u64> {
+        let end_bytes = self.bytes_before(end);
+
+        if end_bytes - self.base_bytes <= max_bytes {
+            return None;
+        }
+
+        let i = self.marks.partition_point(|m| end_bytes.saturating_sub(m.bytes) > max_bytes);
+        Some(self.marks[i].index + 1)
+    }
+
+    /// Returns the index since which the logs are appended within `keep` before `now`.
+    ///
+    /// Returns `None` if every tracked log is appended within `keep`.
+    pub(crate) fn purge_end_by_time(&self, now: InstantOf<C>, keep: Duration) -> Option<u64> {
+        let n = self.marks.partition_point(|m| m.appended_at + keep <= now);
+        if n == 0 {
+            return None;
+        }
+        Some(self.marks[n - 1].index + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::engine::testing::UTConfig;
+    use crate::raft_state::log_retention::LogRetention;
+    use crate::TokioInstant;
+
+    #[test]
+    fn test_log_retention_bytes() {
+        let now = TokioInstant::now();
+
+        let mut r = LogRetention::<UTConfig>::default();
+        r.append(2, 10, now);
+        r.append(5, 30, now);
+        r.append(6, 5, now);
+        assert_eq!(45, r.bytes());
+
+        assert_eq!(None, r.purge_end_by_bytes(7, 45));
+        assert_eq!(Some(3), r.purge_end_by_bytes(7, 44));
+        assert_eq!(Some(3), r.purge_end_by_bytes(7, 35));
+        assert_eq!(Some(6), r.purge_end_by_bytes(7, 34));
+        assert_eq!(Some(7), r.purge_end_by_bytes(7, 0));
+
+        // Logs after `end` are not counted
+        assert_eq!(None, r.purge_end_by_bytes(6, 40));
+        assert_eq!(Some(3), r.purge_end_by_bytes(6, 39));
+
+        r.purge(2);
+        assert_eq!(35, r.bytes());
+        assert_eq!(None, r.purge_end_by_bytes(7, 35));
+        assert_eq!(Some(6), r.purge_end_by_bytes(7, 34));
+
+        r.truncate(6);
+        assert_eq!(30, r.bytes());
+        r.append(6, 1, now);
+        assert_eq!(31, r.bytes());
+    }
+
+    #[test]
+    fn test_log_retention_bytes_between() {
+        let now = TokioInstant::now();
+        let ms = Duration::from_millis;
+
+        let mut r = LogRetention::<UTConfig>::default();
+        r.append(2, 10, now);
+        r.append(5, 30, now + ms(10));
+        r.append(6, 5, now + ms(20));
+
+        assert_eq!(45, r.bytes_between(0, 7));
+        assert_eq!(35, r.bytes_between(3, 7));
+        assert_eq!(30, r.bytes_between(3, 6));
+        assert_eq!(0, r.bytes_between(3, 5), "a partial batch is not counted");
+        assert_eq!(0, r.bytes_between(7, 8));
+
+        assert_eq!(Some(now), r.appended_at(0));
+        assert_eq!(Some(now + ms(10)), r.appended_at(3));
+        assert_eq!(Some(now + ms(10)), r.appended_at(5));
+        assert_eq!(Some(now + ms(20)), r.appended_at(6));
+        assert_eq!(None, r.appended_at(7));
+    }
+
+    #[test]
+    fn test_log_retention_time() {
+        let now = TokioInstant::now();
+        let ms = Duration::from_millis;
+
+        let mut r = LogRetention::<UTConfig>::default();
+        r.append(2, 10, now);
+        r.append(5, 10, now + ms(10));
+        r.append(6, 10, now + ms(20));
+
+        assert_eq!(None, r.purge_end_by_time(now + ms(20), ms(21)));
+        assert_eq!(Some(3), r.purge_end_by_time(now + ms(20), ms(20)));
+        assert_eq!(Some(6), r.purge_end_by_time(now + ms(20), ms(10)));
+        assert_eq!(Some(7), r.purge_end_by_time(now + ms(20), ms(0)));
+    }
+}
@@ -1,21 +1,26 @@
 use std::error::Error;
 use std::ops::Deref;
+use std::time::Duration;
 
 use validit::Validate;
 
 use crate::engine::LogIdList;
+use crate::entry::RaftEntry;
 use crate::error::ForwardToLeader;
 use crate::log_id::RaftLogId;
 use crate::utime::UTime;
+use crate::AsyncRuntime;
 use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::RaftTypeConfig;
 use crate::ServerState;
 use crate::SnapshotMeta;
+use crate::SnapshotTriggerContext;
 use crate::Vote;
 
 mod accepted;
 pub(crate) mod io_state;
+pub(crate) mod log_retention;
 mod log_state_reader;
 mod membership_state;
 pub(crate) mod snapshot_streaming;
@@ -34,6 +39,7 @@ mod tests {
 }
 
 pub(crate) use accepted::Accepted;
+pub(crate) use log_retention::LogRetention;
 pub(crate) use log_state_reader::LogStateReader;
 pub use membership_state::MembershipState;
 pub(crate) use vote_state_reader::VoteStateReader;
@@ -87,6 +93,9 @@ where C: RaftTypeConfig
     /// If a log is in use by a replication task, the purge is postponed and is stored in this
     /// field.
     pub(crate) purge_upto: Option<LogId<C::NodeId>>,
+
+    /// The size and append time of the logs, used by the log retention policy.
+    pub(crate) log_retention: LogRetention<C>,
 }
 
 impl<C> Default for RaftState<C>
@@ -105,6 +114,7 @@ where C: RaftTypeConfig
             io_state: IOState::default(),
             snapshot_streaming: None,
             purge_upto: None,
+            log_retention: LogRetention::default(),
         }
     }
 }
@@ -251,6 +261,36 @@ where C: RaftTypeConfig
         self.log_ids.extend(new_log_id)
     }
 
+    /// Record the size and the append time of the appended entries for the log retention policy.
+    pub(crate) fn track_appended_entries(&mut self, entries: &[C::Entry]) {
+        let Some(last) = entries.last() else {
+            return;
+        };
+
+        let bytes = entries.iter().map(|e| e.size_hint() as u64).sum();
+        self.log_retention.append(last.get_log_id().index, bytes, C::AsyncRuntime::now());
+    }
+
+    /// Collect the state of the committed logs that are not yet included in the snapshot, for the
+    /// [`SnapshotPolicy`](`crate::SnapshotPolicy`) to decide whether to build a snapshot.
+    pub(crate) fn snapshot_trigger_context(&self, now: InstantOf<C>) -> SnapshotTriggerContext {
+        let start = self.snapshot_last_log_id().next_index();
+        let end = self.committed().next_index();
+
+        if end <= start {
+            return SnapshotTriggerContext::default();
+        }
+
+        SnapshotTriggerContext {
+            logs_since_last: end - start,
+            bytes_since_last: self.log_retention.bytes_between(start, end),
+            oldest_log_age: self
+                .log_retention
+                .appended_at(start)
+                .map(|t| if now > t { now - t } else { Duration::ZERO }),
+        }
+    }
+
     /// Update field `committed` if the input is greater.
     /// If updated, it returns the previous value in a `Some()`.
     #[tracing::instrument(level = "debug", skip_all)]
@@ -312,6 +352,7 @@ where C: RaftTypeConfig
     pub(crate) fn purge_log(&mut self, upto: &LogIdOf<C>) {
         self.purged_next = upto.index + 1;
         self.log_ids.purge(upto);
+        self.log_retention.purge(upto.index);
     }
 
     /// Determine the current server state by state.
@@ -0,0 +1,127 @@
+//! Adapts the number of entries per AppendEntries RPC to the observed latency and errors.
+
+use std::time::Duration;
+
+/// Controls the number of log entries sent in an AppendEntries RPC to a target, similar to the
+/// congestion window of TCP.
+///
+/// The batch size starts small and doubles after every full batch acknowledged within the target
+/// latency, until it reaches the slow-start threshold, above which it grows by 1/8 per batch. A
+/// batch acknowledged slower than the target latency shrinks the size by 1/4, and a failed RPC,
+/// such as a timeout, halves it and lowers the threshold to it. The size is always in
+/// `[1, max]`.
+#[derive(Clone, Debug)]
+pub(crate) struct AdaptiveBatch {
+    /// The number of entries to send in the next RPC.
+    size: u64,
+
+    /// Above this size the batch grows linearly instead of exponentially.
+    threshold: u64,
+
+    /// The upper limit of the batch size, i.e., `max_payload_entries`.
+    max: u64,
+
+    /// The expected round-trip time of an RPC.
+    target_latency: Duration,
+}
+
+impl AdaptiveBatch {
+    const INITIAL_SIZE: u64 = 16;
+
+    pub(crate) fn new(max: u64, target_latency: Duration) -> Self {
+        let max = std::cmp::max(max, 1);
+        Self {
+            size: std::cmp::min(Self::INITIAL_SIZE, max),
+            threshold: max,
+            max,
+            target_latency,
+        }
+    }
+
+    /// The number of entries to send in the next RPC.
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Update the batch size with an RPC of `entries` entries acknowledged in `latency`.
+    ///
+    /// A batch smaller than the current size, i.e., there were no more entries to send, does not
+    /// grow the size, because it does not tell whether the target can handle a larger one.
+    pub(crate) fn on_success(&mut self, entries: u64, latency: Duration) {
+        if latency > self.target_latency {
+            self.size = std::cmp::max(self.size - self.size / 4, 1);
+            return;
+        }
+
+        if entries < self.size {
+            return;
+        }
+
+        let grown = if self.size < self.threshold {
+            self.size * 2
+        } else {
+            self.size + std::cmp::max(self.size / 8, 1)
+        };
+        self.size = std::cmp::min(grown, self.max);
+    }
+
+    /// Shrink the batch size after a failed RPC.
+    pub(crate) fn on_error(&mut self) {
+        self.size = std::cmp::max(self.size / 2, 1);
+        self.threshold = self.size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::AdaptiveBatch;
+
+    #[test]
+    fn test_adaptive_batch() {
+        let fast = Duration::from_millis(5);
+        let slow = Duration::from_millis(20);
+
+        let mut b = AdaptiveBatch::new(300, Duration::from_millis(10));
+        assert_eq!(16, b.size());
+
+        // Slow start
+        b.on_success(16, fast);
+        assert_eq!(32, b.size());
+
+        // A partial batch does not grow
+        b.on_success(10, fast);
+        assert_eq!(32, b.size());
+
+        b.on_success(32, fast);
+        b.on_success(64, fast);
+        b.on_success(128, fast);
+        assert_eq!(256, b.size());
+
+        // Capped by max
+        b.on_success(256, fast);
+        assert_eq!(300, b.size());
+
+        // High latency shrinks by 1/4
+        b.on_success(300, slow);
+        assert_eq!(225, b.size());
+
+        // Error halves and ends slow start
+        b.on_error();
+        assert_eq!(112, b.size());
+
+        b.on_success(112, fast);
+        assert_eq!(126, b.size());
+
+        // Never less than 1
+        for _ in 0..10 {
+            b.on_error();
+        }
+        assert_eq!(1, b.size());
+        b.on_success(1, slow);
+        assert_eq!(1, b.size());
+        b.on_success(1, fast);
+        assert_eq!(2, b.size());
+    }
+}
@@ -0,0 +1,245 @@
+//! A long-lived AppendEntries stream to a replication target.
+
+use std::sync::Arc;
+
+use anyerror::AnyError;
+use tokio::select;
+use tokio::sync::Mutex;
+
+use crate::async_runtime::CancellationToken;
+use crate::async_runtime::Mpsc;
+use crate::async_runtime::MpscReceiver;
+use crate::async_runtime::MpscSender;
+use crate::error::NetworkError;
+use crate::error::RPCError;
+use crate::network::v2::RaftNetworkV2;
+use crate::network::RPCOption;
+use crate::raft::AppendEntriesRequest;
+use crate::raft::AppendEntriesResponse;
+use crate::type_config::alias::CancellationTokenOf;
+use crate::type_config::alias::JoinHandleOf;
+use crate::type_config::alias::MpscOf;
+use crate::type_config::alias::MpscReceiverOf;
+use crate::type_config::alias::MpscSenderOf;
+use crate::AsyncRuntime;
+use crate::RaftTypeConfig;
+
+/// The sending and receiving ends of a [`RaftNetworkV2::append_entries_stream()`], which is run
+/// by a task that holds the network until the stream is closed.
+pub(crate) struct EntriesStream<C>
+where C: RaftTypeConfig
+{
+    tx_request: MpscSenderOf<C, AppendEntriesRequest<C>>,
+    rx_response: MpscReceiverOf<C, Result<AppendEntriesResponse<C>, RPCError<C>>>,
+
+    /// Cancelled to stop the task running the stream.
+    cancel: CancellationTokenOf<C>,
+    join_handle: JoinHandleOf<C, ()>,
+}
+
+impl<C> EntriesStream<C>
+where C: RaftTypeConfig
+{
+    /// Open a stream on `network` in a new task.
+    ///
+    /// Up to `window` requests can be sent before receiving their responses. `cancel` stops the
+    /// task, it should be a child of the replication task's token.
+    pub(crate) fn spawn<Net>(
+        network: Arc<Mutex<Net>>,
+        option: RPCOption,
+        window: usize,
+        cancel: CancellationTokenOf<C>,
+    ) -> Self
+    where
+        Net: RaftNetworkV2<C>,
+    {
+        // Both channels hold a full window, so that sending a request never waits for the
+        // responses to be received.
+        let (tx_request, rx_request) = MpscOf::<C>::channel(window);
+        let (tx_response, rx_response) = MpscOf::<C>::channel(window);
+
+        let c = cancel.clone();
+        let join_handle = C::AsyncRuntime::spawn(async move {
+            let mut net = network.lock().await;
+
+            select! {
+                _ = c.cancelled() => {}
+                _ = net.append_entries_stream(rx_request, tx_response, option) => {}
+            }
+        });
+
+        Self {
+            tx_request,
+            rx_response,
+            cancel,
+            join_handle,
+        }
+    }
+
+    /// Send a request on the stream and wait for its response.
+    ///
+    /// If the stream is closed by the network, it returns an [`RPCError::Network`].
+    pub(crate) async fn call(&mut self, rpc: AppendEntriesRequest<C>) -> Result<AppendEntriesResponse<C>, RPCError<C>> {
+        self.send(rpc).await?;
+        self.recv().await
+    }
+
+    /// Send a request on the stream without waiting for its response.
+    ///
+    /// The responses are received with [`Self::recv()`] in the order of the requests.
+    pub(crate) async fn send(&mut self, rpc: AppendEntriesRequest<C>) -> Result<(), RPCError<C>> {
+        self.tx_request.send(rpc).await.map_err(|_| Self::closed_error())
+    }
+
+    /// Receive the response to the earliest request whose response is not yet received.
+    pub(crate) async fn recv(&mut self) -> Result<AppendEntriesResponse<C>, RPCError<C>> {
+        match self.rx_response.recv().await {
+            Some(res) => res,
+            None => Err(Self::closed_error()),
+        }
+    }
+
+    /// Stop the task and wait for it to release the network.
+    pub(crate) async fn close(self) {
+        self.cancel.cancel();
+        drop(self.tx_request);
+
+        let _ = self.join_handle.await;
+    }
+
+    fn closed_error() -> RPCError<C> {
+        let e = AnyError::error("AppendEntries stream is closed");
+        RPCError::Network(NetworkError::new(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use anyerror::AnyError;
+    use tokio::sync::Mutex;
+
+    use crate::engine::testing::UTConfig;
+    use crate::error::RPCError;
+    use crate::error::ReplicationClosed;
+    use crate::error::StreamingError;
+    use crate::error::Unreachable;
+    use crate::network::v2::RaftNetworkV2;
+    use crate::network::RPCOption;
+    use crate::raft::AppendEntriesRequest;
+    use crate::raft::AppendEntriesResponse;
+    use crate::raft::SnapshotResponse;
+    use crate::raft::VoteRequest;
+    use crate::raft::VoteResponse;
+    use crate::replication::entries_stream::EntriesStream;
+    use crate::type_config::alias::CancellationTokenOf;
+    use crate::Snapshot;
+    use crate::Vote;
+
+    /// Fails the AppendEntries with a vote of term 0.
+    struct Net {
+        calls: u64,
+    }
+
+    impl RaftNetworkV2<UTConfig> for Net {
+        async fn append_entries(
+            &mut self,
+            rpc: AppendEntriesRequest<UTConfig>,
+            _option: RPCOption,
+        ) -> Result<AppendEntriesResponse<UTConfig>, RPCError<UTConfig>> {
+            self.calls += 1;
+            if rpc.vote.leader_id().get_term() == 0 {
+                return Err(RPCError::Unreachable(Unreachable::new(&AnyError::error("foo"))));
+            }
+            Ok(AppendEntriesResponse::Success)
+        }
+
+        async fn vote(
+            &mut self,
+            _rpc: VoteRequest<UTConfig>,
+            _option: RPCOption,
+        ) -> Result<VoteResponse<UTConfig>, RPCError<UTConfig>> {
+            unimplemented!()
+        }
+
+        async fn full_snapshot(
+            &mut self,
+            _vote: Vote<u64>,
+            _snapshot: Snapshot<UTConfig>,
+            _cancel: impl Future<Output = ReplicationClosed> + Send + 'static,
+            _option: RPCOption,
+        ) -> Result<SnapshotResponse<UTConfig>, StreamingError<UTConfig>> {
+            unimplemented!()
+        }
+    }
+
+    fn req(term: u64) -> AppendEntriesRequest<UTConfig> {
+        AppendEntriesRequest {
+            vote: Vote::new(term, 1),
+            prev_log_id: None,
+            entries: vec![],
+            leader_commit: None,
+            context: Default::default(),
+        }
+    }
+
+    fn new_stream(net: &Arc<Mutex<Net>>) -> EntriesStream<UTConfig> {
+        let option = RPCOption::new(Duration::from_millis(100));
+        EntriesStream::spawn(net.clone(), option, 2, CancellationTokenOf::<UTConfig>::new())
+    }
+
+    #[tokio::test]
+    async fn test_entries_stream() -> anyhow::Result<()> {
+        let net = Arc::new(Mutex::new(Net { calls: 0 }));
+
+        let mut stream = new_stream(&net);
+        assert!(matches!(stream.call(req(1)).await, Ok(AppendEntriesResponse::Success)));
+        assert!(matches!(stream.call(req(1)).await, Ok(AppendEntriesResponse::Success)));
+
+        assert!(net.try_lock().is_err(), "the stream holds the network");
+
+        // The stream returns after an error.
+        assert!(matches!(stream.call(req(0)).await, Err(RPCError::Unreachable(_))));
+        assert!(matches!(stream.call(req(1)).await, Err(RPCError::Network(_))));
+
+        stream.close().await;
+        assert_eq!(3, net.try_lock()?.calls, "the network is released");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_entries_stream_pipeline() -> anyhow::Result<()> {
+        let net = Arc::new(Mutex::new(Net { calls: 0 }));
+
+        let mut stream = new_stream(&net);
+
+        // A full window is sent before any response is received.
+        stream.send(req(1)).await?;
+        stream.send(req(1)).await?;
+        assert!(matches!(stream.recv().await, Ok(AppendEntriesResponse::Success)));
+        assert!(matches!(stream.recv().await, Ok(AppendEntriesResponse::Success)));
+
+        stream.close().await;
+        assert_eq!(2, net.try_lock()?.calls);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_entries_stream_close() -> anyhow::Result<()> {
+        let net = Arc::new(Mutex::new(Net { calls: 0 }));
+
+        let mut stream = new_stream(&net);
+        assert!(matches!(stream.call(req(1)).await, Ok(AppendEntriesResponse::Success)));
+
+        // Close an idle stream that is waiting for the next request.
+        stream.close().await;
+        assert_eq!(1, net.try_lock()?.calls);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,423 @@
+//! Sends heartbeats to a replication target, decoupled from replicating data.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::select;
+use tokio::sync::mpsc;
+use tokio::sync::watch;
+use tracing_futures::Instrument;
+
+use crate::async_runtime::CancellationToken;
+use crate::config::RuntimeConfig;
+use crate::core::notify::Notify;
+use crate::display_ext::DisplayOptionExt;
+use crate::network::network_observer::RpcRecorder;
+use crate::network::v2::RaftNetworkV2;
+use crate::network::RPCErrorKind;
+use crate::network::RPCTypes;
+use crate::network::RpcAuth;
+use crate::network::RpcContext;
+use crate::raft::AppendEntriesRequest;
+use crate::raft::AppendEntriesResponse;
+use crate::replication::request_id::RequestId;
+use crate::replication::response::ReplicationResult;
+use crate::replication::ReplicationSessionId;
+use crate::replication::Response;
+use crate::type_config::alias::CancellationTokenOf;
+use crate::AsyncRuntime;
+use crate::Instant;
+use crate::LogId;
+use crate::RaftNetworkFactory;
+use crate::RaftTypeConfig;
+
+/// The key in the [`RpcContext`] of a heartbeat of the round-trip time of heartbeats in the
+/// cluster, in microseconds.
+///
+/// See [`Config::adaptive_election_timeout`](`crate::Config::adaptive_election_timeout`).
+pub(crate) const HEARTBEAT_RTT_KEY: &str = "openraft-heartbeat-rtt";
+
+/// A heartbeat to send, broadcast by `RaftCore` to every target.
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+pub(crate) struct HeartbeatEvent<C>
+where C: RaftTypeConfig
+{
+    /// The committed log id of the leader to send to the target.
+    pub(crate) committed: Option<LogId<C::NodeId>>,
+
+    /// The round-trip time of heartbeats in the cluster to send to the target, if the election
+    /// timeout is adaptive.
+    pub(crate) rtt: Option<Duration>,
+}
+
+impl<C> HeartbeatEvent<C>
+where C: RaftTypeConfig
+{
+    pub(crate) fn new(committed: Option<LogId<C::NodeId>>) -> Self {
+        Self { committed, rtt: None }
+    }
+
+    pub(crate) fn with_rtt(mut self, rtt: Option<Duration>) -> Self {
+        self.rtt = rtt;
+        self
+    }
+}
+
+/// Returns the round-trip time of heartbeats carried in the context of an AppendEntries request.
+pub(crate) fn heartbeat_rtt(context: &RpcContext) -> Option<Duration> {
+    let micros = context.get(HEARTBEAT_RTT_KEY)?.parse::<u64>().ok()?;
+    Some(Duration::from_micros(micros))
+}
+
+/// Sends heartbeats to one target, in a task of its own and with a network client of its own.
+///
+/// The replication stream sends AppendEntries one at a time, thus a heartbeat queued behind a
+/// large batch of entries, or on a slow follower, would be delayed until the batch is done, and
+/// the leader lease would expire. This worker sends the heartbeats independently, so that the
+/// lease is kept as long as the target is reachable.
+///
+/// A heartbeat is an AppendEntries without entries and with `prev_log_id=None`, which always
+/// matches on the target. Only the latest heartbeat is kept if the previous one is still being
+/// sent.
+pub(crate) struct HeartbeatWorker<C, N>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+{
+    target: C::NodeId,
+
+    session_id: ReplicationSessionId<C::NodeId>,
+
+    /// The network client for heartbeats only.
+    network: N::Network,
+
+    recorder: RpcRecorder<C>,
+
+    auth: RpcAuth<C>,
+
+    runtime_config: Arc<RuntimeConfig>,
+
+    rx_heartbeat: watch::Receiver<Option<HeartbeatEvent<C>>>,
+
+    tx_raft_core: mpsc::UnboundedSender<Notify<C>>,
+}
+
+impl<C, N> HeartbeatWorker<C, N>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+{
+    /// Spawn a heartbeat worker for the target, returns the sender to trigger a heartbeat.
+    ///
+    /// The worker quits when `cancel` is cancelled or the sender is dropped.
+    pub(crate) fn spawn(
+        target: C::NodeId,
+        session_id: ReplicationSessionId<C::NodeId>,
+        network: N::Network,
+        recorder: RpcRecorder<C>,
+        auth: RpcAuth<C>,
+        runtime_config: Arc<RuntimeConfig>,
+        tx_raft_core: mpsc::UnboundedSender<Notify<C>>,
+        cancel: CancellationTokenOf<C>,
+        span: tracing::Span,
+    ) -> watch::Sender<Option<HeartbeatEvent<C>>> {
+        let (tx_heartbeat, rx_heartbeat) = watch::channel(None);
+
+        let worker = Self {
+            target,
+            session_id,
+            network,
+            recorder,
+            auth,
+            runtime_config,
+            rx_heartbeat,
+            tx_raft_core,
+        };
+
+        let _handle = C::AsyncRuntime::spawn_named(
+            &format!("heartbeat-{}", target),
+            async move {
+                select! {
+                    _ = cancel.cancelled() => {}
+                    _ = worker.main() => {}
+                }
+            }
+            .instrument(span),
+        );
+
+        tx_heartbeat
+    }
+
+    async fn main(mut self) {
+        loop {
+            if self.rx_heartbeat.changed().await.is_err() {
+                tracing::debug!("heartbeat sender is dropped, quit");
+                return;
+            }
+
+            let event = *self.rx_heartbeat.borrow_and_update();
+            if let Some(event) = event {
+                self.send_heartbeat(event).await;
+            }
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(target=display(self.target)))]
+    async fn send_heartbeat(&mut self, event: HeartbeatEvent<C>) {
+        let mut payload = AppendEntriesRequest {
+            vote: self.session_id.vote,
+            prev_log_id: None,
+            entries: vec![],
+            leader_commit: event.committed,
+            context: RpcContext::default(),
+        };
+        if let Some(rtt) = event.rtt {
+            payload.context.set(HEARTBEAT_RTT_KEY, rtt.as_micros());
+        }
+        N::inject_context(&mut payload.context);
+        self.auth.sign_append_entries(self.target, &mut payload);
+
+        let option = self.recorder.option(self.runtime_config.rpc_timeout(RPCTypes::AppendEntries));
+        let the_timeout = option.hard_ttl();
+
+        let sending_time = C::AsyncRuntime::now();
+
+        tracing::debug!(
+            committed = display(event.committed.display()),
+            now = debug(sending_time),
+            "send heartbeat"
+        );
+
+        let res = C::AsyncRuntime::timeout(the_timeout, self.network.append_entries(payload, option)).await;
+        let rtt = sending_time.elapsed();
+
+        let resp = match res {
+            Ok(Ok(resp)) => {
+                self.recorder.record(RPCTypes::AppendEntries, 0, rtt, None);
+                resp
+            }
+            Ok(Err(err)) => {
+                self.recorder.record(RPCTypes::AppendEntries, 0, rtt, Some(RPCErrorKind::from(&err)));
+                tracing::warn!(error = display(&err), "heartbeat to target={} failed", self.target);
+                return;
+            }
+            Err(_timeout) => {
+                self.recorder.record(RPCTypes::AppendEntries, 0, rtt, Some(RPCErrorKind::Timeout));
+                tracing::warn!("heartbeat to target={} timeout after {:?}", self.target, the_timeout);
+                return;
+            }
+        };
+
+        let response = match resp {
+            AppendEntriesResponse::HigherVote(higher) => Response::HigherVote {
+                target: self.target,
+                higher,
+                vote: self.session_id.vote,
+            },
+            // The target accepted the leader, whether the empty log range matches or not.
+            AppendEntriesResponse::Success
+            | AppendEntriesResponse::PartialSuccess(_)
+            | AppendEntriesResponse::Conflict => Response::Progress {
+                target: self.target,
+                request_id: RequestId::new_heartbeat(),
+                result: Ok(ReplicationResult::new(sending_time, Ok(None))),
+                session_id: self.session_id,
+            },
+        };
+
+        let _ = self.tx_raft_core.send(Notify::Network { response });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use tokio::sync::mpsc;
+
+    use crate::config::RuntimeConfig;
+    use crate::core::notify::Notify;
+    use crate::engine::testing::UTConfig;
+    use crate::error::RPCError;
+    use crate::error::ReplicationClosed;
+    use crate::error::StreamingError;
+    use crate::network::network_observer::RpcRecorder;
+    use crate::network::v2::RaftNetworkV2;
+    use crate::network::RPCOption;
+    use crate::network::RaftNetworkFactory;
+    use crate::network::RpcAuth;
+    use crate::raft::AppendEntriesRequest;
+    use crate::raft::AppendEntriesResponse;
+    use crate::raft::SnapshotResponse;
+    use crate::raft::VoteRequest;
+    use crate::raft::VoteResponse;
+    use crate::replication::heartbeat::heartbeat_rtt;
+    use crate::replication::heartbeat::HeartbeatEvent;
+    use crate::replication::heartbeat::HeartbeatWorker;
+    use crate::replication::request_id::RequestId;
+    use crate::replication::ReplicationSessionId;
+    use crate::replication::Response;
+    use crate::testing::log_id;
+    use crate::type_config::alias::CancellationTokenOf;
+    use crate::Config;
+    use crate::Snapshot;
+    use crate::Vote;
+
+    /// Records the received heartbeats, replies with `higher` if it is set, otherwise `Success`.
+    #[derive(Clone)]
+    struct Net {
+        received: Arc<Mutex<Vec<AppendEntriesRequest<UTConfig>>>>,
+        higher: Option<Vote<u64>>,
+    }
+
+    impl RaftNetworkFactory<UTConfig> for Net {
+        type Network = Net;
+
+        async fn new_client(&mut self, _target: u64, _node: &()) -> Self::Network {
+            self.clone()
+        }
+    }
+
+    impl RaftNetworkV2<UTConfig> for Net {
+        async fn append_entries(
+            &mut self,
+            rpc: AppendEntriesRequest<UTConfig>,
+            _option: RPCOption,
+        ) -> Result<AppendEntriesResponse<UTConfig>, RPCError<UTConfig>> {
+            self.received.lock().unwrap().push(rpc);
+            match self.higher {
+                Some(v) => Ok(AppendEntriesResponse::HigherVote(v)),
+                None => Ok(AppendEntriesResponse::Success),
+            }
+        }
+
+        async fn vote(
+            &mut self,
+            _rpc: VoteRequest<UTConfig>,
+            _option: RPCOption,
+        ) -> Result<VoteResponse<UTConfig>, RPCError<UTConfig>> {
+            unimplemented!()
+        }
+
+        async fn full_snapshot(
+            &mut self,
+            _vote: Vote<u64>,
+            _snapshot: Snapshot<UTConfig>,
+            _cancel: impl Future<Output = ReplicationClosed> + Send + 'static,
+            _option: RPCOption,
+        ) -> Result<SnapshotResponse<UTConfig>, StreamingError<UTConfig>> {
+            unimplemented!()
+        }
+    }
+
+    fn session_id() -> ReplicationSessionId<u64> {
+        ReplicationSessionId::new(Vote::new_committed(2, 1), Some(log_id(1, 1, 1)))
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_worker() -> anyhow::Result<()> {
+        let net = Net {
+            received: Default::default(),
+            higher: None,
+        };
+        let runtime_config = Arc::new(RuntimeConfig::new(&Config::default()));
+        let rtt = Arc::new(Mutex::new(BTreeMap::new()));
+        let (tx_notify, mut rx_notify) = mpsc::unbounded_channel();
+        let cancel = CancellationTokenOf::<UTConfig>::new();
+
+        let tx_heartbeat = HeartbeatWorker::<UTConfig, Net>::spawn(
+            2,
+            session_id(),
+            net.clone(),
+            RpcRecorder::new(2, None, rtt.clone()),
+            RpcAuth::new(None),
+            runtime_config,
+            tx_notify,
+            cancel.clone(),
+            tracing::Span::none(),
+        );
+
+        let sent_rtt = Some(Duration::from_micros(1_500));
+        tx_heartbeat.send(Some(HeartbeatEvent::new(Some(log_id(2, 1, 5))).with_rtt(sent_rtt)))?;
+
+        let notify = tokio::time::timeout(Duration::from_secs(1), rx_notify.recv()).await?.unwrap();
+        match notify {
+            Notify::Network {
+                response:
+                    Response::Progress {
+                        target,
+                        request_id,
+                        result,
+                        session_id: sid,
+                    },
+            } => {
+                assert_eq!(2, target);
+                assert_eq!(RequestId::HeartBeat, request_id);
+                assert_eq!(Ok(None), result.unwrap().result);
+                assert_eq!(session_id().to_string(), sid.to_string());
+            }
+            other => panic!("unexpected notify: {}", other),
+        }
+
+        {
+            let received = net.received.lock().unwrap();
+            assert_eq!(1, received.len());
+            assert_eq!(Vote::new_committed(2, 1), received[0].vote);
+            assert_eq!(None, received[0].prev_log_id);
+            assert!(received[0].entries.is_empty());
+            assert_eq!(Some(log_id(2, 1, 5)), received[0].leader_commit);
+            assert_eq!(sent_rtt, heartbeat_rtt(&received[0].context));
+        }
+
+        assert_eq!(1, rtt.lock().unwrap()[&2].samples, "RTT is sampled");
+
+        cancel.cancel();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_worker_higher_vote() -> anyhow::Result<()> {
+        let higher = Vote::new(3, 3);
+        let net = Net {
+            received: Default::default(),
+            higher: Some(higher),
+        };
+        let runtime_config = Arc::new(RuntimeConfig::new(&Config::default()));
+        let (tx_notify, mut rx_notify) = mpsc::unbounded_channel();
+        let cancel = CancellationTokenOf::<UTConfig>::new();
+
+        let tx_heartbeat = HeartbeatWorker::<UTConfig, Net>::spawn(
+            2,
+            session_id(),
+            net,
+            RpcRecorder::new(2, None, Default::default()),
+            RpcAuth::new(None),
+            runtime_config,
+            tx_notify,
+            cancel.clone(),
+            tracing::Span::none(),
+        );
+
+        tx_heartbeat.send(Some(HeartbeatEvent::new(None)))?;
+
+        let notify = tokio::time::timeout(Duration::from_secs(1), rx_notify.recv()).await?.unwrap();
+        assert!(
+            matches!(
+                notify,
+                Notify::Network {
+                    response: Response::HigherVote { target: 2, higher: h, .. }
+                } if h == higher
+            ),
+            "got: {}",
+            notify
+        );
+
+        cancel.cancel();
+        Ok(())
+    }
+}
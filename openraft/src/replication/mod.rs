@@ -1,12 +1,18 @@
 //! Replication stream.
 
+mod adaptive_batch;
 pub(crate) mod callbacks;
+mod entries_stream;
+pub(crate) mod heartbeat;
 pub(crate) mod hint;
+pub(crate) mod rate_limiter;
+mod read_ahead;
 mod replication_session_id;
 pub(crate) mod request;
 pub(crate) mod request_id;
 pub(crate) mod response;
 
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -20,14 +26,23 @@ use response::ReplicationResult;
 pub(crate) use response::Response;
 use tokio::select;
 use tokio::sync::mpsc;
-use tokio::sync::oneshot;
+use tokio::sync::watch;
 use tokio::sync::Mutex;
 use tracing_futures::Instrument;
 
+use self::adaptive_batch::AdaptiveBatch;
+use self::entries_stream::EntriesStream;
+use self::read_ahead::ReadAhead;
+use crate::async_runtime::CancellationToken;
+use crate::async_runtime::Semaphore;
 use crate::config::Config;
+use crate::config::RuntimeConfig;
+use crate::core::log_cache::LogCache;
 use crate::core::notify::Notify;
 use crate::core::sm::handle::SnapshotReader;
 use crate::display_ext::DisplayOptionExt;
+use crate::entry::RaftEntry;
+use crate::entry::RaftPayload;
 use crate::error::HigherVote;
 use crate::error::PayloadTooLarge;
 use crate::error::RPCError;
@@ -36,22 +51,37 @@ use crate::error::ReplicationError;
 use crate::error::Timeout;
 use crate::log_id::LogIdOptionExt;
 use crate::log_id_range::LogIdRange;
+use crate::metrics::BackoffState;
+use crate::metrics::BatchSizeMetrics;
+use crate::metrics::RttMetrics;
+use crate::network::network_observer::RpcRecorder;
 use crate::network::v2::RaftNetworkV2;
 use crate::network::Backoff;
+use crate::network::RPCErrorKind;
 use crate::network::RPCOption;
 use crate::network::RPCTypes;
+use crate::network::RpcAuth;
+use crate::network::RpcContext;
+use crate::network::SnapshotResume;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
 use crate::replication::callbacks::SnapshotCallback;
+use crate::replication::heartbeat::HeartbeatEvent;
+use crate::replication::heartbeat::HeartbeatWorker;
 use crate::replication::hint::ReplicationHint;
+use crate::replication::rate_limiter::min_rate_limit;
+use crate::replication::rate_limiter::RateLimiter;
 use crate::replication::request_id::RequestId;
 use crate::storage::RaftLogReader;
 use crate::storage::RaftLogStorage;
 use crate::storage::Snapshot;
+use crate::storage::SnapshotMeta;
 use crate::type_config::alias::AsyncRuntimeOf;
+use crate::type_config::alias::CancellationTokenOf;
 use crate::type_config::alias::InstantOf;
 use crate::type_config::alias::JoinHandleOf;
 use crate::type_config::alias::LogIdOf;
+use crate::type_config::alias::SemaphoreOf;
 use crate::AsyncRuntime;
 use crate::Instant;
 use crate::LogId;
@@ -71,13 +101,29 @@ where C: RaftTypeConfig
 
     /// The channel used for communicating with the replication task.
     pub(crate) tx_repl: mpsc::UnboundedSender<Replicate<C>>,
+
+    /// Cancelled to stop the replication task and its snapshot transmission.
+    pub(crate) cancel: CancellationTokenOf<C>,
+
+    /// The progress of the snapshot transmission to the target.
+    ///
+    /// It outlives the replication task, so that a new replication stream to the same target
+    /// resumes an interrupted snapshot transmission.
+    pub(crate) snapshot_resume: SnapshotResume,
+
+    /// Triggers the [`HeartbeatWorker`] of the target to send a heartbeat.
+    ///
+    /// It is `None` if the node is driven by `Raft::tick()`, in which case no worker is spawned
+    /// and heartbeats are sent by the replication task with [`Replicate::Heartbeat`].
+    pub(crate) tx_heartbeat: Option<watch::Sender<Option<HeartbeatEvent<C>>>>,
 }
 
 /// A task responsible for sending replication events to a target follower in the Raft cluster.
 ///
-/// NOTE: we do not stack replication requests to targets because this could result in
-/// out-of-order delivery. We always buffer until we receive a success response, then send the
-/// next payload from the buffer.
+/// NOTE: by default we do not stack replication requests to targets: we always buffer until we
+/// receive a success response, then send the next payload from the buffer. If
+/// [`Config::max_inflight_append_entries`] is greater than 1, several AppendEntries RPCs are sent
+/// in order on the entries stream, which delivers them in order.
 pub(crate) struct ReplicationCore<C, N, LS>
 where
     C: RaftTypeConfig,
@@ -87,6 +133,11 @@ where
     /// The ID of the target Raft node which replication events are to be sent to.
     target: C::NodeId,
 
+    /// Whether the target is a witness, which receives no application data.
+    ///
+    /// See [`Membership::is_witness()`](`crate::Membership::is_witness`).
+    witness: bool,
+
     /// Identifies which session this replication belongs to.
     session_id: ReplicationSessionId<C::NodeId>,
 
@@ -105,7 +156,15 @@ where
     weak_tx_event: mpsc::WeakUnboundedSender<Replicate<C>>,
 
     /// The `RaftNetwork` interface for replicating logs and heartbeat.
-    network: N::Network,
+    ///
+    /// It is held by the task running [`Self::entries_stream`] while the stream is open.
+    network: Arc<Mutex<N::Network>>,
+
+    /// The long-lived stream that sends AppendEntries to the target.
+    ///
+    /// It is opened by the first AppendEntries, and closed on an error or a timeout, since the
+    /// response of an unfinished request may be received by the next one.
+    entries_stream: Option<EntriesStream<C>>,
 
     /// Another `RaftNetwork` specific for snapshot replication.
     ///
@@ -114,10 +173,28 @@ where
 
     /// The current snapshot replication state.
     ///
-    /// It includes a cancel signaler and the join handle of the snapshot replication task.
-    /// When ReplicationCore is dropped, this Sender is dropped, the snapshot task will be notified
-    /// to quit.
-    snapshot_state: Option<(oneshot::Sender<()>, JoinHandleOf<C, ()>)>,
+    /// It includes the cancellation token and the join handle of the snapshot replication task.
+    /// The token is a child of [`Self::cancel`], thus the snapshot task is notified to quit when
+    /// this replication task is cancelled or quits.
+    snapshot_state: Option<(CancellationTokenOf<C>, JoinHandleOf<C, ()>)>,
+
+    /// The progress of the snapshot transmission, shared with [`ReplicationHandle`].
+    snapshot_resume: SnapshotResume,
+
+    /// The maximum bytes per second to send to the target, 0 means unlimited.
+    rate_limit: u64,
+
+    /// Limits the rate of sending log entries.
+    rate_limiter: Option<RateLimiter<C>>,
+
+    /// Records the RPCs sent to the target.
+    recorder: RpcRecorder<C>,
+
+    /// Signs the RPCs sent to the target.
+    auth: RpcAuth<C>,
+
+    /// Cancelled to stop this replication task.
+    cancel: CancellationTokenOf<C>,
 
     /// The backoff policy if an [`Unreachable`](`crate::error::Unreachable`) error is returned.
     /// It will be reset to `None` when an successful response is received.
@@ -126,12 +203,25 @@ where
     /// The [`RaftLogStorage::LogReader`] interface.
     log_reader: LS::LogReader,
 
+    /// The latest appended log entries, read before the log store.
+    log_cache: LogCache<C>,
+
+    /// The entries read ahead for the next AppendEntries RPC, if read-ahead is enabled.
+    read_ahead: Option<ReadAhead<C>>,
+
     /// The handle to get a snapshot directly from state machine.
     snapshot_reader: SnapshotReader<C>,
 
+    /// Limits the number of snapshots transmitted at the same time, shared by all replication
+    /// streams of a leader.
+    snapshot_transmission_semaphore: Arc<SemaphoreOf<C>>,
+
     /// The Raft's runtime config.
     config: Arc<Config>,
 
+    /// The config that can be updated at runtime, such as RPC timeouts.
+    runtime_config: Arc<RuntimeConfig>,
+
     /// The log id of the highest log entry which is known to be committed in the cluster.
     committed: Option<LogId<C::NodeId>>,
 
@@ -144,6 +234,23 @@ where
     /// Appropriate number of entries to send.
     /// This is only used by AppendEntries RPC.
     entries_hint: ReplicationHint,
+
+    /// The number of AppendEntries RPCs that can be in flight.
+    ///
+    /// It is [`Config::max_inflight_append_entries`], and falls back to 1, i.e., stop-and-wait,
+    /// after an RPC fails, until an RPC succeeds.
+    inflight_window: u64,
+
+    /// Adapts the number of entries to send to the latency, if adaptive batching is enabled.
+    adaptive_batch: Option<AdaptiveBatch>,
+
+    /// The adaptive batch sizes of all targets, shared with `RaftCore` for metrics.
+    batch_sizes: Arc<std::sync::Mutex<BatchSizeMetrics<C::NodeId>>>,
+
+    /// Whether the target is quarantined by `RaftCore` because its replication lag keeps growing.
+    ///
+    /// See [`Config::quarantine_lag_growth_duration`].
+    quarantined: bool,
 }
 
 impl<C, N, LS> ReplicationCore<C, N, LS>
@@ -160,13 +267,23 @@ where
         target: C::NodeId,
         session_id: ReplicationSessionId<C::NodeId>,
         config: Arc<Config>,
+        runtime_config: Arc<RuntimeConfig>,
         committed: Option<LogId<C::NodeId>>,
         matching: Option<LogId<C::NodeId>>,
+        witness: bool,
         network: N::Network,
         snapshot_network: N::Network,
+        heartbeat_network: Option<N::Network>,
         log_reader: LS::LogReader,
         snapshot_reader: SnapshotReader<C>,
+        snapshot_transmission_semaphore: Arc<SemaphoreOf<C>>,
+        snapshot_resume: SnapshotResume,
+        rtt: Arc<std::sync::Mutex<RttMetrics<C::NodeId>>>,
+        batch_sizes: Arc<std::sync::Mutex<BatchSizeMetrics<C::NodeId>>>,
+        log_cache: LogCache<C>,
+        auth: RpcAuth<C>,
         tx_raft_core: mpsc::UnboundedSender<Notify<C>>,
+        cancel: CancellationTokenOf<C>,
         span: tracing::Span,
     ) -> ReplicationHandle<C> {
         tracing::debug!(
@@ -180,16 +297,53 @@ where
         // other component to ReplicationStream
         let (tx_event, rx_event) = mpsc::unbounded_channel();
 
+        let rate_limit = network.replication_rate_limit().unwrap_or(config.replication_rate_limit_bytes_per_sec);
+        let recorder = RpcRecorder::new(target, network.observer(), rtt);
+
+        let tx_heartbeat = heartbeat_network.map(|heartbeat_network| {
+            HeartbeatWorker::<C, N>::spawn(
+                target,
+                session_id,
+                heartbeat_network,
+                recorder.clone(),
+                auth.clone(),
+                runtime_config.clone(),
+                tx_raft_core.clone(),
+                cancel.child_token(),
+                span.clone(),
+            )
+        });
+
+        let adaptive_batch =
+            config.adaptive_batch_target_latency().map(|t| AdaptiveBatch::new(config.max_payload_entries, t));
+        if let Some(a) = &adaptive_batch {
+            batch_sizes.lock().unwrap().insert(target, a.size());
+        }
+
+        let inflight_window = config.max_inflight_append_entries;
+
         let this = Self {
             target,
+            witness,
             session_id,
-            network,
+            network: Arc::new(Mutex::new(network)),
+            entries_stream: None,
             snapshot_network: Arc::new(Mutex::new(snapshot_network)),
             snapshot_state: None,
+            snapshot_resume: snapshot_resume.clone(),
+            rate_limit,
+            rate_limiter: RateLimiter::new(rate_limit),
+            recorder,
+            auth,
+            cancel: cancel.clone(),
             backoff: None,
             log_reader,
+            log_cache,
+            read_ahead: ReadAhead::new(config.replication_read_ahead_bytes),
             snapshot_reader,
+            snapshot_transmission_semaphore,
             config,
+            runtime_config,
             committed,
             matching,
             tx_raft_core,
@@ -197,16 +351,38 @@ where
             weak_tx_event: tx_event.downgrade(),
             next_action: None,
             entries_hint: Default::default(),
+            inflight_window,
+            adaptive_batch,
+            batch_sizes,
+            quarantined: false,
         };
 
-        let join_handle = C::AsyncRuntime::spawn(this.main().instrument(span));
+        let join_handle = C::AsyncRuntime::spawn_named(
+            &format!("replication-{}", target),
+            Self::run(this, cancel.clone()).instrument(span),
+        );
 
         ReplicationHandle {
             join_handle,
             tx_repl: tx_event,
+            cancel,
+            snapshot_resume,
+            tx_heartbeat,
         }
     }
 
+    /// Run the replication main loop until it quits or `cancel` is cancelled.
+    async fn run(this: Self, cancel: CancellationTokenOf<C>) -> Result<(), ReplicationClosed> {
+        let res = select! {
+            _ = cancel.cancelled() => Err(ReplicationClosed::new("ReplicationCore is cancelled")),
+            res = this.main() => res,
+        };
+
+        // Stop the heartbeat worker and the snapshot transmission spawned by this replication task.
+        cancel.cancel();
+        res
+    }
+
     #[tracing::instrument(level="debug", skip(self), fields(session=%self.session_id, target=display(self.target), cluster=%self.config.cluster_name))]
     async fn main(mut self) -> Result<(), ReplicationClosed> {
         loop {
@@ -235,7 +411,11 @@ where
                 }
                 Data::Logs(log) => {
                     log_data = Some(log.clone());
-                    self.send_log_entries(log).await
+                    if self.inflight_window > 1 {
+                        self.pipeline_log_entries(log).await
+                    } else {
+                        self.send_log_entries(log).await
+                    }
                 }
                 Data::Snapshot(snap) => self.stream_snapshot(snap).await,
                 Data::SnapshotCallback(resp) => self.handle_snapshot_callback(resp),
@@ -246,7 +426,9 @@ where
             match res {
                 Ok(next) => {
                     // reset backoff at once if replication succeeds
-                    self.backoff = None;
+                    if self.backoff.take().is_some() {
+                        self.send_backoff_state(None);
+                    }
 
                     // If the RPC was successful but not finished, continue.
                     if let Some(next) = next {
@@ -289,7 +471,9 @@ where
                                     // period of time. Backoff will be reset if there is a
                                     // successful RPC is sent.
                                     if self.backoff.is_none() {
-                                        self.backoff = Some(self.network.backoff());
+                                        let backoff = self.network.lock().await.backoff();
+                                        let backoff = backoff.or_else(|| self.config.backoff_policy.build());
+                                        self.backoff = Some(backoff);
                                     }
                                     false
                                 }
@@ -325,13 +509,30 @@ where
                 Duration::from_millis(500)
             });
 
-            self.backoff_drain_events(InstantOf::<C>::now() + duration).await?;
+            let state = BackoffState {
+                attempts: b.attempts(),
+                delay_millis: duration.as_millis() as u64,
+            };
+            self.send_backoff_state(Some(state));
+
+            self.backoff_drain_events(C::AsyncRuntime::now() + duration).await?;
         }
 
         self.drain_events().await?;
         Ok(())
     }
 
+    /// Report the backoff state of the target to RaftCore, `None` if it becomes reachable.
+    fn send_backoff_state(&self, state: Option<BackoffState>) {
+        let _ = self.tx_raft_core.send(Notify::Network {
+            response: Response::Backoff {
+                target: self.target,
+                state,
+                session_id: self.session_id,
+            },
+        });
+    }
+
     /// When a [`PayloadTooLarge`] error is received, update the hint for the next several RPC.
     fn update_hint(&mut self, too_large: &PayloadTooLarge) {
         const DEFAULT_ENTRIES_HINT_TTL: u64 = 10;
@@ -371,72 +572,52 @@ where
         );
 
         // Series of logs to send, and the last log id to send
-        let (logs, sending_range) = {
-            let rng = log_ids.data();
-
-            // The log index start and end to send.
-            let (start, end) = {
-                let start = rng.prev.next_index();
-                let end = rng.last.next_index();
-
-                if let Some(hint) = self.entries_hint.get() {
-                    let hint_end = start + hint;
-                    (start, std::cmp::min(end, hint_end))
-                } else {
-                    (start, end)
-                }
-            };
-
-            if start == end {
-                // Heartbeat RPC, no logs to send, last log id is the same as prev_log_id
-                let r = LogIdRange::new(rng.prev, rng.prev);
-                (vec![], r)
-            } else {
-                // limited_get_log_entries will return logs smaller than the range [start, end).
-                let logs = self.log_reader.limited_get_log_entries(start, end).await?;
-
-                let first = *logs.first().map(|x| x.get_log_id()).unwrap();
-                let last = *logs.last().map(|x| x.get_log_id()).unwrap();
+        let (logs, sending_range) = self.read_entries(log_ids.data()).await?;
 
-                debug_assert!(
-                    !logs.is_empty() && logs.len() <= (end - start) as usize,
-                    "expect logs ⊆ [{}..{}) but got {} entries, first: {}, last: {}",
-                    start,
-                    end,
-                    logs.len(),
-                    first,
-                    last
-                );
-
-                let r = LogIdRange::new(rng.prev, Some(last));
-                (logs, r)
-            }
-        };
+        let n_entries = logs.len() as u64;
+        let bytes: u64 = logs.iter().map(|x| x.size_hint() as u64).sum();
+        if let Some(l) = &mut self.rate_limiter {
+            l.acquire(bytes).await;
+        }
 
-        let leader_time = InstantOf::<C>::now();
+        let leader_time = C::AsyncRuntime::now();
 
         // Build the heartbeat frame to be sent to the follower.
-        let payload = AppendEntriesRequest {
-            vote: self.session_id.vote,
-            prev_log_id: sending_range.prev,
-            leader_commit: self.committed,
-            entries: logs,
-        };
+        let payload = self.new_append_entries(sending_range.prev, logs);
 
         // Send the payload.
         tracing::debug!(
             payload = display(&payload),
             now = debug(leader_time),
-            "start sending append_entries, timeout: {:?}",
-            self.config.heartbeat_interval
+            "start sending append_entries"
         );
 
-        let the_timeout = Duration::from_millis(self.config.heartbeat_interval);
-        let option = RPCOption::new(the_timeout);
-        let res = AsyncRuntimeOf::<C>::timeout(the_timeout, self.network.append_entries(payload, option)).await;
+        let read_ahead_range = self.read_ahead_range(&sending_range, log_ids.data().last.next_index(), n_entries);
+
+        let option = self.recorder.option(self.runtime_config.rpc_timeout(RPCTypes::AppendEntries));
+        let the_timeout = option.hard_ttl();
+        let call = Self::call_entries_stream(
+            &mut self.entries_stream,
+            &self.network,
+            &self.cancel,
+            self.config.max_inflight_append_entries as usize,
+            payload,
+            option,
+        );
+        let call = AsyncRuntimeOf::<C>::timeout(the_timeout, call);
+
+        // Read the entries for the next RPC while this one is in flight.
+        let res = match (&mut self.read_ahead, read_ahead_range) {
+            (Some(r), Some((start, end))) => futures::join!(call, r.fill(&mut self.log_reader, start, end)).0,
+            _ => call.await,
+        };
 
         tracing::debug!("append_entries res: {:?}", res);
 
+        if !matches!(res, Ok(Ok(_))) {
+            self.close_entries_stream().await;
+        }
+
         let append_res = res.map_err(|_e| {
             let to = Timeout {
                 action: RPCTypes::AppendEntries,
@@ -445,7 +626,16 @@ where
                 timeout: the_timeout,
             };
             RPCError::Timeout(to)
-        })?; // return Timeout error
+        });
+
+        let error = match &append_res {
+            Ok(res) => res.as_ref().err().map(RPCErrorKind::from),
+            Err(e) => Some(RPCErrorKind::from(e)),
+        };
+        self.recorder.record(RPCTypes::AppendEntries, bytes, leader_time.elapsed(), error);
+        self.update_batch_size(n_entries, leader_time.elapsed(), error.is_none());
+
+        let append_res = append_res?; // return Timeout error
 
         let append_resp = append_res?;
 
@@ -457,6 +647,9 @@ where
 
         match append_resp {
             AppendEntriesResponse::Success => {
+                // Resume pipelining after a stop-and-wait RPC succeeds.
+                self.inflight_window = self.max_inflight_window();
+
                 let matching = sending_range.last;
                 let next = self.finish_success_append(matching, leader_time, log_ids);
                 Ok(next)
@@ -492,6 +685,349 @@ where
         }
     }
 
+    /// Read the entries in `rng` to send in the next AppendEntries RPC, at most as many as the
+    /// batch size allows.
+    ///
+    /// It returns the entries and the range of log ids they cover. No entry is returned for a
+    /// heartbeat.
+    async fn read_entries(
+        &mut self,
+        rng: &LogIdRange<C::NodeId>,
+    ) -> Result<(Vec<C::Entry>, LogIdRange<C::NodeId>), ReplicationError<C>> {
+        // The log index start and end to send.
+        let (start, end) = {
+            let start = rng.prev.next_index();
+            let end = rng.last.next_index();
+
+            // A range with several RPCs in flight is sent in batches.
+            let end = std::cmp::min(end, start + self.config.max_payload_entries);
+
+            let end = if let Some(hint) = self.entries_hint.get() {
+                std::cmp::min(end, start + hint)
+            } else {
+                end
+            };
+
+            if let Some(a) = &self.adaptive_batch {
+                (start, std::cmp::min(end, start + a.size()))
+            } else {
+                (start, end)
+            }
+        };
+
+        if start == end {
+            // Heartbeat RPC, no logs to send, last log id is the same as prev_log_id
+            let r = LogIdRange::new(rng.prev, rng.prev);
+            Ok((vec![], r))
+        } else {
+            // All of them return logs smaller than the range [start, end).
+            let read_ahead = self.read_ahead.as_mut().and_then(|r| r.take(start, end));
+            let logs = match read_ahead.or_else(|| self.log_cache.get(start, end)) {
+                Some(logs) => logs,
+                None => self.log_reader.limited_get_log_entries(start, end).await?,
+            };
+
+            let first = *logs.first().map(|x| x.get_log_id()).unwrap();
+            let last = *logs.last().map(|x| x.get_log_id()).unwrap();
+
+            debug_assert!(
+                !logs.is_empty() && logs.len() <= (end - start) as usize,
+                "expect logs ⊆ [{}..{}) but got {} entries, first: {}, last: {}",
+                start,
+                end,
+                logs.len(),
+                first,
+                last
+            );
+
+            let logs = if self.witness { Self::strip_payload(logs) } else { logs };
+
+            let r = LogIdRange::new(rng.prev, Some(last));
+            Ok((logs, r))
+        }
+    }
+
+    /// Build a signed AppendEntries request to send `entries` following `prev_log_id`.
+    fn new_append_entries(
+        &self,
+        prev_log_id: Option<LogId<C::NodeId>>,
+        entries: Vec<C::Entry>,
+    ) -> AppendEntriesRequest<C> {
+        let mut payload = AppendEntriesRequest {
+            vote: self.session_id.vote,
+            prev_log_id,
+            leader_commit: self.committed,
+            entries,
+            context: RpcContext::default(),
+        };
+        N::inject_context(&mut payload.context);
+        self.auth.sign_append_entries(self.target, &mut payload);
+        payload
+    }
+
+    /// Send the entries in `log_ids` with up to [`Self::inflight_window`] AppendEntries RPCs in
+    /// flight, and report the progress to RaftCore as every RPC is acknowledged.
+    ///
+    /// The RPCs are sent in order on the entries stream and the responses are received in the
+    /// same order. On an error or a response other than success, the responses still in flight
+    /// are discarded by closing the stream, and it falls back to stop-and-wait.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn pipeline_log_entries(
+        &mut self,
+        log_ids: DataWithId<LogIdRange<C::NodeId>>,
+    ) -> Result<Option<Data<C>>, ReplicationError<C>> {
+        let request_id = log_ids.request_id();
+        let last = log_ids.data().last;
+
+        tracing::debug!(
+            request_id = display(request_id),
+            log_id_range = display(log_ids.data()),
+            window = self.inflight_window,
+            "pipeline_log_entries",
+        );
+
+        // The prev log id of the next RPC to send.
+        let mut next_prev = log_ids.data().prev;
+        let mut inflight = VecDeque::<InflightAppend<C>>::new();
+        let mut inflight_bytes = 0;
+
+        // A transport may process the requests on a stream one by one, thus a response is
+        // expected within the timeout since it is sent or since the previous response, whichever
+        // is later.
+        let mut last_response = C::AsyncRuntime::now();
+
+        loop {
+            while next_prev < last && self.has_inflight_room(inflight.len() as u64, inflight_bytes) {
+                let (logs, sending_range) = self.read_entries(&LogIdRange::new(next_prev, last)).await?;
+
+                let n_entries = logs.len() as u64;
+                let bytes: u64 = logs.iter().map(|x| x.size_hint() as u64).sum();
+                if let Some(l) = &mut self.rate_limiter {
+                    l.acquire(bytes).await;
+                }
+
+                let payload = self.new_append_entries(sending_range.prev, logs);
+
+                let option = self.recorder.option(self.runtime_config.rpc_timeout(RPCTypes::AppendEntries));
+                let timeout = option.hard_ttl();
+                let leader_time = C::AsyncRuntime::now();
+
+                let window = self.config.max_inflight_append_entries as usize;
+                let stream = self.entries_stream.get_or_insert_with(|| {
+                    EntriesStream::spawn(self.network.clone(), option, window, self.cancel.child_token())
+                });
+
+                if let Err(e) = stream.send(payload).await {
+                    self.stop_pipeline().await;
+                    return Err(e.into());
+                }
+
+                next_prev = sending_range.last;
+                inflight_bytes += bytes;
+                inflight.push_back(InflightAppend {
+                    sending_range,
+                    n_entries,
+                    bytes,
+                    leader_time,
+                    timeout,
+                });
+            }
+
+            let Some(sent) = inflight.pop_front() else {
+                return Ok(None);
+            };
+            inflight_bytes -= sent.bytes;
+
+            // A stream is open while there are RPCs in flight.
+            let stream = self.entries_stream.as_mut().unwrap();
+            let deadline = std::cmp::max(sent.leader_time, last_response) + sent.timeout;
+            let res = AsyncRuntimeOf::<C>::timeout_at(deadline, stream.recv()).await;
+            last_response = C::AsyncRuntime::now();
+
+            let res = res.unwrap_or_else(|_e| {
+                Err(RPCError::Timeout(Timeout {
+                    action: RPCTypes::AppendEntries,
+                    id: self.session_id.vote.leader_id().voted_for().unwrap(),
+                    target: self.target,
+                    timeout: sent.timeout,
+                }))
+            });
+
+            let error = res.as_ref().err().map(RPCErrorKind::from);
+            let elapsed = sent.leader_time.elapsed();
+            self.recorder.record(RPCTypes::AppendEntries, sent.bytes, elapsed, error);
+            self.update_batch_size(sent.n_entries, elapsed, error.is_none());
+
+            let append_resp = match res {
+                Ok(x) => x,
+                Err(e) => {
+                    self.stop_pipeline().await;
+                    return Err(e.into());
+                }
+            };
+
+            tracing::debug!(
+                req = display(&sent.sending_range),
+                resp = display(&append_resp),
+                inflight = inflight.len(),
+                "pipelined append_entries resp"
+            );
+
+            match append_resp {
+                AppendEntriesResponse::Success => {
+                    let matching = ReplicationResult::new(sent.leader_time, Ok(sent.sending_range.last));
+                    self.send_progress(request_id, matching);
+                }
+                AppendEntriesResponse::PartialSuccess(matching) => {
+                    Self::debug_assert_partial_success(&sent.sending_range, &matching);
+                    self.stop_pipeline().await;
+
+                    let next = self.finish_success_append(matching, sent.leader_time, log_ids);
+                    return Ok(next);
+                }
+                AppendEntriesResponse::HigherVote(vote) => {
+                    self.stop_pipeline().await;
+
+                    return Err(ReplicationError::HigherVote(HigherVote {
+                        higher: vote,
+                        mine: self.session_id.vote,
+                    }));
+                }
+                AppendEntriesResponse::Conflict => {
+                    self.stop_pipeline().await;
+
+                    let conflict = sent.sending_range.prev;
+                    debug_assert!(conflict.is_some(), "prev_log_id=None never conflict");
+
+                    let conflict = conflict.unwrap();
+                    self.send_progress(request_id, ReplicationResult::new(sent.leader_time, Err(conflict)));
+                    return Ok(None);
+                }
+            }
+
+            // Receive the updated committed log id, to send it with the following RPCs.
+            self.try_drain_events().await?;
+        }
+    }
+
+    /// Returns whether another AppendEntries RPC can be sent with `n` RPCs of `bytes` bytes in
+    /// flight.
+    fn has_inflight_room(&self, n: u64, bytes: u64) -> bool {
+        if n == 0 {
+            return true;
+        }
+
+        let max_bytes = self.config.max_inflight_bytes;
+        n < self.inflight_window && (max_bytes == 0 || bytes < max_bytes)
+    }
+
+    /// Returns the number of AppendEntries RPCs allowed in flight when the link is healthy.
+    ///
+    /// A quarantined target is sent one RPC at a time.
+    fn max_inflight_window(&self) -> u64 {
+        if self.quarantined {
+            1
+        } else {
+            self.config.max_inflight_append_entries
+        }
+    }
+
+    /// Returns the maximum bytes per second to send to the target, 0 means unlimited.
+    fn effective_rate_limit(&self) -> u64 {
+        if self.quarantined {
+            min_rate_limit(self.rate_limit, self.config.quarantine_rate_limit_bytes_per_sec)
+        } else {
+            self.rate_limit
+        }
+    }
+
+    /// Discard the responses of the RPCs in flight and fall back to stop-and-wait.
+    async fn stop_pipeline(&mut self) {
+        self.close_entries_stream().await;
+        self.inflight_window = 1;
+    }
+
+    /// Replace every entry except membership entries with a blank entry of the same log id, to
+    /// send to a witness.
+    fn strip_payload(logs: Vec<C::Entry>) -> Vec<C::Entry> {
+        logs.into_iter()
+            .map(|ent| {
+                if ent.get_membership().is_some() {
+                    ent
+                } else {
+                    C::Entry::new_blank(*ent.get_log_id())
+                }
+            })
+            .collect()
+    }
+
+    /// Adapt the batch size to the result of an AppendEntries RPC of `entries` entries.
+    ///
+    /// A heartbeat carries no entry and does not change the batch size.
+    fn update_batch_size(&mut self, entries: u64, latency: Duration, success: bool) {
+        let Some(a) = &mut self.adaptive_batch else {
+            return;
+        };
+
+        if entries == 0 {
+            return;
+        }
+
+        let prev = a.size();
+        if success {
+            a.on_success(entries, latency);
+        } else {
+            a.on_error();
+        }
+
+        if a.size() != prev {
+            tracing::debug!(prev, size = a.size(), "adaptive batch size changed");
+            self.batch_sizes.lock().unwrap().insert(self.target, a.size());
+        }
+    }
+
+    /// Send an AppendEntries request on the stream to the target, open the stream if it is not.
+    /// Send an AppendEntries on the stream, open the stream if it is not open.
+    ///
+    /// It borrows only the fields it uses, so that the log can be read ahead at the same time.
+    async fn call_entries_stream(
+        entries_stream: &mut Option<EntriesStream<C>>,
+        network: &Arc<Mutex<N::Network>>,
+        cancel: &CancellationTokenOf<C>,
+        window: usize,
+        payload: AppendEntriesRequest<C>,
+        option: RPCOption,
+    ) -> Result<AppendEntriesResponse<C>, RPCError<C>> {
+        let stream = entries_stream
+            .get_or_insert_with(|| EntriesStream::spawn(network.clone(), option, window, cancel.child_token()));
+
+        stream.call(payload).await
+    }
+
+    /// Returns the range of entries to read ahead while sending `sending_range` with `n_entries`
+    /// entries, if there are more entries to send before `end`.
+    ///
+    /// A range in the log cache is not read ahead.
+    fn read_ahead_range(&self, sending_range: &LogIdRange<C::NodeId>, end: u64, n_entries: u64) -> Option<(u64, u64)> {
+        if self.quarantined {
+            return None;
+        }
+
+        let r = self.read_ahead.as_ref()?;
+
+        let (start, end) = r.next_range(sending_range.last.next_index(), n_entries, end)?;
+        if self.log_cache.contains(start) {
+            return None;
+        }
+        Some((start, end))
+    }
+
+    async fn close_entries_stream(&mut self) {
+        if let Some(stream) = self.entries_stream.take() {
+            stream.close().await;
+        }
+    }
+
     /// Send the error result to RaftCore.
     /// RaftCore will then submit another replication command.
     fn send_progress_error(&mut self, request_id: RequestId, err: RPCError<C>) {
@@ -571,7 +1107,7 @@ where
     /// in case the channel is closed, it should quit at once.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn backoff_drain_events(&mut self, until: InstantOf<C>) -> Result<(), ReplicationClosed> {
-        let d = until - InstantOf::<C>::now();
+        let d = until - C::AsyncRuntime::now();
         tracing::warn!(
             interval = debug(d),
             "{} backoff mode: drain events without processing them",
@@ -579,7 +1115,7 @@ where
         );
 
         loop {
-            let sleep_duration = until - InstantOf::<C>::now();
+            let sleep_duration = until - C::AsyncRuntime::now();
             let sleep = C::AsyncRuntime::sleep(sleep_duration);
 
             let recv = self.rx_event.recv();
@@ -697,6 +1233,15 @@ where
 
                 self.next_action = Some(d);
             }
+            Replicate::Quarantine(quarantined) => {
+                if quarantined == self.quarantined {
+                    return;
+                }
+
+                self.quarantined = quarantined;
+                self.inflight_window = std::cmp::min(self.inflight_window, self.max_inflight_window());
+                self.rate_limiter = RateLimiter::new(self.effective_rate_limit());
+            }
         }
     }
 
@@ -709,7 +1254,9 @@ where
 
         tracing::info!(request_id = display(request_id), "{}", func_name!());
 
-        let snapshot = self.snapshot_reader.get_snapshot().await.map_err(|reason| {
+        // The target has the logs up to `matching`: a delta since then is enough if the state
+        // machine can build one.
+        let snapshot = self.snapshot_reader.get_snapshot(self.matching).await.map_err(|reason| {
             tracing::warn!(error = display(&reason), "failed to get snapshot from state machine");
             ReplicationClosed::new(reason)
         })?;
@@ -729,52 +1276,103 @@ where
             Some(x) => x,
         };
 
-        let mut option = RPCOption::new(self.config.install_snapshot_timeout());
+        let snapshot = if self.witness {
+            Self::empty_snapshot(&self.snapshot_reader, snapshot.meta).await?
+        } else {
+            snapshot
+        };
+
+        // The snapshot task uses its own recorder, since it runs concurrently with this task.
+        let recorder = self.recorder.clone();
+        let mut option = recorder.option(self.runtime_config.rpc_timeout(RPCTypes::InstallSnapshot));
         option.snapshot_chunk_size = Some(self.config.snapshot_max_chunk_size as usize);
+        option.snapshot_resume = Some(self.snapshot_resume.clone());
+        option.compress_snapshot = self.config.compress_snapshot;
+        let rate_limit = min_rate_limit(
+            self.effective_rate_limit(),
+            self.config.snapshot_transmission_rate_limit_bytes_per_sec,
+        );
+        if rate_limit > 0 {
+            option.replication_rate_limit = Some(rate_limit);
+        }
+        N::inject_context(&mut option.context);
+        self.auth.sign_snapshot(self.target, &self.session_id.vote, &snapshot.meta, &mut option.context);
 
-        let (tx_cancel, rx_cancel) = oneshot::channel();
+        let cancel = self.cancel.child_token();
 
         let jh = AsyncRuntimeOf::<C>::spawn(Self::send_snapshot(
             request_id,
             self.snapshot_network.clone(),
+            self.snapshot_transmission_semaphore.clone(),
             self.session_id.vote,
             snapshot,
             option,
-            rx_cancel,
+            recorder,
+            cancel.clone(),
             self.weak_tx_event.clone(),
         ));
 
-        // When self.rx_event is dropped:
-        // 1) ReplicationCore will return from the main loop;
-        // 2) and tx_cancel is dropped;
-        // 3) and the snapshot task will be notified.
-        self.snapshot_state = Some((tx_cancel, jh));
+        // When this ReplicationCore quits or is cancelled, `cancel` is cancelled too and the
+        // snapshot task will be notified.
+        self.snapshot_state = Some((cancel, jh));
         Ok(None)
     }
 
+    /// Build a full snapshot with `meta` and empty data, to send to a witness.
+    async fn empty_snapshot(
+        snapshot_reader: &SnapshotReader<C>,
+        mut meta: SnapshotMeta<C>,
+    ) -> Result<Snapshot<C>, ReplicationError<C>> {
+        let data = snapshot_reader.empty_snapshot_data().await.map_err(|reason| {
+            tracing::warn!(
+                error = display(&reason),
+                "failed to get empty snapshot data from state machine"
+            );
+            ReplicationClosed::new(reason)
+        })?;
+
+        meta.delta_base = None;
+        meta.checksum = None;
+
+        Ok(Snapshot { meta, snapshot: data })
+    }
+
     async fn send_snapshot(
         request_id: RequestId,
         network: Arc<Mutex<N::Network>>,
+        semaphore: Arc<SemaphoreOf<C>>,
         vote: Vote<C::NodeId>,
         snapshot: Snapshot<C>,
         option: RPCOption,
-        cancel: oneshot::Receiver<()>,
+        recorder: RpcRecorder<C>,
+        cancel: CancellationTokenOf<C>,
         weak_tx: mpsc::WeakUnboundedSender<Replicate<C>>,
     ) {
         let meta = snapshot.meta.clone();
 
+        // Released when the transmission is done.
+        let _permit = semaphore.acquire_owned().await;
+
         let mut net = network.lock().await;
 
-        let start_time = InstantOf::<C>::now();
+        let start_time = C::AsyncRuntime::now();
 
         let cancel = async move {
-            let _ = cancel.await;
-            ReplicationClosed::new("ReplicationCore is dropped")
+            cancel.cancelled().await;
+            ReplicationClosed::new("ReplicationCore is cancelled")
         };
 
         let res = net.full_snapshot(vote, snapshot, cancel, option).await;
-        if let Err(e) = &res {
-            tracing::warn!(error = display(e), "failed to send snapshot");
+        match &res {
+            Ok(_) => recorder.record(RPCTypes::InstallSnapshot, 0, start_time.elapsed(), None),
+            Err(e) => {
+                tracing::warn!(error = display(e), "failed to send snapshot");
+
+                // A canceled transmission or a local error is not an RPC error.
+                if let Some(kind) = RPCErrorKind::of_streaming(e) {
+                    recorder.record(RPCTypes::InstallSnapshot, 0, start_time.elapsed(), Some(kind));
+                }
+            }
         }
 
         if let Some(tx_noty) = weak_tx.upgrade() {
@@ -875,3 +1473,19 @@ where
         );
     }
 }
+
+/// An AppendEntries RPC sent in a pipeline, whose response is not yet received.
+struct InflightAppend<C>
+where C: RaftTypeConfig
+{
+    /// The range of log ids sent in this RPC.
+    sending_range: LogIdRange<C::NodeId>,
+
+    n_entries: u64,
+    bytes: u64,
+
+    /// The time when this RPC was sent.
+    leader_time: InstantOf<C>,
+
+    timeout: Duration,
+}
@@ -0,0 +1,110 @@
+//! Limits the bytes per second sent by a replication stream.
+
+use std::time::Duration;
+
+use crate::type_config::alias::InstantOf;
+use crate::AsyncRuntime;
+use crate::Instant;
+use crate::RaftTypeConfig;
+
+/// Limits the rate of the data sent to a target to `bytes_per_sec`.
+///
+/// Every send is accounted for the time it takes at the limited rate, and a send waits until the
+/// previous sends are done at this rate. Thus the first send after an idle period is not delayed.
+pub(crate) struct RateLimiter<C>
+where C: RaftTypeConfig
+{
+    bytes_per_sec: u64,
+
+    /// When the data sent so far would have been sent at the limited rate.
+    next: InstantOf<C>,
+}
+
+impl<C> RateLimiter<C>
+where C: RaftTypeConfig
+{
+    /// Create a limiter, or `None` if `bytes_per_sec` is 0, i.e., unlimited.
+    pub(crate) fn new(bytes_per_sec: u64) -> Option<Self> {
+        if bytes_per_sec == 0 {
+            return None;
+        }
+
+        Some(Self {
+            bytes_per_sec,
+            next: InstantOf::<C>::now(),
+        })
+    }
+
+    /// Wait until `bytes` can be sent.
+    ///
+    /// It returns at once for 0 bytes, such as a heartbeat, which should never be delayed.
+    pub(crate) async fn acquire(&mut self, bytes: u64) {
+        if bytes == 0 {
+            return;
+        }
+
+        let wait = self.reserve(InstantOf::<C>::now(), bytes);
+        if wait > Duration::ZERO {
+            tracing::debug!(bytes, "replication rate limited, wait for {:?}", wait);
+            C::AsyncRuntime::sleep(wait).await;
+        }
+    }
+
+    /// Account `bytes` sent at `now`, returns how long to wait before sending it.
+    fn reserve(&mut self, now: InstantOf<C>, bytes: u64) -> Duration {
+        if self.next < now {
+            self.next = now;
+        }
+
+        let wait = self.next - now;
+        self.next += Duration::from_secs_f64(bytes as f64 / self.bytes_per_sec as f64);
+        wait
+    }
+}
+
+/// Returns the lower of two rate limits, where `0` means unlimited.
+pub(crate) fn min_rate_limit(a: u64, b: u64) -> u64 {
+    match (a, b) {
+        (0, x) | (x, 0) => x,
+        (a, b) => a.min(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::engine::testing::UTConfig;
+    use crate::replication::rate_limiter::min_rate_limit;
+    use crate::replication::rate_limiter::RateLimiter;
+    use crate::TokioInstant;
+
+    #[test]
+    fn test_rate_limiter_unlimited() {
+        assert!(RateLimiter::<UTConfig>::new(0).is_none());
+    }
+
+    #[test]
+    fn test_min_rate_limit() {
+        assert_eq!(0, min_rate_limit(0, 0));
+        assert_eq!(5, min_rate_limit(0, 5));
+        assert_eq!(5, min_rate_limit(5, 0));
+        assert_eq!(3, min_rate_limit(3, 5));
+        assert_eq!(3, min_rate_limit(5, 3));
+    }
+
+    #[test]
+    fn test_rate_limiter_reserve() {
+        let mut l = RateLimiter::<UTConfig>::new(1_000).unwrap();
+        let now = TokioInstant::now();
+
+        assert_eq!(Duration::ZERO, l.reserve(now, 500));
+        assert_eq!(Duration::from_millis(500), l.reserve(now, 1_000));
+        assert_eq!(Duration::from_millis(1_500), l.reserve(now, 1));
+
+        // After an idle period, a send is not delayed.
+        let later = now + Duration::from_secs(10);
+        assert_eq!(Duration::ZERO, l.reserve(later, 1_000));
+        assert_eq!(Duration::from_secs(1), l.reserve(later, 1_000));
+    }
+}
@@ -0,0 +1,183 @@
+//! Prefetches log entries for a replication stream.
+
+use crate::entry::RaftEntry;
+use crate::storage::RaftLogReader;
+use crate::RaftLogId;
+use crate::RaftTypeConfig;
+
+/// Buffers the log entries to send in the next AppendEntries RPC, read while the current RPC is in
+/// flight, so that a slow log store does not add to the latency of catching up a follower.
+///
+/// The buffered entries are consecutive, and their total size, estimated with
+/// [`RaftEntry::size_hint()`], is limited by a bytes budget.
+pub(crate) struct ReadAhead<C>
+where C: RaftTypeConfig
+{
+    budget: u64,
+
+    entries: Vec<C::Entry>,
+    bytes: u64,
+}
+
+impl<C> ReadAhead<C>
+where C: RaftTypeConfig
+{
+    /// Create a read-ahead buffer, or `None` if `budget` is 0, i.e., disabled.
+    pub(crate) fn new(budget: u64) -> Option<Self> {
+        if budget == 0 {
+            return None;
+        }
+
+        Some(Self {
+            budget,
+            entries: vec![],
+            bytes: 0,
+        })
+    }
+
+    /// Take the buffered entries in `[start, end)` starting from `start`, which may be fewer than
+    /// requested.
+    ///
+    /// It returns `None` and discards the buffer if the buffer does not start from `start`, e.g.,
+    /// when the replication restarts from a conflicting log.
+    pub(crate) fn take(&mut self, start: u64, end: u64) -> Option<Vec<C::Entry>> {
+        let first = self.entries.first().map(|e| e.get_log_id().index);
+        if first != Some(start) {
+            self.clear();
+            return None;
+        }
+
+        let n = std::cmp::min((end - start) as usize, self.entries.len());
+        let rest = self.entries.split_off(n);
+        let taken = std::mem::replace(&mut self.entries, rest);
+
+        self.bytes -= taken.iter().map(|e| e.size_hint() as u64).sum::<u64>();
+        Some(taken)
+    }
+
+    /// Returns the range to read ahead, following the buffered entries or `next_index` if the
+    /// buffer is empty, with at most `len` entries and before `end`.
+    ///
+    /// It returns `None` if there is nothing to read or the budget is used up.
+    pub(crate) fn next_range(&self, next_index: u64, len: u64, end: u64) -> Option<(u64, u64)> {
+        if self.bytes >= self.budget {
+            return None;
+        }
+
+        let start = self.entries.last().map(|e| e.get_log_id().index + 1).unwrap_or(next_index);
+        let end = std::cmp::min(end, start + len);
+
+        if start < end {
+            Some((start, end))
+        } else {
+            None
+        }
+    }
+
+    /// Read the entries in `[start, end)` from `log_reader` into the buffer.
+    ///
+    /// An error is not returned but logged: the entries will be read again when they are sent,
+    /// and the error will be returned then.
+    pub(crate) async fn fill<LR>(&mut self, log_reader: &mut LR, start: u64, end: u64)
+    where LR: RaftLogReader<C> {
+        match log_reader.limited_get_log_entries(start, end).await {
+            Ok(entries) => self.push(entries),
+            Err(e) => {
+                tracing::warn!(
+                    error = display(&e),
+                    "failed to read ahead log entries [{}, {})",
+                    start,
+                    end
+                );
+            }
+        }
+    }
+
+    /// Append entries following the buffered ones, until the budget is used up.
+    ///
+    /// At least one entry is kept if the buffer is empty, so that an entry larger than the budget
+    /// is still read ahead.
+    fn push(&mut self, entries: Vec<C::Entry>) {
+        for entry in entries {
+            let size = entry.size_hint() as u64;
+            if !self.entries.is_empty() && self.bytes + size > self.budget {
+                break;
+            }
+
+            self.bytes += size;
+            self.entries.push(entry);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::testing::UTConfig;
+    use crate::entry::RaftEntry;
+    use crate::replication::read_ahead::ReadAhead;
+    use crate::testing::blank_ent;
+    use crate::Entry;
+
+    fn ents(start: u64, end: u64) -> Vec<Entry<UTConfig>> {
+        (start..end).map(|i| blank_ent(1, 1, i)).collect()
+    }
+
+    fn indexes(entries: &Option<Vec<Entry<UTConfig>>>) -> Option<Vec<u64>> {
+        entries.as_ref().map(|v| v.iter().map(|e| e.log_id.index).collect())
+    }
+
+    #[test]
+    fn test_read_ahead_disabled() {
+        assert!(ReadAhead::<UTConfig>::new(0).is_none());
+    }
+
+    #[test]
+    fn test_read_ahead_take() {
+        let mut r = ReadAhead::<UTConfig>::new(u64::MAX).unwrap();
+        r.push(ents(5, 10));
+
+        assert_eq!(Some(vec![5, 6]), indexes(&r.take(5, 7)));
+        assert_eq!(Some(vec![7, 8, 9]), indexes(&r.take(7, 20)));
+        assert_eq!(None, indexes(&r.take(10, 20)));
+
+        r.push(ents(5, 10));
+        assert_eq!(
+            None,
+            indexes(&r.take(6, 8)),
+            "not starting from the first buffered entry"
+        );
+        assert!(r.entries.is_empty(), "the buffer is discarded");
+        assert_eq!(0, r.bytes);
+    }
+
+    #[test]
+    fn test_read_ahead_budget() {
+        let size = blank_ent::<UTConfig>(1, 1, 1).size_hint() as u64;
+        let mut r = ReadAhead::<UTConfig>::new(size * 3).unwrap();
+
+        assert_eq!(Some((5, 8)), r.next_range(5, 3, 100));
+        assert_eq!(Some((5, 7)), r.next_range(5, 3, 7));
+        assert_eq!(None, r.next_range(5, 3, 5));
+
+        r.push(ents(5, 10));
+        assert_eq!(
+            Some(vec![5, 6, 7]),
+            indexes(&Some(r.entries.clone())),
+            "limited by budget"
+        );
+        assert_eq!(None, r.next_range(5, 3, 100), "budget used up");
+
+        r.take(5, 6);
+        assert_eq!(Some((8, 11)), r.next_range(6, 3, 100), "following the buffered entries");
+
+        // An entry larger than the budget is kept if the buffer is empty.
+        let mut r = ReadAhead::<UTConfig>::new(1).unwrap();
+        r.push(ents(5, 10));
+        assert_eq!(Some(vec![5]), indexes(&Some(r.entries.clone())));
+    }
+}
@@ -15,6 +15,10 @@ where C: RaftTypeConfig
 
     /// Send a chunk of data, e.g., logs or snapshot.
     Data(Data<C>),
+
+    /// Cap the resources used to replicate to the target if `true`, because its replication lag
+    /// keeps growing, or lift the cap if `false`.
+    Quarantine(bool),
 }
 
 impl<C> Replicate<C>
@@ -39,6 +43,7 @@ impl<C: RaftTypeConfig> fmt::Display for Replicate<C> {
             Self::Committed(c) => write!(f, "Committed({})", c.display()),
             Self::Heartbeat => write!(f, "Heartbeat"),
             Self::Data(d) => write!(f, "Data({})", d),
+            Self::Quarantine(q) => write!(f, "Quarantine({})", q),
         }
     }
 }
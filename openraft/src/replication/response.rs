@@ -1,6 +1,7 @@
 use std::fmt;
 
 use crate::display_ext::DisplayOptionExt;
+use crate::metrics::BackoffState;
 use crate::replication::request_id::RequestId;
 use crate::replication::ReplicationSessionId;
 use crate::type_config::alias::InstantOf;
@@ -45,6 +46,19 @@ where C: RaftTypeConfig
         session_id: ReplicationSessionId<C::NodeId>,
     },
 
+    /// The backoff state of a replication target changed.
+    /// Sent by a replication task `ReplicationCore`.
+    Backoff {
+        /// The ID of the target node that is unreachable.
+        target: C::NodeId,
+
+        /// The current backoff state, or `None` if the target becomes reachable.
+        state: Option<BackoffState>,
+
+        /// In which session this message is sent.
+        session_id: ReplicationSessionId<C::NodeId>,
+    },
+
     /// [`StorageError`] error has taken place locally(not on remote node) when replicating, and
     /// [`RaftCore`](`crate::core::RaftCore`) needs to shutdown. Sent by a replication task
     /// [`crate::replication::ReplicationCore`].
@@ -85,6 +99,20 @@ where C: RaftTypeConfig
                 )
             }
 
+            Self::Backoff {
+                target,
+                state,
+                session_id,
+            } => {
+                write!(
+                    f,
+                    "ReplicationBackoff: target: {}, state: {}, session_id: {}",
+                    target,
+                    state.display(),
+                    session_id
+                )
+            }
+
             Self::StorageError { error } => write!(f, "ReplicationStorageError: {}", error),
 
             Self::HigherVote { target, higher, vote } => {
@@ -0,0 +1,326 @@
+//! Run a synchronous storage implementation in blocking threads.
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::Bound;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+use anyerror::AnyError;
+use futures::lock::Mutex;
+
+use crate::async_runtime::Semaphore;
+use crate::storage::LogFlushed;
+use crate::storage::LogState;
+use crate::storage::RaftLogReader;
+use crate::storage::RaftLogStorage;
+use crate::storage::RaftSnapshotBuilder;
+use crate::storage::RaftStateMachine;
+use crate::storage::Snapshot;
+use crate::storage::SnapshotMeta;
+use crate::type_config::alias::SemaphoreOf;
+use crate::AsyncRuntime;
+use crate::LogId;
+use crate::OptionalSend;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+use crate::StorageIOError;
+use crate::StoredMembership;
+use crate::Vote;
+
+/// A synchronous log storage, such as one built on `std::fs` or `rusqlite`.
+///
+/// It is the blocking counterpart of [`RaftLogStorage`] and [`RaftLogReader`], and is used by
+/// wrapping it in a [`BlockingStorageAdapter`]. Every method is called in a blocking thread, thus
+/// it is fine to block on IO.
+pub trait BlockingRaftLogStorage<C>: Send + 'static
+where C: RaftTypeConfig
+{
+    /// Returns the last purged log id and the last log id, see
+    /// [`RaftLogStorage::get_log_state()`].
+    fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>>;
+
+    /// Returns the log entries in range `[start, end)`, see
+    /// [`RaftLogReader::try_get_log_entries()`].
+    fn try_get_log_entries(&mut self, start: u64, end: u64) -> Result<Vec<C::Entry>, StorageError<C::NodeId>>;
+
+    /// Returns the last saved vote.
+    fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>>;
+
+    /// Saves the vote, see [`RaftLogStorage::save_vote()`].
+    ///
+    /// The vote must be persisted on disk before returning.
+    fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Saves the committed log id, see [`RaftLogStorage::save_committed()`].
+    fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        let _ = committed;
+        Ok(())
+    }
+
+    /// Returns the last saved committed log id, see [`RaftLogStorage::read_committed()`].
+    fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        Ok(None)
+    }
+
+    /// Appends log entries.
+    ///
+    /// Unlike [`RaftLogStorage::append()`], the entries must be persisted on disk before returning.
+    fn append(&mut self, entries: Vec<C::Entry>) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Deletes the log entries since `log_id`, inclusive, see [`RaftLogStorage::truncate()`].
+    fn truncate(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Deletes the log entries upto `log_id`, inclusive, see [`RaftLogStorage::purge()`].
+    fn purge(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+}
+
+/// A synchronous state machine.
+///
+/// It is the blocking counterpart of [`RaftStateMachine`] and [`RaftSnapshotBuilder`], and is used
+/// by wrapping it in a [`BlockingStorageAdapter`]. Every method is called in a blocking thread,
+/// thus it is fine to block on IO.
+pub trait BlockingRaftStateMachine<C>: Send + 'static
+where C: RaftTypeConfig
+{
+    /// Returns the last applied log id and the last applied membership config, see
+    /// [`RaftStateMachine::applied_state()`].
+    fn applied_state(&mut self) -> Result<(Option<LogId<C::NodeId>>, StoredMembership<C>), StorageError<C::NodeId>>;
+
+    /// Applies the entries and returns one response for every entry, see
+    /// [`RaftStateMachine::apply()`].
+    fn apply(&mut self, entries: Vec<C::Entry>) -> Result<Vec<C::R>, StorageError<C::NodeId>>;
+
+    /// Builds a snapshot of the current state, see [`RaftSnapshotBuilder::build_snapshot()`].
+    ///
+    /// The state machine is not modified until the snapshot is built.
+    fn build_snapshot(&mut self) -> Result<Snapshot<C>, StorageError<C::NodeId>>;
+
+    /// Creates a blank snapshot to receive data into, see
+    /// [`RaftStateMachine::begin_receiving_snapshot()`].
+    fn begin_receiving_snapshot(&mut self) -> Result<Box<C::SnapshotData>, StorageError<C::NodeId>>;
+
+    /// Installs a received snapshot, see [`RaftStateMachine::install_snapshot()`].
+    fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C>,
+        snapshot: Box<C::SnapshotData>,
+    ) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Returns the last built or installed snapshot, see
+    /// [`RaftStateMachine::get_current_snapshot()`].
+    fn get_current_snapshot(&mut self) -> Result<Option<Snapshot<C>>, StorageError<C::NodeId>>;
+}
+
+/// Implements [`RaftLogStorage`] for a [`BlockingRaftLogStorage`] and [`RaftStateMachine`] for a
+/// [`BlockingRaftStateMachine`], by running every call in a blocking thread with
+/// [`AsyncRuntime::spawn_blocking()`].
+///
+/// Calls to the underlying storage are serialized. A blocking call acquires a permit from `pool`
+/// before it is spawned, so that the number of the threads blocked in storage is bounded by the
+/// permits of `pool`. Share one pool among the log storage and the state machine to bound the
+/// total number.
+///
+/// The log reader and the snapshot builder returned by the adapter are clones of it, that share
+/// the same underlying storage and pool.
+pub struct BlockingStorageAdapter<C, S>
+where
+    C: RaftTypeConfig,
+    S: Send + 'static,
+{
+    inner: Arc<Mutex<S>>,
+    pool: Arc<SemaphoreOf<C>>,
+    _p: PhantomData<C>,
+}
+
+impl<C, S> Clone for BlockingStorageAdapter<C, S>
+where
+    C: RaftTypeConfig,
+    S: Send + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            pool: self.pool.clone(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<C, S> BlockingStorageAdapter<C, S>
+where
+    C: RaftTypeConfig,
+    S: Send + 'static,
+{
+    /// Create an adapter that runs `storage` in blocking threads, bounded by the permits of `pool`.
+    pub fn new(storage: S, pool: Arc<SemaphoreOf<C>>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(storage)),
+            pool,
+            _p: PhantomData,
+        }
+    }
+
+    /// Create a pool that allows at most `n` blocking calls at the same time.
+    pub fn new_pool(n: usize) -> Arc<SemaphoreOf<C>> {
+        Arc::new(SemaphoreOf::<C>::new(n))
+    }
+
+    /// Run `f` with the underlying storage in a blocking thread.
+    ///
+    /// `to_err` builds the error to return if the blocking thread fails.
+    async fn run<T, F>(
+        &self,
+        f: F,
+        to_err: fn(AnyError) -> StorageIOError<C::NodeId>,
+    ) -> Result<T, StorageError<C::NodeId>>
+    where
+        F: FnOnce(&mut S) -> Result<T, StorageError<C::NodeId>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let mut storage = self.inner.clone().lock_owned().await;
+        let _permit = self.pool.clone().acquire_owned().await;
+
+        C::AsyncRuntime::spawn_blocking(move || f(&mut storage))
+            .await
+            .map_err(|e| to_err(AnyError::error(e)))?
+    }
+}
+
+impl<C, S> RaftLogReader<C> for BlockingStorageAdapter<C, S>
+where
+    C: RaftTypeConfig,
+    S: BlockingRaftLogStorage<C>,
+{
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + OptionalSend>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<C::Entry>, StorageError<C::NodeId>> {
+        let start = match range.start_bound() {
+            Bound::Included(x) => *x,
+            Bound::Excluded(x) => *x + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => *x + 1,
+            Bound::Excluded(x) => *x,
+            Bound::Unbounded => u64::MAX,
+        };
+
+        self.run(move |s| s.try_get_log_entries(start, end), StorageIOError::read_logs).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        self.run(|s| s.read_vote(), StorageIOError::read_vote).await
+    }
+}
+
+impl<C, S> RaftLogStorage<C> for BlockingStorageAdapter<C, S>
+where
+    C: RaftTypeConfig,
+    S: BlockingRaftLogStorage<C>,
+{
+    type LogReader = Self;
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.run(|s| s.get_log_state(), StorageIOError::read_logs).await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.clone()
+    }
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        let vote = *vote;
+        self.run(move |s| s.save_vote(&vote), StorageIOError::write_vote).await
+    }
+
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        self.run(move |s| s.save_committed(committed), StorageIOError::write).await
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        self.run(|s| s.read_committed(), StorageIOError::read).await
+    }
+
+    async fn append<I>(&mut self, entries: I, callback: LogFlushed<C>) -> Result<(), StorageError<C::NodeId>>
+    where
+        I: IntoIterator<Item = C::Entry> + OptionalSend,
+        I::IntoIter: OptionalSend,
+    {
+        let entries = entries.into_iter().collect::<Vec<_>>();
+        self.run(move |s| s.append(entries), StorageIOError::write_logs).await?;
+        callback.log_io_completed(Ok(()));
+        Ok(())
+    }
+
+    async fn truncate(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.run(move |s| s.truncate(log_id), StorageIOError::write_logs).await
+    }
+
+    async fn purge(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.run(move |s| s.purge(log_id), StorageIOError::write_logs).await
+    }
+}
+
+impl<C, S> RaftSnapshotBuilder<C> for BlockingStorageAdapter<C, S>
+where
+    C: RaftTypeConfig,
+    S: BlockingRaftStateMachine<C>,
+{
+    async fn build_snapshot(&mut self) -> Result<Snapshot<C>, StorageError<C::NodeId>> {
+        self.run(|s| s.build_snapshot(), |e| StorageIOError::write_snapshot(None, e)).await
+    }
+}
+
+impl<C, S> RaftStateMachine<C> for BlockingStorageAdapter<C, S>
+where
+    C: RaftTypeConfig,
+    S: BlockingRaftStateMachine<C>,
+{
+    type SnapshotBuilder = Self;
+
+    async fn applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, StoredMembership<C>), StorageError<C::NodeId>> {
+        self.run(|s| s.applied_state(), StorageIOError::read_state_machine).await
+    }
+
+    async fn apply<I>(&mut self, entries: I) -> Result<Vec<C::R>, StorageError<C::NodeId>>
+    where
+        I: IntoIterator<Item = C::Entry> + OptionalSend,
+        I::IntoIter: OptionalSend,
+    {
+        let entries = entries.into_iter().collect::<Vec<_>>();
+        self.run(move |s| s.apply(entries), StorageIOError::write_state_machine).await
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.clone()
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<C::SnapshotData>, StorageError<C::NodeId>> {
+        self.run(
+            |s| s.begin_receiving_snapshot(),
+            |e| StorageIOError::write_snapshot(None, e),
+        )
+        .await
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C>,
+        snapshot: Box<C::SnapshotData>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let meta = meta.clone();
+        self.run(
+            move |s| s.install_snapshot(&meta, snapshot),
+            |e| StorageIOError::write_snapshot(None, e),
+        )
+        .await
+    }
+
+    async fn get_current_snapshot(&mut self) -> Result<Option<Snapshot<C>>, StorageError<C::NodeId>> {
+        self.run(|s| s.get_current_snapshot(), |e| StorageIOError::read_snapshot(None, e)).await
+    }
+}
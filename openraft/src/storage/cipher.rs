@@ -0,0 +1,176 @@
+//! Encryption of the log entries and snapshots persisted by a storage.
+
+use std::io;
+
+use crate::OptionalSend;
+use crate::OptionalSync;
+
+/// The size of the key id prepended to a payload sealed by [`seal()`].
+pub const KEY_ID_SIZE: usize = 4;
+
+/// Encrypts the log entries and the snapshots before they are persisted, i.e., encryption at rest.
+///
+/// It is configured by [`RaftTypeConfig::StorageCipher`], and the default is [`NoEncryption`].
+///
+/// Openraft seals every log entry serialized by [`RaftLogStorage::encode_entry()`] with
+/// [`seal()`] before passing it to [`RaftLogStorage::append_vectored()`]. A storage that
+/// serializes entries by itself, or persists snapshots, seals the bytes with [`seal()`] too, and
+/// unseals the bytes it reads back with [`unseal()`] before deserializing them.
+///
+/// Openraft does not check what a storage writes: a storage reports that it seals everything it
+/// persists with [`RaftLogStorage::supports_storage_cipher()`] and
+/// [`RaftStateMachine::supports_storage_cipher()`]. If a cipher other than [`NoEncryption`] is
+/// configured, [`Raft::new()`] refuses to start unless both of them return `true`.
+///
+/// Every sealed payload is tagged with the id of the key it is encrypted with. New payloads are
+/// encrypted with [`Self::current_key_id()`], while an old payload is decrypted with the key it is
+/// tagged with, so that a key can be rotated without re-encrypting the data already persisted,
+/// as long as the old key is still available to [`Self::decrypt()`].
+///
+/// [`RaftTypeConfig::StorageCipher`]: crate::RaftTypeConfig::StorageCipher
+/// [`RaftLogStorage::encode_entry()`]: crate::storage::RaftLogStorage::encode_entry
+/// [`RaftLogStorage::append_vectored()`]: crate::storage::RaftLogStorage::append_vectored
+/// [`RaftLogStorage::supports_storage_cipher()`]: crate::storage::RaftLogStorage::supports_storage_cipher
+/// [`RaftStateMachine::supports_storage_cipher()`]: crate::storage::RaftStateMachine::supports_storage_cipher
+/// [`Raft::new()`]: crate::Raft::new
+pub trait StorageCipher: OptionalSend + OptionalSync + 'static {
+    /// Whether to encrypt at all.
+    ///
+    /// If it is `false`, [`seal()`] and [`unseal()`] return the payload as is, without a key id.
+    const ENABLED: bool = true;
+
+    /// The id of the key to encrypt new payloads with.
+    fn current_key_id() -> u32;
+
+    /// Encrypt a payload with the key identified by `key_id`.
+    fn encrypt(key_id: u32, data: &[u8]) -> io::Result<Vec<u8>>;
+
+    /// Decrypt a payload encrypted by [`Self::encrypt()`] with the key identified by `key_id`.
+    fn decrypt(key_id: u32, data: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+/// Stores the payload as is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoEncryption;
+
+impl StorageCipher for NoEncryption {
+    const ENABLED: bool = false;
+
+    fn current_key_id() -> u32 {
+        0
+    }
+
+    fn encrypt(_key_id: u32, data: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(data.to_vec())
+    }
+
+    fn decrypt(_key_id: u32, data: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(data.to_vec())
+    }
+}
+
+/// Encrypt a payload with the current key of `S`, and prepend the big-endian key id to it.
+pub fn seal<S>(data: Vec<u8>) -> io::Result<Vec<u8>>
+where S: StorageCipher {
+    if !S::ENABLED {
+        return Ok(data);
+    }
+
+    let key_id = S::current_key_id();
+    let encrypted = S::encrypt(key_id, &data)?;
+
+    let mut buf = Vec::with_capacity(KEY_ID_SIZE + encrypted.len());
+    buf.extend_from_slice(&key_id.to_be_bytes());
+    buf.extend_from_slice(&encrypted);
+    Ok(buf)
+}
+
+/// Decrypt a payload sealed by [`seal()`] with the key it is tagged with.
+pub fn unseal<S>(data: Vec<u8>) -> io::Result<Vec<u8>>
+where S: StorageCipher {
+    if !S::ENABLED {
+        return Ok(data);
+    }
+
+    let key_id = sealed_key_id(&data)?;
+    S::decrypt(key_id, &data[KEY_ID_SIZE..])
+}
+
+/// Returns the id of the key a payload sealed by [`seal()`] is encrypted with.
+///
+/// It is used to find out the data that is still encrypted with a retired key.
+pub fn sealed_key_id(data: &[u8]) -> io::Result<u32> {
+    let Some(id) = data.get(..KEY_ID_SIZE) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("sealed payload is too short: {} bytes", data.len()),
+        ));
+    };
+
+    // Safe unwrap: the slice has exactly KEY_ID_SIZE bytes.
+    Ok(u32::from_be_bytes(id.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering;
+
+    use super::seal;
+    use super::sealed_key_id;
+    use super::unseal;
+    use super::NoEncryption;
+    use super::StorageCipher;
+
+    static CURRENT_KEY: AtomicU32 = AtomicU32::new(1);
+
+    /// XOR every byte with the key id, for testing only.
+    struct XorCipher;
+
+    impl StorageCipher for XorCipher {
+        fn current_key_id() -> u32 {
+            CURRENT_KEY.load(Ordering::Relaxed)
+        }
+
+        fn encrypt(key_id: u32, data: &[u8]) -> io::Result<Vec<u8>> {
+            Ok(data.iter().map(|b| b ^ key_id as u8).collect())
+        }
+
+        fn decrypt(key_id: u32, data: &[u8]) -> io::Result<Vec<u8>> {
+            Self::encrypt(key_id, data)
+        }
+    }
+
+    #[test]
+    fn test_no_encryption() -> anyhow::Result<()> {
+        let sealed = seal::<NoEncryption>(b"foo".to_vec())?;
+        assert_eq!(b"foo".to_vec(), sealed);
+        assert_eq!(b"foo".to_vec(), unseal::<NoEncryption>(sealed)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_seal_with_key_rotation() -> anyhow::Result<()> {
+        let sealed1 = seal::<XorCipher>(b"foo".to_vec())?;
+        assert_eq!(1, sealed_key_id(&sealed1)?);
+        assert_ne!(b"foo".as_slice(), &sealed1[4..]);
+
+        CURRENT_KEY.store(2, Ordering::Relaxed);
+
+        let sealed2 = seal::<XorCipher>(b"bar".to_vec())?;
+        assert_eq!(2, sealed_key_id(&sealed2)?);
+
+        // The payload sealed with the old key is still readable.
+        assert_eq!(b"foo".to_vec(), unseal::<XorCipher>(sealed1)?);
+        assert_eq!(b"bar".to_vec(), unseal::<XorCipher>(sealed2)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unseal_too_short() -> anyhow::Result<()> {
+        let res = unseal::<XorCipher>(b"foo".to_vec());
+        assert_eq!(io::ErrorKind::InvalidData, res.unwrap_err().kind());
+        Ok(())
+    }
+}
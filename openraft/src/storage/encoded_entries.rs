@@ -0,0 +1,86 @@
+use std::io::IoSlice;
+
+use crate::RaftTypeConfig;
+
+/// A batch of log entries to append, along with every entry serialized to a buffer by
+/// [`RaftLogStorage::encode_entry()`].
+///
+/// It is passed to [`RaftLogStorage::append_vectored()`], so that a file based storage writes the
+/// whole batch with a single `writev()`, e.g., `file.write_vectored(&entries.io_slices())`.
+///
+/// [`RaftLogStorage::encode_entry()`]: crate::storage::RaftLogStorage::encode_entry
+/// [`RaftLogStorage::append_vectored()`]: crate::storage::RaftLogStorage::append_vectored
+pub struct EncodedEntries<C>
+where C: RaftTypeConfig
+{
+    entries: Vec<C::Entry>,
+    bufs: Vec<Vec<u8>>,
+}
+
+impl<C> EncodedEntries<C>
+where C: RaftTypeConfig
+{
+    /// Create a batch in which `bufs[i]` is the serialized `entries[i]`.
+    pub fn new(entries: Vec<C::Entry>, bufs: Vec<Vec<u8>>) -> Self {
+        debug_assert_eq!(entries.len(), bufs.len());
+        Self { entries, bufs }
+    }
+
+    /// The number of entries in this batch.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[C::Entry] {
+        &self.entries
+    }
+
+    /// The serialized entries, in the same order as [`Self::entries()`].
+    pub fn bufs(&self) -> &[Vec<u8>] {
+        &self.bufs
+    }
+
+    /// The total size in bytes of the serialized entries.
+    pub fn total_size(&self) -> usize {
+        self.bufs.iter().map(|b| b.len()).sum()
+    }
+
+    /// Returns an [`IoSlice`] for every serialized entry, to write them in one vectored write.
+    pub fn io_slices(&self) -> Vec<IoSlice<'_>> {
+        self.bufs.iter().map(|b| IoSlice::new(b)).collect()
+    }
+
+    pub fn into_entries(self) -> Vec<C::Entry> {
+        self.entries
+    }
+
+    pub fn into_parts(self) -> (Vec<C::Entry>, Vec<Vec<u8>>) {
+        (self.entries, self.bufs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::testing::UTConfig;
+    use crate::storage::EncodedEntries;
+    use crate::testing::blank_ent;
+
+    #[test]
+    fn test_encoded_entries() {
+        let entries = vec![blank_ent::<UTConfig>(1, 1, 1), blank_ent::<UTConfig>(1, 1, 2)];
+        let e = EncodedEntries::<UTConfig>::new(entries.clone(), vec![b"foo".to_vec(), b"ba".to_vec()]);
+
+        assert_eq!(2, e.len());
+        assert!(!e.is_empty());
+        assert_eq!(5, e.total_size());
+        assert_eq!(
+            vec![b"foo".as_slice(), b"ba".as_slice()],
+            e.io_slices().iter().map(|s| &s[..]).collect::<Vec<_>>()
+        );
+        assert_eq!(entries, e.into_entries());
+    }
+}
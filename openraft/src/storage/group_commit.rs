@@ -0,0 +1,261 @@
+//! A [`RaftLogStorage`] layer that flushes appended log entries to disk in groups.
+
+use std::fmt::Debug;
+use std::io;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::lock::Mutex;
+use openraft_macros::add_async_trait;
+
+use crate::storage::LogFlushed;
+use crate::storage::LogState;
+use crate::storage::RaftLogReader;
+use crate::storage::RaftLogStorage;
+use crate::storage::RaftLogStorageExt;
+use crate::type_config::alias::AsyncRuntimeOf;
+use crate::AsyncRuntime;
+use crate::LogId;
+use crate::OptionalSend;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+use crate::Vote;
+
+/// A [`RaftLogStorage`] that can flush the appended entries to disk separately.
+///
+/// To be wrapped in a [`GroupCommit`], [`RaftLogStorage::append()`] calls the callback once the
+/// entries are written, without waiting for them to be durable; [`Self::sync()`] makes all
+/// written entries durable, e.g., by calling `fsync()`.
+#[add_async_trait]
+pub trait RaftLogSync<C>: RaftLogStorage<C>
+where C: RaftTypeConfig
+{
+    /// Flush all entries written by [`RaftLogStorage::append()`] to disk.
+    async fn sync(&mut self) -> Result<(), StorageError<C::NodeId>>;
+}
+
+/// When to flush the log entries appended to a [`GroupCommit`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FsyncPolicy {
+    /// Flush when this many appended entries are not flushed.
+    ///
+    /// `1` flushes every append, as if there is no group commit.
+    pub max_entries: u64,
+
+    /// Flush when the first appended entry that is not flushed has waited for this long.
+    pub max_delay: Duration,
+}
+
+impl Default for FsyncPolicy {
+    fn default() -> Self {
+        Self {
+            max_entries: 1024,
+            max_delay: Duration::from_millis(2),
+        }
+    }
+}
+
+/// Wraps a [`RaftLogSync`] storage and flushes the appended entries by group, in order to trade
+/// latency for throughput.
+///
+/// The callback of an append is called only after the entries are flushed according to the
+/// [`FsyncPolicy`]. Because Openraft counts the leader's own log as replicated only when the
+/// callback is called, an entry is committed only when it is durable on the leader, i.e., a
+/// larger group adds latency to every write but reduces the number of flushes.
+///
+/// Pending entries are flushed before any other write operation, such as
+/// [`RaftLogStorage::truncate()`], [`RaftLogStorage::purge()`] or
+/// [`RaftLogStorage::save_vote()`].
+pub struct GroupCommit<C, LS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogSync<C>,
+{
+    policy: FsyncPolicy,
+    shared: Arc<Mutex<Shared<C, LS>>>,
+
+    /// Whether the wrapped store encrypts the entries, read before it is moved into `shared`.
+    supports_storage_cipher: bool,
+}
+
+struct Shared<C, LS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogSync<C>,
+{
+    store: LS,
+
+    /// The callbacks of appends that are written but not flushed.
+    pending: Vec<LogFlushed<C>>,
+
+    /// The number of entries that are written but not flushed.
+    pending_entries: u64,
+
+    /// Incremented by every flush, so that a timer set before a flush does nothing.
+    generation: u64,
+
+    /// Whether a timer is set to flush the pending entries.
+    timer_set: bool,
+}
+
+impl<C, LS> Shared<C, LS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogSync<C>,
+{
+    /// Flush pending entries and call their callbacks.
+    async fn flush(&mut self) -> Result<(), StorageError<C::NodeId>> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        tracing::debug!(entries = self.pending_entries, "group commit: flush");
+
+        let res = self.store.sync().await;
+
+        self.generation += 1;
+        self.timer_set = false;
+        self.pending_entries = 0;
+
+        for callback in self.pending.drain(..) {
+            let r = match &res {
+                Ok(()) => Ok(()),
+                Err(e) => Err(io::Error::new(io::ErrorKind::Other, e.to_string())),
+            };
+            callback.log_io_completed(r);
+        }
+
+        res
+    }
+}
+
+impl<C, LS> GroupCommit<C, LS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogSync<C>,
+{
+    pub fn new(store: LS, policy: FsyncPolicy) -> Self {
+        Self {
+            policy,
+            supports_storage_cipher: store.supports_storage_cipher(),
+            shared: Arc::new(Mutex::new(Shared {
+                store,
+                pending: vec![],
+                pending_entries: 0,
+                generation: 0,
+                timer_set: false,
+            })),
+        }
+    }
+
+    /// Set a timer to flush the pending entries after [`FsyncPolicy::max_delay`].
+    fn set_timer(&self, generation: u64) {
+        let shared = self.shared.clone();
+        let delay = self.policy.max_delay;
+
+        let _handle = AsyncRuntimeOf::<C>::spawn(async move {
+            AsyncRuntimeOf::<C>::sleep(delay).await;
+
+            let mut sh = shared.lock().await;
+            if sh.generation != generation {
+                return;
+            }
+            if let Err(e) = sh.flush().await {
+                tracing::error!(error = display(&e), "group commit: failed to flush");
+            }
+        });
+    }
+}
+
+impl<C, LS> RaftLogReader<C> for GroupCommit<C, LS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogSync<C>,
+{
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + OptionalSend>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<C::Entry>, StorageError<C::NodeId>> {
+        self.shared.lock().await.store.try_get_log_entries(range).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        self.shared.lock().await.store.read_vote().await
+    }
+}
+
+impl<C, LS> RaftLogStorage<C> for GroupCommit<C, LS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogSync<C>,
+{
+    type LogReader = LS::LogReader;
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.shared.lock().await.store.get_log_state().await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.shared.lock().await.store.get_log_reader().await
+    }
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        let mut sh = self.shared.lock().await;
+        sh.flush().await?;
+        sh.store.save_vote(vote).await
+    }
+
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        self.shared.lock().await.store.save_committed(committed).await
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        self.shared.lock().await.store.read_committed().await
+    }
+
+    async fn append<I>(&mut self, entries: I, callback: LogFlushed<C>) -> Result<(), StorageError<C::NodeId>>
+    where
+        I: IntoIterator<Item = C::Entry> + OptionalSend,
+        I::IntoIter: OptionalSend,
+    {
+        let entries = entries.into_iter().collect::<Vec<_>>();
+        let n = entries.len() as u64;
+
+        let mut sh = self.shared.lock().await;
+
+        sh.store.blocking_append(entries).await?;
+
+        sh.pending.push(callback);
+        sh.pending_entries += n;
+
+        if sh.pending_entries >= self.policy.max_entries {
+            sh.flush().await?;
+        } else if !sh.timer_set {
+            sh.timer_set = true;
+            self.set_timer(sh.generation);
+        }
+
+        Ok(())
+    }
+
+    async fn appendable_count(&mut self, entries: &[C::Entry]) -> usize {
+        self.shared.lock().await.store.appendable_count(entries).await
+    }
+
+    fn supports_storage_cipher(&self) -> bool {
+        self.supports_storage_cipher
+    }
+
+    async fn truncate(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        let mut sh = self.shared.lock().await;
+        sh.flush().await?;
+        sh.store.truncate(log_id).await
+    }
+
+    async fn purge(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        let mut sh = self.shared.lock().await;
+        sh.flush().await?;
+        sh.store.purge(log_id).await
+    }
+}
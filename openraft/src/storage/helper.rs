@@ -1,6 +1,7 @@
 use std::marker::PhantomData;
 use std::sync::Arc;
 
+use crate::config::LogCorruptionPolicy;
 use crate::display_ext::DisplayOptionExt;
 use crate::engine::LogIdList;
 use crate::entry::RaftPayload;
@@ -10,10 +11,11 @@ use crate::raft_state::IOState;
 use crate::storage::RaftLogReaderExt;
 use crate::storage::RaftLogStorage;
 use crate::storage::RaftStateMachine;
-use crate::type_config::alias::InstantOf;
 use crate::utime::UTime;
+use crate::AsyncRuntime;
+use crate::CommittedLeaderId;
 use crate::EffectiveMembership;
-use crate::Instant;
+use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::MembershipState;
 use crate::RaftSnapshotBuilder;
@@ -32,6 +34,7 @@ where
 {
     pub(crate) log_store: &'a mut LS,
     pub(crate) state_machine: &'a mut SM,
+    corruption_policy: LogCorruptionPolicy,
     _p: PhantomData<C>,
 }
 
@@ -47,10 +50,19 @@ where
         Self {
             log_store: sto,
             state_machine: sm,
+            corruption_policy: LogCorruptionPolicy::default(),
             _p: Default::default(),
         }
     }
 
+    /// Set what to do if a corrupted log entry is found by [`Self::get_initial_state()`].
+    ///
+    /// By default it is [`LogCorruptionPolicy::Halt`].
+    pub fn log_corruption_policy(mut self, policy: LogCorruptionPolicy) -> Self {
+        self.corruption_policy = policy;
+        self
+    }
+
     // TODO: let RaftStore store node-id.
     //       To achieve this, RaftLogStorage must store node-id
     //       To achieve this, RaftLogStorage has to provide API to initialize with a node id and API to
@@ -71,6 +83,12 @@ where
 
         let (mut last_applied, _) = self.state_machine.applied_state().await?;
 
+        let truncated = self.verify_log(std::cmp::max(last_applied, last_purged_log_id), last_log_id).await?;
+        if truncated {
+            last_log_id = self.log_store.get_log_state().await?.last_log_id;
+            committed = std::cmp::min(committed, last_log_id);
+        }
+
         tracing::info!(
             vote = display(&vote),
             last_purged_log_id = display(last_purged_log_id.display()),
@@ -94,7 +112,9 @@ where
             tracing::info!("re-apply log {}..{} to state machine", start, end);
 
             let entries = self.log_store.get_log_entries(start..end).await?;
+            self.state_machine.begin_apply_batch().await?;
             self.state_machine.apply(entries).await?;
+            self.state_machine.commit_apply_batch(committed.unwrap()).await?;
 
             last_applied = committed;
         }
@@ -149,7 +169,7 @@ where
             last_purged_log_id,
         );
 
-        let now = InstantOf::<C>::now();
+        let now = C::AsyncRuntime::now();
 
         Ok(RaftState {
             committed: last_applied,
@@ -169,9 +189,64 @@ where
             io_state,
             snapshot_streaming: None,
             purge_upto: last_purged_log_id,
+            log_retention: Default::default(),
         })
     }
 
+    /// Read the log entries in `(since, last_log_id]` to find a corrupted one, i.e., a read returns
+    /// [`StorageError::Corrupted`].
+    ///
+    /// If the policy is [`LogCorruptionPolicy::TruncateToLastValid`], the log since the first
+    /// corrupted entry is truncated and it returns `true`. Otherwise the corruption error is
+    /// returned.
+    async fn verify_log(
+        &mut self,
+        since: Option<LogId<C::NodeId>>,
+        last_log_id: Option<LogId<C::NodeId>>,
+    ) -> Result<bool, StorageError<C::NodeId>> {
+        let end = last_log_id.next_index();
+        let step = 64;
+
+        let mut start = since.next_index();
+
+        tracing::info!("verify log: [{}..{})", start, end);
+
+        while start < end {
+            let step_end = std::cmp::min(start + step, end);
+
+            let err = match self.log_store.try_get_log_entries(start..step_end).await {
+                Ok(_) => {
+                    start = step_end;
+                    continue;
+                }
+                Err(e) => e,
+            };
+
+            let index = match &err {
+                StorageError::Corrupted { source } => source.index,
+                _ => return Err(err),
+            };
+
+            if self.corruption_policy == LogCorruptionPolicy::Halt {
+                tracing::error!("{}; halt", err);
+                return Err(err);
+            }
+
+            tracing::warn!("{}; truncate log since {}", err, index);
+
+            // The leader id of the corrupted entry is unknown, and only the index is used to
+            // truncate logs.
+            let log_id = LogId {
+                leader_id: CommittedLeaderId::default(),
+                index,
+            };
+            self.log_store.truncate(log_id).await?;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
     /// Returns the last 2 membership config found in log or state machine.
     ///
     /// A raft node needs to store at most 2 membership config log:
@@ -0,0 +1,82 @@
+//! Migrate the data of a storage to another storage.
+
+use crate::display_ext::DisplayOptionExt;
+use crate::storage::RaftLogReaderExt;
+use crate::storage::RaftLogStorage;
+use crate::storage::RaftLogStorageExt;
+use crate::storage::RaftSnapshotBuilder;
+use crate::storage::RaftStateMachine;
+use crate::LogIdOptionExt;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+
+/// The max number of log entries to read from the source storage at a time.
+const MIGRATE_CHUNK_SIZE: u64 = 1024;
+
+/// Copy all the data in `src_log` and `src_sm` to `dst_log` and `dst_sm`.
+///
+/// It is used to upgrade an application to another storage layout, such as moving the data of a
+/// storage that keeps the log and the state machine together in one DB to a separate
+/// [`RaftLogStorage`] and [`RaftStateMachine`], without manual data surgery. Both the source and
+/// the destination only have to implement the storage traits.
+///
+/// The following data are copied:
+/// - The vote.
+/// - The state machine, by building a snapshot from `src_sm` and installing it into `dst_sm`.
+/// - The log entries that are not purged, and the last purged log id.
+/// - The committed log id, if it is saved by `src_log`.
+///
+/// The destination storage must be empty, and the source storage must not be used by a running
+/// [`Raft`](`crate::Raft`) during migration.
+pub async fn migrate<C, LS1, SM1, LS2, SM2>(
+    src_log: &mut LS1,
+    src_sm: &mut SM1,
+    dst_log: &mut LS2,
+    dst_sm: &mut SM2,
+) -> Result<(), StorageError<C::NodeId>>
+where
+    C: RaftTypeConfig,
+    LS1: RaftLogStorage<C>,
+    SM1: RaftStateMachine<C>,
+    LS2: RaftLogStorage<C>,
+    SM2: RaftStateMachine<C>,
+{
+    if let Some(vote) = src_log.read_vote().await? {
+        tracing::info!("migrate vote: {}", vote);
+        dst_log.save_vote(&vote).await?;
+    }
+
+    let snapshot = src_sm.get_snapshot_builder().await.build_snapshot().await?;
+    if snapshot.meta.last_log_id.is_some() {
+        tracing::info!("migrate state machine: {}", snapshot.meta);
+        dst_sm.install_snapshot(&snapshot.meta, snapshot.snapshot).await?;
+    }
+
+    let log_state = src_log.get_log_state().await?;
+    tracing::info!(
+        "migrate logs: last_purged: {}, last: {}",
+        log_state.last_purged_log_id.display(),
+        log_state.last_log_id.display()
+    );
+
+    let mut start = log_state.last_purged_log_id.next_index();
+    let end = log_state.last_log_id.next_index();
+    while start < end {
+        let chunk_end = std::cmp::min(start + MIGRATE_CHUNK_SIZE, end);
+        let entries = src_log.get_log_entries(start..chunk_end).await?;
+        dst_log.blocking_append(entries).await?;
+        start = chunk_end;
+    }
+
+    if let Some(last_purged) = log_state.last_purged_log_id {
+        dst_log.purge(last_purged).await?;
+    }
+
+    let committed = src_log.read_committed().await?;
+    if committed.is_some() {
+        tracing::info!("migrate committed: {}", committed.display());
+        dst_log.save_committed(committed).await?;
+    }
+
+    Ok(())
+}
@@ -1,22 +1,63 @@
 //! The Raft storage interface and data types.
 
+#[cfg(not(feature = "singlethreaded"))] mod blocking;
 mod callback;
+mod cipher;
+mod encoded_entries;
+mod group_commit;
 mod helper;
 mod log_store_ext;
+mod migrate;
+mod prefixed;
+pub mod session;
+mod snapshot_build;
+mod snapshot_dir;
 mod snapshot_signature;
 mod v2;
+mod vote_storage;
 
 use std::fmt;
 use std::fmt::Debug;
 use std::ops::RangeBounds;
 
+#[cfg(not(feature = "singlethreaded"))]
+pub use blocking::BlockingRaftLogStorage;
+#[cfg(not(feature = "singlethreaded"))]
+pub use blocking::BlockingRaftStateMachine;
+#[cfg(not(feature = "singlethreaded"))]
+pub use blocking::BlockingStorageAdapter;
+pub use cipher::seal;
+pub use cipher::sealed_key_id;
+pub use cipher::unseal;
+pub use cipher::NoEncryption;
+pub use cipher::StorageCipher;
+pub use cipher::KEY_ID_SIZE;
+pub use encoded_entries::EncodedEntries;
+pub use group_commit::FsyncPolicy;
+pub use group_commit::GroupCommit;
+pub use group_commit::RaftLogSync;
 pub use helper::StorageHelper;
 pub use log_store_ext::RaftLogReaderExt;
+pub use migrate::migrate;
 use openraft_macros::add_async_trait;
+pub use prefixed::group_key_prefix;
+pub use prefixed::KvOp;
+pub use prefixed::KvValue;
+pub use prefixed::PrefixedLogStorage;
+pub use prefixed::SharedKvStore;
+pub use session::ClientSession;
+pub use session::SessionTable;
+pub use snapshot_build::SnapshotBuildProgress;
+pub use snapshot_dir::SnapshotDir;
+pub use snapshot_dir::SnapshotFileMeta;
+pub use snapshot_dir::SnapshotManifest;
 pub use snapshot_signature::SnapshotSignature;
 pub use v2::RaftLogStorage;
 pub use v2::RaftLogStorageExt;
 pub use v2::RaftStateMachine;
+pub use vote_storage::RaftVoteStorage;
+pub use vote_storage::WithVoteReader;
+pub use vote_storage::WithVoteStorage;
 
 use crate::display_ext::DisplayOption;
 use crate::raft_types::SnapshotId;
@@ -50,6 +91,21 @@ where C: RaftTypeConfig
     /// Caveat: even when two snapshot is built with the same `last_log_id`, they still could be
     /// different in bytes.
     pub snapshot_id: SnapshotId,
+
+    /// The log id the state machine has to be at before installing this snapshot, if it is a
+    /// delta snapshot built by [`RaftSnapshotBuilder::build_delta()`].
+    ///
+    /// It is `None` for a full snapshot.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub delta_base: Option<LogId<C::NodeId>>,
+
+    /// CRC32 of the entire snapshot data.
+    ///
+    /// It is filled by the sender when transmitting the snapshot with
+    /// [`Chunked`](`crate::network::snapshot_transport::Chunked`), and the receiver verifies the
+    /// received data with it before installing. `None` means it is not checked.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub checksum: Option<u32>,
 }
 
 impl<C> fmt::Display for SnapshotMeta<C>
@@ -58,11 +114,16 @@ where C: RaftTypeConfig
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{snapshot_id: {}, last_log:{}, last_membership: {}}}",
+            "{{snapshot_id: {}, last_log:{}, last_membership: {}",
             self.snapshot_id,
             DisplayOption(&self.last_log_id),
             self.last_membership
-        )
+        )?;
+
+        if let Some(base) = &self.delta_base {
+            write!(f, ", delta_base: {}", base)?;
+        }
+        write!(f, "}}")
     }
 }
 
@@ -81,6 +142,11 @@ where C: RaftTypeConfig
     pub fn last_log_id(&self) -> Option<&LogId<C::NodeId>> {
         self.last_log_id.as_ref()
     }
+
+    /// Returns `true` if it is a delta snapshot, i.e., [`Self::delta_base`] is set.
+    pub fn is_delta(&self) -> bool {
+        self.delta_base.is_some()
+    }
 }
 
 /// The data associated with the current snapshot.
@@ -199,8 +265,53 @@ where C: RaftTypeConfig
     /// - Performing log compaction, e.g. merge log entries that operates on the same key, like a
     ///   LSM-tree does,
     /// - or by fetching a snapshot from the state machine.
+    ///
+    /// CPU or IO intensive work, such as serializing the state machine, should be offloaded with
+    /// [`AsyncRuntime::spawn_blocking`] so that it does not stall other tasks on the executor.
+    /// Alternatively, enable [`Config::offload_snapshot_build`] to run the entire building in a
+    /// blocking thread.
+    ///
+    /// [`AsyncRuntime::spawn_blocking`]: crate::AsyncRuntime::spawn_blocking
+    /// [`Config::offload_snapshot_build`]: crate::Config::offload_snapshot_build
     async fn build_snapshot(&mut self) -> Result<Snapshot<C>, StorageError<C::NodeId>>;
 
+    /// Build a snapshot like [`Self::build_snapshot()`], reporting the progress to `progress`.
+    ///
+    /// Openraft calls this method instead of [`Self::build_snapshot()`], and drops the returned
+    /// future when the build is cancelled with [`SnapshotBuildProgress::cancel()`]. An
+    /// implementation that does work outside of the future, such as in a blocking thread, should
+    /// check [`SnapshotBuildProgress::is_cancelled()`] to stop it.
+    ///
+    /// The default implementation calls [`Self::build_snapshot()`] without reporting progress.
+    async fn build_snapshot_with_progress(
+        &mut self,
+        progress: SnapshotBuildProgress<C>,
+    ) -> Result<Snapshot<C>, StorageError<C::NodeId>> {
+        let _ = progress;
+        self.build_snapshot().await
+    }
+
+    /// Build a delta snapshot that contains only the changes to the state machine after log id
+    /// `since`, or return `None` if it is not supported or the changes are no longer available.
+    ///
+    /// When a follower lags behind the purged logs, the leader sends it a delta snapshot built
+    /// since the last log id it has in common with the follower, instead of a full snapshot.
+    /// Openraft falls back to the current full snapshot if it returns `None`.
+    ///
+    /// The follower installs the delta with [`RaftStateMachine::install_delta_snapshot()`] when
+    /// its state machine is at a log id in range `[since, meta.last_log_id]`, which is not
+    /// necessarily `since`. Thus the delta must be applicable to the state at any log id in this
+    /// range, e.g., it contains the last value of every key changed after `since`, including the
+    /// deleted ones.
+    ///
+    /// [`SnapshotMeta::delta_base`] of the returned snapshot is set to `since` by Openraft.
+    ///
+    /// [`RaftStateMachine::install_delta_snapshot()`]: crate::storage::RaftStateMachine::install_delta_snapshot
+    async fn build_delta(&mut self, since: LogId<C::NodeId>) -> Result<Option<Snapshot<C>>, StorageError<C::NodeId>> {
+        let _ = since;
+        Ok(None)
+    }
+
     // NOTES:
     // This interface is geared toward small file-based snapshots. However, not all snapshots can
     // be easily represented as a file. Probably a more generic interface will be needed to address
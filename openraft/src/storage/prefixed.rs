@@ -0,0 +1,266 @@
+//! Store the logs of multiple Raft groups in one shared key-value store.
+
+use std::fmt::Debug;
+use std::ops::Bound;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+use openraft_macros::add_async_trait;
+
+use crate::storage::LogFlushed;
+use crate::storage::LogState;
+use crate::storage::RaftLogReader;
+use crate::storage::RaftLogStorage;
+use crate::LogId;
+use crate::OptionalSend;
+use crate::OptionalSync;
+use crate::RaftLogId;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+use crate::Vote;
+
+/// The key of the vote of a group, following the group prefix.
+const VOTE_KEY: u8 = 0x01;
+/// The key of the committed log id of a group, following the group prefix.
+const COMMITTED_KEY: u8 = 0x02;
+/// The key of the last purged log id of a group, following the group prefix.
+const PURGED_KEY: u8 = 0x03;
+/// The prefix of the log entries of a group, following the group prefix and followed by the
+/// big-endian log index.
+const LOG_KEY: u8 = 0x10;
+
+/// A value stored in a [`SharedKvStore`] by a [`PrefixedLogStorage`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum KvValue<C>
+where C: RaftTypeConfig
+{
+    Entry(C::Entry),
+    Vote(Vote<C::NodeId>),
+    LogId(LogId<C::NodeId>),
+}
+
+/// An operation in a batch written to a [`SharedKvStore`].
+#[derive(Debug)]
+pub enum KvOp<C>
+where C: RaftTypeConfig
+{
+    Put(Vec<u8>, KvValue<C>),
+
+    Delete(Vec<u8>),
+
+    /// Delete the keys in range `[start, end)`.
+    DeleteRange(Vec<u8>, Vec<u8>),
+}
+
+/// An ordered key-value store shared by the [`PrefixedLogStorage`]s of multiple Raft groups,
+/// such as a RocksDB instance.
+///
+/// Keys are compared as byte strings. The implementation serializes [`KvValue`] in its own way.
+#[add_async_trait]
+pub trait SharedKvStore<C>: OptionalSend + OptionalSync + 'static
+where C: RaftTypeConfig
+{
+    /// Get the value of a key.
+    async fn get(&self, key: &[u8]) -> Result<Option<KvValue<C>>, StorageError<C::NodeId>>;
+
+    /// Returns the values of the keys in range `[start, end)`, in key order.
+    async fn range(&self, start: &[u8], end: &[u8]) -> Result<Vec<KvValue<C>>, StorageError<C::NodeId>>;
+
+    /// Returns the value of the greatest key in range `[start, end)`.
+    async fn last(&self, start: &[u8], end: &[u8]) -> Result<Option<KvValue<C>>, StorageError<C::NodeId>>;
+
+    /// Apply a batch of operations atomically, and persist it on disk before returning.
+    async fn write(&self, ops: Vec<KvOp<C>>) -> Result<(), StorageError<C::NodeId>>;
+}
+
+/// Returns the prefix of all the keys of Raft group `group_id` in a [`SharedKvStore`].
+///
+/// The application can store the data of the state machine of a group under the prefix too, with
+/// a byte following the prefix that is not used by [`PrefixedLogStorage`], i.e., `>= 0x80`.
+pub fn group_key_prefix(group_id: u64) -> Vec<u8> {
+    group_id.to_be_bytes().to_vec()
+}
+
+/// A [`RaftLogStorage`] of a Raft group, which stores the data in a [`SharedKvStore`] with the
+/// keys prefixed by the group id.
+///
+/// It allows to run many Raft groups in one process, i.e., multi-raft, with a single underlying
+/// store instead of one per group. Every group only reads and writes the keys with its own
+/// prefix, and purging or truncating the log of a group deletes only the entries of this group.
+pub struct PrefixedLogStorage<C, K>
+where
+    C: RaftTypeConfig,
+    K: SharedKvStore<C>,
+{
+    kv: Arc<K>,
+    prefix: Vec<u8>,
+    _p: std::marker::PhantomData<C>,
+}
+
+impl<C, K> Clone for PrefixedLogStorage<C, K>
+where
+    C: RaftTypeConfig,
+    K: SharedKvStore<C>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            kv: self.kv.clone(),
+            prefix: self.prefix.clone(),
+            _p: Default::default(),
+        }
+    }
+}
+
+impl<C, K> PrefixedLogStorage<C, K>
+where
+    C: RaftTypeConfig,
+    K: SharedKvStore<C>,
+{
+    /// Create a log storage of Raft group `group_id` in the shared store `kv`.
+    pub fn new(kv: Arc<K>, group_id: u64) -> Self {
+        Self {
+            kv,
+            prefix: group_key_prefix(group_id),
+            _p: Default::default(),
+        }
+    }
+
+    fn key(&self, k: u8) -> Vec<u8> {
+        let mut key = self.prefix.clone();
+        key.push(k);
+        key
+    }
+
+    fn log_key(&self, index: u64) -> Vec<u8> {
+        let mut key = self.key(LOG_KEY);
+        key.extend_from_slice(&index.to_be_bytes());
+        key
+    }
+
+    /// The end of the log keys of this group, exclusive.
+    fn log_key_end(&self) -> Vec<u8> {
+        self.key(LOG_KEY + 1)
+    }
+
+    async fn get_log_id(&self, k: u8) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        match self.kv.get(&self.key(k)).await? {
+            Some(KvValue::LogId(log_id)) => Ok(Some(log_id)),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn into_entry<C>(v: KvValue<C>) -> Option<C::Entry>
+where C: RaftTypeConfig {
+    match v {
+        KvValue::Entry(entry) => Some(entry),
+        _ => None,
+    }
+}
+
+impl<C, K> RaftLogReader<C> for PrefixedLogStorage<C, K>
+where
+    C: RaftTypeConfig,
+    K: SharedKvStore<C>,
+{
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + OptionalSend>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<C::Entry>, StorageError<C::NodeId>> {
+        let start = match range.start_bound() {
+            Bound::Included(x) => self.log_key(*x),
+            Bound::Excluded(x) => self.log_key(*x + 1),
+            Bound::Unbounded => self.log_key(0),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => self.log_key(*x + 1),
+            Bound::Excluded(x) => self.log_key(*x),
+            Bound::Unbounded => self.log_key_end(),
+        };
+
+        let values = self.kv.range(&start, &end).await?;
+        Ok(values.into_iter().filter_map(into_entry).collect())
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        match self.kv.get(&self.key(VOTE_KEY)).await? {
+            Some(KvValue::Vote(vote)) => Ok(Some(vote)),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<C, K> RaftLogStorage<C> for PrefixedLogStorage<C, K>
+where
+    C: RaftTypeConfig,
+    K: SharedKvStore<C>,
+{
+    type LogReader = Self;
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        let last_purged_log_id = self.get_log_id(PURGED_KEY).await?;
+
+        let last = self.kv.last(&self.log_key(0), &self.log_key_end()).await?;
+        let last_log_id = last.and_then(into_entry).map(|e| *e.get_log_id());
+
+        Ok(LogState {
+            last_purged_log_id,
+            last_log_id: std::cmp::max(last_log_id, last_purged_log_id),
+        })
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.clone()
+    }
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.kv.write(vec![KvOp::Put(self.key(VOTE_KEY), KvValue::Vote(*vote))]).await
+    }
+
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        let op = match committed {
+            Some(log_id) => KvOp::Put(self.key(COMMITTED_KEY), KvValue::LogId(log_id)),
+            None => KvOp::Delete(self.key(COMMITTED_KEY)),
+        };
+        self.kv.write(vec![op]).await
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        self.get_log_id(COMMITTED_KEY).await
+    }
+
+    async fn append<I>(&mut self, entries: I, callback: LogFlushed<C>) -> Result<(), StorageError<C::NodeId>>
+    where
+        I: IntoIterator<Item = C::Entry> + OptionalSend,
+        I::IntoIter: OptionalSend,
+    {
+        let ops = entries
+            .into_iter()
+            .map(|entry| KvOp::Put(self.log_key(entry.get_log_id().index), KvValue::Entry(entry)))
+            .collect::<Vec<_>>();
+
+        self.kv.write(ops).await?;
+        callback.log_io_completed(Ok(()));
+        Ok(())
+    }
+
+    async fn truncate(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        tracing::debug!("truncate: [{}, +oo)", log_id);
+
+        let op = KvOp::DeleteRange(self.log_key(log_id.index), self.log_key_end());
+        self.kv.write(vec![op]).await
+    }
+
+    async fn purge(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        tracing::debug!("purge: [0, {}]", log_id);
+
+        // Save the purged log id along with the deletion, so that the last log id is still known
+        // when all the entries are purged.
+        let ops = vec![
+            KvOp::Put(self.key(PURGED_KEY), KvValue::LogId(log_id)),
+            KvOp::DeleteRange(self.log_key(0), self.log_key(log_id.index + 1)),
+        ];
+        self.kv.write(ops).await
+    }
+}
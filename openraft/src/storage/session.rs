@@ -0,0 +1,275 @@
+//! Client sessions for exactly-once apply semantics.
+//!
+//! A client may retry a write whose response is lost, e.g., when the leader crashes after
+//! committing it. Without deduplication the state machine applies the write twice. A
+//! [`SessionTable`] is embedded in the state machine to detect such a replayed proposal at apply
+//! time and return the cached response instead:
+//!
+//! - A client registers a session with a write that calls [`SessionTable::register()`] when
+//!   applied. The id of the session is the index of this log entry, thus it is the same on every
+//!   node.
+//! - The client attaches a [`ClientSession`] to every following write, with a serial number that
+//!   increases by one for every new request, and that is reused when a request is retried.
+//! - The state machine applies such a write with [`SessionTable::apply()`], which runs the write
+//!   only if its serial has not been applied.
+//!
+//! The table is part of the state machine: it must be saved in and restored from snapshots, so
+//! that a node that installs a snapshot still detects the replayed proposals.
+//!
+//! ```ignore
+//! match req {
+//!     Request::RegisterSession => Response::Session(sm.sessions.register(entry.log_id.index)),
+//!     Request::Set { session, key, value } => {
+//!         let log_index = entry.log_id.index;
+//!         match sm.sessions.apply(&session, log_index, || sm.data.insert(key, value)) {
+//!             Ok(prev) => Response::Set(prev),
+//!             Err(e) => Response::SessionError(e),
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::error::SessionError;
+
+/// The default maximum number of sessions kept by a [`SessionTable`].
+pub const DEFAULT_MAX_SESSIONS: usize = 4096;
+
+/// Identifies a write request of a client session, attached to the application data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ClientSession {
+    /// The id returned by [`SessionTable::register()`].
+    pub session_id: u64,
+
+    /// The serial number of this request in the session.
+    pub serial: u64,
+
+    /// The client has received the responses to all the requests with a serial number smaller
+    /// than this, thus their cached responses can be discarded.
+    pub acked: u64,
+}
+
+impl fmt::Display for ClientSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "session-{}:{}(acked:{})", self.session_id, self.serial, self.acked)
+    }
+}
+
+impl ClientSession {
+    /// Create a request of a client that has at most one request in flight, i.e., it has
+    /// received the responses to all the previous requests.
+    pub fn new(session_id: u64, serial: u64) -> Self {
+        Self {
+            session_id,
+            serial,
+            acked: serial,
+        }
+    }
+
+    /// Set the serial number below which all the responses have been received by the client.
+    pub fn with_acked(mut self, acked: u64) -> Self {
+        self.acked = acked;
+        self
+    }
+}
+
+/// The applied requests of a session, which the client has not acknowledged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct SessionState<R> {
+    /// The index of the last log entry applied in this session, used to evict idle sessions.
+    last_active: u64,
+
+    /// The largest [`ClientSession::acked`] received.
+    acked: u64,
+
+    /// The responses of the applied requests with a serial number `>= acked`.
+    responses: BTreeMap<u64, R>,
+}
+
+/// Deduplicates the write requests of client sessions at apply time.
+///
+/// It must be updated only by applying log entries, so that it is the same on every node. When
+/// the number of sessions exceeds the limit, the session that has been idle for the most log
+/// entries is evicted, and a request in it fails with [`SessionError::SessionExpired`].
+///
+/// See the [module documentation](`crate::storage::session`) for how to use it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SessionTable<R> {
+    max_sessions: usize,
+    sessions: BTreeMap<u64, SessionState<R>>,
+}
+
+impl<R> Default for SessionTable<R> {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_SESSIONS)
+    }
+}
+
+impl<R> SessionTable<R> {
+    /// Create a table that keeps at most `max_sessions` sessions.
+    pub fn new(max_sessions: usize) -> Self {
+        Self {
+            max_sessions: std::cmp::max(max_sessions, 1),
+            sessions: BTreeMap::new(),
+        }
+    }
+
+    /// Register a new session with the log entry at `log_index`, and return the session id.
+    ///
+    /// If the table is full, the least recently active session is evicted.
+    pub fn register(&mut self, log_index: u64) -> u64 {
+        self.sessions.insert(log_index, SessionState {
+            last_active: log_index,
+            acked: 0,
+            responses: BTreeMap::new(),
+        });
+
+        while self.sessions.len() > self.max_sessions {
+            let idle = self.sessions.iter().min_by_key(|(_, s)| s.last_active).map(|(id, _)| *id);
+            if let Some(id) = idle {
+                tracing::debug!(session_id = id, "evict idle session");
+                self.sessions.remove(&id);
+            }
+        }
+
+        log_index
+    }
+
+    /// Remove a session. Returns `false` if it does not exist.
+    pub fn unregister(&mut self, session_id: u64) -> bool {
+        self.sessions.remove(&session_id).is_some()
+    }
+
+    /// Returns `true` if the session is registered and not expired.
+    pub fn contains(&self, session_id: u64) -> bool {
+        self.sessions.contains_key(&session_id)
+    }
+
+    /// Returns the number of sessions.
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Returns `true` if there is no session.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Apply a write request of a session in the log entry at `log_index`.
+    ///
+    /// `f` applies the request to the state machine. It is called only if the request has not
+    /// been applied, otherwise the cached response is returned. It fails if the session is
+    /// expired, or if the client has acknowledged the response of this request.
+    pub fn apply<F>(&mut self, session: &ClientSession, log_index: u64, f: F) -> Result<R, SessionError>
+    where
+        R: Clone,
+        F: FnOnce() -> R,
+    {
+        let Some(state) = self.sessions.get_mut(&session.session_id) else {
+            return Err(SessionError::SessionExpired {
+                session_id: session.session_id,
+            });
+        };
+
+        state.last_active = log_index;
+
+        if session.acked > state.acked {
+            state.acked = session.acked;
+            state.responses = state.responses.split_off(&session.acked);
+        }
+
+        if let Some(resp) = state.responses.get(&session.serial) {
+            tracing::debug!(
+                session = display(session),
+                "request is already applied, return cached response"
+            );
+            return Ok(resp.clone());
+        }
+
+        if session.serial < state.acked {
+            return Err(SessionError::StaleRequest {
+                session_id: session.session_id,
+                serial: session.serial,
+                acked: state.acked,
+            });
+        }
+
+        let resp = f();
+        state.responses.insert(session.serial, resp.clone());
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::SessionError;
+    use crate::storage::ClientSession;
+    use crate::storage::SessionTable;
+
+    #[test]
+    fn test_session_table_apply() {
+        let mut t = SessionTable::<u64>::default();
+        let mut applied = 0;
+        let mut apply = |t: &mut SessionTable<u64>, s: ClientSession, log_index: u64| {
+            t.apply(&s, log_index, || {
+                applied += 1;
+                log_index
+            })
+        };
+
+        assert_eq!(
+            Err(SessionError::SessionExpired { session_id: 1 }),
+            apply(&mut t, ClientSession::new(1, 1), 1)
+        );
+
+        let sid = t.register(2);
+        assert_eq!(2, sid);
+
+        assert_eq!(Ok(3), apply(&mut t, ClientSession::new(sid, 1), 3));
+        assert_eq!(Ok(3), apply(&mut t, ClientSession::new(sid, 1), 4), "replayed");
+
+        // Pipelined requests: 2 and 3 are in flight.
+        assert_eq!(Ok(6), apply(&mut t, ClientSession::new(sid, 3).with_acked(2), 6));
+        assert_eq!(Ok(7), apply(&mut t, ClientSession::new(sid, 2).with_acked(2), 7));
+        assert_eq!(
+            Ok(6),
+            apply(&mut t, ClientSession::new(sid, 3).with_acked(2), 8),
+            "replayed"
+        );
+
+        assert_eq!(
+            Err(SessionError::StaleRequest {
+                session_id: sid,
+                serial: 1,
+                acked: 2
+            }),
+            apply(&mut t, ClientSession::new(sid, 1).with_acked(1), 9),
+        );
+
+        assert_eq!(3, applied);
+    }
+
+    #[test]
+    fn test_session_table_evict_idle() {
+        let mut t = SessionTable::<u64>::new(2);
+
+        let s1 = t.register(1);
+        let s2 = t.register(2);
+        t.apply(&ClientSession::new(s1, 1), 3, || 0).unwrap();
+
+        let s3 = t.register(4);
+        assert_eq!(2, t.len());
+        assert!(t.contains(s1));
+        assert!(!t.contains(s2), "least recently active");
+        assert!(t.contains(s3));
+
+        assert!(t.unregister(s1));
+        assert!(!t.unregister(s1));
+        assert_eq!(1, t.len());
+    }
+}
@@ -0,0 +1,101 @@
+//! Progress reporting and cancellation of building a snapshot.
+
+use std::future::Future;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::async_runtime::CancellationToken;
+use crate::metrics::SnapshotBuildMetrics;
+use crate::type_config::alias::CancellationTokenOf;
+use crate::OptionalSend;
+use crate::RaftTypeConfig;
+
+/// A handle to a snapshot being built.
+///
+/// Openraft passes it to [`RaftSnapshotBuilder::build_snapshot_with_progress()`], which reports
+/// the progress with [`Self::add()`] and stops when it is cancelled. It is also returned by
+/// [`Trigger::snapshot()`] for the application to watch or cancel the build, and the progress
+/// is reported in [`RaftMetrics::snapshot_building`].
+///
+/// [`RaftSnapshotBuilder::build_snapshot_with_progress()`]: crate::storage::RaftSnapshotBuilder::build_snapshot_with_progress
+/// [`Trigger::snapshot()`]: crate::raft::trigger::Trigger::snapshot
+/// [`RaftMetrics::snapshot_building`]: crate::RaftMetrics::snapshot_building
+pub struct SnapshotBuildProgress<C>
+where C: RaftTypeConfig
+{
+    entries: Arc<AtomicU64>,
+    bytes: Arc<AtomicU64>,
+    cancel: CancellationTokenOf<C>,
+}
+
+impl<C> Clone for SnapshotBuildProgress<C>
+where C: RaftTypeConfig
+{
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            bytes: self.bytes.clone(),
+            cancel: self.cancel.clone(),
+        }
+    }
+}
+
+impl<C> Default for SnapshotBuildProgress<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            entries: Arc::new(AtomicU64::new(0)),
+            bytes: Arc::new(AtomicU64::new(0)),
+            cancel: CancellationTokenOf::<C>::new(),
+        }
+    }
+}
+
+impl<C> SnapshotBuildProgress<C>
+where C: RaftTypeConfig
+{
+    /// Record that `entries` more entries and `bytes` more bytes are written into the snapshot.
+    pub fn add(&self, entries: u64, bytes: u64) {
+        self.entries.fetch_add(entries, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// The number of entries written into the snapshot so far.
+    pub fn entries(&self) -> u64 {
+        self.entries.load(Ordering::Relaxed)
+    }
+
+    /// The number of bytes written into the snapshot so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Cancel building the snapshot.
+    ///
+    /// Openraft drops the future returned by
+    /// [`build_snapshot_with_progress()`](`crate::storage::RaftSnapshotBuilder::build_snapshot_with_progress`)
+    /// at once, and no snapshot is installed. It has no effect if the snapshot is already built.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Returns `true` if the build is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Waits until the build is cancelled.
+    pub fn cancelled(&self) -> impl Future<Output = ()> + OptionalSend {
+        let cancel = self.cancel.clone();
+        async move { cancel.cancelled().await }
+    }
+
+    pub(crate) fn metrics(&self) -> SnapshotBuildMetrics {
+        SnapshotBuildMetrics {
+            entries: self.entries(),
+            bytes: self.bytes(),
+        }
+    }
+}
@@ -0,0 +1,581 @@
+//! Snapshot data stored as a directory of files.
+
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use tokio::io::AsyncRead;
+use tokio::io::AsyncSeek;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+
+/// The size of the length prefix of an encoded [`SnapshotManifest`].
+const LEN_PREFIX_SIZE: usize = 8;
+
+/// The max size of an encoded [`SnapshotManifest`], to reject a corrupted length prefix.
+const MAX_MANIFEST_SIZE: u64 = 64 * 1024 * 1024;
+
+/// A file in a snapshot directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotFileMeta {
+    /// The path relative to the snapshot directory, with components separated by `/`.
+    pub name: String,
+
+    /// The size of the file in bytes.
+    pub size: u64,
+
+    /// The CRC32 checksum of the file content.
+    pub checksum: u32,
+}
+
+/// Lists the files of a snapshot directory, in the order they are streamed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotManifest {
+    pub files: Vec<SnapshotFileMeta>,
+}
+
+impl SnapshotManifest {
+    /// Build a manifest of all regular files in `dir` and its sub directories, sorted by name.
+    pub fn scan(dir: &Path) -> io::Result<Self> {
+        let mut files = vec![];
+        scan_dir(dir, "", &mut files)?;
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self { files })
+    }
+
+    /// The total size of the files.
+    pub fn data_size(&self) -> u64 {
+        self.files.iter().map(|f| f.size).sum()
+    }
+
+    /// Encode the manifest, prefixed with the length of the rest.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = vec![];
+        body.extend_from_slice(&(self.files.len() as u32).to_be_bytes());
+        for f in &self.files {
+            body.extend_from_slice(&(f.name.len() as u32).to_be_bytes());
+            body.extend_from_slice(f.name.as_bytes());
+            body.extend_from_slice(&f.size.to_be_bytes());
+            body.extend_from_slice(&f.checksum.to_be_bytes());
+        }
+
+        let mut buf = Vec::with_capacity(LEN_PREFIX_SIZE + body.len());
+        buf.extend_from_slice(&(body.len() as u64).to_be_bytes());
+        buf.extend_from_slice(&body);
+        buf
+    }
+
+    /// Decode a manifest encoded by [`Self::encode()`].
+    ///
+    /// It returns an error if the manifest is truncated, or a file name is not a relative path
+    /// inside the snapshot directory.
+    pub fn decode(buf: &[u8]) -> io::Result<Self> {
+        let mut r = Reader { buf };
+
+        let body_len = u64::from_be_bytes(r.take_array()?);
+        if body_len != r.buf.len() as u64 {
+            return Err(invalid_data(format!(
+                "snapshot manifest size mismatch: expect: {}, got: {}",
+                body_len,
+                r.buf.len()
+            )));
+        }
+
+        let n = u32::from_be_bytes(r.take_array()?);
+        let mut files = Vec::with_capacity(std::cmp::min(n as usize, 1024));
+        for _ in 0..n {
+            let name_len = u32::from_be_bytes(r.take_array()?);
+            let name = r.take(name_len as usize)?;
+            let name = String::from_utf8(name.to_vec()).map_err(invalid_data)?;
+            check_name(&name)?;
+
+            let size = u64::from_be_bytes(r.take_array()?);
+            let checksum = u32::from_be_bytes(r.take_array()?);
+            files.push(SnapshotFileMeta { name, size, checksum });
+        }
+
+        Ok(Self { files })
+    }
+}
+
+/// Snapshot data stored as a directory of files, such as the SST files of an LSM tree.
+///
+/// It can be used as [`RaftTypeConfig::SnapshotData`] to stream a snapshot that is not a single
+/// byte stream with the default chunked transport: the data is presented as a stream of the
+/// encoded [`SnapshotManifest`] followed by the content of every file in the manifest.
+///
+/// A sender opens an existing snapshot directory with [`SnapshotDir::open()`]. A receiver
+/// creates an empty one with [`SnapshotDir::create()`] in
+/// [`RaftStateMachine::begin_receiving_snapshot()`], which re-creates the directory layout as the
+/// manifest is received, and verifies the size and checksum of every file when it is shut down
+/// at the end of the transmission. [`RaftStateMachine::install_snapshot()`] then finds the
+/// received files in [`SnapshotDir::dir()`].
+///
+/// The files are accessed with blocking `std::fs` calls, each of which reads or writes at most
+/// one chunk.
+///
+/// [`RaftTypeConfig::SnapshotData`]: crate::RaftTypeConfig::SnapshotData
+/// [`RaftStateMachine::begin_receiving_snapshot()`]: crate::storage::RaftStateMachine::begin_receiving_snapshot
+/// [`RaftStateMachine::install_snapshot()`]: crate::storage::RaftStateMachine::install_snapshot
+#[derive(Debug)]
+pub struct SnapshotDir {
+    dir: PathBuf,
+
+    /// The encoded manifest, or the part of it received so far.
+    header: Vec<u8>,
+
+    /// The manifest, or `None` if it is not yet completely received.
+    manifest: Option<SnapshotManifest>,
+
+    /// The position in the stream of the manifest and the files.
+    pos: u64,
+}
+
+impl SnapshotDir {
+    /// Open the snapshot stored in `dir` to read, with a manifest of all files in it.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let manifest = SnapshotManifest::scan(&dir)?;
+
+        Ok(Self {
+            dir,
+            header: manifest.encode(),
+            manifest: Some(manifest),
+            pos: 0,
+        })
+    }
+
+    /// Create a snapshot to receive into directory `dir`, which is created if it does not exist.
+    pub fn create(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            header: vec![],
+            manifest: None,
+            pos: 0,
+        })
+    }
+
+    /// The directory the files are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The manifest, or `None` if it is not yet completely received.
+    pub fn manifest(&self) -> Option<&SnapshotManifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Check the size and checksum of every file in the manifest.
+    pub fn verify(&self) -> io::Result<()> {
+        let manifest = self.manifest.as_ref().ok_or_else(|| invalid_data("snapshot manifest is not received"))?;
+
+        for f in &manifest.files {
+            let (size, checksum) = checksum_file(&self.dir.join(&f.name))?;
+            if size != f.size || checksum != f.checksum {
+                return Err(invalid_data(format!(
+                    "snapshot file {} mismatch: expect: size {} checksum {:08x}, got: size {} checksum {:08x}",
+                    f.name, f.size, f.checksum, size, checksum
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The total size of the stream, or `None` if the manifest is not yet completely received.
+    fn len(&self) -> Option<u64> {
+        self.manifest.as_ref().map(|m| self.header.len() as u64 + m.data_size())
+    }
+
+    /// Find the file containing stream position `pos`, and the offset in it.
+    fn locate(&self, pos: u64) -> Option<(&SnapshotFileMeta, u64)> {
+        let manifest = self.manifest.as_ref()?;
+
+        let mut start = self.header.len() as u64;
+        for f in &manifest.files {
+            if pos < start + f.size {
+                return Some((f, pos - start));
+            }
+            start += f.size;
+        }
+        None
+    }
+
+    fn read_at_pos(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let header_len = self.header.len() as u64;
+
+        if self.pos < header_len {
+            let h = &self.header[self.pos as usize..];
+            let n = std::cmp::min(h.len(), buf.len());
+            buf[..n].copy_from_slice(&h[..n]);
+            self.pos += n as u64;
+            return Ok(n);
+        }
+
+        let Some((f, offset)) = self.locate(self.pos) else {
+            return Ok(0);
+        };
+
+        let n = std::cmp::min(f.size - offset, buf.len() as u64) as usize;
+        let mut file = fs::File::open(self.dir.join(&f.name))?;
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut buf[..n])?;
+
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn write_at_pos(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.manifest.is_none() {
+            return self.write_header(buf);
+        }
+
+        let header_len = self.header.len() as u64;
+
+        if self.pos < header_len {
+            // The manifest is sent again, e.g., the leader restarts from the beginning.
+            let h = &self.header[self.pos as usize..];
+            let n = std::cmp::min(h.len(), buf.len());
+            if h[..n] != buf[..n] {
+                return Err(invalid_data("snapshot manifest changed"));
+            }
+            self.pos += n as u64;
+            return Ok(n);
+        }
+
+        let Some((f, offset)) = self.locate(self.pos) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("write beyond the end of snapshot at {}", self.pos),
+            ));
+        };
+
+        let n = std::cmp::min(f.size - offset, buf.len() as u64) as usize;
+        let mut file = fs::OpenOptions::new().write(true).open(self.dir.join(&f.name))?;
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(&buf[..n])?;
+
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    /// Receive the manifest, and create the files in it when it is complete.
+    fn write_header(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.pos != self.header.len() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "snapshot manifest must be written in order: expect position {}, got: {}",
+                    self.header.len(),
+                    self.pos
+                ),
+            ));
+        }
+
+        let want = if self.header.len() < LEN_PREFIX_SIZE {
+            LEN_PREFIX_SIZE - self.header.len()
+        } else {
+            let body_len = u64::from_be_bytes(self.header[..LEN_PREFIX_SIZE].try_into().unwrap());
+            if body_len > MAX_MANIFEST_SIZE {
+                return Err(invalid_data(format!("snapshot manifest is too large: {}", body_len)));
+            }
+            LEN_PREFIX_SIZE + body_len as usize - self.header.len()
+        };
+
+        let n = std::cmp::min(want, buf.len());
+        self.header.extend_from_slice(&buf[..n]);
+        self.pos += n as u64;
+
+        if self.header.len() > LEN_PREFIX_SIZE && n == want {
+            let manifest = SnapshotManifest::decode(&self.header)?;
+            for f in &manifest.files {
+                let path = self.dir.join(&f.name);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::File::create(path)?.set_len(f.size)?;
+            }
+            self.manifest = Some(manifest);
+        }
+
+        Ok(n)
+    }
+
+    /// Verify the received files and persist them.
+    fn finish(&self) -> io::Result<()> {
+        self.verify()?;
+
+        let manifest = self.manifest.as_ref().unwrap();
+        for f in &manifest.files {
+            fs::File::open(self.dir.join(&f.name))?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+impl AsyncRead for SnapshotDir {
+    fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let n = this.read_at_pos(buf.initialize_unfilled())?;
+        buf.advance(n);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for SnapshotDir {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.get_mut().write_at_pos(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Verify the size and checksum of every file, and sync them to disk.
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.finish())
+    }
+}
+
+impl AsyncSeek for SnapshotDir {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+
+        let pos = match position {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::Current(d) => this.pos.checked_add_signed(d),
+            SeekFrom::End(d) => {
+                let len = this.len().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "seek from the end before the manifest is received",
+                    )
+                })?;
+                len.checked_add_signed(d)
+            }
+        };
+
+        this.pos = pos.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek position"))?;
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.pos))
+    }
+}
+
+fn scan_dir(dir: &Path, prefix: &str, files: &mut Vec<SnapshotFileMeta>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name =
+            file_name.to_str().ok_or_else(|| invalid_data(format!("non UTF-8 file name: {:?}", entry.path())))?;
+        let name = format!("{}{}", prefix, file_name);
+
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            scan_dir(&entry.path(), &format!("{}/", name), files)?;
+        } else if file_type.is_file() {
+            let (size, checksum) = checksum_file(&entry.path())?;
+            files.push(SnapshotFileMeta { name, size, checksum });
+        }
+    }
+    Ok(())
+}
+
+fn checksum_file(path: &Path) -> io::Result<(u64, u32)> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut size = 0;
+    let mut buf = vec![0; 64 * 1024];
+
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((size, hasher.finalize()))
+}
+
+/// Check that a file name received in a manifest is a relative path inside the snapshot directory.
+fn check_name(name: &str) -> io::Result<()> {
+    let valid =
+        !name.is_empty() && !name.contains('\\') && name.split('/').all(|c| !c.is_empty() && c != "." && c != "..");
+
+    if valid {
+        Ok(())
+    } else {
+        Err(invalid_data(format!("invalid snapshot file name: {:?}", name)))
+    }
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.buf.len() < n {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "truncated snapshot manifest",
+            ));
+        }
+        let (a, b) = self.buf.split_at(n);
+        self.buf = b;
+        Ok(a)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::SeekFrom;
+
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncSeekExt;
+    use tokio::io::AsyncWriteExt;
+
+    use crate::storage::SnapshotDir;
+    use crate::storage::SnapshotFileMeta;
+    use crate::storage::SnapshotManifest;
+
+    fn write_files(dir: &std::path::Path) {
+        fs::create_dir_all(dir.join("sst")).unwrap();
+        fs::write(dir.join("CURRENT"), b"MANIFEST-000001").unwrap();
+        fs::write(dir.join("sst/000001.sst"), vec![1u8; 3000]).unwrap();
+        fs::write(dir.join("sst/000002.sst"), vec![2u8; 10]).unwrap();
+        fs::write(dir.join("LOCK"), b"").unwrap();
+    }
+
+    async fn read_all(src: &mut SnapshotDir, from: u64) -> Vec<u8> {
+        src.seek(SeekFrom::Start(from)).await.unwrap();
+        let mut data = vec![];
+        src.read_to_end(&mut data).await.unwrap();
+        data
+    }
+
+    #[test]
+    fn test_manifest_codec() -> anyhow::Result<()> {
+        let m = SnapshotManifest {
+            files: vec![
+                SnapshotFileMeta {
+                    name: "a/b".to_string(),
+                    size: 3,
+                    checksum: 5,
+                },
+                SnapshotFileMeta {
+                    name: "c".to_string(),
+                    size: 0,
+                    checksum: 0,
+                },
+            ],
+        };
+
+        let buf = m.encode();
+        assert_eq!(m, SnapshotManifest::decode(&buf)?);
+        assert!(SnapshotManifest::decode(&buf[..buf.len() - 1]).is_err());
+
+        for name in ["", "/etc/passwd", "../x", "a/../../x", "a//b", "a\\b"] {
+            let m = SnapshotManifest {
+                files: vec![SnapshotFileMeta {
+                    name: name.to_string(),
+                    size: 0,
+                    checksum: 0,
+                }],
+            };
+            assert!(SnapshotManifest::decode(&m.encode()).is_err(), "name: {:?}", name);
+        }
+        Ok(())
+    }
+
+    /// Stream a directory in chunks, with a chunk sent again, and re-create it in another one.
+    #[tokio::test]
+    async fn test_snapshot_dir_stream() -> anyhow::Result<()> {
+        let td = tempfile::tempdir()?;
+        let src_dir = td.path().join("src");
+        let dst_dir = td.path().join("dst");
+        write_files(&src_dir);
+
+        let mut src = SnapshotDir::open(&src_dir)?;
+        let names = src.manifest().unwrap().files.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["CURRENT", "LOCK", "sst/000001.sst", "sst/000002.sst"]);
+
+        let end = src.seek(SeekFrom::End(0)).await?;
+        let data = read_all(&mut src, 0).await;
+        assert_eq!(end, data.len() as u64);
+
+        let mut dst = SnapshotDir::create(&dst_dir)?;
+        let chunk_size = 1000;
+        let mut offset = 0;
+        while offset < end {
+            let chunk = &data[offset as usize..std::cmp::min(end as usize, (offset + chunk_size) as usize)];
+            dst.seek(SeekFrom::Start(offset)).await?;
+            dst.write_all(chunk).await?;
+
+            if offset == chunk_size {
+                // Send the chunk again.
+                dst.seek(SeekFrom::Start(offset)).await?;
+                dst.write_all(chunk).await?;
+            }
+            offset += chunk.len() as u64;
+        }
+        dst.shutdown().await?;
+
+        assert_eq!(src.manifest(), dst.manifest());
+        assert_eq!(
+            fs::read(src_dir.join("sst/000001.sst"))?,
+            fs::read(dst_dir.join("sst/000001.sst"))?
+        );
+        assert_eq!(fs::read(src_dir.join("CURRENT"))?, fs::read(dst_dir.join("CURRENT"))?);
+        assert!(dst_dir.join("LOCK").exists());
+
+        // Read the received snapshot from the middle.
+        assert_eq!(data[100..].to_vec(), read_all(&mut dst, 100).await);
+        Ok(())
+    }
+
+    /// A file that does not match the manifest fails the verification at shutdown.
+    #[tokio::test]
+    async fn test_snapshot_dir_verify() -> anyhow::Result<()> {
+        let td = tempfile::tempdir()?;
+        let src_dir = td.path().join("src");
+        let dst_dir = td.path().join("dst");
+        write_files(&src_dir);
+
+        let mut src = SnapshotDir::open(&src_dir)?;
+        let data = read_all(&mut src, 0).await;
+
+        let mut dst = SnapshotDir::create(&dst_dir)?;
+        assert!(dst.seek(SeekFrom::End(0)).await.is_err(), "manifest is not received");
+
+        dst.seek(SeekFrom::Start(0)).await?;
+        dst.write_all(&data).await?;
+        fs::write(dst_dir.join("sst/000002.sst"), vec![3u8; 10])?;
+
+        let err = dst.shutdown().await.unwrap_err();
+        assert!(err.to_string().contains("sst/000002.sst"), "{}", err);
+        Ok(())
+    }
+}
@@ -5,10 +5,12 @@
 
 mod raft_log_storage_ext;
 
+use anyerror::AnyError;
 use openraft_macros::add_async_trait;
 pub use raft_log_storage_ext::RaftLogStorageExt;
 
 use crate::storage::callback::LogFlushed;
+use crate::storage::EncodedEntries;
 use crate::LogId;
 use crate::LogState;
 use crate::OptionalSend;
@@ -19,6 +21,7 @@ use crate::RaftTypeConfig;
 use crate::Snapshot;
 use crate::SnapshotMeta;
 use crate::StorageError;
+use crate::StorageIOError;
 use crate::StoredMembership;
 use crate::Vote;
 
@@ -64,6 +67,12 @@ where C: RaftTypeConfig
     /// ### To ensure correctness:
     ///
     /// The vote must be persisted on disk before returning.
+    ///
+    /// To store the vote in a separate storage, wrap this storage and a [`RaftVoteStorage`] in a
+    /// [`WithVoteStorage`].
+    ///
+    /// [`RaftVoteStorage`]: crate::storage::RaftVoteStorage
+    /// [`WithVoteStorage`]: crate::storage::WithVoteStorage
     async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
 
     /// Saves the last committed log id to storage.
@@ -113,6 +122,68 @@ where C: RaftTypeConfig
         I: IntoIterator<Item = C::Entry> + OptionalSend,
         I::IntoIter: OptionalSend;
 
+    /// Serialize an entry into the buffer to be passed to [`Self::append_vectored()`].
+    ///
+    /// If it returns `Some` for every entry in a batch, the batch is appended with
+    /// [`Self::append_vectored()`], otherwise with [`Self::append()`]. The entries are serialized
+    /// before the storage is called, so that a file based storage can write a whole batch with a
+    /// single `writev()` instead of one write per entry.
+    ///
+    /// The buffer is encrypted with [`RaftTypeConfig::StorageCipher`] by [`seal()`] before it is
+    /// passed to [`Self::append_vectored()`]. Thus a storage reading it back has to decrypt it with
+    /// [`unseal()`] before deserializing.
+    ///
+    /// By default it returns `None` and [`Self::append_vectored()`] is not used.
+    ///
+    /// [`seal()`]: crate::storage::seal
+    /// [`unseal()`]: crate::storage::unseal
+    fn encode_entry(&self, _entry: &C::Entry) -> Result<Option<Vec<u8>>, StorageError<C::NodeId>> {
+        Ok(None)
+    }
+
+    /// Append a batch of log entries serialized by [`Self::encode_entry()`], and call the
+    /// `callback` once the entries are persisted on disk.
+    ///
+    /// It has the same correctness requirements as [`Self::append()`]. By default it appends the
+    /// entries with [`Self::append()`].
+    async fn append_vectored(
+        &mut self,
+        entries: EncodedEntries<C>,
+        callback: LogFlushed<C>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        self.append(entries.into_entries(), callback).await
+    }
+
+    /// Whether this storage encrypts every log entry it persists with
+    /// [`RaftTypeConfig::StorageCipher`], including the entries appended with [`Self::append()`].
+    ///
+    /// If a cipher other than [`NoEncryption`] is configured and it returns `false`,
+    /// [`Raft::new()`] refuses to start, instead of letting the entries be persisted in plaintext.
+    ///
+    /// By default it returns `false`.
+    ///
+    /// [`NoEncryption`]: crate::storage::NoEncryption
+    /// [`Raft::new()`]: crate::Raft::new
+    fn supports_storage_cipher(&self) -> bool {
+        false
+    }
+
+    /// Returns how many of the `entries` replicated from the leader, from the first one, can be
+    /// appended now.
+    ///
+    /// A follower calls it before appending the entries of an `AppendEntries` request. If it
+    /// returns less than `entries.len()`, e.g., there is not enough disk space for all of them,
+    /// only this prefix is appended, and the follower replies a
+    /// [`AppendEntriesResponse::PartialSuccess`] with the last appended log id. The leader then
+    /// resends only the rest instead of the whole batch.
+    ///
+    /// By default all the entries are accepted.
+    ///
+    /// [`AppendEntriesResponse::PartialSuccess`]: crate::raft::AppendEntriesResponse::PartialSuccess
+    async fn appendable_count(&mut self, entries: &[C::Entry]) -> usize {
+        entries.len()
+    }
+
     /// Truncate logs since `log_id`, inclusive
     ///
     /// ### To ensure correctness:
@@ -183,11 +254,62 @@ where C: RaftTypeConfig
     /// - An implementation with persistent snapshot: `apply()` does not have to persist state on
     ///   disk. But every snapshot has to be persistent. And when starting up the application, the
     ///   state machine should be rebuilt from the last snapshot.
+    ///
+    /// Calls to `apply()` are grouped into batches, see [`Self::begin_apply_batch()`].
     async fn apply<I>(&mut self, entries: I) -> Result<Vec<C::R>, StorageError<C::NodeId>>
     where
         I: IntoIterator<Item = C::Entry> + OptionalSend,
         I::IntoIter: OptionalSend;
 
+    /// Apply the given payload of entries to the state machine, and return a response or an
+    /// application error for every entry.
+    ///
+    /// Openraft calls this method instead of [`Self::apply()`]. An entry rejected with an
+    /// [`AppError`](`RaftTypeConfig::AppError`) is still applied, i.e., the last applied log id
+    /// has to be updated as [`Self::apply()`] does, and its client receives
+    /// [`ClientWriteError::ApplyError`]. The rejection must be deterministic, so that every node
+    /// rejects the same entries.
+    ///
+    /// The default implementation calls [`Self::apply()`] and never rejects an entry.
+    ///
+    /// [`ClientWriteError::ApplyError`]: crate::error::ClientWriteError::ApplyError
+    async fn try_apply<I>(&mut self, entries: I) -> Result<Vec<Result<C::R, C::AppError>>, StorageError<C::NodeId>>
+    where
+        I: IntoIterator<Item = C::Entry> + OptionalSend,
+        I::IntoIter: OptionalSend,
+    {
+        let responses = self.apply(entries).await?;
+        Ok(responses.into_iter().map(Ok).collect())
+    }
+
+    /// Begin a batch of [`Self::apply()`] calls.
+    ///
+    /// Every time the state machine worker polls committed entries, it calls `begin_apply_batch()`,
+    /// then calls `apply()` one or more times with all the entries already queued, and then calls
+    /// [`Self::commit_apply_batch()`]. No other method of the state machine is called in between.
+    ///
+    /// This allows an implementation to wrap one transaction of a backing DB around every batch,
+    /// instead of around every `apply()`.
+    ///
+    /// The default implementation does nothing.
+    async fn begin_apply_batch(&mut self) -> Result<(), StorageError<C::NodeId>> {
+        Ok(())
+    }
+
+    /// End a batch of [`Self::apply()`] calls started by [`Self::begin_apply_batch()`].
+    ///
+    /// `last_applied` is the log id of the last entry applied in this batch.
+    ///
+    /// The responses of the applied entries are sent to the clients only after this method
+    /// returns. Thus an implementation with persistent state machine may persist the state of the
+    /// entire batch here, instead of in every `apply()`.
+    ///
+    /// The default implementation does nothing.
+    async fn commit_apply_batch(&mut self, last_applied: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        let _ = last_applied;
+        Ok(())
+    }
+
     /// Get the snapshot builder for the state machine.
     ///
     /// Usually it returns a snapshot view of the state machine(i.e., subsequent changes to the
@@ -215,6 +337,11 @@ where C: RaftTypeConfig
     /// - the input snapshot should be saved, i.e., [`Self::get_current_snapshot`] should return it.
     /// - and all other snapshots should be deleted at this point.
     ///
+    /// The snapshot data is already received into the handle returned by
+    /// [`Self::begin_receiving_snapshot`], outside of `RaftCore`. This method should only switch
+    /// the state machine to the snapshot. `RaftCore` keeps responding to other requests, such as
+    /// vote or append-entries, while the snapshot is being installed.
+    ///
     /// ### snapshot
     ///
     /// A snapshot created from an earlier call to `begin_receiving_snapshot` which provided the
@@ -225,6 +352,32 @@ where C: RaftTypeConfig
         snapshot: Box<C::SnapshotData>,
     ) -> Result<(), StorageError<C::NodeId>>;
 
+    /// Install a delta snapshot built by [`RaftSnapshotBuilder::build_delta()`] on the leader,
+    /// which has finished streaming from the leader.
+    ///
+    /// When it is called, the state machine is at a log id in range
+    /// `[meta.delta_base, meta.last_log_id]`. Before this method returns, the changes in the delta
+    /// should be applied to the state machine, so that it is at `meta.last_log_id`.
+    ///
+    /// Unlike [`Self::install_snapshot()`], the delta does not have to be saved:
+    /// Openraft builds a full snapshot with [`Self::get_snapshot_builder()`] after installing it.
+    ///
+    /// The default implementation returns an error. It must be implemented if
+    /// [`RaftSnapshotBuilder::build_delta()`] is.
+    #[allow(clippy::boxed_local)]
+    async fn install_delta_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C>,
+        snapshot: Box<C::SnapshotData>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let _ = snapshot;
+        Err(StorageIOError::write_snapshot(
+            Some(meta.signature()),
+            AnyError::error("delta snapshot is not supported"),
+        )
+        .into())
+    }
+
     /// Get a readable handle to the current snapshot.
     ///
     /// ### implementation algorithm
@@ -239,4 +392,42 @@ where C: RaftTypeConfig
     /// last-applied-membership config as part of the snapshot, which should be decoded for
     /// creating this method's response data.
     async fn get_current_snapshot(&mut self) -> Result<Option<Snapshot<C>>, StorageError<C::NodeId>>;
+
+    /// List the metadata of all the snapshots stored, including the current one.
+    ///
+    /// It is called after a snapshot is built or installed, if
+    /// [`Config::max_snapshots_to_keep`](`crate::Config::max_snapshots_to_keep`) is set, to
+    /// find out the stale snapshots to delete.
+    ///
+    /// The default implementation returns an empty list, i.e., no snapshot is deleted.
+    async fn list_snapshots(&mut self) -> Result<Vec<SnapshotMeta<C>>, StorageError<C::NodeId>> {
+        Ok(vec![])
+    }
+
+    /// Delete a stale snapshot returned by [`Self::list_snapshots()`].
+    ///
+    /// Openraft never deletes the latest snapshot, but a stale one may still be read by a
+    /// snapshot transmission started before it becomes stale: the implementation should keep
+    /// the data readable through a handle already returned by [`Self::get_current_snapshot()`].
+    ///
+    /// The default implementation does nothing.
+    async fn delete_snapshot(&mut self, meta: &SnapshotMeta<C>) -> Result<(), StorageError<C::NodeId>> {
+        let _ = meta;
+        Ok(())
+    }
+
+    /// Whether this state machine encrypts every snapshot it persists, either built or installed,
+    /// with [`RaftTypeConfig::StorageCipher`].
+    ///
+    /// If a cipher other than [`NoEncryption`] is configured and it returns `false`,
+    /// [`Raft::new()`] refuses to start, instead of letting the snapshots be persisted in
+    /// plaintext.
+    ///
+    /// By default it returns `false`.
+    ///
+    /// [`NoEncryption`]: crate::storage::NoEncryption
+    /// [`Raft::new()`]: crate::Raft::new
+    fn supports_storage_cipher(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,225 @@
+//! Store the vote separately from the log.
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+use futures::lock::Mutex;
+use openraft_macros::add_async_trait;
+
+use crate::storage::EncodedEntries;
+use crate::storage::LogFlushed;
+use crate::storage::LogState;
+use crate::storage::RaftLogReader;
+use crate::storage::RaftLogStorage;
+use crate::LogId;
+use crate::OptionalSend;
+use crate::OptionalSync;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+use crate::Vote;
+
+/// A durable storage of the [`Vote`] only.
+///
+/// The vote is small and is saved with a flush every time it changes, unlike the log entries
+/// that are appended in bulk. Implementing this trait allows to store the vote in a small file or
+/// in NVRAM, and to use it along with a [`RaftLogStorage`] by wrapping them in a
+/// [`WithVoteStorage`].
+///
+/// Every [`RaftLogStorage`] is a [`RaftVoteStorage`] that stores the vote along with the log.
+#[add_async_trait]
+pub trait RaftVoteStorage<C>: OptionalSend + OptionalSync + 'static
+where C: RaftTypeConfig
+{
+    /// Save vote to storage.
+    ///
+    /// ### To ensure correctness:
+    ///
+    /// The vote must be persisted on disk, e.g., by calling `fsync()`, before returning.
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Return the last saved vote by [`Self::save_vote()`].
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>>;
+}
+
+impl<C, LS> RaftVoteStorage<C> for LS
+where
+    C: RaftTypeConfig,
+    LS: RaftLogStorage<C>,
+{
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        RaftLogStorage::save_vote(self, vote).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        RaftLogReader::read_vote(self).await
+    }
+}
+
+/// A [`RaftLogStorage`] that stores the log in `LS` and the vote in `VS`.
+///
+/// The vote saved in `LS` is neither written nor read.
+pub struct WithVoteStorage<C, LS, VS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogStorage<C>,
+    VS: RaftVoteStorage<C>,
+{
+    log_store: LS,
+    vote_store: Arc<Mutex<VS>>,
+    _p: PhantomData<C>,
+}
+
+impl<C, LS, VS> WithVoteStorage<C, LS, VS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogStorage<C>,
+    VS: RaftVoteStorage<C>,
+{
+    pub fn new(log_store: LS, vote_store: VS) -> Self {
+        Self {
+            log_store,
+            vote_store: Arc::new(Mutex::new(vote_store)),
+            _p: Default::default(),
+        }
+    }
+
+    /// Returns the underlying log store.
+    pub fn log_store(&mut self) -> &mut LS {
+        &mut self.log_store
+    }
+}
+
+/// The [`RaftLogReader`] of a [`WithVoteStorage`], which reads the vote from the vote storage.
+pub struct WithVoteReader<C, LR, VS>
+where
+    C: RaftTypeConfig,
+    LR: RaftLogReader<C>,
+    VS: RaftVoteStorage<C>,
+{
+    log_reader: LR,
+    vote_store: Arc<Mutex<VS>>,
+    _p: PhantomData<C>,
+}
+
+impl<C, LR, VS> RaftLogReader<C> for WithVoteReader<C, LR, VS>
+where
+    C: RaftTypeConfig,
+    LR: RaftLogReader<C>,
+    VS: RaftVoteStorage<C>,
+{
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + OptionalSend>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<C::Entry>, StorageError<C::NodeId>> {
+        self.log_reader.try_get_log_entries(range).await
+    }
+
+    async fn limited_get_log_entries(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<C::Entry>, StorageError<C::NodeId>> {
+        self.log_reader.limited_get_log_entries(start, end).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        RaftVoteStorage::read_vote(&mut *self.vote_store.lock().await).await
+    }
+}
+
+impl<C, LS, VS> RaftLogReader<C> for WithVoteStorage<C, LS, VS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogStorage<C>,
+    VS: RaftVoteStorage<C>,
+{
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + OptionalSend>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<C::Entry>, StorageError<C::NodeId>> {
+        self.log_store.try_get_log_entries(range).await
+    }
+
+    async fn limited_get_log_entries(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<C::Entry>, StorageError<C::NodeId>> {
+        self.log_store.limited_get_log_entries(start, end).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        RaftVoteStorage::read_vote(&mut *self.vote_store.lock().await).await
+    }
+}
+
+impl<C, LS, VS> RaftLogStorage<C> for WithVoteStorage<C, LS, VS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogStorage<C>,
+    VS: RaftVoteStorage<C>,
+{
+    type LogReader = WithVoteReader<C, LS::LogReader, VS>;
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.log_store.get_log_state().await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        WithVoteReader {
+            log_reader: self.log_store.get_log_reader().await,
+            vote_store: self.vote_store.clone(),
+            _p: Default::default(),
+        }
+    }
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        RaftVoteStorage::save_vote(&mut *self.vote_store.lock().await, vote).await
+    }
+
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        self.log_store.save_committed(committed).await
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        self.log_store.read_committed().await
+    }
+
+    async fn append<I>(&mut self, entries: I, callback: LogFlushed<C>) -> Result<(), StorageError<C::NodeId>>
+    where
+        I: IntoIterator<Item = C::Entry> + OptionalSend,
+        I::IntoIter: OptionalSend,
+    {
+        self.log_store.append(entries, callback).await
+    }
+
+    fn encode_entry(&self, entry: &C::Entry) -> Result<Option<Vec<u8>>, StorageError<C::NodeId>> {
+        self.log_store.encode_entry(entry)
+    }
+
+    async fn append_vectored(
+        &mut self,
+        entries: EncodedEntries<C>,
+        callback: LogFlushed<C>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        self.log_store.append_vectored(entries, callback).await
+    }
+
+    fn supports_storage_cipher(&self) -> bool {
+        self.log_store.supports_storage_cipher()
+    }
+
+    async fn appendable_count(&mut self, entries: &[C::Entry]) -> usize {
+        self.log_store.appendable_count(entries).await
+    }
+
+    async fn truncate(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.log_store.truncate(log_id).await
+    }
+
+    async fn purge(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.log_store.purge(log_id).await
+    }
+}
@@ -198,6 +198,13 @@ where NID: NodeId
         #[cfg_attr(feature = "bt", backtrace)]
         source: StorageIOError<NID>,
     },
+
+    /// A log entry read from storage is corrupted, e.g., its checksum does not match.
+    #[error(transparent)]
+    Corrupted {
+        #[from]
+        source: LogCorruption,
+    },
 }
 
 impl<NID> StorageError<NID>
@@ -217,12 +224,45 @@ where NID: NodeId
         }
     }
 
+    pub fn into_corruption(self) -> Option<LogCorruption> {
+        match self {
+            StorageError::Corrupted { source } => Some(source),
+            _ => None,
+        }
+    }
+
     pub fn from_io_error(subject: ErrorSubject<NID>, verb: ErrorVerb, io_error: std::io::Error) -> Self {
         let sto_io_err = StorageIOError::new(subject, verb, AnyError::new(&io_error));
         StorageError::IO { source: sto_io_err }
     }
 }
 
+/// A log entry that is found corrupted when it is read, e.g., the checksum recorded when it was
+/// appended does not match its content.
+///
+/// When it is found by [`Raft::new()`](`crate::Raft::new`), the node either fails to start or
+/// truncates the log since the corrupted entry, according to
+/// [`Config::log_corruption_policy`](`crate::Config::log_corruption_policy`). When it is found
+/// afterward, the node shuts down like with other storage errors.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[error("log entry at index {index} is corrupted: {source}")]
+pub struct LogCorruption {
+    /// The index of the corrupted log entry.
+    pub index: u64,
+
+    pub source: AnyError,
+}
+
+impl LogCorruption {
+    pub fn new(index: u64, source: impl Into<AnyError>) -> Self {
+        Self {
+            index,
+            source: source.into(),
+        }
+    }
+}
+
 /// Error that occurs when operating the store.
 ///
 /// It indicates a data crash.
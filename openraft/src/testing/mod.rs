@@ -1,12 +1,16 @@
 //! Testing utilities for OpenRaft.
 
+pub mod runtime;
 mod store_builder;
 mod suite;
+mod test_runtime;
 
 use std::collections::BTreeSet;
 
 pub use store_builder::StoreBuilder;
 pub use suite::Suite;
+pub use test_runtime::TestInstant;
+pub use test_runtime::TestRuntime;
 
 use crate::entry::RaftEntry;
 use crate::CommittedLeaderId;
@@ -0,0 +1,293 @@
+//! Test suite for [`AsyncRuntime`] implementations.
+//!
+//! An [`AsyncRuntime`] implementation should pass all the tests in [`Suite`]:
+//!
+//! ```ignore
+//! #[test]
+//! fn test_my_runtime() {
+//!     my_runtime::block_on(Suite::<MyRuntime>::test_all());
+//! }
+//! ```
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::async_runtime::broadcast::RecvError;
+use crate::async_runtime::AsyncOneshotSendExt;
+use crate::async_runtime::Broadcast;
+use crate::async_runtime::BroadcastReceiver;
+use crate::async_runtime::BroadcastSender;
+use crate::async_runtime::CancellationToken;
+use crate::async_runtime::Interval;
+use crate::async_runtime::MissedTickBehavior;
+use crate::async_runtime::Semaphore;
+use crate::AsyncRuntime;
+use crate::Instant;
+
+/// Test suite to ensure an [`AsyncRuntime`] implementation works as expected.
+///
+/// The tests must be run inside an executor of the tested runtime.
+pub struct Suite<Rt>
+where Rt: AsyncRuntime
+{
+    _p: PhantomData<Rt>,
+}
+
+impl<Rt> Suite<Rt>
+where Rt: AsyncRuntime
+{
+    /// Run all the tests.
+    pub async fn test_all() {
+        Self::test_spawn_join_handle().await;
+        Self::test_spawn_panic().await;
+        Self::test_spawn_blocking().await;
+        Self::test_spawn_blocking_panic().await;
+        Self::test_sleep().await;
+        Self::test_sleep_until().await;
+        Self::test_instant_now().await;
+        Self::test_timeout().await;
+        Self::test_timeout_at().await;
+        Self::test_oneshot_drop_tx().await;
+        Self::test_oneshot().await;
+        Self::test_broadcast().await;
+        Self::test_cancellation_token().await;
+        Self::test_semaphore().await;
+        Self::test_interval().await;
+    }
+
+    pub async fn test_spawn_join_handle() {
+        for ret_number in 0..10 {
+            let handle = Rt::spawn(async move { ret_number });
+            let ret_value = handle.await.unwrap();
+            assert_eq!(ret_value, ret_number);
+        }
+    }
+
+    pub async fn test_spawn_panic() {
+        let handle = Rt::spawn(async {
+            panic!("expected panic in spawned task");
+        });
+
+        let err = handle.await.unwrap_err();
+        assert!(Rt::is_panic(&err));
+    }
+
+    pub async fn test_spawn_blocking() {
+        for ret_number in 0..10 {
+            let handle = Rt::spawn_blocking(move || {
+                std::thread::sleep(Duration::from_millis(1));
+                ret_number
+            });
+            let ret_value = handle.await.unwrap();
+            assert_eq!(ret_value, ret_number);
+        }
+    }
+
+    pub async fn test_spawn_blocking_panic() {
+        let handle = Rt::spawn_blocking(|| {
+            panic!("expected panic in blocking task");
+        });
+
+        let err = handle.await.unwrap_err();
+        assert!(Rt::is_panic(&err));
+    }
+
+    pub async fn test_sleep() {
+        let start = Rt::Instant::now();
+        let dur = Duration::from_millis(50);
+
+        Rt::sleep(dur).await;
+
+        let elapsed = start.elapsed();
+        assert!(elapsed >= dur, "elapsed: {:?}, expect >= {:?}", elapsed, dur);
+    }
+
+    pub async fn test_sleep_until() {
+        let start = Rt::Instant::now();
+        let dur = Duration::from_millis(50);
+
+        Rt::sleep_until(start + dur).await;
+
+        let elapsed = start.elapsed();
+        assert!(elapsed >= dur, "elapsed: {:?}, expect >= {:?}", elapsed, dur);
+    }
+
+    pub async fn test_instant_now() {
+        let start = Rt::Instant::now();
+        Rt::sleep(Duration::from_millis(10)).await;
+        let end = Rt::Instant::now();
+
+        assert!(end > start);
+        assert!(end - start >= Duration::from_millis(10));
+    }
+
+    pub async fn test_timeout() {
+        let ret_number = 1;
+
+        // Will not time out
+        let res = Rt::timeout(Duration::from_millis(500), async move {
+            Rt::sleep(Duration::from_millis(10)).await;
+            ret_number
+        })
+        .await;
+        assert_eq!(res.unwrap(), ret_number);
+
+        // Will time out
+        let res = Rt::timeout(Duration::from_millis(10), async move {
+            Rt::sleep(Duration::from_millis(500)).await;
+            ret_number
+        })
+        .await;
+        assert!(res.is_err());
+    }
+
+    pub async fn test_timeout_at() {
+        let ret_number = 1;
+
+        // Will not time out
+        let deadline = Rt::Instant::now() + Duration::from_millis(500);
+        let res = Rt::timeout_at(deadline, async move {
+            Rt::sleep(Duration::from_millis(10)).await;
+            ret_number
+        })
+        .await;
+        assert_eq!(res.unwrap(), ret_number);
+
+        // Will time out
+        let deadline = Rt::Instant::now() + Duration::from_millis(10);
+        let res = Rt::timeout_at(deadline, async move {
+            Rt::sleep(Duration::from_millis(500)).await;
+            ret_number
+        })
+        .await;
+        assert!(res.is_err());
+    }
+
+    pub async fn test_oneshot_drop_tx() {
+        let (tx, rx) = Rt::oneshot::<()>();
+        drop(tx);
+        assert!(rx.await.is_err());
+    }
+
+    pub async fn test_oneshot() {
+        let (tx, rx) = Rt::oneshot::<u32>();
+
+        let send_handle = Rt::spawn(async move {
+            Rt::sleep(Duration::from_millis(10)).await;
+            tx.send(1).unwrap();
+        });
+
+        assert_eq!(rx.await.unwrap(), 1);
+        send_handle.await.unwrap();
+
+        // Sending to a dropped receiver returns the value back.
+        let (tx, rx) = Rt::oneshot::<u32>();
+        drop(rx);
+        assert_eq!(tx.send(2), Err(2));
+    }
+
+    pub async fn test_broadcast() {
+        let (tx, mut rx1) = Rt::Broadcast::channel::<u64>(2);
+        let mut rx2 = tx.subscribe();
+        assert_eq!(tx.receiver_count(), 2);
+
+        assert_eq!(tx.send(1).unwrap(), 2);
+        assert_eq!(rx1.recv().await, Ok(1));
+        assert_eq!(rx2.recv().await, Ok(1));
+
+        // A late subscriber does not see the values sent before it subscribed.
+        let mut rx3 = tx.subscribe();
+
+        // rx1 falls behind and loses the oldest value.
+        tx.send(2).unwrap();
+        tx.send(3).unwrap();
+        tx.send(4).unwrap();
+        assert_eq!(rx1.recv().await, Err(RecvError::Lagged(1)));
+        assert_eq!(rx1.recv().await, Ok(3));
+        assert_eq!(rx1.recv().await, Ok(4));
+        assert_eq!(rx3.recv().await, Err(RecvError::Lagged(1)));
+        assert_eq!(rx3.recv().await, Ok(3));
+
+        drop(rx2);
+        drop(rx3);
+        drop(tx);
+        assert_eq!(rx1.recv().await, Err(RecvError::Closed));
+
+        let (tx, rx) = Rt::Broadcast::channel::<u64>(1);
+        drop(rx);
+        assert!(tx.send(1).is_err(), "no receiver");
+    }
+
+    pub async fn test_cancellation_token() {
+        let parent = Rt::CancellationToken::new();
+        let child = parent.child_token();
+        let grandchild = child.child_token();
+
+        let waiting = Rt::spawn({
+            let grandchild = grandchild.clone();
+            async move { grandchild.cancelled().await }
+        });
+
+        child.child_token().cancel();
+        assert!(!child.is_cancelled(), "cancelling a child does not cancel its parent");
+
+        parent.cancel();
+        assert!(child.is_cancelled());
+        assert!(grandchild.is_cancelled());
+
+        Rt::timeout(Duration::from_millis(500), waiting)
+            .await
+            .expect("cancelling the root wakes up the waiting task")
+            .unwrap();
+
+        // A child of a cancelled token is cancelled at once.
+        assert!(parent.child_token().is_cancelled());
+        Rt::timeout(Duration::from_millis(500), parent.child_token().cancelled()).await.unwrap();
+    }
+
+    pub async fn test_semaphore() {
+        let sem = Arc::new(Rt::Semaphore::new(2));
+
+        let p1 = sem.clone().acquire_owned().await;
+        let p2 = sem.clone().try_acquire_owned();
+        assert!(p2.is_some());
+        assert!(sem.clone().try_acquire_owned().is_none(), "no permit left");
+
+        let waiting = Rt::spawn({
+            let sem = sem.clone();
+            async move {
+                let _p = sem.acquire_owned().await;
+            }
+        });
+
+        Rt::sleep(Duration::from_millis(10)).await;
+        drop(p1);
+
+        Rt::timeout(Duration::from_millis(500), waiting)
+            .await
+            .expect("a released permit wakes up the waiting task")
+            .unwrap();
+        drop(p2);
+    }
+
+    pub async fn test_interval() {
+        let period = Duration::from_millis(20);
+        let start = Rt::Instant::now() + period;
+        let mut interval = Rt::interval_at(start, period, MissedTickBehavior::Delay);
+
+        for i in 0..3 {
+            let scheduled = interval.tick().await;
+            assert!(scheduled >= start + period * i, "scheduled: {:?}, i: {}", scheduled, i);
+            assert!(Rt::Instant::now() >= scheduled);
+        }
+
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= period * 2,
+            "elapsed: {:?}, expect >= {:?}",
+            elapsed,
+            period * 2
+        );
+    }
+}
@@ -24,4 +24,17 @@ where
 {
     /// Build a [`RaftLogStorage`] and [`RaftStateMachine`] implementation
     async fn build(&self) -> Result<(G, LS, SM), StorageError<C::NodeId>>;
+
+    /// Reopen the [`RaftLogStorage`] and [`RaftStateMachine`] built by [`Self::build()`] that
+    /// returned the guard `g`, as if the process crashed and restarted.
+    ///
+    /// The previously built stores are dropped without being shut down before this method is
+    /// called. It is used by the crash recovery tests in [`Suite`](`crate::testing::Suite`).
+    ///
+    /// Returns `Ok(None)` if reopening is not supported, e.g., for an in-memory store, and the
+    /// crash recovery tests are skipped. This is the default.
+    async fn reopen(&self, g: &G) -> Result<Option<(LS, SM)>, StorageError<C::NodeId>> {
+        let _ = g;
+        Ok(None)
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::future::Future;
@@ -6,6 +7,9 @@ use std::time::Duration;
 
 use anyerror::AnyError;
 use maplit::btreeset;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
 
 use crate::entry::RaftEntry;
 use crate::log_id::RaftLogId;
@@ -18,12 +22,15 @@ use crate::storage::LogState;
 use crate::storage::RaftLogReaderExt;
 use crate::storage::RaftLogStorage;
 use crate::storage::RaftStateMachine;
+use crate::storage::SnapshotMeta;
 use crate::storage::StorageHelper;
 use crate::testing::StoreBuilder;
 use crate::type_config::alias::AsyncRuntimeOf;
 use crate::vote::CommittedLeaderId;
 use crate::AsyncRuntime;
 use crate::LogId;
+use crate::LogIdOptionExt;
+use crate::LogIndexOptionExt;
 use crate::Membership;
 use crate::NodeId;
 use crate::OptionalSend;
@@ -36,6 +43,13 @@ use crate::Vote;
 
 const NODE_ID: u64 = 0;
 
+/// The number of crash-and-reopen rounds, each with a different seed, run by
+/// [`Suite::crash_recovery()`].
+const CRASH_RECOVERY_ROUNDS: u64 = 32;
+
+/// The max number of random operations before a crash.
+const CRASH_RECOVERY_MAX_OPS: usize = 64;
+
 /// Helper to construct a `BTreeSet` of `C::NodeId` from numbers.
 macro_rules! btreeset {
     ($($key:expr,)+) => (btreeset!($($key),+));
@@ -119,6 +133,7 @@ where
         run_fut(run_test(builder, Self::apply_multiple))?;
 
         run_fut(Self::transfer_snapshot(builder))?;
+        run_fut(Self::crash_recovery(builder))?;
 
         // TODO(xp): test: do_log_compaction
 
@@ -1213,6 +1228,43 @@ where
         Ok(())
     }
 
+    /// Write a random prefix of operations, "crash" by dropping the stores without shutting them
+    /// down, reopen them with [`StoreBuilder::reopen()`] and check the recovered data:
+    ///
+    /// - The vote is the last saved one.
+    /// - The logs are a contiguous range and every appended log that is not truncated is recovered.
+    ///   A purge or a truncation may not be persisted: the purged or truncated logs may reappear.
+    /// - The committed log id, the last applied log id and the current snapshot are not newer than
+    ///   the saved, applied or built ones.
+    ///
+    /// It is skipped if the builder does not support reopening.
+    pub async fn crash_recovery(builder: &B) -> Result<(), StorageError<C::NodeId>> {
+        for seed in 0..CRASH_RECOVERY_ROUNDS {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut model = CrashModel::<C>::default();
+
+            let (g, mut store, mut sm) = builder.build().await?;
+
+            let n = rng.gen_range(0..=CRASH_RECOVERY_MAX_OPS);
+            for _ in 0..n {
+                model.random_op(&mut rng, &mut store, &mut sm).await?;
+            }
+
+            tracing::info!(seed, n, "--- crash after {} operations", n);
+            drop(store);
+            drop(sm);
+
+            let Some((mut store, mut sm)) = builder.reopen(&g).await? else {
+                tracing::info!("--- reopen is not supported, skip crash recovery test");
+                return Ok(());
+            };
+
+            model.check(seed, &mut store, &mut sm).await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn feed_10_logs_vote_self(sto: &mut LS) -> Result<(), StorageError<C::NodeId>> {
         append(sto, [blank_ent_0::<C>(0, 0)]).await?;
 
@@ -1232,6 +1284,234 @@ where
     }
 }
 
+/// The data expected to be recovered after a crash, updated by every operation in
+/// [`Suite::crash_recovery()`].
+struct CrashModel<C>
+where C: RaftTypeConfig
+{
+    term: u64,
+
+    vote: Option<Vote<C::NodeId>>,
+
+    /// The log id at every index that is appended and not truncated, including the purged ones.
+    logs: BTreeMap<u64, LogId<C::NodeId>>,
+
+    /// The log id at every index that is truncated and not overwritten by a later append.
+    truncated: BTreeMap<u64, LogId<C::NodeId>>,
+
+    last_purged: Option<LogId<C::NodeId>>,
+    committed: Option<LogId<C::NodeId>>,
+    applied: Option<LogId<C::NodeId>>,
+
+    /// Every snapshot built.
+    snapshots: Vec<SnapshotMeta<C>>,
+}
+
+impl<C> Default for CrashModel<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            term: 1,
+            vote: None,
+            logs: BTreeMap::new(),
+            truncated: BTreeMap::new(),
+            last_purged: None,
+            committed: None,
+            applied: None,
+            snapshots: vec![],
+        }
+    }
+}
+
+impl<C> CrashModel<C>
+where
+    C: RaftTypeConfig,
+    C::NodeId: From<u64>,
+{
+    fn last_index(&self) -> Option<u64> {
+        self.logs.keys().next_back().copied()
+    }
+
+    /// Run a random operation that a `RaftCore` may issue on the stores.
+    async fn random_op<LS, SM>(
+        &mut self,
+        rng: &mut StdRng,
+        store: &mut LS,
+        sm: &mut SM,
+    ) -> Result<(), StorageError<C::NodeId>>
+    where
+        LS: RaftLogStorage<C>,
+        SM: RaftStateMachine<C>,
+    {
+        let last = self.last_index();
+
+        match rng.gen_range(0..7) {
+            0 => {
+                self.term += rng.gen_range(0..=1);
+                let vote = Vote::new(self.term, NODE_ID.into());
+                store.save_vote(&vote).await?;
+                self.vote = Some(vote);
+            }
+            1 => {
+                let start = last.next_index();
+                let entries = (start..start + rng.gen_range(1..=3))
+                    .map(|i| {
+                        if i == 0 {
+                            blank_ent_0::<C>(0, 0)
+                        } else {
+                            blank_ent_0::<C>(self.term, i)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                for e in entries.iter() {
+                    self.logs.insert(e.get_log_id().index, *e.get_log_id());
+                    self.truncated.remove(&e.get_log_id().index);
+                }
+                append(store, entries).await?;
+            }
+            2 => {
+                // Committed logs must not be truncated.
+                let since = self.committed.next_index().max(self.last_purged.next_index());
+                if let Some(last) = last {
+                    if since <= last {
+                        let index = rng.gen_range(since..=last);
+                        store.truncate(self.logs[&index]).await?;
+                        self.truncated.extend(self.logs.split_off(&index));
+                    }
+                }
+            }
+            3 => {
+                // Only applied logs can be purged.
+                if let Some(applied) = self.applied {
+                    let since = self.last_purged.next_index();
+                    if since <= applied.index {
+                        let log_id = self.logs[&rng.gen_range(since..=applied.index)];
+                        store.purge(log_id).await?;
+                        self.last_purged = Some(log_id);
+                    }
+                }
+            }
+            4 => {
+                let since = self.committed.next_index();
+                if let Some(last) = last {
+                    if since <= last {
+                        let log_id = self.logs[&rng.gen_range(since..=last)];
+                        store.save_committed(Some(log_id)).await?;
+                        self.committed = Some(log_id);
+                    }
+                }
+            }
+            5 => {
+                if self.applied < self.committed {
+                    let entries = self
+                        .logs
+                        .range(self.applied.next_index()..self.committed.next_index())
+                        .map(|(_, log_id)| C::Entry::new_blank(*log_id))
+                        .collect::<Vec<_>>();
+                    sm.apply(entries).await?;
+                    self.applied = self.committed;
+                }
+            }
+            _ => {
+                let snapshot = sm.get_snapshot_builder().await.build_snapshot().await?;
+                self.snapshots.push(snapshot.meta);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the recovered stores against the expected data.
+    async fn check<LS, SM>(&self, seed: u64, store: &mut LS, sm: &mut SM) -> Result<(), StorageError<C::NodeId>>
+    where
+        LS: RaftLogStorage<C>,
+        SM: RaftStateMachine<C>,
+    {
+        assert_eq!(self.vote, store.read_vote().await?, "seed: {}, recovered vote", seed);
+
+        let log_state = store.get_log_state().await?;
+        let last_purged = log_state.last_purged_log_id;
+        assert!(
+            last_purged <= self.last_purged,
+            "seed: {}, recovered last purged {:?} must not be newer than {:?}",
+            seed,
+            last_purged,
+            self.last_purged
+        );
+        if let Some(p) = last_purged {
+            assert_eq!(
+                Some(&p),
+                self.logs.get(&p.index),
+                "seed: {}, recovered last purged",
+                seed
+            );
+        }
+
+        let start = last_purged.next_index();
+        let entries = store.try_get_log_entries(start..).await?;
+        let log_ids = entries.iter().map(|e| *e.get_log_id()).collect::<Vec<_>>();
+
+        for (i, log_id) in log_ids.iter().enumerate() {
+            let index = start + i as u64;
+            assert_eq!(index, log_id.index, "seed: {}, recovered logs must be contiguous", seed);
+
+            let want = self.logs.get(&index).or_else(|| self.truncated.get(&index));
+            assert_eq!(want, Some(log_id), "seed: {}, recovered log at {}", seed, index);
+        }
+
+        let want_end = self.last_index().next_index().max(start);
+        assert!(
+            start + log_ids.len() as u64 >= want_end,
+            "seed: {}, recovered logs {:?} must include all appended logs since {} before {}",
+            seed,
+            log_ids,
+            start,
+            want_end
+        );
+
+        assert_eq!(
+            log_ids.last().copied().or(last_purged),
+            log_state.last_log_id,
+            "seed: {}, recovered last log id",
+            seed
+        );
+
+        let committed = store.read_committed().await?;
+        assert!(
+            committed <= self.committed,
+            "seed: {}, recovered committed {:?} must not be newer than {:?}",
+            seed,
+            committed,
+            self.committed
+        );
+
+        let (applied, _) = sm.applied_state().await?;
+        assert!(
+            applied <= self.applied,
+            "seed: {}, recovered applied {:?} must not be newer than {:?}",
+            seed,
+            applied,
+            self.applied
+        );
+        if let Some(a) = applied {
+            assert_eq!(Some(&a), self.logs.get(&a.index), "seed: {}, recovered applied", seed);
+        }
+
+        if let Some(snapshot) = sm.get_current_snapshot().await? {
+            assert!(
+                self.snapshots.contains(&snapshot.meta),
+                "seed: {}, recovered snapshot {} must be a built one",
+                seed,
+                snapshot.meta
+            );
+        }
+
+        Ok(())
+    }
+}
+
 /// Create a log id with node id 0 for testing.
 fn log_id_0<NID>(term: u64, index: u64) -> LogId<NID>
 where NID: NodeId + From<u64> {
@@ -1261,7 +1541,11 @@ where
     NID: NodeId,
     F: Future<Output = Result<(), StorageError<NID>>>,
 {
+    #[cfg(not(target_arch = "wasm32"))]
     let rt = tokio::runtime::Runtime::new().unwrap();
+    #[cfg(target_arch = "wasm32")]
+    let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
     rt.block_on(f)?;
     Ok(())
 }
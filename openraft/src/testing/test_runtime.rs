@@ -0,0 +1,307 @@
+//! An [`AsyncRuntime`] with a manually advanced clock, for unit tests.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Sub;
+use std::ops::SubAssign;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+
+use crate::async_runtime::MissedTickBehavior;
+use crate::async_runtime::SleepInterval;
+use crate::async_runtime::TokioBroadcast;
+use crate::async_runtime::TokioMpsc;
+use crate::async_runtime::TokioOneShotSender;
+use crate::AsyncRuntime;
+use crate::Instant;
+use crate::OptionalSend;
+use crate::TokioRuntime;
+
+thread_local! {
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+}
+
+/// A per-thread virtual clock.
+#[derive(Default)]
+struct Clock {
+    /// Time elapsed since the clock started.
+    now: Duration,
+
+    /// Tasks waiting for the clock to reach a deadline.
+    sleepers: Vec<(TestInstant, Waker)>,
+}
+
+/// Asynchronous runtime for unit tests, whose clock does not move until it is advanced with
+/// [`TestRuntime::advance`].
+///
+/// It allows testing time related behaviors, such as election timeouts and leader leases,
+/// without real sleeps. Tasks and channels are provided by `tokio`, only the time is virtual.
+///
+/// The clock is per-thread, thus tests must run on a single-threaded executor, e.g., a `tokio`
+/// current-thread runtime.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TestRuntime;
+
+impl TestRuntime {
+    /// Move the clock of the current thread forward by `duration`, and wake up the sleeps and
+    /// timeouts whose deadline is reached.
+    pub fn advance(duration: Duration) {
+        let wakers = CLOCK.with(|clock| {
+            let mut clock = clock.borrow_mut();
+            clock.now += duration;
+
+            let now = TestInstant(clock.now);
+            let (due, pending) = clock.sleepers.drain(..).partition(|(deadline, _)| *deadline <= now);
+            clock.sleepers = pending;
+            due
+        });
+
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// An instant on the virtual clock of [`TestRuntime`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TestInstant(Duration);
+
+impl Add<Duration> for TestInstant {
+    type Output = Self;
+
+    fn add(self, rhs: Duration) -> Self {
+        TestInstant(self.0 + rhs)
+    }
+}
+
+impl AddAssign<Duration> for TestInstant {
+    fn add_assign(&mut self, rhs: Duration) {
+        self.0 += rhs;
+    }
+}
+
+impl Sub<Duration> for TestInstant {
+    type Output = Self;
+
+    fn sub(self, rhs: Duration) -> Self {
+        TestInstant(self.0.saturating_sub(rhs))
+    }
+}
+
+impl Sub<TestInstant> for TestInstant {
+    type Output = Duration;
+
+    fn sub(self, rhs: TestInstant) -> Duration {
+        self.0.saturating_sub(rhs.0)
+    }
+}
+
+impl SubAssign<Duration> for TestInstant {
+    fn sub_assign(&mut self, rhs: Duration) {
+        self.0 = self.0.saturating_sub(rhs);
+    }
+}
+
+impl Instant for TestInstant {
+    #[inline]
+    fn now() -> Self {
+        CLOCK.with(|clock| TestInstant(clock.borrow().now))
+    }
+}
+
+/// A future that completes when the virtual clock reaches a deadline.
+pub struct TestSleep {
+    deadline: TestInstant,
+}
+
+impl Future for TestSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        CLOCK.with(|clock| {
+            let mut clock = clock.borrow_mut();
+
+            if TestInstant(clock.now) >= self.deadline {
+                return Poll::Ready(());
+            }
+
+            let registered = clock.sleepers.iter().any(|(d, w)| *d == self.deadline && w.will_wake(cx.waker()));
+            if !registered {
+                clock.sleepers.push((self.deadline, cx.waker().clone()));
+            }
+            Poll::Pending
+        })
+    }
+}
+
+/// The error returned by [`TestTimeout`] if the deadline is reached before the inner future
+/// completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("deadline has elapsed")]
+pub struct TestTimeoutError;
+
+/// Require a future to complete before a deadline on the virtual clock.
+pub struct TestTimeout<F> {
+    future: Pin<Box<F>>,
+    sleep: TestSleep,
+}
+
+impl<F> Future for TestTimeout<F>
+where F: Future
+{
+    type Output = Result<F::Output, TestTimeoutError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(v) = this.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(v));
+        }
+
+        match Pin::new(&mut this.sleep).poll(cx) {
+            Poll::Ready(_) => Poll::Ready(Err(TestTimeoutError)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl AsyncRuntime for TestRuntime {
+    type JoinError = <TokioRuntime as AsyncRuntime>::JoinError;
+    type JoinHandle<T: OptionalSend + 'static> = <TokioRuntime as AsyncRuntime>::JoinHandle<T>;
+    type Sleep = TestSleep;
+    type Instant = TestInstant;
+    type TimeoutError = TestTimeoutError;
+    type Timeout<R, T: Future<Output = R> + OptionalSend> = TestTimeout<T>;
+    type ThreadLocalRng = rand::rngs::ThreadRng;
+    type OneshotSender<T: OptionalSend> = TokioOneShotSender<T>;
+    type OneshotReceiver<T: OptionalSend> = tokio::sync::oneshot::Receiver<T>;
+    type OneshotReceiverError = tokio::sync::oneshot::error::RecvError;
+    type Mpsc = TokioMpsc;
+    type Broadcast = TokioBroadcast;
+    type CancellationToken = tokio_util::sync::CancellationToken;
+    type Semaphore = tokio::sync::Semaphore;
+    type Interval = SleepInterval<Self>;
+
+    #[inline]
+    fn spawn<T>(future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        TokioRuntime::spawn(future)
+    }
+
+    #[inline]
+    fn spawn_named<T>(name: &str, future: T) -> Self::JoinHandle<T::Output>
+    where
+        T: Future + OptionalSend + 'static,
+        T::Output: OptionalSend + 'static,
+    {
+        TokioRuntime::spawn_named(name, future)
+    }
+
+    #[inline]
+    fn spawn_blocking<F, T>(f: F) -> Self::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        TokioRuntime::spawn_blocking(f)
+    }
+
+    #[inline]
+    fn sleep(duration: Duration) -> Self::Sleep {
+        Self::sleep_until(Self::now() + duration)
+    }
+
+    #[inline]
+    fn sleep_until(deadline: Self::Instant) -> Self::Sleep {
+        TestSleep { deadline }
+    }
+
+    #[inline]
+    fn interval_at(start: Self::Instant, period: Duration, missed_tick_behavior: MissedTickBehavior) -> Self::Interval {
+        SleepInterval::new(start, period, missed_tick_behavior)
+    }
+
+    #[inline]
+    fn timeout<R, F: Future<Output = R> + OptionalSend>(duration: Duration, future: F) -> Self::Timeout<R, F> {
+        Self::timeout_at(Self::now() + duration, future)
+    }
+
+    #[inline]
+    fn timeout_at<R, F: Future<Output = R> + OptionalSend>(deadline: Self::Instant, future: F) -> Self::Timeout<R, F> {
+        TestTimeout {
+            future: Box::pin(future),
+            sleep: Self::sleep_until(deadline),
+        }
+    }
+
+    #[inline]
+    fn is_panic(join_error: &Self::JoinError) -> bool {
+        TokioRuntime::is_panic(join_error)
+    }
+
+    #[inline]
+    fn thread_rng() -> Self::ThreadLocalRng {
+        rand::thread_rng()
+    }
+
+    #[inline]
+    fn oneshot<T>() -> (Self::OneshotSender<T>, Self::OneshotReceiver<T>)
+    where T: OptionalSend {
+        TokioRuntime::oneshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::FutureExt;
+
+    use crate::testing::TestRuntime;
+    use crate::AsyncRuntime;
+
+    #[test]
+    fn test_now_does_not_move_until_advanced() {
+        let t0 = TestRuntime::now();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(t0, TestRuntime::now());
+
+        TestRuntime::advance(Duration::from_millis(100));
+        assert_eq!(Duration::from_millis(100), TestRuntime::now() - t0);
+    }
+
+    #[test]
+    fn test_sleep_completes_after_advance() {
+        let mut sleep = Box::pin(TestRuntime::sleep(Duration::from_secs(10)));
+        assert!(sleep.as_mut().now_or_never().is_none());
+
+        TestRuntime::advance(Duration::from_secs(9));
+        assert!(sleep.as_mut().now_or_never().is_none());
+
+        TestRuntime::advance(Duration::from_secs(1));
+        assert!(sleep.as_mut().now_or_never().is_some());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let ready = TestRuntime::timeout(Duration::from_secs(1), async { 1 });
+        assert_eq!(Some(Ok(1)), ready.now_or_never().map(|r| r.map_err(|_| ())));
+
+        let mut pending = Box::pin(TestRuntime::timeout(
+            Duration::from_secs(1),
+            futures::future::pending::<()>(),
+        ));
+        assert!(pending.as_mut().now_or_never().is_none());
+
+        TestRuntime::advance(Duration::from_secs(1));
+        assert!(matches!(pending.as_mut().now_or_never(), Some(Err(_))));
+    }
+}
@@ -12,7 +12,6 @@ use tracing::trace_span;
 use tracing::Instrument;
 
 use crate::AsyncRuntime;
-use crate::Instant;
 use crate::OptionalSend;
 
 #[allow(dead_code)]
@@ -60,7 +59,7 @@ impl<RT: AsyncRuntime> RaftTimer<RT> for Timeout<RT> {
         let (tx, rx) = oneshot::channel();
 
         let inner = TimeoutInner {
-            init: RT::Instant::now(),
+            init: RT::now(),
             relative_deadline: AtomicU64::new(timeout.as_micros() as u64),
         };
 
@@ -79,7 +78,7 @@ impl<RT: AsyncRuntime> RaftTimer<RT> for Timeout<RT> {
     }
 
     fn update_timeout(&self, timeout: Duration) {
-        let since_init = RT::Instant::now() + timeout - self.inner.init;
+        let since_init = RT::now() + timeout - self.inner.init;
 
         let new_at = since_init.as_micros() as u64;
 
@@ -4,6 +4,7 @@ use tokio::time::sleep;
 use tokio::time::Instant;
 
 use crate::async_runtime::AsyncOneshotSendExt;
+use crate::testing::TestRuntime;
 use crate::timer::timeout::RaftTimer;
 use crate::timer::Timeout;
 use crate::AsyncRuntime;
@@ -111,3 +112,34 @@ async fn test_timeout_inner() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_timeout_with_virtual_clock() -> anyhow::Result<()> {
+    let rt = tokio::runtime::Builder::new_current_thread().build()?;
+    tokio::task::LocalSet::new().block_on(&rt, async {
+        let (tx, mut rx) = <TestRuntime as AsyncRuntime>::oneshot();
+        let t = Timeout::<TestRuntime>::new(
+            || {
+                let _ = tx.send(1u64);
+            },
+            Duration::from_millis(500),
+        );
+        tokio::task::yield_now().await;
+
+        TestRuntime::advance(Duration::from_millis(400));
+        tokio::task::yield_now().await;
+        assert!(rx.try_recv().is_err(), "not timed out");
+
+        // Extend the deadline to 800 ms since start.
+        t.update_timeout(Duration::from_millis(400));
+
+        TestRuntime::advance(Duration::from_millis(300));
+        tokio::task::yield_now().await;
+        assert!(rx.try_recv().is_err(), "deadline is extended");
+
+        TestRuntime::advance(Duration::from_millis(100));
+        assert_eq!(1u64, rx.await?);
+
+        Ok(())
+    })
+}
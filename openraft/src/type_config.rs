@@ -2,14 +2,18 @@ use std::fmt::Debug;
 
 use crate::entry::FromAppData;
 use crate::entry::RaftEntry;
+use crate::network::PayloadCodec;
 use crate::raft::responder::Responder;
+use crate::storage::StorageCipher;
 use crate::AppData;
 use crate::AppDataResponse;
+use crate::AppError;
 use crate::AsyncRuntime;
 use crate::Node;
 use crate::NodeId;
 use crate::OptionalSend;
 use crate::OptionalSync;
+use crate::SnapshotTrigger;
 
 /// Configuration of types used by the [`Raft`] core engine.
 ///
@@ -46,6 +50,14 @@ pub trait RaftTypeConfig:
     /// Application-specific response data returned by the state machine.
     type R: AppDataResponse;
 
+    /// Application-specific error returned by the state machine when it rejects an entry.
+    ///
+    /// See [`RaftStateMachine::try_apply()`]. The default is [`Infallible`].
+    ///
+    /// [`RaftStateMachine::try_apply()`]: crate::storage::RaftStateMachine::try_apply
+    /// [`Infallible`]: crate::error::Infallible
+    type AppError: AppError;
+
     /// A Raft node's ID.
     type NodeId: NodeId;
 
@@ -53,6 +65,11 @@ pub trait RaftTypeConfig:
     type Node: Node;
 
     /// Raft log entry, which can be built from an AppData.
+    ///
+    /// [`RawEntry`] keeps the AppData encoded until it is applied, so that the entries are
+    /// replicated without being decoded and encoded again.
+    ///
+    /// [`RawEntry`]: crate::entry::RawEntry
     type Entry: RaftEntry<Self> + FromAppData<Self::D>;
 
     /// Snapshot data for exposing a snapshot for reading & writing.
@@ -73,6 +90,32 @@ pub trait RaftTypeConfig:
     /// [`Raft::client_write`]: `crate::raft::Raft::client_write`
     /// [`WriteResult`]: `crate::raft::message::ClientWriteResult`
     type Responder: Responder<Self>;
+
+    /// Compresses the log entries and snapshot chunks sent to other nodes.
+    ///
+    /// It is applied by the [`RaftNetwork`] implementation after serializing an RPC.
+    /// The default is [`NoCompression`].
+    ///
+    /// [`RaftNetwork`]: crate::network::RaftNetwork
+    /// [`NoCompression`]: crate::network::NoCompression
+    type PayloadCodec: PayloadCodec;
+
+    /// Encrypts the log entries and snapshots before they are persisted.
+    ///
+    /// It is applied to the entries serialized by [`RaftLogStorage::encode_entry()`], and by a
+    /// storage implementation to the data it persists. The default is [`NoEncryption`].
+    ///
+    /// [`RaftLogStorage::encode_entry()`]: crate::storage::RaftLogStorage::encode_entry
+    /// [`NoEncryption`]: crate::storage::NoEncryption
+    type StorageCipher: StorageCipher;
+
+    /// Decides when to build a snapshot if [`SnapshotPolicy::Custom`] is configured.
+    ///
+    /// The default is [`NoSnapshotTrigger`], which never builds a snapshot.
+    ///
+    /// [`SnapshotPolicy::Custom`]: crate::SnapshotPolicy::Custom
+    /// [`NoSnapshotTrigger`]: crate::NoSnapshotTrigger
+    type SnapshotTrigger: SnapshotTrigger;
 }
 
 #[allow(dead_code)]
@@ -82,12 +125,16 @@ pub trait RaftTypeConfig:
 ///
 /// [`type-alias`]: crate::docs::feature_flags#feature-flag-type-alias
 pub mod alias {
+    use crate::async_runtime::Broadcast;
+    use crate::async_runtime::Mpsc;
+    use crate::async_runtime::Semaphore;
     use crate::raft::responder::Responder;
     use crate::AsyncRuntime;
     use crate::RaftTypeConfig;
 
     pub type DOf<C> = <C as RaftTypeConfig>::D;
     pub type ROf<C> = <C as RaftTypeConfig>::R;
+    pub type AppErrorOf<C> = <C as RaftTypeConfig>::AppError;
     pub type NodeIdOf<C> = <C as RaftTypeConfig>::NodeId;
     pub type NodeOf<C> = <C as RaftTypeConfig>::Node;
     pub type EntryOf<C> = <C as RaftTypeConfig>::Entry;
@@ -95,6 +142,9 @@ pub mod alias {
     pub type AsyncRuntimeOf<C> = <C as RaftTypeConfig>::AsyncRuntime;
     pub type ResponderOf<C> = <C as RaftTypeConfig>::Responder;
     pub type ResponderReceiverOf<C> = <ResponderOf<C> as Responder<C>>::Receiver;
+    pub type PayloadCodecOf<C> = <C as RaftTypeConfig>::PayloadCodec;
+    pub type StorageCipherOf<C> = <C as RaftTypeConfig>::StorageCipher;
+    pub type SnapshotTriggerOf<C> = <C as RaftTypeConfig>::SnapshotTrigger;
 
     type Rt<C> = AsyncRuntimeOf<C>;
 
@@ -107,6 +157,15 @@ pub mod alias {
     pub type OneshotSenderOf<C, T> = <Rt<C> as AsyncRuntime>::OneshotSender<T>;
     pub type OneshotReceiverErrorOf<C> = <Rt<C> as AsyncRuntime>::OneshotReceiverError;
     pub type OneshotReceiverOf<C, T> = <Rt<C> as AsyncRuntime>::OneshotReceiver<T>;
+    pub type MpscOf<C> = <Rt<C> as AsyncRuntime>::Mpsc;
+    pub type MpscSenderOf<C, T> = <MpscOf<C> as Mpsc>::Sender<T>;
+    pub type MpscReceiverOf<C, T> = <MpscOf<C> as Mpsc>::Receiver<T>;
+    pub type BroadcastOf<C> = <Rt<C> as AsyncRuntime>::Broadcast;
+    pub type BroadcastSenderOf<C, T> = <BroadcastOf<C> as Broadcast>::Sender<T>;
+    pub type BroadcastReceiverOf<C, T> = <BroadcastOf<C> as Broadcast>::Receiver<T>;
+    pub type CancellationTokenOf<C> = <Rt<C> as AsyncRuntime>::CancellationToken;
+    pub type SemaphoreOf<C> = <Rt<C> as AsyncRuntime>::Semaphore;
+    pub type SemaphorePermitOf<C> = <SemaphoreOf<C> as Semaphore>::Permit;
 
     // Usually used types
     pub type LogIdOf<C> = crate::LogId<NodeIdOf<C>>;
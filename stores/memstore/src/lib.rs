@@ -5,12 +5,14 @@
 
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::fmt;
 use std::fmt::Debug;
 use std::io::Cursor;
 use std::ops::RangeBounds;
 use std::sync::Arc;
 use std::sync::Mutex;
 
+use openraft::alias::AsyncRuntimeOf;
 use openraft::alias::SnapshotDataOf;
 use openraft::storage::LogFlushed;
 use openraft::storage::LogState;
@@ -19,6 +21,9 @@ use openraft::storage::RaftLogStorage;
 use openraft::storage::RaftSnapshotBuilder;
 use openraft::storage::RaftStateMachine;
 use openraft::storage::Snapshot;
+use openraft::storage::SnapshotBuildProgress;
+use openraft::AnyError;
+use openraft::AsyncRuntime;
 use openraft::Entry;
 use openraft::EntryPayload;
 use openraft::LogId;
@@ -71,6 +76,25 @@ impl IntoMemClientRequest<ClientRequest> for ClientRequest {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ClientResponse(pub Option<String>);
 
+/// The error returned when the state machine rejects a [`ClientRequest`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum MemAppError {
+    /// A request with an empty `status` is rejected.
+    EmptyStatus { client: String, serial: u64 },
+}
+
+impl fmt::Display for MemAppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemAppError::EmptyStatus { client, serial } => {
+                write!(f, "empty status in request {} of client {}", serial, client)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MemAppError {}
+
 pub type MemNodeId = u64;
 
 openraft::declare_raft_types!(
@@ -78,6 +102,7 @@ openraft::declare_raft_types!(
     pub TypeConfig:
         D = ClientRequest,
         R = ClientResponse,
+        AppError = MemAppError,
         Node = (),
 );
 
@@ -101,6 +126,10 @@ pub struct MemStoreStateMachine {
     pub client_serial_responses: HashMap<String, (u64, Option<String>)>,
     /// The current status of a client by ID.
     pub client_status: HashMap<String, String>,
+
+    /// The index of the last log that changed a client, to build a delta snapshot.
+    #[serde(default)]
+    pub client_modified: HashMap<String, u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -111,7 +140,14 @@ pub enum BlockOperation {
     /// This will prevent building snapshot returning but should not block applying entries.
     DelayBuildingSnapshot,
     BuildSnapshot,
+    /// Block building a snapshot with a synchronous sleep in the builder future, which stalls the
+    /// thread that polls it.
+    BuildSnapshotSync,
     PurgeLog,
+    /// Delay reporting that appended log entries are flushed, without blocking the append.
+    FlushLog,
+    /// Block installing a snapshot on the state machine.
+    InstallSnapshot,
 }
 
 /// Block operations for testing purposes.
@@ -151,6 +187,9 @@ pub struct MemLogStore {
 
     /// The current hard state.
     vote: RwLock<Option<Vote<MemNodeId>>>,
+
+    /// The max number of replicated entries to append at a time, for testing purposes.
+    appendable_limit: Mutex<Option<usize>>,
 }
 
 impl MemLogStore {
@@ -163,8 +202,17 @@ impl MemLogStore {
             log,
             block,
             vote: RwLock::new(None),
+            appendable_limit: Mutex::new(None),
         }
     }
+
+    /// Limit the number of entries replicated from the leader that are appended at a time, to
+    /// emulate a storage that is running out of space.
+    ///
+    /// This method is only used for testing purposes.
+    pub fn set_appendable_limit(&self, limit: Option<usize>) {
+        *self.appendable_limit.lock().unwrap() = limit;
+    }
 }
 
 /// An in-memory key-value storage implementing the `RaftStateMachine` trait.
@@ -177,6 +225,22 @@ pub struct MemStateMachine {
     /// The current snapshot.
     current_snapshot: RwLock<Option<MemStoreSnapshot>>,
 
+    /// The metadata of every built or installed snapshot that is not deleted, for testing
+    /// purposes. Only the data of the current snapshot is kept.
+    stored_snapshots: Mutex<Vec<SnapshotMeta<TypeConfig>>>,
+
+    /// The last log id of every committed apply batch, for testing purposes.
+    apply_batches: Mutex<Vec<LogId<MemNodeId>>>,
+
+    /// The number of the following calls to `apply()` to fail, for testing purposes.
+    apply_failures: Mutex<u64>,
+
+    /// Whether to build delta snapshots, for testing purposes.
+    delta_snapshot: Mutex<bool>,
+
+    /// The number of installed delta snapshots, for testing purposes.
+    delta_installed: Mutex<u64>,
+
     /// Block operations for testing purposes.
     pub block: BlockConfig,
 }
@@ -190,6 +254,11 @@ impl MemStateMachine {
             sm,
             snapshot_idx: Arc::new(Mutex::new(0)),
             current_snapshot,
+            stored_snapshots: Mutex::new(Vec::new()),
+            apply_batches: Mutex::new(Vec::new()),
+            apply_failures: Mutex::new(0),
+            delta_snapshot: Mutex::new(false),
+            delta_installed: Mutex::new(0),
             block,
         }
     }
@@ -202,11 +271,40 @@ impl MemStateMachine {
         *current = None;
     }
 
+    /// Get the metadata of the snapshots that are not deleted, for testing purposes.
+    pub fn get_stored_snapshots(&self) -> Vec<SnapshotMeta<TypeConfig>> {
+        self.stored_snapshots.lock().unwrap().clone()
+    }
+
     /// Get a handle to the state machine for testing purposes.
     pub async fn get_state_machine(&self) -> MemStoreStateMachine {
         self.sm.write().await.clone()
     }
 
+    /// Get the last log id of every committed apply batch for testing purposes.
+    pub fn get_apply_batches(&self) -> Vec<LogId<MemNodeId>> {
+        self.apply_batches.lock().unwrap().clone()
+    }
+
+    /// Make the next `n` calls to `apply()` return a storage error without applying anything.
+    ///
+    /// This method is only used for testing purposes.
+    pub fn set_apply_failures(&self, n: u64) {
+        *self.apply_failures.lock().unwrap() = n;
+    }
+
+    /// Enable or disable building delta snapshots.
+    ///
+    /// This method is only used for testing purposes.
+    pub fn set_delta_snapshot(&self, enabled: bool) {
+        *self.delta_snapshot.lock().unwrap() = enabled;
+    }
+
+    /// Get the number of installed delta snapshots for testing purposes.
+    pub fn get_delta_installed(&self) -> u64 {
+        *self.delta_installed.lock().unwrap()
+    }
+
     /// Clear the state machine for testing purposes.
     pub async fn clear_state_machine(&self) {
         let mut sm = self.sm.write().await;
@@ -247,28 +345,51 @@ impl RaftLogReader<TypeConfig> for Arc<MemLogStore> {
 impl RaftSnapshotBuilder<TypeConfig> for Arc<MemStateMachine> {
     #[tracing::instrument(level = "trace", skip(self))]
     async fn build_snapshot(&mut self) -> Result<Snapshot<TypeConfig>, StorageError<MemNodeId>> {
-        let data;
-        let last_applied_log;
-        let last_membership;
+        self.build_snapshot_with_progress(SnapshotBuildProgress::default()).await
+    }
 
+    #[tracing::instrument(level = "trace", skip(self, progress))]
+    async fn build_snapshot_with_progress(
+        &mut self,
+        progress: SnapshotBuildProgress<TypeConfig>,
+    ) -> Result<Snapshot<TypeConfig>, StorageError<MemNodeId>> {
         if let Some(d) = self.block.get_blocking(&BlockOperation::DelayBuildingSnapshot) {
             tracing::info!(?d, "delay snapshot build");
             tokio::time::sleep(d).await;
         }
 
-        {
-            // Serialize the data of the state machine.
-            let sm = self.sm.read().await;
-            data = serde_json::to_vec(&*sm).map_err(|e| StorageIOError::read_state_machine(&e))?;
+        if let Some(d) = self.block.get_blocking(&BlockOperation::BuildSnapshotSync) {
+            tracing::info!(?d, "block snapshot build synchronously");
+            std::thread::sleep(d);
+        }
 
-            last_applied_log = sm.last_applied_log;
-            last_membership = sm.last_membership.clone();
+        // Serializing the state machine is CPU intensive, run it off the async executor.
+        let this = self.clone();
+        let (data, last_applied_log, last_membership) = AsyncRuntimeOf::<TypeConfig>::spawn_blocking(move || {
+            let sm = this.sm.blocking_read();
+            let data = serde_json::to_vec(&*sm).map_err(|e| StorageIOError::read_state_machine(&e))?;
+            progress.add(sm.client_status.len() as u64, data.len() as u64);
 
-            if let Some(d) = self.block.get_blocking(&BlockOperation::BuildSnapshot) {
+            if let Some(d) = this.block.get_blocking(&BlockOperation::BuildSnapshot) {
                 tracing::info!(?d, "blocking snapshot build");
-                tokio::time::sleep(d).await;
+
+                // Sleep in steps to release the state machine soon after being cancelled.
+                let deadline = std::time::Instant::now() + d;
+                while std::time::Instant::now() < deadline && !progress.is_cancelled() {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
             }
-        }
+
+            if progress.is_cancelled() {
+                return Err(
+                    StorageIOError::read_state_machine(&AnyError::error("snapshot building is cancelled")).into(),
+                );
+            }
+
+            Ok::<_, StorageError<MemNodeId>>((data, sm.last_applied_log, sm.last_membership.clone()))
+        })
+        .await
+        .map_err(|e| StorageIOError::read_state_machine(&e))??;
 
         let snapshot_size = data.len();
 
@@ -288,6 +409,8 @@ impl RaftSnapshotBuilder<TypeConfig> for Arc<MemStateMachine> {
             last_log_id: last_applied_log,
             last_membership,
             snapshot_id,
+            delta_base: None,
+            checksum: None,
         };
 
         let snapshot = MemStoreSnapshot {
@@ -299,6 +422,7 @@ impl RaftSnapshotBuilder<TypeConfig> for Arc<MemStateMachine> {
             let mut current_snapshot = self.current_snapshot.write().await;
             *current_snapshot = Some(snapshot);
         }
+        self.stored_snapshots.lock().unwrap().push(meta.clone());
 
         tracing::info!(snapshot_size, "log compaction complete");
 
@@ -307,6 +431,69 @@ impl RaftSnapshotBuilder<TypeConfig> for Arc<MemStateMachine> {
             snapshot: Box::new(Cursor::new(data)),
         })
     }
+
+    /// Build a delta with the last state of every client changed after `since`.
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn build_delta(
+        &mut self,
+        since: LogId<MemNodeId>,
+    ) -> Result<Option<Snapshot<TypeConfig>>, StorageError<MemNodeId>> {
+        if !*self.delta_snapshot.lock().unwrap() {
+            return Ok(None);
+        }
+
+        let delta = {
+            let sm = self.sm.read().await;
+
+            let mut delta = MemStoreStateMachine {
+                last_applied_log: sm.last_applied_log,
+                last_membership: sm.last_membership.clone(),
+                ..Default::default()
+            };
+
+            for (client, index) in sm.client_modified.iter().filter(|(_, index)| **index > since.index) {
+                delta.client_modified.insert(client.clone(), *index);
+                if let Some(status) = sm.client_status.get(client) {
+                    delta.client_status.insert(client.clone(), status.clone());
+                }
+                if let Some(resp) = sm.client_serial_responses.get(client) {
+                    delta.client_serial_responses.insert(client.clone(), resp.clone());
+                }
+            }
+            delta
+        };
+
+        let data = serde_json::to_vec(&delta).map_err(|e| StorageIOError::read_state_machine(&e))?;
+
+        let snapshot_idx = {
+            let mut l = self.snapshot_idx.lock().unwrap();
+            *l += 1;
+            *l
+        };
+
+        let snapshot_id = match delta.last_applied_log {
+            Some(last) => format!(
+                "{}-{}-{}-delta-{}",
+                last.leader_id, last.index, snapshot_idx, since.index
+            ),
+            None => format!("--{}-delta-{}", snapshot_idx, since.index),
+        };
+
+        let meta = SnapshotMeta {
+            last_log_id: delta.last_applied_log,
+            last_membership: delta.last_membership,
+            snapshot_id,
+            delta_base: Some(since),
+            checksum: None,
+        };
+
+        tracing::info!(snapshot_size = data.len(), "delta snapshot built: {}", meta);
+
+        Ok(Some(Snapshot {
+            meta,
+            snapshot: Box::new(Cursor::new(data)),
+        }))
+    }
 }
 
 impl RaftLogStorage<TypeConfig> for Arc<MemLogStore> {
@@ -362,6 +549,11 @@ impl RaftLogStorage<TypeConfig> for Arc<MemLogStore> {
         Ok(*self.committed.read().await)
     }
 
+    async fn appendable_count(&mut self, entries: &[Entry<TypeConfig>]) -> usize {
+        let limit = *self.appendable_limit.lock().unwrap();
+        limit.map_or(entries.len(), |l| std::cmp::min(l, entries.len()))
+    }
+
     #[tracing::instrument(level = "trace", skip_all)]
     async fn append<I>(&mut self, entries: I, callback: LogFlushed<TypeConfig>) -> Result<(), StorageError<MemNodeId>>
     where I: IntoIterator<Item = Entry<TypeConfig>> + OptionalSend {
@@ -372,6 +564,15 @@ impl RaftLogStorage<TypeConfig> for Arc<MemLogStore> {
             log.insert(entry.log_id.index, s);
         }
 
+        if let Some(d) = self.block.get_blocking(&BlockOperation::FlushLog) {
+            tracing::info!(?d, "delay flushing log");
+            tokio::spawn(async move {
+                tokio::time::sleep(d).await;
+                callback.log_io_completed(Ok(()));
+            });
+            return Ok(());
+        }
+
         callback.log_io_completed(Ok(()));
         Ok(())
     }
@@ -430,12 +631,33 @@ impl RaftStateMachine<TypeConfig> for Arc<MemStateMachine> {
         Ok((sm.last_applied_log, sm.last_membership.clone()))
     }
 
-    #[tracing::instrument(level = "trace", skip(self, entries))]
     async fn apply<I>(&mut self, entries: I) -> Result<Vec<ClientResponse>, StorageError<MemNodeId>>
     where
         I: IntoIterator<Item = Entry<TypeConfig>> + OptionalSend,
         I::IntoIter: OptionalSend,
     {
+        let res = self.try_apply(entries).await?;
+        Ok(res.into_iter().map(|r| r.unwrap_or(ClientResponse(None))).collect())
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, entries))]
+    async fn try_apply<I>(
+        &mut self,
+        entries: I,
+    ) -> Result<Vec<Result<ClientResponse, MemAppError>>, StorageError<MemNodeId>>
+    where
+        I: IntoIterator<Item = Entry<TypeConfig>> + OptionalSend,
+        I::IntoIter: OptionalSend,
+    {
+        {
+            let mut failures = self.apply_failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                let e = std::io::Error::new(std::io::ErrorKind::Other, "injected apply failure");
+                return Err(StorageIOError::write_state_machine(&e).into());
+            }
+        }
+
         let mut res = Vec::new();
 
         let mut sm = self.sm.write().await;
@@ -446,27 +668,40 @@ impl RaftStateMachine<TypeConfig> for Arc<MemStateMachine> {
             sm.last_applied_log = Some(entry.log_id);
 
             match entry.payload {
-                EntryPayload::Blank => res.push(ClientResponse(None)),
+                EntryPayload::Blank => res.push(Ok(ClientResponse(None))),
                 EntryPayload::Normal(ref data) => {
+                    if data.status.is_empty() {
+                        res.push(Err(MemAppError::EmptyStatus {
+                            client: data.client.clone(),
+                            serial: data.serial,
+                        }));
+                        continue;
+                    }
                     if let Some((serial, r)) = sm.client_serial_responses.get(&data.client) {
                         if serial == &data.serial {
-                            res.push(ClientResponse(r.clone()));
+                            res.push(Ok(ClientResponse(r.clone())));
                             continue;
                         }
                     }
                     let previous = sm.client_status.insert(data.client.clone(), data.status.clone());
                     sm.client_serial_responses.insert(data.client.clone(), (data.serial, previous.clone()));
-                    res.push(ClientResponse(previous));
+                    sm.client_modified.insert(data.client.clone(), entry.log_id.index);
+                    res.push(Ok(ClientResponse(previous)));
                 }
                 EntryPayload::Membership(ref mem) => {
                     sm.last_membership = StoredMembership::new(Some(entry.log_id), mem.clone());
-                    res.push(ClientResponse(None))
+                    res.push(Ok(ClientResponse(None)))
                 }
             };
         }
         Ok(res)
     }
 
+    async fn commit_apply_batch(&mut self, last_applied: LogId<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        self.apply_batches.lock().unwrap().push(last_applied);
+        Ok(())
+    }
+
     async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
         self.clone()
     }
@@ -482,6 +717,11 @@ impl RaftStateMachine<TypeConfig> for Arc<MemStateMachine> {
         meta: &SnapshotMeta<TypeConfig>,
         snapshot: Box<SnapshotDataOf<TypeConfig>>,
     ) -> Result<(), StorageError<MemNodeId>> {
+        if let Some(d) = self.block.get_blocking(&BlockOperation::InstallSnapshot) {
+            tracing::info!(?d, "block installing snapshot");
+            tokio::time::sleep(d).await;
+        }
+
         tracing::info!(
             { snapshot_size = snapshot.get_ref().len() },
             "decoding snapshot for installation"
@@ -510,6 +750,29 @@ impl RaftStateMachine<TypeConfig> for Arc<MemStateMachine> {
         // Update current snapshot.
         let mut current_snapshot = self.current_snapshot.write().await;
         *current_snapshot = Some(new_snapshot);
+        self.stored_snapshots.lock().unwrap().push(meta.clone());
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, snapshot))]
+    async fn install_delta_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<TypeConfig>,
+        snapshot: Box<SnapshotDataOf<TypeConfig>>,
+    ) -> Result<(), StorageError<MemNodeId>> {
+        let delta: MemStoreStateMachine = serde_json::from_slice(snapshot.get_ref())
+            .map_err(|e| StorageIOError::read_snapshot(Some(meta.signature()), &e))?;
+
+        {
+            let mut sm = self.sm.write().await;
+            sm.last_applied_log = delta.last_applied_log;
+            sm.last_membership = delta.last_membership;
+            sm.client_status.extend(delta.client_status);
+            sm.client_serial_responses.extend(delta.client_serial_responses);
+            sm.client_modified.extend(delta.client_modified);
+        }
+
+        *self.delta_installed.lock().unwrap() += 1;
         Ok(())
     }
 
@@ -526,4 +789,13 @@ impl RaftStateMachine<TypeConfig> for Arc<MemStateMachine> {
             None => Ok(None),
         }
     }
+
+    async fn list_snapshots(&mut self) -> Result<Vec<SnapshotMeta<TypeConfig>>, StorageError<MemNodeId>> {
+        Ok(self.get_stored_snapshots())
+    }
+
+    async fn delete_snapshot(&mut self, meta: &SnapshotMeta<TypeConfig>) -> Result<(), StorageError<MemNodeId>> {
+        self.stored_snapshots.lock().unwrap().retain(|m| m.snapshot_id != meta.snapshot_id);
+        Ok(())
+    }
 }
@@ -1,9 +1,41 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::io::Cursor;
 use std::sync::Arc;
 
+use openraft::storage::group_key_prefix;
+use openraft::storage::migrate;
+use openraft::storage::BlockingRaftLogStorage;
+use openraft::storage::BlockingRaftStateMachine;
+use openraft::storage::BlockingStorageAdapter;
+use openraft::storage::KvOp;
+use openraft::storage::KvValue;
+use openraft::storage::LogState;
+use openraft::storage::PrefixedLogStorage;
+use openraft::storage::RaftLogStorage;
+use openraft::storage::RaftLogStorageExt;
+use openraft::storage::RaftStateMachine;
+use openraft::storage::RaftVoteStorage;
+use openraft::storage::SharedKvStore;
+use openraft::storage::Snapshot;
+use openraft::storage::WithVoteStorage;
+use openraft::testing::blank_ent;
+use openraft::testing::log_id;
+use openraft::testing::membership_ent;
 use openraft::testing::StoreBuilder;
 use openraft::testing::Suite;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::LogId;
+use openraft::RaftLogId;
+use openraft::RaftLogReader;
+use openraft::SnapshotMeta;
 use openraft::StorageError;
+use openraft::StorageIOError;
+use openraft::StoredMembership;
+use openraft::Vote;
 
+use crate::ClientResponse;
 use crate::MemLogStore;
 use crate::MemNodeId;
 use crate::MemStateMachine;
@@ -18,8 +50,394 @@ impl StoreBuilder<TypeConfig, Arc<MemLogStore>, Arc<MemStateMachine>, ()> for Me
     }
 }
 
+/// A vote store that keeps the vote in memory.
+#[derive(Default)]
+struct MemVoteStore {
+    vote: Option<Vote<MemNodeId>>,
+}
+
+impl RaftVoteStorage<TypeConfig> for MemVoteStore {
+    async fn save_vote(&mut self, vote: &Vote<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        self.vote = Some(*vote);
+        Ok(())
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<MemNodeId>>, StorageError<MemNodeId>> {
+        Ok(self.vote)
+    }
+}
+
+type MemStoreWithVote = WithVoteStorage<TypeConfig, Arc<MemLogStore>, MemVoteStore>;
+
+struct WithVoteBuilder {}
+
+impl StoreBuilder<TypeConfig, MemStoreWithVote, Arc<MemStateMachine>, ()> for WithVoteBuilder {
+    async fn build(&self) -> Result<((), MemStoreWithVote, Arc<MemStateMachine>), StorageError<MemNodeId>> {
+        let (log_store, sm) = crate::new_mem_store();
+        Ok(((), WithVoteStorage::new(log_store, MemVoteStore::default()), sm))
+    }
+}
+
+/// A synchronous log store that keeps the logs in memory.
+#[derive(Default)]
+struct SyncLogStore {
+    vote: Option<Vote<MemNodeId>>,
+    committed: Option<LogId<MemNodeId>>,
+    last_purged_log_id: Option<LogId<MemNodeId>>,
+    log: BTreeMap<u64, Entry<TypeConfig>>,
+}
+
+impl BlockingRaftLogStorage<TypeConfig> for SyncLogStore {
+    fn get_log_state(&mut self) -> Result<LogState<TypeConfig>, StorageError<MemNodeId>> {
+        let last = self.log.values().last().map(|e| *e.get_log_id());
+        Ok(LogState {
+            last_purged_log_id: self.last_purged_log_id,
+            last_log_id: last.or(self.last_purged_log_id),
+        })
+    }
+
+    fn try_get_log_entries(&mut self, start: u64, end: u64) -> Result<Vec<Entry<TypeConfig>>, StorageError<MemNodeId>> {
+        Ok(self.log.range(start..end).map(|(_, e)| e.clone()).collect())
+    }
+
+    fn read_vote(&mut self) -> Result<Option<Vote<MemNodeId>>, StorageError<MemNodeId>> {
+        Ok(self.vote)
+    }
+
+    fn save_vote(&mut self, vote: &Vote<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        self.vote = Some(*vote);
+        Ok(())
+    }
+
+    fn save_committed(&mut self, committed: Option<LogId<MemNodeId>>) -> Result<(), StorageError<MemNodeId>> {
+        self.committed = committed;
+        Ok(())
+    }
+
+    fn read_committed(&mut self) -> Result<Option<LogId<MemNodeId>>, StorageError<MemNodeId>> {
+        Ok(self.committed)
+    }
+
+    fn append(&mut self, entries: Vec<Entry<TypeConfig>>) -> Result<(), StorageError<MemNodeId>> {
+        for entry in entries {
+            self.log.insert(entry.log_id.index, entry);
+        }
+        Ok(())
+    }
+
+    fn truncate(&mut self, log_id: LogId<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        self.log.split_off(&log_id.index);
+        Ok(())
+    }
+
+    fn purge(&mut self, log_id: LogId<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        self.last_purged_log_id = Some(log_id);
+        self.log = self.log.split_off(&(log_id.index + 1));
+        Ok(())
+    }
+}
+
+/// A synchronous state machine that keeps only the last applied log id and membership.
+#[derive(Default)]
+struct SyncStateMachine {
+    last_applied: Option<LogId<MemNodeId>>,
+    last_membership: StoredMembership<TypeConfig>,
+    snapshot_idx: u64,
+    current_snapshot: Option<(SnapshotMeta<TypeConfig>, Vec<u8>)>,
+}
+
+impl BlockingRaftStateMachine<TypeConfig> for SyncStateMachine {
+    fn applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<MemNodeId>>, StoredMembership<TypeConfig>), StorageError<MemNodeId>> {
+        Ok((self.last_applied, self.last_membership.clone()))
+    }
+
+    fn apply(&mut self, entries: Vec<Entry<TypeConfig>>) -> Result<Vec<ClientResponse>, StorageError<MemNodeId>> {
+        let mut res = Vec::new();
+        for entry in entries {
+            self.last_applied = Some(entry.log_id);
+            if let EntryPayload::Membership(mem) = entry.payload {
+                self.last_membership = StoredMembership::new(Some(entry.log_id), mem);
+            }
+            res.push(ClientResponse(None));
+        }
+        Ok(res)
+    }
+
+    fn build_snapshot(&mut self) -> Result<Snapshot<TypeConfig>, StorageError<MemNodeId>> {
+        let data = serde_json::to_vec(&(self.last_applied, &self.last_membership))
+            .map_err(|e| StorageIOError::read_state_machine(&e))?;
+
+        self.snapshot_idx += 1;
+        let meta = SnapshotMeta {
+            last_log_id: self.last_applied,
+            last_membership: self.last_membership.clone(),
+            snapshot_id: format!("{}", self.snapshot_idx),
+            delta_base: None,
+            checksum: None,
+        };
+
+        self.current_snapshot = Some((meta.clone(), data.clone()));
+        Ok(Snapshot {
+            meta,
+            snapshot: Box::new(Cursor::new(data)),
+        })
+    }
+
+    fn begin_receiving_snapshot(&mut self) -> Result<Box<Cursor<Vec<u8>>>, StorageError<MemNodeId>> {
+        Ok(Box::new(Cursor::new(Vec::new())))
+    }
+
+    fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<TypeConfig>,
+        snapshot: Box<Cursor<Vec<u8>>>,
+    ) -> Result<(), StorageError<MemNodeId>> {
+        let data = snapshot.into_inner();
+        let (last_applied, last_membership) =
+            serde_json::from_slice(&data).map_err(|e| StorageIOError::read_snapshot(Some(meta.signature()), &e))?;
+
+        self.last_applied = last_applied;
+        self.last_membership = last_membership;
+        self.current_snapshot = Some((meta.clone(), data));
+        Ok(())
+    }
+
+    fn get_current_snapshot(&mut self) -> Result<Option<Snapshot<TypeConfig>>, StorageError<MemNodeId>> {
+        Ok(self.current_snapshot.clone().map(|(meta, data)| Snapshot {
+            meta,
+            snapshot: Box::new(Cursor::new(data)),
+        }))
+    }
+}
+
+type BlockingLogStore = BlockingStorageAdapter<TypeConfig, SyncLogStore>;
+type BlockingStateMachine = BlockingStorageAdapter<TypeConfig, SyncStateMachine>;
+
+struct BlockingBuilder {}
+
+impl StoreBuilder<TypeConfig, BlockingLogStore, BlockingStateMachine, ()> for BlockingBuilder {
+    async fn build(&self) -> Result<((), BlockingLogStore, BlockingStateMachine), StorageError<MemNodeId>> {
+        let pool = BlockingLogStore::new_pool(2);
+        let log_store = BlockingStorageAdapter::new(SyncLogStore::default(), pool.clone());
+        let sm = BlockingStorageAdapter::new(SyncStateMachine::default(), pool);
+        Ok(((), log_store, sm))
+    }
+}
+
+/// A shared key-value store that keeps the values serialized in memory.
+#[derive(Default)]
+struct BTreeKv {
+    map: std::sync::Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl BTreeKv {
+    fn decode(buf: &[u8]) -> Result<KvValue<TypeConfig>, StorageError<MemNodeId>> {
+        serde_json::from_slice(buf).map_err(|e| StorageIOError::read_logs(&e).into())
+    }
+}
+
+impl SharedKvStore<TypeConfig> for BTreeKv {
+    async fn get(&self, key: &[u8]) -> Result<Option<KvValue<TypeConfig>>, StorageError<MemNodeId>> {
+        let map = self.map.lock().unwrap();
+        map.get(key).map(|v| Self::decode(v)).transpose()
+    }
+
+    async fn range(&self, start: &[u8], end: &[u8]) -> Result<Vec<KvValue<TypeConfig>>, StorageError<MemNodeId>> {
+        let map = self.map.lock().unwrap();
+        map.range(start.to_vec()..end.to_vec()).map(|(_, v)| Self::decode(v)).collect()
+    }
+
+    async fn last(&self, start: &[u8], end: &[u8]) -> Result<Option<KvValue<TypeConfig>>, StorageError<MemNodeId>> {
+        let map = self.map.lock().unwrap();
+        map.range(start.to_vec()..end.to_vec()).next_back().map(|(_, v)| Self::decode(v)).transpose()
+    }
+
+    async fn write(&self, ops: Vec<KvOp<TypeConfig>>) -> Result<(), StorageError<MemNodeId>> {
+        let mut map = self.map.lock().unwrap();
+        for op in ops {
+            match op {
+                KvOp::Put(k, v) => {
+                    let buf = serde_json::to_vec(&v).map_err(|e| StorageIOError::write_logs(&e))?;
+                    map.insert(k, buf);
+                }
+                KvOp::Delete(k) => {
+                    map.remove(&k);
+                }
+                KvOp::DeleteRange(start, end) => {
+                    let keys = map.range(start..end).map(|(k, _)| k.clone()).collect::<Vec<_>>();
+                    for k in keys {
+                        map.remove(&k);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+type PrefixedStore = PrefixedLogStorage<TypeConfig, BTreeKv>;
+
+/// Builds the log storage of group 1 in a store shared with group 0 and group 2.
+struct PrefixedBuilder {}
+
+impl StoreBuilder<TypeConfig, PrefixedStore, Arc<MemStateMachine>, ()> for PrefixedBuilder {
+    async fn build(&self) -> Result<((), PrefixedStore, Arc<MemStateMachine>), StorageError<MemNodeId>> {
+        let kv = Arc::new(BTreeKv::default());
+
+        for group_id in [0, 2] {
+            let mut other = PrefixedLogStorage::new(kv.clone(), group_id);
+            RaftLogStorage::save_vote(&mut other, &Vote::new(5, 5)).await?;
+            other.blocking_append([blank_ent::<TypeConfig>(5, 5, 1), blank_ent::<TypeConfig>(5, 5, 2)]).await?;
+        }
+
+        let (_log_store, sm) = crate::new_mem_store();
+        Ok(((), PrefixedLogStorage::new(kv, 1), sm))
+    }
+}
+
 #[test]
 pub fn test_mem_store() -> Result<(), StorageError<MemNodeId>> {
     Suite::test_all(MemStoreBuilder {})?;
     Ok(())
 }
+
+#[test]
+pub fn test_mem_store_with_vote_storage() -> Result<(), StorageError<MemNodeId>> {
+    Suite::test_all(WithVoteBuilder {})?;
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        let ((), mut store, _sm) = WithVoteBuilder {}.build().await?;
+
+        RaftLogStorage::save_vote(&mut store, &Vote::new(1, 2)).await?;
+
+        assert_eq!(Some(Vote::new(1, 2)), RaftLogReader::read_vote(&mut store).await?);
+        assert_eq!(
+            Some(Vote::new(1, 2)),
+            RaftLogReader::read_vote(&mut store.get_log_reader().await).await?
+        );
+        assert_eq!(
+            None,
+            RaftLogReader::read_vote(store.log_store()).await?,
+            "the vote is not saved in the log store"
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+pub fn test_mem_store_migrate() -> Result<(), StorageError<MemNodeId>> {
+    let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    rt.block_on(async {
+        let (mut src_log, mut src_sm) = crate::new_mem_store();
+
+        let entries = || {
+            vec![
+                blank_ent::<TypeConfig>(0, 0, 0),
+                membership_ent::<TypeConfig>(1, 0, 1, vec![BTreeSet::from([0])]),
+                blank_ent::<TypeConfig>(1, 0, 2),
+                blank_ent::<TypeConfig>(1, 0, 3),
+                blank_ent::<TypeConfig>(1, 0, 4),
+                blank_ent::<TypeConfig>(1, 0, 5),
+            ]
+        };
+
+        RaftLogStorage::save_vote(&mut src_log, &Vote::new(1, 2)).await?;
+        src_log.blocking_append(entries()).await?;
+        src_sm.apply(entries().into_iter().take(4)).await?;
+        src_log.purge(log_id(1, 0, 1)).await?;
+        src_log.save_committed(Some(log_id(1, 0, 3))).await?;
+
+        // Migrate to another store and back again.
+        let (mut dst_log, mut dst_sm) = crate::new_mem_store();
+        migrate(&mut src_log, &mut src_sm, &mut dst_log, &mut dst_sm).await?;
+
+        let (mut back_log, mut back_sm) = crate::new_mem_store();
+        migrate(&mut dst_log, &mut dst_sm, &mut back_log, &mut back_sm).await?;
+
+        for (mut log, mut sm) in [(dst_log, dst_sm), (back_log, back_sm)] {
+            assert_eq!(Some(Vote::new(1, 2)), RaftLogReader::read_vote(&mut log).await?);
+
+            let st = log.get_log_state().await?;
+            assert_eq!(Some(log_id(1, 0, 1)), st.last_purged_log_id);
+            assert_eq!(Some(log_id(1, 0, 5)), st.last_log_id);
+
+            let log_ids = log.try_get_log_entries(0..10).await?.into_iter().map(|e| e.log_id).collect::<Vec<_>>();
+            assert_eq!(
+                vec![log_id(1, 0, 2), log_id(1, 0, 3), log_id(1, 0, 4), log_id(1, 0, 5)],
+                log_ids
+            );
+
+            assert_eq!(Some(log_id(1, 0, 3)), log.read_committed().await?);
+
+            let (applied, membership) = sm.applied_state().await?;
+            assert_eq!(Some(log_id(1, 0, 3)), applied);
+            assert_eq!(&Some(log_id(1, 0, 1)), membership.log_id());
+
+            let state = sm.get_state_machine().await;
+            assert_eq!(Some(log_id(1, 0, 3)), state.last_applied_log);
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+pub fn test_blocking_storage_adapter() -> Result<(), StorageError<MemNodeId>> {
+    Suite::test_all(BlockingBuilder {})?;
+    Ok(())
+}
+
+#[test]
+pub fn test_prefixed_log_storage() -> Result<(), StorageError<MemNodeId>> {
+    Suite::test_all(PrefixedBuilder {})?;
+    Ok(())
+}
+
+#[test]
+pub fn test_prefixed_log_storage_isolation() -> Result<(), StorageError<MemNodeId>> {
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        let kv = Arc::new(BTreeKv::default());
+        let mut g1 = PrefixedLogStorage::new(kv.clone(), 1);
+        let mut g2 = PrefixedLogStorage::new(kv.clone(), 2);
+
+        let entries = |term| (1..6).map(|i| blank_ent::<TypeConfig>(term, 0, i)).collect::<Vec<_>>();
+
+        RaftLogStorage::save_vote(&mut g1, &Vote::new(1, 0)).await?;
+        RaftLogStorage::save_vote(&mut g2, &Vote::new(2, 0)).await?;
+        g1.blocking_append(entries(1)).await?;
+        g2.blocking_append(entries(2)).await?;
+        g2.save_committed(Some(log_id(2, 0, 3))).await?;
+
+        g1.purge(log_id(1, 0, 1)).await?;
+        g1.truncate(log_id(1, 0, 3)).await?;
+        g1.save_committed(None).await?;
+
+        let st = g1.get_log_state().await?;
+        assert_eq!(Some(log_id(1, 0, 1)), st.last_purged_log_id);
+        assert_eq!(Some(log_id(1, 0, 2)), st.last_log_id);
+
+        let st = g2.get_log_state().await?;
+        assert_eq!(None, st.last_purged_log_id);
+        assert_eq!(Some(log_id(2, 0, 5)), st.last_log_id);
+
+        let log_ids = g2.try_get_log_entries(..).await?.into_iter().map(|e| e.log_id).collect::<Vec<_>>();
+        assert_eq!((1..6).map(|i| log_id(2, 0, i)).collect::<Vec<_>>(), log_ids);
+
+        assert_eq!(Some(Vote::new(2, 0)), RaftLogReader::read_vote(&mut g2).await?);
+        assert_eq!(Some(log_id(2, 0, 3)), g2.read_committed().await?);
+
+        // Application data under the group prefix is not touched by the log storage.
+        let mut app_key = group_key_prefix(1);
+        app_key.push(0x80);
+        kv.write(vec![KvOp::Put(app_key.clone(), KvValue::Vote(Vote::new(9, 9)))]).await?;
+        g1.purge(log_id(1, 0, 2)).await?;
+        assert!(kv.get(&app_key).await?.is_some());
+
+        Ok(())
+    })
+}
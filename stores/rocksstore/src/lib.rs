@@ -1,7 +1,17 @@
 //! This rocks-db backed storage implement the v2 storage API: [`RaftLogStorage`] and
-//! [`RaftStateMachine`] traits. Its state machine is pure in-memory store with persisted
-//! snapshot. In other words, `applying` a log entry does not flush data to disk at once.
-//! These data will be flushed to disk when a snapshot is created.
+//! [`RaftStateMachine`] traits.
+//!
+//! The log store and the state machine share one rocks-db instance, with a column family for
+//! every kind of data:
+//! - `logs`: the log entries, keyed by big-endian log index;
+//! - `meta`: the vote and the last purged log id;
+//! - `sm_data`: the key-values of the state machine;
+//! - `sm_meta`: the last applied log id, the last membership and the latest snapshot.
+//!
+//! Applying a batch of log entries updates the data and the last applied log id in one atomic
+//! write, but does not flush it to disk at once. A snapshot is flushed to disk when it is built or
+//! installed, and the state machine is restored from it when restarted if it is newer than the
+//! applied state on disk.
 #![deny(unused_crate_dependencies)]
 #![deny(unused_qualifications)]
 
@@ -43,7 +53,9 @@ use rocksdb::ColumnFamily;
 use rocksdb::ColumnFamilyDescriptor;
 use rocksdb::Direction;
 use rocksdb::Options;
+use rocksdb::WriteBatch;
 use rocksdb::DB;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -88,6 +100,7 @@ pub struct RocksSnapshot {
     pub data: Vec<u8>,
 }
 
+/// The serialized form of the state machine in a snapshot.
 #[derive(Debug, Clone)]
 #[derive(Default)]
 #[derive(Serialize, Deserialize)]
@@ -100,31 +113,100 @@ pub struct StateMachine {
     pub data: BTreeMap<String, String>,
 }
 
-// TODO: restore state from snapshot
-/// State machine in this implementation is a pure in-memory store.
-/// It depends on the latest snapshot to restore the state when restarted.
+/// The state machine persisted in column families `sm_data` and `sm_meta`.
 #[derive(Debug, Clone)]
 pub struct RocksStateMachine {
     db: Arc<DB>,
-    sm: StateMachine,
 }
 
+/// Keys in column family `sm_meta`.
+const SM_LAST_APPLIED: &str = "last_applied_log";
+const SM_LAST_MEMBERSHIP: &str = "last_membership";
+const SM_SNAPSHOT: &str = "snapshot";
+
 impl RocksStateMachine {
     async fn new(db: Arc<DB>) -> RocksStateMachine {
-        let mut state_machine = Self {
-            db,
-            sm: Default::default(),
-        };
-        let snapshot = state_machine.get_current_snapshot().await.unwrap();
+        let mut state_machine = Self { db };
 
-        // Restore previous state from snapshot
+        // Applied entries may be lost by a crash, while a snapshot is always flushed to disk:
+        // restore the state from the snapshot if it is newer.
+        let snapshot = state_machine.get_current_snapshot().await.unwrap();
         if let Some(s) = snapshot {
-            let prev: StateMachine = serde_json::from_slice(s.snapshot.get_ref()).unwrap();
-            state_machine.sm = prev;
+            let (last_applied, _) = state_machine.applied_state().await.unwrap();
+            if s.meta.last_log_id > last_applied {
+                let meta = s.meta.clone();
+                state_machine.install_snapshot(&meta, s.snapshot).await.unwrap();
+            }
         }
 
         state_machine
     }
+
+    fn cf_sm_meta(&self) -> &ColumnFamily {
+        self.db.cf_handle("sm_meta").unwrap()
+    }
+
+    fn cf_sm_data(&self) -> &ColumnFamily {
+        self.db.cf_handle("sm_data").unwrap()
+    }
+
+    /// Read the value of `key` from the state machine.
+    pub fn get(&self, key: &str) -> StorageResult<Option<String>> {
+        let v = self.db.get_cf(self.cf_sm_data(), key).map_err(|e| StorageIOError::read_state_machine(&e))?;
+        let v = v.map(String::from_utf8).transpose().map_err(|e| StorageIOError::read_state_machine(&e))?;
+        Ok(v)
+    }
+
+    /// Read the applied state and all key-values from a consistent view of the state machine.
+    fn read_state_machine(&self) -> StorageResult<StateMachine> {
+        let snap = self.db.snapshot();
+
+        let last_applied_log = decode_sm_meta(snap.get_cf(self.cf_sm_meta(), SM_LAST_APPLIED))?;
+        let last_membership = decode_sm_meta(snap.get_cf(self.cf_sm_meta(), SM_LAST_MEMBERSHIP))?;
+
+        let mut data = BTreeMap::new();
+        for item_res in snap.iterator_cf(self.cf_sm_data(), rocksdb::IteratorMode::Start) {
+            let (key, value) = item_res.map_err(|e| StorageIOError::read_state_machine(&e))?;
+
+            let key = String::from_utf8(key.to_vec()).map_err(|e| StorageIOError::read_state_machine(&e))?;
+            let value = String::from_utf8(value.to_vec()).map_err(|e| StorageIOError::read_state_machine(&e))?;
+            data.insert(key, value);
+        }
+
+        Ok(StateMachine {
+            last_applied_log,
+            last_membership: last_membership.unwrap_or_default(),
+            data,
+        })
+    }
+
+    /// Save a snapshot to `sm_meta`, in `batch`.
+    fn put_snapshot(&self, batch: &mut WriteBatch, snapshot: &RocksSnapshot) -> StorageResult<()> {
+        let meta = &snapshot.meta;
+        let serialized_snapshot = serde_json::to_vec(snapshot)
+            .map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), AnyError::new(&e)))?;
+
+        batch.put_cf(self.cf_sm_meta(), SM_SNAPSHOT, serialized_snapshot);
+        Ok(())
+    }
+}
+
+/// Decode a value read from `sm_meta`.
+fn decode_sm_meta<T: DeserializeOwned>(
+    v: Result<Option<Vec<u8>>, rocksdb::Error>,
+) -> Result<Option<T>, StorageError<RocksNodeId>> {
+    let v = v.map_err(|e| StorageIOError::read_state_machine(&e))?;
+    let t = v
+        .map(|bytes| serde_json::from_slice(&bytes))
+        .transpose()
+        .map_err(|e| StorageIOError::read_state_machine(&e))?;
+    Ok(t)
+}
+
+/// Encode a value to write to `sm_meta`.
+fn encode_sm_meta<T: Serialize>(v: &T) -> Result<Vec<u8>, StorageError<RocksNodeId>> {
+    let bytes = serde_json::to_vec(v).map_err(|e| StorageIOError::write_state_machine(&e))?;
+    Ok(bytes)
 }
 
 #[derive(Debug, Clone)]
@@ -269,11 +351,13 @@ impl RaftLogReader<TypeConfig> for RocksLogStore {
 impl RaftSnapshotBuilder<TypeConfig> for RocksStateMachine {
     #[tracing::instrument(level = "trace", skip(self))]
     async fn build_snapshot(&mut self) -> Result<Snapshot<TypeConfig>, StorageError<RocksNodeId>> {
+        let sm = self.read_state_machine()?;
+
         // Serialize the data of the state machine.
-        let data = serde_json::to_vec(&self.sm).map_err(|e| StorageIOError::read_state_machine(&e))?;
+        let data = serde_json::to_vec(&sm).map_err(|e| StorageIOError::read_state_machine(&e))?;
 
-        let last_applied_log = self.sm.last_applied_log;
-        let last_membership = self.sm.last_membership.clone();
+        let last_applied_log = sm.last_applied_log;
+        let last_membership = sm.last_membership;
 
         // Generate a random snapshot index.
         let snapshot_idx: u64 = rand::thread_rng().gen_range(0..1000);
@@ -288,6 +372,8 @@ impl RaftSnapshotBuilder<TypeConfig> for RocksStateMachine {
             last_log_id: last_applied_log,
             last_membership,
             snapshot_id,
+            delta_base: None,
+            checksum: None,
         };
 
         let snapshot = RocksSnapshot {
@@ -295,12 +381,11 @@ impl RaftSnapshotBuilder<TypeConfig> for RocksStateMachine {
             data: data.clone(),
         };
 
-        let serialized_snapshot = serde_json::to_vec(&snapshot)
-            .map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), AnyError::new(&e)))?;
+        let mut batch = WriteBatch::default();
+        self.put_snapshot(&mut batch, &snapshot)?;
 
-        self.db
-            .put_cf(self.db.cf_handle("sm_meta").unwrap(), "snapshot", serialized_snapshot)
-            .map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), AnyError::new(&e)))?;
+        self.db.write(batch).map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), &e))?;
+        self.db.flush_wal(true).map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), &e))?;
 
         Ok(Snapshot {
             meta,
@@ -408,7 +493,10 @@ impl RaftStateMachine<TypeConfig> for RocksStateMachine {
     async fn applied_state(
         &mut self,
     ) -> Result<(Option<LogId<RocksNodeId>>, StoredMembership<TypeConfig>), StorageError<RocksNodeId>> {
-        Ok((self.sm.last_applied_log, self.sm.last_membership.clone()))
+        let last_applied_log = decode_sm_meta(self.db.get_cf(self.cf_sm_meta(), SM_LAST_APPLIED))?;
+        let last_membership = decode_sm_meta(self.db.get_cf(self.cf_sm_meta(), SM_LAST_MEMBERSHIP))?;
+
+        Ok((last_applied_log, last_membership.unwrap_or_default()))
     }
 
     async fn apply<I>(&mut self, entries: I) -> Result<Vec<RocksResponse>, StorageError<RocksNodeId>>
@@ -416,29 +504,41 @@ impl RaftStateMachine<TypeConfig> for RocksStateMachine {
         let entries_iter = entries.into_iter();
         let mut res = Vec::with_capacity(entries_iter.size_hint().0);
 
-        let sm = &mut self.sm;
+        // The data and the applied state are updated in one atomic write.
+        let mut batch = WriteBatch::default();
+        let mut last_applied_log = None;
+        let mut last_membership = None;
 
         for entry in entries_iter {
             tracing::debug!(%entry.log_id, "replicate to sm");
 
-            sm.last_applied_log = Some(*entry.get_log_id());
+            last_applied_log = Some(*entry.get_log_id());
 
             match entry.payload {
                 EntryPayload::Blank => res.push(RocksResponse { value: None }),
                 EntryPayload::Normal(ref req) => match req {
                     RocksRequest::Set { key, value } => {
-                        sm.data.insert(key.clone(), value.clone());
+                        batch.put_cf(self.cf_sm_data(), key, value);
                         res.push(RocksResponse {
                             value: Some(value.clone()),
                         })
                     }
                 },
                 EntryPayload::Membership(ref mem) => {
-                    sm.last_membership = StoredMembership::new(Some(entry.log_id), mem.clone());
+                    last_membership = Some(StoredMembership::new(Some(entry.log_id), mem.clone()));
                     res.push(RocksResponse { value: None })
                 }
             };
         }
+
+        if let Some(log_id) = last_applied_log {
+            batch.put_cf(self.cf_sm_meta(), SM_LAST_APPLIED, encode_sm_meta(&log_id)?);
+        }
+        if let Some(membership) = last_membership {
+            batch.put_cf(self.cf_sm_meta(), SM_LAST_MEMBERSHIP, encode_sm_meta(&membership)?);
+        }
+
+        self.db.write(batch).map_err(|e| StorageIOError::write_state_machine(&e))?;
         Ok(res)
     }
 
@@ -469,17 +569,30 @@ impl RaftStateMachine<TypeConfig> for RocksStateMachine {
         let updated_state_machine: StateMachine = serde_json::from_slice(&new_snapshot.data)
             .map_err(|e| StorageIOError::read_snapshot(Some(new_snapshot.meta.signature()), &e))?;
 
-        self.sm = updated_state_machine;
+        // Replace the state machine and save the snapshot in one atomic write.
+        let mut batch = WriteBatch::default();
 
-        // Save snapshot
+        for item_res in self.db.iterator_cf(self.cf_sm_data(), rocksdb::IteratorMode::Start) {
+            let (key, _) = item_res.map_err(|e| StorageIOError::read_state_machine(&e))?;
+            batch.delete_cf(self.cf_sm_data(), key);
+        }
+        for (key, value) in updated_state_machine.data.iter() {
+            batch.put_cf(self.cf_sm_data(), key, value);
+        }
 
-        let serialized_snapshot = serde_json::to_vec(&new_snapshot)
-            .map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), AnyError::new(&e)))?;
+        match &updated_state_machine.last_applied_log {
+            Some(log_id) => batch.put_cf(self.cf_sm_meta(), SM_LAST_APPLIED, encode_sm_meta(log_id)?),
+            None => batch.delete_cf(self.cf_sm_meta(), SM_LAST_APPLIED),
+        }
+        batch.put_cf(
+            self.cf_sm_meta(),
+            SM_LAST_MEMBERSHIP,
+            encode_sm_meta(&updated_state_machine.last_membership)?,
+        );
 
-        self.db
-            .put_cf(self.db.cf_handle("sm_meta").unwrap(), "snapshot", serialized_snapshot)
-            .map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), AnyError::new(&e)))?;
+        self.put_snapshot(&mut batch, &new_snapshot)?;
 
+        self.db.write(batch).map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), &e))?;
         self.db.flush_wal(true).map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), &e))?;
         Ok(())
     }
@@ -487,7 +600,7 @@ impl RaftStateMachine<TypeConfig> for RocksStateMachine {
     async fn get_current_snapshot(&mut self) -> Result<Option<Snapshot<TypeConfig>>, StorageError<RocksNodeId>> {
         let x = self
             .db
-            .get_cf(self.db.cf_handle("sm_meta").unwrap(), "snapshot")
+            .get_cf(self.cf_sm_meta(), SM_SNAPSHOT)
             .map_err(|e| StorageIOError::write_snapshot(None, AnyError::new(&e)))?;
 
         let bytes = match x {
@@ -516,9 +629,10 @@ pub async fn new<P: AsRef<Path>>(db_path: P) -> (RocksLogStore, RocksStateMachin
 
     let meta = ColumnFamilyDescriptor::new("meta", Options::default());
     let sm_meta = ColumnFamilyDescriptor::new("sm_meta", Options::default());
+    let sm_data = ColumnFamilyDescriptor::new("sm_data", Options::default());
     let logs = ColumnFamilyDescriptor::new("logs", Options::default());
 
-    let db = DB::open_cf_descriptors(&db_opts, db_path, vec![meta, sm_meta, logs]).unwrap();
+    let db = DB::open_cf_descriptors(&db_opts, db_path, vec![meta, sm_meta, sm_data, logs]).unwrap();
 
     let db = Arc::new(db);
     (RocksLogStore { db: db.clone() }, RocksStateMachine::new(db).await)
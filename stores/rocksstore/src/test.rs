@@ -1,10 +1,15 @@
+use openraft::storage::RaftStateMachine;
+use openraft::testing::log_id;
 use openraft::testing::StoreBuilder;
 use openraft::testing::Suite;
+use openraft::Entry;
+use openraft::EntryPayload;
 use openraft::StorageError;
 use tempfile::TempDir;
 
 use crate::RocksLogStore;
 use crate::RocksNodeId;
+use crate::RocksRequest;
 use crate::RocksStateMachine;
 use crate::TypeConfig;
 
@@ -16,6 +21,13 @@ impl StoreBuilder<TypeConfig, RocksLogStore, RocksStateMachine, TempDir> for Roc
         let (log_store, sm) = crate::new(td.path()).await;
         Ok((td, log_store, sm))
     }
+
+    async fn reopen(
+        &self,
+        td: &TempDir,
+    ) -> Result<Option<(RocksLogStore, RocksStateMachine)>, StorageError<RocksNodeId>> {
+        Ok(Some(crate::new(td.path()).await))
+    }
 }
 /// To customize a builder:
 ///
@@ -41,3 +53,36 @@ pub fn test_rocks_store() -> Result<(), StorageError<RocksNodeId>> {
     Suite::test_all(RocksBuilder {})?;
     Ok(())
 }
+
+/// The state machine is persisted, and is restored when the db is reopened.
+#[test]
+pub fn test_rocks_state_machine_persisted() -> Result<(), StorageError<RocksNodeId>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        {
+            let (_log_store, mut sm) = crate::new(td.path()).await;
+            sm.apply([
+                Entry {
+                    log_id: log_id(1, 0, 1),
+                    payload: EntryPayload::Normal(RocksRequest::Set {
+                        key: "foo".to_string(),
+                        value: "bar".to_string(),
+                    }),
+                },
+                Entry {
+                    log_id: log_id(1, 0, 2),
+                    payload: EntryPayload::Blank,
+                },
+            ])
+            .await?;
+        }
+
+        let (_log_store, mut sm) = crate::new(td.path()).await;
+        let (last_applied, _) = sm.applied_state().await?;
+        assert_eq!(Some(log_id(1, 0, 2)), last_applied);
+        assert_eq!(Some("bar".to_string()), sm.get("foo")?);
+        Ok(())
+    })
+}
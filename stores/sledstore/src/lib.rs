@@ -15,6 +15,9 @@ use byteorder::BigEndian;
 use byteorder::ByteOrder;
 use byteorder::ReadBytesExt;
 use openraft::alias::SnapshotDataOf;
+use openraft::alias::StorageCipherOf;
+use openraft::storage::seal;
+use openraft::storage::unseal;
 use openraft::storage::LogFlushed;
 use openraft::storage::LogState;
 use openraft::storage::RaftLogStorage;
@@ -291,6 +294,12 @@ fn bin_to_id(buf: &[u8]) -> u64 {
     (&buf[0..8]).read_u64::<BigEndian>().unwrap()
 }
 
+/// Decrypt and deserialize a log entry stored by `append()`.
+fn decode_entry(buf: Vec<u8>) -> StorageResult<Entry<TypeConfig>> {
+    let buf = unseal::<StorageCipherOf<TypeConfig>>(buf).map_err(read_logs_err)?;
+    serde_json::from_slice(&buf).map_err(|e| read_logs_err(e).into())
+}
+
 impl SledStore {
     fn get_last_purged_(&self) -> StorageResult<Option<LogId<u64>>> {
         let store_tree = store(&self.db);
@@ -362,7 +371,8 @@ impl SledStore {
         let ivec = store_tree.get(b"snapshot").map_err(read_snap_err)?;
 
         if let Some(ivec) = ivec {
-            let snap = serde_json::from_slice(&ivec).map_err(read_snap_err)?;
+            let buf = unseal::<StorageCipherOf<TypeConfig>>(ivec.to_vec()).map_err(read_snap_err)?;
+            let snap = serde_json::from_slice(&buf).map_err(read_snap_err)?;
             Ok(Some(snap))
         } else {
             Ok(None)
@@ -371,7 +381,10 @@ impl SledStore {
 
     async fn set_current_snapshot_(&self, snap: ExampleSnapshot) -> StorageResult<()> {
         let store_tree = store(&self.db);
-        let val = serde_json::to_vec(&snap).unwrap();
+        let val =
+            seal::<StorageCipherOf<TypeConfig>>(serde_json::to_vec(&snap).unwrap()).map_err(|e| StorageError::IO {
+                source: StorageIOError::write_snapshot(Some(snap.meta.signature()), &e),
+            })?;
         let meta = snap.meta.clone();
         store_tree.insert(b"snapshot", val.as_slice()).map_err(|e| StorageError::IO {
             source: StorageIOError::write_snapshot(Some(snap.meta.signature()), &e),
@@ -403,9 +416,7 @@ impl RaftLogReader<TypeConfig> for Arc<SledStore> {
                 let el = el_res.expect("Failed read log entry");
                 let id = el.0;
                 let val = el.1;
-                let entry: StorageResult<Entry<_>> = serde_json::from_slice(&val).map_err(|e| StorageError::IO {
-                    source: StorageIOError::read_logs(&e),
-                });
+                let entry: StorageResult<Entry<_>> = decode_entry(val.to_vec());
                 let id = bin_to_id(&id);
 
                 assert_eq!(Ok(id), entry.as_ref().map(|e| e.log_id.index));
@@ -452,6 +463,8 @@ impl RaftSnapshotBuilder<TypeConfig> for Arc<SledStore> {
             last_log_id: last_applied_log,
             last_membership,
             snapshot_id,
+            delta_base: None,
+            checksum: None,
         };
 
         let snapshot = ExampleSnapshot {
@@ -485,7 +498,7 @@ impl RaftLogStorage<TypeConfig> for Arc<SledStore> {
             });
         };
 
-        let last_ent = serde_json::from_slice::<Entry<TypeConfig>>(&ent_ivec).map_err(read_logs_err)?;
+        let last_ent = decode_entry(ent_ivec.to_vec())?;
         let last_log_id = Some(*last_ent.get_log_id());
 
         let last_log_id = std::cmp::max(last_log_id, last_purged);
@@ -513,6 +526,7 @@ impl RaftLogStorage<TypeConfig> for Arc<SledStore> {
             let id = id_to_bin(entry.log_id.index);
             assert_eq!(bin_to_id(&id), entry.log_id.index);
             let value = serde_json::to_vec(&entry).map_err(write_logs_err)?;
+            let value = seal::<StorageCipherOf<TypeConfig>>(value).map_err(write_logs_err)?;
             batch.insert(id.as_slice(), value);
         }
         logs_tree.apply_batch(batch).map_err(write_logs_err)?;
@@ -559,6 +573,10 @@ impl RaftLogStorage<TypeConfig> for Arc<SledStore> {
         logs_tree.flush_async().await.map_err(write_logs_err)?;
         Ok(())
     }
+
+    fn supports_storage_cipher(&self) -> bool {
+        true
+    }
 }
 
 impl RaftStateMachine<TypeConfig> for Arc<SledStore> {
@@ -671,6 +689,10 @@ impl RaftStateMachine<TypeConfig> for Arc<SledStore> {
             None => Ok(None),
         }
     }
+
+    fn supports_storage_cipher(&self) -> bool {
+        true
+    }
 }
 
 impl SledStore {
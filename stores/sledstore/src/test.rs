@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use openraft::testing::StoreBuilder;
 use openraft::testing::Suite;
@@ -29,4 +30,21 @@ impl StoreBuilder<TypeConfig, LogStore, StateMachine, TempDir> for SledBuilder {
 
         Ok((td, log_store, sm))
     }
+
+    async fn reopen(&self, td: &TempDir) -> Result<Option<(LogStore, StateMachine)>, StorageError<ExampleNodeId>> {
+        // sled releases the lock of the db files in a background thread after the db is dropped.
+        let mut retry = 0;
+        let db: sled::Db = loop {
+            match sled::open(td.path()) {
+                Ok(db) => break db,
+                Err(e) if retry >= 100 => panic!("reopen sled db: {}", e),
+                Err(_) => {
+                    retry += 1;
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            }
+        };
+
+        Ok(Some(SledStore::new(Arc::new(db)).await))
+    }
 }
@@ -0,0 +1,604 @@
+//! This SQLite backed storage implement the v2 storage API: [`RaftLogStorage`] and
+//! [`RaftStateMachine`] traits.
+//!
+//! The log store and the state machine share one SQLite database file, with a table for every
+//! kind of data:
+//! - `logs`: the log entries, keyed by log index;
+//! - `meta`: the vote, the committed log id and the last purged log id;
+//! - `sm_data`: the key-values of the state machine;
+//! - `sm_meta`: the last applied log id, the last membership and the latest snapshot.
+//!
+//! Every write is a transaction: appending a batch of log entries, purging the log, or applying a
+//! batch of log entries along with the last applied log id, is atomic.
+//!
+//! The database is opened in WAL mode, which is tuned with [`SqliteConfig`]. With the default
+//! [`Synchronous::Full`], a transaction is flushed to disk when it commits, as Raft requires for
+//! the log and the vote.
+#![deny(unused_crate_dependencies)]
+#![deny(unused_qualifications)]
+
+#[cfg(test)] mod test;
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::io::Cursor;
+use std::ops::Bound;
+use std::ops::RangeBounds;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use openraft::alias::SnapshotDataOf;
+use openraft::storage::LogFlushed;
+use openraft::storage::LogState;
+use openraft::storage::RaftLogStorage;
+use openraft::storage::RaftStateMachine;
+use openraft::storage::Snapshot;
+use openraft::AnyError;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::LogId;
+use openraft::OptionalSend;
+use openraft::RaftLogId;
+use openraft::RaftLogReader;
+use openraft::RaftSnapshotBuilder;
+use openraft::SnapshotMeta;
+use openraft::StorageError;
+use openraft::StorageIOError;
+use openraft::StoredMembership;
+use openraft::Vote;
+use rand::Rng;
+use rusqlite::params;
+use rusqlite::Connection;
+use rusqlite::OptionalExtension;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+
+pub type SqliteNodeId = u64;
+
+openraft::declare_raft_types!(
+    /// Declare the type configuration.
+    pub TypeConfig:
+        D = SqliteRequest,
+        R = SqliteResponse,
+);
+
+/// A request to write data to the state machine.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum SqliteRequest {
+    Set { key: String, value: String },
+}
+
+/// The response of applying a [`SqliteRequest`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SqliteResponse {
+    pub value: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SqliteSnapshot {
+    pub meta: SnapshotMeta<TypeConfig>,
+
+    /// The data of the state machine at the time of this snapshot.
+    pub data: Vec<u8>,
+}
+
+/// The serialized form of the state machine in a snapshot.
+#[derive(Debug, Clone)]
+#[derive(Default)]
+#[derive(Serialize, Deserialize)]
+pub struct StateMachine {
+    pub last_applied_log: Option<LogId<SqliteNodeId>>,
+
+    pub last_membership: StoredMembership<TypeConfig>,
+
+    /// Application data.
+    pub data: BTreeMap<String, String>,
+}
+
+/// When SQLite flushes a transaction to disk, i.e., `PRAGMA synchronous`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Synchronous {
+    /// Flush the WAL at checkpoints only. It is faster, but the transactions committed since the
+    /// last checkpoint may be lost on a power failure, including the log entries and the vote
+    /// that Raft assumes to be persisted.
+    Normal,
+
+    /// Flush the WAL when every transaction commits.
+    Full,
+}
+
+impl Synchronous {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Synchronous::Normal => "NORMAL",
+            Synchronous::Full => "FULL",
+        }
+    }
+}
+
+/// Configuration of the SQLite database in WAL mode.
+#[derive(Clone, Debug)]
+pub struct SqliteConfig {
+    /// When to flush a transaction to disk.
+    pub synchronous: Synchronous,
+
+    /// The number of pages in the WAL at which it is checkpointed into the database file, i.e.,
+    /// `PRAGMA wal_autocheckpoint`.
+    pub wal_autocheckpoint: u32,
+
+    /// The size in bytes the WAL file is truncated to after a checkpoint, i.e.,
+    /// `PRAGMA journal_size_limit`.
+    pub journal_size_limit: u64,
+
+    /// The size in KiB of the page cache, i.e., `PRAGMA cache_size`.
+    pub cache_size_kib: u64,
+
+    /// How long to wait for a lock held by another connection to the database before returning
+    /// an error.
+    pub busy_timeout: Duration,
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        Self {
+            synchronous: Synchronous::Full,
+            wal_autocheckpoint: 1000,
+            journal_size_limit: 64 * 1024 * 1024,
+            cache_size_kib: 8 * 1024,
+            busy_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+type StorageResult<T> = Result<T, StorageError<SqliteNodeId>>;
+
+/// Keys in table `meta`.
+const META_VOTE: &str = "vote";
+const META_COMMITTED: &str = "committed";
+const META_LAST_PURGED: &str = "last_purged_log_id";
+
+/// Keys in table `sm_meta`.
+const SM_LAST_APPLIED: &str = "last_applied_log";
+const SM_LAST_MEMBERSHIP: &str = "last_membership";
+const SM_SNAPSHOT: &str = "snapshot";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS logs (idx INTEGER PRIMARY KEY, entry BLOB NOT NULL);
+    CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value BLOB NOT NULL);
+    CREATE TABLE IF NOT EXISTS sm_data (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS sm_meta (key TEXT PRIMARY KEY, value BLOB NOT NULL);
+";
+
+/// Read a json encoded value of `key` from a key-value table `table`.
+fn get_json<T: DeserializeOwned>(conn: &Connection, table: &str, key: &str) -> Result<Option<T>, AnyError> {
+    let sql = format!("SELECT value FROM {} WHERE key = ?1", table);
+    let v: Option<Vec<u8>> = conn.query_row(&sql, [key], |row| row.get(0)).optional().map_err(|e| AnyError::new(&e))?;
+
+    let t = v.map(|bytes| serde_json::from_slice(&bytes)).transpose().map_err(|e| AnyError::new(&e))?;
+    Ok(t)
+}
+
+/// Write a json encoded value of `key` to a key-value table `table`.
+fn put_json<T: Serialize>(conn: &Connection, table: &str, key: &str, value: &T) -> Result<(), AnyError> {
+    let bytes = serde_json::to_vec(value).map_err(|e| AnyError::new(&e))?;
+
+    let sql = format!("INSERT OR REPLACE INTO {} (key, value) VALUES (?1, ?2)", table);
+    conn.execute(&sql, params![key, bytes]).map_err(|e| AnyError::new(&e))?;
+    Ok(())
+}
+
+fn delete_key(conn: &Connection, table: &str, key: &str) -> Result<(), AnyError> {
+    let sql = format!("DELETE FROM {} WHERE key = ?1", table);
+    conn.execute(&sql, [key]).map_err(|e| AnyError::new(&e))?;
+    Ok(())
+}
+
+/// SQLite stores an integer as `i64`.
+fn to_sql_index(index: u64) -> i64 {
+    std::cmp::min(index, i64::MAX as u64) as i64
+}
+
+fn decode_entry(bytes: &[u8]) -> StorageResult<Entry<TypeConfig>> {
+    let ent = serde_json::from_slice(bytes).map_err(|e| StorageIOError::read_logs(&e))?;
+    Ok(ent)
+}
+
+/// The log store persisted in tables `logs` and `meta`.
+#[derive(Debug, Clone)]
+pub struct SqliteLogStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl RaftLogReader<TypeConfig> for SqliteLogStore {
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + OptionalSend>(
+        &mut self,
+        range: RB,
+    ) -> StorageResult<Vec<Entry<TypeConfig>>> {
+        let start = match range.start_bound() {
+            Bound::Included(x) => *x,
+            Bound::Excluded(x) => *x + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => *x + 1,
+            Bound::Excluded(x) => *x,
+            Bound::Unbounded => u64::MAX,
+        };
+
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn
+            .prepare_cached("SELECT entry FROM logs WHERE idx >= ?1 AND idx < ?2 ORDER BY idx")
+            .map_err(|e| StorageIOError::read_logs(&e))?;
+        let rows = stmt
+            .query_map(params![to_sql_index(start), to_sql_index(end)], |row| {
+                row.get::<_, Vec<u8>>(0)
+            })
+            .map_err(|e| StorageIOError::read_logs(&e))?;
+
+        let mut res = Vec::new();
+        for row in rows {
+            let bytes = row.map_err(|e| StorageIOError::read_logs(&e))?;
+            res.push(decode_entry(&bytes)?);
+        }
+        Ok(res)
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<SqliteNodeId>>, StorageError<SqliteNodeId>> {
+        let conn = self.conn.lock().unwrap();
+        let vote = get_json(&conn, "meta", META_VOTE).map_err(StorageIOError::read_vote)?;
+        Ok(vote)
+    }
+}
+
+impl RaftLogStorage<TypeConfig> for SqliteLogStore {
+    type LogReader = Self;
+
+    async fn get_log_state(&mut self) -> StorageResult<LogState<TypeConfig>> {
+        let conn = self.conn.lock().unwrap();
+
+        let last: Option<Vec<u8>> = conn
+            .query_row("SELECT entry FROM logs ORDER BY idx DESC LIMIT 1", [], |row| row.get(0))
+            .optional()
+            .map_err(|e| StorageIOError::read_logs(&e))?;
+        let last_log_id = last.map(|bytes| decode_entry(&bytes)).transpose()?.map(|ent| ent.log_id);
+
+        let last_purged_log_id = get_json(&conn, "meta", META_LAST_PURGED).map_err(StorageIOError::read_logs)?;
+
+        Ok(LogState {
+            last_purged_log_id,
+            last_log_id: last_log_id.or(last_purged_log_id),
+        })
+    }
+
+    async fn save_vote(&mut self, vote: &Vote<SqliteNodeId>) -> Result<(), StorageError<SqliteNodeId>> {
+        let conn = self.conn.lock().unwrap();
+        put_json(&conn, "meta", META_VOTE, vote).map_err(StorageIOError::write_vote)?;
+        Ok(())
+    }
+
+    async fn save_committed(&mut self, committed: Option<LogId<SqliteNodeId>>) -> StorageResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let res = match committed {
+            Some(log_id) => put_json(&conn, "meta", META_COMMITTED, &log_id),
+            None => delete_key(&conn, "meta", META_COMMITTED),
+        };
+        res.map_err(StorageIOError::write)?;
+        Ok(())
+    }
+
+    async fn read_committed(&mut self) -> StorageResult<Option<LogId<SqliteNodeId>>> {
+        let conn = self.conn.lock().unwrap();
+        let committed = get_json(&conn, "meta", META_COMMITTED).map_err(StorageIOError::read)?;
+        Ok(committed)
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.clone()
+    }
+
+    async fn append<I>(&mut self, entries: I, callback: LogFlushed<TypeConfig>) -> StorageResult<()>
+    where I: IntoIterator<Item = Entry<TypeConfig>> + Send {
+        let mut conn = self.conn.lock().unwrap();
+
+        let tx = conn.transaction().map_err(|e| StorageIOError::write_logs(&e))?;
+        {
+            let mut stmt = tx
+                .prepare_cached("INSERT OR REPLACE INTO logs (idx, entry) VALUES (?1, ?2)")
+                .map_err(|e| StorageIOError::write_logs(&e))?;
+
+            for entry in entries {
+                let log_id = *entry.get_log_id();
+                let bytes = serde_json::to_vec(&entry).map_err(|e| StorageIOError::write_log_entry(log_id, &e))?;
+                stmt.execute(params![to_sql_index(log_id.index), bytes])
+                    .map_err(|e| StorageIOError::write_log_entry(log_id, &e))?;
+            }
+        }
+        tx.commit().map_err(|e| StorageIOError::write_logs(&e))?;
+
+        // If there is error, the callback will be dropped.
+        callback.log_io_completed(Ok(()));
+        Ok(())
+    }
+
+    async fn truncate(&mut self, log_id: LogId<SqliteNodeId>) -> StorageResult<()> {
+        tracing::debug!("truncate: [{:?}, +oo)", log_id);
+
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM logs WHERE idx >= ?1", [to_sql_index(log_id.index)])
+            .map_err(|e| StorageIOError::write_logs(&e))?;
+        Ok(())
+    }
+
+    async fn purge(&mut self, log_id: LogId<SqliteNodeId>) -> StorageResult<()> {
+        tracing::debug!("delete_log: [0, {:?}]", log_id);
+
+        let mut conn = self.conn.lock().unwrap();
+
+        // Save the last purged log id and delete the logs in one transaction.
+        let tx = conn.transaction().map_err(|e| StorageIOError::write_logs(&e))?;
+        put_json(&tx, "meta", META_LAST_PURGED, &log_id).map_err(StorageIOError::write_logs)?;
+        tx.execute("DELETE FROM logs WHERE idx <= ?1", [to_sql_index(log_id.index)])
+            .map_err(|e| StorageIOError::write_logs(&e))?;
+        tx.commit().map_err(|e| StorageIOError::write_logs(&e))?;
+        Ok(())
+    }
+}
+
+/// The state machine persisted in tables `sm_data` and `sm_meta`.
+#[derive(Debug, Clone)]
+pub struct SqliteStateMachine {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStateMachine {
+    /// Read the value of `key` from the state machine.
+    pub fn get(&self, key: &str) -> StorageResult<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let v = conn
+            .query_row("SELECT value FROM sm_data WHERE key = ?1", [key], |row| row.get(0))
+            .optional()
+            .map_err(|e| StorageIOError::read_state_machine(&e))?;
+        Ok(v)
+    }
+
+    /// Read the applied state and all key-values of the state machine.
+    fn read_state_machine(conn: &Connection) -> StorageResult<StateMachine> {
+        let last_applied_log =
+            get_json(conn, "sm_meta", SM_LAST_APPLIED).map_err(StorageIOError::read_state_machine)?;
+        let last_membership: Option<_> =
+            get_json(conn, "sm_meta", SM_LAST_MEMBERSHIP).map_err(StorageIOError::read_state_machine)?;
+
+        let mut stmt = conn
+            .prepare_cached("SELECT key, value FROM sm_data")
+            .map_err(|e| StorageIOError::read_state_machine(&e))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| StorageIOError::read_state_machine(&e))?;
+
+        let mut data = BTreeMap::new();
+        for row in rows {
+            let (key, value) = row.map_err(|e| StorageIOError::read_state_machine(&e))?;
+            data.insert(key, value);
+        }
+
+        Ok(StateMachine {
+            last_applied_log,
+            last_membership: last_membership.unwrap_or_default(),
+            data,
+        })
+    }
+}
+
+impl RaftSnapshotBuilder<TypeConfig> for SqliteStateMachine {
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn build_snapshot(&mut self) -> StorageResult<Snapshot<TypeConfig>> {
+        let conn = self.conn.lock().unwrap();
+
+        let sm = Self::read_state_machine(&conn)?;
+
+        // Serialize the data of the state machine.
+        let data = serde_json::to_vec(&sm).map_err(|e| StorageIOError::read_state_machine(&e))?;
+
+        // Generate a random snapshot index.
+        let snapshot_idx: u64 = rand::thread_rng().gen_range(0..1000);
+
+        let snapshot_id = if let Some(last) = sm.last_applied_log {
+            format!("{}-{}-{}", last.leader_id, last.index, snapshot_idx)
+        } else {
+            format!("--{}", snapshot_idx)
+        };
+
+        let meta = SnapshotMeta {
+            last_log_id: sm.last_applied_log,
+            last_membership: sm.last_membership,
+            snapshot_id,
+            delta_base: None,
+            checksum: None,
+        };
+
+        let snapshot = SqliteSnapshot {
+            meta: meta.clone(),
+            data: data.clone(),
+        };
+
+        put_json(&conn, "sm_meta", SM_SNAPSHOT, &snapshot)
+            .map_err(|e| StorageIOError::write_snapshot(Some(meta.signature()), e))?;
+
+        Ok(Snapshot {
+            meta,
+            snapshot: Box::new(Cursor::new(data)),
+        })
+    }
+}
+
+impl RaftStateMachine<TypeConfig> for SqliteStateMachine {
+    type SnapshotBuilder = Self;
+
+    async fn applied_state(&mut self) -> StorageResult<(Option<LogId<SqliteNodeId>>, StoredMembership<TypeConfig>)> {
+        let conn = self.conn.lock().unwrap();
+
+        let last_applied_log =
+            get_json(&conn, "sm_meta", SM_LAST_APPLIED).map_err(StorageIOError::read_state_machine)?;
+        let last_membership: Option<_> =
+            get_json(&conn, "sm_meta", SM_LAST_MEMBERSHIP).map_err(StorageIOError::read_state_machine)?;
+
+        Ok((last_applied_log, last_membership.unwrap_or_default()))
+    }
+
+    async fn apply<I>(&mut self, entries: I) -> StorageResult<Vec<SqliteResponse>>
+    where I: IntoIterator<Item = Entry<TypeConfig>> + Send {
+        let entries_iter = entries.into_iter();
+        let mut res = Vec::with_capacity(entries_iter.size_hint().0);
+
+        let mut conn = self.conn.lock().unwrap();
+
+        // The data and the applied state are updated in one transaction.
+        let tx = conn.transaction().map_err(|e| StorageIOError::write_state_machine(&e))?;
+
+        let mut last_applied_log = None;
+        let mut last_membership = None;
+
+        for entry in entries_iter {
+            tracing::debug!(%entry.log_id, "replicate to sm");
+
+            last_applied_log = Some(entry.log_id);
+
+            match entry.payload {
+                EntryPayload::Blank => res.push(SqliteResponse { value: None }),
+                EntryPayload::Normal(SqliteRequest::Set { key, value }) => {
+                    tx.execute("INSERT OR REPLACE INTO sm_data (key, value) VALUES (?1, ?2)", params![
+                        key, value
+                    ])
+                    .map_err(|e| StorageIOError::write_state_machine(&e))?;
+                    res.push(SqliteResponse { value: Some(value) })
+                }
+                EntryPayload::Membership(mem) => {
+                    last_membership = Some(StoredMembership::new(Some(entry.log_id), mem));
+                    res.push(SqliteResponse { value: None })
+                }
+            };
+        }
+
+        if let Some(log_id) = last_applied_log {
+            put_json(&tx, "sm_meta", SM_LAST_APPLIED, &log_id).map_err(StorageIOError::write_state_machine)?;
+        }
+        if let Some(membership) = last_membership {
+            put_json(&tx, "sm_meta", SM_LAST_MEMBERSHIP, &membership).map_err(StorageIOError::write_state_machine)?;
+        }
+
+        tx.commit().map_err(|e| StorageIOError::write_state_machine(&e))?;
+        Ok(res)
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.clone()
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> StorageResult<Box<SnapshotDataOf<TypeConfig>>> {
+        Ok(Box::new(Cursor::new(Vec::new())))
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<TypeConfig>,
+        snapshot: Box<SnapshotDataOf<TypeConfig>>,
+    ) -> StorageResult<()> {
+        tracing::info!(
+            { snapshot_size = snapshot.get_ref().len() },
+            "decoding snapshot for installation"
+        );
+
+        let new_snapshot = SqliteSnapshot {
+            meta: meta.clone(),
+            data: snapshot.into_inner(),
+        };
+
+        let updated_state_machine: StateMachine = serde_json::from_slice(&new_snapshot.data)
+            .map_err(|e| StorageIOError::read_snapshot(Some(new_snapshot.meta.signature()), &e))?;
+
+        let write_err = |e: AnyError| StorageIOError::write_snapshot(Some(meta.signature()), e);
+
+        let mut conn = self.conn.lock().unwrap();
+
+        // Replace the state machine and save the snapshot in one transaction.
+        let tx = conn.transaction().map_err(|e| write_err(AnyError::new(&e)))?;
+
+        tx.execute("DELETE FROM sm_data", []).map_err(|e| write_err(AnyError::new(&e)))?;
+        for (key, value) in updated_state_machine.data.iter() {
+            tx.execute("INSERT INTO sm_data (key, value) VALUES (?1, ?2)", params![key, value])
+                .map_err(|e| write_err(AnyError::new(&e)))?;
+        }
+
+        match &updated_state_machine.last_applied_log {
+            Some(log_id) => put_json(&tx, "sm_meta", SM_LAST_APPLIED, log_id),
+            None => delete_key(&tx, "sm_meta", SM_LAST_APPLIED),
+        }
+        .map_err(write_err)?;
+        put_json(
+            &tx,
+            "sm_meta",
+            SM_LAST_MEMBERSHIP,
+            &updated_state_machine.last_membership,
+        )
+        .map_err(write_err)?;
+        put_json(&tx, "sm_meta", SM_SNAPSHOT, &new_snapshot).map_err(write_err)?;
+
+        tx.commit().map_err(|e| write_err(AnyError::new(&e)))?;
+        Ok(())
+    }
+
+    async fn get_current_snapshot(&mut self) -> StorageResult<Option<Snapshot<TypeConfig>>> {
+        let conn = self.conn.lock().unwrap();
+
+        let snapshot: Option<SqliteSnapshot> =
+            get_json(&conn, "sm_meta", SM_SNAPSHOT).map_err(|e| StorageIOError::read_snapshot(None, e))?;
+
+        Ok(snapshot.map(|s| Snapshot {
+            meta: s.meta,
+            snapshot: Box::new(Cursor::new(s.data)),
+        }))
+    }
+}
+
+/// Open the SQLite database at `db_path` in WAL mode, create it if it does not exist, and return
+/// a pair of `SqliteLogStore` and `SqliteStateMachine` that share the database.
+pub fn open<P: AsRef<Path>>(db_path: P, config: SqliteConfig) -> StorageResult<(SqliteLogStore, SqliteStateMachine)> {
+    let conn = open_connection(db_path.as_ref(), &config).map_err(|e| StorageIOError::read(&e))?;
+
+    let conn = Arc::new(Mutex::new(conn));
+    Ok((SqliteLogStore { conn: conn.clone() }, SqliteStateMachine { conn }))
+}
+
+fn open_connection(db_path: &Path, config: &SqliteConfig) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(db_path)?;
+
+    conn.busy_timeout(config.busy_timeout)?;
+
+    // These pragmas return the value set, which must be read.
+    let journal_mode: String = conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+    if !journal_mode.eq_ignore_ascii_case("wal") {
+        tracing::warn!(
+            "SQLite database {} is not in WAL mode: {}",
+            db_path.display(),
+            journal_mode
+        );
+    }
+    conn.pragma_update_and_check(None, "journal_size_limit", config.journal_size_limit as i64, |row| {
+        row.get::<_, i64>(0)
+    })?;
+    conn.pragma_update_and_check(None, "wal_autocheckpoint", config.wal_autocheckpoint, |row| {
+        row.get::<_, i64>(0)
+    })?;
+
+    conn.pragma_update(None, "synchronous", config.synchronous.as_str())?;
+    // A negative cache size is in KiB rather than in pages.
+    conn.pragma_update(None, "cache_size", -(config.cache_size_kib as i64))?;
+
+    conn.execute_batch(SCHEMA)?;
+
+    Ok(conn)
+}
@@ -0,0 +1,111 @@
+use openraft::storage::RaftStateMachine;
+use openraft::testing::log_id;
+use openraft::testing::StoreBuilder;
+use openraft::testing::Suite;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::StorageError;
+use tempfile::TempDir;
+
+use crate::SqliteConfig;
+use crate::SqliteLogStore;
+use crate::SqliteNodeId;
+use crate::SqliteRequest;
+use crate::SqliteStateMachine;
+use crate::Synchronous;
+use crate::TypeConfig;
+
+const DB_FILE: &str = "raft.db";
+
+struct SqliteBuilder {}
+
+impl StoreBuilder<TypeConfig, SqliteLogStore, SqliteStateMachine, TempDir> for SqliteBuilder {
+    async fn build(&self) -> Result<(TempDir, SqliteLogStore, SqliteStateMachine), StorageError<SqliteNodeId>> {
+        let td = TempDir::new().expect("couldn't create temp dir");
+        let (log_store, sm) = crate::open(td.path().join(DB_FILE), SqliteConfig::default())?;
+        Ok((td, log_store, sm))
+    }
+
+    async fn reopen(
+        &self,
+        td: &TempDir,
+    ) -> Result<Option<(SqliteLogStore, SqliteStateMachine)>, StorageError<SqliteNodeId>> {
+        let stores = crate::open(td.path().join(DB_FILE), SqliteConfig::default())?;
+        Ok(Some(stores))
+    }
+}
+
+#[test]
+pub fn test_sqlite_store() -> Result<(), StorageError<SqliteNodeId>> {
+    Suite::test_all(SqliteBuilder {})?;
+    Ok(())
+}
+
+/// The state machine is persisted, and is restored when the database is reopened.
+#[test]
+pub fn test_sqlite_state_machine_persisted() -> Result<(), StorageError<SqliteNodeId>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+    let path = td.path().join(DB_FILE);
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        {
+            let (_log_store, mut sm) = crate::open(&path, SqliteConfig::default())?;
+            sm.apply([
+                Entry {
+                    log_id: log_id(1, 0, 1),
+                    payload: EntryPayload::Normal(SqliteRequest::Set {
+                        key: "foo".to_string(),
+                        value: "bar".to_string(),
+                    }),
+                },
+                Entry {
+                    log_id: log_id(1, 0, 2),
+                    payload: EntryPayload::Blank,
+                },
+            ])
+            .await?;
+        }
+
+        let (_log_store, mut sm) = crate::open(&path, SqliteConfig::default())?;
+        let (last_applied, _) = sm.applied_state().await?;
+        assert_eq!(Some(log_id(1, 0, 2)), last_applied);
+        assert_eq!(Some("bar".to_string()), sm.get("foo")?);
+        Ok(())
+    })
+}
+
+/// The database is opened in WAL mode with the configured pragmas.
+#[test]
+pub fn test_sqlite_wal_config() -> Result<(), StorageError<SqliteNodeId>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+
+    let config = SqliteConfig {
+        synchronous: Synchronous::Normal,
+        wal_autocheckpoint: 100,
+        cache_size_kib: 1024,
+        ..Default::default()
+    };
+    let (log_store, _sm) = crate::open(td.path().join(DB_FILE), config)?;
+
+    let conn = log_store.conn.lock().unwrap();
+    let pragma = |name: &str| -> String {
+        conn.query_row(&format!("PRAGMA {}", name), [], |row| {
+            row.get::<_, rusqlite::types::Value>(0)
+        })
+        .map(|v| match v {
+            rusqlite::types::Value::Integer(i) => i.to_string(),
+            rusqlite::types::Value::Text(s) => s,
+            other => format!("{:?}", other),
+        })
+        .unwrap()
+    };
+
+    assert_eq!("wal", pragma("journal_mode"));
+    // NORMAL is 1.
+    assert_eq!("1", pragma("synchronous"));
+    assert_eq!("100", pragma("wal_autocheckpoint"));
+    assert_eq!("-1024", pragma("cache_size"));
+    assert_eq!((64 * 1024 * 1024).to_string(), pragma("journal_size_limit"));
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The name of the file storing the generation of the last open of a directory.
+pub(crate) const FENCE_FILE: &str = "fence";
+
+/// A fencing token that detects another process opening the same directory.
+///
+/// Every open of a directory increments the generation persisted in [`FENCE_FILE`], and a store
+/// checks before every write that the persisted generation is still its own. When a second
+/// process opens the directory, the writes of the first one fail instead of interleaving with
+/// the writes of the second one and corrupting the log.
+///
+/// It does not prevent two processes from opening a directory at exactly the same time, in which
+/// case both may get the same generation.
+pub(crate) struct Fence {
+    path: PathBuf,
+    generation: u64,
+}
+
+impl Fence {
+    /// Take over directory `dir` by persisting a generation greater than any previous one.
+    pub(crate) fn acquire(dir: &Path) -> io::Result<Self> {
+        let path = dir.join(FENCE_FILE);
+        let generation = read_generation(&path)?.unwrap_or_default() + 1;
+
+        let tmp = dir.join(format!("{}.tmp", FENCE_FILE));
+        fs::write(&tmp, generation.to_string())?;
+        fs::File::open(&tmp)?.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        fs::File::open(dir)?.sync_all()?;
+
+        Ok(Self { path, generation })
+    }
+
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns an error if the directory has been opened again since this fence is acquired.
+    pub(crate) fn check(&self) -> io::Result<()> {
+        let current = read_generation(&self.path)?;
+        if current == Some(self.generation) {
+            return Ok(());
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "fenced: {} is opened by another store, generation: {:?}, expect: {}",
+                self.path.display(),
+                current,
+                self.generation
+            ),
+        ))
+    }
+}
+
+fn read_generation(path: &Path) -> io::Result<Option<u64>> {
+    let s = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let generation = s.trim().parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Some(generation))
+}
@@ -0,0 +1,584 @@
+//! A pure-Rust, file based implementation of [`RaftLogStorage`].
+//!
+//! The log is stored in a directory as a sequence of append-only segment files, each of which
+//! holds the entries from the index in its file name, e.g., `00000000000000000042.wal`:
+//! - Every entry is a record of `len: u32 | crc32: u32 | payload`, in which the payload is the
+//!   entry serialized with `serde_json`. A torn or corrupted record at the end of the last segment,
+//!   left by a crash during appending, is truncated when the store is opened. Reading a corrupted
+//!   record elsewhere returns [`StorageError::Corrupted`], which is handled at startup according to
+//!   `Config::log_corruption_policy`.
+//! - A new segment is created when the last one reaches [`WalConfig::segment_size`].
+//! - The offset of every record is stored in an index sidecar file, e.g.,
+//!   `00000000000000000042.idx`, so that an entry is read without scanning the segment.
+//! - [`RaftLogStorage::purge()`] deletes the segments whose entries are all purged, and
+//!   [`RaftLogStorage::truncate()`] deletes the segments after the truncation point and truncates
+//!   the segment containing it.
+//!
+//! Every append is flushed to disk by default. To flush appended entries by group, disable
+//! [`WalConfig::sync_on_append`] and wrap the store in a [`GroupCommit`].
+//!
+//! [`GroupCommit`]: openraft::storage::GroupCommit
+//!
+//! The payload of every record is encrypted with `RaftTypeConfig::StorageCipher`, if it is
+//! configured.
+//!
+//! The vote, the committed log id and the last purged log id are stored in `meta.json`, which is
+//! replaced atomically by writing a temporary file and renaming it.
+//!
+//! Every open increments the generation stored in the `fence` file, and every write checks that
+//! the generation is still the one of this store. If another process opens the same directory,
+//! the writes of this store fail instead of corrupting the log, and
+//! [`WalLogStore::check_fence()`] returns an error.
+#![deny(unused_crate_dependencies)]
+#![deny(unused_qualifications)]
+
+#[cfg(test)] mod test;
+
+mod fence;
+mod segment;
+
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::ops::Bound;
+use std::ops::RangeBounds;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use openraft::storage::seal;
+use openraft::storage::unseal;
+use openraft::storage::EncodedEntries;
+use openraft::storage::LogFlushed;
+use openraft::storage::LogState;
+use openraft::storage::RaftLogStorage;
+use openraft::storage::RaftLogSync;
+use openraft::LogCorruption;
+use openraft::LogId;
+use openraft::OptionalSend;
+use openraft::RaftLogId;
+use openraft::RaftLogReader;
+use openraft::RaftTypeConfig;
+use openraft::StorageError;
+use openraft::StorageIOError;
+use openraft::Vote;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::fence::Fence;
+use crate::segment::Segment;
+
+/// The name of the file storing [`Meta`].
+const META_FILE: &str = "meta.json";
+
+/// Configuration of a [`WalLogStore`].
+#[derive(Clone, Debug)]
+pub struct WalConfig {
+    /// The size in bytes at which a segment is closed and a new one is created for further
+    /// entries.
+    ///
+    /// A segment is deleted as a whole when purged, thus a smaller size releases disk space
+    /// sooner, with more files.
+    pub segment_size: u64,
+
+    /// Whether to flush the entries to disk in every [`RaftLogStorage::append()`].
+    ///
+    /// Disable it only when the store is wrapped in a [`GroupCommit`], which flushes the entries
+    /// with [`RaftLogSync::sync()`].
+    ///
+    /// [`GroupCommit`]: openraft::storage::GroupCommit
+    pub sync_on_append: bool,
+}
+
+impl Default for WalConfig {
+    fn default() -> Self {
+        Self {
+            segment_size: 64 * 1024 * 1024,
+            sync_on_append: true,
+        }
+    }
+}
+
+/// The non-log states stored in the `meta.json` file.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct Meta<C>
+where C: RaftTypeConfig
+{
+    vote: Option<Vote<C::NodeId>>,
+    committed: Option<LogId<C::NodeId>>,
+    last_purged_log_id: Option<LogId<C::NodeId>>,
+}
+
+impl<C> Default for Meta<C>
+where C: RaftTypeConfig
+{
+    fn default() -> Self {
+        Self {
+            vote: None,
+            committed: None,
+            last_purged_log_id: None,
+        }
+    }
+}
+
+/// A [`RaftLogStorage`] that stores the log in segmented write-ahead-log files in a directory.
+///
+/// It is cheap to clone: the clones share the same files, and a clone is used as the
+/// [`RaftLogStorage::LogReader`].
+pub struct WalLogStore<C>
+where C: RaftTypeConfig
+{
+    inner: Arc<Mutex<Inner<C>>>,
+}
+
+impl<C> Clone for WalLogStore<C>
+where C: RaftTypeConfig
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+struct Inner<C>
+where C: RaftTypeConfig
+{
+    dir: PathBuf,
+    config: WalConfig,
+
+    /// Segments sorted by their first index, with contiguous entries.
+    segments: Vec<Segment>,
+
+    meta: Meta<C>,
+
+    fence: Fence,
+}
+
+impl<C> WalLogStore<C>
+where C: RaftTypeConfig
+{
+    /// Open the log store in directory `dir`, create the directory if it does not exist.
+    pub fn open(dir: impl AsRef<Path>, config: WalConfig) -> Result<Self, StorageError<C::NodeId>> {
+        let inner = Inner::open(dir.as_ref(), config).map_err(|e| StorageIOError::read_logs(&e))?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(inner)),
+        })
+    }
+
+    /// The generation of this store, incremented every time the directory is opened.
+    pub fn generation(&self) -> u64 {
+        self.inner.lock().unwrap().fence.generation()
+    }
+
+    /// Returns an error if the directory has been opened by another store since this store is
+    /// opened, in which case this store can no longer write.
+    pub fn check_fence(&self) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.lock().unwrap().fence.check().map_err(|e| StorageIOError::write_logs(&e))?;
+        Ok(())
+    }
+}
+
+impl<C> Inner<C>
+where C: RaftTypeConfig
+{
+    fn open(dir: &Path, config: WalConfig) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let fence = Fence::acquire(dir)?;
+
+        let meta = match fs::read(dir.join(META_FILE)) {
+            Ok(buf) => serde_json::from_slice(&buf)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Meta::default(),
+            Err(e) => return Err(e),
+        };
+
+        let mut first_indexes = vec![];
+        for dir_entry in fs::read_dir(dir)? {
+            let name = dir_entry?.file_name();
+            if let Some(first_index) = name.to_str().and_then(Segment::parse_file_name) {
+                first_indexes.push(first_index);
+            }
+        }
+        first_indexes.sort();
+
+        let mut segments: Vec<Segment> = vec![];
+        let n = first_indexes.len();
+        for (i, first_index) in first_indexes.into_iter().enumerate() {
+            let (seg, torn) = Segment::open(dir, first_index)?;
+
+            if torn && i + 1 < n {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("torn record in segment {} which is not the last", first_index),
+                ));
+            }
+
+            // A segment is left empty by a crash right after it is created.
+            if seg.is_empty() {
+                seg.remove()?;
+                continue;
+            }
+
+            if let Some(prev) = segments.last() {
+                if prev.next_index() != seg.first_index() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "segment {} does not follow the previous one ending at {}",
+                            first_index,
+                            prev.next_index()
+                        ),
+                    ));
+                }
+            }
+
+            segments.push(seg);
+        }
+
+        let mut this = Self {
+            dir: dir.to_path_buf(),
+            config,
+            segments,
+            meta,
+            fence,
+        };
+
+        // Segments that are purged but not yet deleted before a crash.
+        this.remove_purged_segments()?;
+
+        Ok(this)
+    }
+
+    /// The index of the next entry to append, `None` if there is no entry.
+    fn next_index(&self) -> Option<u64> {
+        self.segments.last().map(|s| s.next_index())
+    }
+
+    fn read_entry(&mut self, index: u64) -> io::Result<C::Entry> {
+        let i = self.segments.partition_point(|s| s.first_index() <= index) - 1;
+        let buf = self.segments[i].read(index)?;
+        let buf = unseal::<C::StorageCipher>(buf)?;
+        Ok(serde_json::from_slice(&buf)?)
+    }
+
+    /// Read an entry and report a CRC mismatch or an undecodable record as corruption.
+    fn read_verified_entry(&mut self, index: u64) -> Result<C::Entry, StorageError<C::NodeId>> {
+        self.read_entry(index).map_err(|e| {
+            if e.kind() == io::ErrorKind::InvalidData {
+                LogCorruption::new(index, &e).into()
+            } else {
+                StorageIOError::read_logs(&e).into()
+            }
+        })
+    }
+
+    fn append_entry(&mut self, entry: &C::Entry) -> io::Result<()> {
+        let index = entry.get_log_id().index;
+
+        self.check_contiguous(index)?;
+        self.roll_if_full(index)?;
+
+        let buf = seal::<C::StorageCipher>(serde_json::to_vec(entry)?)?;
+        self.segments.last_mut().unwrap().append(&buf)
+    }
+
+    fn check_contiguous(&self, index: u64) -> io::Result<()> {
+        if let Some(next) = self.next_index() {
+            if index != next {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("appending entry at {} is not contiguous, expect: {}", index, next),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a new segment starting from `index` if there is no segment or the last one is full.
+    fn roll_if_full(&mut self, index: u64) -> io::Result<()> {
+        let roll = match self.segments.last() {
+            None => true,
+            Some(last) => last.size() >= self.config.segment_size,
+        };
+        if roll {
+            if let Some(last) = self.segments.last_mut() {
+                last.sync()?;
+            }
+            self.segments.push(Segment::create(&self.dir, index)?);
+        }
+        Ok(())
+    }
+
+    /// Flush the appended entries if [`WalConfig::sync_on_append`] is enabled, and call the
+    /// callback.
+    fn complete_append(&mut self, callback: LogFlushed<C>) -> Result<(), StorageError<C::NodeId>> {
+        let res = if self.config.sync_on_append {
+            self.sync()
+        } else {
+            Ok(())
+        };
+
+        if let Err(e) = res {
+            let err = StorageIOError::write_logs(&e);
+            callback.log_io_completed(Err(e));
+            return Err(err.into());
+        }
+
+        callback.log_io_completed(Ok(()));
+        Ok(())
+    }
+
+    /// Append serialized entries, with one vectored write for the entries in the same segment.
+    fn append_encoded(&mut self, first_index: u64, bufs: &[Vec<u8>]) -> io::Result<()> {
+        self.fence.check()?;
+
+        self.check_contiguous(first_index)?;
+
+        let mut index = first_index;
+        let mut rest = bufs;
+
+        while !rest.is_empty() {
+            self.roll_if_full(index)?;
+
+            // Same as appending one by one: add entries until the segment reaches the size limit.
+            let last = self.segments.last_mut().unwrap();
+            let mut size = last.size();
+            let mut n = 0;
+            while n < rest.len() && size < self.config.segment_size {
+                size += segment::HEADER_SIZE + rest[n].len() as u64;
+                n += 1;
+            }
+
+            last.append_batch(&rest[..n])?;
+            index += n as u64;
+            rest = &rest[n..];
+        }
+
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        match self.segments.last_mut() {
+            Some(last) => last.sync(),
+            None => Ok(()),
+        }
+    }
+
+    fn truncate(&mut self, index: u64) -> io::Result<()> {
+        self.fence.check()?;
+
+        while let Some(last) = self.segments.last() {
+            if last.first_index() < index {
+                break;
+            }
+            self.segments.pop().unwrap().remove()?;
+        }
+
+        if let Some(last) = self.segments.last_mut() {
+            if last.next_index() > index {
+                last.truncate(index)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Delete the segments in which every entry is purged.
+    fn remove_purged_segments(&mut self) -> io::Result<()> {
+        let Some(purged) = self.meta.last_purged_log_id else {
+            return Ok(());
+        };
+
+        let n = self.segments.partition_point(|s| s.next_index() <= purged.index + 1);
+        for seg in self.segments.drain(..n) {
+            seg.remove()?;
+        }
+        Ok(())
+    }
+
+    fn save_meta(&self) -> io::Result<()> {
+        self.fence.check()?;
+
+        let buf = serde_json::to_vec(&self.meta)?;
+
+        let tmp = self.dir.join(format!("{}.tmp", META_FILE));
+        fs::write(&tmp, buf)?;
+        fs::File::open(&tmp)?.sync_all()?;
+        fs::rename(&tmp, self.dir.join(META_FILE))?;
+        fs::File::open(&self.dir)?.sync_all()
+    }
+}
+
+impl<C> RaftLogReader<C> for WalLogStore<C>
+where C: RaftTypeConfig
+{
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + OptionalSend>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<C::Entry>, StorageError<C::NodeId>> {
+        let mut inner = self.inner.lock().unwrap();
+
+        let Some(next_index) = inner.next_index() else {
+            return Ok(vec![]);
+        };
+
+        let start = match range.start_bound() {
+            Bound::Included(x) => *x,
+            Bound::Excluded(x) => *x + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => *x + 1,
+            Bound::Excluded(x) => *x,
+            Bound::Unbounded => next_index,
+        };
+
+        // A segment may contain purged entries, which should not be visible.
+        let first_index = match inner.meta.last_purged_log_id {
+            Some(purged) => purged.index + 1,
+            None => inner.segments[0].first_index(),
+        };
+
+        let start = std::cmp::max(start, first_index);
+        let end = std::cmp::min(end, next_index);
+
+        let mut entries = vec![];
+        for index in start..end {
+            let ent = inner.read_verified_entry(index)?;
+            entries.push(ent);
+        }
+
+        Ok(entries)
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        Ok(self.inner.lock().unwrap().meta.vote)
+    }
+}
+
+impl<C> RaftLogStorage<C> for WalLogStore<C>
+where C: RaftTypeConfig
+{
+    type LogReader = Self;
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        let mut inner = self.inner.lock().unwrap();
+
+        let last_purged_log_id = inner.meta.last_purged_log_id;
+
+        let last_log_id = match inner.next_index() {
+            None => last_purged_log_id,
+            Some(next_index) => {
+                let ent = inner.read_verified_entry(next_index - 1)?;
+                Some(*ent.get_log_id())
+            }
+        };
+
+        Ok(LogState {
+            last_purged_log_id,
+            last_log_id,
+        })
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.clone()
+    }
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.meta.vote = Some(*vote);
+        inner.save_meta().map_err(|e| StorageIOError::write_vote(&e))?;
+        Ok(())
+    }
+
+    async fn save_committed(&mut self, committed: Option<LogId<C::NodeId>>) -> Result<(), StorageError<C::NodeId>> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.meta.committed = committed;
+        inner.save_meta().map_err(|e| StorageIOError::write(&e))?;
+        Ok(())
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        Ok(self.inner.lock().unwrap().meta.committed)
+    }
+
+    #[tracing::instrument(level = "trace", skip_all)]
+    async fn append<I>(&mut self, entries: I, callback: LogFlushed<C>) -> Result<(), StorageError<C::NodeId>>
+    where
+        I: IntoIterator<Item = C::Entry> + OptionalSend,
+        I::IntoIter: OptionalSend,
+    {
+        let mut inner = self.inner.lock().unwrap();
+        inner.fence.check().map_err(|e| StorageIOError::write_logs(&e))?;
+
+        for entry in entries {
+            inner.append_entry(&entry).map_err(|e| StorageIOError::write_log_entry(*entry.get_log_id(), &e))?;
+        }
+
+        inner.complete_append(callback)
+    }
+
+    fn encode_entry(&self, entry: &C::Entry) -> Result<Option<Vec<u8>>, StorageError<C::NodeId>> {
+        let buf = serde_json::to_vec(entry).map_err(|e| StorageIOError::write_log_entry(*entry.get_log_id(), &e))?;
+        Ok(Some(buf))
+    }
+
+    #[tracing::instrument(level = "trace", skip_all)]
+    async fn append_vectored(
+        &mut self,
+        entries: EncodedEntries<C>,
+        callback: LogFlushed<C>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let Some(first) = entries.entries().first() else {
+            callback.log_io_completed(Ok(()));
+            return Ok(());
+        };
+        let first_index = first.get_log_id().index;
+
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.append_encoded(first_index, entries.bufs()).map_err(|e| StorageIOError::write_logs(&e))?;
+
+        inner.complete_append(callback)
+    }
+
+    fn supports_storage_cipher(&self) -> bool {
+        true
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn truncate(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        tracing::debug!("delete_log: [{:?}, +oo)", log_id);
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.truncate(log_id.index).map_err(|e| StorageIOError::write_logs(&e))?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn purge(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        tracing::debug!("purge_log_upto: {:?}", log_id);
+
+        let mut inner = self.inner.lock().unwrap();
+
+        // Save the purged log id before deleting any segment, so that a crash in between leaves
+        // only deleted entries that are already invisible.
+        inner.meta.last_purged_log_id = Some(log_id);
+        inner.save_meta().map_err(|e| StorageIOError::write_logs(&e))?;
+
+        inner.remove_purged_segments().map_err(|e| StorageIOError::write_logs(&e))?;
+        Ok(())
+    }
+}
+
+impl<C> RaftLogSync<C> for WalLogStore<C>
+where C: RaftTypeConfig
+{
+    async fn sync(&mut self) -> Result<(), StorageError<C::NodeId>> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.sync().map_err(|e| StorageIOError::write_logs(&e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,450 @@
+//! A segment file of the log and its index sidecar file.
+
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::IoSlice;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The size of a record header: the payload length and the CRC32 of the payload, both `u32` in
+/// little-endian.
+pub(crate) const HEADER_SIZE: u64 = 8;
+
+/// A segment stores the records of consecutive log entries starting from `first_index`.
+///
+/// A record is `len: u32 | crc32: u32 | payload`. The offset of every record is stored in the
+/// index file as a little-endian `u64`, so that an entry can be located without scanning the
+/// segment. The index file is only a cache: it is rebuilt from the segment if it lags behind.
+pub(crate) struct Segment {
+    first_index: u64,
+
+    /// The offset of every record in the segment file: entry `first_index + i` is at `offsets[i]`.
+    offsets: Vec<u64>,
+
+    /// The size of the segment file, i.e., the end of the last record.
+    size: u64,
+
+    path: PathBuf,
+    index_path: PathBuf,
+
+    file: File,
+    index_file: File,
+}
+
+impl Segment {
+    /// Create a new empty segment whose first entry is `first_index`.
+    pub(crate) fn create(dir: &Path, first_index: u64) -> io::Result<Self> {
+        let (path, index_path) = Self::paths(dir, first_index);
+
+        let file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        let index_file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&index_path)?;
+
+        Ok(Self {
+            first_index,
+            offsets: Vec::new(),
+            size: 0,
+            path,
+            index_path,
+            file,
+            index_file,
+        })
+    }
+
+    /// Open an existing segment.
+    ///
+    /// The records that are not in the index file are recovered by scanning the segment, and a
+    /// torn record at the end, left by a crash during writing, is truncated. It returns the
+    /// segment and whether a torn record is truncated.
+    pub(crate) fn open(dir: &Path, first_index: u64) -> io::Result<(Self, bool)> {
+        let (path, index_path) = Self::paths(dir, first_index);
+
+        let mut file = OpenOptions::new().read(true).write(true).open(&path)?;
+        let mut index_file =
+            OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&index_path)?;
+
+        let file_size = file.metadata()?.len();
+
+        let mut buf = Vec::new();
+        index_file.read_to_end(&mut buf)?;
+        let mut offsets = buf
+            .chunks_exact(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+            .take_while(|offset| *offset < file_size)
+            .collect::<Vec<_>>();
+
+        // The last indexed record may be torn; scan again from it.
+        let mut size = offsets.pop().unwrap_or(0);
+
+        file.seek(SeekFrom::Start(size))?;
+        let mut reader = io::BufReader::new(&mut file);
+        while size < file_size {
+            match read_raw_record(&mut reader) {
+                Ok((payload, crc_ok)) => {
+                    let end = size + HEADER_SIZE + payload.len() as u64;
+
+                    // A corrupted record at the end is a torn write, but one followed by other records
+                    // is kept, so that reading it reports the corruption.
+                    if !crc_ok && end == file_size {
+                        tracing::warn!(
+                            path = display(path.display()),
+                            offset = size,
+                            "truncate torn record: CRC mismatch"
+                        );
+                        break;
+                    }
+
+                    offsets.push(size);
+                    size = end;
+                }
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    tracing::warn!(
+                        path = display(path.display()),
+                        offset = size,
+                        error = display(&e),
+                        "truncate torn record"
+                    );
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let torn = size < file_size;
+        if torn {
+            file.set_len(size)?;
+            file.sync_data()?;
+        }
+
+        let mut this = Self {
+            first_index,
+            offsets,
+            size,
+            path,
+            index_path,
+            file,
+            index_file,
+        };
+        this.rewrite_index()?;
+
+        Ok((this, torn))
+    }
+
+    /// The path of the segment file and the index file of a segment starting from `first_index`.
+    fn paths(dir: &Path, first_index: u64) -> (PathBuf, PathBuf) {
+        let name = format!("{:020}", first_index);
+        (dir.join(format!("{}.wal", name)), dir.join(format!("{}.idx", name)))
+    }
+
+    /// Parse the first index from the file name of a segment.
+    pub(crate) fn parse_file_name(name: &str) -> Option<u64> {
+        name.strip_suffix(".wal")?.parse().ok()
+    }
+
+    pub(crate) fn first_index(&self) -> u64 {
+        self.first_index
+    }
+
+    /// The index of the entry after the last one in this segment.
+    pub(crate) fn next_index(&self) -> u64 {
+        self.first_index + self.offsets.len() as u64
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Append a record of the next entry.
+    pub(crate) fn append(&mut self, payload: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record is larger than 4GiB"))?;
+
+        let mut buf = Vec::with_capacity(HEADER_SIZE as usize + payload.len());
+        buf.extend_from_slice(&len.to_le_bytes());
+        buf.extend_from_slice(&crc32fast::hash(payload).to_le_bytes());
+        buf.extend_from_slice(payload);
+
+        self.file.seek(SeekFrom::Start(self.size))?;
+        self.file.write_all(&buf)?;
+
+        self.index_file.seek(SeekFrom::Start(self.offsets.len() as u64 * 8))?;
+        self.index_file.write_all(&self.size.to_le_bytes())?;
+
+        self.offsets.push(self.size);
+        self.size += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Append the records of the next entries with a single vectored write.
+    pub(crate) fn append_batch(&mut self, payloads: &[Vec<u8>]) -> io::Result<()> {
+        let mut headers = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            let len = u32::try_from(payload.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record is larger than 4GiB"))?;
+
+            let mut header = [0u8; HEADER_SIZE as usize];
+            header[0..4].copy_from_slice(&len.to_le_bytes());
+            header[4..8].copy_from_slice(&crc32fast::hash(payload).to_le_bytes());
+            headers.push(header);
+        }
+
+        let mut slices = Vec::with_capacity(payloads.len() * 2);
+        for (header, payload) in headers.iter().zip(payloads) {
+            slices.push(IoSlice::new(header));
+            slices.push(IoSlice::new(payload));
+        }
+
+        self.file.seek(SeekFrom::Start(self.size))?;
+        write_all_vectored(&mut self.file, &slices)?;
+
+        let mut index_buf = Vec::with_capacity(payloads.len() * 8);
+        for payload in payloads {
+            index_buf.extend_from_slice(&self.size.to_le_bytes());
+            self.offsets.push(self.size);
+            self.size += HEADER_SIZE + payload.len() as u64;
+        }
+
+        let first = (self.offsets.len() - payloads.len()) as u64;
+        self.index_file.seek(SeekFrom::Start(first * 8))?;
+        self.index_file.write_all(&index_buf)?;
+
+        Ok(())
+    }
+
+    /// Read the payload of the record of entry `index`, which must be in this segment.
+    pub(crate) fn read(&mut self, index: u64) -> io::Result<Vec<u8>> {
+        let offset = self.offsets[(index - self.first_index) as usize];
+
+        self.file.seek(SeekFrom::Start(offset))?;
+        read_record(&mut self.file)
+    }
+
+    /// Flush the written records to disk.
+    pub(crate) fn sync(&mut self) -> io::Result<()> {
+        self.file.sync_data()?;
+        self.index_file.sync_data()?;
+        Ok(())
+    }
+
+    /// Remove the entries since `index`, which must be in this segment.
+    pub(crate) fn truncate(&mut self, index: u64) -> io::Result<()> {
+        let n = (index - self.first_index) as usize;
+
+        self.size = self.offsets[n];
+        self.offsets.truncate(n);
+
+        self.file.set_len(self.size)?;
+        self.index_file.set_len(n as u64 * 8)?;
+        self.sync()
+    }
+
+    /// Remove the segment file and the index file.
+    pub(crate) fn remove(self) -> io::Result<()> {
+        let Self {
+            path,
+            index_path,
+            file,
+            index_file,
+            ..
+        } = self;
+
+        drop(file);
+        drop(index_file);
+
+        fs::remove_file(path)?;
+        fs::remove_file(index_path)?;
+        Ok(())
+    }
+
+    /// Write the index file with the offsets in memory.
+    fn rewrite_index(&mut self) -> io::Result<()> {
+        let buf = self.offsets.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<_>>();
+
+        self.index_file.set_len(0)?;
+        self.index_file.seek(SeekFrom::Start(0))?;
+        self.index_file.write_all(&buf)?;
+        self.index_file.sync_data()
+    }
+}
+
+/// Write all of the slices, with as few `writev()` calls as possible.
+fn write_all_vectored(w: &mut impl Write, slices: &[IoSlice<'_>]) -> io::Result<()> {
+    let mut i = 0;
+    while i < slices.len() {
+        let mut n = w.write_vectored(&slices[i..])?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer"));
+        }
+
+        // Skip the slices that are completely written and finish the partially written one.
+        while i < slices.len() && n >= slices[i].len() {
+            n -= slices[i].len();
+            i += 1;
+        }
+        if n > 0 {
+            w.write_all(&slices[i][n..])?;
+            i += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Read a record and verify its CRC.
+///
+/// It returns an `UnexpectedEof` error if the record is incomplete, and an `InvalidData` error if
+/// the CRC does not match.
+fn read_record(r: &mut impl Read) -> io::Result<Vec<u8>> {
+    let (payload, crc_ok) = read_raw_record(r)?;
+
+    if !crc_ok {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "record CRC mismatch"));
+    }
+
+    Ok(payload)
+}
+
+/// Read a record without failing on CRC mismatch: it returns the payload and whether the CRC
+/// matches.
+fn read_raw_record(r: &mut impl Read) -> io::Result<(Vec<u8>, bool)> {
+    let mut header = [0u8; HEADER_SIZE as usize];
+    r.read_exact(&mut header)?;
+
+    let len = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    let crc = u32::from_le_bytes(header[4..8].try_into().unwrap());
+
+    let mut payload = vec![0u8; len as usize];
+    r.read_exact(&mut payload)?;
+
+    let crc_ok = crc32fast::hash(&payload) == crc;
+    Ok((payload, crc_ok))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    use super::Segment;
+    use super::HEADER_SIZE;
+
+    #[test]
+    fn test_segment_append_read_truncate() -> std::io::Result<()> {
+        let td = tempfile::TempDir::new()?;
+
+        let mut seg = Segment::create(td.path(), 5)?;
+        seg.append(b"foo")?;
+        seg.append(b"bar")?;
+        seg.append(b"baz")?;
+        seg.sync()?;
+
+        assert_eq!(8, seg.next_index());
+        assert_eq!(b"bar".to_vec(), seg.read(6)?);
+
+        seg.truncate(7)?;
+        assert_eq!(7, seg.next_index());
+        assert_eq!(22, seg.size());
+
+        seg.append(b"qux")?;
+        assert_eq!(b"qux".to_vec(), seg.read(7)?);
+        seg.sync()?;
+        drop(seg);
+
+        let (mut seg, torn) = Segment::open(td.path(), 5)?;
+        assert!(!torn);
+        assert_eq!(8, seg.next_index());
+        assert_eq!(b"foo".to_vec(), seg.read(5)?);
+        assert_eq!(b"qux".to_vec(), seg.read(7)?);
+
+        seg.remove()?;
+        assert_eq!(0, std::fs::read_dir(td.path())?.count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_append_batch() -> std::io::Result<()> {
+        let td = tempfile::TempDir::new()?;
+
+        let mut seg = Segment::create(td.path(), 5)?;
+        seg.append(b"foo")?;
+        seg.append_batch(&[b"bar".to_vec(), b"".to_vec(), b"quux".to_vec()])?;
+        seg.sync()?;
+
+        assert_eq!(9, seg.next_index());
+        assert_eq!(b"".to_vec(), seg.read(7)?);
+        assert_eq!(b"quux".to_vec(), seg.read(8)?);
+        drop(seg);
+
+        let (mut seg, torn) = Segment::open(td.path(), 5)?;
+        assert!(!torn);
+        assert_eq!(9, seg.next_index());
+        assert_eq!(b"bar".to_vec(), seg.read(6)?);
+        assert_eq!(b"quux".to_vec(), seg.read(8)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_recover() -> std::io::Result<()> {
+        let td = tempfile::TempDir::new()?;
+
+        let mut seg = Segment::create(td.path(), 0)?;
+        seg.append(b"foo")?;
+        seg.append(b"bar")?;
+        seg.sync()?;
+        drop(seg);
+
+        // Lose the index file and tear the last record.
+        let (path, index_path) = Segment::paths(td.path(), 0);
+        std::fs::remove_file(index_path)?;
+        let mut f = OpenOptions::new().append(true).open(&path)?;
+        f.write_all(&[3, 0, 0, 0, 1, 2])?;
+        drop(f);
+
+        let (mut seg, torn) = Segment::open(td.path(), 0)?;
+        assert!(torn);
+        assert_eq!(2, seg.next_index());
+        assert_eq!(22, seg.size());
+        assert_eq!(b"bar".to_vec(), seg.read(1)?);
+
+        // A corrupted record is truncated too.
+        seg.append(b"baz")?;
+        seg.sync()?;
+        drop(seg);
+
+        let mut content = std::fs::read(&path)?;
+        let last = content.len() - 1;
+        content[last] ^= 0xff;
+        std::fs::write(&path, content)?;
+
+        let (mut seg, torn) = Segment::open(td.path(), 0)?;
+        assert!(torn);
+        assert_eq!(2, seg.next_index());
+
+        // A corrupted record followed by other records is kept and reported when reading.
+        seg.append(b"baz")?;
+        seg.sync()?;
+        drop(seg);
+
+        let mut content = std::fs::read(&path)?;
+        content[11 + HEADER_SIZE as usize] ^= 0xff;
+        std::fs::write(&path, content)?;
+
+        let (mut seg, torn) = Segment::open(td.path(), 0)?;
+        assert!(!torn);
+        assert_eq!(3, seg.next_index());
+        assert_eq!(std::io::ErrorKind::InvalidData, seg.read(1).unwrap_err().kind());
+        assert_eq!(b"baz".to_vec(), seg.read(2)?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+use std::io;
+use std::io::Cursor;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use openraft::entry::RaftEntry;
+use openraft::storage::seal;
+use openraft::storage::FsyncPolicy;
+use openraft::storage::GroupCommit;
+use openraft::storage::RaftLogStorage;
+use openraft::storage::RaftLogStorageExt;
+use openraft::storage::StorageCipher;
+use openraft::storage::StorageHelper;
+use openraft::testing::log_id;
+use openraft::testing::StoreBuilder;
+use openraft::testing::Suite;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::LogCorruptionPolicy;
+use openraft::RaftLogReader;
+use openraft::StorageError;
+use openraft::Vote;
+use openraft_memstore::BlockConfig;
+use openraft_memstore::MemNodeId;
+use openraft_memstore::MemStateMachine;
+use openraft_memstore::TypeConfig;
+use tempfile::TempDir;
+
+use crate::WalConfig;
+use crate::WalLogStore;
+
+struct WalBuilder {}
+
+impl StoreBuilder<TypeConfig, WalLogStore<TypeConfig>, Arc<MemStateMachine>, TempDir> for WalBuilder {
+    async fn build(&self) -> Result<(TempDir, WalLogStore<TypeConfig>, Arc<MemStateMachine>), StorageError<MemNodeId>> {
+        let td = TempDir::new().expect("couldn't create temp dir");
+
+        let log_store = WalLogStore::open(td.path(), config())?;
+        let sm = Arc::new(MemStateMachine::new(BlockConfig::default()));
+        Ok((td, log_store, sm))
+    }
+
+    async fn reopen(
+        &self,
+        td: &TempDir,
+    ) -> Result<Option<(WalLogStore<TypeConfig>, Arc<MemStateMachine>)>, StorageError<MemNodeId>> {
+        let log_store = WalLogStore::open(td.path(), config())?;
+        let sm = Arc::new(MemStateMachine::new(BlockConfig::default()));
+        Ok(Some((log_store, sm)))
+    }
+}
+
+struct GroupCommitBuilder {}
+
+impl StoreBuilder<TypeConfig, GroupCommit<TypeConfig, WalLogStore<TypeConfig>>, Arc<MemStateMachine>, TempDir>
+    for GroupCommitBuilder
+{
+    async fn build(
+        &self,
+    ) -> Result<
+        (
+            TempDir,
+            GroupCommit<TypeConfig, WalLogStore<TypeConfig>>,
+            Arc<MemStateMachine>,
+        ),
+        StorageError<MemNodeId>,
+    > {
+        let td = TempDir::new().expect("couldn't create temp dir");
+        let (log_store, sm) = self.open(&td)?;
+        Ok((td, log_store, sm))
+    }
+
+    async fn reopen(
+        &self,
+        td: &TempDir,
+    ) -> Result<Option<(GroupCommit<TypeConfig, WalLogStore<TypeConfig>>, Arc<MemStateMachine>)>, StorageError<MemNodeId>>
+    {
+        Ok(Some(self.open(td)?))
+    }
+}
+
+impl GroupCommitBuilder {
+    #[allow(clippy::type_complexity)]
+    fn open(
+        &self,
+        td: &TempDir,
+    ) -> Result<(GroupCommit<TypeConfig, WalLogStore<TypeConfig>>, Arc<MemStateMachine>), StorageError<MemNodeId>> {
+        let config = WalConfig {
+            sync_on_append: false,
+            ..config()
+        };
+        let log_store = GroupCommit::new(WalLogStore::open(td.path(), config)?, FsyncPolicy {
+            max_entries: 4,
+            max_delay: Duration::from_millis(1),
+        });
+        let sm = Arc::new(MemStateMachine::new(BlockConfig::default()));
+        Ok((log_store, sm))
+    }
+}
+
+#[test]
+pub fn test_wal_store() -> Result<(), StorageError<MemNodeId>> {
+    Suite::test_all(WalBuilder {})?;
+    Ok(())
+}
+
+#[test]
+pub fn test_wal_store_group_commit() -> Result<(), StorageError<MemNodeId>> {
+    Suite::test_all(GroupCommitBuilder {})?;
+    Ok(())
+}
+
+/// An append is acknowledged only when enough entries are appended, or after the max delay.
+#[test]
+pub fn test_group_commit_policy() -> Result<(), StorageError<MemNodeId>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+    let config = WalConfig {
+        sync_on_append: false,
+        ..config()
+    };
+
+    let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    rt.block_on(async {
+        let mut store = GroupCommit::new(WalLogStore::<TypeConfig>::open(td.path(), config)?, FsyncPolicy {
+            max_entries: 3,
+            max_delay: Duration::from_millis(500),
+        });
+
+        // Not flushed until 3 entries are appended.
+        let res = tokio::time::timeout(
+            Duration::from_millis(100),
+            store.blocking_append([blank(1, 1), blank(1, 2)]),
+        )
+        .await;
+        assert!(res.is_err(), "2 entries are not flushed");
+
+        store.blocking_append([blank(1, 3)]).await?;
+
+        // Flushed after the max delay.
+        let now = Instant::now();
+        store.blocking_append([blank(1, 4)]).await?;
+        assert!(now.elapsed() >= Duration::from_millis(500));
+
+        assert_eq!(Some(log_id(1, 0, 4)), store.get_log_state().await?.last_log_id);
+
+        Ok(())
+    })
+}
+
+fn blank(term: u64, index: u64) -> Entry<TypeConfig> {
+    Entry::new_blank(log_id(term, 0, index))
+}
+
+/// A small segment size to test entries across segments.
+fn config() -> WalConfig {
+    WalConfig {
+        segment_size: 256,
+        ..Default::default()
+    }
+}
+
+/// The log and the meta are restored after reopening the store.
+#[test]
+pub fn test_wal_store_reopen() -> Result<(), StorageError<MemNodeId>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        {
+            let mut store = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+
+            store.save_vote(&Vote::new(3, 1)).await?;
+            store.save_committed(Some(log_id(1, 0, 5))).await?;
+            store.blocking_append((1..=20).map(|i| blank(1, i))).await?;
+            store.truncate(log_id(1, 0, 16)).await?;
+            store.purge(log_id(1, 0, 4)).await?;
+        }
+
+        let mut store = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+
+        assert_eq!(Some(Vote::new(3, 1)), store.read_vote().await?);
+        assert_eq!(Some(log_id(1, 0, 5)), store.read_committed().await?);
+
+        let st = store.get_log_state().await?;
+        assert_eq!(Some(log_id(1, 0, 4)), st.last_purged_log_id);
+        assert_eq!(Some(log_id(1, 0, 15)), st.last_log_id);
+
+        let entries = store.try_get_log_entries(..).await?;
+        assert_eq!(
+            (5..=15).collect::<Vec<_>>(),
+            entries.iter().map(|e| e.log_id.index).collect::<Vec<_>>()
+        );
+
+        store.blocking_append([blank(2, 16)]).await?;
+        assert_eq!(Some(log_id(2, 0, 16)), store.get_log_state().await?.last_log_id);
+
+        Ok(())
+    })
+}
+
+/// A torn record at the end of the last segment is discarded when reopening the store.
+#[test]
+pub fn test_wal_store_torn_tail() -> Result<(), StorageError<MemNodeId>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        {
+            let mut store = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+            store.blocking_append((1..=3).map(|i| blank(1, i))).await?;
+        }
+
+        let path = td.path().join(format!("{:020}.wal", 1));
+        let len = std::fs::metadata(&path).unwrap().len();
+        std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(len - 1).unwrap();
+
+        let mut store = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+        assert_eq!(Some(log_id(1, 0, 2)), store.get_log_state().await?.last_log_id);
+
+        store.blocking_append([blank(1, 3)]).await?;
+        assert_eq!(3, store.try_get_log_entries(..).await?.len());
+
+        Ok(())
+    })
+}
+
+/// A batch of serialized entries is written across segments.
+#[test]
+pub fn test_wal_store_append_encoded() -> Result<(), StorageError<MemNodeId>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        {
+            let store = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+
+            let mut bufs = vec![];
+            for i in 1..=20 {
+                bufs.push(store.encode_entry(&blank(1, i))?.unwrap());
+            }
+
+            let mut inner = store.inner.lock().unwrap();
+            inner.append_encoded(1, &bufs[..5]).unwrap();
+            inner.append_encoded(6, &bufs[5..]).unwrap();
+            inner.sync().unwrap();
+
+            assert!(inner.segments.len() > 1, "entries are split into segments");
+        }
+
+        let mut store = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+        let entries = store.try_get_log_entries(..).await?;
+        assert_eq!(
+            (1..=20).collect::<Vec<_>>(),
+            entries.iter().map(|e| e.log_id.index).collect::<Vec<_>>()
+        );
+
+        Ok(())
+    })
+}
+
+/// A corrupted record that is not at the end is reported when reading, and is handled at startup
+/// according to the `LogCorruptionPolicy`.
+#[test]
+pub fn test_wal_store_corrupted_entry() -> Result<(), StorageError<MemNodeId>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        {
+            let mut store = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+            store.blocking_append([blank(0, 0)]).await?;
+            store.blocking_append((1..=3).map(|i| blank(1, i))).await?;
+        }
+
+        // Flip the first payload byte of the third record, i.e., the entry at index 2.
+        let path = td.path().join(format!("{:020}.wal", 0));
+        let mut content = std::fs::read(&path).unwrap();
+        let mut offset = 0;
+        for _ in 0..2 {
+            offset += 8 + u32::from_le_bytes(content[offset..offset + 4].try_into().unwrap()) as usize;
+        }
+        content[offset + 8] ^= 0xff;
+        std::fs::write(&path, content).unwrap();
+
+        let mut store = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+        assert_eq!(Some(log_id(1, 0, 3)), store.get_log_state().await?.last_log_id);
+
+        let err = store.try_get_log_entries(1..4).await.unwrap_err();
+        assert_eq!(Some(2), err.into_corruption().map(|c| c.index));
+
+        let mut sm = Arc::new(MemStateMachine::new(BlockConfig::default()));
+
+        let res = StorageHelper::new(&mut store, &mut sm).get_initial_state().await;
+        assert_eq!(Some(2), res.unwrap_err().into_corruption().map(|c| c.index));
+
+        StorageHelper::new(&mut store, &mut sm)
+            .log_corruption_policy(LogCorruptionPolicy::TruncateToLastValid)
+            .get_initial_state()
+            .await?;
+        assert_eq!(Some(log_id(1, 0, 1)), store.get_log_state().await?.last_log_id);
+
+        store.blocking_append((2..=3).map(|i| blank(1, i))).await?;
+        assert_eq!(4, store.try_get_log_entries(..).await?.len());
+
+        Ok(())
+    })
+}
+
+/// XOR every byte with the key id, for testing only.
+struct XorCipher;
+
+impl StorageCipher for XorCipher {
+    fn current_key_id() -> u32 {
+        0x5a
+    }
+
+    fn encrypt(key_id: u32, data: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(data.iter().map(|b| b ^ key_id as u8).collect())
+    }
+
+    fn decrypt(key_id: u32, data: &[u8]) -> io::Result<Vec<u8>> {
+        Self::encrypt(key_id, data)
+    }
+}
+
+openraft::declare_raft_types!(
+    CipherConfig:
+        StorageCipher = XorCipher,
+);
+
+/// Entries appended in either way are encrypted on disk, and are decrypted when read.
+#[test]
+pub fn test_wal_store_storage_cipher() -> Result<(), StorageError<u64>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        let app = |i: u64| Entry::<CipherConfig> {
+            log_id: log_id(1, 0, i),
+            payload: EntryPayload::Normal(format!("plaintext-{}", i)),
+        };
+        {
+            let mut store = WalLogStore::<CipherConfig>::open(td.path(), config())?;
+            store.blocking_append((1..=5).map(app)).await?;
+
+            // Sealed as RaftCore does before calling append_vectored().
+            let mut bufs = vec![];
+            for i in 6..=10 {
+                let buf = store.encode_entry(&app(i))?.unwrap();
+                bufs.push(seal::<XorCipher>(buf).unwrap());
+            }
+            let mut inner = store.inner.lock().unwrap();
+            inner.append_encoded(6, &bufs).unwrap();
+            inner.sync().unwrap();
+        }
+
+        for dir_entry in std::fs::read_dir(td.path()).unwrap() {
+            let data = std::fs::read(dir_entry.unwrap().path()).unwrap();
+            assert!(
+                !data.windows(b"plaintext".len()).any(|w| w == b"plaintext"),
+                "no plaintext on disk"
+            );
+        }
+
+        let mut store = WalLogStore::<CipherConfig>::open(td.path(), config())?;
+        let entries = store.try_get_log_entries(..).await?;
+        assert_eq!((1..=10).map(app).collect::<Vec<_>>(), entries);
+
+        Ok(())
+    })
+}
+
+/// A store can not write after another store opens the same directory.
+#[test]
+pub fn test_wal_store_fence() -> Result<(), StorageError<MemNodeId>> {
+    let td = TempDir::new().expect("couldn't create temp dir");
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        let mut first = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+        first.blocking_append([Entry::new_blank(log_id(1, 0, 1))]).await?;
+        first.check_fence()?;
+
+        let mut second = WalLogStore::<TypeConfig>::open(td.path(), config())?;
+        assert_eq!(first.generation() + 1, second.generation());
+
+        assert!(first.check_fence().is_err());
+        assert!(first.blocking_append([Entry::new_blank(log_id(1, 0, 2))]).await.is_err());
+        assert!(RaftLogStorage::save_vote(&mut first, &Vote::new(2, 0)).await.is_err());
+        assert!(first.truncate(log_id(1, 0, 1)).await.is_err());
+        assert!(first.purge(log_id(1, 0, 1)).await.is_err());
+
+        // The new store is not affected by the failed writes of the fenced one.
+        second.check_fence()?;
+        second.blocking_append([Entry::new_blank(log_id(1, 0, 2))]).await?;
+        assert_eq!(None, RaftLogReader::read_vote(&mut second).await?);
+        let entries = second.try_get_log_entries(..).await?;
+        assert_eq!(
+            vec![log_id(1, 0, 1), log_id(1, 0, 2)],
+            entries.iter().map(|e| e.log_id).collect::<Vec<_>>()
+        );
+
+        Ok(())
+    })
+}
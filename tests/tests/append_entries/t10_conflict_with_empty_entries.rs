@@ -57,6 +57,7 @@ async fn conflict_with_empty_entries() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(1, 0), 5)),
         entries: vec![],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 5)),
+        context: Default::default(),
     };
 
     let option = RPCOption::new(Duration::from_millis(1_000));
@@ -78,6 +79,7 @@ async fn conflict_with_empty_entries() -> Result<()> {
             }),
         }],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 5)),
+        context: Default::default(),
     };
 
     let option = RPCOption::new(Duration::from_millis(1_000));
@@ -93,6 +95,7 @@ async fn conflict_with_empty_entries() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(1, 0), 3)),
         entries: vec![],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 5)),
+        context: Default::default(),
     };
 
     let option = RPCOption::new(Duration::from_millis(1_000));
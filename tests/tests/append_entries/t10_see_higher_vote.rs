@@ -50,6 +50,8 @@ async fn append_sees_higher_vote() -> Result<()> {
                 VoteRequest {
                     vote: Vote::new(10, 1),
                     last_log_id: Some(LogId::new(CommittedLeaderId::new(10, 1), 5)),
+                    pre_vote: false,
+                    context: Default::default(),
                 },
                 option,
             )
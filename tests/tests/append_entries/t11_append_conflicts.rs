@@ -49,6 +49,7 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: None,
         entries: vec![],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -63,6 +64,7 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: None,
         entries: vec![blank_ent(0, 0, 0)],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -76,6 +78,7 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(0, 0), 0)),
         entries: vec![],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -97,6 +100,7 @@ async fn append_conflicts() -> Result<()> {
         ],
         // this set the last_applied to 2
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req()).await?;
@@ -121,6 +125,7 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(1, 0), 1)),
         entries: vec![blank_ent(1, 0, 2)],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -137,6 +142,7 @@ async fn append_conflicts() -> Result<()> {
         entries: vec![blank_ent(2, 0, 3)],
         // this set the last_applied to 2
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -151,6 +157,7 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(1, 0), 2000)),
         entries: vec![],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -166,6 +173,7 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(3, 0), 3)),
         entries: vec![],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -181,6 +189,7 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
         entries: vec![blank_ent(2, 0, 3), blank_ent(2, 0, 4), blank_ent(2, 0, 5)],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -196,6 +205,7 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(2, 0), 3)),
         entries: vec![blank_ent(3, 0, 4)],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -212,6 +222,7 @@ async fn append_conflicts() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(1, 0), 200)),
         entries: vec![],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+        context: Default::default(),
     };
 
     let resp = r0.append_entries(req).await?;
@@ -51,6 +51,7 @@ async fn append_entries_with_bigger_term() -> Result<()> {
         prev_log_id: Some(LogId::new(CommittedLeaderId::new(1, 0), log_index)),
         entries: vec![],
         leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), log_index)),
+        context: Default::default(),
     };
 
     let option = RPCOption::new(Duration::from_millis(1_000));
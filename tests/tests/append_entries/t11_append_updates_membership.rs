@@ -61,6 +61,7 @@ async fn append_updates_membership() -> Result<()> {
                 blank_ent(1, 0, 5),
             ],
             leader_commit: Some(LogId::new(CommittedLeaderId::new(0, 0), 0)),
+            context: Default::default(),
         };
 
         let resp = r0.append_entries(req).await?;
@@ -77,6 +78,7 @@ async fn append_updates_membership() -> Result<()> {
             prev_log_id: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
             entries: vec![blank_ent(2, 0, 3)],
             leader_commit: Some(LogId::new(CommittedLeaderId::new(0, 0), 0)),
+            context: Default::default(),
         };
 
         let resp = r0.append_entries(req).await?;
@@ -0,0 +1,70 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::RPCTypes;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RPCRequest;
+use crate::fixtures::RaftRouter;
+use crate::fixtures::EXTRACTED_RPC_CONTEXTS;
+use crate::fixtures::RPC_CONTEXT_KEY;
+use crate::fixtures::RPC_CONTEXT_VALUE;
+
+/// The context injected by the sender is sent with every RPC and extracted by the receiver.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn propagate_rpc_context() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let missing = Arc::new(AtomicU64::new(0));
+    for rpc_type in [RPCTypes::AppendEntries, RPCTypes::Vote] {
+        let missing = missing.clone();
+        router.set_rpc_pre_hook(rpc_type, move |_router, rpc, _from, _to| {
+            let context = match &rpc {
+                RPCRequest::AppendEntries(r) => &r.context,
+                RPCRequest::Vote(r) => &r.context,
+                RPCRequest::InstallSnapshot(r) => &r.context,
+            };
+            if context.get(RPC_CONTEXT_KEY) != Some(RPC_CONTEXT_VALUE) {
+                missing.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(())
+        });
+    }
+
+    let extracted_before = EXTRACTED_RPC_CONTEXTS.load(Ordering::Relaxed);
+
+    tracing::info!("--- initialize cluster, elect and replicate");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let log_index = log_index + router.client_request_many(0, "foo", 3).await?;
+    for id in [0, 1, 2] {
+        router.wait(&id, timeout()).applied_index(Some(log_index), "replicated").await?;
+    }
+
+    tracing::info!("--- heartbeat carries the context too");
+    router.get_raft_handle(&0)?.trigger().heartbeat().await?;
+
+    assert_eq!(0, missing.load(Ordering::Relaxed), "every RPC carries the context");
+    assert!(
+        EXTRACTED_RPC_CONTEXTS.load(Ordering::Relaxed) > extracted_before,
+        "the receiver extracts the context"
+    );
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
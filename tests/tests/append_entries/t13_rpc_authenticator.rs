@@ -0,0 +1,111 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyerror::AnyError;
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::RaftError;
+use openraft::network::AuthRpc;
+use openraft::network::RpcAuthenticator;
+use openraft::raft::VoteRequest;
+use openraft::Config;
+use openraft::RPCTypes;
+use openraft::Vote;
+use openraft_memstore::TypeConfig;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Signs an RPC with its sender, and rejects the RPCs from a revoked node.
+#[derive(Default)]
+struct Auth {
+    revoked: Mutex<BTreeSet<u64>>,
+}
+
+impl RpcAuthenticator<TypeConfig> for Auth {
+    fn sign(&self, _target: u64, rpc: AuthRpc<'_, TypeConfig>) -> String {
+        format!("signed-by-{:?}", rpc.sender())
+    }
+
+    fn verify(&self, rpc: AuthRpc<'_, TypeConfig>, token: Option<&str>) -> Result<(), AnyError> {
+        let want = format!("signed-by-{:?}", rpc.sender());
+        if token != Some(want.as_str()) {
+            return Err(AnyError::error(format!("invalid token: {:?}", token)));
+        }
+
+        let sender = rpc.sender().unwrap_or_default();
+        if self.revoked.lock().unwrap().contains(&sender) {
+            return Err(AnyError::error(format!("node {} is revoked", sender)));
+        }
+        Ok(())
+    }
+}
+
+/// RPCs are signed by the sender and verified by the receiver: unsigned RPCs and RPCs from a
+/// revoked node are rejected.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn rpc_authenticator() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let auth = Arc::new(Auth::default());
+    router.set_authenticator(auth.clone());
+
+    tracing::info!("--- signed RPCs are accepted");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+    let log_index = log_index + router.client_request_many(0, "foo", 3).await?;
+    for id in [0, 1, 2] {
+        router.wait(&id, timeout()).applied_index(Some(log_index), "replicated").await?;
+    }
+
+    tracing::info!("--- an unsigned RPC is rejected");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.vote(VoteRequest::new(Vote::new(10, 2), None)).await;
+        match res {
+            Err(RaftError::APIError(e)) => assert_eq!(RPCTypes::Vote, e.rpc_type),
+            other => panic!("expect Unauthenticated, got: {:?}", other),
+        }
+    }
+
+    tracing::info!("--- the RPCs from a revoked node are rejected");
+    {
+        let leader_vote = router.get_metrics(&0)?.vote;
+
+        auth.revoked.lock().unwrap().insert(2);
+
+        let n2 = router.get_raft_handle(&2)?;
+        n2.trigger().elect().await?;
+        router
+            .wait(&2, timeout())
+            .metrics(|m| m.vote.leader_id().term > leader_vote.leader_id().term, "n2 elects")
+            .await?;
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        for id in [0, 1] {
+            let m = router.get_metrics(&id)?;
+            assert_eq!(
+                leader_vote, m.vote,
+                "node {} does not see the vote of the revoked node",
+                id
+            );
+        }
+        assert_eq!(Some(0), router.get_metrics(&0)?.current_leader);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -86,6 +86,45 @@ async fn heartbeat_reject_vote() -> Result<()> {
     Ok(())
 }
 
+/// Disabling `reject_vote_within_lease` at runtime lets a follower grant a vote within the leader
+/// lease.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn heartbeat_reject_vote_disabled_at_runtime() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 200,
+            election_timeout_min: 1000,
+            election_timeout_max: 1001,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let node1 = router.get_raft_handle(&1)?;
+
+    tracing::info!(log_index, "--- leader lease rejects vote request");
+    {
+        let res = node1.vote(VoteRequest::new(Vote::new(10, 2), Some(log_id(10, 1, 10)))).await?;
+        assert!(!res.vote_granted);
+    }
+
+    tracing::info!(
+        log_index,
+        "--- disable reject_vote_within_lease, vote request will be granted"
+    );
+    {
+        node1.runtime_config().reject_vote_within_lease(false).await?;
+
+        let res = node1.vote(VoteRequest::new(Vote::new(10, 2), Some(log_id(10, 1, 10)))).await?;
+        assert!(res.vote_granted, "vote is granted within the leader lease");
+    }
+
+    Ok(())
+}
+
 fn timeout() -> Option<Duration> {
     Some(Duration::from_millis(1_000))
 }
@@ -4,6 +4,7 @@ use std::time::Duration;
 use anyhow::Result;
 use maplit::btreeset;
 use openraft::Config;
+use openraft::LogRetentionPolicy;
 
 use crate::fixtures::init_default_ut_tracing;
 use crate::fixtures::RaftRouter;
@@ -24,7 +25,7 @@ async fn stale_last_log_id() -> Result<()> {
             election_timeout_min: 500,
             election_timeout_max: 1000,
             max_payload_entries: 1,
-            max_in_snapshot_log_to_keep: 0,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(0),
             purge_batch_size: 1,
             enable_heartbeat: false,
             ..Default::default()
@@ -7,6 +7,10 @@ mod fixtures;
 // The number indicate the preferred running order for these case.
 // See ./README.md
 
+mod t10_client_write_apply_error;
+mod t10_client_write_coalesce;
+mod t10_client_write_deadline;
+mod t10_client_write_forwarded;
 mod t10_client_writes;
 mod t11_client_reads;
 mod t12_trigger_purge_log;
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ApplyError;
+use openraft::error::ClientWriteError;
+use openraft::error::RaftError;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+use openraft_memstore::MemAppError;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A write rejected by the state machine returns the application error to the client, while the
+/// entry is still committed and applied on every node.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_apply_error() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let rejected = ClientRequest {
+        client: "foo".to_string(),
+        serial: 1,
+        status: "".to_string(),
+    };
+    let want_err = |index| ApplyError {
+        log_id: log_id(1, 0, index),
+        error: MemAppError::EmptyStatus {
+            client: "foo".to_string(),
+            serial: 1,
+        },
+    };
+
+    tracing::info!(log_index, "--- a rejected write returns the application error");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0.client_write(rejected.clone()).await;
+        log_index += 1;
+
+        match res {
+            Err(RaftError::APIError(ClientWriteError::ApplyError(e))) => assert_eq!(want_err(log_index), e),
+            other => panic!("expect ApplyError, got: {:?}", other),
+        }
+
+        for id in [0, 1, 2] {
+            router.wait(&id, timeout()).applied_index(Some(log_index), "rejected write is applied").await?;
+        }
+    }
+
+    tracing::info!(log_index, "--- a forwarded write returns the application error");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.client_write_forwarded(rejected.clone()).await;
+        log_index += 1;
+
+        match res {
+            Err(RaftError::APIError(ClientWriteError::ApplyError(e))) => assert_eq!(want_err(log_index), e),
+            other => panic!("expect ApplyError, got: {:?}", other),
+        }
+    }
+
+    tracing::info!(log_index, "--- the following writes are not affected");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let resp = n0.client_write(ClientRequest::make_request("foo", 2)).await?;
+        log_index += 1;
+
+        assert_eq!(log_index, resp.log_id.index);
+        assert_eq!(None, resp.data.0);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::metrics::WriteBatchMetrics;
+use openraft::Config;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Client writes are coalesced into one batch, which is appended when it is full or when the
+/// coalescing window expires.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_coalesce() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_tick: false,
+            // 300 ms
+            write_coalesce_window: 300_000,
+            write_coalesce_max_entries: 10,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- a full batch is appended at once");
+    {
+        let mut rxs = vec![];
+        for i in 0..10 {
+            rxs.push(n0.client_write_ff(ClientRequest::make_request("foo", i)).await?);
+        }
+
+        for (i, rx) in rxs.into_iter().enumerate() {
+            let resp = rx.await??;
+            assert_eq!(
+                log_index + 1 + i as u64,
+                resp.log_id.index,
+                "writes are appended in order"
+            );
+        }
+    }
+    let log_index = log_index + 10;
+
+    tracing::info!(log_index, "--- a partial batch is appended when the window expires");
+    {
+        let mut rxs = vec![];
+        for i in 10..13 {
+            rxs.push(n0.client_write_ff(ClientRequest::make_request("foo", i)).await?);
+        }
+
+        let n = router.wait(&0, timeout()).applied_index(Some(log_index), "batch is not appended yet").await?;
+        assert_eq!(Some(log_index), n.last_log_index);
+
+        for (i, rx) in rxs.into_iter().enumerate() {
+            let resp = rx.await??;
+            assert_eq!(log_index + 1 + i as u64, resp.log_id.index);
+        }
+    }
+    let log_index = log_index + 3;
+
+    router.wait_for_log(&btreeset![0, 1, 2], Some(log_index), None, "replicate batches").await?;
+
+    let metrics = router.get_metrics(&0)?;
+    assert_eq!(
+        WriteBatchMetrics {
+            batches: 2,
+            writes: 13,
+            last_batch_size: 3,
+            max_batch_size: 10,
+        },
+        metrics.write_batch
+    );
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
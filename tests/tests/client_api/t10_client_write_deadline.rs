@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ClientWriteError;
+use openraft::error::ClientWriteTimeout;
+use openraft::error::RaftError;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A client write that is not committed before its deadline fails with a timeout error.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_deadline() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- a write committed in time succeeds");
+    {
+        let deadline = Instant::now() + Duration::from_millis(1_000);
+        let resp = n0.client_write_with_deadline(ClientRequest::make_request("foo", 1), deadline).await?;
+        assert_eq!(log_id(1, 0, log_index + 1), resp.log_id);
+    }
+    let log_index = log_index + 1;
+
+    tracing::info!(log_index, "--- a write that can not be committed times out");
+    {
+        router.set_network_error(1, true);
+        router.set_network_error(2, true);
+
+        let now = Instant::now();
+        let deadline = now + Duration::from_millis(300);
+        let res = n0.client_write_with_deadline(ClientRequest::make_request("foo", 2), deadline).await;
+
+        assert!(Instant::now() >= deadline);
+        assert!(
+            Instant::now() < now + Duration::from_millis(1_000),
+            "resolved at the deadline"
+        );
+        // Either RaftCore or the caller gives up at the deadline, whichever is the first.
+        match res {
+            Err(RaftError::APIError(ClientWriteError::Timeout(ClientWriteTimeout { log_id: got }))) => {
+                if let Some(got) = got {
+                    assert_eq!(log_id(1, 0, log_index + 1), got);
+                }
+            }
+            _ => panic!("expect ClientWriteTimeout, got: {:?}", res.map(|_| ())),
+        }
+    }
+
+    Ok(())
+}
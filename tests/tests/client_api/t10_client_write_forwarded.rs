@@ -0,0 +1,69 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ClientWriteError;
+use openraft::error::RaftError;
+use openraft::Config;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A write submitted to a follower with `client_write_forwarded()` is forwarded to the leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_forwarded() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- a plain client_write on a follower is rejected");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.client_write(ClientRequest::make_request("foo", 1)).await;
+        match res {
+            Err(RaftError::APIError(ClientWriteError::ForwardToLeader(f))) => assert_eq!(Some(0), f.leader_id),
+            other => panic!("expect ForwardToLeader, got: {:?}", other),
+        }
+    }
+
+    tracing::info!(
+        log_index,
+        "--- client_write_forwarded on a follower is applied by the leader"
+    );
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let resp = n1.client_write_forwarded(ClientRequest::make_request("foo", 2)).await?;
+        log_index += 1;
+        assert_eq!(log_index, resp.log_id.index);
+
+        for id in [0, 1, 2] {
+            router.wait(&id, timeout()).applied_index(Some(log_index), "forwarded write applied").await?;
+        }
+    }
+
+    tracing::info!(log_index, "--- client_write_forwarded on the leader is written locally");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let resp = n0.client_write_forwarded(ClientRequest::make_request("foo", 3)).await?;
+        log_index += 1;
+        assert_eq!(log_index, resp.log_id.index);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
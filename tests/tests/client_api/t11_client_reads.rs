@@ -6,6 +6,7 @@ use anyhow::Result;
 use maplit::btreeset;
 use openraft::error::NetworkError;
 use openraft::error::RPCError;
+use openraft::raft::ReadPolicy;
 use openraft::Config;
 use openraft::LogIdOptionExt;
 use openraft::RPCTypes;
@@ -65,6 +66,202 @@ async fn client_reads() -> Result<()> {
     Ok(())
 }
 
+/// Concurrent read requests are coalesced into heartbeat rounds.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn read_index_batch() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- send 100 reads concurrently");
+    {
+        let mut handles = vec![];
+        for _ in 0..100 {
+            let n = n0.clone();
+            handles.push(tokio::spawn(async move { n.ensure_linearizable().await }));
+        }
+
+        for h in handles {
+            let read_log_id = h.await??;
+            assert_eq!(read_log_id.index(), Some(log_index));
+        }
+    }
+
+    tracing::info!(log_index, "--- reads are coalesced");
+    {
+        let m = n0.metrics().borrow().read_index;
+        assert_eq!(100, m.reads);
+        assert!(m.rounds < 100, "rounds: {}", m.rounds);
+        assert!(m.max_batch_size > 1, "max_batch_size: {}", m.max_batch_size);
+    }
+
+    Ok(())
+}
+
+/// A lease read is served by the leader without contacting a quorum, until the lease expires.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn lease_read() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            election_timeout_min: 500,
+            election_timeout_max: 501,
+            lease_read_clock_drift: 1,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    router.network_send_delay(0);
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(
+        log_index,
+        "--- write a log to renew the lease, then isolate the followers"
+    );
+    {
+        log_index += router.client_request_many(0, "foo", 1).await?;
+        router.wait(&0, timeout()).applied_index(Some(log_index), "write 1 log").await?;
+
+        router.set_network_error(1, true);
+        router.set_network_error(2, true);
+    }
+
+    tracing::info!(log_index, "--- lease read succeeds without a quorum, read index fails");
+    {
+        let read_log_id = n0.ensure_linearizable_with(ReadPolicy::LeaseRead).await?;
+        assert_eq!(read_log_id.index(), Some(log_index));
+
+        let res = n0.ensure_linearizable().await;
+        assert!(res.is_err(), "a quorum is unreachable");
+    }
+
+    tracing::info!(log_index, "--- lease read fails after the lease expires");
+    {
+        tokio::time::sleep(Duration::from_millis(600)).await;
+
+        let res = n0.ensure_linearizable_with(ReadPolicy::LeaseRead).await;
+        assert!(res.is_err(), "lease expired and a quorum is unreachable");
+    }
+
+    Ok(())
+}
+
+/// Without rejecting votes within the leader lease, a leader holds no lease, and a lease read
+/// falls back to a heartbeat round.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn lease_read_without_vote_lease() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            election_timeout_min: 500,
+            election_timeout_max: 501,
+            lease_read_clock_drift: 1,
+            reject_vote_within_lease: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    router.network_send_delay(0);
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- a lease read contacts a quorum");
+    {
+        log_index += router.client_request_many(0, "foo", 1).await?;
+        router.wait(&0, timeout()).applied_index(Some(log_index), "write 1 log").await?;
+
+        let read_log_id = n0.ensure_linearizable_with(ReadPolicy::LeaseRead).await?;
+        assert_eq!(read_log_id.index(), Some(log_index));
+        assert_eq!(1, n0.metrics().borrow().read_index.rounds);
+    }
+
+    tracing::info!(log_index, "--- a lease read fails without a quorum");
+    {
+        router.set_network_error(1, true);
+        router.set_network_error(2, true);
+
+        let res = n0.ensure_linearizable_with(ReadPolicy::LeaseRead).await;
+        assert!(res.is_err(), "no lease and a quorum is unreachable");
+    }
+
+    Ok(())
+}
+
+/// A follower or learner gets the read log id from the leader, and serves reads locally once it
+/// has applied up to it.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn follower_read() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    log_index += router.client_request_many(0, "foo", 5).await?;
+
+    for id in [1, 3] {
+        tracing::info!(log_index, "--- read on node {}", id);
+
+        let n = router.get_raft_handle(&id)?;
+        let read_log_id = n.ensure_linearizable_on_follower(ReadPolicy::ReadIndex).await?;
+        assert_eq!(read_log_id.index(), Some(log_index));
+
+        let applied = n.metrics().borrow().last_applied;
+        assert!(applied.index() >= Some(log_index));
+    }
+
+    tracing::info!(log_index, "--- the reads are served by the leader");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        assert_eq!(2, n0.metrics().borrow().read_index.reads);
+
+        let read_log_id = n0.ensure_linearizable_on_follower(ReadPolicy::ReadIndex).await?;
+        assert_eq!(read_log_id.index(), Some(log_index));
+    }
+
+    tracing::info!(log_index, "--- isolate the leader: node 1 can not read");
+    {
+        router.set_network_error(0, true);
+
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.ensure_linearizable_on_follower(ReadPolicy::ReadIndex).await;
+        assert!(res.is_err());
+    }
+
+    Ok(())
+}
+
 /// - A leader that has not yet committed any log entries returns leader initialization log id(blank
 ///   log id).
 /// - Return the last committed log id if the leader has committed any log entries.
@@ -4,6 +4,7 @@ use std::time::Duration;
 use maplit::btreeset;
 use openraft::testing::log_id;
 use openraft::Config;
+use openraft::LogRetentionPolicy;
 use openraft::SnapshotPolicy;
 
 use crate::fixtures::init_default_ut_tracing;
@@ -18,7 +19,7 @@ async fn trigger_purge_log() -> anyhow::Result<()> {
             // Disable building snapshot by policy.
             snapshot_policy: SnapshotPolicy::Never,
             // Disable auto purge by policy.
-            max_in_snapshot_log_to_keep: u64::MAX,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(u64::MAX),
             ..Default::default()
         }
         .validate()?,
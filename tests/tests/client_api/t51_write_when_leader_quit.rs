@@ -70,6 +70,7 @@ async fn write_when_leader_quit_and_log_revert() -> Result<()> {
 
                 entries: vec![],
                 leader_commit: None,
+                context: Default::default(),
             })
             .await?;
 
@@ -146,6 +147,7 @@ async fn write_when_leader_switched() -> Result<()> {
 
                 // Inform node-0 to commit the pending log.
                 leader_commit: Some(log_id(1, 0, log_index + 1)),
+                context: Default::default(),
             })
             .await?;
 
@@ -9,3 +9,13 @@ mod fixtures;
 
 mod t10_elect_compare_last_log;
 mod t11_elect_seize_leadership;
+mod t12_elect_vote_hedging;
+mod t13_elect_pre_vote;
+mod t14_elect_check_quorum;
+mod t15_elect_transfer_leader;
+mod t16_elect_preferred_zone;
+mod t16_elect_priority;
+mod t17_elect_leader_lease;
+mod t18_elect_adaptive_timeout;
+mod t19_elect_step_down;
+mod t20_elect_without_blank_entry;
@@ -0,0 +1,66 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::NetworkError;
+use openraft::error::RPCError;
+use openraft::network::RPCTypes;
+use openraft::AnyError;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With vote hedging, a candidate sends a vote request again to a node that does not respond,
+/// instead of waiting for the next election.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_vote_hedging() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            election_timeout_min: 1_000,
+            election_timeout_max: 1_001,
+            vote_hedge_delay: 50,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- wait for the leader lease to expire");
+    tokio::time::sleep(Duration::from_millis(1_200)).await;
+
+    tracing::info!(log_index, "--- lose the first vote request to every node");
+    {
+        let lost = Arc::new(Mutex::new(BTreeSet::new()));
+        router.set_rpc_pre_hook(RPCTypes::Vote, move |_router, _req, _from, to| {
+            if lost.lock().unwrap().insert(to) {
+                let any_err = AnyError::error("lost vote request");
+                Err(RPCError::Network(NetworkError::new(&any_err)))
+            } else {
+                Ok(())
+            }
+        });
+    }
+
+    tracing::info!(log_index, "--- node 1 is elected before the vote timeout");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        n1.trigger().elect().await?;
+
+        n1.wait(Some(Duration::from_millis(500)))
+            .state(ServerState::Leader, "node 1 becomes leader with hedged vote requests")
+            .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With pre-vote enabled, a partitioned node does not increase its term, and it does not disturb
+/// the leader when it rejoins the cluster.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_pre_vote() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_pre_vote: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- isolate node 2 for several election timeouts");
+    {
+        router.set_network_error(2, true);
+        tokio::time::sleep(Duration::from_millis(2_000)).await;
+
+        let n2 = router.get_raft_handle(&2)?;
+        let metrics = n2.metrics().borrow().clone();
+        assert_eq!(1, metrics.current_term, "pre-vote does not increase the term");
+        assert_eq!(ServerState::Follower, metrics.state);
+    }
+
+    tracing::info!(log_index, "--- node 2 rejoins, leader 0 is not disturbed");
+    {
+        router.set_network_error(2, false);
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        for id in [0, 1, 2] {
+            let metrics = router.get_raft_handle(&id)?.metrics().borrow().clone();
+            assert_eq!(1, metrics.current_term, "node {} term does not change", id);
+            assert_eq!(Some(0), metrics.current_leader, "node {} still sees leader 0", id);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ClientWriteError;
+use openraft::Config;
+use openraft::ServerState;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With check-quorum enabled, a leader that is partitioned from the other nodes steps down, and
+/// stops accepting writes.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_check_quorum() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_check_quorum: true,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- leader keeps leadership while a quorum acknowledges it");
+    {
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+        assert_eq!(ServerState::Leader, n0.metrics().borrow().state);
+    }
+
+    tracing::info!(log_index, "--- isolate leader 0, it steps down");
+    {
+        router.set_network_error(0, true);
+
+        n0.wait(timeout()).metrics(|m| m.state != ServerState::Leader, "leader 0 steps down").await?;
+
+        let res = router.send_client_request(0, ClientRequest::make_request("foo", 1)).await;
+        match res {
+            Ok(_) => {
+                unreachable!("a leader that stepped down does not accept writes");
+            }
+            Err(cli_err) => match cli_err.api_error().unwrap() {
+                ClientWriteError::ForwardToLeader(fwd) => {
+                    assert!(fwd.leader_id.is_none());
+                }
+                _ => {
+                    unreachable!("expect ForwardToLeader");
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::TransferLeaderError;
+use openraft::Config;
+use openraft::ServerState;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader hands over its leadership to a voter, before the leader lease expires.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_transfer_leader() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2 and learner 3");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let n1 = router.get_raft_handle(&1)?;
+
+    tracing::info!(log_index, "--- transfer to a learner: rejected");
+    {
+        let err = n0.transfer_leadership(3).await.unwrap_err();
+        assert!(matches!(err.api_error(), Some(TransferLeaderError::NotVoter(_))));
+    }
+
+    tracing::info!(log_index, "--- transfer by a follower: rejected");
+    {
+        let err = n1.transfer_leadership(2).await.unwrap_err();
+        match err.api_error() {
+            Some(TransferLeaderError::ForwardToLeader(fwd)) => {
+                assert_eq!(Some(0), fwd.leader_id);
+            }
+            _ => {
+                unreachable!("expect ForwardToLeader");
+            }
+        }
+    }
+
+    tracing::info!(log_index, "--- transfer from 0 to 1");
+    {
+        n0.transfer_leadership(1).await?;
+
+        n1.wait(timeout()).state(ServerState::Leader, "node 1 becomes leader").await?;
+        n0.wait(timeout()).state(ServerState::Follower, "node 0 becomes follower").await?;
+
+        assert_eq!(Vote::new_committed(2, 1), n1.metrics().borrow().vote);
+    }
+
+    tracing::info!(log_index, "--- the new leader accepts writes");
+    {
+        router.client_request_many(1, "foo", 1).await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
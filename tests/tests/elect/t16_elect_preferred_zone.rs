@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use openraft::metrics::ZoneHealth;
+use openraft::ChangeMembers;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader hands over its leadership to a voter in the preferred leader zone, and reports the
+/// health of every zone.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_preferred_zone() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            preferred_leader_zone: "b".to_string(),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let n2 = router.get_raft_handle(&2)?;
+
+    tracing::info!(log_index, "--- node 2 is in the preferred zone: it becomes the leader");
+    {
+        let zones = btreemap! {0=>s("a"), 1=>s("a"), 2=>s("b")};
+        n0.change_membership(ChangeMembers::SetPriorities(btreemap! {0=>10}), true).await?;
+        n0.change_membership(ChangeMembers::SetZones(zones), true).await?;
+
+        n2.wait(timeout()).state(ServerState::Leader, "node 2 becomes leader").await?;
+        n0.wait(timeout()).state(ServerState::Follower, "node 0 becomes follower").await?;
+    }
+
+    tracing::info!(log_index, "--- the leader reports the health of every zone");
+    {
+        let want = btreemap! {
+            s("a") => ZoneHealth { voters: 2, learners: 0, alive: 2, critical: true },
+            s("b") => ZoneHealth { voters: 1, learners: 0, alive: 1, critical: false },
+        };
+        n2.wait(timeout()).metrics(|m| m.zones.as_ref() == Some(&want), "zone health reported").await?;
+    }
+
+    tracing::info!(log_index, "--- node 2 keeps the leadership");
+    {
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+        assert_eq!(ServerState::Leader, n2.metrics().borrow().state);
+    }
+
+    Ok(())
+}
+
+fn s(x: &str) -> String {
+    x.to_string()
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use openraft::ChangeMembers;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader hands over its leadership to the voter with a higher election priority.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_priority() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let n2 = router.get_raft_handle(&2)?;
+
+    tracing::info!(log_index, "--- a lower priority than the leader's: leadership is kept");
+    {
+        n0.change_membership(ChangeMembers::SetPriorities(btreemap! {0=>10, 2=>5}), true).await?;
+
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+        assert_eq!(ServerState::Leader, n0.metrics().borrow().state);
+    }
+
+    tracing::info!(log_index, "--- node 2 has the highest priority: it becomes the leader");
+    {
+        n0.change_membership(ChangeMembers::SetPriorities(btreemap! {2=>20}), true).await?;
+
+        n2.wait(timeout()).state(ServerState::Leader, "node 2 becomes leader").await?;
+        n0.wait(timeout()).state(ServerState::Follower, "node 0 becomes follower").await?;
+
+        let m = n2.metrics().borrow().membership_config.clone();
+        assert_eq!(20, m.membership().election_priority(&2));
+    }
+
+    tracing::info!(log_index, "--- node 2 keeps the leadership");
+    {
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+        assert_eq!(ServerState::Leader, n2.metrics().borrow().state);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
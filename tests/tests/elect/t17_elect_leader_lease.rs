@@ -0,0 +1,105 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::VoteRequest;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::ServerState;
+use openraft::Vote;
+use tokio::time::sleep;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A follower rejects vote requests within the configured leader lease since it received the
+/// last heartbeat, even if it is longer than the election timeout.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_leader_lease_rejects_vote() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            leader_lease: 1_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let n1 = router.get_raft_handle(&1)?;
+
+    tracing::info!(
+        log_index,
+        "--- stop heartbeat, the lease rejects a vote after election_timeout_max"
+    );
+    {
+        n0.runtime_config().heartbeat(false);
+        sleep(Duration::from_millis(500)).await;
+
+        let res = n1.vote(VoteRequest::new(Vote::new(10, 2), Some(log_id(10, 1, 10)))).await?;
+        assert!(!res.vote_granted, "vote is rejected within the leader lease");
+    }
+
+    tracing::info!(log_index, "--- the vote is granted after the lease expires");
+    {
+        sleep(Duration::from_millis(800)).await;
+
+        let res = n1.vote(VoteRequest::new(Vote::new(10, 2), Some(log_id(10, 1, 10)))).await?;
+        assert!(res.vote_granted, "vote is granted after the leader lease expired");
+    }
+
+    Ok(())
+}
+
+/// Followers that lose the leader wait for the leader lease, the election timeout and a random
+/// backoff before electing a new leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_leader_lease_election_backoff() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            leader_lease: 1_000,
+            election_backoff_max: 500,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n1 = router.get_raft_handle(&1)?;
+    let n2 = router.get_raft_handle(&2)?;
+
+    tracing::info!(log_index, "--- isolate leader 0, no election within the lease");
+    {
+        router.set_network_error(0, true);
+        sleep(Duration::from_millis(900)).await;
+
+        assert_eq!(ServerState::Follower, n1.metrics().borrow().state);
+        assert_eq!(ServerState::Follower, n2.metrics().borrow().state);
+    }
+
+    tracing::info!(log_index, "--- a new leader is elected after the lease and the backoff");
+    {
+        n1.wait(timeout())
+            .metrics(
+                |m| m.current_leader.is_some_and(|l| l != 0) && m.vote.is_committed(),
+                "node 1 sees a new leader",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With adaptive election timeout enabled, the leader and the followers tune their election
+/// timeout to the RTT of the RPCs in the cluster.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_adaptive_timeout() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 20,
+            election_timeout_min: 50,
+            election_timeout_max: 5_000,
+            adaptive_election_timeout: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- the election timeout is tuned down to the minimal range");
+    {
+        for id in [0, 1, 2] {
+            router
+                .wait(&id, Some(Duration::from_millis(2_000)))
+                .metrics(|m| m.election_timeout_ms <= 100, "tuned to [50, 100]")
+                .await?;
+        }
+    }
+
+    tracing::info!(log_index, "--- a slow network increases the election timeout");
+    {
+        router.network_send_delay(40);
+
+        for id in [0, 1, 2] {
+            router
+                .wait(&id, Some(Duration::from_millis(5_000)))
+                .metrics(
+                    |m| m.election_timeout_ms > 100 && m.election_timeout_ms <= 1_000,
+                    "tuned to RTT",
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ClientWriteError;
+use openraft::error::RaftError;
+use openraft::error::TransferLeaderError;
+use openraft::Config;
+use openraft::ServerState;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Step down with a leadership transfer.
+///
+/// - A follower refuses to step down.
+/// - The leader hands over the leadership to another voter without election timeout.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn step_down_with_transfer() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- a follower can not step down");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.step_down(true).await;
+        match res {
+            Err(RaftError::APIError(TransferLeaderError::ForwardToLeader(f))) => {
+                assert_eq!(Some(0), f.leader_id);
+            }
+            _ => panic!("expect ForwardToLeader, got: {:?}", res),
+        }
+    }
+
+    tracing::info!(log_index, "--- leader steps down and hands over the leadership");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.step_down(true).await?;
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.state == ServerState::Follower && m.current_leader.is_some_and(|l| l != 0),
+                "old leader follows a new leader",
+            )
+            .await?;
+    }
+
+    tracing::info!(log_index, "--- the new leader serves writes");
+    {
+        let leader = router.get_raft_handle(&0)?.current_leader().await.unwrap();
+        router.client_request_many(leader, "foo", 1).await?;
+    }
+
+    Ok(())
+}
+
+/// Step down without a leadership transfer.
+///
+/// - The leader stops accepting writes and does not elect itself again at once.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn step_down_without_transfer() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- leader steps down");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.step_down(false).await?;
+
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.current_leader.is_none(), "old leader is not a leader")
+            .await?;
+
+        let res = n0.client_write(ClientRequest::make_request("foo", 1)).await;
+        match res {
+            Err(RaftError::APIError(ClientWriteError::ForwardToLeader(_))) => {}
+            _ => panic!("expect ForwardToLeader, got: {:?}", res),
+        }
+    }
+
+    tracing::info!(log_index, "--- it does not elect itself within an election timeout");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.runtime_config().elect(true);
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max + 100)).await;
+
+        let m = n0.metrics().borrow().clone();
+        assert_ne!(ServerState::Leader, m.state);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
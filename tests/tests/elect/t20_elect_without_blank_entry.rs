@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `leader_blank_entry` disabled, a new leader does not append a blank entry, unless the
+/// last membership config is not committed, or a linearizable read has to wait for it.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_without_blank_entry() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            leader_blank_entry: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_raft_node(0).await;
+    router.new_raft_node(1).await;
+    router.new_raft_node(2).await;
+
+    let mut log_index = 0;
+
+    tracing::info!(log_index, "--- initialize cluster and elect a leader");
+    {
+        router.initialize(0).await?;
+        router.wait(&0, timeout()).state(ServerState::Leader, "node 0 becomes leader").await?;
+    }
+
+    tracing::info!(
+        log_index,
+        "--- a blank entry is appended to commit the membership config"
+    );
+    {
+        log_index += 1;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "blank entry").await?;
+    }
+
+    tracing::info!(log_index, "--- transfer the leadership to node 1");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.transfer_leadership(1).await?;
+        router.wait(&1, timeout()).state(ServerState::Leader, "node 1 becomes leader").await?;
+    }
+
+    let n1 = router.get_raft_handle(&1)?;
+
+    tracing::info!(log_index, "--- no blank entry is appended by node 1");
+    {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(Some(log_index), router.get_metrics(&1)?.last_log_index);
+    }
+
+    tracing::info!(log_index, "--- a linearizable read appends a blank entry");
+    {
+        let read_log_id = n1.ensure_linearizable().await?;
+        log_index += 1;
+        assert_eq!(Some(log_index), read_log_id.map(|x| x.index));
+    }
+
+    tracing::info!(log_index, "--- the first write is proposed after the blank entry");
+    {
+        let resp = n1.client_write(ClientRequest::make_request("foo", 1)).await?;
+        log_index += 1;
+        assert_eq!(log_index, resp.log_id.index);
+
+        router
+            .wait_for_log(
+                &btreeset! {0,1,2},
+                Some(log_index),
+                timeout(),
+                "all nodes apply the write",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -30,16 +30,26 @@ use openraft::error::PayloadTooLarge;
 use openraft::error::RPCError;
 use openraft::error::RaftError;
 use openraft::error::RemoteError;
+use openraft::error::TransferSnapshotError;
 use openraft::error::Unreachable;
 use openraft::metrics::Wait;
+use openraft::network::NetworkObserver;
+use openraft::network::RPCErrorKind;
+use openraft::network::RPCEvent;
 use openraft::network::RPCOption;
 use openraft::network::RaftNetwork;
 use openraft::network::RaftNetworkFactory;
+use openraft::network::RpcAuthenticator;
+use openraft::network::RpcContext;
 use openraft::raft::AppendEntriesRequest;
 use openraft::raft::AppendEntriesResponse;
 use openraft::raft::ClientWriteResponse;
 use openraft::raft::InstallSnapshotRequest;
 use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::ReadPolicy;
+use openraft::raft::TransferLeaderRequest;
+use openraft::raft::TransferSnapshotRequest;
+use openraft::raft::TransferSnapshotResponse;
 use openraft::raft::VoteRequest;
 use openraft::raft::VoteResponse;
 use openraft::storage::RaftLogStorage;
@@ -212,6 +222,16 @@ impl<C: RaftTypeConfig> RPCRequest<C> {
     }
 }
 
+/// The key and value [`TypedRaftRouter`] injects into the context of every RPC.
+pub const RPC_CONTEXT_KEY: &str = "sent-by";
+pub const RPC_CONTEXT_VALUE: &str = "TypedRaftRouter";
+
+/// The number of received RPCs whose context injected by [`TypedRaftRouter`] is extracted.
+pub static EXTRACTED_RPC_CONTEXTS: AtomicU64 = AtomicU64::new(0);
+
+/// An RPC reported to the `NetworkObserver`: `(target, rpc_type, error)`
+pub type ObservedRpc = (MemNodeId, RPCTypes, Option<RPCErrorKind>);
+
 /// Arguments: `(router, rpc, from_id, to_id)`
 pub type RPCPreHook =
     Box<dyn Fn(&TypedRaftRouter, RPCRequest<TypeConfig>, MemNodeId, MemNodeId) -> PreHookResult + Send + 'static>;
@@ -243,8 +263,17 @@ pub struct TypedRaftRouter {
     /// Count of RPCs sent.
     rpc_count: Arc<Mutex<HashMap<RPCTypes, u64>>>,
 
+    /// Count of RPCs reported to the `NetworkObserver`, by target, RPC type and error.
+    observed_rpcs: Arc<Mutex<HashMap<ObservedRpc, u64>>>,
+
     /// A hook function to be called when before an RPC is sent to target node.
     rpc_pre_hook: Arc<Mutex<HashMap<RPCTypes, RPCPreHook>>>,
+
+    /// The `RpcAuthenticator` of the nodes created after it is set.
+    authenticator: Arc<Mutex<Option<Arc<dyn RpcAuthenticator<MemConfig>>>>>,
+
+    /// The `(source, target)` of every snapshot sent by a follower on behalf of the leader.
+    transferred_snapshots: Arc<Mutex<Vec<(MemNodeId, MemNodeId)>>>,
 }
 
 /// Default `RaftRouter` for memstore.
@@ -279,7 +308,10 @@ impl Builder {
             send_delay: Arc::new(AtomicU64::new(send_delay)),
             append_entries_quota: Arc::new(Mutex::new(None)),
             rpc_count: Default::default(),
+            observed_rpcs: Default::default(),
             rpc_pre_hook: Default::default(),
+            authenticator: Default::default(),
+            transferred_snapshots: Default::default(),
         }
     }
 }
@@ -325,6 +357,21 @@ impl TypedRaftRouter {
         self.rpc_count.lock().unwrap().clone()
     }
 
+    /// Get the `(source, target)` of every snapshot sent by a follower on behalf of the leader.
+    pub fn get_transferred_snapshots(&self) -> Vec<(MemNodeId, MemNodeId)> {
+        self.transferred_snapshots.lock().unwrap().clone()
+    }
+
+    /// Set the `RpcAuthenticator` of the nodes created after this call.
+    pub fn set_authenticator(&self, authenticator: Arc<dyn RpcAuthenticator<MemConfig>>) {
+        *self.authenticator.lock().unwrap() = Some(authenticator);
+    }
+
+    /// Get the count of RPCs reported to the `NetworkObserver`, by target, RPC type and error.
+    pub fn get_observed_rpcs(&self) -> HashMap<ObservedRpc, u64> {
+        self.observed_rpcs.lock().unwrap().clone()
+    }
+
     /// Create a cluster: 0 is the initial leader, others are voters and learners
     ///
     /// NOTE: it create a single node cluster first, then change it to a multi-voter cluster.
@@ -968,6 +1015,27 @@ impl RaftNetworkFactory<MemConfig> for TypedRaftRouter {
             owner: self.clone(),
         }
     }
+
+    fn inject_context(context: &mut RpcContext) {
+        context.set(RPC_CONTEXT_KEY, RPC_CONTEXT_VALUE);
+    }
+
+    fn extract_context(context: &RpcContext, _span: &tracing::Span) {
+        if context.get(RPC_CONTEXT_KEY) == Some(RPC_CONTEXT_VALUE) {
+            EXTRACTED_RPC_CONTEXTS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn authenticator(&self) -> Option<Arc<dyn RpcAuthenticator<MemConfig>>> {
+        self.authenticator.lock().unwrap().clone()
+    }
+}
+
+impl NetworkObserver<MemConfig> for TypedRaftRouter {
+    fn on_rpc(&self, event: &RPCEvent<MemConfig>) {
+        let mut observed = self.observed_rpcs.lock().unwrap();
+        *observed.entry((event.target, event.rpc_type, event.error)).or_default() += 1;
+    }
 }
 
 pub struct RaftRouterNetwork {
@@ -1082,6 +1150,84 @@ impl RaftNetwork<MemConfig> for RaftRouterNetwork {
 
         Ok(resp)
     }
+
+    async fn forward_client_write(
+        &mut self,
+        app_data: ClientRequest,
+        _option: RPCOption,
+    ) -> Result<ClientWriteResponse<MemConfig>, RPCError<MemConfig, RaftError<MemConfig, ClientWriteError<MemConfig>>>>
+    {
+        self.owner.rand_send_delay().await;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let resp = node.client_write(app_data).await;
+        let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
+
+        Ok(resp)
+    }
+
+    async fn get_read_log_id(
+        &mut self,
+        read_policy: ReadPolicy,
+        _option: RPCOption,
+    ) -> Result<Option<LogId<MemNodeId>>, RPCError<MemConfig, RaftError<MemConfig, CheckIsLeaderError<MemConfig>>>>
+    {
+        self.owner.rand_send_delay().await;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let resp = node.get_read_log_id_with(read_policy).await;
+        let (read_log_id, _applied) = resp.map_err(|e| RemoteError::new(self.target, e))?;
+
+        Ok(read_log_id)
+    }
+
+    async fn transfer_snapshot(
+        &mut self,
+        rpc: TransferSnapshotRequest<MemConfig>,
+        _option: RPCOption,
+    ) -> Result<
+        TransferSnapshotResponse<MemConfig>,
+        RPCError<MemConfig, RaftError<MemConfig, TransferSnapshotError<MemConfig>>>,
+    > {
+        let from_id = rpc.vote.leader_id().voted_for().unwrap();
+
+        self.owner.emit_rpc_error(from_id, self.target)?;
+        self.owner.rand_send_delay().await;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let target = rpc.target;
+        let resp = node.transfer_snapshot(rpc).await;
+        let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
+
+        self.owner.transferred_snapshots.lock().unwrap().push((self.target, target));
+
+        Ok(resp)
+    }
+
+    async fn transfer_leader(
+        &mut self,
+        rpc: TransferLeaderRequest<MemConfig>,
+        _option: RPCOption,
+    ) -> Result<(), RPCError<MemConfig, RaftError<MemConfig>>> {
+        let from_id = rpc.from_leader.leader_id().voted_for().unwrap();
+
+        self.owner.emit_rpc_error(from_id, self.target)?;
+        self.owner.rand_send_delay().await;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let resp = node.handle_transfer_leader(rpc).await;
+        resp.map_err(|e| RemoteError::new(self.target, e))?;
+
+        Ok(())
+    }
+
+    fn observer(&self) -> Option<Arc<dyn NetworkObserver<MemConfig>>> {
+        Some(Arc::new(self.owner.clone()))
+    }
 }
 
 pub enum ValueTest<T> {
@@ -9,6 +9,10 @@ mod fixtures;
 
 mod t10_initialization;
 mod t11_shutdown;
+mod t12_new_on_runtime;
+mod t13_error_policy;
+mod t14_tick_driven;
+mod t15_bootstrap;
 mod t50_follower_restart_does_not_interrupt;
 mod t50_single_follower_restart;
 mod t50_single_leader_restart_re_apply_logs;
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::Raft;
+use openraft_memstore::ClientRequest;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A Raft node created with `Raft::new_on()` runs its tasks on the given runtime.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn new_on_runtime() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let raft_rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("dedicated-raft-rt")
+        .enable_all()
+        .build()?;
+
+    tracing::info!("--- create a node on the dedicated runtime");
+    let (log_store, sm) = router.new_store();
+    let node = Raft::new_on(raft_rt.handle(), 0, config.clone(), router.clone(), log_store, sm).await?;
+
+    node.initialize(btreeset! {0}).await?;
+    node.wait(timeout()).state(openraft::ServerState::Leader, "become leader").await?;
+
+    node.client_write(ClientRequest {
+        client: "foo".to_string(),
+        serial: 0,
+        status: "bar".to_string(),
+    })
+    .await?;
+
+    tracing::info!("--- RaftCore runs on the dedicated runtime");
+    {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        node.external_request(move |_st| {
+            let _ = tx.send(std::thread::current().name().map(|x| x.to_string()));
+        });
+        let thread_name = rx.await?;
+        assert_eq!(Some("dedicated-raft-rt".to_string()), thread_name);
+    }
+
+    node.shutdown().await?;
+    raft_rt.shutdown_background();
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::Fatal;
+use openraft::Config;
+use openraft::ErrorPolicy;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `ErrorPolicy::StepDownAndRetry`, a node restarts from storage after a state machine error,
+/// and becomes the leader again.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn error_policy_step_down_and_retry() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            error_policy: ErrorPolicy::StepDownAndRetry {
+                max_retries: 3,
+                min: 10,
+                max: 100,
+            },
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- fail applying the next log");
+    {
+        let (_log_store, sm) = router.get_storage_handle(&0)?;
+        sm.set_apply_failures(1);
+
+        let res = router.client_request(0, "foo", 1).await;
+        assert!(res.is_err(), "the write fails when the state machine fails");
+    }
+
+    tracing::info!(log_index, "--- the node restarts and applies the log again");
+    {
+        log_index += 1;
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| {
+                    m.running_state.is_ok()
+                        && m.state == ServerState::Leader
+                        && m.last_applied.map(|x| x.index) >= Some(log_index)
+                },
+                "restarted as leader",
+            )
+            .await?;
+    }
+
+    tracing::info!(log_index, "--- the node serves writes after restart");
+    {
+        router.client_request(0, "foo", 2).await?;
+        log_index += 1;
+
+        router.wait(&0, timeout()).applied_index(Some(log_index), "write after restart").await?;
+
+        let (_log_store, sm) = router.get_storage_handle(&0)?;
+        let state = sm.get_state_machine().await;
+        // The response of serial 2 is the value written by serial 1, which is applied at restart.
+        assert_eq!(
+            Some(&(2, Some("request-1".to_string()))),
+            state.client_serial_responses.get("foo")
+        );
+    }
+
+    Ok(())
+}
+
+/// With `ErrorPolicy::StepDownAndRetry`, a node shuts down if it keeps failing to restart.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn error_policy_retries_used_up() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            error_policy: ErrorPolicy::StepDownAndRetry {
+                max_retries: 3,
+                min: 10,
+                max: 100,
+            },
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!(
+        log_index,
+        "--- fail applying logs, including the re-applying at restart"
+    );
+    {
+        let (_log_store, sm) = router.get_storage_handle(&0)?;
+        sm.set_apply_failures(100);
+
+        let res = router.client_request(0, "foo", 1).await;
+        assert!(res.is_err());
+    }
+
+    tracing::info!(log_index, "--- the node shuts down with the storage error");
+    {
+        let m = router
+            .wait(&0, timeout())
+            .metrics(|m| m.state == ServerState::Shutdown, "shutdown after retries")
+            .await?;
+
+        let err = m.running_state.unwrap_err();
+        assert!(matches!(err, Fatal::StorageError(_)), "got: {:?}", err);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,177 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::AppendEntriesRequest;
+use openraft::raft::AppendEntriesResponse;
+use openraft::raft::StepRequest;
+use openraft::raft::StepResponse;
+use openraft::raft::VoteRequest;
+use openraft::Config;
+use openraft::RPCTypes;
+use openraft::ServerState;
+use openraft::Vote;
+use tokio::time::sleep;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `enable_tick=false`, timers are driven only by `Raft::tick()`.
+///
+/// - The followers do not elect a new leader after the leader is lost, until they are ticked.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn tick_driven_election() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n1 = router.get_raft_handle(&1)?;
+    let n2 = router.get_raft_handle(&2)?;
+
+    tracing::info!(log_index, "--- lose leader 0, no election without tick");
+    {
+        router.set_network_error(0, true);
+        sleep(Duration::from_millis(
+            config.leader_lease + config.election_timeout_max + 200,
+        ))
+        .await;
+
+        assert_eq!(ServerState::Follower, n1.metrics().borrow().state);
+        assert_eq!(ServerState::Follower, n2.metrics().borrow().state);
+    }
+
+    tracing::info!(log_index, "--- tick the followers, a new leader is elected");
+    {
+        for _ in 0..40 {
+            n1.tick().await?;
+            n2.tick().await?;
+
+            if n1.metrics().borrow().current_leader.is_some_and(|l| l != 0) {
+                break;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+
+        n1.wait(timeout())
+            .metrics(
+                |m| m.current_leader.is_some_and(|l| l != 0),
+                "a new leader is elected by tick",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// With `enable_tick=false`, openraft spawns no timer: no election and no heartbeat happens until
+/// `Raft::tick()` is called.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn no_timer_without_tick() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    // Let the RPCs sent for setting up the cluster finish.
+    sleep(Duration::from_millis(200)).await;
+
+    tracing::info!(log_index, "--- no RPC is sent without tick");
+    let before = router.get_rpc_count();
+    let vote_before = router.get_metrics(&0)?.vote;
+    {
+        sleep(Duration::from_millis(
+            config.heartbeat_interval * 5 + config.election_timeout_max,
+        ))
+        .await;
+
+        assert_eq!(before, router.get_rpc_count());
+
+        for id in [0, 1, 2] {
+            let m = router.get_metrics(&id)?;
+            assert_eq!(vote_before, m.vote, "node {} does not elect", id);
+            assert_eq!(Some(0), m.current_leader, "node {} keeps the leader", id);
+        }
+    }
+
+    tracing::info!(log_index, "--- tick the leader, it sends heartbeats");
+    {
+        n0.tick().await?;
+
+        let appended = |rpc_count: &std::collections::HashMap<RPCTypes, u64>| {
+            rpc_count.get(&RPCTypes::AppendEntries).copied().unwrap_or_default()
+        };
+
+        for _ in 0..20 {
+            if appended(&router.get_rpc_count()) > appended(&before) {
+                break;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+        assert!(appended(&router.get_rpc_count()) > appended(&before));
+    }
+
+    Ok(())
+}
+
+/// `Raft::step()` handles the RPCs received from other nodes.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn step_rpc() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n1 = router.get_raft_handle(&1)?;
+    let m0 = router.get_metrics(&0)?;
+
+    tracing::info!(log_index, "--- step a heartbeat from the leader");
+    {
+        let rpc = AppendEntriesRequest {
+            vote: m0.vote,
+            prev_log_id: m0.last_applied,
+            entries: vec![],
+            leader_commit: m0.last_applied,
+            context: Default::default(),
+        };
+
+        let resp = n1.step(StepRequest::AppendEntries(rpc)).await?;
+        assert_eq!(StepResponse::AppendEntries(AppendEntriesResponse::Success), resp);
+    }
+
+    tracing::info!(log_index, "--- step a stale vote request");
+    {
+        let resp = n1.step(StepRequest::Vote(VoteRequest::new(Vote::new(0, 2), None))).await?;
+        match resp {
+            StepResponse::Vote(v) => assert!(!v.vote_granted),
+            _ => panic!("expect Vote response, got: {}", resp),
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
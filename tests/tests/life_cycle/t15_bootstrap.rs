@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use openraft::bootstrap::Bootstrap;
+use openraft::bootstrap::BootstrapOutcome;
+use openraft::error::BootstrapError;
+use openraft::AnyError;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Every node bootstraps with the same static seeds: the one with the smallest id initializes the
+/// cluster and the others join it.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn bootstrap_static_seeds() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    for id in [0, 1, 2] {
+        router.new_raft_node(id).await;
+    }
+
+    tracing::info!("--- bootstrap every node");
+    let seeds = btreemap! {0 => (), 1 => (), 2 => ()};
+
+    let mut handles = vec![];
+    for id in [2, 1, 0] {
+        let n = router.get_raft_handle(&id)?;
+        let b = Bootstrap::new(n, seeds.clone()).timeout(timeout());
+        handles.push((id, tokio::spawn(b.run())));
+    }
+
+    let mut outcomes = BTreeMap::new();
+    for (id, h) in handles {
+        outcomes.insert(id, h.await??);
+    }
+
+    assert_eq!(
+        btreemap! {
+            0 => BootstrapOutcome::Initialized,
+            1 => BootstrapOutcome::Joined,
+            2 => BootstrapOutcome::Joined,
+        },
+        outcomes
+    );
+
+    let n0 = router.get_raft_handle(&0)?;
+    n0.wait(Some(timeout())).current_leader(0, "node 0 is leader").await?;
+
+    let m = n0.metrics().borrow().clone();
+    assert_eq!(
+        btreeset! {0,1,2},
+        m.membership_config.membership().voter_ids().collect()
+    );
+
+    tracing::info!("--- bootstrap again after the cluster is formed");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let outcome = Bootstrap::new(n1, seeds.clone()).run().await?;
+        assert_eq!(BootstrapOutcome::AlreadyInitialized, outcome);
+    }
+
+    Ok(())
+}
+
+/// The members are found one by one by a discovery callback: no node is initialized until all of
+/// them are found.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn bootstrap_discovery() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+    let mut router = RaftRouter::new(config.clone());
+
+    for id in [0, 1] {
+        router.new_raft_node(id).await;
+    }
+
+    // Every call finds one more member.
+    let found = Arc::new(AtomicU64::new(0));
+    let discover = move || {
+        let n = found.fetch_add(1, Ordering::Relaxed) + 1;
+        async move { Ok::<_, AnyError>((0..n.min(2)).map(|id| (id, ())).collect::<BTreeMap<_, _>>()) }
+    };
+
+    let n1 = router.get_raft_handle(&1)?;
+    let h1 =
+        tokio::spawn(Bootstrap::new(n1, btreemap! {0 => (), 1 => ()}).expected_members(2).timeout(timeout()).run());
+
+    let n0 = router.get_raft_handle(&0)?;
+    let outcome = Bootstrap::new(n0.clone(), discover)
+        .expected_members(2)
+        .retry_interval(Duration::from_millis(50))
+        .timeout(timeout())
+        .run()
+        .await?;
+    assert_eq!(BootstrapOutcome::Initialized, outcome);
+    assert_eq!(BootstrapOutcome::Joined, h1.await??);
+
+    tracing::info!("--- a discovery that finds too few members times out");
+    {
+        router.new_raft_node(5).await;
+        let n5 = router.get_raft_handle(&5)?;
+
+        let err = Bootstrap::new(n5, btreemap! {5 => ()})
+            .expected_members(3)
+            .retry_interval(Duration::from_millis(50))
+            .timeout(Duration::from_millis(200))
+            .run()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BootstrapError::Timeout(_)));
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Duration {
+    Duration::from_millis(5_000)
+}
@@ -11,6 +11,8 @@ mod t10_learner_restart;
 mod t10_single_node;
 mod t11_add_learner;
 mod t12_concurrent_write_and_add_learner;
+mod t13_replication_status;
+mod t14_membership_history;
 mod t20_change_membership;
 mod t21_change_membership_cases;
 mod t30_commit_joint_config;
@@ -18,7 +20,18 @@ mod t30_elect_with_new_config;
 mod t31_add_remove_follower;
 mod t31_remove_leader;
 mod t31_removed_follower;
+mod t31_replace_node;
+mod t32_remove_leader_transfer;
+mod t33_demote_self;
+mod t34_membership_change_guard;
+mod t35_membership_change_interval;
+mod t40_witness;
+mod t41_quorum_policy;
+mod t42_auto_promote_learner;
+mod t43_auto_remove_dead_member;
 mod t51_remove_unreachable_follower;
+#[cfg(feature = "unsafe-force-reset-membership")]
+mod t60_force_reset_membership;
 mod t99_issue_471_adding_learner_uses_uninit_leader_id;
 mod t99_issue_584_replication_state_reverted;
 mod t99_new_leader_auto_commit_uniform_config;
@@ -12,6 +12,7 @@ use openraft::ChangeMembers;
 use openraft::CommittedLeaderId;
 use openraft::Config;
 use openraft::LogId;
+use openraft::LogRetentionPolicy;
 use openraft::Membership;
 use openraft::StorageHelper;
 use tokio::time::sleep;
@@ -29,7 +30,7 @@ async fn add_learner_basic() -> Result<()> {
     let config = Arc::new(
         Config {
             replication_lag_threshold: 0,
-            max_in_snapshot_log_to_keep: 2000, // prevent snapshot
+            log_retention_policy: LogRetentionPolicy::KeepEntries(2000), // prevent snapshot
             purge_batch_size: 1,
             enable_tick: false,
             ..Default::default()
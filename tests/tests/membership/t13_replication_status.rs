@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader reports how far every voter and learner is behind it.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replication_status() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- write 100 logs");
+    {
+        router.client_request_many(0, "client", 100).await?;
+        log_index += 100;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write 100 logs").await?;
+    }
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- add an isolated learner 3");
+    {
+        router.new_raft_node(3).await;
+        router.set_network_error(3, true);
+
+        n0.add_learner(3, (), false).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "add learner").await?;
+    }
+
+    tracing::info!(log_index, "--- the learner is behind, the voters have caught up");
+    {
+        let status = n0.replication_status().await?;
+        assert_eq!(btreeset! {1,2,3}, status.keys().copied().collect());
+
+        for id in [1, 2] {
+            let st = &status[&id];
+            assert!(st.voter);
+            assert_eq!(Some(log_index), st.matched.map(|x| x.index));
+            assert_eq!(0, st.lag_entries);
+            assert_eq!(Some(Duration::default()), st.catch_up_time);
+        }
+
+        let st = &status[&3];
+        assert!(!st.voter);
+        assert_eq!(None, st.matched);
+        assert_eq!(log_index + 1, st.lag_entries);
+        assert_eq!(None, st.catch_up_time);
+    }
+
+    tracing::info!(log_index, "--- a follower does not report the replication status");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let err = n1.replication_status().await.unwrap_err();
+        assert_eq!(Some(0), err.api_error().and_then(|e| e.leader_id));
+    }
+
+    tracing::info!(log_index, "--- the learner catches up");
+    {
+        router.set_network_error(3, false);
+        router.client_request_many(0, "client", 1).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset! {0,1,2,3}, Some(log_index), timeout(), "learner catches up").await?;
+
+        let status = n0.replication_status().await?;
+        assert_eq!(0, status[&3].lag_entries);
+        assert_eq!(Some(Duration::default()), status[&3].catch_up_time);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2000))
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Every node keeps a bounded history of the committed membership configs applied to its state
+/// machine.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn membership_history() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            membership_history_size: 3,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- remove node 2");
+    {
+        n0.change_membership([0, 1], false).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "node 2 removed").await?;
+    }
+
+    tracing::info!(log_index, "--- the latest configs are kept on every node");
+    {
+        for id in [0, 1] {
+            let n = router.get_raft_handle(&id)?;
+            let history = n.membership_history();
+
+            let voters = history.iter().map(|r| r.membership.get_joint_config().clone()).collect::<Vec<_>>();
+            assert_eq!(
+                vec![vec![btreeset! {0,1,2}], vec![btreeset! {0,1,2}, btreeset! {0,1}], vec![
+                    btreeset! {0,1}
+                ],],
+                voters,
+                "node {}",
+                id
+            );
+
+            let last = history.last().unwrap();
+            assert_eq!(log_index, last.log_id.index);
+            assert!(history.windows(2).all(|w| w[0].log_id < w[1].log_id));
+            assert!(history.windows(2).all(|w| w[0].applied_at <= w[1].applied_at));
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
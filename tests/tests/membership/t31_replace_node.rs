@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Replace a voter with a new node in one call.
+///
+/// - brings 3 nodes online and writes some logs.
+/// - replace node-2 with a new node-3.
+/// - asserts node-3 caught up with the logs and became a voter, and node-2 is removed.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replace_node() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- write 100 logs");
+    {
+        router.client_request_many(0, "client", 100).await?;
+        log_index += 100;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write 100 logs").await?;
+    }
+
+    tracing::info!(log_index, "--- replace n2 with n3");
+    {
+        router.new_raft_node(3).await;
+
+        let n0 = router.get_raft_handle(&0)?;
+        n0.replace_node(2, 3, ()).await?;
+        log_index += 3; // add-learner log and two member-change logs
+
+        router.wait_for_log(&btreeset! {0,1,3}, Some(log_index), timeout(), "n3 replaced n2").await?;
+
+        let metrics = n0.metrics().borrow().clone();
+        let membership = metrics.membership_config.membership();
+        assert_eq!(vec![btreeset! {0,1,3}], membership.get_joint_config().clone());
+        assert!(membership.get_node(&2).is_none());
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2000))
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Change membership from {0,1,2} to {1,2}, with `transfer_leader_on_removal` enabled.
+///
+/// - The leader hands over the leadership to a remaining voter, without waiting for an election
+///   timeout, and steps down.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn remove_leader_transfer_leadership() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            transfer_leader_on_removal: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- remove leader 0");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.change_membership([1, 2], false).await?;
+    }
+
+    tracing::info!(log_index, "--- a remaining voter becomes the leader by transfer");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        n1.wait(timeout())
+            .metrics(
+                |m| m.current_leader.is_some_and(|l| l != 0) && m.current_term > 1,
+                "a new leader is elected without election timeout",
+            )
+            .await?;
+    }
+
+    tracing::info!(log_index, "--- the old leader steps down");
+    {
+        router.wait(&0, timeout()).state(ServerState::Learner, "old leader steps down").await?;
+    }
+
+    tracing::info!(log_index, "--- the new leader serves writes");
+    {
+        let leader = router.get_raft_handle(&1)?.current_leader().await.unwrap();
+        router.client_request_many(leader, "foo", 1).await?;
+    }
+
+    Ok(())
+}
+
+/// Change membership from {0,1,2} to {1,2} and keep 0 as a learner, with
+/// `transfer_leader_on_removal` enabled.
+///
+/// - The leader hands over the leadership and becomes a learner of the new leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn remove_leader_transfer_leadership_retain_learner() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            transfer_leader_on_removal: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- remove leader 0 and keep it as a learner");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.change_membership([1, 2], true).await?;
+    }
+
+    tracing::info!(log_index, "--- the old leader follows a new leader");
+    {
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.state == ServerState::Learner && m.current_leader.is_some_and(|l| l != 0),
+                "old leader becomes a learner of the new leader",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader of {0,1,2} demotes itself to a learner.
+///
+/// - The leader removes itself from the voters, hands over the leadership, and becomes a learner of
+///   the new leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn demote_self_to_learner() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- leader 0 demotes itself");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let resp = n0.demote_self_to_learner().await?;
+
+        let membership = resp.membership.unwrap();
+        assert_eq!(btreeset! {1,2}, membership.voter_ids().collect());
+        assert!(membership.learner_ids().any(|id| id == 0));
+    }
+
+    tracing::info!(log_index, "--- the old leader follows a new leader");
+    {
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.state == ServerState::Learner && m.current_leader.is_some_and(|l| l != 0),
+                "old leader becomes a learner of the new leader",
+            )
+            .await?;
+    }
+
+    tracing::info!(log_index, "--- the new leader serves writes");
+    {
+        let leader = router.get_raft_handle(&0)?.current_leader().await.unwrap();
+        router.client_request_many(leader, "foo", 1).await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
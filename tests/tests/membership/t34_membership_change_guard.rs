@@ -0,0 +1,123 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use openraft::error::ChangeMembershipError;
+use openraft::error::ClientWriteError;
+use openraft::ChangeMembers;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A guarded membership change that reduces the fault tolerance below the minimum is refused,
+/// and it can still be applied with `change_membership_unguarded()`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn membership_change_guard_fault_tolerance() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            membership_change_guard: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2,3,4");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2,3,4}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- isolate node 4 and wait for its lease to expire");
+    {
+        router.set_network_error(4, true);
+        tokio::time::sleep(config.leader_lease() * 2).await;
+    }
+
+    tracing::info!(log_index, "--- removing voter 1 leaves no fault tolerance");
+    {
+        let err = n0.change_membership([0, 2, 3, 4], false).await.unwrap_err();
+        assert!(matches!(
+            err.api_error(),
+            Some(ClientWriteError::ChangeMembershipError(
+                ChangeMembershipError::UnsafeMembershipChange(_)
+            ))
+        ));
+    }
+
+    tracing::info!(log_index, "--- the unguarded change is applied");
+    {
+        n0.change_membership_unguarded([0, 2, 3, 4], false).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset! {0,2,3}, Some(log_index), timeout(), "membership changed").await?;
+    }
+
+    Ok(())
+}
+
+/// A guarded membership change that removes the last alive replica of a zone is refused.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn membership_change_guard_zone() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            membership_change_guard: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2,3,4");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2,3,4}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- place node 3 alone in zone b");
+    {
+        let zones = btreemap! {
+            0 => s("a"),
+            1 => s("a"),
+            2 => s("a"),
+            3 => s("b"),
+            4 => s("c"),
+        };
+        n0.change_membership(ChangeMembers::SetZones(zones), false).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset! {0,1,2,3,4}, Some(log_index), timeout(), "zones set").await?;
+    }
+
+    tracing::info!(log_index, "--- removing node 3 leaves no replica in zone b");
+    {
+        let err = n0.change_membership([0, 1, 2, 4], false).await.unwrap_err();
+        assert!(matches!(
+            err.api_error(),
+            Some(ClientWriteError::ChangeMembershipError(
+                ChangeMembershipError::UnsafeMembershipChange(_)
+            ))
+        ));
+    }
+
+    tracing::info!(log_index, "--- removing node 2 keeps a replica in zone a");
+    {
+        n0.change_membership([0, 1, 3, 4], false).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset! {0,1,3,4}, Some(log_index), timeout(), "node 2 removed").await?;
+    }
+
+    Ok(())
+}
+
+fn s(x: &str) -> String {
+    x.to_string()
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1000))
+}
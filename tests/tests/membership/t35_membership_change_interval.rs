@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ChangeMembershipError;
+use openraft::error::ClientWriteError;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A membership change within the minimum interval since the last one is refused.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn membership_change_min_interval() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            membership_change_min_interval: 1_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2 and learner 3");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- the first change is applied, in two steps");
+    {
+        // new_cluster() has just changed the membership.
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        n0.change_membership([0, 1, 2, 3], false).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset! {0,1,2,3}, Some(log_index), timeout(), "3 becomes voter").await?;
+    }
+
+    tracing::info!(log_index, "--- adding a learner is not limited");
+    {
+        router.new_raft_node(4).await;
+        router.add_learner(0, 4).await?;
+        log_index += 1;
+    }
+
+    tracing::info!(log_index, "--- a change right after it is refused");
+    {
+        let err = n0.change_membership([0, 1, 2], false).await.unwrap_err();
+        assert!(matches!(
+            err.api_error(),
+            Some(ClientWriteError::ChangeMembershipError(
+                ChangeMembershipError::ChangeMembershipTooFrequent(_)
+            ))
+        ));
+    }
+
+    tracing::info!(log_index, "--- a change after the interval is applied");
+    {
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        n0.change_membership([0, 1, 2], false).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "3 removed").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1000))
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ChangeMembershipError;
+use openraft::error::ClientWriteError;
+use openraft::error::TransferLeaderError;
+use openraft::error::WitnessCleared;
+use openraft::storage::RaftLogReaderExt;
+use openraft::ChangeMembers;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft_memstore::ClientRequest;
+use openraft_memstore::IntoMemClientRequest;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A witness counts for the commit quorum but receives no application data, and can not become
+/// the leader. An entry is not committed until a non-witness follower accepts it.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn witness() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- make node 2 a witness");
+    {
+        n0.change_membership(ChangeMembers::SetWitnesses(btreeset! {2}), true).await?;
+        log_index += 1;
+
+        let m = n0.metrics().borrow().membership_config.clone();
+        assert!(m.membership().is_witness(&2));
+    }
+
+    let first = log_index + 1;
+
+    tracing::info!(log_index, "--- write to the leader, the witness receives no payload");
+    {
+        log_index += router.client_request_many(0, "foo", 10).await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write 10 logs").await?;
+
+        let (mut sto2, sm2) = router.get_storage_handle(&2)?;
+        let logs = sto2.get_log_entries(first..=log_index).await?;
+        assert_eq!(10, logs.len());
+        for ent in logs {
+            assert!(
+                matches!(ent.payload, EntryPayload::Blank),
+                "witness log is blank: {}",
+                ent
+            );
+        }
+        assert!(sm2.get_state_machine().await.client_status.is_empty());
+
+        let (_sto1, sm1) = router.get_storage_handle(&1)?;
+        assert!(!sm1.get_state_machine().await.client_status.is_empty());
+    }
+
+    tracing::info!(
+        log_index,
+        "--- isolate node 1: the leader does not commit with only the witness"
+    );
+    {
+        router.set_network_error(1, true);
+
+        let n0c = n0.clone();
+        let write = tokio::spawn(async move { n0c.client_write(ClientRequest::make_request("foo", 100)).await });
+        log_index += 1;
+
+        router.wait(&2, timeout()).log_index(Some(log_index), "accepted by witness 2").await?;
+
+        let res = router.wait(&0, timeout()).applied_index(Some(log_index), "not committed by 0 and 2").await;
+        assert!(res.is_err(), "an entry is not committed by the leader and the witness");
+
+        tracing::info!(log_index, "--- restore node 1: the entry is committed");
+
+        router.set_network_error(1, false);
+        write.await??;
+        router.wait(&0, timeout()).applied_index(Some(log_index), "committed by 0 and 1").await?;
+    }
+
+    tracing::info!(log_index, "--- the witness can not be turned into a normal member");
+    {
+        let err = n0.change_membership(ChangeMembers::SetWitnesses(btreeset! {}), true).await.unwrap_err();
+        assert!(
+            matches!(
+                err.api_error(),
+                Some(ClientWriteError::ChangeMembershipError(
+                    ChangeMembershipError::WitnessCleared(WitnessCleared { node_id: 2 })
+                ))
+            ),
+            "got: {:?}",
+            err
+        );
+    }
+
+    tracing::info!(log_index, "--- the witness can not become the leader");
+    {
+        let err = n0.transfer_leadership(2).await.unwrap_err();
+        assert!(matches!(err.api_error(), Some(TransferLeaderError::NotVoter(_))));
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
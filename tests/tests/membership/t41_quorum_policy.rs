@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use openraft::error::ChangeMembershipError;
+use openraft::error::ClientWriteError;
+use openraft::ChangeMembers;
+use openraft::Config;
+use openraft::QuorumPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A quorum policy defines the commit quorum, and a policy whose quorums do not intersect the
+/// current ones is rejected.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn quorum_policy() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let weighted = QuorumPolicy::Weighted(btreemap! {0=>3});
+
+    tracing::info!(log_index, "--- a weighted policy does not intersect the majority");
+    {
+        let err = n0.change_membership(ChangeMembers::SetQuorumPolicy(weighted.clone()), true).await.unwrap_err();
+        assert!(matches!(
+            err.api_error(),
+            Some(ClientWriteError::ChangeMembershipError(
+                ChangeMembershipError::InvalidQuorumPolicy(_)
+            ))
+        ));
+    }
+
+    tracing::info!(log_index, "--- change to the weighted policy via majority-with-0");
+    {
+        n0.change_membership(
+            ChangeMembers::SetQuorumPolicy(QuorumPolicy::MajorityWith(btreeset! {0})),
+            true,
+        )
+        .await?;
+        log_index += 1;
+
+        n0.change_membership(ChangeMembers::SetQuorumPolicy(weighted.clone()), true).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "policy changed").await?;
+
+        let m = n0.metrics().borrow().membership_config.clone();
+        assert_eq!(&weighted, m.membership().quorum_policy());
+    }
+
+    tracing::info!(log_index, "--- isolate node 1 and 2: node 0 commits alone");
+    {
+        router.set_network_error(1, true);
+        router.set_network_error(2, true);
+
+        log_index += router.client_request_many(0, "foo", 1).await?;
+        router.wait(&0, timeout()).applied_index(Some(log_index), "committed by 0 alone").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
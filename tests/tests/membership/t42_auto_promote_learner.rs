@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreemap;
+use maplit::btreeset;
+use openraft::ChangeMembers;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A learner added with `add_learner()` is promoted to a voter once it has kept up with the
+/// leader for `auto_promote_learner_duration`, while a learner added with `AddNodes` is not.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn auto_promote_learner() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            auto_promote_learner_duration: 500,
+            auto_promote_learner_lag: 0,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- add node-2 as learner with AddNodes");
+    {
+        router.new_raft_node(2).await;
+        n0.change_membership(ChangeMembers::AddNodes(btreemap! {2=>()}), true).await?;
+        log_index += 1;
+    }
+
+    tracing::info!(log_index, "--- add node-1 with add_learner");
+    {
+        router.new_raft_node(1).await;
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.client_request_many(0, "foo", 10).await?;
+        log_index += 10;
+    }
+
+    tracing::info!(log_index, "--- node-1 is promoted to voter");
+    {
+        n0.wait(timeout()).metrics(|m| m.learner_promotion.promoted == 1, "node-1 promoted").await?;
+        // joint config and uniform config
+        log_index += 2;
+
+        let m = n0.metrics().borrow().clone();
+        assert_eq!(Some(1), m.learner_promotion.last_promoted);
+
+        let membership = m.membership_config.membership();
+        assert_eq!(vec![btreeset! {0,1}], membership.get_joint_config().clone());
+        assert_eq!(btreeset! {2}, membership.learner_ids().collect());
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "promotion committed").await?;
+    }
+
+    tracing::info!(log_index, "--- node-2 stays a learner");
+    {
+        tokio::time::sleep(Duration::from_millis(1_000)).await;
+
+        let m = n0.metrics().borrow().clone();
+        assert_eq!(1, m.learner_promotion.promoted);
+        assert_eq!(btreeset! {2}, m.membership_config.membership().learner_ids().collect());
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
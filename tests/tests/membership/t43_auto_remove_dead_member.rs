@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A voter or learner that has been unreachable for `auto_remove_dead_member_duration` is removed
+/// by the leader, unless the alive voters would not form a quorum without it.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn auto_remove_dead_member() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            auto_remove_dead_member_duration: 1_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- create cluster of 0,1,2 and learner 3");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let mut rx = n0.subscribe_dead_members();
+
+    tracing::info!(log_index, "--- voter 2 and learner 3 become unreachable");
+    {
+        router.set_network_error(2, true);
+        router.set_network_error(3, true);
+    }
+
+    tracing::info!(log_index, "--- both are reported before being removed");
+    {
+        let mut removed = btreeset! {};
+        for _ in 0..2 {
+            let dead = tokio::time::timeout(timeout(), rx.recv()).await??;
+            assert_eq!(dead.id == 2, dead.voter);
+            assert!(dead.unreachable_for >= Duration::from_millis(1_000));
+            removed.insert(dead.id);
+        }
+        assert_eq!(btreeset! {2,3}, removed);
+
+        n0.wait(Some(timeout()))
+            .metrics(
+                |m| {
+                    let membership = m.membership_config.membership();
+                    membership.get_joint_config() == &vec![btreeset! {0,1}] && membership.nodes().count() == 2
+                },
+                "2 and 3 removed",
+            )
+            .await?;
+    }
+
+    tracing::info!(log_index, "--- voter 1 is not removed, 0 alone is not a quorum of 0,1");
+    {
+        router.set_network_error(1, true);
+        tokio::time::sleep(Duration::from_millis(2_000)).await;
+
+        assert!(rx.try_recv().is_err());
+
+        let m = n0.metrics().borrow().clone();
+        assert_eq!(btreeset! {0,1}, m.membership_config.membership().voter_ids().collect());
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Duration {
+    Duration::from_millis(5_000)
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A cluster {0,1,2,3,4} loses 3 voters including the leader permanently, and is recovered from
+/// the surviving nodes {3,4} by force resetting the membership on node 3.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn force_reset_membership() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_cluster(btreeset! {0,1,2,3,4}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- lose 0,1,2 permanently");
+    {
+        for id in [0, 1, 2] {
+            let (raft, _, _) = router.remove_node(id).unwrap();
+            raft.shutdown().await?;
+        }
+
+        // Wait for the leader lease to expire on the survivors.
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max + 200)).await;
+    }
+
+    tracing::info!(log_index, "--- force reset membership to {{3,4}} on node 3");
+    {
+        let n3 = router.get_raft_handle(&3)?;
+        n3.force_reset_membership(btreeset! {3,4}).await?;
+
+        // The membership log and the blank log of the new leader.
+        log_index += 2;
+    }
+
+    tracing::info!(log_index, "--- node 3 becomes the leader of {{3,4}}");
+    {
+        for id in [3, 4] {
+            router
+                .wait(&id, timeout())
+                .metrics(
+                    |m| m.current_leader == Some(3) && m.membership_config.voter_ids().eq([3, 4]),
+                    "new membership takes effect",
+                )
+                .await?;
+        }
+
+        router.wait(&3, timeout()).state(ServerState::Leader, "node 3 is the leader").await?;
+        router.wait_for_log(&btreeset! {3,4}, Some(log_index), timeout(), "the reset is committed").await?;
+    }
+
+    tracing::info!(log_index, "--- the recovered cluster serves writes");
+    {
+        router.client_request_many(3, "foo", 3).await?;
+        log_index += 3;
+
+        router.wait_for_log(&btreeset! {3,4}, Some(log_index), timeout(), "write is committed").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
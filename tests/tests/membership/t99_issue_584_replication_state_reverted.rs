@@ -4,6 +4,7 @@ use std::time::Duration;
 use anyhow::Result;
 use maplit::btreeset;
 use openraft::Config;
+use openraft::LogRetentionPolicy;
 
 use crate::fixtures::init_default_ut_tracing;
 use crate::fixtures::RaftRouter;
@@ -18,7 +19,7 @@ async fn t99_issue_584_replication_state_reverted() -> Result<()> {
 
     let config = Arc::new(
         Config {
-            max_in_snapshot_log_to_keep: 2000, // prevent snapshot
+            log_retention_policy: LogRetentionPolicy::KeepEntries(2000), // prevent snapshot
             enable_tick: false,
             ..Default::default()
         }
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::LogRetentionPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `LogRetentionPolicy::SlowestReplica`, a leader does not purge the logs a follower has not
+/// yet received, and the policy state is reported in metrics.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn log_retention_slowest_replica() -> Result<()> {
+    let policy = LogRetentionPolicy::SlowestReplica { max_entries: 1000 };
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            log_retention_policy: policy.clone(),
+            purge_batch_size: 1,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initialize cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+    let replicated = log_index;
+
+    tracing::info!(log_index, "--- isolate node-2 and write logs");
+    router.set_network_error(2, true);
+    log_index += router.client_request_many(0, "foo", 10).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- build snapshot, logs not replicated to node-2 are kept");
+    {
+        n0.trigger().snapshot().await?;
+        n0.wait(timeout()).snapshot(log_id(1, 0, log_index), "build snapshot").await?;
+
+        let m = n0.metrics().borrow().clone();
+        assert_eq!(policy, m.log_retention.policy);
+        assert!(m.log_retention.bytes > 0);
+        assert!(
+            m.purged.map(|x| x.index) <= Some(replicated),
+            "purged: {:?}, replicated to node-2: {}",
+            m.purged,
+            replicated
+        );
+    }
+
+    tracing::info!(log_index, "--- restore node-2, logs are purged after node-2 catches up");
+    {
+        router.set_network_error(2, false);
+        n0.trigger().heartbeat().await?;
+
+        router.wait(&2, timeout()).applied_index(Some(log_index), "node-2 catches up").await?;
+        n0.wait(timeout())
+            .metrics(
+                |m| m.purged == Some(log_id(1, 0, log_index)),
+                "logs are purged upto snapshot",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -6,6 +6,7 @@ use maplit::btreeset;
 use openraft::storage::RaftLogStorage;
 use openraft::testing::log_id;
 use openraft::Config;
+use openraft::LogRetentionPolicy;
 
 use crate::fixtures::init_default_ut_tracing;
 use crate::fixtures::RaftRouter;
@@ -16,7 +17,7 @@ async fn metrics_purged() -> Result<()> {
     let config = Arc::new(
         Config {
             enable_heartbeat: false,
-            max_in_snapshot_log_to_keep: 0,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(0),
             purge_batch_size: 1,
             ..Default::default()
         }
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Every subscriber of `Raft::subscribe_role_changes()` receives the server state changes of a
+/// leadership transfer.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn role_change() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let n1 = router.get_raft_handle(&1)?;
+    let mut rx0 = n0.subscribe_role_changes();
+    let mut rx1 = n1.subscribe_role_changes();
+
+    tracing::info!(log_index, "--- transfer leadership from 0 to 1");
+    {
+        n0.transfer_leadership(1).await?;
+    }
+
+    tracing::info!(log_index, "--- node 1 becomes the leader");
+    {
+        // The candidate state may be too brief to be reported.
+        let mut change = tokio::time::timeout(timeout(), rx1.recv()).await??;
+        assert_eq!(ServerState::Follower, change.prev);
+        if change.state == ServerState::Candidate {
+            change = tokio::time::timeout(timeout(), rx1.recv()).await??;
+        }
+
+        assert_eq!(1, change.id);
+        assert_eq!(ServerState::Leader, change.state);
+        assert!(change.is_leader());
+        assert_eq!(Some(1), change.current_leader);
+        assert_eq!(2, change.term());
+        assert_eq!(
+            btreeset! {0,1,2},
+            change.membership_config.membership().voter_ids().collect()
+        );
+    }
+
+    tracing::info!(log_index, "--- node 0 steps down to a follower");
+    {
+        let change = tokio::time::timeout(timeout(), rx0.recv()).await??;
+        assert_eq!(ServerState::Leader, change.prev);
+        assert_eq!(ServerState::Follower, change.state);
+        assert!(!change.is_leader());
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Duration {
+    Duration::from_millis(3_000)
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::network::RPCErrorKind;
+use openraft::Config;
+use openraft::RPCTypes;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Every RPC is reported to the `NetworkObserver` of the target, and the leader reports the RTT
+/// of the followers in metrics.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn network_observer() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 50,
+            election_timeout_min: 10_000,
+            election_timeout_max: 10_001,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- the leader reports the RTT of every follower");
+    {
+        let m = router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.rtt.get(&1).map_or(false, |x| x.samples > 0) && m.rtt.get(&2).map_or(false, |x| x.samples > 0),
+                "RTT of node 1 and 2 is estimated",
+            )
+            .await?;
+
+        assert!(!m.rtt.contains_key(&0), "no RPC is sent to the leader itself");
+
+        let observed = router.get_observed_rpcs();
+        assert!(observed.get(&(1, RPCTypes::AppendEntries, None)).copied().unwrap_or_default() > 0);
+        assert!(observed.get(&(2, RPCTypes::AppendEntries, None)).copied().unwrap_or_default() > 0);
+    }
+
+    tracing::info!(log_index, "--- failed RPCs are reported with the error class");
+    {
+        router.set_unreachable(2, true);
+
+        let unreachable = (2, RPCTypes::AppendEntries, Some(RPCErrorKind::Unreachable));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        while router.get_observed_rpcs().get(&unreachable).copied().unwrap_or_default() == 0 {
+            assert!(tokio::time::Instant::now() < deadline, "no unreachable RPC is observed");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
@@ -6,7 +6,16 @@ mod fixtures;
 
 mod t10_append_entries_partial_success;
 mod t50_append_entries_backoff;
+mod t50_append_entries_backoff_metrics;
 mod t50_append_entries_backoff_rejoin;
 mod t51_append_entries_too_large;
+mod t52_replication_rate_limit;
+mod t53_append_entries_partial_accept;
+mod t54_adaptive_batch;
+mod t55_log_cache;
+mod t56_read_ahead;
+mod t57_parallel_local_append;
+mod t58_pipeline_append_entries;
+mod t59_quarantine_slow_follower;
 #[cfg(feature = "loosen-follower-log-revert")]
 mod t60_feature_loosen_follower_log_revert;
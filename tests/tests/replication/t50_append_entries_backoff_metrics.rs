@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::BackoffConfig;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader reports the backoff state of an unreachable node in metrics, and removes it when
+/// the node becomes reachable again.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn append_entries_backoff_metrics() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            heartbeat_interval: 100,
+            election_timeout_min: 10_000,
+            election_timeout_max: 10_001,
+            backoff_policy: BackoffConfig::CircuitBreaker {
+                threshold: 3,
+                retry_interval: 50,
+                open_duration: 200,
+            },
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- set node 2 to unreachable");
+    {
+        router.set_unreachable(2, true);
+
+        // Heartbeats are sent by a dedicated worker, the replication stream backs off only when
+        // there are logs to replicate.
+        log_index += router.client_request_many(0, "0", 1).await?;
+
+        let m = router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.backoff.as_ref().and_then(|b| b.get(&2)).map_or(false, |s| s.attempts >= 3),
+                "node 2 is backing off, with the circuit open",
+            )
+            .await?;
+
+        let backoff = m.backoff.unwrap();
+        assert_eq!(200, backoff[&2].delay_millis);
+        assert!(!backoff.contains_key(&1), "node 1 is reachable");
+    }
+
+    tracing::info!(log_index, "--- set node 2 to reachable");
+    {
+        router.set_unreachable(2, false);
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.backoff.as_ref().map_or(false, |b| b.is_empty()),
+                "node 2 is no longer backing off",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
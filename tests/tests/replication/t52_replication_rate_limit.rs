@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::Entry;
+use openraft_memstore::TypeConfig;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Catching up a new learner is limited by `replication_rate_limit_bytes_per_sec`.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replication_rate_limit() -> Result<()> {
+    // 100 entries per second.
+    let entry_size = std::mem::size_of::<Entry<TypeConfig>>() as u64;
+
+    let config = Arc::new(
+        Config {
+            max_payload_entries: 5,
+            replication_rate_limit_bytes_per_sec: entry_size * 100,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- write 50 logs");
+    log_index += router.client_request_many(0, "foo", 50).await?;
+
+    tracing::info!(log_index, "--- add learner 1, it takes at least 0.4 second to catch up");
+    {
+        let now = Instant::now();
+
+        router.new_raft_node(1).await;
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait(&1, timeout()).applied_index(Some(log_index), "learner 1 catches up").await?;
+
+        let elapsed = now.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(400),
+            "replication is rate limited, elapsed: {:?}",
+            elapsed
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
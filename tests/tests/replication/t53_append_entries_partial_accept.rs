@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::RPCTypes;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A follower that can append only a prefix of the replicated entries replies `PartialSuccess`,
+/// and the leader sends only the rest.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn append_entries_partial_accept() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            max_payload_entries: 100,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {1}).await?;
+
+    tracing::info!(log_index, "--- node 1 appends at most 3 entries at a time");
+    {
+        let (sto1, _sm1) = router.get_storage_handle(&1)?;
+        sto1.set_appendable_limit(Some(3));
+    }
+
+    tracing::info!(log_index, "--- isolate node 1 and write 30 logs");
+    {
+        router.set_network_error(1, true);
+        log_index += router.client_request_many(0, "0", 30).await?;
+        router.wait(&0, timeout()).applied_index(Some(log_index), "leader applied").await?;
+    }
+
+    let before = router.get_rpc_count().get(&RPCTypes::AppendEntries).copied().unwrap_or_default();
+
+    tracing::info!(log_index, "--- node 1 catches up 3 entries at a time");
+    {
+        router.set_network_error(1, false);
+        router.wait(&1, timeout()).applied_index(Some(log_index), "node 1 catches up").await?;
+
+        let after = router.get_rpc_count().get(&RPCTypes::AppendEntries).copied().unwrap_or_default();
+        assert!(
+            after - before >= 10,
+            "30 entries are sent at most 3 at a time, but only {} AppendEntries are sent",
+            after - before
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
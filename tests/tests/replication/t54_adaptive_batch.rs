@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `adaptive_batch_target_latency`, the number of entries per AppendEntries RPC starts small
+/// and grows up to `max_payload_entries` while the RPCs are fast.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn adaptive_batch() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            max_payload_entries: 100,
+            adaptive_batch_target_latency: 1_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    router.network_send_delay(0);
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- write 300 logs");
+    log_index += router.client_request_many(0, "foo", 300).await?;
+
+    tracing::info!(log_index, "--- add learner 1, the batch size grows while catching up");
+    {
+        router.new_raft_node(1).await;
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait(&1, timeout()).applied_index(Some(log_index), "learner 1 catches up").await?;
+
+        let metrics = router.get_metrics(&0)?;
+        let batch_size = metrics.batch_size.unwrap();
+        assert_eq!(Some(&100), batch_size.get(&1), "grows from 16 to max_payload_entries");
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With the log cache enabled, the leader replicates the latest entries from memory, and reads
+/// the older ones from the log store.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn log_cache() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- write 10 logs before enabling the cache");
+    log_index += router.client_request_many(0, "foo", 10).await?;
+
+    tracing::info!(log_index, "--- enable the cache and write 10 logs");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.enable_log_cache(1_000, u64::MAX);
+
+        log_index += router.client_request_many(0, "foo", 10).await?;
+        for id in [0, 1, 2] {
+            router.wait(&id, timeout()).applied_index(Some(log_index), "replicated").await?;
+        }
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(10, m.log_cache.entries);
+        assert!(m.log_cache.hits > 0, "replicated from the cache: {}", m.log_cache);
+    }
+
+    tracing::info!(log_index, "--- a new learner reads the older logs from the log store");
+    {
+        let misses = router.get_metrics(&0)?.log_cache.misses;
+
+        router.new_raft_node(3).await;
+        router.add_learner(0, 3).await?;
+        log_index += 1;
+
+        router.wait(&3, timeout()).applied_index(Some(log_index), "learner 3 catches up").await?;
+
+        let m = router.get_metrics(&0)?;
+        assert!(m.log_cache.misses > misses, "read from the log store: {}", m.log_cache);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::storage::RaftLogStorage;
+use openraft::Config;
+use openraft::RaftLogReader;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With read-ahead enabled, a learner far behind catches up with the entries read ahead while
+/// the previous AppendEntries is in flight, and receives the same log as the leader.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn read_ahead() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            max_payload_entries: 3,
+            // About the size of a few entries, so that the budget limits the read-ahead.
+            replication_read_ahead_bytes: 1024,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- write 50 logs");
+    log_index += router.client_request_many(0, "foo", 50).await?;
+
+    tracing::info!(log_index, "--- a new learner catches up");
+    {
+        router.new_raft_node(1).await;
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait(&1, timeout()).applied_index(Some(log_index), "learner 1 catches up").await?;
+    }
+
+    tracing::info!(log_index, "--- the learner has the same log as the leader");
+    {
+        let (mut leader_log, _) = router.get_storage_handle(&0)?;
+        let (mut learner_log, _) = router.get_storage_handle(&1)?;
+
+        let want = leader_log.try_get_log_entries(..).await?;
+        let got = learner_log.try_get_log_entries(..).await?;
+
+        let log_ids = |entries: &[openraft::Entry<_>]| entries.iter().map(|e| e.log_id).collect::<Vec<_>>();
+        assert_eq!(log_ids(&want), log_ids(&got));
+        assert_eq!(
+            Some(log_index),
+            learner_log.get_log_state().await?.last_log_id.map(|x| x.index)
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LogIdOptionExt;
+use openraft_memstore::BlockOperation;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader replicates entries without waiting for them to be flushed to its own log store,
+/// but by default commits an entry only after flushing it.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn parallel_local_append() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- delay flushing log on the leader");
+    {
+        let (_sto0, sm0) = router.get_storage_handle(&0)?;
+        sm0.block.set_blocking(BlockOperation::FlushLog, Duration::from_millis(2_000));
+    }
+
+    tracing::info!(log_index, "--- followers receive the entry before the leader flushes");
+    {
+        let start = Instant::now();
+        let write = tokio::spawn({
+            let router = router.clone();
+            async move { router.client_request_many(0, "foo", 1).await }
+        });
+        log_index += 1;
+
+        for id in [1, 2] {
+            router
+                .wait(&id, Some(Duration::from_millis(1_000)))
+                .metrics(|m| m.last_log_index == Some(log_index), "replicated to follower")
+                .await?;
+        }
+
+        let n0 = router.get_raft_handle(&0)?;
+        assert!(n0.metrics().borrow().last_applied.index() < Some(log_index));
+
+        write.await??;
+        assert!(
+            start.elapsed() >= Duration::from_millis(1_500),
+            "commit waits for the leader to flush"
+        );
+    }
+
+    Ok(())
+}
+
+/// With `commit_without_local_flush`, an entry is committed by a quorum of followers before the
+/// leader's flush finishes.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn commit_without_local_flush() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            commit_without_local_flush: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- delay flushing log on the leader");
+    {
+        let (_sto0, sm0) = router.get_storage_handle(&0)?;
+        sm0.block.set_blocking(BlockOperation::FlushLog, Duration::from_millis(3_000));
+    }
+
+    tracing::info!(log_index, "--- write is committed before the leader flushes");
+    {
+        let start = Instant::now();
+        log_index += router.client_request_many(0, "foo", 1).await?;
+
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed < Duration::from_millis(2_000),
+            "write should not wait for the leader to flush, elapsed: {:?}",
+            elapsed
+        );
+
+        let n0 = router.get_raft_handle(&0)?;
+        let matching = n0.metrics().borrow().replication.as_ref().unwrap()[&0];
+        assert!(matching.index() < Some(log_index), "leader has not flushed yet");
+    }
+
+    tracing::info!(log_index, "--- the leader's own progress is updated once flushed");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.wait(timeout())
+            .metrics(
+                |m| m.replication.as_ref().and_then(|r| r[&0]).index() == Some(log_index),
+                "leader flushed",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::storage::RaftLogStorage;
+use openraft::Config;
+use openraft::RaftLogReader;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::MemLogStore;
+use crate::fixtures::RaftRouter;
+
+/// With several AppendEntries RPCs in flight, a learner far behind catches up and receives the
+/// same log as the leader.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn pipeline_append_entries() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            max_payload_entries: 3,
+            max_inflight_append_entries: 4,
+            // About the size of a few entries, so that the bytes in flight limit the window.
+            max_inflight_bytes: 1024,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    router.network_send_delay(5);
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- write 50 logs");
+    log_index += router.client_request_many(0, "foo", 50).await?;
+
+    tracing::info!(log_index, "--- a new learner catches up");
+    {
+        router.new_raft_node(1).await;
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait(&1, timeout()).applied_index(Some(log_index), "learner 1 catches up").await?;
+    }
+
+    tracing::info!(log_index, "--- the learner has the same log as the leader");
+    {
+        let (mut leader_log, _) = router.get_storage_handle(&0)?;
+        let (mut learner_log, _) = router.get_storage_handle(&1)?;
+        assert_same_log(&mut leader_log, &mut learner_log, log_index).await?;
+    }
+
+    Ok(())
+}
+
+/// A partial success response stops the pipeline, the leader falls back to one RPC at a time and
+/// resumes pipelining once an RPC succeeds.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn pipeline_fall_back_to_stop_and_wait() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            max_payload_entries: 3,
+            max_inflight_append_entries: 4,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- add a learner");
+    {
+        router.new_raft_node(1).await;
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait(&1, timeout()).applied_index(Some(log_index), "learner 1 is added").await?;
+    }
+
+    tracing::info!(log_index, "--- the learner accepts no entry, write 30 logs");
+    {
+        router.set_append_entries_quota(Some(0));
+        log_index += router.client_request_many(0, "foo", 30).await?;
+    }
+
+    tracing::info!(log_index, "--- the learner accepts only 4 entries");
+    {
+        router.set_append_entries_quota(Some(4));
+
+        let res = router.wait(&1, Some(Duration::from_millis(500))).applied_index(Some(log_index), "blocked").await;
+        assert!(res.is_err(), "learner 1 is limited by quota");
+    }
+
+    tracing::info!(log_index, "--- remove the quota, the learner catches up");
+    {
+        router.set_append_entries_quota(None);
+
+        router.wait(&1, timeout()).applied_index(Some(log_index), "learner 1 catches up").await?;
+
+        let (mut leader_log, _) = router.get_storage_handle(&0)?;
+        let (mut learner_log, _) = router.get_storage_handle(&1)?;
+        assert_same_log(&mut leader_log, &mut learner_log, log_index).await?;
+    }
+
+    Ok(())
+}
+
+async fn assert_same_log(leader_log: &mut MemLogStore, learner_log: &mut MemLogStore, log_index: u64) -> Result<()> {
+    let want = leader_log.try_get_log_entries(..).await?;
+    let got = learner_log.try_get_log_entries(..).await?;
+
+    let log_ids = |entries: &[openraft::Entry<_>]| entries.iter().map(|e| e.log_id).collect::<Vec<_>>();
+    assert_eq!(log_ids(&want), log_ids(&got));
+    assert_eq!(
+        Some(log_index),
+        learner_log.get_log_state().await?.last_log_id.map(|x| x.index)
+    );
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `quarantine_lag_growth_duration`, a follower whose replication lag keeps growing is
+/// quarantined, and is released once it catches up.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn quarantine_slow_follower() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            quarantine_lag_growth_duration: 300,
+            quarantine_rate_limit_bytes_per_sec: 1024 * 1024,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- node 2 can not keep up while logs are written");
+    {
+        router.set_network_error(2, true);
+
+        for _ in 0..10 {
+            log_index += router.client_request_many(0, "foo", 5).await?;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.quarantined.as_ref().is_some_and(|q| q.contains(&2)),
+                "node 2 is quarantined",
+            )
+            .await?;
+
+        let metrics = router.get_metrics(&0)?;
+        assert_eq!(Some(btreeset! {2}), metrics.quarantined, "node 1 keeps up");
+    }
+
+    tracing::info!(log_index, "--- node 2 recovers and is released once caught up");
+    {
+        router.set_network_error(2, false);
+
+        router.wait(&2, timeout()).applied_index(Some(log_index), "node 2 catches up").await?;
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.quarantined.as_ref().is_some_and(|q| q.is_empty()),
+                "node 2 is released",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
@@ -7,4 +7,8 @@ mod fixtures;
 mod t10_build_snapshot;
 mod t35_building_snapshot_does_not_block_append;
 mod t35_building_snapshot_does_not_block_apply;
+mod t36_offload_snapshot_build;
 mod t60_snapshot_policy_never;
+mod t61_snapshot_policy_switch;
+mod t62_max_snapshots_to_keep;
+mod t63_cancel_building_snapshot;
@@ -15,6 +15,7 @@ use openraft::Config;
 use openraft::Entry;
 use openraft::EntryPayload;
 use openraft::LogId;
+use openraft::LogRetentionPolicy;
 use openraft::Membership;
 use openraft::SnapshotPolicy;
 use openraft::Vote;
@@ -37,7 +38,7 @@ async fn build_snapshot() -> Result<()> {
     let config = Arc::new(
         Config {
             snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
-            max_in_snapshot_log_to_keep: 2,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(2),
             purge_batch_size: 1,
             enable_tick: false,
             ..Default::default()
@@ -138,6 +139,7 @@ async fn build_snapshot() -> Result<()> {
                     prev_log_id: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
                     entries: vec![],
                     leader_commit: Some(LogId::new(CommittedLeaderId::new(0, 0), 0)),
+                    context: Default::default(),
                 },
                 option,
             )
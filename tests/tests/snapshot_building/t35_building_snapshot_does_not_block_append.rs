@@ -65,6 +65,7 @@ async fn building_snapshot_does_not_block_append() -> Result<()> {
             prev_log_id: Some(log_id(1, 0, log_index)),
             entries: vec![blank_ent(1, 0, 15)],
             leader_commit: None,
+            context: Default::default(),
         };
 
         let mut cli = router.new_client(1, &()).await;
@@ -70,6 +70,7 @@ async fn building_snapshot_does_not_block_apply() -> Result<()> {
             entries: vec![blank_ent(1, 0, next)],
             // Append and commit this entry
             leader_commit: Some(log_id(1, 0, next)),
+            context: Default::default(),
         };
 
         let mut cli = router.new_client(1, &()).await;
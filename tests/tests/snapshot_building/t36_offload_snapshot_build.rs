@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::network::RPCOption;
+use openraft::network::RaftNetwork;
+use openraft::network::RaftNetworkFactory;
+use openraft::raft::AppendEntriesRequest;
+use openraft::testing::blank_ent;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::Vote;
+use openraft_memstore::BlockOperation;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `offload_snapshot_build`, a snapshot builder that blocks synchronously does not stall the
+/// executor, and append-entries request to the building node is responded in time.
+///
+/// The runtime has only one worker thread, which would be blocked if the builder runs on it.
+#[async_entry::test(worker_threads = 1, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn offload_snapshot_build() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_tick: false,
+            offload_snapshot_build: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    let mut log_index = router.new_cluster(btreeset! {0,1}, btreeset! {}).await?;
+
+    let follower = router.get_raft_handle(&1)?;
+
+    tracing::info!(log_index, "--- set flag to block snapshot building synchronously");
+    {
+        let (mut _sto1, sm1) = router.get_storage_handle(&1)?;
+        sm1.block.set_blocking(BlockOperation::BuildSnapshotSync, Duration::from_millis(3_000));
+    }
+
+    // The time when building snapshot starts. The executor would be blocked since then, if the
+    // snapshot is not built in a blocking thread.
+    let now;
+
+    tracing::info!(log_index, "--- build snapshot on follower, it should block");
+    {
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait(&1, timeout()).applied_index(Some(log_index), "written 10 logs").await?;
+
+        now = std::time::Instant::now();
+        follower.trigger().snapshot().await?;
+
+        tracing::info!(log_index, "--- sleep 500 ms to make sure snapshot is started");
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    tracing::info!(
+        log_index,
+        "--- send append-entries request to the follower that is building snapshot"
+    );
+    {
+        let rpc = AppendEntriesRequest::<openraft_memstore::TypeConfig> {
+            vote: Vote::new_committed(1, 0),
+            prev_log_id: Some(log_id(1, 0, log_index)),
+            entries: vec![blank_ent(1, 0, 15)],
+            leader_commit: None,
+            context: Default::default(),
+        };
+
+        let mut cli = router.new_client(1, &()).await;
+        let option = RPCOption::new(Duration::from_millis(1_000));
+        let resp = cli.append_entries(rpc, option).await?;
+        assert!(resp.is_success());
+
+        assert!(
+            now.elapsed() < Duration::from_millis(2_000),
+            "append-entries is not blocked by building snapshot: {:?}",
+            now.elapsed()
+        );
+    }
+
+    tracing::info!(log_index, "--- the snapshot is built after unblocking");
+    {
+        router
+            .wait(&1, Some(Duration::from_millis(5_000)))
+            .snapshot(log_id(1, 0, log_index), "follower built snapshot")
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
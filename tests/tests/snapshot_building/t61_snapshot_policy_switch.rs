@@ -0,0 +1,84 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The snapshot policy can be changed at runtime.
+///
+/// What does this test do?
+///
+/// - build a single node cluster with snapshot policy `Never`, no snapshot is built.
+/// - switch to `LogsSinceLast`, a snapshot is built at once for the logs committed so far.
+/// - switch to `Bytes`, a snapshot is built when enough data is written.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_policy_switch() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Never,
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!(log_index, "--- write logs, no snapshot is built");
+    {
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait(&0, timeout()).applied_index(Some(log_index), "write logs").await?;
+
+        let res = router
+            .wait(&0, Some(Duration::from_millis(500)))
+            .metrics(|m| m.snapshot.is_some(), "no snapshot will be built")
+            .await;
+        assert!(res.is_err(), "no snapshot should be built");
+    }
+
+    tracing::info!(log_index, "--- switch to since_last:5, build a snapshot at once");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.runtime_config().snapshot_policy(SnapshotPolicy::LogsSinceLast(5)).await?;
+
+        router.wait(&0, timeout()).snapshot(log_id(1, 0, log_index), "switched to since_last").await?;
+    }
+
+    tracing::info!(
+        log_index,
+        "--- switch to bytes, build a snapshot when enough data is written"
+    );
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.runtime_config().snapshot_policy(SnapshotPolicy::Bytes(1024 * 1024)).await?;
+
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait(&0, timeout()).applied_index(Some(log_index), "write logs").await?;
+
+        let res = router
+            .wait(&0, Some(Duration::from_millis(500)))
+            .metrics(
+                |m| m.snapshot != Some(log_id(1, 0, log_index - 10)),
+                "no snapshot will be built for small logs",
+            )
+            .await;
+        assert!(res.is_err(), "no new snapshot should be built");
+
+        n0.runtime_config().snapshot_policy(SnapshotPolicy::Bytes(1)).await?;
+        router.wait(&0, timeout()).snapshot(log_id(1, 0, log_index), "switched to bytes").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
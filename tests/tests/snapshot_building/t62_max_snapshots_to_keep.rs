@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::SnapshotPolicy;
+use tokio::time::sleep;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Stale snapshots are deleted after a new one is built.
+///
+/// What does this test do?
+///
+/// - build a single node cluster with `max_snapshots_to_keep = 2`.
+/// - build 4 snapshots one by one.
+/// - assert that only the latest 2 snapshots are kept in the state machine.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn max_snapshots_to_keep() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Never,
+            max_snapshots_to_keep: 2,
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let mut snapshot_indexes = vec![];
+
+    for i in 0..4 {
+        tracing::info!(log_index, "--- build snapshot {}", i);
+
+        log_index += router.client_request_many(0, "0", 2).await?;
+        router.wait(&0, timeout()).applied_index(Some(log_index), "write logs").await?;
+
+        n0.trigger().snapshot().await?;
+        router.wait(&0, timeout()).snapshot(log_id(1, 0, log_index), "build snapshot").await?;
+
+        snapshot_indexes.push(log_index);
+    }
+
+    tracing::info!(log_index, "--- only the latest 2 snapshots are kept");
+    {
+        let (_log, sm) = router.get_storage_handle(&0)?;
+
+        let mut stored = vec![];
+        for _ in 0..20 {
+            stored = sm.get_stored_snapshots();
+            if stored.len() == 2 {
+                break;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+
+        let indexes = stored.iter().map(|m| m.last_log_id.unwrap().index).collect::<Vec<_>>();
+        assert_eq!(snapshot_indexes[2..].to_vec(), indexes);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::SnapshotPolicy;
+use openraft_memstore::BlockOperation;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The handle returned by `trigger().snapshot()` reports the progress and cancels the building.
+///
+/// What does this test do?
+///
+/// - build a single node cluster and block building snapshot after the state machine is serialized.
+/// - trigger a snapshot, assert the progress is reported by the handle and in the metrics.
+/// - cancel the building, assert no snapshot is built.
+/// - unblock and trigger again, assert a snapshot is built.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn cancel_building_snapshot() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Never,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    log_index += router.client_request_many(0, "0", 10).await?;
+    router.wait(&0, timeout()).applied_index(Some(log_index), "write logs").await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let (_log, sm) = router.get_storage_handle(&0)?;
+
+    tracing::info!(log_index, "--- block building snapshot");
+    sm.block.set_blocking(BlockOperation::BuildSnapshot, Duration::from_millis(10_000));
+
+    let progress = n0.trigger().snapshot().await?;
+
+    tracing::info!(log_index, "--- progress is reported");
+    {
+        n0.wait(timeout())
+            .metrics(
+                |m| m.snapshot_building.map(|b| b.bytes > 0).unwrap_or_default(),
+                "progress is reported in metrics",
+            )
+            .await?;
+
+        assert_eq!(1, progress.entries());
+        assert!(progress.bytes() > 0);
+
+        let again = n0.trigger().snapshot().await?;
+        assert_eq!(progress.bytes(), again.bytes(), "the same building is returned");
+    }
+
+    tracing::info!(log_index, "--- cancel building snapshot");
+    {
+        progress.cancel();
+
+        n0.wait(timeout()).metrics(|m| m.snapshot_building.is_none(), "building is cancelled").await?;
+
+        let m = n0.metrics().borrow().clone();
+        assert_eq!(None, m.snapshot, "no snapshot is built");
+    }
+
+    tracing::info!(log_index, "--- build snapshot again");
+    {
+        let mut block = sm.block.clone();
+        block.clear_blocking(BlockOperation::BuildSnapshot);
+
+        n0.trigger().snapshot().await?;
+        n0.wait(timeout()).snapshot(log_id(1, 0, log_index), "build snapshot").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
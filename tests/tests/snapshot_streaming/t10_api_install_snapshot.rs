@@ -17,6 +17,7 @@ use crate::fixtures::RaftRouter;
 ///
 /// - build a stable single node cluster.
 /// - send install_snapshot request with matched/mismatched id and offset
+/// - a request with a gap is rejected with the offset the node has received
 #[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
 async fn snapshot_arguments() -> Result<()> {
     let config = Arc::new(
@@ -43,10 +44,15 @@ async fn snapshot_arguments() -> Result<()> {
             snapshot_id: "ss1".into(),
             last_log_id: Some(log_id(1, 0, 0)),
             last_membership: Default::default(),
+            delta_base: None,
+            checksum: None,
         },
         offset: 0,
         data: vec![1, 2, 3],
         done: false,
+        checksum: None,
+        compression: None,
+        context: Default::default(),
     };
 
     tracing::info!(log_index, "--- only allow to begin a new session when offset is 0");
@@ -90,11 +96,28 @@ async fn snapshot_arguments() -> Result<()> {
         n.0.install_snapshot(req).await?;
     }
 
-    tracing::info!("-- continue write with mismatched offset is allowed");
+    tracing::info!("-- continue write with a gap, report the received offset to resume from");
     {
         let mut req = make_req();
         req.offset = 8;
         req.meta.snapshot_id = "ss2".into();
+        let res = n.0.install_snapshot(req).await;
+        assert_eq!(
+            "snapshot segment id mismatch, expect: ss2+6, got: ss2+8",
+            res.unwrap_err().to_string()
+        );
+
+        let mut req = make_req();
+        req.offset = 6;
+        req.meta.snapshot_id = "ss2".into();
+        n.0.install_snapshot(req).await?;
+    }
+
+    tracing::info!("-- re-write received data is allowed");
+    {
+        let mut req = make_req();
+        req.offset = 3;
+        req.meta.snapshot_id = "ss2".into();
         n.0.install_snapshot(req).await?;
     }
     Ok(())
@@ -40,10 +40,15 @@ async fn install_snapshot_lower_vote() -> Result<()> {
             snapshot_id: "ss1".into(),
             last_log_id: Some(log_id(1, 0, 0)),
             last_membership: Default::default(),
+            delta_base: None,
+            checksum: None,
         },
         offset: 0,
         data: vec![1, 2, 3],
         done: false,
+        checksum: None,
+        compression: None,
+        context: Default::default(),
     };
 
     tracing::info!(log_index, "--- force the vote on target node to be higher");
@@ -54,6 +59,7 @@ async fn install_snapshot_lower_vote() -> Result<()> {
                 prev_log_id: None,
                 entries: vec![],
                 leader_commit: None,
+                context: Default::default(),
             })
             .await;
         let vote = n0.with_raft_state(|st| *st.vote_ref()).await?;
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::SnapshotMeta;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// API test: the received snapshot data is verified with the checksum in `SnapshotMeta`.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster.
+/// - send a complete snapshot with a mismatched checksum, it is rejected and the sender is asked to
+///   send from offset 0.
+/// - send it again with a matching checksum, it is accepted.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_checksum() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    let n = router.remove_node(0).unwrap();
+    let make_req = |offset: u64, data: Vec<u8>, done: bool, checksum: u32| InstallSnapshotRequest {
+        // force it to be a follower
+        vote: Vote::new_committed(2, 1),
+        meta: SnapshotMeta {
+            snapshot_id: "ss1".into(),
+            // Less than the committed, the received snapshot will not be installed.
+            last_log_id: Some(log_id(1, 0, 0)),
+            last_membership: Default::default(),
+            delta_base: None,
+            checksum: Some(checksum),
+        },
+        offset,
+        data,
+        done,
+        checksum: None,
+        compression: None,
+        context: Default::default(),
+    };
+
+    let good = crc32fast::hash(&[1, 2, 3, 4, 5, 6]);
+
+    tracing::info!("--- a snapshot with mismatched checksum is rejected");
+    {
+        n.0.install_snapshot(make_req(0, vec![1, 2, 3], false, good + 1)).await?;
+        let res = n.0.install_snapshot(make_req(3, vec![4, 5, 6], true, good + 1)).await;
+        assert_eq!(
+            "snapshot segment id mismatch, expect: ss1+0, got: ss1+3",
+            res.unwrap_err().to_string()
+        );
+    }
+
+    tracing::info!("--- the received data is discarded, it has to be sent from the start");
+    {
+        let res = n.0.install_snapshot(make_req(3, vec![4, 5, 6], true, good)).await;
+        assert_eq!(
+            "snapshot segment id mismatch, expect: ss1+0, got: ss1+3",
+            res.unwrap_err().to_string()
+        );
+    }
+
+    tracing::info!("--- a snapshot with matching checksum is accepted");
+    {
+        n.0.install_snapshot(make_req(0, vec![1, 2, 3], false, good)).await?;
+        n.0.install_snapshot(make_req(3, vec![4, 5, 6], true, good)).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::SnapshotMeta;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// API test: a snapshot chunk compressed by an algorithm the node can not decode is not received,
+/// and the response does not acknowledge the compression.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster.
+/// - send a chunk compressed with an unknown algorithm, the compression is not acknowledged.
+/// - the chunk is not received, the next chunk is rejected and the sender is asked to send from
+///   offset 0.
+/// - send the chunks uncompressed, they are accepted.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_compression() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    let n = router.remove_node(0).unwrap();
+    let make_req = |offset: u64, data: Vec<u8>, done: bool, compression: Option<&str>| InstallSnapshotRequest {
+        // force it to be a follower
+        vote: Vote::new_committed(2, 1),
+        meta: SnapshotMeta {
+            snapshot_id: "ss1".into(),
+            // Less than the committed, the received snapshot will not be installed.
+            last_log_id: Some(log_id(1, 0, 0)),
+            last_membership: Default::default(),
+            delta_base: None,
+            checksum: Some(crc32fast::hash(&[1, 2, 3, 4, 5, 6])),
+        },
+        offset,
+        data,
+        done,
+        checksum: None,
+        compression: compression.map(|x| x.to_string()),
+        context: Default::default(),
+    };
+
+    tracing::info!("--- a chunk compressed with an unknown algorithm is not acknowledged");
+    {
+        let resp = n.0.install_snapshot(make_req(0, vec![9, 9], false, Some("brotli"))).await?;
+        assert_eq!(None, resp.compression);
+    }
+
+    tracing::info!("--- the chunk is not received, it has to be sent from the start");
+    {
+        let res = n.0.install_snapshot(make_req(3, vec![4, 5, 6], true, None)).await;
+        assert_eq!(
+            "snapshot segment id mismatch, expect: ss1+0, got: ss1+3",
+            res.unwrap_err().to_string()
+        );
+    }
+
+    tracing::info!("--- uncompressed chunks are accepted");
+    {
+        let resp = n.0.install_snapshot(make_req(0, vec![1, 2, 3], false, None)).await?;
+        assert_eq!(None, resp.compression);
+
+        let resp = n.0.install_snapshot(make_req(3, vec![4, 5, 6], true, None)).await?;
+        assert_eq!(None, resp.compression);
+    }
+
+    Ok(())
+}
@@ -6,6 +6,7 @@ use openraft::storage::RaftLogStorage;
 use openraft::storage::RaftStateMachine;
 use openraft::testing::log_id;
 use openraft::Config;
+use openraft::LogRetentionPolicy;
 
 use crate::fixtures::init_default_ut_tracing;
 use crate::fixtures::RaftRouter;
@@ -17,7 +18,7 @@ async fn startup_build_snapshot() -> anyhow::Result<()> {
     let config = Arc::new(
         Config {
             enable_heartbeat: false,
-            max_in_snapshot_log_to_keep: 0,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(0),
             ..Default::default()
         }
         .validate()?,
@@ -6,13 +6,14 @@ use maplit::btreeset;
 use openraft::CommittedLeaderId;
 use openraft::Config;
 use openraft::LogId;
+use openraft::LogRetentionPolicy;
 use openraft::RaftLogReader;
 use tokio::time::sleep;
 
 use crate::fixtures::init_default_ut_tracing;
 use crate::fixtures::RaftRouter;
 
-/// Leader logs should be deleted upto snapshot.last_log_id-max_in_snapshot_log_to_keep after
+/// Leader logs should be deleted upto snapshot.last_log_id-KeepEntries(n) after
 /// building snapshot; Follower/learner should delete upto snapshot.last_log_id after installing
 /// snapshot.
 #[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
@@ -21,7 +22,7 @@ async fn purge_in_snapshot_logs() -> Result<()> {
 
     let config = Arc::new(
         Config {
-            max_in_snapshot_log_to_keep: max_keep,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(max_keep),
             purge_batch_size: 1,
             enable_tick: false,
             ..Default::default()
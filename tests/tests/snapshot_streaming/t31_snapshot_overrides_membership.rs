@@ -15,6 +15,7 @@ use openraft::EffectiveMembership;
 use openraft::Entry;
 use openraft::EntryPayload;
 use openraft::LogId;
+use openraft::LogRetentionPolicy;
 use openraft::Membership;
 use openraft::SnapshotPolicy;
 use openraft::Vote;
@@ -36,7 +37,7 @@ async fn snapshot_overrides_membership() -> Result<()> {
     let config = Arc::new(
         Config {
             snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
-            max_in_snapshot_log_to_keep: 0,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(0),
             purge_batch_size: 1,
             enable_heartbeat: false,
             ..Default::default()
@@ -96,6 +97,7 @@ async fn snapshot_overrides_membership() -> Result<()> {
                     payload: EntryPayload::Membership(Membership::new(vec![btreeset! {2,3}], None)),
                 }],
                 leader_commit: Some(LogId::new(CommittedLeaderId::new(0, 0), 0)),
+                context: Default::default(),
             };
             let option = RPCOption::new(Duration::from_millis(1_000));
 
@@ -7,6 +7,7 @@ use openraft::storage::RaftLogReaderExt;
 use openraft::CommittedLeaderId;
 use openraft::Config;
 use openraft::LogId;
+use openraft::LogRetentionPolicy;
 use openraft::Membership;
 use openraft::SnapshotPolicy;
 use openraft::StorageHelper;
@@ -36,7 +37,7 @@ async fn snapshot_uses_prev_snap_membership() -> Result<()> {
             snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
             // Use 3, with 1 it triggers a compaction when replicating ent-1,
             // because ent-0 is removed.
-            max_in_snapshot_log_to_keep: 3,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(3),
             purge_batch_size: 1,
             enable_tick: false,
             ..Default::default()
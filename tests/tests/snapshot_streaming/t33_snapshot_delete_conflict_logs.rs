@@ -19,6 +19,7 @@ use openraft::Config;
 use openraft::Entry;
 use openraft::EntryPayload;
 use openraft::LogId;
+use openraft::LogRetentionPolicy;
 use openraft::Membership;
 use openraft::RaftLogReader;
 use openraft::RaftSnapshotBuilder;
@@ -44,7 +45,7 @@ async fn snapshot_delete_conflicting_logs() -> Result<()> {
     let config = Arc::new(
         Config {
             snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
-            max_in_snapshot_log_to_keep: 0,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(0),
             purge_batch_size: 1,
             enable_heartbeat: false,
             ..Default::default()
@@ -116,6 +117,7 @@ async fn snapshot_delete_conflicting_logs() -> Result<()> {
                 },
             ],
             leader_commit: Some(LogId::new(CommittedLeaderId::new(1, 0), 2)),
+            context: Default::default(),
         };
         let option = RPCOption::new(Duration::from_millis(1_000));
 
@@ -153,6 +155,9 @@ async fn snapshot_delete_conflicting_logs() -> Result<()> {
             offset: 0,
             data: snap.snapshot.into_inner(),
             done: true,
+            checksum: None,
+            compression: None,
+            context: Default::default(),
         };
 
         let option = RPCOption::new(Duration::from_millis(1_000));
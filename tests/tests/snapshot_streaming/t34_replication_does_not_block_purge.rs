@@ -6,6 +6,7 @@ use maplit::btreeset;
 use openraft::CommittedLeaderId;
 use openraft::Config;
 use openraft::LogId;
+use openraft::LogRetentionPolicy;
 use openraft::RaftLogReader;
 use tokio::time::sleep;
 
@@ -26,7 +27,7 @@ async fn replication_does_not_block_purge() -> Result<()> {
 
     let config = Arc::new(
         Config {
-            max_in_snapshot_log_to_keep: max_keep,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(max_keep),
             purge_batch_size: 1,
             enable_tick: false,
             ..Default::default()
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::AppendEntriesRequest;
+use openraft::raft::VoteRequest;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::LogRetentionPolicy;
+use openraft::Vote;
+use openraft_memstore::BlockOperation;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// When a follower is installing a snapshot, vote and append-entries requests should not be
+/// blocked.
+///
+/// - Build a snapshot on the leader and purge the logs, so that a new learner has to install it.
+/// - Block installing snapshot on the learner.
+/// - The learner should still respond to vote and append-entries requests in time.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn installing_snapshot_does_not_block_vote() -> Result<()> {
+    let max_keep = 2;
+
+    let config = Arc::new(
+        Config {
+            log_retention_policy: LogRetentionPolicy::KeepEntries(max_keep),
+            purge_batch_size: 1,
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {}).await?;
+
+    let leader = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- build snapshot on leader and purge logs");
+    {
+        log_index += router.client_request_many(0, "0", 10).await?;
+
+        leader.trigger().snapshot().await?;
+        leader.wait(timeout()).snapshot(log_id(1, 0, log_index), "built snapshot").await?;
+        leader.wait(timeout()).purged(Some(log_id(1, 0, log_index - max_keep)), "purged logs").await?;
+    }
+
+    tracing::info!(log_index, "--- add learner with installing snapshot blocked");
+    {
+        router.new_raft_node(1).await;
+
+        let (mut _sto1, sm1) = router.get_storage_handle(&1)?;
+        sm1.block.set_blocking(BlockOperation::InstallSnapshot, Duration::from_millis(5_000));
+
+        leader.add_learner(1, (), false).await?;
+        log_index += 1;
+
+        tracing::info!(
+            log_index,
+            "--- sleep 500 ms to make sure installing snapshot is started"
+        );
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let res = router
+            .wait(&1, Some(Duration::from_millis(500)))
+            .snapshot(log_id(1, 0, log_index - 1), "installing snapshot is blocked")
+            .await;
+        assert!(res.is_err(), "snapshot should be blocked and can not finish");
+    }
+
+    let learner = router.get_raft_handle(&1)?;
+
+    tracing::info!(
+        log_index,
+        "--- send vote request to the learner that is installing snapshot"
+    );
+    {
+        let rpc = VoteRequest::new(Vote::new(1, 2), None);
+
+        let fu = tokio::time::timeout(Duration::from_millis(500), learner.vote(rpc));
+        let resp = fu.await??;
+        assert!(!resp.vote_granted, "lower vote is rejected");
+    }
+
+    tracing::info!(
+        log_index,
+        "--- send append-entries request to the learner that is installing snapshot"
+    );
+    {
+        let rpc = AppendEntriesRequest {
+            vote: Vote::new_committed(1, 0),
+            prev_log_id: Some(log_id(1, 0, log_index - 1)),
+            entries: vec![],
+            leader_commit: None,
+            context: Default::default(),
+        };
+
+        let fu = tokio::time::timeout(Duration::from_millis(500), learner.append_entries(rpc));
+        let resp = fu.await??;
+        assert!(resp.is_success());
+    }
+
+    tracing::info!(log_index, "--- the snapshot is installed after unblocking");
+    {
+        router
+            .wait(&1, Some(Duration::from_millis(10_000)))
+            .snapshot(log_id(1, 0, log_index - 1), "learner installed snapshot")
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -5,6 +5,7 @@ use maplit::btreeset;
 use openraft::CommittedLeaderId;
 use openraft::Config;
 use openraft::LogId;
+use openraft::LogRetentionPolicy;
 use openraft::SnapshotPolicy;
 
 use crate::fixtures::init_default_ut_tracing;
@@ -23,7 +24,7 @@ async fn switch_to_snapshot_replication_when_lacking_log() -> Result<()> {
     let config = Arc::new(
         Config {
             snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
-            max_in_snapshot_log_to_keep: 0,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(0),
             purge_batch_size: 1,
             enable_heartbeat: false,
             ..Default::default()
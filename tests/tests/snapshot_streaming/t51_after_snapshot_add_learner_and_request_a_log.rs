@@ -7,6 +7,7 @@ use openraft::testing::log_id;
 use openraft::CommittedLeaderId;
 use openraft::Config;
 use openraft::LogId;
+use openraft::LogRetentionPolicy;
 use openraft::SnapshotPolicy;
 
 use crate::fixtures::init_default_ut_tracing;
@@ -20,8 +21,8 @@ async fn after_snapshot_add_learner_and_request_a_log() -> Result<()> {
     let config = Arc::new(
         Config {
             snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
-            max_in_snapshot_log_to_keep: 2, /* do not let add-learner log and client-write log to
-                                             * trigger a snapshot. */
+            // do not let add-learner log and client-write log to trigger a snapshot.
+            log_retention_policy: LogRetentionPolicy::KeepEntries(2),
             purge_batch_size: 1,
             enable_heartbeat: false,
             ..Default::default()
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::CommittedLeaderId;
+use openraft::Config;
+use openraft::LogId;
+use openraft::LogRetentionPolicy;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A follower lacking the purged logs receives only a delta since the last log it has.
+///
+/// - bring on a cluster of 1 voter and 1 learner, and enable delta snapshot on node 0.
+/// - isolate node 1, write to another client and trigger a snapshot that purges logs on node 0.
+/// - restore node 1: it receives a delta snapshot and ends up with the same state as node 0.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn delta_snapshot() -> Result<()> {
+    let snapshot_threshold: u64 = 10;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            log_retention_policy: LogRetentionPolicy::KeepEntries(0),
+            purge_batch_size: 1,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_cluster(btreeset! {0}, btreeset! {1}).await?;
+
+    let (_, sm0) = router.get_storage_handle(&0)?;
+    let (_, sm1) = router.get_storage_handle(&1)?;
+    sm0.set_delta_snapshot(true);
+
+    tracing::info!(log_index, "--- write to client a");
+    {
+        log_index += router.client_request_many(0, "a", 3).await?;
+        router.wait_for_log(&btreeset![0, 1], Some(log_index), timeout(), "replicate client a").await?;
+    }
+
+    tracing::info!(log_index, "--- isolate node 1, write to client b to trigger snapshot");
+    {
+        router.set_network_error(1, true);
+
+        let n = snapshot_threshold - 1 - log_index;
+        log_index += router.client_request_many(0, "b", n as usize).await?;
+
+        router
+            .wait_for_snapshot(
+                &btreeset![0],
+                LogId::new(CommittedLeaderId::new(1, 0), log_index),
+                timeout(),
+                "snapshot on node 0",
+            )
+            .await?;
+        router
+            .wait(&0, timeout())
+            .purged(
+                Some(LogId::new(CommittedLeaderId::new(1, 0), log_index)),
+                "purge logs on node 0",
+            )
+            .await?;
+    }
+
+    tracing::info!(log_index, "--- restore node 1, it receives a delta");
+    {
+        router.set_network_error(1, false);
+
+        router.wait_for_log(&btreeset![1], Some(log_index), timeout(), "replicate by delta").await?;
+
+        assert_eq!(1, sm1.get_delta_installed());
+
+        let want = sm0.get_state_machine().await;
+        let got = sm1.get_state_machine().await;
+        assert_eq!(want.client_status, got.client_status);
+        assert_eq!(want.last_applied_log, got.last_applied_log);
+
+        router
+            .wait_for_snapshot(
+                &btreeset![1],
+                LogId::new(CommittedLeaderId::new(1, 0), log_index),
+                timeout(),
+                "a full snapshot is built on node 1",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
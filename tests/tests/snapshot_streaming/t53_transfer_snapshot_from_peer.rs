@@ -0,0 +1,114 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::testing::log_id;
+use openraft::Config;
+use openraft::LogRetentionPolicy;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `delegate_snapshot_transfer` enabled, a leader asks an up-to-date follower to send a
+/// snapshot to a lagging node.
+///
+/// What does this test do?
+///
+/// - build a cluster of voter 0 and 1, build a snapshot on the leader and purge the logs.
+/// - add learner 2: follower 1 has no snapshot thus the leader sends the snapshot by itself.
+/// - build a snapshot on every node and purge the logs on the leader again.
+/// - add learner 3: a follower sends the snapshot on behalf of the leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn transfer_snapshot_from_peer() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Never,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(0),
+            purge_batch_size: 1,
+            delegate_snapshot_transfer: true,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!(log_index, "--- build snapshot on leader and purge logs");
+    {
+        log_index += router.client_request_many(0, "0", 5).await?;
+        router.wait(&1, timeout()).applied_index(Some(log_index), "write logs").await?;
+
+        n0.trigger().snapshot().await?;
+        n0.wait(timeout()).snapshot(log_id(1, 0, log_index), "leader builds snapshot").await?;
+        n0.trigger().purge_log(log_index).await?;
+        n0.wait(timeout()).purged(Some(log_id(1, 0, log_index)), "leader purges logs").await?;
+    }
+
+    tracing::info!(log_index, "--- add learner 2, the leader sends snapshot by itself");
+    {
+        router.new_raft_node(2).await;
+        router.add_learner(0, 2).await?;
+        log_index += 1;
+
+        router.wait(&2, timeout()).applied_index(Some(log_index), "learner-2 catches up").await?;
+        router
+            .wait(&2, timeout())
+            .snapshot(log_id(1, 0, log_index - 1), "learner-2 receives snapshot")
+            .await?;
+
+        assert!(
+            router.get_transferred_snapshots().is_empty(),
+            "follower-1 has no snapshot to transfer"
+        );
+    }
+
+    tracing::info!(log_index, "--- build snapshot on every node and purge logs on leader");
+    {
+        log_index += router.client_request_many(0, "0", 5).await?;
+        for id in [0, 1, 2] {
+            router.wait(&id, timeout()).applied_index(Some(log_index), "write logs").await?;
+
+            let n = router.get_raft_handle(&id)?;
+            n.trigger().snapshot().await?;
+            n.wait(timeout()).snapshot(log_id(1, 0, log_index), "build snapshot").await?;
+        }
+
+        n0.trigger().purge_log(log_index).await?;
+        n0.wait(timeout()).purged(Some(log_id(1, 0, log_index)), "leader purges logs").await?;
+    }
+
+    tracing::info!(
+        log_index,
+        "--- add learner 3, a follower sends snapshot on behalf of the leader"
+    );
+    {
+        router.new_raft_node(3).await;
+        router.add_learner(0, 3).await?;
+        log_index += 1;
+
+        router.wait(&3, timeout()).applied_index(Some(log_index), "learner-3 catches up").await?;
+        router
+            .wait(&3, timeout())
+            .snapshot(log_id(1, 0, log_index - 1), "learner-3 receives snapshot")
+            .await?;
+
+        let transferred = router.get_transferred_snapshots();
+        assert_eq!(1, transferred.len(), "one snapshot is transferred: {:?}", transferred);
+        let (source, target) = transferred[0];
+        assert_ne!(0, source, "snapshot is not sent by the leader");
+        assert_eq!(3, target);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
@@ -5,6 +5,7 @@ use anyhow::Result;
 use maplit::btreeset;
 use openraft::testing::log_id;
 use openraft::Config;
+use openraft::LogRetentionPolicy;
 
 use crate::fixtures::init_default_ut_tracing;
 use crate::fixtures::RaftRouter;
@@ -15,7 +16,7 @@ async fn snapshot_to_unreachable_node_should_not_block() -> Result<()> {
     let config = Arc::new(
         Config {
             purge_batch_size: 1,
-            max_in_snapshot_log_to_keep: 0,
+            log_retention_policy: LogRetentionPolicy::KeepEntries(0),
             enable_heartbeat: false,
             ..Default::default()
         }
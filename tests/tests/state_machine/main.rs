@@ -9,3 +9,5 @@ mod fixtures;
 
 mod t10_total_order_apply;
 mod t20_state_machine_apply_membership;
+mod t30_subscribe_applied;
+mod t40_apply_batch;
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::testing::log_id;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Every subscriber of `Raft::subscribe_applied()` receives every applied log id in order.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn subscribe_applied() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initialize cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let n1 = router.get_raft_handle(&1)?;
+
+    let mut subscribers = [n0.subscribe_applied(), n0.subscribe_applied(), n1.subscribe_applied()];
+
+    let n = 10;
+    tracing::info!(log_index, "--- write {} logs", n);
+    let since = log_index + 1;
+    log_index += router.client_request_many(0, "foo", n).await?;
+
+    for (i, rx) in subscribers.iter_mut().enumerate() {
+        tracing::info!(log_index, "--- subscriber {} receives all applied log ids", i);
+
+        for index in since..=log_index {
+            let got = tokio::time::timeout(Duration::from_millis(1_000), rx.recv()).await??;
+            assert_eq!(log_id(1, 0, index), got);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Every applied log is committed in an apply batch, and the batches end in log index order.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn apply_batch() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initialize cluster");
+    let mut log_index = router.new_cluster(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n = 10;
+    tracing::info!(log_index, "--- write {} logs", n);
+    log_index += router.client_request_many(0, "foo", n).await?;
+    router.wait(&0, timeout()).applied_index(Some(log_index), "n0 applied all").await?;
+
+    tracing::info!(log_index, "--- every applied log is in a committed batch");
+    {
+        let (_sto0, sm0) = router.get_storage_handle(&0)?;
+        let batches = sm0.get_apply_batches();
+
+        assert!(!batches.is_empty());
+        for w in batches.windows(2) {
+            assert!(w[0].index < w[1].index, "batches out of order: {:?}", batches);
+        }
+        assert_eq!(Some(log_index), batches.last().map(|x| x.index));
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}